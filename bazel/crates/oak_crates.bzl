@@ -163,6 +163,16 @@ def _common_crates(std):
         "maplit": crate.spec(
             version = "1.0.2",
         ),
+        "miniz_oxide": crate.spec(
+            default_features = False,
+            features = ["with-alloc"],
+            version = "0.7.1",
+        ),
+        "ml-kem": crate.spec(
+            default_features = False,
+            features = ["zeroize"],
+            version = "0.2.1",
+        ),
         "mockall": crate.spec(
             version = "0.13.0",
         ),
@@ -397,6 +407,7 @@ OAK_STD_CRATES = _common_crates(std = True) | {
     "criterion-macro": crate.spec(version = "0.4.0"),
     "crossbeam-channel": crate.spec(version = "0.5.15"),
     "env_logger": crate.spec(version = "0.11.2"),
+    "flate2": crate.spec(version = "1.0.28"),
     "futures": crate.spec(version = "0.3.31"),
     "futures-util": crate.spec(version = "0.3.31"),
     "googletest": crate.spec(version = "0.14.2"),
@@ -280,7 +280,8 @@ fn create_reference_values(tee_proof: &TeeProof) -> ReferenceValues {
                                 digests: vec![raw_digests::sha2_256("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")]
                             }))
                         })
-                    })
+                    }),
+                    additional_container_layers: vec![],
                 }))
             }
         }
@@ -844,6 +845,7 @@ mod tests {
                         )),
                     }),
                 }),
+                additional_container_layers: vec![],
             })),
         }
     }
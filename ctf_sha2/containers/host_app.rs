@@ -46,7 +46,8 @@ pub fn launcher_args(
     anyhow::ensure!(initrd.exists(), "Stage1 not found at {initrd:?}");
     Ok(oak_containers_launcher::Args {
         system_image,
-        container_bundle,
+        container_bundle: Some(container_bundle),
+        container_manifest: None,
         application_config: Vec::new(),
         qemu_params: oak_containers_launcher::QemuParams {
             vmm_binary,
@@ -95,6 +95,7 @@ pub fn serialize_amd_attestation_report(instance: &AmdAttestationReport) -> serd
         product,
         hardware_id,
         vmpl,
+        signing_key,
     } = instance;
 
     json!({
@@ -106,6 +107,7 @@ pub fn serialize_amd_attestation_report(instance: &AmdAttestationReport) -> serd
         "product": product,
         "hardware_id": hex::encode(hardware_id),
         "vmpl": vmpl,
+        "signing_key": signing_key,
     })
 }
 
@@ -466,7 +468,7 @@ fn serialize_verifying_key_set(instance: &VerifyingKeySet) -> serde_json::Value
     // all fields. If a new field is added to the struct, this code won't
     // compile unless this destructuring operation is updated, thereby reminding us
     // to keep the serialization in sync manually.
-    let VerifyingKeySet { keys, signed_timestamp } = instance;
+    let VerifyingKeySet { keys, signed_timestamp, require_inclusion_proof } = instance;
 
     result["keys"] =
         json!(keys.iter().map(serialize_verifying_key).collect::<Vec<serde_json::Value>>());
@@ -475,6 +477,10 @@ fn serialize_verifying_key_set(instance: &VerifyingKeySet) -> serde_json::Value
         result["signed_timestamp"] = serialize_timestamp_reference_value(timestamp_ref);
     }
 
+    if *require_inclusion_proof {
+        result["require_inclusion_proof"] = json!(true);
+    }
+
     result
 }
 
@@ -521,6 +527,8 @@ pub fn serialize_endorsement_reference_value(
         endorser,
         required_claims,
         rekor,
+        rekor_checkpoint_consistency,
+        timestamp_authority,
     } = instance;
     json!({
         "endorser_public_key": hex::encode(endorser_public_key),
@@ -528,6 +536,46 @@ pub fn serialize_endorsement_reference_value(
         "endorser": endorser.as_ref().map(serialize_verifying_key_set),
         "required_claims": required_claims.as_ref().map(serialize_claim_reference_value),
         "rekor": rekor.as_ref().map(serialize_verifying_key_reference_value),
+        "rekor_checkpoint_consistency": rekor_checkpoint_consistency
+            .as_ref()
+            .map(serialize_checkpoint_consistency_reference_value),
+        "timestamp_authority": timestamp_authority
+            .as_ref()
+            .map(serialize_timestamp_authority_reference_value),
+    })
+}
+
+fn serialize_checkpoint_consistency_reference_value(
+    instance: &CheckpointConsistencyReferenceValue,
+) -> serde_json::Value {
+    // Exhaustive destructuring (e.g., without ", ..") ensures this function handles
+    // all fields. If a new field is added to the struct, this code won't
+    // compile unless this destructuring operation is updated, thereby reminding us
+    // to keep the serialization in sync manually.
+    let CheckpointConsistencyReferenceValue { checkpoint, proof_hashes } = instance;
+    json!({
+        "checkpoint": checkpoint,
+        "proof_hashes": proof_hashes.iter().map(hex::encode).collect::<Vec<String>>(),
+    })
+}
+
+fn serialize_timestamp_authority_reference_value(
+    instance: &TimestampAuthorityReferenceValue,
+) -> serde_json::Value {
+    // Exhaustive destructuring (e.g., without ", ..") ensures this function handles
+    // all fields. If a new field is added to the struct, this code won't
+    // compile unless this destructuring operation is updated, thereby reminding us
+    // to keep the serialization in sync manually.
+    let TimestampAuthorityReferenceValue {
+        root_certificates,
+        intermediate_certificates,
+        signed_timestamp,
+    } = instance;
+    json!({
+        "root_certificates": root_certificates.iter().map(hex::encode).collect::<Vec<String>>(),
+        "intermediate_certificates":
+            intermediate_certificates.iter().map(hex::encode).collect::<Vec<String>>(),
+        "signed_timestamp": signed_timestamp.as_ref().map(serialize_timestamp_reference_value),
     })
 }
 
@@ -702,6 +750,9 @@ pub fn serialize_amd_sev_reference_values(instance: &AmdSevReferenceValues) -> s
         allow_debug,
         stage0,
         check_vcek_cert_expiry,
+        require_crls,
+        reject_stale_crls,
+        allowed_signing_keys,
     } = instance;
     json!({
         "min_tcb_version": min_tcb_version.as_ref().map(serialize_tcb_version),
@@ -710,6 +761,9 @@ pub fn serialize_amd_sev_reference_values(instance: &AmdSevReferenceValues) -> s
         "turin": turin.as_ref().map(serialize_tcb_version_reference_value),
         "allow_debug": allow_debug,
         "check_vcek_cert_expiry": check_vcek_cert_expiry,
+        "require_crls": require_crls,
+        "reject_stale_crls": reject_stale_crls,
+        "allowed_signing_keys": allowed_signing_keys,
         "stage0": stage0.as_ref().map(serialize_binary_reference_value),
     })
 }
@@ -826,14 +880,26 @@ pub fn serialize_oak_restricted_kernel_reference_values(
 pub fn serialize_oak_containers_reference_values(
     instance: &OakContainersReferenceValues,
 ) -> serde_json::Value {
-    let OakContainersReferenceValues { root_layer, kernel_layer, system_layer, container_layer } =
-        instance;
-    json!({
+    let OakContainersReferenceValues {
+        root_layer,
+        kernel_layer,
+        system_layer,
+        container_layer,
+        additional_container_layers,
+    } = instance;
+    let mut result = json!({
         "root_layer": root_layer.as_ref().map(serialize_root_layer_reference_values),
         "kernel_layer": kernel_layer.as_ref().map(serialize_kernel_layer_reference_values),
         "system_layer":  system_layer.as_ref().map(serialize_system_layer_reference_values),
         "container_layer":  container_layer.as_ref().map(serialize_container_layer_reference_values),
-    })
+    });
+    if !additional_container_layers.is_empty() {
+        result["additional_container_layers"] = additional_container_layers
+            .iter()
+            .map(serialize_container_layer_reference_values)
+            .collect();
+    }
+    result
 }
 
 pub fn serialize_cb_reference_values(instance: &CbReferenceValues) -> serde_json::Value {
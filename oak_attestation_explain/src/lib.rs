@@ -42,6 +42,7 @@ use zerocopy::{FromBytes, KnownLayout};
 use crate::alloc::{borrow::ToOwned, string::ToString};
 
 mod json_serialization;
+mod verification_report;
 
 const AMD_SEV_SNP_TITLE: &str = "AMD SEV-SNP";
 const INTEL_TDX_TITLE: &str = "Intel TDX";
@@ -144,8 +145,9 @@ impl HumanReadableExplanation for ReferenceValues {
                         kernel_layer: Some(kernel_layer),
                         system_layer: Some(system_layer),
                         container_layer: Some(container_layer),
+                        additional_container_layers,
                     })),
-            } => Ok(format!(
+            } if additional_container_layers.is_empty() => Ok(format!(
                 "_____ {} _____\n\n{}\n\n_____ {} _____\n\n{}\n\n_____ {} _____\n\n{}\n\n_____ {} _____\n\n{}",
                 root_layer.title()?,
                 root_layer.description()?,
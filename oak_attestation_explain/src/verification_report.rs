@@ -0,0 +1,65 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Renders structured verification reports, as produced by the verifiers in
+//! `oak_attestation_verification`, for humans.
+
+use alloc::{format, string::String};
+use std::fmt::Write;
+
+use oak_attestation_verification_types::report::{CheckStatus, VerificationReport};
+
+use crate::{alloc::borrow::ToOwned, HumanReadableExplanation, HumanReadableTitle};
+
+impl HumanReadableTitle for VerificationReport {
+    fn title(&self) -> Result<String, anyhow::Error> {
+        match self.failed_checks().count() {
+            0 => Ok("Attestation verification succeeded".to_owned()),
+            1 => Ok("Attestation verification failed 1 check".to_owned()),
+            failed => Ok(format!("Attestation verification failed {failed} checks")),
+        }
+    }
+}
+
+impl HumanReadableExplanation for VerificationReport {
+    fn description(&self) -> Result<String, anyhow::Error> {
+        let mut description = String::new();
+        for layer in self.layers.iter() {
+            writeln!(description, "_____ {} _____", layer.layer)?;
+            for check in layer.checks.iter() {
+                match (check.status, check.message.as_ref()) {
+                    (CheckStatus::Passed, _) => writeln!(description, "✅ {}", check.id)?,
+                    (CheckStatus::Skipped, _) => {
+                        writeln!(description, "⏭️ {} (skipped)", check.id)?
+                    }
+                    (CheckStatus::Failed, None) => writeln!(description, "❌ {}", check.id)?,
+                    (CheckStatus::Failed, Some(message)) => {
+                        writeln!(description, "❌ {}: {message}", check.id)?
+                    }
+                }
+                if check.status == CheckStatus::Failed {
+                    if let Some(expected) = check.expected.as_ref() {
+                        writeln!(description, "   expected: {expected}")?;
+                    }
+                    if let Some(actual) = check.actual.as_ref() {
+                        writeln!(description, "   actual: {actual}")?;
+                    }
+                }
+            }
+        }
+        Ok(description)
+    }
+}
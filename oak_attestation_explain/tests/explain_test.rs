@@ -17,6 +17,7 @@
 
 use oak_attestation_explain::{HumanReadableExplanation, HumanReadableTitle};
 use oak_attestation_verification::extract_evidence;
+use oak_attestation_verification_types::report::{CheckStatus, LayerReport, VerificationReport};
 use oak_proto_rust::oak::attestation::v1::{
     extracted_evidence::EvidenceValues, OakRestrictedKernelData,
 };
@@ -69,6 +70,7 @@ sev_snp:
     microcode: 219
     snp: 24
     tee: 0
+  signing_key: 1
   vmpl: 0
 "
                     );
@@ -131,6 +133,7 @@ The reference values describing this layer are printed below.
 
 amd_sev:
   allow_debug: false
+  allowed_signing_keys: []
   check_vcek_cert_expiry: true
   genoa:
     minimum:
@@ -152,6 +155,8 @@ amd_sev:
     microcode: 219
     snp: 24
     tee: 0
+  reject_stale_crls: false
+  require_crls: false
   stage0:
     digests:
     - sha2_384: 0a3c0fa3f1558a883660cb3f37491e6da05e3445dc0f517ab5f9b8f7be6dc2ae2fa46a23b501d66d1d7e24796d1c2e20
@@ -205,3 +210,27 @@ configuration:
   skip: {}
 ");
 }
+
+#[test]
+fn produces_expected_verification_report_explanation() {
+    let mut kernel = LayerReport::new("kernel");
+    kernel.add("image", CheckStatus::Passed);
+    kernel
+        .add("init_ram_fs", CheckStatus::Failed)
+        .with_values("sha2-256:00".to_string(), "sha2-256:11".to_string())
+        .message = Some("no match".to_string());
+    kernel.add("cmd_line", CheckStatus::Skipped);
+    let report = VerificationReport { layers: vec![kernel] };
+
+    assert_eq!(report.title().unwrap(), "Attestation verification failed 1 check");
+    assert_eq!(
+        report.description().unwrap(),
+        "_____ kernel _____
+✅ kernel.image
+❌ kernel.init_ram_fs: no match
+   expected: sha2-256:00
+   actual: sha2-256:11
+⏭️ kernel.cmd_line (skipped)
+"
+    );
+}
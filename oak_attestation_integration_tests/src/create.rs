@@ -117,6 +117,7 @@ fn reference_values_for_oak_containers_measurements(
                     })),
                 }),
             }),
+            additional_container_layers: vec![],
         })),
     }
 }
@@ -204,6 +204,7 @@ fn oak_containers_skip_all_reference_values() -> ReferenceValues {
                     r#type: Some(binary_reference_value::Type::Skip(SkipVerification {})),
                 }),
             }),
+            additional_container_layers: vec![],
         })),
     }
 }
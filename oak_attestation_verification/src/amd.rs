@@ -16,14 +16,25 @@
 
 //! Contains code related to AMD hardware certificates and attestation report.
 
-use alloc::string::String;
+use alloc::{string::String, vec::Vec};
 
-use const_oid::ObjectIdentifier;
-use oak_sev_snp_attestation_report::{AmdProduct, AttestationReport, SigningAlgorithm, TcbVersion};
+use anyhow::Context;
+use const_oid::{db::rfc4519::COMMON_NAME, ObjectIdentifier};
+use oak_sev_snp_attestation_report::{
+    AmdProduct, AttestationReport, SigningAlgorithm, SigningKey, TcbVersion,
+};
+use oak_time::Instant;
 use p384::ecdsa::{signature::Verifier, VerifyingKey};
-use x509_cert::{der::referenced::OwnedToRef, Certificate};
+use x509_cert::{
+    crl::CertificateList,
+    der::{asn1::Utf8StringRef, referenced::OwnedToRef, Decode},
+    ext::pkix::{BasicConstraints, KeyUsage},
+    Certificate,
+};
 use zerocopy::IntoBytes;
 
+use crate::x509::{check_crl_validity, is_revoked, verify_crl_signature};
+
 // The keys in the key-value map of X509 certificates are Object Identifiers
 // (OIDs) which have a global registry. The present OIDs are taken from
 // Table 8 of
@@ -36,7 +47,7 @@ const CHIP_ID_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.
 const UCODE_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.3704.1.3.8");
 const FMC_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.3704.1.3.9");
 
-/// Extracts the product name from the VCEK certificate.
+/// Extracts the product name from the VCEK or VLEK certificate.
 pub fn get_product(cert: &Certificate) -> anyhow::Result<AmdProduct> {
     let exts = cert
         .tbs_certificate
@@ -86,14 +97,14 @@ fn chip_id(cert: &Certificate) -> anyhow::Result<[u8; 64]> {
     Ok(result)
 }
 
-/// Parses the TCB version contained in the VCEK certificate.
-fn parse_tcb_version(vcek: &Certificate) -> anyhow::Result<TcbVersion> {
+/// Parses the TCB version contained in the VCEK or VLEK certificate.
+fn parse_tcb_version(cert: &Certificate) -> anyhow::Result<TcbVersion> {
     let mut tcb = TcbVersion { ..Default::default() };
-    for ext in vcek
+    for ext in cert
         .tbs_certificate
         .extensions
         .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("could not get extensions from VCEK cert"))?
+        .ok_or_else(|| anyhow::anyhow!("could not get extensions from cert"))?
     {
         // What's the appropriate way of extracting u8 from OctetString?
         let arr = ext.extn_value.as_bytes();
@@ -113,27 +124,82 @@ fn parse_tcb_version(vcek: &Certificate) -> anyhow::Result<TcbVersion> {
     Ok(tcb)
 }
 
+/// Returns the names of the intermediate and leaf certificates of the chain
+/// that endorses the given signing key.
+pub fn certificate_names(signing_key: SigningKey) -> (&'static str, &'static str) {
+    match signing_key {
+        SigningKey::VLEK => ("ASVK", "VLEK"),
+        _ => ("ASK", "VCEK"),
+    }
+}
+
+/// Checks that `asvk` is an AMD SEV VLEK signing key (ASVK) certificate.
+///
+/// Unlike the ASK, the ASVK isn't bundled but comes with the endorsements, and
+/// the product-specific ARK signs both of them. Without this check the ASK
+/// could be presented as the ASVK, and a VCEK certificate as a VLEK one, which
+/// skips the chip ID check.
+pub fn verify_asvk_cert_fields(asvk: &Certificate) -> anyhow::Result<()> {
+    let common_name = asvk
+        .tbs_certificate
+        .subject
+        .0
+        .iter()
+        .flat_map(|rdn| rdn.0.iter())
+        .find(|attribute| attribute.oid == COMMON_NAME)
+        .and_then(|attribute| attribute.value.decode_as::<Utf8StringRef>().ok())
+        .ok_or_else(|| anyhow::anyhow!("no common name found in ASVK cert"))?;
+    anyhow::ensure!(
+        common_name.as_str().starts_with("SEV-VLEK-"),
+        "unexpected ASVK cert common name: {}",
+        common_name.as_str()
+    );
+
+    let basic_constraints = asvk
+        .tbs_certificate
+        .get::<BasicConstraints>()
+        .map_err(|_err| anyhow::anyhow!("could not parse ASVK cert basic constraints"))?;
+    anyhow::ensure!(
+        basic_constraints.is_some_and(|(_, constraints)| constraints.ca),
+        "ASVK cert is not a CA cert"
+    );
+    let key_usage = asvk
+        .tbs_certificate
+        .get::<KeyUsage>()
+        .map_err(|_err| anyhow::anyhow!("could not parse ASVK cert key usage"))?;
+    anyhow::ensure!(
+        key_usage.is_some_and(|(_, key_usage)| key_usage.key_cert_sign()),
+        "ASVK cert is not allowed to sign certs"
+    );
+    Ok(())
+}
+
+/// Verifies that the attestation report is signed by the key in `cert`, which
+/// is a VCEK or a VLEK certificate depending on the report's signing key.
 pub fn verify_attestation_report_signature(
-    vcek: &Certificate,
+    cert: &Certificate,
     report: &AttestationReport,
 ) -> anyhow::Result<()> {
     // First check some necessary conditions for the signature to be valid.
-    let arpt_chip_id = report.data.chip_id;
-    let vcek_chip_id = chip_id(vcek)?;
-    anyhow::ensure!(
-        arpt_chip_id == vcek_chip_id,
-        "chip id differs: reported={} vcek={}",
-        hex::encode(arpt_chip_id),
-        hex::encode(vcek_chip_id)
-    );
+    // A VLEK is not bound to a chip, so its certificate has no chip ID.
+    if report.data.get_signing_key() == Some(SigningKey::VCEK) {
+        let arpt_chip_id = report.data.chip_id;
+        let vcek_chip_id = chip_id(cert)?;
+        anyhow::ensure!(
+            arpt_chip_id == vcek_chip_id,
+            "chip id differs: reported={} vcek={}",
+            hex::encode(arpt_chip_id),
+            hex::encode(vcek_chip_id)
+        );
+    }
 
     let reported_tcb = report.data.get_reported_tcb_version();
-    let vcek_tcb = parse_tcb_version(vcek)?;
+    let cert_tcb = parse_tcb_version(cert)?;
     anyhow::ensure!(
-        reported_tcb == vcek_tcb,
-        "mismatch in TCB version: reported={:?} vcek={:?}",
+        reported_tcb == cert_tcb,
+        "mismatch in TCB version: reported={:?} cert={:?}",
         reported_tcb,
-        vcek_tcb
+        cert_tcb
     );
     anyhow::ensure!(
         report.data.get_signature_algo() == Some(SigningAlgorithm::EcdsaP384Sha384),
@@ -141,7 +207,7 @@ pub fn verify_attestation_report_signature(
         report.data.get_signature_algo(),
     );
 
-    let verifying_key: VerifyingKey = vcek
+    let verifying_key: VerifyingKey = cert
         .tbs_certificate
         .subject_public_key_info
         .owned_to_ref()
@@ -169,5 +235,56 @@ pub fn verify_attestation_report_signature(
         .map_err(|_err| anyhow::anyhow!("failed to verify ECDSA P-384 signature"))
 }
 
+/// Determines how the certificate revocation lists (CRLs) supplied with the
+/// endorsements are used when verifying the ARK -> ASK -> VCEK or the
+/// ARK -> ASVK -> VLEK chain.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CrlPolicy {
+    /// Whether a CRL issued by the ARK must be supplied.
+    pub require_crls: bool,
+    /// Whether CRLs that are past their next update time are rejected.
+    pub reject_stale_crls: bool,
+}
+
+/// Checks that neither the intermediate nor the leaf certificate of the chain
+/// that endorses `signing_key` is revoked by the supplied DER-encoded CRLs.
+///
+/// CRLs issued by the ARK list revoked ASKs (or ASVKs), and CRLs issued by the
+/// ASK list revoked VCEKs. CRLs issued by other certificates are ignored, so
+/// that the CRLs of all AMD products can be supplied regardless of the product.
+pub fn check_revocation(
+    verification_time: Instant,
+    signing_key: SigningKey,
+    ark: &Certificate,
+    intermediate: &Certificate,
+    leaf: &Certificate,
+    crls: &[Vec<u8>],
+    policy: CrlPolicy,
+) -> anyhow::Result<()> {
+    let (intermediate_name, leaf_name) = certificate_names(signing_key);
+    let mut has_ark_crl = false;
+    for crl in crls {
+        let crl = CertificateList::from_der(crl)
+            .map_err(|err| anyhow::anyhow!("couldn't parse CRL: {:?}", err))?;
+        let issuer = &crl.tbs_cert_list.issuer;
+        let (signer, cert, cert_name) = if *issuer == ark.tbs_certificate.subject {
+            has_ark_crl = true;
+            (ark, intermediate, intermediate_name)
+        } else if *issuer == intermediate.tbs_certificate.subject {
+            (intermediate, leaf, leaf_name)
+        } else {
+            continue;
+        };
+
+        verify_crl_signature(signer, &crl).context("verifying CRL signature")?;
+        if policy.reject_stale_crls {
+            check_crl_validity(verification_time, &crl).context("checking CRL validity")?;
+        }
+        anyhow::ensure!(!is_revoked(&crl, cert), "the {} certificate has been revoked", cert_name);
+    }
+    anyhow::ensure!(has_ark_crl || !policy.require_crls, "no CRL issued by the ARK was supplied");
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests;
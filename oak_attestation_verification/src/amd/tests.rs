@@ -18,18 +18,25 @@ extern crate std;
 
 use std::eprintln;
 
-use oak_sev_snp_attestation_report::AttestationReport;
+use oak_sev_snp_attestation_report::{AttestationReport, SigningKey};
 use oak_time::{make_instant, Instant};
+use p384::{
+    ecdsa::{signature::Signer, Signature, SigningKey as EcdsaSigningKey},
+    pkcs8::DecodePrivateKey,
+};
 use test_util::attestation_data::AttestationData;
 use x509_cert::{
     certificate::{CertificateInner, Version},
     der::{Decode, DecodePem},
     Certificate,
 };
-use zerocopy::FromBytes;
+use zerocopy::{FromBytes, FromZeros, IntoBytes};
 
 use crate::{
-    amd::{get_product, AmdProduct},
+    amd::{
+        check_revocation, get_product, verify_asvk_cert_fields,
+        verify_attestation_report_signature, AmdProduct, CrlPolicy,
+    },
     x509::{check_certificate_validity, verify_cert_signature},
 };
 
@@ -40,6 +47,25 @@ const ASK_MILAN_CERT_PEM: &str = include_str!("../../data/ask_milan.pem");
 const ASK_GENOA_CERT_PEM: &str = include_str!("../../data/ask_genoa.pem");
 const ASK_TURIN_CERT_PEM: &str = include_str!("../../data/ask_turin.pem");
 
+// A test certificate chain and CRLs generated by testdata/amd_crl/generate.sh.
+const TEST_ARK_CERT_PEM: &str = include_str!("../../testdata/amd_crl/ark.pem");
+const TEST_ASK_CERT_PEM: &str = include_str!("../../testdata/amd_crl/ask.pem");
+const TEST_VCEK_CERT_PEM: &str = include_str!("../../testdata/amd_crl/vcek.pem");
+const TEST_ARK_CRL_EMPTY: &[u8] = include_bytes!("../../testdata/amd_crl/ark_crl_empty.der");
+const TEST_ARK_CRL_REVOKING_ASK: &[u8] =
+    include_bytes!("../../testdata/amd_crl/ark_crl_revoking_ask.der");
+const TEST_ASK_CRL_REVOKING_VCEK: &[u8] =
+    include_bytes!("../../testdata/amd_crl/ask_crl_revoking_vcek.der");
+
+// A test VLEK certificate chain, the VLEK private key and a CRL generated by
+// testdata/amd_vlek/generate.sh.
+const TEST_VLEK_ARK_CERT_PEM: &str = include_str!("../../testdata/amd_vlek/ark.pem");
+const TEST_ASVK_CERT_PEM: &str = include_str!("../../testdata/amd_vlek/asvk.pem");
+const TEST_VLEK_CERT_PEM: &str = include_str!("../../testdata/amd_vlek/vlek.pem");
+const TEST_VLEK_KEY_PEM: &str = include_str!("../../testdata/amd_vlek/vlek.key");
+const TEST_VLEK_ARK_CRL_REVOKING_ASVK: &[u8] =
+    include_bytes!("../../testdata/amd_vlek/ark_crl_revoking_asvk.der");
+
 fn current_time() -> Instant {
     // Valid time for checking certificate validity.
     make_instant!("2025-10-01T08:00:00Z")
@@ -319,3 +345,215 @@ fn test_product_from_vcek_genoa() {
 fn test_product_from_vcek_turin() {
     assert_eq!(get_product_from_vcek(&AttestationData::load_turin_oc()), AmdProduct::Turin);
 }
+
+// The test CRLs are valid from 2025-09-01 until 2025-12-01.
+fn crl_valid_time() -> Instant {
+    make_instant!("2025-10-01T08:00:00Z")
+}
+
+fn check_test_chain_revocation(
+    verification_time: Instant,
+    crls: &[&[u8]],
+    policy: CrlPolicy,
+) -> anyhow::Result<()> {
+    let crls: std::vec::Vec<std::vec::Vec<u8>> = crls.iter().map(|crl| crl.to_vec()).collect();
+    check_revocation(
+        verification_time,
+        SigningKey::VCEK,
+        &load_cert(TEST_ARK_CERT_PEM),
+        &load_cert(TEST_ASK_CERT_PEM),
+        &load_cert(TEST_VCEK_CERT_PEM),
+        &crls,
+        policy,
+    )
+}
+
+#[test]
+fn test_chain_is_valid() {
+    let ark = load_cert(TEST_ARK_CERT_PEM);
+    let ask = load_cert(TEST_ASK_CERT_PEM);
+    let vcek = load_cert(TEST_VCEK_CERT_PEM);
+    assert!(validate_ark_ask_certs(crl_valid_time(), &ark, &ask).is_ok());
+    assert!(verify_cert_signature(&ask, &vcek).is_ok());
+}
+
+#[test]
+fn crl_without_revocations_succeeds() {
+    let policy = CrlPolicy { require_crls: true, reject_stale_crls: true };
+    let result = check_test_chain_revocation(crl_valid_time(), &[TEST_ARK_CRL_EMPTY], policy);
+    assert!(result.is_ok(), "Failed: {:?}", result.err().unwrap());
+}
+
+#[test]
+fn crl_revoking_ask_fails() {
+    let result = check_test_chain_revocation(
+        crl_valid_time(),
+        &[TEST_ARK_CRL_REVOKING_ASK],
+        CrlPolicy::default(),
+    );
+    assert!(result.is_err_and(|err| err.to_string().contains("ASK certificate has been revoked")));
+}
+
+#[test]
+fn crl_revoking_vcek_fails() {
+    let result = check_test_chain_revocation(
+        crl_valid_time(),
+        &[TEST_ARK_CRL_EMPTY, TEST_ASK_CRL_REVOKING_VCEK],
+        CrlPolicy::default(),
+    );
+    assert!(result.is_err_and(|err| err.to_string().contains("VCEK certificate has been revoked")));
+}
+
+#[test]
+fn missing_crl_fails_only_when_required() {
+    let required = CrlPolicy { require_crls: true, ..Default::default() };
+    assert!(check_test_chain_revocation(crl_valid_time(), &[], CrlPolicy::default()).is_ok());
+    assert!(check_test_chain_revocation(crl_valid_time(), &[], required).is_err());
+}
+
+#[test]
+fn stale_crl_fails_only_when_rejected() {
+    let stale_time = make_instant!("2026-01-01T00:00:00Z");
+    let reject_stale = CrlPolicy { reject_stale_crls: true, ..Default::default() };
+    assert!(check_test_chain_revocation(stale_time, &[TEST_ARK_CRL_EMPTY], CrlPolicy::default())
+        .is_ok());
+    assert!(check_test_chain_revocation(stale_time, &[TEST_ARK_CRL_EMPTY], reject_stale).is_err());
+    // Stale CRLs still revoke certificates.
+    assert!(check_test_chain_revocation(
+        stale_time,
+        &[TEST_ARK_CRL_REVOKING_ASK],
+        CrlPolicy::default()
+    )
+    .is_err());
+}
+
+#[test]
+fn crl_with_invalid_signature_fails() {
+    let mut crl = TEST_ARK_CRL_EMPTY.to_vec();
+    let last = crl.len() - 1;
+    crl[last] ^= 1;
+    let result = check_test_chain_revocation(crl_valid_time(), &[&crl], CrlPolicy::default());
+    assert!(result.is_err());
+}
+
+#[test]
+fn malformed_crl_fails() {
+    let result =
+        check_test_chain_revocation(crl_valid_time(), &[b"not a CRL"], CrlPolicy::default());
+    assert!(result.is_err());
+}
+
+#[test]
+fn crl_from_other_chain_is_ignored() {
+    let crls = std::vec![TEST_ARK_CRL_REVOKING_ASK.to_vec()];
+    let ark = load_cert(ARK_MILAN_CERT_PEM);
+    let ask = load_cert(ASK_MILAN_CERT_PEM);
+    let vcek = load_vcek_milan();
+    assert!(check_revocation(
+        current_time(),
+        SigningKey::VCEK,
+        &ark,
+        &ask,
+        &vcek,
+        &crls,
+        CrlPolicy::default()
+    )
+    .is_ok());
+    let required = CrlPolicy { require_crls: true, ..Default::default() };
+    assert!(check_revocation(current_time(), SigningKey::VCEK, &ark, &ask, &vcek, &crls, required)
+        .is_err());
+}
+
+// Offset of the bit-packed field holding SIGNING_KEY in the attestation report.
+// See Table 23 of the SEV-SNP specification.
+const REPORT_KEY_OFFSET: usize = 0x48;
+
+// Creates an AMD EPYC Milan attestation report with the TCB version of the
+// test VLEK certificate that is signed by the test VLEK, but claims to be
+// signed by `signing_key`.
+fn create_vlek_signed_report(signing_key: SigningKey) -> AttestationReport {
+    let mut report = AttestationReport::new_zeroed();
+    report.data.version = 3;
+    report.data.signature_algo = 1;
+    report.data.cpuid_fam_id = 0x19;
+    report.data.cpuid_mod_id = 0x01;
+    report.data.reported_tcb = [3, 0, 0, 0, 0, 0, 20, 209];
+    report.data.as_mut_bytes()[REPORT_KEY_OFFSET] = (signing_key as u8) << 2;
+
+    let key = EcdsaSigningKey::from_pkcs8_pem(TEST_VLEK_KEY_PEM).expect("invalid VLEK key");
+    let signature: Signature = key.sign(report.data.as_bytes());
+    // The report stores the scalars little-endian encoded.
+    let (r, s) = signature.split_bytes();
+    for i in 0..48 {
+        report.signature.r[i] = r[47 - i];
+        report.signature.s[i] = s[47 - i];
+    }
+    report
+}
+
+#[test]
+fn test_vlek_chain_is_valid() {
+    let ark = load_cert(TEST_VLEK_ARK_CERT_PEM);
+    let asvk = load_cert(TEST_ASVK_CERT_PEM);
+    let vlek = load_cert(TEST_VLEK_CERT_PEM);
+    assert!(validate_ark_ask_certs(crl_valid_time(), &ark, &asvk).is_ok());
+    assert!(verify_cert_signature(&asvk, &vlek).is_ok());
+    assert_eq!(get_product(&vlek).unwrap(), AmdProduct::Milan);
+}
+
+#[test]
+fn asvk_cert_fields_are_valid() {
+    assert!(verify_asvk_cert_fields(&load_cert(TEST_ASVK_CERT_PEM)).is_ok());
+}
+
+#[test]
+fn ask_cert_is_not_accepted_as_asvk() {
+    // The ASK is signed by the same ARK as the ASVK.
+    let result = verify_asvk_cert_fields(&load_cert(ASK_MILAN_CERT_PEM));
+    assert!(result.is_err_and(|err| err.to_string().contains("unexpected ASVK cert common name")));
+}
+
+#[test]
+fn leaf_cert_is_not_accepted_as_asvk() {
+    assert!(verify_asvk_cert_fields(&load_cert(TEST_VLEK_CERT_PEM)).is_err());
+}
+
+#[test]
+fn vlek_signed_report_verifies() {
+    let vlek = load_cert(TEST_VLEK_CERT_PEM);
+    let report = create_vlek_signed_report(SigningKey::VLEK);
+    let result = verify_attestation_report_signature(&vlek, &report);
+    assert!(result.is_ok(), "Failed: {:?}", result.err().unwrap());
+}
+
+#[test]
+fn vlek_cert_does_not_verify_report_claiming_vcek() {
+    // A VLEK certificate has no chip ID to match the report against.
+    let vlek = load_cert(TEST_VLEK_CERT_PEM);
+    let report = create_vlek_signed_report(SigningKey::VCEK);
+    assert!(verify_attestation_report_signature(&vlek, &report).is_err());
+}
+
+#[test]
+fn vlek_signed_report_with_other_tcb_fails() {
+    let vlek = load_cert(TEST_VLEK_CERT_PEM);
+    let mut report = create_vlek_signed_report(SigningKey::VLEK);
+    report.data.reported_tcb[7] = 210;
+    let result = verify_attestation_report_signature(&vlek, &report);
+    assert!(result.is_err_and(|err| err.to_string().contains("mismatch in TCB version")));
+}
+
+#[test]
+fn crl_revoking_asvk_fails() {
+    let crls = std::vec![TEST_VLEK_ARK_CRL_REVOKING_ASVK.to_vec()];
+    let result = check_revocation(
+        crl_valid_time(),
+        SigningKey::VLEK,
+        &load_cert(TEST_VLEK_ARK_CERT_PEM),
+        &load_cert(TEST_ASVK_CERT_PEM),
+        &load_cert(TEST_VLEK_CERT_PEM),
+        &crls,
+        CrlPolicy::default(),
+    );
+    assert!(result.is_err_and(|err| err.to_string().contains("ASVK certificate has been revoked")));
+}
@@ -0,0 +1,411 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Caching of successful verification results.
+//!
+//! Many clients typically present identical endorsements, e.g. all servers
+//! running the same build on the same platform. The decorators in this module
+//! memoize successful results keyed by the digest of the verified content,
+//! such that signatures, certificate chains and transparency log entries are
+//! only checked once for each distinct input.
+//!
+//! The evidence differs for every session, since the attestation report or
+//! quote binds a fresh key. [`CertificateChainCache`] therefore caches the
+//! verification of the platform certificate chain (VCEK or VLEK on AMD
+//! SEV-SNP, PCK on Intel TDX) on its own, keyed by the digest of the
+//! certificates, while the signature over the report or quote is verified
+//! every time.
+//!
+//! A cached result is reused from the time it was produced until the earliest
+//! `not_after` of the endorsements and platform certificates involved, or
+//! until a maximum age has passed, whichever comes first. Failures are never
+//! cached.
+
+use alloc::{collections::BTreeMap, sync::Arc, vec::Vec};
+
+use oak_attestation_verification_types::{
+    policy::Policy,
+    report::{LayerReport, VerificationReport},
+    verifier::AttestationVerifier,
+};
+use oak_dice::evidence::TeePlatform;
+use oak_proto_rust::oak::{
+    attestation::v1::{
+        AmdSevSnpEndorsement, ApplicationEndorsement, AttestationResults, ContainerEndorsement,
+        Endorsements, EventAttestationResults, Evidence, FirmwareEndorsement, KernelEndorsement,
+        SignedEndorsement, SystemEndorsement,
+    },
+    Variant,
+};
+use oak_tdx_quote::TdxQuoteWrapper;
+use oak_time::{Clock, Duration, Instant};
+use prost::Message;
+use sha2::{Digest, Sha256};
+use spinning_top::Spinlock;
+use x509_cert::{crl::CertificateList, der::Decode, Certificate};
+
+use crate::{intel::get_quote_cert_chain_not_after, x509::get_certificate_not_after};
+
+/// SHA2-256 digest of the verified content.
+type CacheKey = [u8; 32];
+
+struct CacheEntry<T> {
+    value: T,
+    // Time at which the value was produced.
+    verified_at: Instant,
+    // Time after which the value must no longer be used.
+    expires_at: Instant,
+    // Logical time of the last access, used for LRU eviction.
+    last_used: u64,
+}
+
+struct CacheState<T> {
+    entries: BTreeMap<CacheKey, CacheEntry<T>>,
+    tick: u64,
+}
+
+/// A bounded, least-recently-used cache of verification results.
+pub struct VerificationCache<T> {
+    state: Spinlock<CacheState<T>>,
+    capacity: usize,
+    max_age: Duration,
+}
+
+impl<T: Clone> VerificationCache<T> {
+    /// Creates a cache which holds at most `capacity` results, each for no
+    /// longer than `max_age`.
+    pub fn new(capacity: usize, max_age: Duration) -> Self {
+        Self {
+            state: Spinlock::new(CacheState { entries: BTreeMap::new(), tick: 0 }),
+            capacity,
+            max_age,
+        }
+    }
+
+    /// Returns the number of results currently held by the cache, including
+    /// expired ones which have not been evicted yet.
+    pub fn len(&self) -> usize {
+        self.state.lock().entries.len()
+    }
+
+    /// Returns whether the cache holds no results.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all results from the cache.
+    pub fn clear(&self) {
+        self.state.lock().entries.clear();
+    }
+
+    /// Returns the cached value for `key` if it is valid at `time`.
+    fn get(&self, key: &CacheKey, time: Instant) -> Option<T> {
+        let mut state = self.state.lock();
+        state.tick += 1;
+        let tick = state.tick;
+        let entry = state.entries.get_mut(key)?;
+        if time < entry.verified_at {
+            // The result does not vouch for earlier points in time.
+            return None;
+        }
+        if time > entry.expires_at {
+            state.entries.remove(key);
+            return None;
+        }
+        entry.last_used = tick;
+        Some(entry.value.clone())
+    }
+
+    /// Stores `value`, which was produced at `verified_at`. It expires at
+    /// `not_after` at the latest.
+    fn insert(&self, key: CacheKey, value: T, verified_at: Instant, not_after: Option<Instant>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let max_expires_at = verified_at + self.max_age;
+        let expires_at = not_after.map_or(max_expires_at, |time| time.min(max_expires_at));
+        if expires_at < verified_at {
+            return;
+        }
+
+        let mut state = self.state.lock();
+        state.tick += 1;
+        let last_used = state.tick;
+        if !state.entries.contains_key(&key) && state.entries.len() >= self.capacity {
+            // Expired entries go first, the least recently used one otherwise.
+            state.entries.retain(|_, entry| entry.expires_at >= verified_at);
+            if state.entries.len() >= self.capacity {
+                let lru_key = state
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.last_used)
+                    .map(|(key, _)| *key);
+                if let Some(lru_key) = lru_key {
+                    state.entries.remove(&lru_key);
+                }
+            }
+        }
+        state.entries.insert(key, CacheEntry { value, verified_at, expires_at, last_used });
+    }
+}
+
+/// Cache of verified platform certificate chains, to be shared between the
+/// platform policies via `with_cert_chain_cache`.
+///
+/// Entries are keyed by the digest of the certificates and CRLs of the chain
+/// together with the checks which were applied to them. They expire with the
+/// earliest `not_after` of the certificates or `next_update` of the CRLs.
+pub struct CertificateChainCache {
+    cache: VerificationCache<()>,
+}
+
+impl CertificateChainCache {
+    /// Creates a cache which holds at most `capacity` chains, each for no
+    /// longer than `max_age`.
+    pub fn new(capacity: usize, max_age: Duration) -> Self {
+        Self { cache: VerificationCache::new(capacity, max_age) }
+    }
+
+    /// Returns the underlying cache.
+    pub fn cache(&self) -> &VerificationCache<()> {
+        &self.cache
+    }
+
+    /// Runs `verify` unless a chain with the same `parts` was verified before
+    /// and is still valid at `verification_time`.
+    pub(crate) fn verify(
+        &self,
+        verification_time: Instant,
+        parts: &[&[u8]],
+        not_after: Option<Instant>,
+        verify: impl FnOnce() -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        let mut hasher = Sha256::new();
+        for part in parts {
+            hasher.update(Sha256::digest(part));
+        }
+        let key: CacheKey = hasher.finalize().into();
+        if self.cache.get(&key, verification_time).is_some() {
+            return Ok(());
+        }
+
+        verify()?;
+        self.cache.insert(key, (), verification_time, not_after);
+        Ok(())
+    }
+}
+
+/// Decorates an attestation verifier such that successful results are cached.
+///
+/// Results are keyed by the whole evidence, so they are only reused when the
+/// same evidence is verified again, e.g. when a client re-checks a server it
+/// is already connected to. Use a [`CertificateChainCache`] to share work
+/// between sessions.
+pub struct CachingAttestationVerifier<V> {
+    inner: V,
+    cache: VerificationCache<AttestationResults>,
+    clock: Arc<dyn Clock>,
+}
+
+impl<V: AttestationVerifier> CachingAttestationVerifier<V> {
+    /// Creates a caching verifier. The clock should be the same as the one of
+    /// the inner verifier.
+    pub fn new(inner: V, clock: Arc<dyn Clock>, capacity: usize, max_age: Duration) -> Self {
+        Self { inner, cache: VerificationCache::new(capacity, max_age), clock }
+    }
+
+    /// Returns the cache of the verifier.
+    pub fn cache(&self) -> &VerificationCache<AttestationResults> {
+        &self.cache
+    }
+}
+
+impl<V: AttestationVerifier> AttestationVerifier for CachingAttestationVerifier<V> {
+    fn verify(
+        &self,
+        evidence: &Evidence,
+        endorsements: &Endorsements,
+    ) -> anyhow::Result<AttestationResults> {
+        let verification_time = self.clock.get_time();
+        let key = digest_pair(evidence, endorsements);
+        if let Some(results) = self.cache.get(&key, verification_time) {
+            return Ok(results);
+        }
+
+        let results = self.inner.verify(evidence, endorsements)?;
+        let not_after = earliest_not_after(evidence, endorsements);
+        self.cache.insert(key, results.clone(), verification_time, not_after);
+        Ok(results)
+    }
+
+    // Reports are meant for diagnosis, hence they are never cached.
+    fn report(&self, evidence: &Evidence, endorsements: &Endorsements) -> VerificationReport {
+        self.inner.report(evidence, endorsements)
+    }
+}
+
+/// Decorates an event policy such that successful results are cached.
+///
+/// The policy is given the verification time, so unlike
+/// [`CachingAttestationVerifier`] it does not need a clock.
+pub struct CachingPolicy<P> {
+    inner: P,
+    cache: VerificationCache<EventAttestationResults>,
+}
+
+impl<P: Policy<[u8]>> CachingPolicy<P> {
+    /// Creates a caching policy which holds at most `capacity` results, each
+    /// for no longer than `max_age`.
+    pub fn new(inner: P, capacity: usize, max_age: Duration) -> Self {
+        Self { inner, cache: VerificationCache::new(capacity, max_age) }
+    }
+
+    /// Returns the cache of the policy.
+    pub fn cache(&self) -> &VerificationCache<EventAttestationResults> {
+        &self.cache
+    }
+}
+
+impl<P: Policy<[u8]>> Policy<[u8]> for CachingPolicy<P> {
+    fn verify(
+        &self,
+        verification_time: Instant,
+        evidence: &[u8],
+        endorsement: &Variant,
+    ) -> anyhow::Result<EventAttestationResults> {
+        let mut hasher = Sha256::new();
+        hasher.update(Sha256::digest(evidence));
+        hasher.update(Sha256::digest(endorsement.encode_to_vec()));
+        let key: CacheKey = hasher.finalize().into();
+        if let Some(results) = self.cache.get(&key, verification_time) {
+            return Ok(results);
+        }
+
+        let results = self.inner.verify(verification_time, evidence, endorsement)?;
+        let not_after = variant_not_after(endorsement);
+        self.cache.insert(key, results.clone(), verification_time, not_after);
+        Ok(results)
+    }
+
+    fn layer(&self) -> &'static str {
+        self.inner.layer()
+    }
+
+    fn report(
+        &self,
+        verification_time: Instant,
+        evidence: &[u8],
+        endorsement: &Variant,
+    ) -> LayerReport {
+        self.inner.report(verification_time, evidence, endorsement)
+    }
+}
+
+fn digest_pair(evidence: &Evidence, endorsements: &Endorsements) -> CacheKey {
+    let mut hasher = Sha256::new();
+    hasher.update(Sha256::digest(evidence.encode_to_vec()));
+    hasher.update(Sha256::digest(endorsements.encode_to_vec()));
+    hasher.finalize().into()
+}
+
+/// Returns the earliest time at which an endorsement or platform certificate
+/// involved in the verification expires, if any of them could be parsed.
+fn earliest_not_after(evidence: &Evidence, endorsements: &Endorsements) -> Option<Instant> {
+    // The PCK certificate chain of Intel TDX is part of the evidence.
+    let quote_not_after = evidence
+        .root_layer
+        .as_ref()
+        .filter(|root_layer| root_layer.platform == TeePlatform::IntelTdx as i32)
+        .and_then(|root_layer| {
+            let quote = TdxQuoteWrapper::new(&root_layer.remote_attestation_report);
+            get_quote_cert_chain_not_after(&quote).ok()
+        });
+    endorsements
+        .platform
+        .iter()
+        .chain(endorsements.initial.iter())
+        .chain(endorsements.events.iter())
+        .filter_map(variant_not_after)
+        .chain(quote_not_after)
+        .min()
+}
+
+/// Returns the earliest `not_after` among the signed endorsements and
+/// certificates contained in an endorsement. Unknown endorsement types don't
+/// contribute.
+fn variant_not_after(variant: &Variant) -> Option<Instant> {
+    if let Ok(endorsement) = AmdSevSnpEndorsement::try_from(variant) {
+        return amd_cert_chain_not_after(
+            &endorsement.tee_certificate,
+            &endorsement.asvk_certificate,
+            &endorsement.crls,
+        );
+    }
+    let signed_endorsements: Vec<SignedEndorsement> =
+        if let Ok(endorsement) = FirmwareEndorsement::try_from(variant) {
+            endorsement.firmware.into_iter().collect()
+        } else if let Ok(endorsement) = KernelEndorsement::try_from(variant) {
+            [
+                endorsement.kernel,
+                endorsement.kernel_cmd_line,
+                endorsement.init_ram_fs,
+                endorsement.memory_map,
+                endorsement.acpi,
+            ]
+            .into_iter()
+            .flatten()
+            .collect()
+        } else if let Ok(endorsement) = SystemEndorsement::try_from(variant) {
+            endorsement.system_image.into_iter().collect()
+        } else if let Ok(endorsement) = ContainerEndorsement::try_from(variant) {
+            [endorsement.binary, endorsement.configuration].into_iter().flatten().collect()
+        } else if let Ok(endorsement) = ApplicationEndorsement::try_from(variant) {
+            [endorsement.binary, endorsement.configuration].into_iter().flatten().collect()
+        } else {
+            return None;
+        };
+    signed_endorsements
+        .iter()
+        .filter_map(|signed_endorsement| {
+            let statement = intoto::statement::parse_statement(
+                &signed_endorsement.endorsement.as_ref()?.serialized,
+            )
+            .ok()?;
+            Some(statement.predicate.validity?.not_after)
+        })
+        .min()
+}
+
+/// Returns the earliest time at which the VCEK or VLEK certificate, the ASVK
+/// certificate or one of the CRLs of an AMD SEV-SNP endorsement expires.
+pub(crate) fn amd_cert_chain_not_after(
+    tee_certificate: &[u8],
+    asvk_certificate: &[u8],
+    crls: &[Vec<u8>],
+) -> Option<Instant> {
+    let certificates_not_after = [tee_certificate, asvk_certificate]
+        .into_iter()
+        .filter_map(|cert| Certificate::from_der(cert).ok())
+        .map(|cert| get_certificate_not_after(&cert));
+    let crls_next_update = crls.iter().filter_map(|crl| {
+        let next_update = CertificateList::from_der(crl).ok()?.tbs_cert_list.next_update?;
+        Some(Instant::from_unix_nanos(next_update.to_unix_duration().as_nanos() as i128))
+    });
+    certificates_not_after.chain(crls_next_update).min()
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,374 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use core::sync::atomic::{AtomicI64, AtomicUsize, Ordering};
+
+use oak_proto_rust::oak::attestation::v1::{
+    attestation_results::Status, AmdSevReferenceValues, AttestationResults, RootLayerEvidence,
+};
+use test_util::{get_oc_reference_values, AttestationData};
+
+use super::*;
+use crate::{AmdSevSnpPolicy, IntelTdxPolicy};
+
+const MAX_AGE: Duration = Duration::from_days(365);
+
+// A clock which can be moved forward by the test.
+struct TestClock(AtomicI64);
+
+impl TestClock {
+    fn at(time: Instant) -> Arc<Self> {
+        Arc::new(Self(AtomicI64::new(time.into_unix_millis())))
+    }
+
+    fn set(&self, time: Instant) {
+        self.0.store(time.into_unix_millis(), Ordering::SeqCst);
+    }
+}
+
+impl Clock for TestClock {
+    fn get_time(&self) -> Instant {
+        Instant::from_unix_millis(self.0.load(Ordering::SeqCst))
+    }
+}
+
+// Counts invocations and succeeds unless the evidence is empty.
+struct CountingVerifier {
+    calls: Arc<AtomicUsize>,
+}
+
+impl AttestationVerifier for CountingVerifier {
+    fn verify(
+        &self,
+        evidence: &Evidence,
+        _endorsements: &Endorsements,
+    ) -> anyhow::Result<AttestationResults> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        anyhow::ensure!(evidence.root_layer.is_some(), "no root layer");
+        Ok(AttestationResults { status: Status::Success.into(), ..Default::default() })
+    }
+}
+
+struct CountingPolicy {
+    calls: Arc<AtomicUsize>,
+}
+
+impl Policy<[u8]> for CountingPolicy {
+    fn verify(
+        &self,
+        _verification_time: Instant,
+        _evidence: &[u8],
+        _endorsement: &Variant,
+    ) -> anyhow::Result<EventAttestationResults> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Ok(EventAttestationResults::default())
+    }
+
+    fn layer(&self) -> &'static str {
+        "counting"
+    }
+}
+
+// Returns a time at which all endorsements of the example are valid.
+fn endorsed_time(d: &AttestationData) -> Instant {
+    earliest_not_after(&d.evidence, &d.endorsements).expect("no expiry in endorsements")
+        - Duration::from_days(1)
+}
+
+fn create_verifier(
+    clock: Arc<TestClock>,
+    capacity: usize,
+) -> (CachingAttestationVerifier<CountingVerifier>, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let inner = CountingVerifier { calls: calls.clone() };
+    (CachingAttestationVerifier::new(inner, clock, capacity, MAX_AGE), calls)
+}
+
+#[test]
+fn verifier_reuses_cached_results() {
+    let d = AttestationData::load_milan_oc_release();
+    let (verifier, calls) = create_verifier(TestClock::at(endorsed_time(&d)), 8);
+
+    for _ in 0..3 {
+        verifier.verify(&d.evidence, &d.endorsements).expect("verification failed");
+    }
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(verifier.cache().len(), 1);
+}
+
+#[test]
+fn verifier_distinguishes_endorsements() {
+    let d = AttestationData::load_milan_oc_release();
+    let (verifier, calls) = create_verifier(TestClock::at(endorsed_time(&d)), 8);
+
+    verifier.verify(&d.evidence, &d.endorsements).expect("verification failed");
+    verifier.verify(&d.evidence, &Endorsements::default()).expect("verification failed");
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn verifier_does_not_cache_failures() {
+    let d = AttestationData::load_milan_oc_release();
+    let (verifier, calls) = create_verifier(TestClock::at(endorsed_time(&d)), 8);
+
+    assert!(verifier.verify(&Evidence::default(), &d.endorsements).is_err());
+    assert!(verifier.verify(&Evidence::default(), &d.endorsements).is_err());
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert!(verifier.cache().is_empty());
+}
+
+#[test]
+fn verifier_expires_results_with_endorsements() {
+    let d = AttestationData::load_milan_oc_release();
+    let not_after =
+        earliest_not_after(&d.evidence, &d.endorsements).expect("no expiry in endorsements");
+    let clock = TestClock::at(endorsed_time(&d));
+    let (verifier, calls) = create_verifier(clock.clone(), 8);
+
+    verifier.verify(&d.evidence, &d.endorsements).expect("verification failed");
+    clock.set(not_after);
+    verifier.verify(&d.evidence, &d.endorsements).expect("verification failed");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    clock.set(not_after + Duration::from_seconds(1));
+    verifier.verify(&d.evidence, &d.endorsements).expect("verification failed");
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn verifier_does_not_reuse_results_for_earlier_times() {
+    let d = AttestationData::load_milan_oc_release();
+    let clock = TestClock::at(endorsed_time(&d));
+    let (verifier, calls) = create_verifier(clock.clone(), 8);
+
+    verifier.verify(&d.evidence, &d.endorsements).expect("verification failed");
+    clock.set(endorsed_time(&d) - Duration::from_seconds(1));
+    verifier.verify(&d.evidence, &d.endorsements).expect("verification failed");
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn endorsements_expire_no_later_than_vcek_certificate() {
+    let d = AttestationData::load_milan_oc_release();
+    let platform: AmdSevSnpEndorsement =
+        d.endorsements.platform.as_ref().expect("no platform endorsement").try_into().unwrap();
+    let vcek = Certificate::from_der(&platform.tee_certificate).expect("invalid VCEK");
+
+    let not_after = earliest_not_after(&d.evidence, &d.endorsements);
+
+    assert!(not_after.is_some_and(|time| time <= get_certificate_not_after(&vcek)));
+    assert!(not_after.is_some_and(|time| time > d.valid_not_before));
+}
+
+#[test]
+fn verifier_does_not_cache_results_with_expired_endorsements() {
+    let d = AttestationData::load_milan_oc_release();
+    let not_after =
+        earliest_not_after(&d.evidence, &d.endorsements).expect("no expiry in endorsements");
+    let (verifier, calls) =
+        create_verifier(TestClock::at(not_after + Duration::from_seconds(1)), 8);
+
+    verifier.verify(&d.evidence, &d.endorsements).expect("verification failed");
+    verifier.verify(&d.evidence, &d.endorsements).expect("verification failed");
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert!(verifier.cache().is_empty());
+}
+
+#[test]
+fn tdx_evidence_expires_with_pck_chain() {
+    let d = AttestationData::load_tdx_oc();
+
+    let not_after = earliest_not_after(&d.evidence, &Endorsements::default());
+
+    assert!(not_after.is_some_and(|time| time > d.make_valid_time()));
+}
+
+#[test]
+fn cache_evicts_least_recently_used() {
+    let cache = VerificationCache::new(2, MAX_AGE);
+    let now = Instant::from_unix_seconds(1_000);
+
+    cache.insert([1; 32], 1, now, None);
+    cache.insert([2; 32], 2, now, None);
+    assert_eq!(cache.get(&[1; 32], now), Some(1));
+    cache.insert([3; 32], 3, now, None);
+
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(&[1; 32], now), Some(1));
+    assert_eq!(cache.get(&[2; 32], now), None);
+    assert_eq!(cache.get(&[3; 32], now), Some(3));
+}
+
+#[test]
+fn cache_evicts_expired_entries_first() {
+    let cache = VerificationCache::new(2, MAX_AGE);
+    let now = Instant::from_unix_seconds(1_000);
+
+    cache.insert([1; 32], 1, now, Some(now + Duration::from_seconds(1)));
+    cache.insert([2; 32], 2, now, None);
+    let later = now + Duration::from_seconds(2);
+    assert_eq!(cache.get(&[2; 32], later), Some(2));
+    cache.insert([3; 32], 3, later, None);
+
+    assert_eq!(cache.get(&[2; 32], later), Some(2));
+    assert_eq!(cache.get(&[3; 32], later), Some(3));
+}
+
+#[test]
+fn cache_bounds_age() {
+    let cache = VerificationCache::new(2, Duration::from_seconds(10));
+    let now = Instant::from_unix_seconds(1_000);
+
+    cache.insert([1; 32], 1, now, Some(now + Duration::from_days(1)));
+
+    assert_eq!(cache.get(&[1; 32], now + Duration::from_seconds(10)), Some(1));
+    assert_eq!(cache.get(&[1; 32], now + Duration::from_seconds(11)), None);
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn cache_with_zero_capacity_holds_nothing() {
+    let cache = VerificationCache::new(0, MAX_AGE);
+    let now = Instant::from_unix_seconds(1_000);
+
+    cache.insert([1; 32], 1, now, None);
+
+    assert_eq!(cache.get(&[1; 32], now), None);
+}
+
+#[test]
+fn policy_reuses_cached_results() {
+    let d = AttestationData::load_milan_oc_release();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let policy = CachingPolicy::new(CountingPolicy { calls: calls.clone() }, 8, MAX_AGE);
+    let event = &d.evidence.event_log.as_ref().unwrap().encoded_events[0];
+    let endorsement = &d.endorsements.events[0];
+    let now = endorsed_time(&d);
+
+    policy.verify(now, event, endorsement).expect("verification failed");
+    policy.verify(now, event, endorsement).expect("verification failed");
+    policy.verify(now, b"other event", endorsement).expect("verification failed");
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(policy.layer(), "counting");
+    assert!(variant_not_after(endorsement).is_some_and(|time| time > now));
+}
+
+#[test]
+fn chain_cache_verifies_each_chain_once() {
+    let cache = CertificateChainCache::new(8, MAX_AGE);
+    let now = Instant::from_unix_seconds(1_000);
+    let calls = AtomicUsize::new(0);
+    let verify = || {
+        calls.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    };
+
+    cache.verify(now, &[b"vcek", b"crl"], None, verify).expect("verification failed");
+    cache.verify(now, &[b"vcek", b"crl"], None, verify).expect("verification failed");
+    cache.verify(now, &[b"vcek"], None, verify).expect("verification failed");
+    cache.verify(now, &[b"vcekcrl"], None, verify).expect("verification failed");
+
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert_eq!(cache.cache().len(), 3);
+}
+
+#[test]
+fn chain_cache_does_not_cache_failures() {
+    let cache = CertificateChainCache::new(8, MAX_AGE);
+    let now = Instant::from_unix_seconds(1_000);
+
+    assert!(cache.verify(now, &[b"vcek"], None, || anyhow::bail!("invalid chain")).is_err());
+    assert!(cache.verify(now, &[b"vcek"], None, || anyhow::bail!("invalid chain")).is_err());
+
+    assert!(cache.cache().is_empty());
+}
+
+fn amd_platform_reference_values(d: &AttestationData) -> AmdSevReferenceValues {
+    let ref_values = get_oc_reference_values(&d.reference_values);
+    ref_values.root_layer.as_ref().unwrap().amd_sev.clone().unwrap()
+}
+
+#[test]
+fn amd_policy_shares_cert_chain_between_reports() {
+    let d = AttestationData::load_milan_oc_release();
+    let cache = Arc::new(CertificateChainCache::new(8, MAX_AGE));
+    let policy = AmdSevSnpPolicy::new(&amd_platform_reference_values(&d))
+        .with_cert_chain_cache(cache.clone());
+    let evidence = d.evidence.root_layer.as_ref().unwrap();
+    let endorsement = d.endorsements.platform.as_ref().unwrap();
+
+    policy.verify(d.make_valid_time(), evidence, endorsement).expect("verification failed");
+    policy.verify(d.make_valid_time(), evidence, endorsement).expect("verification failed");
+    assert_eq!(cache.cache().len(), 1);
+
+    // The chain is cached, but the report signature is still checked.
+    let mut tampered = evidence.remote_attestation_report.clone();
+    let signature_offset = tampered.len() - 512;
+    tampered[signature_offset] ^= 1;
+    let tampered = RootLayerEvidence { remote_attestation_report: tampered, ..evidence.clone() };
+    assert!(policy.verify(d.make_valid_time(), &tampered, endorsement).is_err());
+    assert_eq!(cache.cache().len(), 1);
+}
+
+#[test]
+fn amd_policy_does_not_reuse_chain_verified_with_laxer_checks() {
+    let d = AttestationData::load_milan_oc_release();
+    let cache = Arc::new(CertificateChainCache::new(8, MAX_AGE));
+    let lax_reference_values = AmdSevReferenceValues {
+        check_vcek_cert_expiry: false,
+        ..amd_platform_reference_values(&d)
+    };
+    let strict_reference_values =
+        AmdSevReferenceValues { require_crls: true, ..lax_reference_values.clone() };
+    let evidence = d.evidence.root_layer.as_ref().unwrap();
+    let endorsement = d.endorsements.platform.as_ref().unwrap();
+
+    AmdSevSnpPolicy::new(&lax_reference_values)
+        .with_cert_chain_cache(cache.clone())
+        .verify(d.make_valid_time(), evidence, endorsement)
+        .expect("verification failed");
+    let result = AmdSevSnpPolicy::new(&strict_reference_values)
+        .with_cert_chain_cache(cache.clone())
+        .verify(d.make_valid_time(), evidence, endorsement);
+
+    assert!(result.is_err());
+    assert_eq!(cache.cache().len(), 1);
+}
+
+#[test]
+fn tdx_policy_caches_pck_chain() {
+    let d = AttestationData::load_tdx_oc();
+    let cache = Arc::new(CertificateChainCache::new(8, MAX_AGE));
+    let ref_values = get_oc_reference_values(&d.reference_values);
+    let policy =
+        IntelTdxPolicy::new(ref_values.root_layer.as_ref().unwrap().intel_tdx.as_ref().unwrap())
+            .with_cert_chain_cache(cache.clone());
+    let evidence = d.evidence.root_layer.as_ref().unwrap();
+
+    policy.verify(d.make_valid_time(), evidence, &Variant::default()).expect("verification failed");
+    policy.verify(d.make_valid_time(), evidence, &Variant::default()).expect("verification failed");
+
+    assert_eq!(cache.cache().len(), 1);
+    // The chain is not reused before it was verified.
+    let early_time = d.make_valid_time() - Duration::from_days(365 * 20);
+    assert!(policy.verify(early_time, evidence, &Variant::default()).is_err());
+}
@@ -16,10 +16,15 @@
 
 //! Provides verification based on evidence, endorsements and reference values.
 
-use alloc::format;
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
 
 use anyhow::Context;
-use digest_util::is_raw_digest_match;
+use digest_util::{is_raw_digest_match, raw_to_hex_digest};
+use oak_attestation_verification_types::report::{CheckStatus, LayerReport};
 use oak_proto_rust::oak::{
     attestation::v1::{
         expected_digests, expected_values, extracted_evidence::EvidenceValues,
@@ -81,12 +86,14 @@ fn compare_oak_restricted_kernel_measurement_digests(
     compare_kernel_layer_measurement_digests(
         values.kernel_layer.as_ref().context("no kernel layer evidence values")?,
         expected.kernel_layer.as_ref().context("no kernel layer expected values")?,
+        None,
     )
     .context("comparing kernel layer values")?;
 
     compare_application_layer_measurement_digests(
         values.application_layer.as_ref().context("no applications layer evidence values")?,
         expected.application_layer.as_ref().context("no application layer expected values")?,
+        None,
     )
     .context("comparing application layer values")
 }
@@ -106,18 +113,21 @@ fn compare_oak_containers_measurement_digests(
     compare_kernel_layer_measurement_digests(
         values.kernel_layer.as_ref().context("no kernel layer evidence values")?,
         expected.kernel_layer.as_ref().context("no kernel layer expected_values")?,
+        None,
     )
     .context("comparing kernel layer digests")?;
 
     compare_system_layer_measurement_digests(
         values.system_layer.as_ref().context("no system layer evidence values")?,
         expected.system_layer.as_ref().context("no system layer expected_values")?,
+        None,
     )
     .context("comparing system layer digests")?;
 
     compare_container_layer_measurement_digests(
         values.container_layer.as_ref().context("no container layer evidence values")?,
         expected.container_layer.as_ref().context("no system layer expected_values")?,
+        None,
     )
     .context("comparing container layer digests")
 }
@@ -145,7 +155,7 @@ fn compare_cb_measurement_digests(
         ));
     }
     for (value_layer, expected_layer) in values.layers.iter().zip(expected.layers.iter()) {
-        compare_event_measurement_digests(value_layer, expected_layer)
+        compare_event_measurement_digests(value_layer, expected_layer, None)
             .context("layer verification failed")?;
     }
 
@@ -169,11 +179,21 @@ fn compare_root_layer_measurement_digests(
                     .stage0_expected
                     .as_ref()
                     .context("no stage0 expected value provided")?,
+                None,
             )
             .context("comparing firmware layer digests")?;
             verify_amd_sev_attestation_report_values(report_values, amd_sev_values)
         }
         (Some(Report::Tdx(quote_values)), _, Some(intel_tdx_values), _) => {
+            // MRTD covers the initial memory of the TD, which includes stage0.
+            if let Some(stage0_expected) = intel_tdx_values.stage0_expected.as_ref() {
+                compare_firmware_layer_measurement_digests(
+                    &quote_values.mr_td,
+                    stage0_expected,
+                    None,
+                )
+                .context("comparing firmware layer digests")?;
+            }
             verify_intel_tdx_attestation_quote(quote_values, intel_tdx_values)
         }
         (_, _, _, Some(insecure_values)) => {
@@ -192,119 +212,233 @@ fn compare_root_layer_measurement_digests(
 pub(crate) fn compare_firmware_layer_measurement_digests(
     initial_measurement: &[u8],
     expected_values: &ExpectedDigests,
+    report: Option<&mut LayerReport>,
 ) -> anyhow::Result<()> {
     let measurement = convert_amd_sev_snp_initial_measurement(initial_measurement);
-    compare_measurement_digest(&measurement, expected_values)
+    check_measurement_digest(report, "measurement", Ok(&measurement), Ok(expected_values))
 }
 
 /// Verifies the measurement values of the kernel layer, which is common to both
 /// the Oak Restricted Kernel and Oak Containers setups.
+///
+/// All values are compared, and each comparison is recorded as a check in
+/// `report` if given. The first failure determines the result.
 pub(crate) fn compare_kernel_layer_measurement_digests(
     values: &KernelLayerData,
     expected: &KernelLayerExpectedValues,
+    mut report: Option<&mut LayerReport>,
 ) -> anyhow::Result<()> {
-    let expected_kernel_values = expected.kernel.as_ref().context("no kernel expected values")?;
-
-    compare_measurement_digest(
-        values.kernel_image.as_ref().context("no kernel evidence value")?,
-        expected_kernel_values
-            .image
-            .as_ref()
-            .context("expected values contained no image digests")?,
-    )
-    .context("comparing kernel image digests")?;
-
-    compare_measurement_digest(
-        values.kernel_setup_data.as_ref().context("no kernel setup data evidence value")?,
-        expected_kernel_values
-            .setup_data
-            .as_ref()
-            .context("expected values contained no setup_data digests")?,
-    )
-    .context("comparing kernel setup data digests")?;
+    let expected_kernel_values = || expected.kernel.as_ref().context("no kernel expected values");
 
+    [
+        check_measurement_digest(
+            report.as_deref_mut(),
+            "image",
+            values.kernel_image.as_ref().context("no kernel evidence value"),
+            expected_kernel_values().and_then(|values| {
+                values.image.as_ref().context("expected values contained no image digests")
+            }),
+        )
+        .context("comparing kernel image digests"),
+        check_measurement_digest(
+            report.as_deref_mut(),
+            "setup_data",
+            values.kernel_setup_data.as_ref().context("no kernel setup data evidence value"),
+            expected_kernel_values().and_then(|values| {
+                values
+                    .setup_data
+                    .as_ref()
+                    .context("expected values contained no setup_data digests")
+            }),
+        )
+        .context("comparing kernel setup data digests"),
+        check_kernel_cmd_line(report.as_deref_mut(), values, expected),
+        check_measurement_digest(
+            report.as_deref_mut(),
+            "init_ram_fs",
+            values.init_ram_fs.as_ref().context("no initramfs value provided"),
+            expected.init_ram_fs.as_ref().context("no initramfs expected value provided"),
+        )
+        .context("comparing init_ram_fs digests"),
+        check_measurement_digest(
+            report.as_deref_mut(),
+            "memory_map",
+            values.memory_map.as_ref().context("no memory_map value provided"),
+            expected.memory_map.as_ref().context("no memory_map expected value provided"),
+        )
+        .context("comparing memory_map digests"),
+        check_measurement_digest(
+            report,
+            "acpi",
+            values.acpi.as_ref().context("no ACPI table value provided"),
+            expected.acpi.as_ref().context("no ACPI table expected value provided"),
+        )
+        .context("comparing ACPI digests"),
+    ]
+    .into_iter()
+    .collect()
+}
+
+fn check_kernel_cmd_line(
+    report: Option<&mut LayerReport>,
+    values: &KernelLayerData,
+    expected: &KernelLayerExpectedValues,
+) -> anyhow::Result<()> {
+    let actual = values.kernel_raw_cmd_line.clone();
     // TODO: b/331252282 - Remove temporary workaround for cmd line.
     match (&values.kernel_raw_cmd_line, &expected.kernel_cmd_line_text) {
         // We support the skipped value, whether the kernel cmd line text is valid or not.
         // Skipping is a way to work around the cmd line length limit.
         (_, Some(TextExpectedValue { r#type: Some(text_expected_value::Type::Skipped(_)) })) => {
-            Ok(())
+            record_comparison(report, "cmd_line", Ok(()), true, None, actual)
         }
 
         // If a kernel cmd line is provided, it must be shorter than 256 bytes.
         (Some(kernel_raw_cmd_line), Some(expected)) if kernel_raw_cmd_line.len() < 256 => {
-            compare_text_value(kernel_raw_cmd_line.as_str(), expected)
+            record_comparison(
+                report,
+                "cmd_line",
+                compare_text_value(kernel_raw_cmd_line.as_str(), expected),
+                false,
+                Some(format_text_expected_value(expected)),
+                actual,
+            )
         }
-        _ => Err(anyhow::anyhow!("No valid kernel_raw_cmd_line provided")),
-    }?;
-
-    compare_measurement_digest(
-        values.init_ram_fs.as_ref().context("no initramfs value provided")?,
-        expected.init_ram_fs.as_ref().context("no initramfs expected value provided")?,
-    )
-    .context("comparing init_ram_fs digests")?;
-    compare_measurement_digest(
-        values.memory_map.as_ref().context("no memory_map value provided")?,
-        expected.memory_map.as_ref().context("no memory_map expected value provided")?,
-    )
-    .context("comparing memory_map digests")?;
-    compare_measurement_digest(
-        values.acpi.as_ref().context("no ACPI table value provided")?,
-        expected.acpi.as_ref().context("no ACPI table expected value provided")?,
-    )
-    .context("comparing ACPI digests")
+        _ => record_comparison(
+            report,
+            "cmd_line",
+            Err(anyhow::anyhow!("No valid kernel_raw_cmd_line provided")),
+            false,
+            None,
+            actual,
+        ),
+    }
 }
 
 pub(crate) fn compare_event_measurement_digests(
     values: &EventData,
     expected: &EventExpectedValues,
+    report: Option<&mut LayerReport>,
 ) -> anyhow::Result<()> {
-    compare_measurement_digest(
-        values.event.as_ref().context("no event evidence value")?,
-        expected.event.as_ref().context("no event image value")?,
+    check_measurement_digest(
+        report,
+        "event",
+        values.event.as_ref().context("no event evidence value"),
+        expected.event.as_ref().context("no event image value"),
     )
 }
 
 pub(crate) fn compare_system_layer_measurement_digests(
     values: &SystemLayerData,
     expected: &SystemLayerExpectedValues,
+    report: Option<&mut LayerReport>,
 ) -> anyhow::Result<()> {
-    compare_measurement_digest(
-        values.system_image.as_ref().context("no system image evidence value")?,
-        expected.system_image.as_ref().context("no expected system image value")?,
+    check_measurement_digest(
+        report,
+        "image",
+        values.system_image.as_ref().context("no system image evidence value"),
+        expected.system_image.as_ref().context("no expected system image value"),
     )
 }
 
 pub(crate) fn compare_application_layer_measurement_digests(
     values: &ApplicationLayerData,
     expected: &ApplicationLayerExpectedValues,
+    mut report: Option<&mut LayerReport>,
 ) -> anyhow::Result<()> {
-    compare_measurement_digest(
-        values.binary.as_ref().context("no binary evidence value")?,
-        expected.binary.as_ref().context("no expected binary value")?,
-    )
-    .context("comparing application binary digests")?;
-    compare_measurement_digest(
-        values.config.as_ref().context("no config evidence value")?,
-        expected.configuration.as_ref().context("no expected config value")?,
-    )
-    .context("comparing application config digests")
+    [
+        check_measurement_digest(
+            report.as_deref_mut(),
+            "binary",
+            values.binary.as_ref().context("no binary evidence value"),
+            expected.binary.as_ref().context("no expected binary value"),
+        )
+        .context("comparing application binary digests"),
+        check_measurement_digest(
+            report,
+            "config",
+            values.config.as_ref().context("no config evidence value"),
+            expected.configuration.as_ref().context("no expected config value"),
+        )
+        .context("comparing application config digests"),
+    ]
+    .into_iter()
+    .collect()
 }
 
 pub(crate) fn compare_container_layer_measurement_digests(
     values: &ContainerLayerData,
     expected: &ContainerLayerExpectedValues,
+    mut report: Option<&mut LayerReport>,
 ) -> anyhow::Result<()> {
-    compare_measurement_digest(
-        values.bundle.as_ref().context("no bundle evidence value")?,
-        expected.bundle.as_ref().context("no expected bundle value")?,
-    )
-    .context("comparing container bundle digests")?;
-    compare_measurement_digest(
-        values.config.as_ref().context("no config evidence value")?,
-        expected.config.as_ref().context("no expected config value")?,
-    )
-    .context("comparing container config digests")
+    [
+        check_measurement_digest(
+            report.as_deref_mut(),
+            "bundle",
+            values.bundle.as_ref().context("no bundle evidence value"),
+            expected.bundle.as_ref().context("no expected bundle value"),
+        )
+        .context("comparing container bundle digests"),
+        check_measurement_digest(
+            report,
+            "config",
+            values.config.as_ref().context("no config evidence value"),
+            expected.config.as_ref().context("no expected config value"),
+        )
+        .context("comparing container config digests"),
+    ]
+    .into_iter()
+    .collect()
+}
+
+/// Compares a measurement digest against the expected digests, and records
+/// the comparison as the check `name` in `report` if given, including both
+/// values.
+fn check_measurement_digest(
+    report: Option<&mut LayerReport>,
+    name: &str,
+    measurement: anyhow::Result<&RawDigest>,
+    expected: anyhow::Result<&ExpectedDigests>,
+) -> anyhow::Result<()> {
+    let actual = measurement.as_ref().ok().map(|measurement| format_raw_digest(measurement));
+    let skipped = matches!(
+        expected,
+        Ok(ExpectedDigests { r#type: Some(expected_digests::Type::Skipped(_)) })
+    );
+    let expected_value = expected
+        .as_ref()
+        .ok()
+        .filter(|_| !skipped)
+        .map(|expected| format_expected_digests(expected));
+    let result =
+        measurement.and_then(|measurement| compare_measurement_digest(measurement, expected?));
+    record_comparison(report, name, result, skipped, expected_value, actual)
+}
+
+/// Records the outcome of a comparison as the check `name` in `report` if
+/// given, and passes it on.
+fn record_comparison(
+    report: Option<&mut LayerReport>,
+    name: &str,
+    result: anyhow::Result<()>,
+    skipped: bool,
+    expected: Option<String>,
+    actual: Option<String>,
+) -> anyhow::Result<()> {
+    if let Some(report) = report {
+        let check = match &result {
+            Ok(()) if skipped => report.add(name, CheckStatus::Skipped),
+            Ok(()) => report.add(name, CheckStatus::Passed),
+            Err(err) => {
+                let check = report.add(name, CheckStatus::Failed);
+                check.message = Some(format!("{err:#}"));
+                check
+            }
+        };
+        check.expected = expected;
+        check.actual = actual;
+    }
+    result
 }
 
 /// Verifies the measurement digest value against a reference value and
@@ -362,3 +496,48 @@ fn verify_regex(actual: &str, regex: &str) -> anyhow::Result<()> {
 fn verify_regex(_actual: &str, _regex: &str) -> anyhow::Result<()> {
     Err(anyhow::anyhow!("verification of regex values not supported"))
 }
+
+/// Renders all digests in a raw digest as typed hashes, e.g. `sha2-256:ab12`.
+pub(crate) fn format_raw_digest(digest: &RawDigest) -> String {
+    let hex = raw_to_hex_digest(digest);
+    [
+        ("psha2", hex.psha2),
+        ("sha1", hex.sha1),
+        ("sha2-256", hex.sha2_256),
+        ("sha2-384", hex.sha2_384),
+        ("sha2-512", hex.sha2_512),
+        ("sha3-224", hex.sha3_224),
+        ("sha3-256", hex.sha3_256),
+        ("sha3-384", hex.sha3_384),
+        ("sha3-512", hex.sha3_512),
+    ]
+    .into_iter()
+    .filter(|(_, value)| !value.is_empty())
+    .map(|(name, value)| format!("{name}:{value}"))
+    .collect::<Vec<String>>()
+    .join(" ")
+}
+
+fn format_expected_digests(expected: &ExpectedDigests) -> String {
+    match expected.r#type.as_ref() {
+        Some(expected_digests::Type::Skipped(_)) => "skipped".to_string(),
+        Some(expected_digests::Type::Digests(digests)) => {
+            digests.digests.iter().map(format_raw_digest).collect::<Vec<String>>().join(", ")
+        }
+        None => "empty".to_string(),
+    }
+}
+
+fn format_text_expected_value(expected: &TextExpectedValue) -> String {
+    match expected.r#type.as_ref() {
+        Some(text_expected_value::Type::Skipped(_)) => "skipped".to_string(),
+        Some(text_expected_value::Type::Regex(regex)) => format!("regex:{}", regex.value),
+        Some(text_expected_value::Type::StringLiterals(string_literals)) => {
+            string_literals.value.join(", ")
+        }
+        None => "empty".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests;
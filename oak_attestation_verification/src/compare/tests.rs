@@ -0,0 +1,148 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use oak_proto_rust::oak::{
+    attestation::v1::{
+        binary_reference_value, root_layer_data::Report, tdx_tcb_svn_reference_value,
+        BinaryReferenceValue, Digests, IntelTdxReferenceValues, RootLayerData,
+        RootLayerReferenceValues, TdxTcbSvn, TdxTcbSvnReferenceValue,
+    },
+    RawDigest,
+};
+use oak_tdx_quote::TdxQuoteWrapper;
+use test_util::AttestationData;
+
+use super::compare_root_layer_measurement_digests;
+use crate::{
+    expect::get_root_layer_expected_values, platform::convert_intel_tdx_attestation_quote,
+};
+
+// Pretend the tests run at this time: 2025-10-01, 08:00 UTC.
+const NOW_UTC_MILLIS: i64 = 1_759_305_600_000;
+
+fn get_tdx_root_layer_data() -> RootLayerData {
+    let d = AttestationData::load_tdx_oc();
+    let quote_buffer = d.evidence.root_layer.expect("no root layer").remote_attestation_report;
+    let wrapper = TdxQuoteWrapper::new(quote_buffer.as_slice());
+    let quote = wrapper.parse_quote().expect("invalid quote");
+    RootLayerData { report: Some(Report::Tdx(convert_intel_tdx_attestation_quote(&quote))) }
+}
+
+// Creates reference values which exactly match the given TDX root layer, with
+// an optional stage0 reference value.
+fn get_tdx_reference_values(
+    root_layer_data: &RootLayerData,
+    stage0: Option<BinaryReferenceValue>,
+) -> IntelTdxReferenceValues {
+    let report = match root_layer_data.report.as_ref() {
+        Some(Report::Tdx(report)) => report,
+        _ => panic!("not an Intel TDX report"),
+    };
+    IntelTdxReferenceValues {
+        tee_tcb_svn: Some(TdxTcbSvnReferenceValue {
+            r#type: Some(tdx_tcb_svn_reference_value::Type::Minimum(
+                report.tee_tcb_svn.expect("no TEE TCB SVN"),
+            )),
+        }),
+        allow_debug: report.debug,
+        stage0,
+    }
+}
+
+fn get_mr_td(root_layer_data: &RootLayerData) -> Vec<u8> {
+    match root_layer_data.report.as_ref() {
+        Some(Report::Tdx(report)) => report.mr_td.clone(),
+        _ => panic!("not an Intel TDX report"),
+    }
+}
+
+fn stage0_digests(mr_td: Vec<u8>) -> BinaryReferenceValue {
+    BinaryReferenceValue {
+        r#type: Some(binary_reference_value::Type::Digests(Digests {
+            digests: vec![RawDigest { sha2_384: mr_td, ..Default::default() }],
+        })),
+    }
+}
+
+fn compare_tdx(
+    root_layer_data: &RootLayerData,
+    intel_tdx: IntelTdxReferenceValues,
+) -> anyhow::Result<()> {
+    let reference_values =
+        RootLayerReferenceValues { intel_tdx: Some(intel_tdx), ..Default::default() };
+    let expected_values = get_root_layer_expected_values(NOW_UTC_MILLIS, None, &reference_values)?;
+    compare_root_layer_measurement_digests(root_layer_data, &expected_values)
+}
+
+#[test]
+fn tdx_root_layer_matching_values_succeeds() {
+    let data = get_tdx_root_layer_data();
+    let rvs = get_tdx_reference_values(&data, Some(stage0_digests(get_mr_td(&data))));
+
+    let result = compare_tdx(&data, rvs);
+
+    assert!(result.is_ok(), "{:?}", result);
+}
+
+#[test]
+fn tdx_root_layer_other_stage0_fails() {
+    let data = get_tdx_root_layer_data();
+    let rvs = get_tdx_reference_values(&data, Some(stage0_digests(vec![0u8; 48])));
+
+    let result = compare_tdx(&data, rvs);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn tdx_root_layer_without_stage0_skips_mrtd_check() {
+    let data = get_tdx_root_layer_data();
+    let rvs = get_tdx_reference_values(&data, None);
+
+    let result = compare_tdx(&data, rvs);
+
+    assert!(result.is_ok(), "{:?}", result);
+}
+
+#[test]
+fn tdx_root_layer_higher_minimum_tcb_svn_fails() {
+    let data = get_tdx_root_layer_data();
+    let mut rvs = get_tdx_reference_values(&data, Some(stage0_digests(get_mr_td(&data))));
+    rvs.tee_tcb_svn = Some(TdxTcbSvnReferenceValue {
+        r#type: Some(tdx_tcb_svn_reference_value::Type::Minimum(TdxTcbSvn {
+            svn_0: u32::MAX,
+            ..Default::default()
+        })),
+    });
+
+    let result = compare_tdx(&data, rvs);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn tdx_root_layer_debug_not_allowed_fails() {
+    let mut data = get_tdx_root_layer_data();
+    let mut rvs = get_tdx_reference_values(&data, Some(stage0_digests(get_mr_td(&data))));
+    rvs.allow_debug = false;
+    if let Some(Report::Tdx(report)) = data.report.as_mut() {
+        report.debug = true;
+    }
+
+    let result = compare_tdx(&data, rvs);
+
+    assert!(result.is_err());
+}
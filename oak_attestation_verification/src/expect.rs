@@ -131,6 +131,12 @@ pub(crate) fn get_oak_containers_expected_values(
     endorsements: &OakContainersEndorsements,
     reference_values: &OakContainersReferenceValues,
 ) -> anyhow::Result<OakContainersExpectedValues> {
+    // Expected values only cover a single container, so accepting reference
+    // values for more containers would leave the others unverified.
+    anyhow::ensure!(
+        reference_values.additional_container_layers.is_empty(),
+        "multiple container layers are only supported by the policy-based verifiers"
+    );
     Ok(OakContainersExpectedValues {
         root_layer: Some(
             get_root_layer_expected_values(
@@ -267,15 +273,37 @@ pub(crate) fn get_root_layer_expected_values(
             turin: tcb_version_rv_to_ev(rvs.turin),
             allow_debug: rvs.allow_debug,
             check_vcek_cert_expiry: rvs.check_vcek_cert_expiry,
+            require_crls: rvs.require_crls,
+            reject_stale_crls: rvs.reject_stale_crls,
+            allowed_signing_keys: rvs.allowed_signing_keys.clone(),
         })
     } else {
         None
     };
 
-    let intel_tdx = reference_values
-        .intel_tdx
-        .as_ref()
-        .map(|_| IntelTdxExpectedValues { tee_tcb_svn: None, allow_debug: false });
+    let intel_tdx = if let Some(rvs) = reference_values.intel_tdx.as_ref() {
+        // Reference values without stage0 don't constrain MRTD.
+        let stage0_expected = rvs
+            .stage0
+            .as_ref()
+            .map(|stage0| {
+                get_stage0_expected_values(
+                    now_utc_millis,
+                    endorsements.and_then(|value| value.stage0.as_ref()),
+                    stage0,
+                )
+            })
+            .transpose()
+            .context("getting stage0 values")?;
+
+        Some(IntelTdxExpectedValues {
+            tee_tcb_svn: tdx_tcb_svn_rv_to_ev(rvs.tee_tcb_svn),
+            allow_debug: rvs.allow_debug,
+            stage0_expected,
+        })
+    } else {
+        None
+    };
     let insecure = reference_values.insecure.as_ref().map(|_| InsecureExpectedValues {});
 
     Ok(RootLayerExpectedValues { amd_sev, intel_tdx, insecure })
@@ -296,6 +324,9 @@ pub(crate) fn get_amd_sev_snp_expected_values(
         turin: tcb_version_rv_to_ev(reference_values.turin),
         allow_debug: reference_values.allow_debug,
         check_vcek_cert_expiry: reference_values.check_vcek_cert_expiry,
+        require_crls: reference_values.require_crls,
+        reject_stale_crls: reference_values.reject_stale_crls,
+        allowed_signing_keys: reference_values.allowed_signing_keys.clone(),
     })
 }
 
@@ -305,6 +336,8 @@ pub(crate) fn get_intel_tdx_expected_values(
     Ok(IntelTdxExpectedValues {
         tee_tcb_svn: tdx_tcb_svn_rv_to_ev(reference_values.tee_tcb_svn),
         allow_debug: reference_values.allow_debug,
+        // The stage0 firmware is verified by the firmware policy.
+        stage0_expected: None,
     })
 }
 
@@ -1103,6 +1136,8 @@ fn verify_endorsement_wrapper(
             }),
             signature: Some(Signature { key_id: key.key_id, raw: signature.to_vec() }),
             rekor_log_entry: log_entry.to_vec(),
+            sigstore_bundle: Vec::new(),
+            rfc3161_timestamp: Vec::new(),
         };
 
         let result = verify_endorsement(now_utc_millis, &signed_endorsement, ref_value);
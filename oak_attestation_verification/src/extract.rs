@@ -37,11 +37,15 @@ use oak_proto_rust::oak::{
     RawDigest,
 };
 use oak_sev_snp_attestation_report::AttestationReport;
+use oak_tdx_quote::TdxQuoteWrapper;
 use prost::Message;
 use sha2::Digest;
 use zerocopy::FromBytes;
 
-use crate::{platform::convert_amd_sev_snp_attestation_report, verifier::EventLogType};
+use crate::{
+    platform::{convert_amd_sev_snp_attestation_report, convert_intel_tdx_attestation_quote},
+    verifier::EventLogType,
+};
 
 pub(crate) struct ApplicationKeyValues {
     pub(crate) encryption_public_key: Vec<u8>,
@@ -333,7 +337,15 @@ fn extract_root_values(root_layer: &RootLayerEvidence) -> anyhow::Result<RootLay
             let converted_attestation_report = convert_amd_sev_snp_attestation_report(report)?;
             Ok(RootLayerData { report: Some(Report::SevSnp(converted_attestation_report)) })
         }
-        TeePlatform::IntelTdx => Err(anyhow::anyhow!("not supported")),
+        TeePlatform::IntelTdx => {
+            let wrapper = TdxQuoteWrapper::new(&root_layer.remote_attestation_report);
+            let quote = wrapper
+                .parse_quote()
+                .map_err(|err| anyhow::anyhow!("invalid Intel TDX attestation quote: {}", err))?;
+
+            let converted_attestation_quote = convert_intel_tdx_attestation_quote(&quote);
+            Ok(RootLayerData { report: Some(Report::Tdx(converted_attestation_quote)) })
+        }
         TeePlatform::None => {
             // We use an unsigned, mostly empty AMD SEV-SNP attestation report as a fake
             // when not running in a TEE.
@@ -641,3 +653,6 @@ pub(crate) fn claims_set_from_serialized_cert(slice: &[u8]) -> anyhow::Result<Cl
     ClaimsSet::from_slice(&payload)
         .map_err(|_cose_err| anyhow::anyhow!("could not parse claims set"))
 }
+
+#[cfg(test)]
+mod tests;
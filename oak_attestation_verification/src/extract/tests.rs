@@ -0,0 +1,50 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use oak_proto_rust::oak::attestation::v1::{root_layer_data::Report, TeePlatform};
+use oak_tdx_quote::TdxQuoteWrapper;
+use test_util::AttestationData;
+
+use super::extract_root_values;
+
+#[test]
+fn tdx_root_layer_values_are_extracted() {
+    let d = AttestationData::load_tdx_oc();
+    let root_layer = d.evidence.root_layer.expect("no root layer");
+    assert_eq!(root_layer.platform(), TeePlatform::IntelTdx);
+    let wrapper = TdxQuoteWrapper::new(root_layer.remote_attestation_report.as_slice());
+    let quote = wrapper.parse_quote().expect("invalid quote");
+
+    let root_layer_data = extract_root_values(&root_layer).expect("failed to extract root layer");
+
+    match root_layer_data.report {
+        Some(Report::Tdx(report)) => {
+            assert_eq!(report.mr_td, quote.body.mr_td.to_vec());
+            assert_eq!(report.report_data, quote.body.report_data.to_vec());
+            assert!(report.tee_tcb_svn.is_some());
+        }
+        _ => panic!("expected an Intel TDX report"),
+    }
+}
+
+#[test]
+fn tdx_root_layer_with_invalid_quote_fails() {
+    let d = AttestationData::load_tdx_oc();
+    let mut root_layer = d.evidence.root_layer.expect("no root layer");
+    root_layer.remote_attestation_report.truncate(100);
+
+    assert!(extract_root_values(&root_layer).is_err());
+}
@@ -0,0 +1,265 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Generates reference values from a known-good pair of evidence and
+//! endorsements, such that policies can be bootstrapped and reviewed instead
+//! of being written by hand.
+//!
+//! The generated reference values are minimal: measurements are checked via
+//! their endorsements where an endorsement is present and the endorser keys
+//! are known, and pinned to the digests in the evidence otherwise. The TCB
+//! versions in the attestation report become the minimum accepted versions.
+
+use alloc::vec::Vec;
+
+use anyhow::Context;
+use oak_dice::evidence::TeePlatform;
+use oak_proto_rust::oak::{
+    attestation::v1::{
+        binary_reference_value, kernel_binary_reference_value, reference_values,
+        text_reference_value, ApplicationEndorsement, BinaryReferenceValue, ContainerEndorsement,
+        EndorsementReferenceValue, Endorsements, Event, Evidence, FirmwareEndorsement,
+        InsecureReferenceValues, KernelBinaryReferenceValue, KernelEndorsement,
+        OakContainersReferenceValues, OakRestrictedKernelReferenceValues, ReferenceValues,
+        RootLayerReferenceValues, SignedEndorsement, SystemEndorsement, TextReferenceValue,
+    },
+    Variant,
+};
+use prost::Message;
+use verify_endorsement::{create_endorsement_reference_value, create_verifying_key_from_raw};
+
+use crate::{
+    AmdSevSnpPolicy, ApplicationPolicy, ContainerPolicy, IntelTdxPolicy, KernelPolicy, SystemPolicy,
+};
+
+const SYSTEM_LAYER_TYPE_URL: &str = "type.googleapis.com/oak.attestation.v1.SystemLayerData";
+const APPLICATION_LAYER_TYPE_URL: &str =
+    "type.googleapis.com/oak.attestation.v1.ApplicationLayerData";
+
+/// Public keys needed to verify the endorsements which accompany the
+/// evidence. If they are not provided, all measurements are pinned by digest.
+#[derive(Clone, Debug, Default)]
+pub struct EndorsementKeys {
+    /// Raw public key of the endorser.
+    pub endorser_public_key: Vec<u8>,
+
+    /// Raw public key of Rekor. If empty, the generated reference values skip
+    /// the verification of log entries.
+    pub rekor_public_key: Vec<u8>,
+}
+
+/// Returns reference values which accept the given evidence and
+/// endorsements, and as little else as possible.
+///
+/// The evidence is expected to be known-good, it is not verified here.
+pub fn generate_reference_values(
+    evidence: &Evidence,
+    endorsements: &Endorsements,
+    keys: Option<&EndorsementKeys>,
+) -> anyhow::Result<ReferenceValues> {
+    let generator = Generator { keys };
+    let root_layer = generator.root_layer_reference_values(evidence, endorsements)?;
+
+    let events = &evidence.event_log.as_ref().context("no event log in evidence")?.encoded_events;
+    let empty_endorsement = Variant::default();
+    let event_endorsement =
+        |index: usize| endorsements.events.get(index).unwrap_or(&empty_endorsement);
+
+    // The type of the second event identifies the stack.
+    let second_event = events.get(1).context("event log has less than two events")?;
+    let second_type_url = Event::decode(second_event.as_slice())
+        .map_err(|err| anyhow::anyhow!("failed to decode event: {err}"))?
+        .event
+        .context("no event found in the `event` field")?
+        .type_url;
+
+    let kernel_layer = {
+        let mut rv = KernelPolicy::evidence_to_reference_values(&events[0])?;
+        let endorsement: Option<KernelEndorsement> =
+            event_endorsement(0).try_into().map_err(anyhow::Error::msg)?;
+        if let Some(endorsement) = endorsement {
+            if let Some(ref_value) = generator.endorsement(endorsement.kernel.as_ref()) {
+                rv.kernel = Some(KernelBinaryReferenceValue {
+                    r#type: Some(kernel_binary_reference_value::Type::Endorsement(ref_value)),
+                });
+            }
+            generator
+                .replace_text(&mut rv.kernel_cmd_line_text, endorsement.kernel_cmd_line.as_ref());
+            generator.replace(&mut rv.init_ram_fs, endorsement.init_ram_fs.as_ref());
+            generator.replace(&mut rv.memory_map, endorsement.memory_map.as_ref());
+            generator.replace(&mut rv.acpi, endorsement.acpi.as_ref());
+        }
+        rv
+    };
+
+    let r#type = match second_type_url.as_str() {
+        SYSTEM_LAYER_TYPE_URL => {
+            anyhow::ensure!(
+                events.len() >= 3,
+                "expected at least 3 events, found {}",
+                events.len()
+            );
+            let mut system_layer = SystemPolicy::evidence_to_reference_values(&events[1])?;
+            let endorsement: Option<SystemEndorsement> =
+                event_endorsement(1).try_into().map_err(anyhow::Error::msg)?;
+            if let Some(endorsement) = endorsement {
+                generator
+                    .replace(&mut system_layer.system_image, endorsement.system_image.as_ref());
+            }
+            // Every event after the system layer belongs to a container.
+            let mut container_layers = (2..events.len())
+                .map(|index| {
+                    let mut container_layer =
+                        ContainerPolicy::evidence_to_reference_values(&events[index])?;
+                    let endorsement: Option<ContainerEndorsement> =
+                        event_endorsement(index).try_into().map_err(anyhow::Error::msg)?;
+                    if let Some(endorsement) = endorsement {
+                        generator.replace(&mut container_layer.binary, endorsement.binary.as_ref());
+                        generator.replace(
+                            &mut container_layer.configuration,
+                            endorsement.configuration.as_ref(),
+                        );
+                    }
+                    Ok(container_layer)
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let container_layer = container_layers.pop();
+            reference_values::Type::OakContainers(OakContainersReferenceValues {
+                root_layer: Some(root_layer),
+                kernel_layer: Some(kernel_layer),
+                system_layer: Some(system_layer),
+                container_layer,
+                additional_container_layers: container_layers,
+            })
+        }
+        APPLICATION_LAYER_TYPE_URL => {
+            anyhow::ensure!(events.len() == 2, "expected 2 events, found {}", events.len());
+            let mut application_layer =
+                ApplicationPolicy::evidence_to_reference_values(&events[1])?;
+            let endorsement: Option<ApplicationEndorsement> =
+                event_endorsement(1).try_into().map_err(anyhow::Error::msg)?;
+            if let Some(endorsement) = endorsement {
+                generator.replace(&mut application_layer.binary, endorsement.binary.as_ref());
+                generator.replace(
+                    &mut application_layer.configuration,
+                    endorsement.configuration.as_ref(),
+                );
+            }
+            reference_values::Type::OakRestrictedKernel(OakRestrictedKernelReferenceValues {
+                root_layer: Some(root_layer),
+                kernel_layer: Some(kernel_layer),
+                application_layer: Some(application_layer),
+            })
+        }
+        type_url => anyhow::bail!("unsupported event log with second event of type {type_url}"),
+    };
+    Ok(ReferenceValues { r#type: Some(r#type) })
+}
+
+struct Generator<'a> {
+    keys: Option<&'a EndorsementKeys>,
+}
+
+impl Generator<'_> {
+    fn root_layer_reference_values(
+        &self,
+        evidence: &Evidence,
+        endorsements: &Endorsements,
+    ) -> anyhow::Result<RootLayerReferenceValues> {
+        let root_layer = evidence.root_layer.as_ref().context("no root layer in evidence")?;
+        let firmware_endorsement: Option<FirmwareEndorsement> = match endorsements.initial.as_ref()
+        {
+            Some(variant) => variant.try_into().map_err(anyhow::Error::msg)?,
+            None => None,
+        };
+        let firmware_endorsement =
+            firmware_endorsement.as_ref().and_then(|endorsement| endorsement.firmware.as_ref());
+
+        if root_layer.platform == TeePlatform::AmdSevSnp as i32 {
+            let (mut amd_sev, mut firmware) =
+                AmdSevSnpPolicy::evidence_to_reference_values(root_layer)?;
+            self.replace_binary(&mut firmware, firmware_endorsement);
+            amd_sev.stage0 = Some(firmware);
+            Ok(RootLayerReferenceValues { amd_sev: Some(amd_sev), ..Default::default() })
+        } else if root_layer.platform == TeePlatform::IntelTdx as i32 {
+            let (mut intel_tdx, mut firmware) =
+                IntelTdxPolicy::evidence_to_reference_values(root_layer)?;
+            self.replace_binary(&mut firmware, firmware_endorsement);
+            intel_tdx.stage0 = Some(firmware);
+            Ok(RootLayerReferenceValues { intel_tdx: Some(intel_tdx), ..Default::default() })
+        } else {
+            Ok(RootLayerReferenceValues {
+                insecure: Some(InsecureReferenceValues {}),
+                ..Default::default()
+            })
+        }
+    }
+
+    /// Returns an endorsement reference value for the signed endorsement, if
+    /// there is one and the endorser keys are known.
+    fn endorsement(
+        &self,
+        signed_endorsement: Option<&SignedEndorsement>,
+    ) -> Option<EndorsementReferenceValue> {
+        let keys = self.keys?;
+        let signed_endorsement = signed_endorsement?;
+        let signature = signed_endorsement.signature.as_ref()?;
+        let endorser_key =
+            create_verifying_key_from_raw(&keys.endorser_public_key, signature.key_id);
+        let rekor_key = (!keys.rekor_public_key.is_empty())
+            .then(|| create_verifying_key_from_raw(&keys.rekor_public_key, 1));
+        Some(create_endorsement_reference_value(endorser_key, rekor_key))
+    }
+
+    /// Replaces the digest-based reference value by an endorsement-based one
+    /// where possible.
+    fn replace(
+        &self,
+        reference_value: &mut Option<BinaryReferenceValue>,
+        signed_endorsement: Option<&SignedEndorsement>,
+    ) {
+        if let Some(reference_value) = reference_value.as_mut() {
+            self.replace_binary(reference_value, signed_endorsement);
+        }
+    }
+
+    fn replace_text(
+        &self,
+        reference_value: &mut Option<TextReferenceValue>,
+        signed_endorsement: Option<&SignedEndorsement>,
+    ) {
+        // The endorsed text is a regex, which can only be checked if regex
+        // support is compiled in. Otherwise the text stays pinned.
+        if !cfg!(feature = "regex") {
+            return;
+        }
+        if let Some(ref_value) = self.endorsement(signed_endorsement) {
+            *reference_value = Some(TextReferenceValue {
+                r#type: Some(text_reference_value::Type::Endorsement(ref_value)),
+            });
+        }
+    }
+
+    fn replace_binary(
+        &self,
+        reference_value: &mut BinaryReferenceValue,
+        signed_endorsement: Option<&SignedEndorsement>,
+    ) {
+        if let Some(ref_value) = self.endorsement(signed_endorsement) {
+            reference_value.r#type = Some(binary_reference_value::Type::Endorsement(ref_value));
+        }
+    }
+}
@@ -17,6 +17,7 @@
 //! Utilities for validating Intel provisioning certificates and attestation
 //! quotes.
 
+use alloc::vec::Vec;
 use core::convert::Into;
 
 use anyhow::{anyhow, Context};
@@ -33,7 +34,10 @@ use x509_cert::{
     Certificate,
 };
 
-use crate::x509::{check_certificate_validity, verify_cert_signature};
+use crate::{
+    cache::CertificateChainCache,
+    x509::{check_certificate_validity, get_certificate_not_after, verify_cert_signature},
+};
 
 const PCK_ROOT: &str = include_str!("../data/Intel_SGX_Provisioning_Certification_RootCA.pem");
 /// The size in bytes of a SHA2-384 digest.
@@ -42,10 +46,19 @@ const SHA2_384_DIGEST_SIZE: usize = 48;
 /// Verifies that the TDX Attestation Quote is correctly signed and that the
 /// entire chain of trust is valid all the way to the Provisioning Certification
 /// Key (PCK) root certificate.
-#[allow(unused)]
 pub fn verify_intel_tdx_quote_validity(
     verification_time: Instant,
     quote: &TdxQuoteWrapper,
+) -> anyhow::Result<()> {
+    verify_intel_tdx_quote_validity_with_cache(verification_time, quote, None)
+}
+
+/// Like [`verify_intel_tdx_quote_validity`], but skips the verification of a
+/// PCK certificate chain which is found in `cert_chain_cache`.
+pub(crate) fn verify_intel_tdx_quote_validity_with_cache(
+    verification_time: Instant,
+    quote: &TdxQuoteWrapper,
+    cert_chain_cache: Option<&CertificateChainCache>,
 ) -> anyhow::Result<()> {
     let signature_data = quote.parse_signature_data().context("parsing signature data")?;
 
@@ -57,11 +70,23 @@ pub fn verify_intel_tdx_quote_validity(
     }?;
 
     // Verify that the PCK certificate chain is valid.
-    let pck_leaf = verify_quote_cert_chain_and_extract_leaf(
-        verification_time,
-        &report_certification.certification_data,
-    )
-    .context("verifying quote cert chain")?;
+    let pck_leaf = match (cert_chain_cache, &report_certification.certification_data) {
+        (Some(cache), &QeCertificationData::PckCertChain(chain)) => {
+            let certificates = parse_quote_cert_chain(&report_certification.certification_data)?;
+            let not_after = certificates.iter().map(get_certificate_not_after).min();
+            cache
+                .verify(verification_time, &[chain], not_after, || {
+                    verify_quote_cert_chain(verification_time, &certificates)
+                })
+                .context("verifying quote cert chain")?;
+            certificates.into_iter().next().ok_or_else(|| anyhow!("certificate chain is empty"))?
+        }
+        _ => verify_quote_cert_chain_and_extract_leaf(
+            verification_time,
+            &report_certification.certification_data,
+        )
+        .context("verifying quote cert chain")?,
+    };
 
     // Verify that the Quoting Enclave report is signed using the PCK leaf
     // certificate.
@@ -110,6 +135,16 @@ pub fn verify_quote_cert_chain_and_extract_leaf(
     verification_time: Instant,
     certification_data: &QeCertificationData,
 ) -> anyhow::Result<Certificate> {
+    let certificates = parse_quote_cert_chain(certification_data)?;
+    verify_quote_cert_chain(verification_time, &certificates)?;
+    certificates.into_iter().next().ok_or_else(|| anyhow!("certificate chain is empty"))
+}
+
+// Parses the PCK certificate chain, with the root certificate replaced by the
+// published one.
+fn parse_quote_cert_chain(
+    certification_data: &QeCertificationData,
+) -> anyhow::Result<Vec<Certificate>> {
     let mut certificates = if let &QeCertificationData::PckCertChain(chain) = certification_data {
         Ok(Certificate::load_pem_chain(chain)
             .map_err(anyhow::Error::msg)
@@ -125,11 +160,17 @@ pub fn verify_quote_cert_chain_and_extract_leaf(
         .map_err(anyhow::Error::msg)
         .context("parsing known root certificate")?;
     certificates.push(root);
+    Ok(certificates)
+}
+
+fn verify_quote_cert_chain(
+    verification_time: Instant,
+    certificates: &[Certificate],
+) -> anyhow::Result<()> {
     let mut chain = certificates.iter();
     let mut signee = chain.next().ok_or_else(|| anyhow!("certificate chain is empty"))?;
-    let leaf = signee.clone();
     // Make sure the leaf certificate is valid.
-    check_certificate_validity(verification_time, &leaf)?;
+    check_certificate_validity(verification_time, signee)?;
     // Each certificate must be signed by the next one in the chain and the signer
     // must be valid.
     for signer in chain {
@@ -137,7 +178,28 @@ pub fn verify_quote_cert_chain_and_extract_leaf(
         verify_cert_signature(signer, signee).context("verifying cert signature")?;
         signee = signer;
     }
-    Ok(leaf)
+    Ok(())
+}
+
+/// Returns the earliest time at which a certificate in the PCK certificate
+/// chain of the quote expires. The chain is not verified.
+pub fn get_quote_cert_chain_not_after(quote: &TdxQuoteWrapper) -> anyhow::Result<Instant> {
+    let signature_data = quote.parse_signature_data().context("parsing signature data")?;
+    let QeCertificationData::QeReportCertificationData(report_certification) =
+        signature_data.certification_data
+    else {
+        anyhow::bail!("signature data contains the wrong type of certification data");
+    };
+    let QeCertificationData::PckCertChain(chain) = report_certification.certification_data else {
+        anyhow::bail!("certification data is not a PCK certificate chain");
+    };
+    Certificate::load_pem_chain(chain)
+        .map_err(anyhow::Error::msg)
+        .context("parsing certificate chain")?
+        .iter()
+        .map(get_certificate_not_after)
+        .min()
+        .ok_or_else(|| anyhow!("certificate chain is empty"))
 }
 
 /// Software implementation of the RTMR logic that can be used to replay a
@@ -18,6 +18,8 @@ extern crate std;
 
 use core::assert_eq;
 
+use digest_util::hash_sha2_256;
+use oak_proto_rust::oak::attestation::v1::RootLayerEvidence;
 use oak_tdx_quote::{QeCertificationData, TdxQuoteWrapper};
 use oak_time::{Duration, Instant};
 use sha2::{Digest, Sha384};
@@ -28,13 +30,47 @@ use super::{
     verify_intel_tdx_quote_validity, verify_quote_cert_chain_and_extract_leaf, RtmrEmulator,
     PCK_ROOT,
 };
-use crate::x509::verify_cert_signature;
+use crate::{
+    amd::CrlPolicy,
+    platform::{verify_dice_root_eca_key_tdx, verify_root_attestation_signature},
+    x509::verify_cert_signature,
+};
+
+// Offset of the report data within a TDX quote: the 48-byte quote header
+// followed by the first 520 bytes of the TD quote body.
+const REPORT_DATA_OFFSET: usize = 568;
 
 fn get_evidence_quote_bytes() -> Vec<u8> {
     let d = AttestationData::load_tdx_oc();
     d.evidence.root_layer.expect("no root layer").remote_attestation_report
 }
 
+fn get_root_layer_evidence() -> RootLayerEvidence {
+    let d = AttestationData::load_tdx_oc();
+    d.evidence.root_layer.expect("no root layer")
+}
+
+// Returns a copy of the quote whose report data binds the given ECA public key.
+// This invalidates the quote signature.
+fn bind_eca_public_key(quote: &[u8], eca_public_key: &[u8]) -> Vec<u8> {
+    let mut quote = quote.to_vec();
+    let digest = hash_sha2_256(eca_public_key);
+    quote[REPORT_DATA_OFFSET..REPORT_DATA_OFFSET + digest.len()].copy_from_slice(&digest);
+    quote
+}
+
+fn verify_root_layer(root_layer: &RootLayerEvidence) -> anyhow::Result<()> {
+    verify_root_attestation_signature(
+        get_valid_time(),
+        true,
+        CrlPolicy::default(),
+        root_layer,
+        &[],
+        &[],
+        &[],
+    )
+}
+
 fn get_leaf_cert_not_before_and_not_after() -> (Instant, Instant) {
     let quote_buffer = get_evidence_quote_bytes();
     let wrapper = TdxQuoteWrapper::new(quote_buffer.as_slice());
@@ -309,3 +345,56 @@ fn tampered_event_log_doesnt_match_rtmr_evidence() {
         "RTMR2 in quote matches the value calculated from the tampered event log"
     );
 }
+
+#[test]
+fn tdx_root_layer_with_unbound_eca_key_fails() {
+    let root_layer = get_root_layer_evidence();
+
+    // The quote is genuine, but its report data does not bind a DICE chain.
+    let result = verify_root_layer(&root_layer);
+
+    assert!(result.is_err());
+    assert!(format!("{:?}", result.unwrap_err()).contains("not bound"));
+}
+
+#[test]
+fn tdx_root_layer_with_tampered_quote_fails() {
+    let mut root_layer = get_root_layer_evidence();
+    root_layer.eca_public_key = b"some ECA public key".to_vec();
+    root_layer.remote_attestation_report =
+        bind_eca_public_key(&root_layer.remote_attestation_report, &root_layer.eca_public_key);
+
+    let result = verify_root_layer(&root_layer);
+
+    assert!(result.is_err());
+    assert!(format!("{:?}", result.unwrap_err()).contains("verifying Intel TDX quote validity"));
+}
+
+#[test]
+fn tdx_root_layer_outside_validity_fails() {
+    let root_layer = get_root_layer_evidence();
+
+    let result = verify_root_attestation_signature(
+        get_late_time(),
+        true,
+        CrlPolicy::default(),
+        &root_layer,
+        &[],
+        &[],
+        &[],
+    );
+
+    assert!(result.is_err());
+    assert!(format!("{:?}", result.unwrap_err()).contains("verifying Intel TDX quote validity"));
+}
+
+#[test]
+fn tdx_eca_key_bound_to_report_data_succeeds() {
+    let eca_public_key = b"some ECA public key";
+    let quote_buffer = bind_eca_public_key(&get_evidence_quote_bytes(), eca_public_key);
+    let wrapper = TdxQuoteWrapper::new(quote_buffer.as_slice());
+    let quote = wrapper.parse_quote().expect("invalid quote");
+
+    assert!(verify_dice_root_eca_key_tdx(&quote, eca_public_key).is_ok());
+    assert!(verify_dice_root_eca_key_tdx(&quote, b"other ECA public key").is_err());
+}
@@ -21,10 +21,12 @@
 extern crate alloc;
 
 mod amd;
+mod cache;
 mod compare;
 mod endorsement;
 mod expect;
 mod extract;
+mod generate;
 mod intel;
 mod platform;
 mod policy;
@@ -39,8 +41,12 @@ mod x509;
 #[cfg(test)]
 mod test_util;
 
+pub use cache::{
+    CachingAttestationVerifier, CachingPolicy, CertificateChainCache, VerificationCache,
+};
 pub use expect::get_expected_values;
 pub use extract::extract_evidence;
+pub use generate::{generate_reference_values, EndorsementKeys};
 pub use key_util::convert_pem_to_raw;
 use oak_proto_rust::oak::attestation::v1::{
     EndorsementDetails, EndorsementReferenceValue, SignedEndorsement,
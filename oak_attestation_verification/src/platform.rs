@@ -16,32 +16,40 @@
 
 //! Provides verification based on evidence, endorsements and reference values.
 
-use alloc::format;
+use alloc::{format, vec, vec::Vec};
 
 use anyhow::Context;
 use digest_util::hash_sha2_256;
 use oak_proto_rust::oak::{
     attestation::v1::{
         tcb_version_expected_value, tdx_tcb_svn_expected_value, AmdAttestationReport,
-        AmdSevExpectedValues, InsecureExpectedValues, IntelTdxAttestationReport,
+        AmdSevExpectedValues, AmdSigningKey, InsecureExpectedValues, IntelTdxAttestationReport,
         IntelTdxExpectedValues, RootLayerEvidence, TcbVersion, TdxTcbSvn, TeePlatform,
     },
     RawDigest,
 };
-use oak_sev_snp_attestation_report::{AmdProduct, AttestationReport};
-use oak_tdx_quote::{ParsedTdxQuote, TdAttributes};
+use oak_sev_snp_attestation_report::{AmdProduct, AttestationReport, SigningKey};
+use oak_tdx_quote::{ParsedTdxQuote, TdAttributes, TdxQuoteWrapper};
 use oak_time::Instant;
 use x509_cert::{
-    der::{Decode, DecodePem},
+    der::{Decode, DecodePem, Encode},
     Certificate,
 };
 use zerocopy::FromBytes;
 
 use crate::{
-    amd::{get_product, verify_attestation_report_signature},
+    amd::{
+        certificate_names, check_revocation, get_product, verify_asvk_cert_fields,
+        verify_attestation_report_signature, CrlPolicy,
+    },
+    cache::{amd_cert_chain_not_after, CertificateChainCache},
+    intel::verify_intel_tdx_quote_validity_with_cache,
     x509::{check_certificate_validity, verify_cert_signature},
 };
 
+const ARK_MILAN_CERT_PEM: &str = include_str!("../data/ark_milan.pem");
+const ARK_GENOA_CERT_PEM: &str = include_str!("../data/ark_genoa.pem");
+const ARK_TURIN_CERT_PEM: &str = include_str!("../data/ark_turin.pem");
 const ASK_MILAN_CERT_PEM: &str = include_str!("../data/ask_milan.pem");
 const ASK_GENOA_CERT_PEM: &str = include_str!("../data/ask_genoa.pem");
 const ASK_TURIN_CERT_PEM: &str = include_str!("../data/ask_turin.pem");
@@ -52,24 +60,78 @@ pub fn verify_dice_root_eca_key(
     attestation_report: &AttestationReport,
     eca_public_key: &[u8],
 ) -> anyhow::Result<()> {
-    let expected = &hash_sha2_256(eca_public_key)[..];
-    let actual = attestation_report.data.report_data;
     anyhow::ensure!(
-        // The report data contains 64 bytes by default, but we only use the
-        // first 32 bytes at the moment.
-        expected.len() < actual.len() && expected == &actual[..expected.len()],
+        is_eca_key_bound(&attestation_report.data.report_data, eca_public_key),
         "the root ECA public key is not bound to the AMD SEV-SNP attestation report"
     );
     Ok(())
 }
 
+/// Check that the root ECA public key for the DICE chain is bound to the
+/// report data of the Intel TDX attestation quote.
+pub fn verify_dice_root_eca_key_tdx(
+    quote: &ParsedTdxQuote,
+    eca_public_key: &[u8],
+) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        is_eca_key_bound(quote.body.report_data, eca_public_key),
+        "the root ECA public key is not bound to the Intel TDX attestation quote"
+    );
+    Ok(())
+}
+
+fn is_eca_key_bound(report_data: &[u8], eca_public_key: &[u8]) -> bool {
+    let expected = &hash_sha2_256(eca_public_key)[..];
+    // The report data contains 64 bytes by default, but we only use the first
+    // 32 bytes at the moment.
+    expected.len() < report_data.len() && expected == &report_data[..expected.len()]
+}
+
 /// Verifies the signature chain for the attestation report included in the
 /// root.
+///
+/// On AMD SEV-SNP, `tee_certificate` is the VCEK or the VLEK certificate,
+/// depending on the key that signed the attestation report. A VLEK is endorsed
+/// by the DER-encoded ASVK certificate in `asvk_certificate`. The certificates
+/// of the chain are also checked against the DER-encoded certificate
+/// revocation lists in `crls`, as set by `crl_policy`.
+///
+/// On Intel TDX, the quote carries its own PCK certificate chain, so the
+/// certificate arguments are ignored.
 pub fn verify_root_attestation_signature(
     current_time: Instant,
     check_cert_expiry: bool,
+    crl_policy: CrlPolicy,
     root_layer: &RootLayerEvidence,
     tee_certificate: &[u8],
+    asvk_certificate: &[u8],
+    crls: &[Vec<u8>],
+) -> anyhow::Result<()> {
+    verify_root_attestation_signature_with_cache(
+        current_time,
+        check_cert_expiry,
+        crl_policy,
+        root_layer,
+        tee_certificate,
+        asvk_certificate,
+        crls,
+        None,
+    )
+}
+
+/// Like [`verify_root_attestation_signature`], but skips the verification of
+/// a platform certificate chain which is found in `cert_chain_cache`. The
+/// signature over the attestation report or quote is always verified.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn verify_root_attestation_signature_with_cache(
+    current_time: Instant,
+    check_cert_expiry: bool,
+    crl_policy: CrlPolicy,
+    root_layer: &RootLayerEvidence,
+    tee_certificate: &[u8],
+    asvk_certificate: &[u8],
+    crls: &[Vec<u8>],
+    cert_chain_cache: Option<&CertificateChainCache>,
 ) -> anyhow::Result<()> {
     match root_layer.platform() {
         TeePlatform::Unspecified => anyhow::bail!("unspecified TEE platform"),
@@ -79,21 +141,36 @@ pub fn verify_root_attestation_signature(
             )
             .map_err(|err| anyhow::anyhow!("invalid AMD SEV-SNP attestation report: {}", err))?;
 
-            let vcek_cert = Certificate::from_der(tee_certificate)
-                .map_err(|err| anyhow::anyhow!("couldn't parse VCEK certificate: {:?}", err))?;
+            let tee_cert = Certificate::from_der(tee_certificate)
+                .map_err(|err| anyhow::anyhow!("couldn't parse TEE certificate: {:?}", err))?;
 
             // Ensure the Attestation report is properly signed by the platform and the
             // corresponding certificate is signed by AMD.
             verify_amd_sev_snp_attestation_report_validity(
                 current_time,
                 check_cert_expiry,
+                crl_policy,
                 attestation_report,
-                &vcek_cert,
+                &tee_cert,
+                asvk_certificate,
+                crls,
+                cert_chain_cache,
             )
             .context("verifying AMD SEV-SNP attestation report validity")?;
             verify_dice_root_eca_key(attestation_report, &root_layer.eca_public_key)
         }
-        TeePlatform::IntelTdx => anyhow::bail!("not supported"),
+        TeePlatform::IntelTdx => {
+            let wrapper = TdxQuoteWrapper::new(&root_layer.remote_attestation_report);
+
+            // Ensure the quote is properly signed by the Quoting Enclave and
+            // that the PCK certificate chain is signed by Intel.
+            verify_intel_tdx_quote_validity_with_cache(current_time, &wrapper, cert_chain_cache)
+                .context("verifying Intel TDX quote validity")?;
+            let quote = wrapper
+                .parse_quote()
+                .map_err(|err| anyhow::anyhow!("invalid Intel TDX attestation quote: {}", err))?;
+            verify_dice_root_eca_key_tdx(&quote, &root_layer.eca_public_key)
+        }
 
         // For non-AMD-SEV-SNP and non-Intel-TDX we just verify that the
         // attestation report contains the expected public key.
@@ -107,38 +184,132 @@ pub fn verify_root_attestation_signature(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn verify_amd_sev_snp_attestation_report_validity(
     current_time: Instant,
     check_cert_expiry: bool,
+    crl_policy: CrlPolicy,
     attestation_report: &AttestationReport,
-    vcek_cert: &Certificate,
+    tee_cert: &Certificate,
+    asvk_certificate: &[u8],
+    crls: &[Vec<u8>],
+    cert_chain_cache: Option<&CertificateChainCache>,
 ) -> anyhow::Result<()> {
-    let product = get_product(vcek_cert)?;
-    let ask_cert_pem = match product {
-        AmdProduct::Unsupported => anyhow::bail!("unsupported AMD product"),
-        AmdProduct::Milan => ASK_MILAN_CERT_PEM,
-        AmdProduct::Genoa => ASK_GENOA_CERT_PEM,
-        AmdProduct::Turin => ASK_TURIN_CERT_PEM,
+    let signing_key = match attestation_report.data.get_signing_key() {
+        Some(SigningKey::VCEK) => SigningKey::VCEK,
+        Some(SigningKey::VLEK) => SigningKey::VLEK,
+        _ => anyhow::bail!("attestation report is not signed by a VCEK or a VLEK"),
     };
-    let ask = Certificate::from_pem(ask_cert_pem)
-        .map_err(|_err| anyhow::anyhow!("couldn't parse ASK certificate for {:?}", product))?;
 
-    if check_cert_expiry {
-        check_certificate_validity(current_time, vcek_cert)
-            .context("checking VCEK cert validity")?;
+    let verify_chain = || {
+        verify_amd_sev_snp_cert_chain(
+            current_time,
+            check_cert_expiry,
+            crl_policy,
+            signing_key,
+            tee_cert,
+            asvk_certificate,
+            crls,
+        )
+    };
+    match cert_chain_cache {
+        Some(cache) => {
+            let tee_certificate = tee_cert
+                .to_der()
+                .map_err(|err| anyhow::anyhow!("couldn't encode TEE certificate: {:?}", err))?;
+            // The checks belong to the key, so that a chain which was verified
+            // with laxer checks is not reused.
+            let checks = [
+                signing_key == SigningKey::VLEK,
+                check_cert_expiry,
+                crl_policy.require_crls,
+                crl_policy.reject_stale_crls,
+            ]
+            .map(u8::from);
+            let mut parts = vec![&checks[..], &tee_certificate, asvk_certificate];
+            parts.extend(crls.iter().map(Vec::as_slice));
+            let not_after = amd_cert_chain_not_after(&tee_certificate, asvk_certificate, crls);
+            cache.verify(current_time, &parts, not_after, verify_chain)?;
+        }
+        None => verify_chain()?,
     }
 
-    // We demand that the product-specific ASK signs the VCEK.
-    verify_cert_signature(&ask, vcek_cert).context("verifying VCEK cert signature")?;
-
     // Validate attestation report signature format.
     attestation_report.validate().map_err(|msg| anyhow::anyhow!(msg))?;
 
-    // Ensure that the attestation report is signed by the VCEK public key.
-    verify_attestation_report_signature(vcek_cert, attestation_report)
+    // Ensure that the attestation report is signed by the VCEK or VLEK public
+    // key.
+    verify_attestation_report_signature(tee_cert, attestation_report)
         .context("verifying attestation report signature")
 }
 
+// Verifies that the VCEK (or VLEK) certificate is endorsed by AMD and not
+// revoked.
+fn verify_amd_sev_snp_cert_chain(
+    current_time: Instant,
+    check_cert_expiry: bool,
+    crl_policy: CrlPolicy,
+    signing_key: SigningKey,
+    tee_cert: &Certificate,
+    asvk_certificate: &[u8],
+    crls: &[Vec<u8>],
+) -> anyhow::Result<()> {
+    let (intermediate_name, leaf_name) = certificate_names(signing_key);
+
+    let product = get_product(tee_cert)?;
+    let (ark_cert_pem, ask_cert_pem) = match product {
+        AmdProduct::Unsupported => anyhow::bail!("unsupported AMD product"),
+        AmdProduct::Milan => (ARK_MILAN_CERT_PEM, ASK_MILAN_CERT_PEM),
+        AmdProduct::Genoa => (ARK_GENOA_CERT_PEM, ASK_GENOA_CERT_PEM),
+        AmdProduct::Turin => (ARK_TURIN_CERT_PEM, ASK_TURIN_CERT_PEM),
+    };
+    let ark = Certificate::from_pem(ark_cert_pem)
+        .map_err(|_err| anyhow::anyhow!("couldn't parse ARK certificate for {:?}", product))?;
+    let intermediate = match signing_key {
+        SigningKey::VLEK => {
+            anyhow::ensure!(
+                !asvk_certificate.is_empty(),
+                "no ASVK certificate supplied for VLEK-signed attestation report"
+            );
+            let asvk = Certificate::from_der(asvk_certificate)
+                .map_err(|err| anyhow::anyhow!("couldn't parse ASVK certificate: {:?}", err))?;
+            // Unlike the bundled ASK, the ASVK comes with the endorsements, so
+            // we demand that the product-specific ARK signs it.
+            verify_cert_signature(&ark, &asvk).context("verifying ASVK cert signature")?;
+            verify_asvk_cert_fields(&asvk)?;
+            asvk
+        }
+        _ => Certificate::from_pem(ask_cert_pem)
+            .map_err(|_err| anyhow::anyhow!("couldn't parse ASK certificate for {:?}", product))?,
+    };
+
+    if check_cert_expiry {
+        check_certificate_validity(current_time, tee_cert)
+            .with_context(|| format!("checking {} cert validity", leaf_name))?;
+    }
+
+    // We demand that the product-specific ASK (or the ASVK) signs the VCEK
+    // (or the VLEK).
+    verify_cert_signature(&intermediate, tee_cert)
+        .with_context(|| format!("verifying {} cert signature", leaf_name))?;
+
+    if !crls.is_empty() || crl_policy.require_crls {
+        check_revocation(
+            current_time,
+            signing_key,
+            &ark,
+            &intermediate,
+            tee_cert,
+            crls,
+            crl_policy,
+        )
+        .with_context(|| {
+            format!("checking {} and {} cert revocation", intermediate_name, leaf_name)
+        })?;
+    }
+    Ok(())
+}
+
 // Returns Ok whenever each component in `expected` is less or equal to the
 // corresponding component in `actual`.
 fn is_less_or_equal(expected: &TcbVersion, actual: &TcbVersion) -> anyhow::Result<()> {
@@ -173,6 +344,19 @@ pub fn verify_amd_sev_attestation_report_values(
         anyhow::bail!("debug mode not allowed");
     }
 
+    // Only VCEK-signed reports are accepted unless other keys are allowed.
+    let signing_key = attestation_report_values.signing_key();
+    let allowed = if expected_values.allowed_signing_keys.is_empty() {
+        signing_key == AmdSigningKey::Vcek
+    } else {
+        expected_values.allowed_signing_keys().any(|allowed| allowed == signing_key)
+    };
+    anyhow::ensure!(
+        allowed,
+        "attestation report signing key not allowed: {}",
+        signing_key.as_str_name()
+    );
+
     // Legacy TCB version comparison (deprecated).
     // TODO: b/433225656 - Remove this block.
     #[allow(deprecated)]
@@ -236,6 +420,11 @@ pub fn convert_amd_sev_snp_attestation_report(
         AmdProduct::Genoa => oak_proto_rust::oak::attestation::v1::AmdProduct::Genoa,
         AmdProduct::Turin => oak_proto_rust::oak::attestation::v1::AmdProduct::Turin,
     };
+    let signing_key = match report.data.get_signing_key() {
+        Some(SigningKey::VCEK) => AmdSigningKey::Vcek,
+        Some(SigningKey::VLEK) => AmdSigningKey::Vlek,
+        _ => AmdSigningKey::Unspecified,
+    };
     let hardware_id = report.data.chip_id.as_ref().to_vec();
     let initial_measurement = report.data.measurement.as_ref().to_vec();
     let report_data = report.data.report_data.as_ref().to_vec();
@@ -250,6 +439,7 @@ pub fn convert_amd_sev_snp_attestation_report(
         initial_measurement,
         hardware_id,
         vmpl,
+        signing_key: signing_key.into(),
     })
 }
 
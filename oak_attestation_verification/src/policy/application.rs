@@ -17,7 +17,10 @@
 use alloc::vec;
 
 use anyhow::Context;
-use oak_attestation_verification_types::policy::Policy;
+use oak_attestation_verification_types::{
+    policy::Policy,
+    report::{record, LayerReport},
+};
 use oak_proto_rust::oak::{
     attestation::v1::{
         binary_reference_value, ApplicationEndorsement, ApplicationLayerData,
@@ -75,6 +78,45 @@ impl ApplicationPolicy {
             }),
         })
     }
+
+    /// Verifies the application event, recording each check in `report` if
+    /// given.
+    fn check(
+        &self,
+        verification_time: Instant,
+        evidence: &[u8],
+        endorsement: &Variant,
+        mut report: Option<&mut LayerReport>,
+    ) -> anyhow::Result<EventAttestationResults> {
+        let event = record(
+            report.as_deref_mut(),
+            "evidence",
+            decode_event_proto::<ApplicationLayerData>(
+                "type.googleapis.com/oak.attestation.v1.ApplicationLayerData",
+                evidence,
+            ),
+        )?;
+        let expected_values = record(
+            report.as_deref_mut(),
+            "endorsement",
+            endorsement.try_into().map_err(anyhow::Error::msg).and_then(
+                |endorsement: Option<ApplicationEndorsement>| {
+                    acquire_application_event_expected_values(
+                        verification_time.into_unix_millis(),
+                        endorsement.as_ref(),
+                        &self.reference_values,
+                    )
+                    .context("acquiring application event expected values")
+                },
+            ),
+        )?;
+
+        compare_application_layer_measurement_digests(&event, &expected_values, report)
+            .context("comparing application event digests")?;
+
+        // TODO: b/356631062 - Return detailed attestation results.
+        Ok(EventAttestationResults { ..Default::default() })
+    }
 }
 
 // We have to use [`Policy<[u8]>`] instead of [`EventPolicy`], because
@@ -87,25 +129,22 @@ impl Policy<[u8]> for ApplicationPolicy {
         evidence: &[u8],
         endorsement: &Variant,
     ) -> anyhow::Result<EventAttestationResults> {
-        let event = decode_event_proto::<ApplicationLayerData>(
-            "type.googleapis.com/oak.attestation.v1.ApplicationLayerData",
-            evidence,
-        )?;
-        let endorsement: Option<ApplicationEndorsement> =
-            endorsement.try_into().map_err(anyhow::Error::msg)?;
-
-        let expected_values = acquire_application_event_expected_values(
-            verification_time.into_unix_millis(),
-            endorsement.as_ref(),
-            &self.reference_values,
-        )
-        .context("acquiring application event expected values")?;
+        self.check(verification_time, evidence, endorsement, None)
+    }
 
-        compare_application_layer_measurement_digests(&event, &expected_values)
-            .context("comparing application event digests")?;
+    fn layer(&self) -> &'static str {
+        "application"
+    }
 
-        // TODO: b/356631062 - Return detailed attestation results.
-        Ok(EventAttestationResults { ..Default::default() })
+    fn report(
+        &self,
+        verification_time: Instant,
+        evidence: &[u8],
+        endorsement: &Variant,
+    ) -> LayerReport {
+        LayerReport::collect(self.layer(), |report| {
+            self.check(verification_time, evidence, endorsement, Some(report))
+        })
     }
 }
 
@@ -15,7 +15,10 @@
 //
 
 use anyhow::Context;
-use oak_attestation_verification_types::policy::Policy;
+use oak_attestation_verification_types::{
+    policy::Policy,
+    report::{record, LayerReport},
+};
 use oak_proto_rust::oak::{
     attestation::v1::{EventAttestationResults, EventData, EventReferenceValues},
     Variant,
@@ -35,30 +38,64 @@ impl BinaryPolicy {
     pub fn new(reference_values: &EventReferenceValues) -> Self {
         Self { reference_values: reference_values.clone() }
     }
-}
 
-impl Policy<[u8]> for BinaryPolicy {
-    fn verify(
+    /// Verifies the binary event, recording each check in `report` if given.
+    fn check(
         &self,
         verification_time: Instant,
         evidence: &[u8],
         _endorsement: &Variant,
+        mut report: Option<&mut LayerReport>,
     ) -> anyhow::Result<EventAttestationResults> {
-        let event = decode_event_proto::<EventData>(
-            "type.googleapis.com/oak.attestation.v1.EventData",
-            evidence,
+        let event = record(
+            report.as_deref_mut(),
+            "evidence",
+            decode_event_proto::<EventData>(
+                "type.googleapis.com/oak.attestation.v1.EventData",
+                evidence,
+            ),
         )?;
 
-        let expected_values = acquire_event_expected_values(
-            verification_time.into_unix_millis(),
-            &self.reference_values,
-        )
-        .context("acquiring event expected values")?;
+        let expected_values = record(
+            report.as_deref_mut(),
+            "reference_values",
+            acquire_event_expected_values(
+                verification_time.into_unix_millis(),
+                &self.reference_values,
+            )
+            .context("acquiring event expected values"),
+        )?;
 
-        compare_event_measurement_digests(&event, &expected_values)
+        compare_event_measurement_digests(&event, &expected_values, report)
             .context("comparing event digests")?;
 
         // TODO: b/356631062 - Return detailed attestation results.
         Ok(EventAttestationResults { ..Default::default() })
     }
 }
+
+impl Policy<[u8]> for BinaryPolicy {
+    fn verify(
+        &self,
+        verification_time: Instant,
+        evidence: &[u8],
+        endorsement: &Variant,
+    ) -> anyhow::Result<EventAttestationResults> {
+        self.check(verification_time, evidence, endorsement, None)
+    }
+
+    fn layer(&self) -> &'static str {
+        "binary"
+    }
+
+    fn report(
+        &self,
+        verification_time: Instant,
+        evidence: &[u8],
+        endorsement: &Variant,
+    ) -> LayerReport {
+        LayerReport::collect(self.layer(), |report| {
+            self.check(verification_time, evidence, endorsement, Some(report))
+        })
+    }
+}
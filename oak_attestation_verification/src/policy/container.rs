@@ -17,7 +17,10 @@
 use alloc::vec;
 
 use anyhow::Context;
-use oak_attestation_verification_types::policy::Policy;
+use oak_attestation_verification_types::{
+    policy::Policy,
+    report::{record, LayerReport},
+};
 use oak_proto_rust::oak::{
     attestation::v1::{
         binary_reference_value, BinaryReferenceValue, ContainerEndorsement, ContainerLayerData,
@@ -69,33 +72,39 @@ impl ContainerPolicy {
             }),
         })
     }
-}
 
-// We have to use [`Policy<[u8]>`] instead of [`EventPolicy`], because
-// Rust doesn't yet support implementing trait aliases.
-// <https://github.com/rust-lang/rfcs/blob/master/text/1733-trait-alias.md>
-impl Policy<[u8]> for ContainerPolicy {
-    fn verify(
+    /// Verifies the container event, recording each check in `report` if given.
+    fn check(
         &self,
         verification_time: Instant,
         evidence: &[u8],
         endorsement: &Variant,
+        mut report: Option<&mut LayerReport>,
     ) -> anyhow::Result<EventAttestationResults> {
-        let event = decode_event_proto::<ContainerLayerData>(
-            "type.googleapis.com/oak.attestation.v1.ContainerLayerData",
-            evidence,
+        let event = record(
+            report.as_deref_mut(),
+            "evidence",
+            decode_event_proto::<ContainerLayerData>(
+                "type.googleapis.com/oak.attestation.v1.ContainerLayerData",
+                evidence,
+            ),
+        )?;
+        let expected_values = record(
+            report.as_deref_mut(),
+            "endorsement",
+            endorsement.try_into().map_err(anyhow::Error::msg).and_then(
+                |endorsement: Option<ContainerEndorsement>| {
+                    acquire_container_event_expected_values(
+                        verification_time.into_unix_millis(),
+                        endorsement.as_ref(),
+                        &self.reference_values,
+                    )
+                    .context("acquiring container event expected values")
+                },
+            ),
         )?;
-        let endorsement: Option<ContainerEndorsement> =
-            endorsement.try_into().map_err(anyhow::Error::msg)?;
-
-        let expected_values = acquire_container_event_expected_values(
-            verification_time.into_unix_millis(),
-            endorsement.as_ref(),
-            &self.reference_values,
-        )
-        .context("acquiring container event expected values")?;
 
-        compare_container_layer_measurement_digests(&event, &expected_values)
+        compare_container_layer_measurement_digests(&event, &expected_values, report)
             .context("comparing container layer digests")?;
 
         let mut results = EventAttestationResults { ..Default::default() };
@@ -114,6 +123,35 @@ impl Policy<[u8]> for ContainerPolicy {
     }
 }
 
+// We have to use [`Policy<[u8]>`] instead of [`EventPolicy`], because
+// Rust doesn't yet support implementing trait aliases.
+// <https://github.com/rust-lang/rfcs/blob/master/text/1733-trait-alias.md>
+impl Policy<[u8]> for ContainerPolicy {
+    fn verify(
+        &self,
+        verification_time: Instant,
+        evidence: &[u8],
+        endorsement: &Variant,
+    ) -> anyhow::Result<EventAttestationResults> {
+        self.check(verification_time, evidence, endorsement, None)
+    }
+
+    fn layer(&self) -> &'static str {
+        "container"
+    }
+
+    fn report(
+        &self,
+        verification_time: Instant,
+        evidence: &[u8],
+        endorsement: &Variant,
+    ) -> LayerReport {
+        LayerReport::collect(self.layer(), |report| {
+            self.check(verification_time, evidence, endorsement, Some(report))
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use test_util::{get_oc_reference_values, AttestationData};
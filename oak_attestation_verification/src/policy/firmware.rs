@@ -15,7 +15,10 @@
 //
 
 use anyhow::Context;
-use oak_attestation_verification_types::policy::Policy;
+use oak_attestation_verification_types::{
+    policy::Policy,
+    report::{record, LayerReport},
+};
 use oak_proto_rust::oak::{
     attestation::v1::{BinaryReferenceValue, EventAttestationResults, FirmwareEndorsement},
     Variant,
@@ -34,6 +37,35 @@ impl FirmwarePolicy {
     pub fn new(reference_values: &BinaryReferenceValue) -> Self {
         Self { reference_values: reference_values.clone() }
     }
+
+    /// Verifies the firmware event, recording each check in `report` if given.
+    fn check(
+        &self,
+        verification_time: Instant,
+        evidence: &[u8],
+        endorsement: &Variant,
+        mut report: Option<&mut LayerReport>,
+    ) -> anyhow::Result<EventAttestationResults> {
+        let expected_values = record(
+            report.as_deref_mut(),
+            "endorsement",
+            endorsement.try_into().map_err(anyhow::Error::msg).and_then(
+                |endorsement: Option<FirmwareEndorsement>| {
+                    acquire_stage0_expected_values(
+                        verification_time.into_unix_millis(),
+                        endorsement.as_ref(),
+                        &self.reference_values,
+                    )
+                    .context("acquiring firmware expected values")
+                },
+            ),
+        )?;
+
+        compare_firmware_layer_measurement_digests(evidence, &expected_values, report)
+            .context("comparing firmware digests")?;
+
+        Ok(EventAttestationResults { ..Default::default() })
+    }
 }
 
 // Policy which verifies the firmware.
@@ -44,20 +76,22 @@ impl Policy<[u8]> for FirmwarePolicy {
         evidence: &[u8],
         endorsement: &Variant,
     ) -> anyhow::Result<EventAttestationResults> {
-        let endorsement: Option<FirmwareEndorsement> =
-            endorsement.try_into().map_err(anyhow::Error::msg)?;
-
-        let expected_values = acquire_stage0_expected_values(
-            verification_time.into_unix_millis(),
-            endorsement.as_ref(),
-            &self.reference_values,
-        )
-        .context("acquiring firmware expected values")?;
+        self.check(verification_time, evidence, endorsement, None)
+    }
 
-        compare_firmware_layer_measurement_digests(evidence, &expected_values)
-            .context("comparing firmware digests")?;
+    fn layer(&self) -> &'static str {
+        "firmware"
+    }
 
-        Ok(EventAttestationResults { ..Default::default() })
+    fn report(
+        &self,
+        verification_time: Instant,
+        evidence: &[u8],
+        endorsement: &Variant,
+    ) -> LayerReport {
+        LayerReport::collect(self.layer(), |report| {
+            self.check(verification_time, evidence, endorsement, Some(report))
+        })
     }
 }
 
@@ -17,7 +17,10 @@
 use alloc::{string::String, vec};
 
 use anyhow::Context;
-use oak_attestation_verification_types::policy::Policy;
+use oak_attestation_verification_types::{
+    policy::Policy,
+    report::{record, LayerReport},
+};
 use oak_proto_rust::oak::{
     attestation::v1::{
         binary_reference_value, kernel_binary_reference_value, text_reference_value,
@@ -118,31 +121,40 @@ impl KernelPolicy {
             })),
         }
     }
-}
 
-impl Policy<[u8]> for KernelPolicy {
-    fn verify(
+    /// Verifies the kernel event, recording each check in `report` if given.
+    fn check(
         &self,
         verification_time: Instant,
         evidence: &[u8],
         endorsement: &Variant,
+        mut report: Option<&mut LayerReport>,
     ) -> anyhow::Result<EventAttestationResults> {
-        let event =
-            stage0_measurements_to_kernel_layer_data(decode_event_proto::<Stage0Measurements>(
+        let event = record(
+            report.as_deref_mut(),
+            "evidence",
+            decode_event_proto::<Stage0Measurements>(
                 "type.googleapis.com/oak.attestation.v1.Stage0Measurements",
                 evidence,
-            )?);
-        let endorsement: Option<KernelEndorsement> =
-            endorsement.try_into().map_err(anyhow::Error::msg)?;
-
-        let expected_values = acquire_kernel_event_expected_values(
-            verification_time.into_unix_millis(),
-            endorsement.as_ref(),
-            &self.reference_values,
-        )
-        .context("acquiring kernel event expected values")?;
+            )
+            .map(stage0_measurements_to_kernel_layer_data),
+        )?;
+        let expected_values = record(
+            report.as_deref_mut(),
+            "endorsement",
+            endorsement.try_into().map_err(anyhow::Error::msg).and_then(
+                |endorsement: Option<KernelEndorsement>| {
+                    acquire_kernel_event_expected_values(
+                        verification_time.into_unix_millis(),
+                        endorsement.as_ref(),
+                        &self.reference_values,
+                    )
+                    .context("acquiring kernel event expected values")
+                },
+            ),
+        )?;
 
-        compare_kernel_layer_measurement_digests(&event, &expected_values)
+        compare_kernel_layer_measurement_digests(&event, &expected_values, report)
             .context("comparing kernel event digests")?;
 
         // TODO: b/356631062 - Return detailed attestation results.
@@ -150,6 +162,32 @@ impl Policy<[u8]> for KernelPolicy {
     }
 }
 
+impl Policy<[u8]> for KernelPolicy {
+    fn verify(
+        &self,
+        verification_time: Instant,
+        evidence: &[u8],
+        endorsement: &Variant,
+    ) -> anyhow::Result<EventAttestationResults> {
+        self.check(verification_time, evidence, endorsement, None)
+    }
+
+    fn layer(&self) -> &'static str {
+        "kernel"
+    }
+
+    fn report(
+        &self,
+        verification_time: Instant,
+        evidence: &[u8],
+        endorsement: &Variant,
+    ) -> LayerReport {
+        LayerReport::collect(self.layer(), |report| {
+            self.check(verification_time, evidence, endorsement, Some(report))
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use test_util::{get_oc_reference_values, get_rk_reference_values, AttestationData};
@@ -14,43 +14,57 @@
 // limitations under the License.
 //
 
-use alloc::vec;
+use alloc::{sync::Arc, vec};
 
 use anyhow::Context;
-use oak_attestation_verification_types::policy::Policy;
+use oak_attestation_verification_types::{
+    policy::Policy,
+    report::{record, LayerReport},
+};
 use oak_dice::evidence::TeePlatform;
 use oak_proto_rust::oak::{
     attestation::v1::{
         binary_reference_value, tcb_version_reference_value, tdx_tcb_svn_reference_value,
-        AmdSevReferenceValues, AmdSevSnpEndorsement, BinaryReferenceValue, Digests,
+        AmdSevReferenceValues, AmdSevSnpEndorsement, AmdSigningKey, BinaryReferenceValue, Digests,
         EventAttestationResults, IntelTdxReferenceValues, RootLayerEvidence, TcbVersion,
         TcbVersionReferenceValue, TdxTcbSvnReferenceValue,
     },
     RawDigest, Variant,
 };
-use oak_sev_snp_attestation_report::{AmdProduct, AttestationReport};
+use oak_sev_snp_attestation_report::{AmdProduct, AttestationReport, SigningKey};
 use oak_tdx_quote::{TdAttributes, TdxQuoteWrapper};
 use oak_time::Instant;
 use zerocopy::FromBytes;
 
 use crate::{
+    amd::CrlPolicy,
+    cache::CertificateChainCache,
     expect::{get_amd_sev_snp_expected_values, get_intel_tdx_expected_values},
     intel,
     platform::{
         convert_amd_sev_snp_attestation_report, convert_intel_tdx_attestation_quote,
         new_tdx_tcb_svn, verify_amd_sev_attestation_report_values,
         verify_intel_tdx_attestation_quote, verify_root_attestation_signature,
+        verify_root_attestation_signature_with_cache,
     },
-    results::set_initial_measurement,
+    results::{set_amd_sev_snp_signing_key, set_initial_measurement},
 };
 
 pub struct AmdSevSnpPolicy {
     reference_values: AmdSevReferenceValues,
+    cert_chain_cache: Option<Arc<CertificateChainCache>>,
 }
 
 impl AmdSevSnpPolicy {
     pub fn new(reference_values: &AmdSevReferenceValues) -> Self {
-        Self { reference_values: reference_values.clone() }
+        Self { reference_values: reference_values.clone(), cert_chain_cache: None }
+    }
+
+    /// Reuses VCEK (or VLEK) certificate chains which were verified before,
+    /// as recorded in `cache`. The cache can be shared between policies.
+    pub fn with_cert_chain_cache(mut self, cache: Arc<CertificateChainCache>) -> Self {
+        self.cert_chain_cache = Some(cache);
+        self
     }
 
     /// Returns AmdSevReferenceValues and firmware reference values
@@ -77,8 +91,14 @@ impl AmdSevSnpPolicy {
                 .has_debug_flag()
                 .map_err(|err| anyhow::anyhow!("failed to get debug flag: {}", err))?,
             check_vcek_cert_expiry: false,
+            require_crls: false,
+            reject_stale_crls: false,
+            allowed_signing_keys: vec![],
             stage0: None,
         };
+        if report.data.get_signing_key() == Some(SigningKey::VLEK) {
+            rv.allowed_signing_keys.push(AmdSigningKey::Vlek.into());
+        }
         let tcb_version = report.data.get_reported_tcb_version();
         let product = match report.data.get_product() {
             AmdProduct::Milan => &mut rv.milan,
@@ -109,6 +129,65 @@ impl AmdSevSnpPolicy {
         };
         Ok((rv, firmware_rv))
     }
+
+    /// Verifies the hardware root, recording each check in `report` if given.
+    fn check(
+        &self,
+        verification_time: Instant,
+        evidence: &RootLayerEvidence,
+        endorsement: &Variant,
+        mut report: Option<&mut LayerReport>,
+    ) -> anyhow::Result<EventAttestationResults> {
+        record(
+            report.as_deref_mut(),
+            "tee_platform",
+            if evidence.platform == TeePlatform::AmdSevSnp as i32 {
+                Ok(())
+            } else {
+                Err(anyhow::anyhow!("unsupported TEE platform value"))
+            },
+        )?;
+
+        let signature = endorsement.try_into().map_err(anyhow::Error::msg).and_then(
+            |endorsement: AmdSevSnpEndorsement| {
+                let crl_policy = CrlPolicy {
+                    require_crls: self.reference_values.require_crls,
+                    reject_stale_crls: self.reference_values.reject_stale_crls,
+                };
+                verify_root_attestation_signature_with_cache(
+                    verification_time,
+                    self.reference_values.check_vcek_cert_expiry,
+                    crl_policy,
+                    evidence,
+                    &endorsement.tee_certificate,
+                    &endorsement.asvk_certificate,
+                    &endorsement.crls,
+                    self.cert_chain_cache.as_deref(),
+                )
+            },
+        );
+        let signature = record(report.as_deref_mut(), "signature", signature);
+
+        // Verify attestation report values.
+        let report_values = AttestationReport::ref_from_bytes(&evidence.remote_attestation_report)
+            .map_err(|err| anyhow::anyhow!("invalid AMD SEV-SNP attestation report: {}", err))
+            .and_then(|attestation_report| {
+                let amd_report = convert_amd_sev_snp_attestation_report(attestation_report)?;
+                let expected_values = get_amd_sev_snp_expected_values(&self.reference_values)
+                    .context("getting AMD SEV-SNP expected values")?;
+                verify_amd_sev_attestation_report_values(&amd_report, &expected_values)
+                    .context("verifying AMD SEV-SNP attestation report values")?;
+                Ok((attestation_report, amd_report))
+            });
+        let report_values = record(report, "report_values", report_values);
+
+        signature?;
+        let (attestation_report, amd_report) = report_values?;
+        let mut results = EventAttestationResults { ..Default::default() };
+        set_initial_measurement(&mut results, &attestation_report.data.measurement);
+        set_amd_sev_snp_signing_key(&mut results, amd_report.signing_key());
+        Ok(results)
+    }
 }
 
 // Policy which verifies the AMD SEV-SNP hardware root.
@@ -122,41 +201,40 @@ impl Policy<RootLayerEvidence> for AmdSevSnpPolicy {
         evidence: &RootLayerEvidence,
         endorsement: &Variant,
     ) -> anyhow::Result<EventAttestationResults> {
-        if evidence.platform != TeePlatform::AmdSevSnp as i32 {
-            anyhow::bail!("unsupported TEE platform value");
-        }
-
-        let endorsement: AmdSevSnpEndorsement =
-            endorsement.try_into().map_err(anyhow::Error::msg)?;
-        verify_root_attestation_signature(
-            verification_time,
-            self.reference_values.check_vcek_cert_expiry,
-            evidence,
-            &endorsement.tee_certificate,
-        )?;
+        self.check(verification_time, evidence, endorsement, None)
+    }
 
-        // Verify attestation report values.
-        let report = AttestationReport::ref_from_bytes(&evidence.remote_attestation_report)
-            .map_err(|err| anyhow::anyhow!("invalid AMD SEV-SNP attestation report: {}", err))?;
-        let amd_report = convert_amd_sev_snp_attestation_report(report)?;
-        let expected_values = get_amd_sev_snp_expected_values(&self.reference_values)
-            .context("getting AMD SEV-SNP expected values")?;
-        verify_amd_sev_attestation_report_values(&amd_report, &expected_values)
-            .context("verifying AMD SEV-SNP attestation report values")?;
+    fn layer(&self) -> &'static str {
+        "platform"
+    }
 
-        let mut results = EventAttestationResults { ..Default::default() };
-        set_initial_measurement(&mut results, &report.data.measurement);
-        Ok(results)
+    fn report(
+        &self,
+        verification_time: Instant,
+        evidence: &RootLayerEvidence,
+        endorsement: &Variant,
+    ) -> LayerReport {
+        LayerReport::collect(self.layer(), |report| {
+            self.check(verification_time, evidence, endorsement, Some(report))
+        })
     }
 }
 
 pub struct IntelTdxPolicy {
     reference_values: IntelTdxReferenceValues,
+    cert_chain_cache: Option<Arc<CertificateChainCache>>,
 }
 
 impl IntelTdxPolicy {
     pub fn new(reference_values: &IntelTdxReferenceValues) -> Self {
-        Self { reference_values: reference_values.clone() }
+        Self { reference_values: reference_values.clone(), cert_chain_cache: None }
+    }
+
+    /// Reuses PCK certificate chains which were verified before, as recorded
+    /// in `cache`. The cache can be shared between policies.
+    pub fn with_cert_chain_cache(mut self, cache: Arc<CertificateChainCache>) -> Self {
+        self.cert_chain_cache = Some(cache);
+        self
     }
 
     /// Returns IntelTdxReferenceValues and firmware reference values
@@ -197,6 +275,57 @@ impl IntelTdxPolicy {
         };
         Ok((rv, firmware_rv))
     }
+
+    /// Verifies the hardware root, recording each check in `report` if given.
+    fn check(
+        &self,
+        verification_time: Instant,
+        evidence: &RootLayerEvidence,
+        mut report: Option<&mut LayerReport>,
+    ) -> anyhow::Result<EventAttestationResults> {
+        record(
+            report.as_deref_mut(),
+            "tee_platform",
+            if evidence.platform == TeePlatform::IntelTdx as i32 {
+                Ok(())
+            } else {
+                Err(anyhow::anyhow!("unsupported TEE platform value"))
+            },
+        )?;
+        let wrapper = TdxQuoteWrapper::new(evidence.remote_attestation_report.as_slice());
+
+        // Verify validity of the quote.
+        let quote_validity = record(
+            report.as_deref_mut(),
+            "quote_validity",
+            intel::verify_intel_tdx_quote_validity_with_cache(
+                verification_time,
+                &wrapper,
+                self.cert_chain_cache.as_deref(),
+            )
+            .context("verifying TDX quote validity"),
+        );
+
+        // Verify attestation quote values.
+        let quote_values = wrapper
+            .parse_quote()
+            .map_err(|err| anyhow::anyhow!("invalid Intel TDX attestation quote: {}", err))
+            .and_then(|quote| {
+                let quote_values = convert_intel_tdx_attestation_quote(&quote);
+                let expected_values = get_intel_tdx_expected_values(&self.reference_values)
+                    .context("getting Intel TDX expected values")?;
+                verify_intel_tdx_attestation_quote(&quote_values, &expected_values)
+                    .context("verifying Intel TDX attestation quote values")?;
+                Ok(quote)
+            });
+        let quote_values = record(report, "quote_values", quote_values);
+
+        quote_validity?;
+        let quote = quote_values?;
+        let mut results = EventAttestationResults { ..Default::default() };
+        set_initial_measurement(&mut results, quote.body.mr_td.as_slice());
+        Ok(results)
+    }
 }
 
 // Policy which verifies the AMD SEV-SNP hardware root.
@@ -210,29 +339,22 @@ impl Policy<RootLayerEvidence> for IntelTdxPolicy {
         evidence: &RootLayerEvidence,
         _endorsement: &Variant,
     ) -> anyhow::Result<EventAttestationResults> {
-        if evidence.platform != TeePlatform::IntelTdx as i32 {
-            anyhow::bail!("unsupported TEE platform value");
-        }
-        let wrapper = TdxQuoteWrapper::new(evidence.remote_attestation_report.as_slice());
-
-        // Verify validity of the quote.
-        intel::verify_intel_tdx_quote_validity(verification_time, &wrapper)
-            .context("verifying TDX quote validity")?;
-
-        let quote = wrapper
-            .parse_quote()
-            .map_err(|err| anyhow::anyhow!("invalid Intel TDX attestation quote: {}", err))?;
+        self.check(verification_time, evidence, None)
+    }
 
-        // Verify attestation quote values.
-        let quote_values = convert_intel_tdx_attestation_quote(&quote);
-        let expected_values = get_intel_tdx_expected_values(&self.reference_values)
-            .context("getting Intel TDX expected values")?;
-        verify_intel_tdx_attestation_quote(&quote_values, &expected_values)
-            .context("verifying Intel TDX attestation quote values")?;
+    fn layer(&self) -> &'static str {
+        "platform"
+    }
 
-        let mut results = EventAttestationResults { ..Default::default() };
-        set_initial_measurement(&mut results, quote.body.mr_td.as_slice());
-        Ok(results)
+    fn report(
+        &self,
+        verification_time: Instant,
+        evidence: &RootLayerEvidence,
+        _endorsement: &Variant,
+    ) -> LayerReport {
+        LayerReport::collect(self.layer(), |report| {
+            self.check(verification_time, evidence, Some(report))
+        })
     }
 }
 
@@ -259,19 +381,28 @@ impl Policy<RootLayerEvidence> for InsecurePolicy {
         verify_root_attestation_signature(
             verification_time,
             false,
+            CrlPolicy::default(),
             evidence,
             &endorsement.tee_certificate,
+            &endorsement.asvk_certificate,
+            &endorsement.crls,
         )?;
 
         Ok(EventAttestationResults { ..Default::default() })
     }
+
+    fn layer(&self) -> &'static str {
+        "platform"
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use core::default::Default;
 
-    use oak_attestation_verification_results::get_initial_measurement;
+    use oak_attestation_verification_results::{
+        get_amd_sev_snp_signing_key, get_initial_measurement,
+    };
     use oak_proto_rust::oak::attestation::v1::endorsements;
     use test_util::{get_oc_reference_values, AttestationData};
 
@@ -288,6 +419,8 @@ mod tests {
                 }
                 _ => panic!("bad endorsement type"),
             },
+            crls: vec![],
+            asvk_certificate: vec![],
         };
         let ref_values = get_oc_reference_values(&d.reference_values);
         let platform_ref_values = ref_values.root_layer.as_ref().unwrap().amd_sev.as_ref().unwrap();
@@ -300,6 +433,65 @@ mod tests {
         );
 
         assert!(result.is_ok(), "Failed: {:?}", result.err().unwrap());
+        assert_eq!(get_amd_sev_snp_signing_key(&result.unwrap()), Some(AmdSigningKey::Vcek));
+    }
+
+    #[test]
+    fn verify_milan_oc_with_only_vlek_allowed_fails() {
+        let d = AttestationData::load_milan_oc_release();
+        let endorsement = AmdSevSnpEndorsement {
+            tee_certificate: match d.endorsements.r#type.as_ref() {
+                Some(endorsements::Type::OakContainers(e)) => {
+                    e.root_layer.as_ref().unwrap().tee_certificate.to_vec()
+                }
+                _ => panic!("bad endorsement type"),
+            },
+            crls: vec![],
+            asvk_certificate: vec![],
+        };
+        let ref_values = get_oc_reference_values(&d.reference_values);
+        let platform_ref_values = AmdSevReferenceValues {
+            allowed_signing_keys: vec![AmdSigningKey::Vlek.into()],
+            ..ref_values.root_layer.as_ref().unwrap().amd_sev.clone().unwrap()
+        };
+        let policy = AmdSevSnpPolicy::new(&platform_ref_values);
+
+        let result = policy.verify(
+            d.make_valid_time(),
+            d.evidence.root_layer.as_ref().unwrap(),
+            &endorsement.into(),
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn verify_milan_oc_without_required_crls_fails() {
+        let d = AttestationData::load_milan_oc_release();
+        let endorsement = AmdSevSnpEndorsement {
+            tee_certificate: match d.endorsements.r#type.as_ref() {
+                Some(endorsements::Type::OakContainers(e)) => {
+                    e.root_layer.as_ref().unwrap().tee_certificate.to_vec()
+                }
+                _ => panic!("bad endorsement type"),
+            },
+            crls: vec![],
+            asvk_certificate: vec![],
+        };
+        let ref_values = get_oc_reference_values(&d.reference_values);
+        let platform_ref_values = AmdSevReferenceValues {
+            require_crls: true,
+            ..ref_values.root_layer.as_ref().unwrap().amd_sev.clone().unwrap()
+        };
+        let policy = AmdSevSnpPolicy::new(&platform_ref_values);
+
+        let result = policy.verify(
+            d.make_valid_time(),
+            d.evidence.root_layer.as_ref().unwrap(),
+            &endorsement.into(),
+        );
+
+        assert!(result.is_err());
     }
 
     #[test]
@@ -313,6 +505,8 @@ mod tests {
                 }
                 _ => panic!("bad endorsement type"),
             },
+            crls: vec![],
+            asvk_certificate: vec![],
         };
 
         let (rv, firmware_rv) = AmdSevSnpPolicy::evidence_to_reference_values(event)
@@ -17,7 +17,10 @@
 use alloc::vec;
 
 use anyhow::Context;
-use oak_attestation_verification_types::policy::Policy;
+use oak_attestation_verification_types::{
+    policy::Policy,
+    report::{record, LayerReport},
+};
 use oak_proto_rust::oak::{
     attestation::v1::{
         binary_reference_value, BinaryReferenceValue, Digests, EventAttestationResults,
@@ -60,30 +63,39 @@ impl SystemPolicy {
             }),
         })
     }
-}
 
-impl Policy<[u8]> for SystemPolicy {
-    fn verify(
+    /// Verifies the system event, recording each check in `report` if given.
+    fn check(
         &self,
         verification_time: Instant,
         evidence: &[u8],
         endorsement: &Variant,
+        mut report: Option<&mut LayerReport>,
     ) -> anyhow::Result<EventAttestationResults> {
-        let event = decode_event_proto::<SystemLayerData>(
-            "type.googleapis.com/oak.attestation.v1.SystemLayerData",
-            evidence,
+        let event = record(
+            report.as_deref_mut(),
+            "evidence",
+            decode_event_proto::<SystemLayerData>(
+                "type.googleapis.com/oak.attestation.v1.SystemLayerData",
+                evidence,
+            ),
+        )?;
+        let expected_values = record(
+            report.as_deref_mut(),
+            "endorsement",
+            endorsement.try_into().map_err(anyhow::Error::msg).and_then(
+                |endorsement: Option<SystemEndorsement>| {
+                    acquire_system_event_expected_values(
+                        verification_time.into_unix_millis(),
+                        endorsement.as_ref(),
+                        &self.reference_values,
+                    )
+                    .context("acquiring system event expected values")
+                },
+            ),
         )?;
-        let endorsement: Option<SystemEndorsement> =
-            endorsement.try_into().map_err(anyhow::Error::msg)?;
-
-        let expected_values = acquire_system_event_expected_values(
-            verification_time.into_unix_millis(),
-            endorsement.as_ref(),
-            &self.reference_values,
-        )
-        .context("acquiring system event expected values")?;
 
-        compare_system_layer_measurement_digests(&event, &expected_values)
+        compare_system_layer_measurement_digests(&event, &expected_values, report)
             .context("comparing system event digests")?;
 
         // TODO: b/356631062 - Return detailed attestation results.
@@ -91,6 +103,32 @@ impl Policy<[u8]> for SystemPolicy {
     }
 }
 
+impl Policy<[u8]> for SystemPolicy {
+    fn verify(
+        &self,
+        verification_time: Instant,
+        evidence: &[u8],
+        endorsement: &Variant,
+    ) -> anyhow::Result<EventAttestationResults> {
+        self.check(verification_time, evidence, endorsement, None)
+    }
+
+    fn layer(&self) -> &'static str {
+        "system"
+    }
+
+    fn report(
+        &self,
+        verification_time: Instant,
+        evidence: &[u8],
+        endorsement: &Variant,
+    ) -> LayerReport {
+        LayerReport::collect(self.layer(), |report| {
+            self.check(verification_time, evidence, endorsement, Some(report))
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use test_util::{get_oc_reference_values, AttestationData};
@@ -22,11 +22,16 @@ extern crate alloc;
 
 use alloc::{string::ToString, vec::Vec};
 
-use oak_proto_rust::oak::attestation::v1::{AttestationResults, EventAttestationResults};
+use oak_proto_rust::oak::attestation::v1::{
+    AmdSigningKey, AttestationResults, EventAttestationResults,
+};
 
 /// Key for the initial measurement of stage0.
 const INITIAL_MEASUREMENT_ID: &str = "initial-measurement";
 
+/// Key for the kind of key that signed the AMD SEV-SNP attestation report.
+const AMD_SEV_SNP_SIGNING_KEY_ID: &str = "amd-sev-snp-signing-key";
+
 /// Denotes an artifact ID of a public key used to verify the Noise handshake
 /// transcript signature.
 const SESSION_BINDING_PUBLIC_KEY_ID: &str = "oak-session-binding-public-key:ecdsa-p256";
@@ -47,6 +52,32 @@ pub fn set_initial_measurement(results: &mut EventAttestationResults, key: &[u8]
     results.artifacts.insert(INITIAL_MEASUREMENT_ID.to_string(), key.to_vec());
 }
 
+/// Returns the kind of key that signed the AMD SEV-SNP attestation report, if
+/// present in the event results.
+pub fn get_amd_sev_snp_signing_key(results: &EventAttestationResults) -> Option<AmdSigningKey> {
+    let value = results.artifacts.get(AMD_SEV_SNP_SIGNING_KEY_ID)?;
+    AmdSigningKey::from_str_name(core::str::from_utf8(value).ok()?)
+}
+
+/// Returns the kind of key that signed the AMD SEV-SNP attestation report
+/// contained in the attestation results, or an error whenever it is missing,
+/// not unique or invalid.
+pub fn unique_amd_sev_snp_signing_key(
+    results: &AttestationResults,
+) -> Result<AmdSigningKey, &'static str> {
+    let value = unique_event_artifact(results, AMD_SEV_SNP_SIGNING_KEY_ID)?;
+    core::str::from_utf8(value)
+        .ok()
+        .and_then(AmdSigningKey::from_str_name)
+        .ok_or("invalid AMD SEV-SNP signing key")
+}
+
+pub fn set_amd_sev_snp_signing_key(results: &mut EventAttestationResults, key: AmdSigningKey) {
+    results
+        .artifacts
+        .insert(AMD_SEV_SNP_SIGNING_KEY_ID.to_string(), key.as_str_name().as_bytes().to_vec());
+}
+
 /// Returns the session binding key artifact contained in the event results,
 /// or an error whenever it is missing or not unique.
 pub fn unique_session_binding_public_key(
@@ -155,4 +186,29 @@ mod tests {
 
         assert!(unique_event_artifact(&results, "id_999").is_err());
     }
+
+    #[test]
+    fn test_amd_sev_snp_signing_key() {
+        let mut event_results = EventAttestationResults { ..Default::default() };
+        assert_eq!(get_amd_sev_snp_signing_key(&event_results), None);
+
+        set_amd_sev_snp_signing_key(&mut event_results, AmdSigningKey::Vlek);
+        assert_eq!(get_amd_sev_snp_signing_key(&event_results), Some(AmdSigningKey::Vlek));
+
+        let results = AttestationResults {
+            event_attestation_results: vec![event_results],
+            ..Default::default()
+        };
+        assert_eq!(unique_amd_sev_snp_signing_key(&results), Ok(AmdSigningKey::Vlek));
+
+        let invalid_results = AttestationResults {
+            event_attestation_results: vec![EventAttestationResults {
+                artifacts: [(AMD_SEV_SNP_SIGNING_KEY_ID.to_string(), b"VLEK".to_vec())]
+                    .into_iter()
+                    .collect::<BTreeMap<String, Vec<u8>>>(),
+            }],
+            ..Default::default()
+        };
+        assert!(unique_amd_sev_snp_signing_key(&invalid_results).is_err());
+    }
 }
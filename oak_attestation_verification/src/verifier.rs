@@ -31,6 +31,7 @@ use oak_time::{Clock, Instant};
 use p256::ecdsa::VerifyingKey;
 
 use crate::{
+    amd::CrlPolicy,
     compare::compare_expected_values,
     expect::get_expected_values,
     extract::{claims_set_from_serialized_cert, extract_event_data, extract_evidence, EventIdType},
@@ -195,27 +196,15 @@ pub fn verify_with_expected_values(
     // Ensure the Attestation report is properly signed by the platform and that it
     // includes the root public key used in the DICE chain.
     {
-        let tee_certificate: &[u8] =
+        let root_layer_endorsements =
             match endorsements.r#type.as_ref().context("no endorsements")? {
-                endorsements::Type::OakRestrictedKernel(endorsements) => endorsements
-                    .root_layer
-                    .as_ref()
-                    .context("no root layer endorsements")?
-                    .tee_certificate
-                    .as_ref(),
-                endorsements::Type::OakContainers(endorsements) => endorsements
-                    .root_layer
-                    .as_ref()
-                    .context("no root layer endorsements")?
-                    .tee_certificate
-                    .as_ref(),
-                endorsements::Type::Cb(endorsements) => endorsements
-                    .root_layer
-                    .as_ref()
-                    .context("no root layer endorsements")?
-                    .tee_certificate
-                    .as_ref(),
-            };
+                endorsements::Type::OakRestrictedKernel(endorsements) => {
+                    endorsements.root_layer.as_ref()
+                }
+                endorsements::Type::OakContainers(endorsements) => endorsements.root_layer.as_ref(),
+                endorsements::Type::Cb(endorsements) => endorsements.root_layer.as_ref(),
+            }
+            .context("no root layer endorsements")?;
         let amd_expected_values =
             match expected_values.r#type.as_ref().context("no expected values")? {
                 expected_values::Type::OakRestrictedKernel(expected_values) => expected_values
@@ -239,12 +228,21 @@ pub fn verify_with_expected_values(
             };
         let check_vcek_cert_expiry =
             amd_expected_values.map(|value| value.check_vcek_cert_expiry).unwrap_or(false);
+        let crl_policy = amd_expected_values
+            .map(|value| CrlPolicy {
+                require_crls: value.require_crls,
+                reject_stale_crls: value.reject_stale_crls,
+            })
+            .unwrap_or_default();
         let root_layer = evidence.root_layer.as_ref().context("no root layer evidence")?;
         verify_root_attestation_signature(
             Instant::from_unix_millis(now_utc_millis),
             check_vcek_cert_expiry,
+            crl_policy,
             root_layer,
-            tee_certificate,
+            &root_layer_endorsements.tee_certificate,
+            &root_layer_endorsements.asvk_certificate,
+            &root_layer_endorsements.crls,
         )
         .context("verifying root signature")?;
     };
@@ -167,6 +167,8 @@ fn create_oc_endorsements_reference_values(
                     root_layer: Some(RootLayerEndorsements {
                         tee_certificate: vcek_cert,
                         stage0: None,
+                        crls: vec![],
+                        asvk_certificate: vec![],
                     }),
                     kernel_layer: Some(KernelLayerEndorsements {
                         kernel: None,
@@ -197,6 +199,9 @@ fn create_oc_endorsements_reference_values(
                         turin: Some(TcbVersionReferenceValue { r#type: Some(tcb_version_reference_value::Type::Minimum(tcb_version)) }),
                         allow_debug: false,
                         check_vcek_cert_expiry: true,
+                        require_crls: false,
+                        reject_stale_crls: false,
+                        allowed_signing_keys: vec![],
                         stage0: Some(skip.clone()),
                     }),
                     ..Default::default()
@@ -223,6 +228,7 @@ fn create_oc_endorsements_reference_values(
                     binary: Some(binary_reference_value_for_endorser_pk(container_binary_vkey)),
                     configuration: Some(skip.clone()),
                 }),
+                additional_container_layers: vec![],
             })),
         },
     )
@@ -277,6 +283,8 @@ fn create_rk_endorsements_reference_values(
                         root_layer: Some(RootLayerEndorsements {
                             tee_certificate: vcek_cert,
                             stage0: None,
+                            crls: vec![],
+                            asvk_certificate: vec![],
                         }),
                         kernel_layer: Some(KernelLayerEndorsements {
                             kernel: None,
@@ -318,6 +326,9 @@ fn create_rk_endorsements_reference_values(
                             }),
                             allow_debug: false,
                             check_vcek_cert_expiry: true,
+                            require_crls: false,
+                            reject_stale_crls: false,
+                            allowed_signing_keys: vec![],
                             stage0: Some(skip.clone()),
                         }),
                         ..Default::default()
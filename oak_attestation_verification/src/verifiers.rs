@@ -17,23 +17,28 @@
 //! Provides verifiers based on verification policies.
 
 use alloc::{boxed::Box, sync::Arc, vec, vec::Vec};
+use core::borrow::Borrow;
 
 use anyhow::Context;
 use itertools::izip;
 use oak_attestation_verification_types::{
     policy::{EventPolicy, Policy},
+    report::{LayerReport, VerificationReport},
     verifier::AttestationVerifier,
 };
 use oak_proto_rust::oak::{
     attestation::v1::{
         attestation_results::Status, reference_values, AttestationResults, Endorsements,
-        EventAttestationResults, EventLog, Evidence, ReferenceValues,
+        EventAttestationResults, EventLog, Evidence, OakContainersReferenceValues, ReferenceValues,
+        RootLayerEvidence,
     },
     Variant,
 };
+use oak_sev_snp_attestation_report::AttestationReport;
 use oak_tdx_quote::TdxQuoteWrapper;
 use oak_time::{Clock, Instant};
 use sha2::{Digest, Sha384};
+use zerocopy::FromBytes;
 
 use crate::{
     intel::RtmrEmulator,
@@ -50,6 +55,9 @@ use crate::{
     IntelTdxPolicy,
 };
 
+/// Name of the layer which reports on the event log as a whole.
+const EVENT_LOG_LAYER: &str = "event_log";
+
 // Base AMD SEV-SNP verifier that validates AMD SEV-SNP platform authenticity
 // and configuration and the firmware measurement.
 // TODO: b/452736849 - Utilize the `BaseAmdSevSnpVerifier` for the transparent
@@ -94,6 +102,41 @@ impl BaseAmdSevSnpVerifier {
             .context("verifying firmware policy")?;
         Ok(firmware_results)
     }
+
+    // Reports on all layers like the verifiers do on success, including the
+    // event log of the given type.
+    fn report(
+        &self,
+        verification_time: Instant,
+        evidence: &Evidence,
+        endorsements: &Endorsements,
+        event_log_type: EventLogType,
+        event_log: Option<&EventLog>,
+        event_policies: &[Box<dyn EventPolicy>],
+    ) -> VerificationReport {
+        let mut layers = vec![
+            report_policy(
+                &self.platform_policy,
+                verification_time,
+                get_root_layer(evidence),
+                endorsements.platform.as_ref(),
+            ),
+            report_policy(
+                self.firmware_policy.as_ref(),
+                verification_time,
+                get_root_layer(evidence).and_then(extract_amd_sev_snp_initial_measurement),
+                endorsements.initial.as_ref(),
+            ),
+        ];
+        layers.extend(report_event_log(
+            verification_time,
+            event_log,
+            Some(verify_dice_chain(evidence, event_log_type).map(|_| ())),
+            &endorsements.events,
+            (!endorsements.events.is_empty()).then_some(event_policies),
+        ));
+        VerificationReport { layers }
+    }
 }
 
 /// Attestation verifier that verifies an attestation rooted in AMD SEV-SNP.
@@ -167,6 +210,17 @@ impl AttestationVerifier for AmdSevSnpDiceAttestationVerifier {
             ..Default::default()
         })
     }
+
+    fn report(&self, evidence: &Evidence, endorsements: &Endorsements) -> VerificationReport {
+        self.base_verifier.report(
+            self.clock.get_time(),
+            evidence,
+            endorsements,
+            EventLogType::OriginalEventLog,
+            evidence.event_log.as_ref(),
+            &self.event_policies,
+        )
+    }
 }
 
 pub struct AmdSevSnpTransparentDiceAttestationVerifier {
@@ -224,6 +278,17 @@ impl AttestationVerifier for AmdSevSnpTransparentDiceAttestationVerifier {
             ..Default::default()
         })
     }
+
+    fn report(&self, evidence: &Evidence, endorsements: &Endorsements) -> VerificationReport {
+        self.base_verifier.report(
+            self.clock.get_time(),
+            evidence,
+            endorsements,
+            EventLogType::TransparentEventLog,
+            evidence.transparent_event_log.as_ref(),
+            &self.event_policies,
+        )
+    }
 }
 
 /// Attestation verifier that verifies an attestation rooted in Intel TDX.
@@ -274,18 +339,11 @@ impl AttestationVerifier for IntelTdxAttestationVerifier {
             .verify(verification_time, measurement, firmware_endorsement)
             .context("verifying firmware policy")?;
 
-        let wrapper = TdxQuoteWrapper::new(root_layer.remote_attestation_report.as_slice());
-        let expected = wrapper.parse_quote().expect("invalid quote").body.rtmr_2;
-
         let event_log =
             evidence.event_log.as_ref().ok_or_else(|| anyhow::anyhow!("no event log"))?;
 
         // Verify integrity of the event log.
-        let mut rtmr_2 = RtmrEmulator::new();
-        for entry in event_log.encoded_events.as_slice().iter() {
-            rtmr_2.extend(&Sha384::digest(entry.as_slice()).into());
-        }
-        anyhow::ensure!(rtmr_2.get_state() == expected, "event log integrity check failed");
+        verify_intel_tdx_event_log_integrity(root_layer, event_log)?;
 
         // Verify event log and event endorsements with corresponding policies.
         let mut event_attestation_results = Vec::new();
@@ -311,6 +369,36 @@ impl AttestationVerifier for IntelTdxAttestationVerifier {
             ..Default::default()
         })
     }
+
+    fn report(&self, evidence: &Evidence, endorsements: &Endorsements) -> VerificationReport {
+        let verification_time = self.clock.get_time();
+        let integrity = get_root_layer(evidence).and_then(|root_layer| {
+            let event_log = evidence.event_log.as_ref().context("no event log")?;
+            verify_intel_tdx_event_log_integrity(root_layer, event_log)
+        });
+        let mut layers = vec![
+            report_policy(
+                &self.platform_policy,
+                verification_time,
+                get_root_layer(evidence),
+                endorsements.platform.as_ref(),
+            ),
+            report_policy(
+                self.firmware_policy.as_ref(),
+                verification_time,
+                get_root_layer(evidence).and_then(extract_intel_tdx_initial_measurement),
+                endorsements.initial.as_ref(),
+            ),
+        ];
+        layers.extend(report_event_log(
+            verification_time,
+            evidence.event_log.as_ref(),
+            Some(integrity),
+            &endorsements.events,
+            (!endorsements.events.is_empty()).then_some(self.event_policies.as_slice()),
+        ));
+        VerificationReport { layers }
+    }
 }
 
 /// Attestation verifier for cases when there is no hardware root of trust.
@@ -375,6 +463,24 @@ impl AttestationVerifier for InsecureAttestationVerifier {
             ..Default::default()
         })
     }
+
+    fn report(&self, evidence: &Evidence, endorsements: &Endorsements) -> VerificationReport {
+        let verification_time = self.clock.get_time();
+        let mut layers = vec![report_policy(
+            &self.insecure_policy,
+            verification_time,
+            get_root_layer(evidence),
+            endorsements.platform.as_ref(),
+        )];
+        layers.extend(report_event_log(
+            verification_time,
+            evidence.event_log.as_ref(),
+            Some(verify_dice_chain(evidence, EventLogType::OriginalEventLog).map(|_| ())),
+            &endorsements.events,
+            (!endorsements.events.is_empty()).then_some(self.event_policies.as_slice()),
+        ));
+        VerificationReport { layers }
+    }
 }
 
 // Attestation verifier that only verifies the EventLog, i.e. it doesn't verify
@@ -422,6 +528,38 @@ impl AttestationVerifier for EventLogVerifier {
             ..Default::default()
         })
     }
+
+    fn report(&self, evidence: &Evidence, endorsements: &Endorsements) -> VerificationReport {
+        let layers = report_event_log(
+            self.clock.get_time(),
+            evidence.event_log.as_ref(),
+            None,
+            &endorsements.events,
+            Some(self.event_policies.as_slice()),
+        );
+        VerificationReport { layers }
+    }
+}
+
+// Creates the event policies for Oak Containers: the kernel and system layers
+// are followed by one container layer per container of the enclave.
+fn oak_containers_event_policies(
+    rvs: &OakContainersReferenceValues,
+) -> anyhow::Result<Vec<Box<dyn Policy<[u8]>>>> {
+    let kernel_policy =
+        KernelPolicy::new(rvs.kernel_layer.as_ref().context("no kernel layer reference values")?);
+    let system_policy =
+        SystemPolicy::new(rvs.system_layer.as_ref().context("no system layer reference values")?);
+    let container_policy = ContainerPolicy::new(
+        rvs.container_layer.as_ref().context("no container layer reference values")?,
+    );
+    let mut event_policies: Vec<Box<dyn Policy<[u8]>>> =
+        vec![Box::new(kernel_policy), Box::new(system_policy)];
+    for container_layer in &rvs.additional_container_layers {
+        event_policies.push(Box::new(ContainerPolicy::new(container_layer)));
+    }
+    event_policies.push(Box::new(container_policy));
+    Ok(event_policies)
 }
 
 // Creates an AMD SEV-SNP verifier from reference values.
@@ -436,17 +574,7 @@ pub fn create_amd_verifier<T: Clock + 'static>(
             let platform_policy = AmdSevSnpPolicy::new(amd);
             let firmware_policy =
                 FirmwarePolicy::new(amd.stage0.as_ref().context("no stage0 reference value")?);
-            let kernel_policy = KernelPolicy::new(
-                rvs.kernel_layer.as_ref().context("no kernel layer reference values")?,
-            );
-            let system_policy = SystemPolicy::new(
-                rvs.system_layer.as_ref().context("no system layer reference values")?,
-            );
-            let container_policy = ContainerPolicy::new(
-                rvs.container_layer.as_ref().context("no container layer reference values")?,
-            );
-            let event_policies: Vec<Box<dyn Policy<[u8]>>> =
-                vec![Box::new(kernel_policy), Box::new(system_policy), Box::new(container_policy)];
+            let event_policies = oak_containers_event_policies(rvs)?;
 
             Ok(AmdSevSnpDiceAttestationVerifier::new(
                 platform_policy,
@@ -495,17 +623,7 @@ pub fn create_intel_tdx_verifier<T: Clock + 'static>(
             let firmware_policy = FirmwarePolicy::new(
                 intel_rvs.stage0.as_ref().context("no stage0 reference value")?,
             );
-            let kernel_policy = KernelPolicy::new(
-                rvs.kernel_layer.as_ref().context("no kernel layer reference values")?,
-            );
-            let system_policy = SystemPolicy::new(
-                rvs.system_layer.as_ref().context("no system layer reference values")?,
-            );
-            let container_policy = ContainerPolicy::new(
-                rvs.container_layer.as_ref().context("no container layer reference values")?,
-            );
-            let event_policies: Vec<Box<dyn Policy<[u8]>>> =
-                vec![Box::new(kernel_policy), Box::new(system_policy), Box::new(container_policy)];
+            let event_policies = oak_containers_event_policies(rvs)?;
 
             Ok(IntelTdxAttestationVerifier::new(
                 platform_policy,
@@ -531,17 +649,7 @@ pub fn create_insecure_verifier<T: Clock + 'static>(
         Some(reference_values::Type::OakContainers(rvs)) => {
             let root_rvs = rvs.root_layer.as_ref().context("no root layer reference values")?;
             anyhow::ensure!(root_rvs.insecure.is_some(), "insecure not allowed");
-            let kernel_policy = KernelPolicy::new(
-                rvs.kernel_layer.as_ref().context("no kernel layer reference values")?,
-            );
-            let system_policy = SystemPolicy::new(
-                rvs.system_layer.as_ref().context("no system layer reference values")?,
-            );
-            let container_policy = ContainerPolicy::new(
-                rvs.container_layer.as_ref().context("no container layer reference values")?,
-            );
-            let event_policies: Vec<Box<dyn Policy<[u8]>>> =
-                vec![Box::new(kernel_policy), Box::new(system_policy), Box::new(container_policy)];
+            let event_policies = oak_containers_event_policies(rvs)?;
 
             Ok(InsecureAttestationVerifier::new(Arc::new(clock), event_policies))
         }
@@ -575,20 +683,7 @@ fn verify_event_log(
     event_endorsements: &[Variant],
     policies: &[Box<dyn EventPolicy>],
 ) -> anyhow::Result<Vec<EventAttestationResults>> {
-    if policies.len() != event_log.encoded_events.len() {
-        anyhow::bail!(
-            "number of policies ({}) is not equal to the event log length ({})",
-            policies.len(),
-            event_log.encoded_events.len()
-        );
-    }
-    if event_log.encoded_events.len() < event_endorsements.len() {
-        anyhow::bail!(
-            "event log length ({}) is smaller than the number of endorsements ({})",
-            event_log.encoded_events.len(),
-            event_endorsements.len()
-        );
-    }
+    verify_event_log_length(event_log, event_endorsements, policies)?;
 
     // Pad `event_endorsements` with an empty [`Variant`] to the same length as the
     // event log.
@@ -609,3 +704,132 @@ fn verify_event_log(
         })
         .collect::<Result<Vec<EventAttestationResults>, anyhow::Error>>()
 }
+
+/// Verifies that there is a policy for every event in the event log, and at
+/// most one endorsement.
+fn verify_event_log_length(
+    event_log: &EventLog,
+    event_endorsements: &[Variant],
+    policies: &[Box<dyn EventPolicy>],
+) -> anyhow::Result<()> {
+    if policies.len() != event_log.encoded_events.len() {
+        anyhow::bail!(
+            "number of policies ({}) is not equal to the event log length ({})",
+            policies.len(),
+            event_log.encoded_events.len()
+        );
+    }
+    if event_log.encoded_events.len() < event_endorsements.len() {
+        anyhow::bail!(
+            "event log length ({}) is smaller than the number of endorsements ({})",
+            event_log.encoded_events.len(),
+            event_endorsements.len()
+        );
+    }
+    Ok(())
+}
+
+/// Reports on an event log like [`verify_event_log`] verifies it. The event
+/// log layer records the presence, the optional `integrity` check and the
+/// length of the event log. It is followed by the layers of the event
+/// policies, unless `policies` is `None`.
+fn report_event_log(
+    verification_time: Instant,
+    event_log: Option<&EventLog>,
+    integrity: Option<anyhow::Result<()>>,
+    event_endorsements: &[Variant],
+    policies: Option<&[Box<dyn EventPolicy>]>,
+) -> Vec<LayerReport> {
+    let mut event_log_report = LayerReport::new(EVENT_LOG_LAYER);
+    let event_log = event_log_report.check("evidence", event_log.context("no event log"));
+    if let Some(integrity) = integrity {
+        event_log_report.record("integrity", integrity);
+    }
+    let (Some(event_log), Some(policies)) = (event_log, policies) else {
+        return vec![event_log_report];
+    };
+    let length = verify_event_log_length(event_log, event_endorsements, policies);
+    if event_log_report.check("length", length).is_none() {
+        return vec![event_log_report];
+    }
+
+    let empty_endorsement = Variant::default();
+    let padded_event_endorsements =
+        event_endorsements.iter().chain(core::iter::repeat(&empty_endorsement));
+    let mut reports = vec![event_log_report];
+    reports.extend(
+        izip!(policies.iter(), event_log.encoded_events.iter(), padded_event_endorsements).map(
+            |(event_policy, event, event_endorsement)| {
+                event_policy.report(verification_time, event, event_endorsement)
+            },
+        ),
+    );
+    reports
+}
+
+/// Returns the report of `policy`, or a report with a single failed check if
+/// the evidence or the endorsement for the policy is missing.
+fn report_policy<E: ?Sized, P: Policy<E> + ?Sized>(
+    policy: &P,
+    verification_time: Instant,
+    evidence: anyhow::Result<impl Borrow<E>>,
+    endorsement: Option<&Variant>,
+) -> LayerReport {
+    match (evidence, endorsement) {
+        (Ok(evidence), Some(endorsement)) => {
+            policy.report(verification_time, evidence.borrow(), endorsement)
+        }
+        (evidence, _) => {
+            let mut report = LayerReport::new(policy.layer());
+            match evidence {
+                Ok(_) => report.record("endorsement", Err(anyhow::anyhow!("no endorsement"))),
+                Err(err) => report.record("evidence", Err(err)),
+            };
+            report
+        }
+    }
+}
+
+fn get_root_layer(evidence: &Evidence) -> anyhow::Result<&RootLayerEvidence> {
+    evidence.root_layer.as_ref().context("no root layer in evidence")
+}
+
+/// Extracts the initial measurement from an AMD SEV-SNP attestation report
+/// without verifying the report, which is up to the platform policy.
+fn extract_amd_sev_snp_initial_measurement(
+    root_layer: &RootLayerEvidence,
+) -> anyhow::Result<Vec<u8>> {
+    let report = AttestationReport::ref_from_bytes(&root_layer.remote_attestation_report)
+        .map_err(|err| anyhow::anyhow!("invalid AMD SEV-SNP attestation report: {}", err))?;
+    Ok(report.data.measurement.to_vec())
+}
+
+/// Extracts the initial measurement from an Intel TDX quote without verifying
+/// the quote, which is up to the platform policy.
+fn extract_intel_tdx_initial_measurement(
+    root_layer: &RootLayerEvidence,
+) -> anyhow::Result<Vec<u8>> {
+    let quote = TdxQuoteWrapper::new(root_layer.remote_attestation_report.as_slice())
+        .parse_quote()
+        .map_err(|err| anyhow::anyhow!("invalid Intel TDX attestation quote: {}", err))?;
+    Ok(quote.body.mr_td.to_vec())
+}
+
+/// Verifies that the event log is captured in RTMR2 of the Intel TDX quote.
+fn verify_intel_tdx_event_log_integrity(
+    root_layer: &RootLayerEvidence,
+    event_log: &EventLog,
+) -> anyhow::Result<()> {
+    let wrapper = TdxQuoteWrapper::new(root_layer.remote_attestation_report.as_slice());
+    let expected = wrapper
+        .parse_quote()
+        .map_err(|err| anyhow::anyhow!("invalid Intel TDX attestation quote: {}", err))?
+        .body
+        .rtmr_2;
+    let mut rtmr_2 = RtmrEmulator::new();
+    for entry in event_log.encoded_events.as_slice().iter() {
+        rtmr_2.extend(&Sha384::digest(entry.as_slice()).into());
+    }
+    anyhow::ensure!(rtmr_2.get_state() == expected, "event log integrity check failed");
+    Ok(())
+}
@@ -20,7 +20,9 @@ use p256::ecdsa::{Signature as P256Signature, VerifyingKey as P256VerifyingKey};
 use rsa::{pss::Signature as RsaSignature, signature::Verifier, RsaPublicKey};
 use sha2::Sha384;
 use x509_cert::{
+    crl::CertificateList,
     der::{referenced::OwnedToRef, Encode},
+    spki::AlgorithmIdentifierOwned,
     Certificate,
 };
 
@@ -30,9 +32,26 @@ pub fn verify_cert_signature(signer: &Certificate, signee: &Certificate) -> anyh
         .tbs_certificate
         .to_der()
         .map_err(|_err| anyhow::anyhow!("could not extract message to verify RSA signature"))?;
-    let signature_bytes = signee.signature.raw_bytes();
+    verify_signature(signer, &signee.signature_algorithm, &message, signee.signature.raw_bytes())
+}
+
+/// Verifies that the certificate revocation list is correctly signed by the
+/// `signer`.
+pub fn verify_crl_signature(signer: &Certificate, crl: &CertificateList) -> anyhow::Result<()> {
+    let message = crl
+        .tbs_cert_list
+        .to_der()
+        .map_err(|_err| anyhow::anyhow!("could not extract message to verify CRL signature"))?;
+    verify_signature(signer, &crl.signature_algorithm, &message, crl.signature.raw_bytes())
+}
 
-    match signee.signature_algorithm.oid {
+fn verify_signature(
+    signer: &Certificate,
+    algorithm: &AlgorithmIdentifierOwned,
+    message: &[u8],
+    signature_bytes: &[u8],
+) -> anyhow::Result<()> {
+    match algorithm.oid {
         ID_RSASSA_PSS => {
             let verifying_key = {
                 let pubkey_info = signer.tbs_certificate.subject_public_key_info.owned_to_ref();
@@ -43,7 +62,7 @@ pub fn verify_cert_signature(signer: &Certificate, signee: &Certificate) -> anyh
             let signature = RsaSignature::try_from(signature_bytes)
                 .map_err(|_err| anyhow::anyhow!("could not extract RSA signature"))?;
             verifying_key
-                .verify(&message, &signature)
+                .verify(message, &signature)
                 .map_err(|_err| anyhow::anyhow!("signature verification failed"))
         }
         ECDSA_WITH_SHA_256 => {
@@ -56,16 +75,20 @@ pub fn verify_cert_signature(signer: &Certificate, signee: &Certificate) -> anyh
             let signature = P256Signature::from_der(signature_bytes)
                 .map_err(|_err| anyhow::anyhow!("could not extract NIST P-256 ECDSA signature"))?;
             verifying_key
-                .verify(&message, &signature)
+                .verify(message, &signature)
                 .map_err(|_err| anyhow::anyhow!("signature verification failed"))
         }
-        _ => Err(anyhow::anyhow!(
-            "unsupported signature algorithm: {:?}",
-            signee.signature_algorithm
-        )),
+        _ => Err(anyhow::anyhow!("unsupported signature algorithm: {:?}", algorithm)),
     }
 }
 
+/// Returns the time after which a certificate is no longer valid.
+pub fn get_certificate_not_after(cert: &Certificate) -> Instant {
+    Instant::from_unix_nanos(
+        cert.tbs_certificate.validity.not_after.to_unix_duration().as_nanos() as i128
+    )
+}
+
 /// Checks whether a certificate is considered valid at the specified time.
 pub fn check_certificate_validity(
     verification_time: Instant,
@@ -85,3 +108,36 @@ pub fn check_certificate_validity(
     }
     Ok(())
 }
+
+/// Checks whether a certificate revocation list is up to date at the specified
+/// time, i.e. whether it has been issued and its next update is not yet due.
+pub fn check_crl_validity(verification_time: Instant, crl: &CertificateList) -> anyhow::Result<()> {
+    let this_update = Instant::from_unix_nanos(
+        crl.tbs_cert_list.this_update.to_unix_duration().as_nanos() as i128,
+    );
+    if verification_time < this_update {
+        anyhow::bail!("CRL is not yet valid");
+    }
+    let next_update = crl
+        .tbs_cert_list
+        .next_update
+        .ok_or_else(|| anyhow::anyhow!("CRL has no next update time"))?;
+    if verification_time
+        > Instant::from_unix_nanos(next_update.to_unix_duration().as_nanos() as i128)
+    {
+        anyhow::bail!("CRL is stale");
+    }
+    Ok(())
+}
+
+/// Returns whether the certificate revocation list revokes `cert`.
+///
+/// The caller must ensure that the CRL was issued by the issuer of `cert`,
+/// since serial numbers are only unique per issuer.
+pub fn is_revoked(crl: &CertificateList, cert: &Certificate) -> bool {
+    crl.tbs_cert_list
+        .revoked_certificates
+        .iter()
+        .flatten()
+        .any(|revoked| revoked.serial_number == cert.tbs_certificate.serial_number)
+}
@@ -108,6 +108,8 @@ impl EndorsementData {
                 endorsement: Some(endorsement),
                 signature: Some(Signature { key_id: KEY_ID, raw: signature.clone() }),
                 rekor_log_entry: log_entry.clone(),
+                sigstore_bundle: Vec::new(),
+                rfc3161_timestamp: Vec::new(),
             },
             ref_value: EndorsementReferenceValue {
                 endorser: Some(VerifyingKeySet {
@@ -21,16 +21,17 @@ use oak_proto_rust::oak::{
     attestation::v1::{
         binary_reference_value, endorsements, extracted_evidence::EvidenceValues,
         kernel_binary_reference_value, reference_values, root_layer_data::Report,
-        tcb_version_reference_value, text_reference_value, AmdSevReferenceValues,
-        ApplicationLayerEndorsements, ApplicationLayerReferenceValues, BinaryReferenceValue,
-        CbReferenceValues, ContainerLayerEndorsements, ContainerLayerReferenceValues, Digests,
-        Endorsements, Evidence, ExtractedEvidence, InsecureReferenceValues,
-        KernelBinaryReferenceValue, KernelDigests, KernelLayerData, KernelLayerEndorsements,
-        KernelLayerReferenceValues, OakContainersEndorsements, OakContainersReferenceValues,
-        OakRestrictedKernelEndorsements, OakRestrictedKernelReferenceValues, ReferenceValues,
-        RootLayerData, RootLayerEndorsements, RootLayerReferenceValues, SkipVerification,
-        StringLiterals, SystemLayerEndorsements, SystemLayerReferenceValues, TcbVersion,
-        TcbVersionReferenceValue, TextReferenceValue,
+        tcb_version_reference_value, tdx_tcb_svn_reference_value, text_reference_value,
+        AmdSevReferenceValues, ApplicationLayerEndorsements, ApplicationLayerReferenceValues,
+        BinaryReferenceValue, CbReferenceValues, ContainerLayerEndorsements,
+        ContainerLayerReferenceValues, Digests, Endorsements, Evidence, ExtractedEvidence,
+        InsecureReferenceValues, IntelTdxReferenceValues, KernelBinaryReferenceValue,
+        KernelDigests, KernelLayerData, KernelLayerEndorsements, KernelLayerReferenceValues,
+        OakContainersEndorsements, OakContainersReferenceValues, OakRestrictedKernelEndorsements,
+        OakRestrictedKernelReferenceValues, ReferenceValues, RootLayerData, RootLayerEndorsements,
+        RootLayerReferenceValues, SkipVerification, StringLiterals, SystemLayerEndorsements,
+        SystemLayerReferenceValues, TcbVersion, TcbVersionReferenceValue, TdxTcbSvnReferenceValue,
+        TextReferenceValue,
     },
     RawDigest,
 };
@@ -39,7 +40,12 @@ use zerocopy::FromBytes;
 
 // Creates mock endorsements for an Oak Containers chain.
 pub fn create_oc_endorsements(vcek_cert: &[u8]) -> Endorsements {
-    let root_layer = RootLayerEndorsements { tee_certificate: vcek_cert.to_vec(), stage0: None };
+    let root_layer = RootLayerEndorsements {
+        tee_certificate: vcek_cert.to_vec(),
+        stage0: None,
+        crls: vec![],
+        asvk_certificate: vec![],
+    };
     let kernel_layer = KernelLayerEndorsements {
         kernel: None,
         kernel_cmd_line: None,
@@ -65,7 +71,12 @@ pub fn create_oc_endorsements(vcek_cert: &[u8]) -> Endorsements {
 
 // Creates mock endorsements for a restricted kernel application.
 pub fn create_rk_endorsements(vcek_cert: &[u8]) -> Endorsements {
-    let root_layer = RootLayerEndorsements { tee_certificate: vcek_cert.to_vec(), stage0: None };
+    let root_layer = RootLayerEndorsements {
+        tee_certificate: vcek_cert.to_vec(),
+        stage0: None,
+        crls: vec![],
+        asvk_certificate: vec![],
+    };
     let kernel_layer = KernelLayerEndorsements {
         kernel: None,
         kernel_cmd_line: None,
@@ -109,6 +120,9 @@ pub fn create_oc_reference_values() -> ReferenceValues {
         turin: Some(tcb_version_ref_value),
         allow_debug: false,
         check_vcek_cert_expiry: true,
+        require_crls: false,
+        reject_stale_crls: false,
+        allowed_signing_keys: vec![],
         stage0: Some(skip.clone()),
     };
 
@@ -138,6 +152,7 @@ pub fn create_oc_reference_values() -> ReferenceValues {
         kernel_layer: Some(kernel_layer),
         system_layer: Some(system_layer),
         container_layer: Some(container_layer),
+        additional_container_layers: vec![],
     };
     ReferenceValues { r#type: Some(reference_values::Type::OakContainers(vs)) }
 }
@@ -163,6 +178,9 @@ pub fn create_rk_reference_values() -> ReferenceValues {
         turin: Some(tcb_version_ref_value),
         allow_debug: false,
         check_vcek_cert_expiry: true,
+        require_crls: false,
+        reject_stale_crls: false,
+        allowed_signing_keys: vec![],
         stage0: Some(skip.clone()),
     };
 
@@ -276,6 +294,7 @@ pub fn create_reference_values_for_extracted_evidence(
                         })),
                     }),
                 }),
+                additional_container_layers: vec![],
             }))
         }
         EvidenceValues::Cb(_) => panic!("not yet supported"),
@@ -310,15 +329,29 @@ fn root_layer_reference_values_from_evidence(
                 }),
                 allow_debug: r.debug,
                 check_vcek_cert_expiry: true,
+                require_crls: false,
+                reject_stale_crls: false,
+                allowed_signing_keys: vec![],
             })
         }
         _ => None,
     });
-    let intel_tdx = if let Some(Report::Tdx(_)) = root_layer.report.clone() {
-        panic!("not yet supported");
-    } else {
-        None
-    };
+    let intel_tdx = root_layer.report.clone().and_then(|report| match report {
+        Report::Tdx(r) => Some(IntelTdxReferenceValues {
+            tee_tcb_svn: Some(TdxTcbSvnReferenceValue {
+                r#type: Some(tdx_tcb_svn_reference_value::Type::Minimum(
+                    r.tee_tcb_svn.expect("no TEE TCB SVN"),
+                )),
+            }),
+            allow_debug: r.debug,
+            stage0: Some(BinaryReferenceValue {
+                r#type: Some(binary_reference_value::Type::Digests(Digests {
+                    digests: vec![RawDigest { sha2_384: r.mr_td, ..Default::default() }],
+                })),
+            }),
+        }),
+        _ => None,
+    });
     let insecure = root_layer.report.and_then(|report| match report {
         Report::Fake(_) => Some(InsecureReferenceValues {}),
         _ => None,
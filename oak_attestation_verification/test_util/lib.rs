@@ -20,6 +20,8 @@ pub mod attestation_data;
 pub mod endorsement_data;
 mod factory;
 pub mod manipulate;
+pub mod sigstore_bundle_data;
+pub mod timestamp_authority_data;
 
 pub use attestation_data::AttestationData;
 pub use endorsement_data::EndorsementData;
@@ -29,3 +31,5 @@ pub use factory::{
     create_rk_reference_values, extract_attestation_report, get_cb_reference_values,
     get_oc_reference_values, get_rk_reference_values,
 };
+pub use sigstore_bundle_data::SigstoreBundleData;
+pub use timestamp_authority_data::TimestampAuthorityData;
@@ -0,0 +1,350 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// Provides Sigstore bundles over the endorsement test data. Since there is no
+// access to the private keys of real endorsements, the bundles are created on
+// the fly with fixed synthetic endorser and log keys.
+
+use base64::{prelude::BASE64_STANDARD, Engine as _};
+use digest_util::hash_sha2_256;
+use oak_proto_rust::oak::attestation::v1::{
+    endorsement::Format, verifying_key_reference_value, ClaimReferenceValue, Endorsement,
+    EndorsementReferenceValue, KeyType, SignedEndorsement, VerifyingKey,
+    VerifyingKeyReferenceValue, VerifyingKeySet,
+};
+use oak_time::Instant;
+use p256::{
+    ecdsa::{signature::Signer, Signature, SigningKey},
+    pkcs8::{EncodePublicKey, LineEnding},
+};
+use serde_json::{json, Value};
+
+use crate::EndorsementData;
+
+/// The log origin in the checkpoints of the bundles.
+pub const CHECKPOINT_ORIGIN: &str = "rekor.test.oak - 1";
+
+const IN_TOTO_PAYLOAD_TYPE: &str = "application/vnd.in-toto+json";
+const MEDIA_TYPE: &str = "application/vnd.dev.sigstore.bundle.v0.3+json";
+
+// The bundled log entry is the second of two entries in the log.
+const LOG_INDEX: u64 = 1;
+const OTHER_LEAF: &[u8] = b"another log entry";
+
+const ENDORSER_KEY_ID: u32 = 1;
+
+/// How the bundle signs the endorsement.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BundleContent {
+    MessageSignature,
+    DsseEnvelope,
+}
+
+/// Which Rekor version created the log entry of the bundle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RekorVersion {
+    V1,
+    V2,
+}
+
+pub struct SigstoreBundleData {
+    pub endorsement: Vec<u8>,
+    pub endorser_public_key: Vec<u8>,
+    pub rekor_public_key: Vec<u8>,
+    pub valid_not_before: Instant,
+    pub valid_not_after: Instant,
+
+    /// The time the log entry was integrated, within the validity of the
+    /// endorsement.
+    pub integrated_time: Instant,
+
+    endorser_key: SigningKey,
+    rekor_key: SigningKey,
+}
+
+impl SigstoreBundleData {
+    pub fn load() -> SigstoreBundleData {
+        let d = EndorsementData::load();
+        let endorser_key = SigningKey::from_slice(&[0x11; 32]).expect("invalid endorser key");
+        let rekor_key = SigningKey::from_slice(&[0x22; 32]).expect("invalid Rekor key");
+        SigstoreBundleData {
+            endorsement: d.endorsement.clone(),
+            endorser_public_key: public_key_der(&endorser_key),
+            rekor_public_key: public_key_der(&rekor_key),
+            valid_not_before: d.valid_not_before,
+            valid_not_after: d.valid_not_after,
+            integrated_time: d.make_valid_time(),
+            endorser_key,
+            rekor_key,
+        }
+    }
+
+    /// Creates a serialized bundle over the endorsement.
+    pub fn create_bundle(&self, content: BundleContent, version: RekorVersion) -> Vec<u8> {
+        serde_json::to_vec(&self.create_bundle_json(content, version))
+            .expect("serialization failed")
+    }
+
+    /// Creates a bundle over the endorsement as JSON value, for tests which
+    /// manipulate individual fields.
+    pub fn create_bundle_json(&self, content: BundleContent, version: RekorVersion) -> Value {
+        let (signature, content_json) = match content {
+            BundleContent::MessageSignature => {
+                let signature = sign(&self.endorser_key, &self.endorsement);
+                let json = json!({
+                    "messageSignature": {
+                        "messageDigest": {
+                            "algorithm": "SHA2_256",
+                            "digest": BASE64_STANDARD.encode(hash_sha2_256(&self.endorsement)),
+                        },
+                        "signature": BASE64_STANDARD.encode(&signature),
+                    }
+                });
+                (signature, json)
+            }
+            BundleContent::DsseEnvelope => {
+                let mut pae = format!(
+                    "DSSEv1 {} {} {} ",
+                    IN_TOTO_PAYLOAD_TYPE.len(),
+                    IN_TOTO_PAYLOAD_TYPE,
+                    self.endorsement.len()
+                )
+                .into_bytes();
+                pae.extend_from_slice(&self.endorsement);
+                let signature = sign(&self.endorser_key, &pae);
+                let json = json!({
+                    "dsseEnvelope": {
+                        "payload": BASE64_STANDARD.encode(&self.endorsement),
+                        "payloadType": IN_TOTO_PAYLOAD_TYPE,
+                        "signatures": [{ "sig": BASE64_STANDARD.encode(&signature), "keyid": "" }],
+                    }
+                });
+                (signature, json)
+            }
+        };
+
+        let body = serde_json::to_vec(&self.create_body(content, version, &signature))
+            .expect("serialization failed");
+        let mut bundle = json!({
+            "mediaType": MEDIA_TYPE,
+            "verificationMaterial": {
+                "publicKey": { "hint": "" },
+                "tlogEntries": [self.create_tlog_entry(content, version, &body)],
+            },
+        });
+        for (key, value) in content_json.as_object().expect("not an object") {
+            bundle[key] = value.clone();
+        }
+        bundle
+    }
+
+    /// Creates a signed endorsement carrying the bundle.
+    pub fn create_signed_endorsement(&self, bundle: Vec<u8>) -> SignedEndorsement {
+        SignedEndorsement {
+            endorsement: Some(Endorsement {
+                format: Format::EndorsementFormatJsonIntoto.into(),
+                serialized: self.endorsement.clone(),
+                ..Default::default()
+            }),
+            signature: None,
+            rekor_log_entry: Vec::new(),
+            sigstore_bundle: bundle,
+            rfc3161_timestamp: Vec::new(),
+        }
+    }
+
+    /// Returns the reference value for the synthetic endorser and log keys.
+    pub fn ref_value(&self) -> EndorsementReferenceValue {
+        EndorsementReferenceValue {
+            endorser: Some(VerifyingKeySet {
+                keys: [create_key(&self.endorser_public_key, ENDORSER_KEY_ID)].to_vec(),
+                ..Default::default()
+            }),
+            required_claims: Some(ClaimReferenceValue { claim_types: vec![] }),
+            rekor: Some(VerifyingKeyReferenceValue {
+                r#type: Some(verifying_key_reference_value::Type::Verify(self.rekor_key_set())),
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the key set with the synthetic log key.
+    pub fn rekor_key_set(&self) -> VerifyingKeySet {
+        VerifyingKeySet {
+            keys: [create_key(&self.rekor_public_key, 0)].to_vec(),
+            ..Default::default()
+        }
+    }
+
+    fn create_body(
+        &self,
+        content: BundleContent,
+        version: RekorVersion,
+        signature: &[u8],
+    ) -> Value {
+        let endorser_key_pem = self
+            .endorser_key
+            .verifying_key()
+            .to_public_key_pem(LineEnding::LF)
+            .expect("couldn't create PEM");
+        let signature = BASE64_STANDARD.encode(signature);
+        let verifier_v002 = json!({
+            "publicKey": { "rawBytes": BASE64_STANDARD.encode(&self.endorser_public_key) },
+            "keyDetails": "PKIX_ECDSA_P256_SHA_256",
+        });
+        match (content, version) {
+            (BundleContent::MessageSignature, RekorVersion::V1) => json!({
+                "apiVersion": "0.0.1",
+                "kind": "hashedrekord",
+                "spec": {
+                    "data": {
+                        "hash": {
+                            "algorithm": "sha256",
+                            "value": hex::encode(hash_sha2_256(&self.endorsement)),
+                        }
+                    },
+                    "signature": {
+                        "content": signature,
+                        "publicKey": { "content": BASE64_STANDARD.encode(endorser_key_pem) },
+                    },
+                },
+            }),
+            (BundleContent::MessageSignature, RekorVersion::V2) => json!({
+                "apiVersion": "0.0.2",
+                "kind": "hashedrekord",
+                "spec": {
+                    "hashedRekordV002": {
+                        "data": {
+                            "algorithm": "SHA2_256",
+                            "digest": BASE64_STANDARD.encode(hash_sha2_256(&self.endorsement)),
+                        },
+                        "signature": { "content": signature, "verifier": verifier_v002 },
+                    }
+                },
+            }),
+            (BundleContent::DsseEnvelope, RekorVersion::V1) => json!({
+                "apiVersion": "0.0.1",
+                "kind": "dsse",
+                "spec": {
+                    "envelopeHash": { "algorithm": "sha256", "value": hex::encode([0u8; 32]) },
+                    "payloadHash": {
+                        "algorithm": "sha256",
+                        "value": hex::encode(hash_sha2_256(&self.endorsement)),
+                    },
+                    "signatures": [{
+                        "signature": signature,
+                        "verifier": BASE64_STANDARD.encode(endorser_key_pem),
+                    }],
+                },
+            }),
+            (BundleContent::DsseEnvelope, RekorVersion::V2) => json!({
+                "apiVersion": "0.0.2",
+                "kind": "dsse",
+                "spec": {
+                    "dsseV002": {
+                        "payloadHash": {
+                            "algorithm": "SHA2_256",
+                            "digest": BASE64_STANDARD.encode(hash_sha2_256(&self.endorsement)),
+                        },
+                        "signatures": [{ "content": signature, "verifier": verifier_v002 }],
+                    }
+                },
+            }),
+        }
+    }
+
+    fn create_tlog_entry(
+        &self,
+        content: BundleContent,
+        version: RekorVersion,
+        body: &[u8],
+    ) -> Value {
+        let body = BASE64_STANDARD.encode(body);
+        let log_id = hash_sha2_256(&self.rekor_public_key);
+        let kind = match content {
+            BundleContent::MessageSignature => "hashedrekord",
+            BundleContent::DsseEnvelope => "dsse",
+        };
+
+        // A tree of size 2 whose root covers both leaves.
+        let other_leaf_hash = hash_leaf(OTHER_LEAF);
+        let leaf_hash = hash_leaf(&BASE64_STANDARD.decode(&body).expect("invalid body"));
+        let mut node = vec![1u8];
+        node.extend_from_slice(&other_leaf_hash);
+        node.extend_from_slice(&leaf_hash);
+        let root_hash = hash_sha2_256(&node);
+
+        let mut entry = json!({
+            "logIndex": LOG_INDEX.to_string(),
+            "logId": { "keyId": BASE64_STANDARD.encode(log_id) },
+            "kindVersion": { "kind": kind },
+            "inclusionProof": {
+                "logIndex": LOG_INDEX.to_string(),
+                "rootHash": BASE64_STANDARD.encode(root_hash),
+                "treeSize": "2",
+                "hashes": [BASE64_STANDARD.encode(other_leaf_hash)],
+                "checkpoint": { "envelope": self.create_checkpoint(2, &root_hash) },
+            },
+            "canonicalizedBody": body,
+        });
+        match version {
+            RekorVersion::V1 => {
+                let integrated_time = self.integrated_time.into_unix_seconds();
+                let canonical = format!(
+                    r#"{{"body":"{body}","integratedTime":{integrated_time},"logID":"{}","logIndex":{LOG_INDEX}}}"#,
+                    hex::encode(log_id)
+                );
+                let set = sign(&self.rekor_key, canonical.as_bytes());
+                entry["kindVersion"]["version"] = json!("0.0.1");
+                entry["integratedTime"] = json!(integrated_time.to_string());
+                entry["inclusionPromise"] =
+                    json!({ "signedEntryTimestamp": BASE64_STANDARD.encode(set) });
+            }
+            RekorVersion::V2 => {
+                entry["kindVersion"]["version"] = json!("0.0.2");
+            }
+        }
+        entry
+    }
+
+    /// Creates a checkpoint in signed note format, signed by the log key.
+    pub fn create_checkpoint(&self, tree_size: u64, root_hash: &[u8]) -> String {
+        let text =
+            format!("{CHECKPOINT_ORIGIN}\n{tree_size}\n{}\n", BASE64_STANDARD.encode(root_hash));
+        let mut signature = hash_sha2_256(&self.rekor_public_key)[..4].to_vec();
+        signature.extend_from_slice(&sign(&self.rekor_key, text.as_bytes()));
+        format!("{text}\n\u{2014} rekor.test.oak {}\n", BASE64_STANDARD.encode(signature))
+    }
+}
+
+fn sign(key: &SigningKey, data: &[u8]) -> Vec<u8> {
+    let signature: Signature = key.sign(data);
+    signature.to_der().as_bytes().to_vec()
+}
+
+fn public_key_der(key: &SigningKey) -> Vec<u8> {
+    key.verifying_key().to_public_key_der().expect("couldn't encode public key").into_vec()
+}
+
+fn hash_leaf(leaf: &[u8]) -> [u8; 32] {
+    let mut data = vec![0u8];
+    data.extend_from_slice(leaf);
+    hash_sha2_256(&data)
+}
+
+fn create_key(raw: &[u8], key_id: u32) -> VerifyingKey {
+    VerifyingKey { r#type: KeyType::EcdsaP256Sha256.into(), key_id, raw: raw.to_vec() }
+}
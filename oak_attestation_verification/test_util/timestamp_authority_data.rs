@@ -0,0 +1,307 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// Provides a synthetic RFC 3161 timestamp authority (TSA) consisting of a root
+// certificate and a timestamp signing certificate, both with ECDSA P-256 keys.
+// Timestamps are created on the fly over arbitrary data.
+
+use core::time::Duration;
+
+use digest_util::hash_sha2_256;
+use oak_proto_rust::oak::attestation::v1::TimestampAuthorityReferenceValue;
+use oak_time::{make_instant, Instant};
+use p256::{
+    ecdsa::{signature::Signer, Signature, SigningKey},
+    pkcs8::EncodePublicKey,
+};
+use x509_cert::der::{asn1::ObjectIdentifier, DateTime, Encode};
+
+const TAG_BOOLEAN: u8 = 0x01;
+const TAG_INTEGER: u8 = 0x02;
+const TAG_BIT_STRING: u8 = 0x03;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_UTF8_STRING: u8 = 0x0c;
+const TAG_GENERALIZED_TIME: u8 = 0x18;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_SET: u8 = 0x31;
+const TAG_CONTEXT_0: u8 = 0xa0;
+const TAG_CONTEXT_3: u8 = 0xa3;
+
+const ID_SIGNED_DATA: &str = "1.2.840.113549.1.7.2";
+const ID_CT_TST_INFO: &str = "1.2.840.113549.1.9.16.1.4";
+const ID_CONTENT_TYPE: &str = "1.2.840.113549.1.9.3";
+const ID_MESSAGE_DIGEST: &str = "1.2.840.113549.1.9.4";
+const ID_SHA256: &str = "2.16.840.1.101.3.4.2.1";
+const ID_ECDSA_WITH_SHA256: &str = "1.2.840.10045.4.3.2";
+const ID_COMMON_NAME: &str = "2.5.4.3";
+const ID_BASIC_CONSTRAINTS: &str = "2.5.29.19";
+const ID_EXTENDED_KEY_USAGE: &str = "2.5.29.37";
+const ID_KP_TIME_STAMPING: &str = "1.3.6.1.5.5.7.3.8";
+const TSA_POLICY: &str = "1.3.6.1.4.1.57264.2";
+
+const ROOT_SERIAL_NUMBER: u8 = 1;
+const SIGNER_SERIAL_NUMBER: u8 = 2;
+
+pub struct TimestampAuthorityData {
+    /// The ASN.1 DER encoded root certificate.
+    pub root_certificate: Vec<u8>,
+
+    /// The ASN.1 DER encoded timestamp signing certificate.
+    pub signer_certificate: Vec<u8>,
+
+    /// Both certificates are valid within this time range.
+    pub valid_not_before: Instant,
+    pub valid_not_after: Instant,
+
+    signer_key: SigningKey,
+}
+
+impl TimestampAuthorityData {
+    /// Creates a TSA whose keys are derived from `seed`, such that different
+    /// seeds result in unrelated authorities.
+    pub fn load(seed: u8) -> TimestampAuthorityData {
+        let root_key = SigningKey::from_slice(&[seed; 32]).expect("invalid root key");
+        let signer_key =
+            SigningKey::from_slice(&[seed.wrapping_add(1); 32]).expect("invalid signer key");
+        let valid_not_before = make_instant!("2024-01-01T00:00:00Z");
+        let valid_not_after = make_instant!("2026-01-01T00:00:00Z");
+
+        let root_name = name("Oak Test TSA Root");
+        let root_extensions =
+            [extension(ID_BASIC_CONSTRAINTS, true, &tlv(TAG_SEQUENCE, &tlv(TAG_BOOLEAN, &[0xff])))];
+        let root_certificate = certificate(
+            ROOT_SERIAL_NUMBER,
+            &root_name,
+            &root_name,
+            (valid_not_before, valid_not_after),
+            &root_key,
+            &root_key,
+            &root_extensions,
+        );
+        let signer_extensions =
+            [extension(ID_EXTENDED_KEY_USAGE, true, &tlv(TAG_SEQUENCE, &oid(ID_KP_TIME_STAMPING)))];
+        let signer_certificate = certificate(
+            SIGNER_SERIAL_NUMBER,
+            &root_name,
+            &name("Oak Test TSA"),
+            (valid_not_before, valid_not_after),
+            &signer_key,
+            &root_key,
+            &signer_extensions,
+        );
+
+        TimestampAuthorityData {
+            root_certificate,
+            signer_certificate,
+            valid_not_before,
+            valid_not_after,
+            signer_key,
+        }
+    }
+
+    /// Returns a reference value which trusts the root of this TSA.
+    pub fn ref_value(&self) -> TimestampAuthorityReferenceValue {
+        TimestampAuthorityReferenceValue {
+            root_certificates: vec![self.root_certificate.clone()],
+            ..Default::default()
+        }
+    }
+
+    /// Creates a TimeStampToken over `data` certifying `time`, optionally
+    /// embedding the signing certificate.
+    pub fn create_timestamp_token(&self, data: &[u8], time: Instant, embed: bool) -> Vec<u8> {
+        let tst_info = tlv(
+            TAG_SEQUENCE,
+            &[
+                integer(1),
+                oid(TSA_POLICY),
+                tlv(
+                    TAG_SEQUENCE,
+                    &[algorithm(ID_SHA256), tlv(TAG_OCTET_STRING, &hash_sha2_256(data))].concat(),
+                ),
+                integer(42),
+                tlv(TAG_GENERALIZED_TIME, generalized_time(time).as_bytes()),
+            ]
+            .concat(),
+        );
+
+        let signed_attributes = [
+            tlv(TAG_SEQUENCE, &[oid(ID_CONTENT_TYPE), tlv(TAG_SET, &oid(ID_CT_TST_INFO))].concat()),
+            tlv(
+                TAG_SEQUENCE,
+                &[
+                    oid(ID_MESSAGE_DIGEST),
+                    tlv(TAG_SET, &tlv(TAG_OCTET_STRING, &hash_sha2_256(&tst_info))),
+                ]
+                .concat(),
+            ),
+        ]
+        .concat();
+        let signature = sign(&self.signer_key, &tlv(TAG_SET, &signed_attributes));
+        let signer_info = tlv(
+            TAG_SEQUENCE,
+            &[
+                integer(1),
+                tlv(
+                    TAG_SEQUENCE,
+                    &[name("Oak Test TSA Root"), integer(SIGNER_SERIAL_NUMBER)].concat(),
+                ),
+                algorithm(ID_SHA256),
+                tlv(TAG_CONTEXT_0, &signed_attributes),
+                algorithm(ID_ECDSA_WITH_SHA256),
+                tlv(TAG_OCTET_STRING, &signature),
+            ]
+            .concat(),
+        );
+
+        let certificates =
+            if embed { tlv(TAG_CONTEXT_0, &self.signer_certificate) } else { Vec::new() };
+        let signed_data = tlv(
+            TAG_SEQUENCE,
+            &[
+                integer(3),
+                tlv(TAG_SET, &algorithm(ID_SHA256)),
+                tlv(
+                    TAG_SEQUENCE,
+                    &[oid(ID_CT_TST_INFO), tlv(TAG_CONTEXT_0, &tlv(TAG_OCTET_STRING, &tst_info))]
+                        .concat(),
+                ),
+                certificates,
+                tlv(TAG_SET, &signer_info),
+            ]
+            .concat(),
+        );
+        tlv(TAG_SEQUENCE, &[oid(ID_SIGNED_DATA), tlv(TAG_CONTEXT_0, &signed_data)].concat())
+    }
+
+    /// Creates a TimeStampResp with the given status around a TimeStampToken
+    /// over `data` certifying `time`.
+    pub fn create_timestamp_response(&self, data: &[u8], time: Instant, status: u8) -> Vec<u8> {
+        let token = self.create_timestamp_token(data, time, true);
+        tlv(TAG_SEQUENCE, &[tlv(TAG_SEQUENCE, &integer(status)), token].concat())
+    }
+}
+
+/// Encodes a DER tag-length-value triple.
+fn tlv(tag: u8, value: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![tag];
+    match value.len() {
+        len @ 0..=0x7f => encoded.push(len as u8),
+        len => {
+            let len_bytes: Vec<u8> =
+                len.to_be_bytes().into_iter().skip_while(|b| *b == 0).collect();
+            encoded.push(0x80 | len_bytes.len() as u8);
+            encoded.extend(len_bytes);
+        }
+    }
+    encoded.extend_from_slice(value);
+    encoded
+}
+
+fn integer(value: u8) -> Vec<u8> {
+    assert!(value < 0x80, "only small integers are supported");
+    tlv(TAG_INTEGER, &[value])
+}
+
+fn oid(value: &str) -> Vec<u8> {
+    ObjectIdentifier::new_unwrap(value).to_der().expect("couldn't encode OID")
+}
+
+fn algorithm(value: &str) -> Vec<u8> {
+    tlv(TAG_SEQUENCE, &oid(value))
+}
+
+fn name(common_name: &str) -> Vec<u8> {
+    let attribute = tlv(
+        TAG_SEQUENCE,
+        &[oid(ID_COMMON_NAME), tlv(TAG_UTF8_STRING, common_name.as_bytes())].concat(),
+    );
+    tlv(TAG_SEQUENCE, &tlv(TAG_SET, &attribute))
+}
+
+fn extension(id: &str, critical: bool, value: &[u8]) -> Vec<u8> {
+    let critical = if critical { tlv(TAG_BOOLEAN, &[0xff]) } else { Vec::new() };
+    tlv(TAG_SEQUENCE, &[oid(id), critical, tlv(TAG_OCTET_STRING, value)].concat())
+}
+
+fn generalized_time(time: Instant) -> String {
+    let millis = time.into_unix_millis();
+    let date_time =
+        DateTime::from_unix_duration(Duration::from_millis(millis as u64)).expect("invalid time");
+    format!(
+        "{:04}{:02}{:02}{:02}{:02}{:02}.{:03}Z",
+        date_time.year(),
+        date_time.month(),
+        date_time.day(),
+        date_time.hour(),
+        date_time.minutes(),
+        date_time.seconds(),
+        millis % 1000
+    )
+}
+
+fn certificate(
+    serial_number: u8,
+    issuer: &[u8],
+    subject: &[u8],
+    (not_before, not_after): (Instant, Instant),
+    subject_key: &SigningKey,
+    issuer_key: &SigningKey,
+    extensions: &[Vec<u8>],
+) -> Vec<u8> {
+    let validity = tlv(
+        TAG_SEQUENCE,
+        &[
+            tlv(TAG_GENERALIZED_TIME, generalized_time(not_before).replace(".000", "").as_bytes()),
+            tlv(TAG_GENERALIZED_TIME, generalized_time(not_after).replace(".000", "").as_bytes()),
+        ]
+        .concat(),
+    );
+    let public_key = subject_key
+        .verifying_key()
+        .to_public_key_der()
+        .expect("couldn't encode public key")
+        .into_vec();
+    let tbs_certificate = tlv(
+        TAG_SEQUENCE,
+        &[
+            tlv(TAG_CONTEXT_0, &integer(2)),
+            integer(serial_number),
+            algorithm(ID_ECDSA_WITH_SHA256),
+            issuer.to_vec(),
+            validity,
+            subject.to_vec(),
+            public_key,
+            tlv(TAG_CONTEXT_3, &tlv(TAG_SEQUENCE, &extensions.concat())),
+        ]
+        .concat(),
+    );
+    let signature = sign(issuer_key, &tbs_certificate);
+    tlv(
+        TAG_SEQUENCE,
+        &[
+            tbs_certificate,
+            algorithm(ID_ECDSA_WITH_SHA256),
+            tlv(TAG_BIT_STRING, &[&[0u8][..], &signature].concat()),
+        ]
+        .concat(),
+    )
+}
+
+fn sign(key: &SigningKey, data: &[u8]) -> Vec<u8> {
+    let signature: Signature = key.sign(data);
+    signature.to_der().as_bytes().to_vec()
+}
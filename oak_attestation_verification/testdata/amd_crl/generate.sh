@@ -0,0 +1,101 @@
+#!/bin/bash
+#
+# Copyright 2025 The Project Oak Authors
+#
+# Licensed under the Apache License, Version 2.0 (the "License");
+# you may not use this file except in compliance with the License.
+# You may obtain a copy of the License at
+#
+#     http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing, software
+# distributed under the License is distributed on an "AS IS" BASIS,
+# WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+# See the License for the specific language governing permissions and
+# limitations under the License.
+#
+
+# Generates a test ARK -> ASK -> VCEK certificate chain that mimics the AMD
+# one, and CRLs issued by the test ARK and ASK. AMD doesn't publish CRLs that
+# revoke any of its certificates, so revocation can only be tested with our
+# own chain. Requires OpenSSL 3.4 or later.
+#
+# Usage: ./generate.sh (run from this directory)
+
+set -o errexit
+set -o nounset
+set -o pipefail
+
+readonly NOT_BEFORE=20250101000000Z
+readonly NOT_AFTER=20450101000000Z
+readonly CRL_LAST_UPDATE=20250901000000Z
+readonly CRL_NEXT_UPDATE=20251201000000Z
+readonly PSS_OPTS=(-sigopt rsa_padding_mode:pss -sigopt rsa_pss_saltlen:48 -sha384)
+
+WORK_DIR="$(mktemp -d)"
+readonly WORK_DIR
+trap 'rm -rf "${WORK_DIR}"' EXIT
+
+openssl genpkey -algorithm RSA -pkeyopt rsa_keygen_bits:2048 -out "${WORK_DIR}/ark.key"
+openssl genpkey -algorithm RSA -pkeyopt rsa_keygen_bits:2048 -out "${WORK_DIR}/ask.key"
+openssl genpkey -algorithm EC -pkeyopt ec_paramgen_curve:P-384 -out "${WORK_DIR}/vcek.key"
+
+openssl req -x509 -new -key "${WORK_DIR}/ark.key" -subj "/CN=ARK-Test" \
+  -set_serial 1 -not_before "${NOT_BEFORE}" -not_after "${NOT_AFTER}" \
+  -addext "basicConstraints=critical,CA:TRUE" -addext "keyUsage=critical,keyCertSign,cRLSign" \
+  "${PSS_OPTS[@]}" -out ark.pem
+openssl req -x509 -new -key "${WORK_DIR}/ask.key" -subj "/CN=SEV-Test" \
+  -CA ark.pem -CAkey "${WORK_DIR}/ark.key" \
+  -set_serial 2 -not_before "${NOT_BEFORE}" -not_after "${NOT_AFTER}" \
+  -addext "basicConstraints=critical,CA:TRUE" -addext "keyUsage=critical,keyCertSign,cRLSign" \
+  "${PSS_OPTS[@]}" -out ask.pem
+openssl req -x509 -new -key "${WORK_DIR}/vcek.key" -subj "/CN=SEV-VCEK-Test" \
+  -CA ask.pem -CAkey "${WORK_DIR}/ask.key" \
+  -set_serial 3 -not_before "${NOT_BEFORE}" -not_after "${NOT_AFTER}" \
+  "${PSS_OPTS[@]}" -out vcek.pem
+
+# Issues a CRL signed by `issuer` that revokes the certificates with the given
+# serial numbers (in hex).
+generate_crl() {
+  local issuer="$1"
+  local output="$2"
+  shift 2
+  local ca_dir="${WORK_DIR}/ca_${output}"
+  mkdir "${ca_dir}"
+  touch "${ca_dir}/index.txt"
+  for serial in "$@"; do
+    local subject
+    subject="$(openssl x509 -in "$(serial_to_cert "${serial}")" -noout -subject -nameopt compat | sed 's/^subject=//')"
+    printf 'R\t%s\t%s\t%s\tunknown\t%s\n' "${NOT_AFTER:2}" "${CRL_LAST_UPDATE:2}" "${serial}" \
+      "${subject}" >> "${ca_dir}/index.txt"
+  done
+  cat > "${ca_dir}/ca.cnf" <<CNF
+[ca]
+default_ca = test_ca
+[test_ca]
+database = ${ca_dir}/index.txt
+crlnumber = ${ca_dir}/crlnumber
+default_md = sha384
+CNF
+  echo 01 > "${ca_dir}/crlnumber"
+  openssl ca -gencrl -config "${ca_dir}/ca.cnf" -cert "${issuer}.pem" \
+    -keyfile "${WORK_DIR}/${issuer}.key" -crl_lastupdate "${CRL_LAST_UPDATE}" \
+    -crl_nextupdate "${CRL_NEXT_UPDATE}" \
+    -sigopt rsa_padding_mode:pss -sigopt rsa_pss_saltlen:48 -out "${ca_dir}/crl.pem"
+  openssl crl -in "${ca_dir}/crl.pem" -outform DER -out "${output}"
+}
+
+serial_to_cert() {
+  case "$1" in
+    02) echo ask.pem ;;
+    03) echo vcek.pem ;;
+    *) echo "unknown serial $1" >&2; return 1 ;;
+  esac
+}
+
+# A CRL that doesn't revoke anything.
+generate_crl ark ark_crl_empty.der
+# A CRL that revokes the ASK.
+generate_crl ark ark_crl_revoking_ask.der 02
+# A CRL that revokes the VCEK.
+generate_crl ask ask_crl_revoking_vcek.der 03
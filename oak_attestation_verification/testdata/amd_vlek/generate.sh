@@ -0,0 +1,88 @@
+#!/bin/bash
+#
+# Copyright 2025 The Project Oak Authors
+#
+# Licensed under the Apache License, Version 2.0 (the "License");
+# you may not use this file except in compliance with the License.
+# You may obtain a copy of the License at
+#
+#     http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing, software
+# distributed under the License is distributed on an "AS IS" BASIS,
+# WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+# See the License for the specific language governing permissions and
+# limitations under the License.
+#
+
+# Generates a test ARK -> ASVK -> VLEK certificate chain that mimics the AMD
+# one for AMD EPYC Milan, and a CRL issued by the test ARK that revokes the
+# ASVK. VLEKs are only provisioned to cloud providers, so VLEK-signed
+# attestation reports can only be tested with our own chain. The VLEK private
+# key is kept so that tests can sign attestation reports with it. Requires
+# OpenSSL 3.4 or later.
+#
+# Usage: ./generate.sh (run from this directory)
+
+set -o errexit
+set -o nounset
+set -o pipefail
+
+readonly NOT_BEFORE=20250101000000Z
+readonly NOT_AFTER=20450101000000Z
+readonly CRL_LAST_UPDATE=20250901000000Z
+readonly CRL_NEXT_UPDATE=20251201000000Z
+readonly PSS_OPTS=(-sigopt rsa_padding_mode:pss -sigopt rsa_pss_saltlen:48 -sha384)
+
+WORK_DIR="$(mktemp -d)"
+readonly WORK_DIR
+trap 'rm -rf "${WORK_DIR}"' EXIT
+
+openssl genpkey -algorithm RSA -pkeyopt rsa_keygen_bits:2048 -out "${WORK_DIR}/ark.key"
+openssl genpkey -algorithm RSA -pkeyopt rsa_keygen_bits:2048 -out "${WORK_DIR}/asvk.key"
+openssl genpkey -algorithm EC -pkeyopt ec_paramgen_curve:P-384 -out vlek.key
+
+openssl req -x509 -new -key "${WORK_DIR}/ark.key" -subj "/CN=ARK-Milan-Test" \
+  -set_serial 1 -not_before "${NOT_BEFORE}" -not_after "${NOT_AFTER}" \
+  -addext "basicConstraints=critical,CA:TRUE" -addext "keyUsage=critical,keyCertSign,cRLSign" \
+  "${PSS_OPTS[@]}" -out ark.pem
+openssl req -x509 -new -key "${WORK_DIR}/asvk.key" -subj "/CN=SEV-VLEK-Milan-Test" \
+  -CA ark.pem -CAkey "${WORK_DIR}/ark.key" \
+  -set_serial 2 -not_before "${NOT_BEFORE}" -not_after "${NOT_AFTER}" \
+  -addext "basicConstraints=critical,CA:TRUE" -addext "keyUsage=critical,keyCertSign,cRLSign" \
+  "${PSS_OPTS[@]}" -out asvk.pem
+
+# Unlike a VCEK, a VLEK has no hardware ID extension but identifies the cloud
+# provider instead. The TCB version is bootloader 3, TEE 0, SNP 20 and
+# microcode 209. See Table 11 of
+# https://www.amd.com/content/dam/amd/en/documents/epyc-technical-docs/specifications/57230.pdf
+openssl req -x509 -new -key vlek.key -subj "/CN=SEV-VLEK-Test" \
+  -CA asvk.pem -CAkey "${WORK_DIR}/asvk.key" \
+  -set_serial 3 -not_before "${NOT_BEFORE}" -not_after "${NOT_AFTER}" \
+  -addext "1.3.6.1.4.1.3704.1.2=ASN1:IA5STRING:Milan-B0" \
+  -addext "1.3.6.1.4.1.3704.1.3.1=ASN1:INTEGER:3" \
+  -addext "1.3.6.1.4.1.3704.1.3.2=ASN1:INTEGER:0" \
+  -addext "1.3.6.1.4.1.3704.1.3.3=ASN1:INTEGER:20" \
+  -addext "1.3.6.1.4.1.3704.1.3.8=ASN1:INTEGER:209" \
+  -addext "1.3.6.1.4.1.3704.1.5=ASN1:IA5STRING:Test-CSP" \
+  "${PSS_OPTS[@]}" -out vlek.pem
+
+# A CRL that revokes the ASVK.
+mkdir "${WORK_DIR}/ca"
+subject="$(openssl x509 -in asvk.pem -noout -subject -nameopt compat | sed 's/^subject=//')"
+printf 'R\t%s\t%s\t02\tunknown\t%s\n' "${NOT_AFTER:2}" "${CRL_LAST_UPDATE:2}" "${subject}" \
+  > "${WORK_DIR}/ca/index.txt"
+cat > "${WORK_DIR}/ca/ca.cnf" <<CNF
+[ca]
+default_ca = test_ca
+[test_ca]
+database = ${WORK_DIR}/ca/index.txt
+crlnumber = ${WORK_DIR}/ca/crlnumber
+default_md = sha384
+CNF
+echo 01 > "${WORK_DIR}/ca/crlnumber"
+openssl ca -gencrl -config "${WORK_DIR}/ca/ca.cnf" -cert ark.pem \
+  -keyfile "${WORK_DIR}/ark.key" -crl_lastupdate "${CRL_LAST_UPDATE}" \
+  -crl_nextupdate "${CRL_NEXT_UPDATE}" \
+  -sigopt rsa_padding_mode:pss -sigopt rsa_pss_saltlen:48 -out "${WORK_DIR}/crl.pem"
+openssl crl -in "${WORK_DIR}/crl.pem" -outform DER -out ark_crl_revoking_asvk.der
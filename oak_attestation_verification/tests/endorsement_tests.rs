@@ -14,13 +14,49 @@
 // limitations under the License.
 //
 
+use base64::{prelude::BASE64_STANDARD, Engine as _};
 use digest_util::raw_to_hex_digest;
 use oak_attestation_verification::verify_endorsement;
 use oak_proto_rust::oak::attestation::v1::{
-    verifying_key_reference_value, SkipVerification, VerifyingKeyReferenceValue,
+    verifying_key_reference_value, CheckpointConsistencyReferenceValue, SkipVerification,
+    TimestampReferenceValue, VerifyingKeyReferenceValue, VerifyingKeySet,
 };
 use oak_time::{Duration, Instant};
-use test_util::endorsement_data::EndorsementData;
+use rekor::{
+    bundle::{get_bundle_signatures, parse_sigstore_bundle},
+    log_entry::parse_rekor_log_entry,
+};
+use test_util::{
+    endorsement_data::EndorsementData,
+    sigstore_bundle_data::{BundleContent, RekorVersion, SigstoreBundleData},
+    TimestampAuthorityData,
+};
+
+fn get_rekor_key_set(d: &mut EndorsementData) -> &mut VerifyingKeySet {
+    match d
+        .ref_value
+        .rekor
+        .as_mut()
+        .expect("no verifying key reference value")
+        .r#type
+        .as_mut()
+        .expect("no key set")
+    {
+        verifying_key_reference_value::Type::Verify(ks) => ks,
+        _ => panic!("wrong reference value type"),
+    }
+}
+
+// Returns the checkpoint on which the inclusion proof of the log entry is
+// based.
+fn get_checkpoint(d: &EndorsementData) -> String {
+    parse_rekor_log_entry(&d.log_entry)
+        .expect("could not parse log entry")
+        .verification
+        .and_then(|v| v.inclusion_proof)
+        .expect("no inclusion proof")
+        .checkpoint
+}
 
 #[test]
 #[allow(deprecated)]
@@ -311,3 +347,337 @@ fn test_verify_endorsement_succeeds_with_no_log_entry_and_no_rekor_key() {
     );
     assert!(result.is_ok(), "{:?}", result);
 }
+
+#[test]
+fn test_verify_endorsement_succeeds_with_inclusion_proof() {
+    let mut d = EndorsementData::load();
+
+    get_rekor_key_set(&mut d).require_inclusion_proof = true;
+
+    let result = verify_endorsement(
+        d.make_valid_time().into_unix_millis(),
+        &d.signed_endorsement,
+        &d.ref_value,
+    );
+    assert!(result.is_ok(), "{:?}", result);
+}
+
+#[test]
+fn test_verify_endorsement_fails_with_tampered_inclusion_proof() {
+    let mut d = EndorsementData::load();
+
+    get_rekor_key_set(&mut d).require_inclusion_proof = true;
+    let log_entry = String::from_utf8(d.signed_endorsement.rekor_log_entry.clone()).unwrap();
+    d.signed_endorsement.rekor_log_entry = log_entry
+        .replace(
+            "fa1d3c5e0a5995707d0be8a05b58310bb65b85f07bbf42263c0613ec233ee0f9",
+            "fa1d3c5e0a5995707d0be8a05b58310bb65b85f07bbf42263c0613ec233ee0f8",
+        )
+        .into_bytes();
+
+    let result = verify_endorsement(
+        d.make_valid_time().into_unix_millis(),
+        &d.signed_endorsement,
+        &d.ref_value,
+    );
+    assert!(result.is_err(), "{:?}", result);
+}
+
+#[test]
+fn test_verify_endorsement_succeeds_with_consistent_checkpoint() {
+    let mut d = EndorsementData::load();
+
+    d.ref_value.rekor_checkpoint_consistency = Some(CheckpointConsistencyReferenceValue {
+        checkpoint: get_checkpoint(&d),
+        proof_hashes: vec![],
+    });
+
+    let result = verify_endorsement(
+        d.make_valid_time().into_unix_millis(),
+        &d.signed_endorsement,
+        &d.ref_value,
+    );
+    assert!(result.is_ok(), "{:?}", result);
+}
+
+#[test]
+fn test_verify_endorsement_fails_with_inconsistent_checkpoint() {
+    let mut d = EndorsementData::load();
+
+    d.ref_value.rekor_checkpoint_consistency = Some(CheckpointConsistencyReferenceValue {
+        checkpoint: get_checkpoint(&d),
+        proof_hashes: vec![vec![0; 32]],
+    });
+
+    let result = verify_endorsement(
+        d.make_valid_time().into_unix_millis(),
+        &d.signed_endorsement,
+        &d.ref_value,
+    );
+    assert!(result.is_err(), "{:?}", result);
+}
+
+#[test]
+fn test_verify_endorsement_fails_with_unsigned_trusted_checkpoint() {
+    let mut d = EndorsementData::load();
+
+    let checkpoint = get_checkpoint(&d);
+    let (text, _) = checkpoint.split_once("\n\n").expect("no signatures");
+    d.ref_value.rekor_checkpoint_consistency = Some(CheckpointConsistencyReferenceValue {
+        checkpoint: format!("{text}\n\n"),
+        proof_hashes: vec![],
+    });
+
+    let result = verify_endorsement(
+        d.make_valid_time().into_unix_millis(),
+        &d.signed_endorsement,
+        &d.ref_value,
+    );
+    assert!(result.is_err(), "{:?}", result);
+}
+
+#[test]
+fn test_verify_endorsement_succeeds_with_sigstore_bundle() {
+    let d = SigstoreBundleData::load();
+
+    for content in [BundleContent::MessageSignature, BundleContent::DsseEnvelope] {
+        for version in [RekorVersion::V1, RekorVersion::V2] {
+            let signed_endorsement = d.create_signed_endorsement(d.create_bundle(content, version));
+            let result = verify_endorsement(
+                d.integrated_time.into_unix_millis(),
+                &signed_endorsement,
+                &d.ref_value(),
+            );
+            assert!(result.is_ok(), "{content:?} {version:?}: {:?}", result);
+        }
+    }
+}
+
+#[test]
+fn test_verify_endorsement_fails_with_sigstore_bundle_by_other_endorser() {
+    let d = SigstoreBundleData::load();
+    let other = EndorsementData::load();
+    let signed_endorsement =
+        d.create_signed_endorsement(d.create_bundle(BundleContent::DsseEnvelope, RekorVersion::V2));
+    let mut ref_value = d.ref_value();
+    ref_value.endorser = other.ref_value.endorser;
+
+    let result =
+        verify_endorsement(d.integrated_time.into_unix_millis(), &signed_endorsement, &ref_value);
+    assert!(result.is_err(), "{:?}", result);
+}
+
+#[test]
+fn test_verify_endorsement_fails_with_sigstore_bundle_too_late() {
+    let d = SigstoreBundleData::load();
+    let too_late = d.valid_not_after + Duration::from_seconds(3_600);
+    let signed_endorsement = d.create_signed_endorsement(
+        d.create_bundle(BundleContent::MessageSignature, RekorVersion::V1),
+    );
+
+    let result =
+        verify_endorsement(too_late.into_unix_millis(), &signed_endorsement, &d.ref_value());
+    assert!(result.is_err(), "{:?}", result);
+}
+
+#[test]
+fn test_verify_endorsement_succeeds_with_sigstore_bundle_and_no_rekor_key() {
+    let d = SigstoreBundleData::load();
+    let mut bundle = d.create_bundle_json(BundleContent::DsseEnvelope, RekorVersion::V1);
+    bundle["verificationMaterial"]["tlogEntries"] = serde_json::json!([]);
+    let signed_endorsement =
+        d.create_signed_endorsement(serde_json::to_vec(&bundle).expect("serialization failed"));
+    let mut ref_value = d.ref_value();
+    ref_value.rekor = Some(VerifyingKeyReferenceValue {
+        r#type: Some(verifying_key_reference_value::Type::Skip(SkipVerification {})),
+    });
+
+    let result =
+        verify_endorsement(d.integrated_time.into_unix_millis(), &signed_endorsement, &ref_value);
+    assert!(result.is_ok(), "{:?}", result);
+}
+
+#[test]
+fn test_verify_endorsement_succeeds_with_sigstore_bundle_and_consistent_checkpoint() {
+    let d = SigstoreBundleData::load();
+    let bundle = d.create_bundle_json(BundleContent::MessageSignature, RekorVersion::V2);
+    let checkpoint = bundle["verificationMaterial"]["tlogEntries"][0]["inclusionProof"]
+        ["checkpoint"]["envelope"]
+        .as_str()
+        .expect("no checkpoint")
+        .to_string();
+    let signed_endorsement =
+        d.create_signed_endorsement(serde_json::to_vec(&bundle).expect("serialization failed"));
+    let mut ref_value = d.ref_value();
+    ref_value.rekor_checkpoint_consistency =
+        Some(CheckpointConsistencyReferenceValue { checkpoint, proof_hashes: vec![] });
+
+    let result =
+        verify_endorsement(d.integrated_time.into_unix_millis(), &signed_endorsement, &ref_value);
+    assert!(result.is_ok(), "{:?}", result);
+}
+
+// Replaces the Rekor log entry of the endorsement by an RFC 3161 timestamp
+// over the signature, and requires the timestamp instead of the log entry.
+fn use_timestamp_authority(d: &mut EndorsementData, tsa: &TimestampAuthorityData, time: Instant) {
+    d.signed_endorsement.rekor_log_entry.clear();
+    d.signed_endorsement.rfc3161_timestamp = tsa.create_timestamp_response(&d.signature, time, 0);
+    d.ref_value.rekor = Some(VerifyingKeyReferenceValue {
+        r#type: Some(verifying_key_reference_value::Type::Skip(SkipVerification {})),
+    });
+    d.ref_value.timestamp_authority = Some(tsa.ref_value());
+}
+
+#[test]
+fn test_verify_endorsement_succeeds_with_rfc3161_timestamp() {
+    let mut d = EndorsementData::load();
+    let tsa = TimestampAuthorityData::load(0x33);
+    let now = d.make_valid_time();
+    use_timestamp_authority(&mut d, &tsa, now);
+
+    let result = verify_endorsement(
+        d.make_valid_time().into_unix_millis(),
+        &d.signed_endorsement,
+        &d.ref_value,
+    );
+    assert!(result.is_ok(), "{:?}", result);
+}
+
+#[test]
+fn test_verify_endorsement_succeeds_with_rfc3161_timestamp_and_log_entry() {
+    let mut d = EndorsementData::load();
+    let tsa = TimestampAuthorityData::load(0x33);
+    d.signed_endorsement.rfc3161_timestamp =
+        tsa.create_timestamp_token(&d.signature, d.make_valid_time(), true);
+    d.ref_value.timestamp_authority = Some(tsa.ref_value());
+
+    let result = verify_endorsement(
+        d.make_valid_time().into_unix_millis(),
+        &d.signed_endorsement,
+        &d.ref_value,
+    );
+    assert!(result.is_ok(), "{:?}", result);
+}
+
+#[test]
+fn test_verify_endorsement_fails_with_timestamp_authority_but_no_timestamp() {
+    let mut d = EndorsementData::load();
+    let tsa = TimestampAuthorityData::load(0x33);
+    let now = d.make_valid_time();
+    use_timestamp_authority(&mut d, &tsa, now);
+    d.signed_endorsement.rfc3161_timestamp.clear();
+
+    let result = verify_endorsement(
+        d.make_valid_time().into_unix_millis(),
+        &d.signed_endorsement,
+        &d.ref_value,
+    );
+    assert!(result.is_err(), "{:?}", result);
+}
+
+#[test]
+fn test_verify_endorsement_fails_with_rfc3161_timestamp_by_other_authority() {
+    let mut d = EndorsementData::load();
+    let tsa = TimestampAuthorityData::load(0x33);
+    let now = d.make_valid_time();
+    use_timestamp_authority(&mut d, &tsa, now);
+    d.ref_value.timestamp_authority = Some(TimestampAuthorityData::load(0x55).ref_value());
+
+    let result = verify_endorsement(
+        d.make_valid_time().into_unix_millis(),
+        &d.signed_endorsement,
+        &d.ref_value,
+    );
+    assert!(result.is_err(), "{:?}", result);
+}
+
+#[test]
+fn test_verify_endorsement_applies_rfc3161_timestamp_policy() {
+    let mut d = EndorsementData::load();
+    let tsa = TimestampAuthorityData::load(0x33);
+    let now = d.make_valid_time();
+    let policy = TimestampReferenceValue {
+        not_before_relative: Some(prost_types::Duration { seconds: -3_600, nanos: 0 }),
+        ..Default::default()
+    };
+
+    use_timestamp_authority(&mut d, &tsa, now - Duration::from_seconds(60));
+    d.ref_value.timestamp_authority.as_mut().unwrap().signed_timestamp = Some(policy);
+    let result = verify_endorsement(now.into_unix_millis(), &d.signed_endorsement, &d.ref_value);
+    assert!(result.is_ok(), "{:?}", result);
+
+    d.signed_endorsement.rfc3161_timestamp =
+        tsa.create_timestamp_token(&d.signature, now - Duration::from_seconds(7_200), true);
+    let result = verify_endorsement(now.into_unix_millis(), &d.signed_endorsement, &d.ref_value);
+    assert!(result.is_err(), "{:?}", result);
+}
+
+#[test]
+fn test_verify_endorsement_succeeds_with_sigstore_bundle_and_rfc3161_timestamp() {
+    let d = SigstoreBundleData::load();
+    let tsa = TimestampAuthorityData::load(0x33);
+
+    for content in [BundleContent::MessageSignature, BundleContent::DsseEnvelope] {
+        let mut bundle = d.create_bundle_json(content, RekorVersion::V2);
+        let serialized = serde_json::to_vec(&bundle).expect("serialization failed");
+        let signature = get_bundle_signatures(&parse_sigstore_bundle(&serialized).unwrap())
+            .expect("no bundle signature")
+            .remove(0);
+        let timestamp = tsa.create_timestamp_response(&signature, d.integrated_time, 0);
+        bundle["verificationMaterial"]["timestampVerificationData"] = serde_json::json!({
+            "rfc3161Timestamps": [{ "signedTimestamp": BASE64_STANDARD.encode(timestamp) }]
+        });
+        let signed_endorsement =
+            d.create_signed_endorsement(serde_json::to_vec(&bundle).expect("serialization failed"));
+        let mut ref_value = d.ref_value();
+        ref_value.timestamp_authority = Some(tsa.ref_value());
+
+        let result = verify_endorsement(
+            d.integrated_time.into_unix_millis(),
+            &signed_endorsement,
+            &ref_value,
+        );
+        assert!(result.is_ok(), "{content:?}: {:?}", result);
+    }
+}
+
+#[test]
+fn test_verify_endorsement_fails_with_rfc3161_timestamp_over_unverified_signature() {
+    let d = SigstoreBundleData::load();
+    let tsa = TimestampAuthorityData::load(0x33);
+    let mut bundle = d.create_bundle_json(BundleContent::DsseEnvelope, RekorVersion::V2);
+    // Only the first signature of the envelope is verified by the endorser key.
+    let unverified_signature = b"unverified signature".to_vec();
+    bundle["dsseEnvelope"]["signatures"].as_array_mut().expect("no DSSE signatures").push(
+        serde_json::json!({ "sig": BASE64_STANDARD.encode(&unverified_signature), "keyid": "" }),
+    );
+    let timestamp = tsa.create_timestamp_response(&unverified_signature, d.integrated_time, 0);
+    bundle["verificationMaterial"]["timestampVerificationData"] = serde_json::json!({
+        "rfc3161Timestamps": [{ "signedTimestamp": BASE64_STANDARD.encode(timestamp) }]
+    });
+    let signed_endorsement =
+        d.create_signed_endorsement(serde_json::to_vec(&bundle).expect("serialization failed"));
+    let mut ref_value = d.ref_value();
+    ref_value.rekor = Some(VerifyingKeyReferenceValue {
+        r#type: Some(verifying_key_reference_value::Type::Skip(SkipVerification {})),
+    });
+    ref_value.timestamp_authority = Some(tsa.ref_value());
+
+    let result =
+        verify_endorsement(d.integrated_time.into_unix_millis(), &signed_endorsement, &ref_value);
+    assert!(result.is_err(), "{:?}", result);
+}
+
+#[test]
+fn test_verify_endorsement_fails_with_sigstore_bundle_without_rfc3161_timestamp() {
+    let d = SigstoreBundleData::load();
+    let signed_endorsement = d.create_signed_endorsement(
+        d.create_bundle(BundleContent::MessageSignature, RekorVersion::V2),
+    );
+    let mut ref_value = d.ref_value();
+    ref_value.timestamp_authority = Some(TimestampAuthorityData::load(0x33).ref_value());
+
+    let result =
+        verify_endorsement(d.integrated_time.into_unix_millis(), &signed_endorsement, &ref_value);
+    assert!(result.is_err(), "{:?}", result);
+}
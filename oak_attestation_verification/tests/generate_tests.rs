@@ -0,0 +1,157 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Tests for generating reference values from evidence and endorsements.
+
+use oak_attestation_verification::{
+    create_amd_verifier, create_intel_tdx_verifier, generate_reference_values, EndorsementKeys,
+};
+use oak_attestation_verification_types::verifier::AttestationVerifier;
+use oak_proto_rust::oak::attestation::v1::{
+    binary_reference_value, kernel_binary_reference_value, text_reference_value, Endorsements,
+    Evidence,
+};
+use oak_time::clock::FixedClock;
+use test_util::{get_oc_reference_values, get_rk_reference_values, AttestationData};
+
+// Returns the endorser key used in the staging reference values.
+fn staging_endorsement_keys(d: &AttestationData) -> EndorsementKeys {
+    let kernel = get_oc_reference_values(&d.reference_values)
+        .kernel_layer
+        .expect("no kernel layer")
+        .kernel
+        .expect("no kernel reference value");
+    let Some(kernel_binary_reference_value::Type::Endorsement(endorsement)) = kernel.r#type else {
+        panic!("kernel not checked via endorsement");
+    };
+    let key = endorsement.endorser.expect("no endorser").keys.remove(0);
+    EndorsementKeys { endorser_public_key: key.raw, rekor_public_key: vec![] }
+}
+
+#[test]
+fn generated_milan_oc_reference_values_verify() {
+    let d = AttestationData::load_milan_oc_release();
+
+    let reference_values = generate_reference_values(&d.evidence, &d.endorsements, None)
+        .expect("failed to generate reference values");
+
+    let verifier =
+        create_amd_verifier(FixedClock::at_instant(d.make_valid_time()), &reference_values)
+            .expect("failed to create verifier");
+    assert!(verifier.verify(&d.evidence, &d.endorsements).is_ok());
+}
+
+#[test]
+fn generated_milan_rk_reference_values_verify() {
+    let d = AttestationData::load_milan_rk_release();
+
+    let reference_values = generate_reference_values(&d.evidence, &d.endorsements, None)
+        .expect("failed to generate reference values");
+
+    assert!(get_rk_reference_values(&reference_values).application_layer.is_some());
+    let verifier =
+        create_amd_verifier(FixedClock::at_instant(d.make_valid_time()), &reference_values)
+            .expect("failed to create verifier");
+    assert!(verifier.verify(&d.evidence, &d.endorsements).is_ok());
+}
+
+#[test]
+fn generated_tdx_oc_reference_values_verify() {
+    let d = AttestationData::load_tdx_oc();
+
+    let reference_values = generate_reference_values(&d.evidence, &d.endorsements, None)
+        .expect("failed to generate reference values");
+
+    let verifier =
+        create_intel_tdx_verifier(FixedClock::at_instant(d.make_valid_time()), &reference_values)
+            .expect("failed to create verifier");
+    assert!(verifier.verify(&d.evidence, &d.endorsements).is_ok());
+}
+
+#[test]
+fn generated_reference_values_use_endorsements() {
+    let d = AttestationData::load_milan_oc_staging();
+    let keys = staging_endorsement_keys(&d);
+
+    let reference_values = generate_reference_values(&d.evidence, &d.endorsements, Some(&keys))
+        .expect("failed to generate reference values");
+
+    let oc = get_oc_reference_values(&reference_values);
+    let kernel_layer = oc.kernel_layer.as_ref().expect("no kernel layer");
+    assert!(matches!(
+        kernel_layer.kernel.as_ref().and_then(|rv| rv.r#type.as_ref()),
+        Some(kernel_binary_reference_value::Type::Endorsement(_))
+    ));
+    #[cfg(feature = "regex")]
+    assert!(matches!(
+        kernel_layer.kernel_cmd_line_text.as_ref().and_then(|rv| rv.r#type.as_ref()),
+        Some(text_reference_value::Type::Endorsement(_))
+    ));
+    #[cfg(not(feature = "regex"))]
+    assert!(matches!(
+        kernel_layer.kernel_cmd_line_text.as_ref().and_then(|rv| rv.r#type.as_ref()),
+        Some(text_reference_value::Type::StringLiterals(_))
+    ));
+    let system_layer = oc.system_layer.as_ref().expect("no system layer");
+    assert!(matches!(
+        system_layer.system_image.as_ref().and_then(|rv| rv.r#type.as_ref()),
+        Some(binary_reference_value::Type::Endorsement(_))
+    ));
+    let verifier =
+        create_amd_verifier(FixedClock::at_instant(d.make_valid_time()), &reference_values)
+            .expect("failed to create verifier");
+    assert!(verifier.verify(&d.evidence, &d.endorsements).is_ok());
+}
+
+#[test]
+fn generated_reference_values_pin_digests_without_endorsements() {
+    let d = AttestationData::load_milan_oc_staging();
+    let keys = staging_endorsement_keys(&d);
+
+    let reference_values =
+        generate_reference_values(&d.evidence, &Endorsements::default(), Some(&keys))
+            .expect("failed to generate reference values");
+
+    let oc = get_oc_reference_values(&reference_values);
+    let system_layer = oc.system_layer.as_ref().expect("no system layer");
+    assert!(matches!(
+        system_layer.system_image.as_ref().and_then(|rv| rv.r#type.as_ref()),
+        Some(binary_reference_value::Type::Digests(_))
+    ));
+}
+
+#[test]
+fn generated_reference_values_cover_every_container() {
+    let mut d = AttestationData::load_milan_oc_release();
+    // Adds a second container to the event log, measured before the last one.
+    let events = &mut d.evidence.event_log.as_mut().expect("no event log").encoded_events;
+    let container_event = events.last().expect("no container event").clone();
+    events.insert(events.len() - 1, container_event);
+
+    let reference_values = generate_reference_values(&d.evidence, &d.endorsements, None)
+        .expect("failed to generate reference values");
+
+    let oc = get_oc_reference_values(&reference_values);
+    assert_eq!(oc.additional_container_layers.len(), 1);
+    assert_eq!(oc.additional_container_layers.first(), oc.container_layer.as_ref());
+}
+
+#[test]
+fn generate_reference_values_fails_without_evidence() {
+    let result = generate_reference_values(&Evidence::default(), &Endorsements::default(), None);
+
+    assert!(result.is_err());
+}
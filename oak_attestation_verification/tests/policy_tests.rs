@@ -40,7 +40,8 @@ use test_util::{
     allow_insecure, create_reference_values_for_extracted_evidence, get_cb_reference_values,
     manipulate::{
         get_acpi_rv, get_init_ram_fs_rv, get_kernel_cmd_line_rv, get_kernel_rv, get_oc_config_rv,
-        get_oc_container_rv, get_oc_system_image_rv, get_rk_application_rv, get_rk_config_rv,
+        get_oc_container_rv, get_oc_reference_values_mut, get_oc_system_image_rv,
+        get_rk_application_rv, get_rk_config_rv,
         get_stage0_rv, manipulate_kernel_cmd_line, manipulate_kernel_image,
         manipulate_kernel_setup_data, manipulate_sha2_256, manipulate_sha2_384,
     },
@@ -115,6 +116,8 @@ fn cb_dice_verify_succeeds() {
             }
             _ => vec![],
         },
+        crls: vec![],
+        asvk_certificate: vec![],
     };
     let empty_variant: Variant = Variant::default();
     let endorsements = Endorsements {
@@ -333,6 +336,22 @@ verify_insecure_success! {
     load_fake
 }
 
+#[test]
+fn verify_amd_with_missing_container_failure() {
+    let mut d = AttestationData::load_milan_oc_release();
+    // Reference values for a second container, which isn't in the evidence.
+    let oc = get_oc_reference_values_mut(&mut d.reference_values);
+    let container_layer = oc.container_layer.clone().expect("no container layer");
+    oc.additional_container_layers.push(container_layer);
+
+    assert_failure(verify_amd(
+        d.make_valid_time(),
+        &d.evidence,
+        &d.endorsements,
+        &d.reference_values,
+    ));
+}
+
 macro_rules! verify_insecure_manipulated_root_public_key_failure {
     ($($name:tt)*) => {
         mod verify_insecure_manipulated_root_public_key_failure {
@@ -0,0 +1,300 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Tests for the structured verification reports.
+
+use oak_attestation_verification::{
+    create_amd_verifier, create_intel_tdx_verifier,
+    verifier::verify_dice_chain_and_extract_evidence,
+};
+use oak_attestation_verification_types::{
+    report::{CheckReport, CheckStatus, LayerReport, VerificationReport},
+    verifier::AttestationVerifier,
+};
+use oak_proto_rust::oak::attestation::v1::{Endorsements, Evidence, ReferenceValues};
+use oak_time::{clock::FixedClock, Duration, Instant};
+use test_util::{
+    create_reference_values_for_extracted_evidence,
+    manipulate::{
+        get_acpi_rv, get_init_ram_fs_rv, get_kernel_cmd_line_rv, get_kernel_rv, get_oc_config_rv,
+        get_oc_container_rv, get_oc_system_image_rv, get_rk_application_rv, get_stage0_rv,
+        manipulate_kernel_cmd_line, manipulate_kernel_image, manipulate_kernel_setup_data,
+        manipulate_sha2_256, manipulate_sha2_384,
+    },
+    AttestationData,
+};
+
+type Manipulation = (&'static str, fn(&mut ReferenceValues));
+
+// Manipulations of the reference values which apply to both Oak Containers and
+// Oak Restricted Kernel, each making a different policy fail.
+const COMMON_MANIPULATIONS: &[Manipulation] = &[
+    ("none", |_| {}),
+    ("stage0", |rvs| manipulate_sha2_384(get_stage0_rv(rvs))),
+    ("kernel_image", |rvs| manipulate_kernel_image(get_kernel_rv(rvs))),
+    ("kernel_setup_data", |rvs| manipulate_kernel_setup_data(get_kernel_rv(rvs))),
+    ("kernel_cmd_line", |rvs| manipulate_kernel_cmd_line(get_kernel_cmd_line_rv(rvs))),
+    ("init_ram_fs", |rvs| manipulate_sha2_256(get_init_ram_fs_rv(rvs))),
+    ("acpi", |rvs| manipulate_sha2_256(get_acpi_rv(rvs))),
+];
+
+const OC_MANIPULATIONS: &[Manipulation] = &[
+    ("system_image", |rvs| manipulate_sha2_256(get_oc_system_image_rv(rvs))),
+    ("container", |rvs| manipulate_sha2_256(get_oc_container_rv(rvs))),
+    ("config", |rvs| manipulate_sha2_256(get_oc_config_rv(rvs))),
+];
+
+const RK_MANIPULATIONS: &[Manipulation] =
+    &[("application", |rvs| manipulate_sha2_256(get_rk_application_rv(rvs)))];
+
+// Asserts that the report of the verifier passes exactly when the verification
+// succeeds, across all policies and the event log checks of the verifier.
+fn assert_parity(
+    case: &str,
+    verifier: &dyn AttestationVerifier,
+    evidence: &Evidence,
+    endorsements: &Endorsements,
+) {
+    let result = verifier.verify(evidence, endorsements);
+    let report = verifier.report(evidence, endorsements);
+    assert_eq!(
+        result.is_ok(),
+        report.is_ok(),
+        "{case}: verify returned {:?}, report returned {:?}",
+        result.err(),
+        report.to_result()
+    );
+}
+
+// Checks parity on the attestation with the published reference values at
+// various times, and with digest-based reference values manipulated in turn.
+fn assert_amd_parity(name: &str, d: &AttestationData, manipulations: &[Manipulation]) {
+    let valid_time = d.make_valid_time();
+    for (time_name, time) in [
+        ("valid", valid_time),
+        ("epoch", Instant::UNIX_EPOCH),
+        ("far_future", valid_time + Duration::from_days(100 * 365)),
+    ] {
+        let clock = FixedClock::at_instant(time);
+        let verifier = create_amd_verifier(clock, &d.reference_values).expect("no verifier");
+        assert_parity(&format!("{name}/{time_name}"), &verifier, &d.evidence, &d.endorsements);
+    }
+
+    let extracted_evidence =
+        verify_dice_chain_and_extract_evidence(&d.evidence).expect("invalid DICE evidence");
+    let reference_values = create_reference_values_for_extracted_evidence(extracted_evidence);
+    for (manipulation_name, manipulate) in COMMON_MANIPULATIONS.iter().chain(manipulations) {
+        let mut reference_values = reference_values.clone();
+        manipulate(&mut reference_values);
+        let clock = FixedClock::at_instant(valid_time);
+        let verifier = create_amd_verifier(clock, &reference_values).expect("no verifier");
+        let case = format!("{name}/{manipulation_name}");
+        assert_parity(&case, &verifier, &d.evidence, &d.endorsements);
+        assert_parity(
+            &format!("{case}/no_endorsements"),
+            &verifier,
+            &d.evidence,
+            &Endorsements::default(),
+        );
+    }
+}
+
+// Reports on the attestation with digest-based reference values produced from
+// the evidence, such that every measurement is actually compared.
+fn report_amd(
+    d: &AttestationData,
+    manipulate: impl FnOnce(&mut ReferenceValues),
+) -> VerificationReport {
+    let extracted_evidence =
+        verify_dice_chain_and_extract_evidence(&d.evidence).expect("invalid DICE evidence");
+    let mut reference_values = create_reference_values_for_extracted_evidence(extracted_evidence);
+    manipulate(&mut reference_values);
+    let clock = FixedClock::at_instant(d.make_valid_time());
+    let verifier = create_amd_verifier(clock, &reference_values).expect("no verifier");
+    verifier.report(&d.evidence, &d.endorsements)
+}
+
+fn find_check<'a>(report: &'a VerificationReport, id: &str) -> &'a CheckReport {
+    report
+        .layers
+        .iter()
+        .flat_map(|layer| layer.checks.iter())
+        .find(|check| check.id == id)
+        .unwrap_or_else(|| panic!("no check {id}"))
+}
+
+fn create_report() -> VerificationReport {
+    let mut kernel = LayerReport::new("kernel");
+    kernel.check("evidence", Ok(()));
+    kernel
+        .add("image", CheckStatus::Failed)
+        .with_values("sha2-256:00".to_string(), "sha2-256:11".to_string())
+        .message = Some("no match".to_string());
+    kernel.add("cmd_line", CheckStatus::Skipped);
+    let mut system = LayerReport::new("system");
+    system.check::<()>("image", Err(anyhow::anyhow!("inner").context("outer")));
+    VerificationReport { layers: vec![kernel, system] }
+}
+
+#[test]
+fn check_ids_are_prefixed_with_layer() {
+    let report = create_report();
+
+    let ids: Vec<&str> =
+        report.layers.iter().flat_map(|l| l.checks.iter()).map(|c| c.id.as_str()).collect();
+
+    assert_eq!(ids, vec!["kernel.evidence", "kernel.image", "kernel.cmd_line", "system.image"]);
+}
+
+#[test]
+fn failed_checks_make_report_fail() {
+    let report = create_report();
+
+    assert!(!report.is_ok());
+    assert!(!report.layers[0].is_ok());
+    assert_eq!(
+        report.to_result().unwrap_err().to_string(),
+        "failed checks: kernel.image: no match; system.image: outer: inner"
+    );
+}
+
+#[test]
+fn skipped_checks_do_not_make_report_fail() {
+    let mut layer = LayerReport::new("kernel");
+    layer.add("cmd_line", CheckStatus::Skipped);
+    let report = VerificationReport { layers: vec![layer] };
+
+    assert!(report.is_ok());
+    assert!(report.to_result().is_ok());
+}
+
+#[test]
+fn report_serializes_to_json() {
+    let report = create_report();
+
+    let json = serde_json::to_value(&report).expect("couldn't serialize report");
+
+    assert_eq!(
+        json["layers"][0]["checks"][1],
+        serde_json::json!({
+            "id": "kernel.image",
+            "status": "failed",
+            "expected": "sha2-256:00",
+            "actual": "sha2-256:11",
+            "message": "no match",
+        })
+    );
+    assert_eq!(
+        json["layers"][0]["checks"][2],
+        serde_json::json!({"id": "kernel.cmd_line", "status": "skipped"})
+    );
+    let parsed: VerificationReport =
+        serde_json::from_value(json).expect("couldn't deserialize report");
+    assert_eq!(parsed, report);
+}
+
+#[test]
+fn amd_oc_report_succeeds() {
+    let d = AttestationData::load_milan_oc_release();
+
+    let report = report_amd(&d, |_| {});
+
+    assert!(report.to_result().is_ok(), "{:?}", report.to_result());
+    let layers: Vec<&str> = report.layers.iter().map(|l| l.layer.as_str()).collect();
+    assert_eq!(layers, vec!["platform", "firmware", "event_log", "kernel", "system", "container"]);
+    let check = find_check(&report, "kernel.image");
+    assert_eq!(check.status, CheckStatus::Passed);
+    assert!(check.actual.as_ref().is_some_and(|actual| actual.starts_with("sha2-256:")));
+}
+
+#[test]
+fn amd_oc_report_with_manipulated_kernel_fails_only_kernel_image() {
+    let d = AttestationData::load_milan_oc_release();
+
+    let report = report_amd(&d, |rvs| manipulate_kernel_image(get_kernel_rv(rvs)));
+
+    let failed: Vec<&str> = report.failed_checks().map(|c| c.id.as_str()).collect();
+    assert_eq!(failed, vec!["kernel.image"]);
+    let check = find_check(&report, "kernel.image");
+    assert!(check.expected.is_some());
+    assert_ne!(check.expected, check.actual);
+    // Verification continues beyond the failed check.
+    assert_eq!(find_check(&report, "container.bundle").status, CheckStatus::Passed);
+}
+
+#[test]
+fn tdx_oc_report_succeeds() {
+    let d = AttestationData::load_tdx_oc();
+    let clock = FixedClock::at_instant(d.make_valid_time());
+    let verifier = create_intel_tdx_verifier(clock, &d.reference_values).expect("no verifier");
+
+    let report = verifier.report(&d.evidence, &d.endorsements);
+
+    assert!(report.to_result().is_ok(), "{:?}", report.to_result());
+    assert_eq!(find_check(&report, "event_log.integrity").status, CheckStatus::Passed);
+}
+
+#[test]
+fn report_with_empty_args_fails() {
+    let d = AttestationData::load_milan_oc_release();
+    let clock = FixedClock::at_instant(d.make_valid_time());
+    let verifier = create_amd_verifier(clock, &d.reference_values).expect("no verifier");
+
+    let report = verifier.report(&Evidence::default(), &Endorsements::default());
+
+    assert_eq!(find_check(&report, "platform.evidence").status, CheckStatus::Failed);
+    assert_eq!(find_check(&report, "firmware.evidence").status, CheckStatus::Failed);
+    assert_eq!(find_check(&report, "event_log.evidence").status, CheckStatus::Failed);
+}
+
+#[test]
+fn amd_oc_report_matches_verify() {
+    for (name, d) in [
+        ("milan_oc_release", AttestationData::load_milan_oc_release()),
+        ("milan_oc_staging", AttestationData::load_milan_oc_staging()),
+        ("genoa_oc", AttestationData::load_genoa_oc()),
+        ("turin_oc", AttestationData::load_turin_oc()),
+    ] {
+        assert_amd_parity(name, &d, OC_MANIPULATIONS);
+    }
+}
+
+#[test]
+fn amd_rk_report_matches_verify() {
+    for (name, d) in [
+        ("milan_rk_release", AttestationData::load_milan_rk_release()),
+        ("milan_rk_staging", AttestationData::load_milan_rk_staging()),
+    ] {
+        assert_amd_parity(name, &d, RK_MANIPULATIONS);
+    }
+}
+
+#[test]
+fn tdx_oc_report_matches_verify() {
+    let d = AttestationData::load_tdx_oc();
+    let valid_time = d.make_valid_time();
+    for (time_name, time) in [("valid", valid_time), ("epoch", Instant::UNIX_EPOCH)] {
+        let clock = FixedClock::at_instant(time);
+        let verifier = create_intel_tdx_verifier(clock, &d.reference_values).expect("no verifier");
+        assert_parity(time_name, &verifier, &d.evidence, &d.endorsements);
+        assert_parity(
+            &format!("{time_name}/empty_evidence"),
+            &verifier,
+            &Evidence::default(),
+            &Endorsements::default(),
+        );
+    }
+}
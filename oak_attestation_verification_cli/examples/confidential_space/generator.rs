@@ -309,8 +309,10 @@ fn generate_log_entry(
         verification: None,
     };
     let rekor_signature: Signature = rekor_key.sign(&serde_json::to_vec(&log_entry)?);
-    log_entry.verification =
-        Some(Verification { signed_entry_timestamp: STANDARD.encode(rekor_signature.to_der()) });
+    log_entry.verification = Some(Verification {
+        inclusion_proof: None,
+        signed_entry_timestamp: STANDARD.encode(rekor_signature.to_der()),
+    });
     Ok(log_entry)
 }
 
@@ -0,0 +1,102 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Generates reference values from a known-good collected attestation.
+
+use std::{fs, path::PathBuf};
+
+use anyhow::Context;
+use clap::Args;
+use oak_attestation_verification::{
+    convert_pem_to_raw, generate_reference_values, EndorsementKeys,
+};
+use oak_proto_rust::{
+    attestation::{CERTIFICATE_BASED_ATTESTATION_ID, CONFIDENTIAL_SPACE_ATTESTATION_ID},
+    oak::attestation::v1::{CollectedAttestation, ReferenceValuesCollection},
+};
+use prost::Message;
+
+use crate::{proto_decoder, resolve_path};
+
+#[derive(Args, Debug)]
+pub struct GenerateFlags {
+    /// Path of the known-good collected attestation, encoded as a binary
+    /// protobuf.
+    #[arg(long, value_parser = proto_decoder::<CollectedAttestation>)]
+    attestation: CollectedAttestation,
+
+    /// Path of the PEM-encoded public key of the endorser. If set, measurements
+    /// which come with an endorsement are checked via the endorsement instead
+    /// of being pinned by digest.
+    #[arg(long)]
+    endorser_public_key: Option<PathBuf>,
+
+    /// Path of the PEM-encoded public key of Rekor. If not set, the generated
+    /// reference values skip the verification of log entries.
+    #[arg(long, requires = "endorser_public_key")]
+    rekor_public_key: Option<PathBuf>,
+
+    /// Path where the generated reference values are written to, encoded as a
+    /// binary `ReferenceValuesCollection` protobuf.
+    #[arg(long)]
+    output: PathBuf,
+}
+
+/// Writes reference values for every attestation in the collected attestation
+/// which is based on DICE evidence. Other attestation types are skipped.
+pub fn generate(flags: GenerateFlags) -> anyhow::Result<()> {
+    let GenerateFlags { attestation, endorser_public_key, rekor_public_key, output } = flags;
+    let keys = endorser_public_key
+        .map(|endorser_public_key| -> anyhow::Result<EndorsementKeys> {
+            Ok(EndorsementKeys {
+                endorser_public_key: read_pem_key(endorser_public_key)?,
+                rekor_public_key: rekor_public_key
+                    .map(read_pem_key)
+                    .transpose()?
+                    .unwrap_or_default(),
+            })
+        })
+        .transpose()?;
+
+    let mut collection = ReferenceValuesCollection::default();
+    for (attestation_type_id, endorsed_evidence) in attestation.endorsed_evidence.iter() {
+        if matches!(
+            attestation_type_id.as_str(),
+            CONFIDENTIAL_SPACE_ATTESTATION_ID | CERTIFICATE_BASED_ATTESTATION_ID
+        ) {
+            eprintln!("Skipping unsupported attestation type ID: {}", attestation_type_id);
+            continue;
+        }
+        let reference_values = generate_reference_values(
+            endorsed_evidence.evidence.as_ref().context("missing evidence")?,
+            endorsed_evidence.endorsements.as_ref().context("missing endorsements")?,
+            keys.as_ref(),
+        )
+        .with_context(|| format!("generating reference values for {attestation_type_id}"))?;
+        collection.reference_values.insert(attestation_type_id.clone(), reference_values);
+    }
+    anyhow::ensure!(!collection.reference_values.is_empty(), "no supported attestation found");
+
+    fs::write(resolve_path(output), collection.encode_to_vec())
+        .context("writing reference values")?;
+    Ok(())
+}
+
+fn read_pem_key(path: PathBuf) -> anyhow::Result<Vec<u8>> {
+    let pem = fs::read_to_string(resolve_path(&path))
+        .with_context(|| format!("reading {}", path.display()))?;
+    convert_pem_to_raw(&pem)
+}
@@ -16,18 +16,23 @@
 
 #![feature(try_blocks)]
 
+mod generate;
 mod print;
 mod report;
 
 use std::{
+    collections::BTreeMap,
     fmt::Write,
     fs,
-    path::Path,
+    path::{Path, PathBuf},
     time::{SystemTime, UNIX_EPOCH},
 };
 
-use anyhow::anyhow;
-use clap::Parser;
+use anyhow::{anyhow, Context};
+use clap::{Parser, Subcommand};
+use oak_attestation_verification_types::report::{
+    LayerReport, VerificationReport as StructuredReport,
+};
 use oak_proto_rust::{
     attestation::{CERTIFICATE_BASED_ATTESTATION_ID, CONFIDENTIAL_SPACE_ATTESTATION_ID},
     oak::{
@@ -41,30 +46,68 @@ use oak_proto_rust::{
 use oak_time::Instant;
 use prost::Message;
 
-use crate::{print::print_indented, report::VerificationReport};
+use crate::{
+    generate::{generate, GenerateFlags},
+    print::print_indented,
+    report::VerificationReport,
+};
+
+/// Name of the layer which covers the collected attestation as a whole in the
+/// machine-readable report.
+const COLLECTION_LAYER: &str = "collection";
 
 #[derive(Parser, Debug)]
-#[group(required = true)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Flags {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Path of the collected attestation, encoded as a binary protobuf.
-    #[arg(long, value_parser = proto_decoder::<CollectedAttestation>)]
-    attestation: CollectedAttestation,
+    #[arg(long, required = true, value_parser = proto_decoder::<CollectedAttestation>)]
+    attestation: Option<CollectedAttestation>,
+
+    #[arg(long, required = true, value_parser = proto_decoder::<ReferenceValuesCollection>)]
+    reference_values: Option<ReferenceValuesCollection>,
+
+    /// Prints a machine-readable report in JSON instead of the human readable
+    /// one.
+    #[arg(long)]
+    json: bool,
+}
 
-    #[arg(long, value_parser = proto_decoder::<ReferenceValuesCollection>)]
-    reference_values: ReferenceValuesCollection,
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generates reference values from a known-good collected attestation.
+    GenerateReferenceValues(GenerateFlags),
+}
+
+/// Resolves [path] relative to the directory the tool was invoked from.
+fn resolve_path(path: impl AsRef<Path>) -> PathBuf {
+    // https://bazel.build/docs/user-manual#running-executables
+    Path::new(&std::env::var("BUILD_WORKING_DIRECTORY").unwrap_or_default()).join(path)
 }
 
 /// Decodes the (binary format) proto stored in the [path] file. [path] may be
 /// an absolute or relative file path.
 fn proto_decoder<T: Message + std::default::Default>(path: &str) -> anyhow::Result<T> {
-    // https://bazel.build/docs/user-manual#running-executables
-    let path = Path::new(&std::env::var("BUILD_WORKING_DIRECTORY").unwrap_or_default()).join(path);
-    Ok(T::decode(fs::read(path)?.as_slice())?)
+    Ok(T::decode(fs::read(resolve_path(path))?.as_slice())?)
 }
 
 fn main() -> anyhow::Result<()> {
-    let Flags { attestation, reference_values: ReferenceValuesCollection { reference_values } } =
-        Flags::parse();
+    let Flags { command, attestation, reference_values, json } = Flags::parse();
+    if let Some(Command::GenerateReferenceValues(flags)) = command {
+        return generate(flags);
+    }
+    // Both are required by clap unless a subcommand is given.
+    let attestation = attestation.context("missing attestation")?;
+    let ReferenceValuesCollection { reference_values } =
+        reference_values.context("missing reference values")?;
+
+    if json {
+        let report = create_structured_report(&attestation, &reference_values);
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return report.to_result();
+    }
 
     let mut buffer = String::new();
     let indent = 0;
@@ -154,6 +197,54 @@ fn process_attestation(
     }
 }
 
+/// Creates the machine-readable counterpart of the printed report. The first
+/// layer covers the collected attestation as a whole, followed by one layer
+/// per attestation type.
+fn create_structured_report(
+    attestation: &CollectedAttestation,
+    reference_values: &BTreeMap<String, ReferenceValues>,
+) -> StructuredReport {
+    let mut collection = LayerReport::new(COLLECTION_LAYER);
+    let attestation_timestamp = get_timestamp(attestation).and_then(|timestamp| {
+        anyhow::ensure!(timestamp != Instant::UNIX_EPOCH, "timestamp is unset");
+        Ok(timestamp)
+    });
+    let attestation_timestamp =
+        collection.check("timestamp", attestation_timestamp).unwrap_or(Instant::UNIX_EPOCH);
+    let handshake_hash = &attestation.handshake_hash;
+    collection.record(
+        "handshake_hash",
+        if handshake_hash.is_empty() { Err(anyhow!("handshake hash is missing")) } else { Ok(()) },
+    );
+
+    let mut layers = vec![collection];
+    for (attestation_type_id, endorsed_evidence) in attestation.endorsed_evidence.iter() {
+        let layer = match attestation_type_id.as_str() {
+            CONFIDENTIAL_SPACE_ATTESTATION_ID => "confidential_space",
+            CERTIFICATE_BASED_ATTESTATION_ID => "certificate_based",
+            _ => attestation_type_id.as_str(),
+        };
+        match process_attestation(
+            attestation_type_id.clone(),
+            endorsed_evidence,
+            attestation_timestamp,
+            reference_values.get(attestation_type_id),
+        ) {
+            Ok(report) => layers.push(report.to_layer_report(
+                layer,
+                handshake_hash,
+                attestation.session_bindings.get(attestation_type_id),
+            )),
+            Err(err) => {
+                let mut layer_report = LayerReport::new(layer);
+                layer_report.record("evidence", Err(err));
+                layers.push(layer_report);
+            }
+        }
+    }
+    StructuredReport { layers }
+}
+
 fn get_timestamp(attestation: &CollectedAttestation) -> anyhow::Result<Instant> {
     let request_time =
         attestation.request_metadata.clone().unwrap_or_default().request_time.unwrap_or_default();
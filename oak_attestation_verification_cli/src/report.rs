@@ -14,7 +14,7 @@
 // limitations under the License.
 //
 
-use std::fmt::Write;
+use std::fmt::{Display, Write};
 
 use anyhow::anyhow;
 use oak_attestation_gcp::{
@@ -25,6 +25,7 @@ use oak_attestation_gcp::{
 use oak_attestation_verification::{
     SessionBindingPublicKeyPolicy, SessionBindingPublicKeyVerificationReport,
 };
+use oak_attestation_verification_types::report::{CheckStatus, LayerReport};
 use oak_crypto::certificate::certificate_verifier::{
     CertificateVerificationReport, CertificateVerifier,
 };
@@ -121,6 +122,56 @@ impl VerificationReport {
         }
     }
 
+    /// Converts the report into a structured layer report with the given
+    /// layer name, for machine-readable output.
+    pub fn to_layer_report(
+        &self,
+        layer: &str,
+        handshake_hash: &[u8],
+        session_binding: Option<&SessionBinding>,
+    ) -> LayerReport {
+        let mut layer_report = LayerReport::new(layer);
+        match self {
+            VerificationReport::ConfidentialSpace(report) => {
+                record(&mut layer_report, "public_key", &report.public_key_verification);
+                let token_report = &report.token_report;
+                record(&mut layer_report, "token_claims", &token_report.has_required_claims);
+                record(&mut layer_report, "token_validity", &token_report.validity);
+                record(&mut layer_report, "token_verification", &token_report.verification);
+                layer_report.record(
+                    "certificate_chain",
+                    certificate_chain_result(&token_report.issuer_report),
+                );
+                match &report.workload_endorsement_verification {
+                    None => {
+                        layer_report.add("workload_endorsement", CheckStatus::Skipped);
+                    }
+                    Some(result) => record(&mut layer_report, "workload_endorsement", result),
+                }
+            }
+            VerificationReport::CertificateBased(report) => match &report.endorsement {
+                Err(err) => record(&mut layer_report, "endorsement", &Err::<(), _>(err)),
+                Ok(CertificateVerificationReport { validity, verification, freshness }) => {
+                    record(&mut layer_report, "certificate_validity", validity);
+                    record(&mut layer_report, "certificate_verification", verification);
+                    if let Some(freshness) = freshness {
+                        record(&mut layer_report, "certificate_freshness", freshness);
+                    }
+                }
+            },
+        }
+        let session_binding_result = match session_binding {
+            None => Err(anyhow!("no session binding found")),
+            Some(session_binding) => verify_session_binding(
+                &self.session_binding_public_key(),
+                handshake_hash,
+                &session_binding.binding,
+            ),
+        };
+        layer_report.record("session_binding", session_binding_result);
+        layer_report
+    }
+
     fn session_binding_public_key(&self) -> Vec<u8> {
         match self {
             VerificationReport::ConfidentialSpace(report) => {
@@ -265,6 +316,28 @@ fn print_certificate_chain(
     }
 }
 
+// Records the outcome of a result with a displayable error as a check.
+fn record<T, E: Display>(layer_report: &mut LayerReport, name: &str, result: &Result<T, E>) {
+    layer_report.record(name, result.as_ref().map(|_| ()).map_err(|err| anyhow!("{}", err)));
+}
+
+// Combines the reports of all certificates in the chain into a single result,
+// which fails with the first error encountered from the leaf.
+fn certificate_chain_result(
+    report: &Result<
+        CertificateReport,
+        oak_attestation_gcp::jwt::verification::AttestationVerificationError,
+    >,
+) -> anyhow::Result<()> {
+    let report = report.as_ref().map_err(|err| anyhow!("invalid: {}", err))?;
+    report.validity.as_ref().map_err(|err| anyhow!("certificate is invalid: {}", err))?;
+    report.verification.as_ref().map_err(|err| anyhow!("certificate failed to verify: {}", err))?;
+    match report.issuer_report.as_ref() {
+        IssuerReport::OtherCertificate(report) => certificate_chain_result(report),
+        IssuerReport::Root => Ok(()),
+    }
+}
+
 fn verify_session_binding(
     session_binding_public_key: &[u8],
     handshake_hash: &[u8],
@@ -372,6 +445,44 @@ Nj98VHCkMOChdP0NoY0+ASi3S9WesDHql/SS3TeVKIW0W7VRIYDz51rU
         );
     }
 
+    #[test]
+    fn test_certificate_based_layer_report() {
+        let mut signing_key = SigningKey::from_str(SIGNING_KEY).unwrap();
+        let handshake_signature: Signature = signing_key.sign(HANDSHAKE_HASH);
+
+        let report =
+            VerificationReport::CertificateBased(SessionBindingPublicKeyVerificationReport {
+                endorsement: Ok(CertificateVerificationReport {
+                    validity: Ok(()),
+                    verification: Err(CertificateVerificationError::UnknownError(
+                        "verification error",
+                    )),
+                    freshness: None,
+                }),
+                session_binding_public_key: signing_key.verifying_key().to_sec1_bytes().to_vec(),
+            });
+        let layer_report = report.to_layer_report(
+            "certificate_based",
+            HANDSHAKE_HASH,
+            Option::Some(&session_binding(&handshake_signature.to_bytes())),
+        );
+
+        let checks: Vec<(&str, CheckStatus)> =
+            layer_report.checks.iter().map(|check| (check.id.as_str(), check.status)).collect();
+        assert_eq!(
+            checks,
+            vec![
+                ("certificate_based.certificate_validity", CheckStatus::Passed),
+                ("certificate_based.certificate_verification", CheckStatus::Failed),
+                ("certificate_based.session_binding", CheckStatus::Passed),
+            ]
+        );
+        assert_eq!(
+            layer_report.checks[1].message.as_deref(),
+            Some("Unknown error: verification error")
+        );
+    }
+
     #[test]
     fn test_print_certificate_based_report_certificate_verification_session_binding_errors() {
         let signing_key = SigningKey::from_str(SIGNING_KEY).unwrap();
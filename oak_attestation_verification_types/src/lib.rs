@@ -21,5 +21,6 @@ extern crate alloc;
 
 pub mod assertion_verifier;
 pub mod policy;
+pub mod report;
 pub mod util;
 pub mod verifier;
@@ -17,6 +17,8 @@
 use oak_proto_rust::oak::{attestation::v1::EventAttestationResults, Variant};
 use oak_time::Instant;
 
+use crate::report::{LayerReport, VERIFY_CHECK};
+
 /// A verification policy takes generic evidence and endorsement and performs
 /// verification. Policies represent individual steps inside a verifier.
 ///
@@ -44,6 +46,31 @@ pub trait Policy<V: ?Sized>: Send + Sync {
         evidence: &V,
         endorsement: &Variant,
     ) -> anyhow::Result<EventAttestationResults>;
+
+    /// Stable name of the layer that the policy verifies, e.g. `kernel`. It
+    /// prefixes the identifiers of all checks in the report of the policy.
+    fn layer(&self) -> &'static str {
+        "event"
+    }
+
+    /// Invokes the policy like `verify`, but records the outcome of every
+    /// individual check in a report instead of stopping at the first failure.
+    ///
+    /// The default implementation records the outcome of `verify` as a single
+    /// check. Policies override it to provide more detail, in particular the
+    /// expected and the actual values. An override should share its checks
+    /// with `verify`, e.g. via [`LayerReport::collect`], so that it passes
+    /// exactly when `verify` succeeds.
+    fn report(
+        &self,
+        verification_time: Instant,
+        evidence: &V,
+        endorsement: &Variant,
+    ) -> LayerReport {
+        let mut report = LayerReport::new(self.layer());
+        report.check(VERIFY_CHECK, self.verify(verification_time, evidence, endorsement));
+        report
+    }
 }
 
 /// Policy that takes an byte-encoded event with accompanying encoded
@@ -0,0 +1,209 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Provides a structured, machine-readable report of an attestation
+//! verification.
+//!
+//! A report is a tree: the verification consists of layers, and each layer
+//! consists of individual checks. Every check carries a stable identifier of
+//! the form `<layer>.<check>`, e.g. `kernel.image`, such that outcomes can be
+//! aggregated across many verifications. Where applicable, a check also
+//! records the expected and the actual value.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// The name of the check which the default implementations of `report` use
+/// to record the outcome of `verify`.
+pub const VERIFY_CHECK: &str = "verify";
+
+/// The outcome of a single check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Passed,
+    Failed,
+    /// The reference values explicitly skip the check.
+    Skipped,
+}
+
+/// The report of a single check within a layer.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CheckReport {
+    /// Stable identifier of the check, e.g. `kernel.image`.
+    pub id: String,
+
+    pub status: CheckStatus,
+
+    /// Human readable rendering of the accepted value(s), if applicable.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+
+    /// Human readable rendering of the value found in the evidence, if
+    /// applicable.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actual: Option<String>,
+
+    /// The reason of a failure.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl CheckReport {
+    /// Attaches the expected and the actual value to the check.
+    pub fn with_values(&mut self, expected: String, actual: String) -> &mut Self {
+        self.expected = Some(expected);
+        self.actual = Some(actual);
+        self
+    }
+}
+
+/// The report of all checks of a single layer, e.g. the kernel.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LayerReport {
+    /// Stable name of the layer, e.g. `kernel`.
+    pub layer: String,
+
+    pub checks: Vec<CheckReport>,
+}
+
+impl LayerReport {
+    pub fn new(layer: &str) -> Self {
+        Self { layer: layer.to_string(), checks: Vec::new() }
+    }
+
+    /// Records a check with the given name and status.
+    pub fn add(&mut self, name: &str, status: CheckStatus) -> &mut CheckReport {
+        self.checks.push(CheckReport {
+            id: format!("{}.{}", self.layer, name),
+            status,
+            expected: None,
+            actual: None,
+            message: None,
+        });
+        self.checks.last_mut().expect("check was just added")
+    }
+
+    /// Records a check with the outcome of `result`.
+    pub fn record(&mut self, name: &str, result: anyhow::Result<()>) -> &mut CheckReport {
+        match result {
+            Ok(()) => self.add(name, CheckStatus::Passed),
+            Err(err) => {
+                let check = self.add(name, CheckStatus::Failed);
+                check.message = Some(format!("{err:#}"));
+                check
+            }
+        }
+    }
+
+    /// Records a check with the outcome of `result`, and passes on the
+    /// successful value.
+    pub fn check<T>(&mut self, name: &str, result: anyhow::Result<T>) -> Option<T> {
+        match result {
+            Ok(value) => {
+                self.add(name, CheckStatus::Passed);
+                Some(value)
+            }
+            Err(err) => {
+                self.record(name, Err(err));
+                None
+            }
+        }
+    }
+
+    /// Returns true if none of the checks failed.
+    pub fn is_ok(&self) -> bool {
+        self.checks.iter().all(|check| check.status != CheckStatus::Failed)
+    }
+
+    /// Creates the report of `layer` by running `verify`, which records its
+    /// checks in the report as it goes. A failure that none of the recorded
+    /// checks accounts for is recorded as [`VERIFY_CHECK`], so the report
+    /// passes exactly when `verify` succeeds.
+    pub fn collect<T>(
+        layer: &str,
+        verify: impl FnOnce(&mut LayerReport) -> anyhow::Result<T>,
+    ) -> Self {
+        let mut report = Self::new(layer);
+        match verify(&mut report) {
+            Err(err) if report.is_ok() => {
+                report.record(VERIFY_CHECK, Err(err));
+            }
+            _ => {}
+        }
+        report
+    }
+}
+
+/// Records the outcome of `result` as the check `name` in `report`, if there
+/// is one, and passes `result` on.
+pub fn record<T>(
+    report: Option<&mut LayerReport>,
+    name: &str,
+    result: anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    if let Some(report) = report {
+        match &result {
+            Ok(_) => {
+                report.add(name, CheckStatus::Passed);
+            }
+            Err(err) => {
+                report.add(name, CheckStatus::Failed).message = Some(format!("{err:#}"));
+            }
+        }
+    }
+    result
+}
+
+/// The report of a complete attestation verification.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct VerificationReport {
+    pub layers: Vec<LayerReport>,
+}
+
+impl VerificationReport {
+    /// Returns true if none of the checks in any layer failed.
+    pub fn is_ok(&self) -> bool {
+        self.layers.iter().all(LayerReport::is_ok)
+    }
+
+    /// Returns all failed checks across layers.
+    pub fn failed_checks(&self) -> impl Iterator<Item = &CheckReport> {
+        self.layers
+            .iter()
+            .flat_map(|layer| layer.checks.iter())
+            .filter(|check| check.status == CheckStatus::Failed)
+    }
+
+    /// Converts the report into a result, where the error lists the failed
+    /// checks by their identifiers.
+    pub fn to_result(&self) -> anyhow::Result<()> {
+        let failures: Vec<String> = self
+            .failed_checks()
+            .map(|check| match &check.message {
+                Some(message) => format!("{}: {}", check.id, message),
+                None => check.id.clone(),
+            })
+            .collect();
+        anyhow::ensure!(failures.is_empty(), "failed checks: {}", failures.join("; "));
+        Ok(())
+    }
+}
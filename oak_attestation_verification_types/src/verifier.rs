@@ -14,8 +14,16 @@
 // limitations under the License.
 //
 
+use alloc::vec;
+
 use oak_proto_rust::oak::attestation::v1::{AttestationResults, Endorsements, Evidence};
 
+use crate::report::{LayerReport, VerificationReport, VERIFY_CHECK};
+
+/// The layer name which the default implementation of
+/// [`AttestationVerifier::report`] uses.
+pub const ATTESTATION_LAYER: &str = "attestation";
+
 /// Trait that provides the functionality for appraising the attestation
 /// evidence and endorsements and producing attestation results.
 ///
@@ -40,4 +48,16 @@ pub trait AttestationVerifier: Send + Sync {
         evidence: &Evidence,
         endorsements: &Endorsements,
     ) -> anyhow::Result<AttestationResults>;
+
+    /// Verifies an attestation like `verify`, but records the outcome of
+    /// every layer and check in a report instead of stopping at the first
+    /// failure.
+    ///
+    /// The default implementation records the outcome of `verify` as a single
+    /// check.
+    fn report(&self, evidence: &Evidence, endorsements: &Endorsements) -> VerificationReport {
+        let mut layer = LayerReport::new(ATTESTATION_LAYER);
+        layer.check(VERIFY_CHECK, self.verify(evidence, endorsements));
+        VerificationReport { layers: vec![layer] }
+    }
 }
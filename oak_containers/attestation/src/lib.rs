@@ -128,15 +128,21 @@ impl InstanceKeys {
     }
 
     pub fn provide_group_keys(&self, group_keys: GroupKeysProto) -> anyhow::Result<GroupKeys> {
-        // Create server encryptor for decrypting the group keys received from the
-        // leader enclave.
         let encrypted_encryption_private_key = group_keys
             .encrypted_encryption_private_key
             .context("encrypted encryption key wasn't provided")?;
+        self.decrypt_group_keys(&encrypted_encryption_private_key)
+    }
 
+    /// Decrypts a group encryption private key received from the leader
+    /// enclave, which was encrypted with the instance encryption public key.
+    pub fn decrypt_group_keys(
+        &self,
+        encrypted_encryption_private_key: &EncryptedRequest,
+    ) -> anyhow::Result<GroupKeys> {
         // Decrypt group keys.
         let (_, mut decrypted_encryption_private_key, _) =
-            ServerEncryptor::decrypt(&encrypted_encryption_private_key, &self.encryption_key)
+            ServerEncryptor::decrypt(encrypted_encryption_private_key, &self.encryption_key)
                 .context("couldn't decrypt the encryption private key")?;
 
         let group_encryption_key =
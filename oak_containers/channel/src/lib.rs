@@ -77,7 +77,8 @@ mod tests {
     use oak_proto_rust::oak::{
         attestation::v1::Endorsements,
         containers::{
-            GetApplicationConfigResponse, GetImageResponse, SendAttestationEvidenceRequest,
+            ContainerBundleManifest, ContainerStatus, GetApplicationConfigResponse,
+            GetImageResponse, GetManifestContainerBundleRequest, SendAttestationEvidenceRequest,
         },
     };
     use tokio::net::TcpListener;
@@ -97,6 +98,7 @@ mod tests {
     impl Launcher for TestLauncherServer {
         type GetOakSystemImageStream = GetImageResponseStream;
         type GetContainerBundleStream = GetImageResponseStream;
+        type GetManifestContainerBundleStream = GetImageResponseStream;
 
         async fn get_oak_system_image(
             &self,
@@ -112,6 +114,20 @@ mod tests {
             Err(tonic::Status::unimplemented("unimplemented"))
         }
 
+        async fn get_container_bundle_manifest(
+            &self,
+            _request: Request<()>,
+        ) -> Result<Response<ContainerBundleManifest>, tonic::Status> {
+            Err(tonic::Status::unimplemented("unimplemented"))
+        }
+
+        async fn get_manifest_container_bundle(
+            &self,
+            _request: Request<GetManifestContainerBundleRequest>,
+        ) -> Result<Response<Self::GetManifestContainerBundleStream>, tonic::Status> {
+            Err(tonic::Status::unimplemented("unimplemented"))
+        }
+
         async fn get_application_config(
             &self,
             _request: Request<()>,
@@ -140,6 +156,13 @@ mod tests {
         ) -> Result<Response<()>, tonic::Status> {
             Err(tonic::Status::unimplemented("unimplemented"))
         }
+
+        async fn notify_container_status(
+            &self,
+            _request: Request<ContainerStatus>,
+        ) -> Result<Response<()>, tonic::Status> {
+            Err(tonic::Status::unimplemented("unimplemented"))
+        }
     }
 
     #[tokio::test]
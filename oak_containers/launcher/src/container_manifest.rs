@@ -0,0 +1,263 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Host-side description of the containers to run in the enclave.
+
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    path::{Path, PathBuf},
+    pin::Pin,
+};
+
+use anyhow::Context;
+use bytes::{Bytes, BytesMut};
+use futures::Stream;
+use oak_proto_rust::oak::containers::{
+    ContainerBundle, ContainerBundleFormat, ContainerBundleManifest, ResourceLimits, RestartPolicy,
+};
+use serde::Deserialize;
+use tokio::{
+    io::{AsyncReadExt, BufReader},
+    sync::mpsc,
+};
+use tokio_stream::wrappers::ReceiverStream;
+
+/// Name of the container when the launcher is given a single container bundle.
+const DEFAULT_CONTAINER_NAME: &str = "oakc";
+
+/// JSON manifest passed with `--container-manifest`, for example:
+///
+/// ```json
+/// {
+///   "containers": [
+///     { "name": "sidecar", "bundle": "sidecar.tar", "restart_policy": "ALWAYS" },
+///     { "name": "app", "bundle": "app.tar", "resource_limits": { "memory_bytes": 1073741824 } },
+///     { "name": "server", "bundle": "server_image", "format": "OCI_IMAGE_LAYOUT" }
+///   ]
+/// }
+/// ```
+///
+/// Bundle paths are relative to the directory of the manifest. OCI image
+/// layouts can be given either as a tarball or as a directory.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HostManifest {
+    containers: Vec<HostContainer>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HostContainer {
+    name: String,
+    bundle: PathBuf,
+    #[serde(default)]
+    restart_policy: Option<String>,
+    #[serde(default)]
+    resource_limits: Option<HostResourceLimits>,
+    #[serde(default)]
+    format: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct HostResourceLimits {
+    memory_bytes: u64,
+    cpu_millis: u64,
+    pids: i64,
+}
+
+/// Where the bundle of a container is read from when the orchestrator
+/// requests it.
+pub enum BundleSource {
+    /// A tarball.
+    File(PathBuf),
+    /// A directory, which is packed into a tarball while it is streamed.
+    Dir(PathBuf),
+}
+
+/// The containers to run in the enclave. The bundles stay on disk until the
+/// orchestrator requests them.
+pub struct ContainerManifest {
+    /// Manifest without the contents of the bundles.
+    pub manifest: ContainerBundleManifest,
+    bundles: BTreeMap<String, BundleSource>,
+}
+
+impl ContainerManifest {
+    /// Returns the bundle of the container with the given name.
+    pub fn bundle(&self, name: &str) -> Option<&BundleSource> {
+        self.bundles.get(name)
+    }
+
+    /// Returns the bundle to serve to orchestrators that expect a single OCI
+    /// runtime bundle, if the manifest consists of such a bundle.
+    pub fn legacy_bundle(&self) -> Option<&BundleSource> {
+        match self.manifest.containers.as_slice() {
+            [container]
+                if matches!(
+                    container.format(),
+                    ContainerBundleFormat::Unspecified | ContainerBundleFormat::OciRuntimeBundle
+                ) =>
+            {
+                self.bundle(&container.name)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Returns the manifest for a single container bundle.
+pub fn from_bundle(container_bundle: &Path) -> ContainerManifest {
+    ContainerManifest {
+        manifest: ContainerBundleManifest {
+            containers: vec![ContainerBundle {
+                name: DEFAULT_CONTAINER_NAME.to_string(),
+                ..Default::default()
+            }],
+        },
+        bundles: BTreeMap::from([(
+            DEFAULT_CONTAINER_NAME.to_string(),
+            BundleSource::File(container_bundle.to_path_buf()),
+        )]),
+    }
+}
+
+/// Loads a JSON manifest and checks that the bundles it refers to exist.
+pub async fn from_manifest(container_manifest: &Path) -> anyhow::Result<ContainerManifest> {
+    let host_manifest: HostManifest = serde_json::from_slice(
+        &tokio::fs::read(container_manifest)
+            .await
+            .with_context(|| format!("couldn't read container manifest {container_manifest:?}"))?,
+    )
+    .context("couldn't parse container manifest")?;
+    let base_dir = container_manifest.parent().unwrap_or(Path::new(""));
+
+    let mut containers = Vec::with_capacity(host_manifest.containers.len());
+    let mut bundles = BTreeMap::new();
+    for HostContainer { name, bundle, restart_policy, resource_limits, format } in
+        host_manifest.containers
+    {
+        let restart_policy = match restart_policy {
+            Some(restart_policy) => RestartPolicy::from_str_name(&restart_policy)
+                .with_context(|| format!("invalid restart policy {restart_policy:?}"))?,
+            None => RestartPolicy::Unspecified,
+        };
+        let format = match format {
+            Some(format) => ContainerBundleFormat::from_str_name(&format)
+                .with_context(|| format!("invalid container bundle format {format:?}"))?,
+            None => ContainerBundleFormat::Unspecified,
+        };
+        let bundle_path = base_dir.join(bundle);
+        let metadata = tokio::fs::metadata(&bundle_path)
+            .await
+            .with_context(|| format!("couldn't read container bundle {bundle_path:?}"))?;
+        let source = if format == ContainerBundleFormat::OciImageLayout && metadata.is_dir() {
+            BundleSource::Dir(bundle_path)
+        } else {
+            BundleSource::File(bundle_path)
+        };
+        anyhow::ensure!(
+            bundles.insert(name.clone(), source).is_none(),
+            "duplicate container name {name:?}"
+        );
+        containers.push(ContainerBundle {
+            name,
+            bundle: Default::default(),
+            restart_policy: restart_policy.into(),
+            resource_limits: resource_limits.map(
+                |HostResourceLimits { memory_bytes, cpu_millis, pids }| ResourceLimits {
+                    memory_bytes,
+                    cpu_millis,
+                    pids,
+                },
+            ),
+            format: format.into(),
+        });
+    }
+    anyhow::ensure!(!containers.is_empty(), "container manifest is empty");
+    Ok(ContainerManifest { manifest: ContainerBundleManifest { containers }, bundles })
+}
+
+/// Reads the bundle in chunks of at most `chunk_size` bytes.
+pub fn read_chunks(
+    source: &BundleSource,
+    chunk_size: usize,
+) -> Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send>> {
+    match source {
+        BundleSource::File(path) => {
+            let path = path.clone();
+            Box::pin(async_stream::try_stream! {
+                let mut reader = BufReader::new(tokio::fs::File::open(&path).await?);
+                loop {
+                    let mut buffer = BytesMut::with_capacity(chunk_size);
+                    if reader.read_buf(&mut buffer).await? == 0 {
+                        break;
+                    }
+                    yield buffer.freeze();
+                }
+            })
+        }
+        BundleSource::Dir(dir) => {
+            let dir = dir.clone();
+            let (sender, receiver) = mpsc::channel(1);
+            tokio::task::spawn_blocking(move || {
+                let mut builder = tar::Builder::new(ChunkWriter {
+                    sender: sender.clone(),
+                    chunk_size,
+                    buffer: BytesMut::new(),
+                });
+                // Image layouts built by Bazel link to their blobs, which the
+                // builder follows by default.
+                let result = builder
+                    .append_dir_all(".", &dir)
+                    .and_then(|()| builder.into_inner())
+                    .and_then(|mut writer| writer.flush());
+                if let Err(err) = result {
+                    // The receiver is gone if the orchestrator cancelled the request.
+                    let _ = sender.blocking_send(Err(err));
+                }
+            });
+            Box::pin(ReceiverStream::new(receiver))
+        }
+    }
+}
+
+/// Sends the written data to a channel in chunks.
+struct ChunkWriter {
+    sender: mpsc::Sender<std::io::Result<Bytes>>,
+    chunk_size: usize,
+    buffer: BytesMut,
+}
+
+impl Write for ChunkWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= self.chunk_size {
+            self.flush()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        while !self.buffer.is_empty() {
+            let chunk = self.buffer.split_to(self.buffer.len().min(self.chunk_size)).freeze();
+            self.sender
+                .blocking_send(Ok(chunk))
+                .map_err(|_| std::io::Error::from(std::io::ErrorKind::BrokenPipe))?;
+        }
+        Ok(())
+    }
+}
@@ -15,10 +15,12 @@
 
 #![feature(let_chains)]
 
+mod container_manifest;
 mod qemu;
 mod server;
 
 use std::{
+    collections::BTreeMap,
     fmt::Display,
     net::{IpAddr, Ipv4Addr, SocketAddr},
 };
@@ -28,6 +30,7 @@ use clap::{Parser, ValueEnum};
 use oak_grpc::oak::key_provisioning::v1::key_provisioning_client::KeyProvisioningClient;
 use oak_proto_rust::oak::{
     attestation::v1::{endorsements, Endorsements, Evidence, OakContainersEndorsements},
+    containers::ContainerStatus,
     key_provisioning::v1::{GetGroupKeysRequest, GetGroupKeysResponse},
     session::v1::EndorsedEvidence,
 };
@@ -72,8 +75,12 @@ pub enum ChannelType {
 pub struct Args {
     #[arg(long, required = true, value_parser = path_exists,)]
     pub system_image: std::path::PathBuf,
-    #[arg(long, required = true, value_parser = path_exists,)]
-    pub container_bundle: std::path::PathBuf,
+    #[arg(long, required_unless_present = "container_manifest", value_parser = path_exists,)]
+    pub container_bundle: Option<std::path::PathBuf>,
+    /// JSON manifest of the container bundles to run in the enclave, used
+    /// instead of `--container-bundle` to run multiple containers.
+    #[arg(long, conflicts_with = "container_bundle", value_parser = path_exists,)]
+    pub container_manifest: Option<std::path::PathBuf>,
     #[clap(skip)]
     pub application_config: Vec<u8>,
     #[command(flatten)]
@@ -140,6 +147,8 @@ pub struct Launcher {
     // Receiver that is used to get the Attestation Evidence from the server implementation.
     evidence_receiver: Option<oneshot::Receiver<Evidence>>,
     app_ready_notifier: Option<oneshot::Receiver<()>>,
+    // Latest status of every container that exited at least once, by name.
+    container_statuses: watch::Receiver<BTreeMap<String, ContainerStatus>>,
     orchestrator_key_provisioning_client: Option<KeyProvisioningClient<TonicChannel>>,
     trusted_app_channel: Channel,
     shutdown: Option<watch::Sender<()>>,
@@ -160,15 +169,24 @@ impl Launcher {
         let (shutdown_sender, mut shutdown_receiver) = watch::channel::<()>(());
         shutdown_receiver.mark_unchanged(); // Don't immediately notify on the initial value.
         let (app_notifier_sender, app_notifier_receiver) = oneshot::channel::<()>();
+        let (container_status_sender, container_status_receiver) = watch::channel(BTreeMap::new());
         let endorsements = get_endorsements();
+        let container_manifest = match (&args.container_manifest, &args.container_bundle) {
+            (Some(container_manifest), _) => {
+                container_manifest::from_manifest(container_manifest).await?
+            }
+            (None, Some(container_bundle)) => container_manifest::from_bundle(container_bundle),
+            (None, None) => anyhow::bail!("no container bundle provided"),
+        };
         let server = tokio::spawn(server::new(
             listener,
             vsock_listener,
             args.system_image,
-            args.container_bundle,
+            container_manifest,
             args.application_config,
             evidence_sender,
             app_notifier_sender,
+            container_status_sender,
             shutdown_receiver,
             endorsements,
         ));
@@ -211,6 +229,7 @@ impl Launcher {
             endorsed_evidence: None,
             evidence_receiver: Some(evidence_receiver),
             app_ready_notifier: Some(app_notifier_receiver),
+            container_statuses: container_status_receiver,
             orchestrator_key_provisioning_client: None,
             trusted_app_channel,
             shutdown: Some(shutdown_sender),
@@ -303,6 +322,13 @@ impl Launcher {
         Ok(get_group_keys_response)
     }
 
+    /// Returns the latest status of every container that exited at least once,
+    /// including how often it has been restarted. The statuses are reported
+    /// by the orchestrator over the host channel and aren't attested.
+    pub fn get_container_statuses(&self) -> Vec<ContainerStatus> {
+        self.container_statuses.borrow().values().cloned().collect()
+    }
+
     pub async fn wait(&mut self) -> Result<(), anyhow::Error> {
         self.vmm.wait().await?;
         Ok(())
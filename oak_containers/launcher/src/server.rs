@@ -14,13 +14,14 @@
 // limitations under the License.
 
 use std::{
+    collections::BTreeMap,
     pin::Pin,
     sync::{Arc, Mutex},
 };
 
 use anyhow::anyhow;
 use bytes::BytesMut;
-use futures::{FutureExt, Stream};
+use futures::{FutureExt, Stream, TryStreamExt};
 use oak_grpc::oak::containers::{
     launcher_server::{Launcher, LauncherServer},
     v1::hostlib_key_provisioning_server::{HostlibKeyProvisioning, HostlibKeyProvisioningServer},
@@ -29,7 +30,8 @@ use oak_proto_rust::oak::{
     attestation::v1::{Endorsements, Evidence},
     containers::{
         v1::{GetGroupKeysResponse, GetKeyProvisioningRoleResponse, KeyProvisioningRole},
-        GetApplicationConfigResponse, GetImageResponse, SendAttestationEvidenceRequest,
+        ContainerBundleManifest, ContainerStatus, GetApplicationConfigResponse, GetImageResponse,
+        GetManifestContainerBundleRequest, SendAttestationEvidenceRequest,
     },
 };
 use opentelemetry_proto::tonic::{
@@ -54,22 +56,25 @@ use tokio_stream::wrappers::TcpListenerStream;
 use tokio_vsock::VsockListener;
 use tonic::{transport::Server, Request, Response, Status};
 
+use crate::container_manifest::{read_chunks, BundleSource, ContainerManifest};
+
 // Most gRPC implementations limit message sizes to 4MiB. Let's stay
 // comfortably below that by limiting responses to 3MiB.
 const MAX_RESPONSE_SIZE: usize = 3 * 1024 * 1024;
 
 type GetImageResponseStream = Pin<Box<dyn Stream<Item = Result<GetImageResponse, Status>> + Send>>;
 
-#[derive(Default)]
 struct LauncherServerImplementation {
     system_image: std::path::PathBuf,
-    container_bundle: std::path::PathBuf,
+    container_manifest: ContainerManifest,
     application_config: Vec<u8>,
     // Will be used to send the Attestation Evidence to the Launcher.
     evidence_sender: Mutex<Option<oneshot::Sender<Evidence>>>,
     // Will be used to notify the untrusted application that the trusted application is ready and
     // listening on a socket address.
     app_ready_notifier: Mutex<Option<oneshot::Sender<()>>>,
+    // Will be used to share the status of the containers with the untrusted application.
+    container_status_sender: watch::Sender<BTreeMap<String, ContainerStatus>>,
     endorsements: Endorsements,
 }
 
@@ -77,6 +82,7 @@ struct LauncherServerImplementation {
 impl Launcher for LauncherServerImplementation {
     type GetOakSystemImageStream = GetImageResponseStream;
     type GetContainerBundleStream = GetImageResponseStream;
+    type GetManifestContainerBundleStream = GetImageResponseStream;
 
     async fn get_oak_system_image(
         &self,
@@ -101,19 +107,31 @@ impl Launcher for LauncherServerImplementation {
         &self,
         _request: Request<()>,
     ) -> Result<Response<Self::GetContainerBundleStream>, tonic::Status> {
-        let container_bundle_file = tokio::fs::File::open(&self.container_bundle).await?;
-
-        let mut reader = BufReader::new(container_bundle_file);
+        let bundle = self.container_manifest.legacy_bundle().ok_or_else(|| {
+            Status::failed_precondition(
+                "the containers can only be fetched with GetContainerBundleManifest",
+            )
+        })?;
+        Ok(Response::new(stream_bundle(bundle)))
+    }
 
-        let response_stream = async_stream::try_stream! {
-            while let mut buffer = BytesMut::with_capacity(MAX_RESPONSE_SIZE) && reader.read_buf(&mut buffer).await? > 0 {
-                yield GetImageResponse {
-                    image_chunk: buffer.freeze()
-                };
-            }
-        };
+    async fn get_container_bundle_manifest(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<ContainerBundleManifest>, tonic::Status> {
+        Ok(Response::new(self.container_manifest.manifest.clone()))
+    }
 
-        Ok(Response::new(Box::pin(response_stream) as Self::GetContainerBundleStream))
+    async fn get_manifest_container_bundle(
+        &self,
+        request: Request<GetManifestContainerBundleRequest>,
+    ) -> Result<Response<Self::GetManifestContainerBundleStream>, tonic::Status> {
+        let name = request.into_inner().name;
+        let bundle = self
+            .container_manifest
+            .bundle(&name)
+            .ok_or_else(|| Status::not_found(format!("no container named {name:?}")))?;
+        Ok(Response::new(stream_bundle(bundle)))
     }
 
     async fn get_application_config(
@@ -175,6 +193,27 @@ impl Launcher for LauncherServerImplementation {
             .map_err(|_err| tonic::Status::internal("couldn't send notification".to_string()))?;
         Ok(tonic::Response::new(()))
     }
+
+    async fn notify_container_status(
+        &self,
+        request: Request<ContainerStatus>,
+    ) -> Result<Response<()>, tonic::Status> {
+        let status = request.into_inner();
+        if status.restarting {
+            log::warn!(
+                "Container {} exited with code {:?}, restarting (restart count: {})",
+                status.name,
+                status.last_exit_code,
+                status.restart_count
+            );
+        } else {
+            log::info!("Container {} exited with code {:?}", status.name, status.last_exit_code);
+        }
+        self.container_status_sender.send_modify(|statuses| {
+            statuses.insert(status.name.clone(), status);
+        });
+        Ok(tonic::Response::new(()))
+    }
 }
 
 #[tonic::async_trait]
@@ -251,6 +290,15 @@ impl LogsService for LauncherServerImplementation {
     }
 }
 
+/// Streams the bundle from disk in chunks that fit into a response.
+fn stream_bundle(bundle: &BundleSource) -> GetImageResponseStream {
+    Box::pin(
+        read_chunks(bundle, MAX_RESPONSE_SIZE)
+            .map_ok(|image_chunk| GetImageResponse { image_chunk })
+            .map_err(Status::from),
+    )
+}
+
 // Clippy is not wrong, but hopefully the situation with two listeners is only
 // temporary.
 #[allow(clippy::too_many_arguments)]
@@ -258,19 +306,21 @@ pub async fn new(
     listener: TcpListener,
     vsock_listener: VsockListener,
     system_image: std::path::PathBuf,
-    container_bundle: std::path::PathBuf,
+    container_manifest: ContainerManifest,
     application_config: Vec<u8>,
     evidence_sender: oneshot::Sender<Evidence>,
     app_ready_notifier: oneshot::Sender<()>,
+    container_status_sender: watch::Sender<BTreeMap<String, ContainerStatus>>,
     shutdown: watch::Receiver<()>,
     endorsements: Endorsements,
 ) -> Result<(), anyhow::Error> {
     let server_impl = Arc::new(LauncherServerImplementation {
         system_image,
-        container_bundle,
+        container_manifest,
         application_config,
         evidence_sender: Mutex::new(Some(evidence_sender)),
         app_ready_notifier: Mutex::new(Some(app_ready_notifier)),
+        container_status_sender,
         endorsements,
     });
 
@@ -14,44 +14,244 @@
 // limitations under the License.
 
 use std::{
+    collections::HashSet,
     ffi::OsStr,
     os::unix::fs::lchown,
     path::{Path, PathBuf},
+    process::ExitStatus,
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
 use anyhow::Context;
-use bytes::Buf;
+use bytes::{Buf, Bytes};
 use nix::unistd::{Gid, Uid};
-use oci_spec::runtime::{LinuxIdMapping, LinuxIdMappingBuilder, Mount, Spec};
+use oak_proto_rust::oak::containers::{
+    ContainerBundle, ContainerBundleFormat, ContainerBundleManifest, ContainerStatus,
+    ResourceLimits, RestartPolicy,
+};
+use oci_spec::runtime::{
+    LinuxIdMapping, LinuxIdMappingBuilder, LinuxPidsBuilder, LinuxResources, Mount, Spec,
+};
+use tokio::{sync::mpsc::UnboundedSender, task::JoinSet};
 use tokio_util::sync::CancellationToken;
 
-pub async fn run<B: Buf>(
-    container_bundle: B,
+use crate::oci_image::OciImageLayout;
+
+/// Directory shared between all containers of the manifest.
+const SHARED_DIR_DESTINATION: &str = "/oak_utils/shared";
+
+/// Delay before a container that exited is started again for the first time.
+/// The delay doubles with every consecutive restart, up to
+/// `MAX_RESTART_DELAY`.
+const INITIAL_RESTART_DELAY: Duration = Duration::from_secs(1);
+
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(60);
+
+/// Containers that ran at least this long before exiting are restarted after
+/// the initial delay again.
+const RESTART_DELAY_RESET_THRESHOLD: Duration = Duration::from_secs(300);
+
+/// CPU period used to express CPU limits as a quota, in microseconds.
+const CPU_PERIOD_MICROS: u64 = 100_000;
+
+/// Checks that the manifest contains at least one container and that container
+/// names are valid and unique.
+pub fn validate_manifest(manifest: &ContainerBundleManifest) -> anyhow::Result<()> {
+    anyhow::ensure!(!manifest.containers.is_empty(), "container manifest is empty");
+    let mut names = HashSet::new();
+    for container in &manifest.containers {
+        anyhow::ensure!(
+            !container.name.is_empty()
+                && container
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+            "invalid container name {:?}",
+            container.name
+        );
+        anyhow::ensure!(
+            names.insert(container.name.as_str()),
+            "duplicate container name {:?}",
+            container.name
+        );
+    }
+    Ok(())
+}
+
+/// Returns the contents that identify the container and are measured into its
+/// event: the bundle itself for OCI runtime bundles, and the image manifest for
+/// OCI image layouts.
+pub fn measured_bundle(container: &ContainerBundle) -> anyhow::Result<Bytes> {
+    match container.format() {
+        ContainerBundleFormat::Unspecified | ContainerBundleFormat::OciRuntimeBundle => {
+            Ok(container.bundle.clone())
+        }
+        ContainerBundleFormat::OciImageLayout => Ok(OciImageLayout::parse(&container.bundle)
+            .with_context(|| format!("invalid image layout of container {}", container.name))?
+            .manifest()),
+    }
+}
+
+/// Unpacks and runs all containers of the manifest, restarting them according
+/// to their restart policy. The status of a container is sent to
+/// `status_sender` whenever it exits. Returns once a container with the
+/// `TERMINATE` policy exits or no container is left running.
+///
+/// Only the last container gets access to the orchestrator IPC socket, as the
+/// crypto and sealing services are bound to its measurement.
+#[allow(clippy::too_many_arguments)]
+pub async fn run(
+    manifest: ContainerBundleManifest,
     container_dir: &Path,
     runtime_uid: Uid,
     runtime_gid: Gid,
     ipc_socket_path: &Path,
+    status_sender: UnboundedSender<ContainerStatus>,
     cancellation_token: CancellationToken,
 ) -> Result<(), anyhow::Error> {
-    tokio::fs::create_dir_all(container_dir).await?;
-    log::info!("Unpacking container bundle");
+    validate_manifest(&manifest)?;
+
+    let shared_dir = container_dir.join("shared");
+    tokio::fs::create_dir_all(&shared_dir).await?;
+    lchown(&shared_dir, Some(runtime_uid.into()), Some(runtime_gid.into()))
+        .context(format!("failed to chown path {:?}", shared_dir))?;
+
+    let container_count = manifest.containers.len();
+    let mut containers = Vec::with_capacity(container_count);
+    for (index, container) in manifest.containers.into_iter().enumerate() {
+        let restart_policy = container.restart_policy();
+        let format = container.format();
+        let ContainerBundle { name, bundle, resource_limits, .. } = container;
+        let mut setup = ContainerSetup {
+            bundle_dir: container_dir.join("bundles").join(&name),
+            name,
+            bundle,
+            format,
+            resource_limits: resource_limits.unwrap_or_default(),
+            shared_dir: shared_dir.clone(),
+            runtime_uid,
+            runtime_gid,
+            ipc_socket_path: (index + 1 == container_count).then(|| ipc_socket_path.to_path_buf()),
+        };
+        setup.unpack()?;
+        if !matches!(restart_policy, RestartPolicy::OnFailure | RestartPolicy::Always) {
+            setup.bundle = Bytes::new();
+        }
+        containers.push((Arc::new(setup), restart_policy));
+    }
+
+    let mut supervisors = JoinSet::new();
+    for (setup, restart_policy) in containers {
+        let status_sender = status_sender.clone();
+        let cancellation_token = cancellation_token.clone();
+        supervisors.spawn(async move {
+            let result = supervise_container(
+                setup.clone(),
+                restart_policy,
+                status_sender,
+                cancellation_token,
+            )
+            .await;
+            (setup.name.clone(), result)
+        });
+    }
+
+    loop {
+        let (name, result) = tokio::select! {
+            joined = supervisors.join_next() => match joined {
+                Some(joined) => joined.context("container supervisor panicked")?,
+                None => {
+                    log::info!("No containers left running");
+                    break;
+                }
+            },
+            _ = cancellation_token.cancelled() => break,
+        };
+        let terminate = result.context(format!("failed to run container {name}"))?;
+        if terminate {
+            log::info!("Container {name} exited, shutting down");
+            break;
+        }
+    }
+
+    cancellation_token.cancel();
+    Ok(())
+}
+
+/// Everything needed to unpack a container into its bundle directory. Every
+/// restart starts from a freshly unpacked bundle, so that changes a previous
+/// run made to the root filesystem don't persist.
+struct ContainerSetup {
+    name: String,
+    // Empty for containers that are never restarted, so that their bundle
+    // isn't kept in memory.
+    bundle: Bytes,
+    format: ContainerBundleFormat,
+    resource_limits: ResourceLimits,
+    bundle_dir: PathBuf,
+    shared_dir: PathBuf,
+    runtime_uid: Uid,
+    runtime_gid: Gid,
+    ipc_socket_path: Option<PathBuf>,
+}
+
+impl ContainerSetup {
+    fn unpack(&self) -> Result<(), anyhow::Error> {
+        setup_container(
+            &self.name,
+            self.bundle.clone(),
+            self.format,
+            &self.resource_limits,
+            &self.bundle_dir,
+            &self.shared_dir,
+            self.runtime_uid,
+            self.runtime_gid,
+            self.ipc_socket_path.as_deref(),
+        )
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn setup_container(
+    name: &str,
+    bundle: Bytes,
+    format: ContainerBundleFormat,
+    resource_limits: &ResourceLimits,
+    bundle_dir: &Path,
+    shared_dir: &Path,
+    runtime_uid: Uid,
+    runtime_gid: Gid,
+    ipc_socket_path: Option<&Path>,
+) -> Result<(), anyhow::Error> {
+    if bundle_dir.exists() {
+        std::fs::remove_dir_all(bundle_dir)
+            .context(format!("failed to remove previous bundle {:?}", bundle_dir))?;
+    }
+    std::fs::create_dir_all(bundle_dir)?;
+    log::info!("Unpacking container bundle {name}");
 
     // Ensure that the archive, with the reader and bundle, go out of scope so that
     // we don't accidentally keep a copy of the container bundle around in memory.
-    {
-        let mut archive = tar::Archive::new(container_bundle.reader());
-        archive.unpack(container_dir)?;
+    match format {
+        ContainerBundleFormat::Unspecified | ContainerBundleFormat::OciRuntimeBundle => {
+            let mut archive = tar::Archive::new(bundle.reader());
+            archive.unpack(bundle_dir)?;
+        }
+        ContainerBundleFormat::OciImageLayout => {
+            OciImageLayout::parse(&bundle)?.unpack(bundle_dir)?;
+        }
     }
 
-    for entry in walkdir::WalkDir::new(container_dir) {
+    for entry in walkdir::WalkDir::new(bundle_dir) {
         let entry = entry?;
         lchown(entry.path(), Some(runtime_uid.into()), Some(runtime_gid.into()))
             .context(format!("failed to chown path {:?}", entry.path()))?;
     }
 
-    log::info!("Setting up container");
+    log::info!("Setting up container {name}");
 
-    let spec_path = container_dir.join("config.json");
+    let spec_path = bundle_dir.join("config.json");
     let mut spec = Spec::load(&spec_path).context("error reading OCI spec")?;
 
     // Inject any nvidia cards, if we have a CDI spec available.
@@ -73,12 +273,22 @@ pub async fn run<B: Buf>(
     }
 
     let mut mounts = spec.mounts().as_ref().cloned().unwrap_or_default();
+    if let Some(ipc_socket_path) = ipc_socket_path {
+        mounts.push({
+            let mut mount = Mount::default();
+            mount.set_source(Some(ipc_socket_path.into()));
+            mount.set_destination(PathBuf::from("/oak_utils/orchestrator_ipc"));
+            mount.set_typ(Some("bind".to_string()));
+            mount.set_options(Some(vec!["rbind".to_string()]));
+            mount
+        });
+    }
     mounts.push({
         let mut mount = Mount::default();
-        mount.set_source(Some(ipc_socket_path.into()));
-        mount.set_destination(PathBuf::from("/oak_utils/orchestrator_ipc"));
+        mount.set_source(Some(shared_dir.into()));
+        mount.set_destination(PathBuf::from(SHARED_DIR_DESTINATION));
         mount.set_typ(Some("bind".to_string()));
-        mount.set_options(Some(vec!["rbind".to_string()]));
+        mount.set_options(Some(vec!["rbind".to_string(), "rw".to_string()]));
         mount
     });
     spec.set_mounts(Some(mounts));
@@ -109,17 +319,152 @@ pub async fn run<B: Buf>(
             .collect()
     });
     linux.set_gid_mappings(gid_mappings);
+    let mut resources = linux.resources().as_ref().cloned().unwrap_or_default();
+    apply_resource_limits(&mut resources, resource_limits)?;
+    linux.set_resources(Some(resources));
     spec.set_linux(Some(linux));
     spec.save(spec_path).context("error writing OCI spec")?;
 
+    Ok(())
+}
+
+fn apply_resource_limits(
+    resources: &mut LinuxResources,
+    resource_limits: &ResourceLimits,
+) -> Result<(), anyhow::Error> {
+    if resource_limits.memory_bytes > 0 {
+        let mut memory = resources.memory().as_ref().cloned().unwrap_or_default();
+        memory.set_limit(Some(
+            resource_limits.memory_bytes.try_into().context("memory limit is too large")?,
+        ));
+        resources.set_memory(Some(memory));
+    }
+    if resource_limits.cpu_millis > 0 {
+        let quota = resource_limits
+            .cpu_millis
+            .checked_mul(CPU_PERIOD_MICROS / 1000)
+            .and_then(|quota| i64::try_from(quota).ok())
+            .context("CPU limit is too large")?;
+        let mut cpu = resources.cpu().as_ref().cloned().unwrap_or_default();
+        cpu.set_quota(Some(quota));
+        cpu.set_period(Some(CPU_PERIOD_MICROS));
+        resources.set_cpu(Some(cpu));
+    }
+    if resource_limits.pids > 0 {
+        resources.set_pids(Some(LinuxPidsBuilder::default().limit(resource_limits.pids).build()?));
+    }
+    Ok(())
+}
+
+/// What happens after a container exits.
+#[derive(Debug, PartialEq)]
+enum ExitAction {
+    /// The enclave shuts down.
+    Terminate,
+    /// The container stays stopped.
+    Stop,
+    Restart,
+}
+
+impl ExitAction {
+    fn new(restart_policy: RestartPolicy, status: &ExitStatus) -> Self {
+        match restart_policy {
+            RestartPolicy::Unspecified | RestartPolicy::Terminate => Self::Terminate,
+            RestartPolicy::Never => Self::Stop,
+            RestartPolicy::OnFailure if status.success() => Self::Stop,
+            RestartPolicy::OnFailure | RestartPolicy::Always => Self::Restart,
+        }
+    }
+}
+
+/// Exponential backoff between consecutive restarts of a container.
+struct RestartBackoff {
+    next_delay: Duration,
+}
+
+impl RestartBackoff {
+    fn new() -> Self {
+        Self { next_delay: INITIAL_RESTART_DELAY }
+    }
+
+    /// Returns the delay before restarting a container that exited after
+    /// running for `run_time`.
+    fn delay(&mut self, run_time: Duration) -> Duration {
+        if run_time >= RESTART_DELAY_RESET_THRESHOLD {
+            self.next_delay = INITIAL_RESTART_DELAY;
+        }
+        let delay = self.next_delay;
+        self.next_delay = (delay * 2).min(MAX_RESTART_DELAY);
+        delay
+    }
+}
+
+/// Runs the container until it exits and shouldn't be restarted anymore.
+/// Returns whether the enclave should shut down.
+async fn supervise_container(
+    setup: Arc<ContainerSetup>,
+    restart_policy: RestartPolicy,
+    status_sender: UnboundedSender<ContainerStatus>,
+    cancellation_token: CancellationToken,
+) -> Result<bool, anyhow::Error> {
+    let name = &setup.name;
+    let mut backoff = RestartBackoff::new();
+    let mut restart_count = 0u32;
+    loop {
+        let start = Instant::now();
+        let status = run_container(
+            name,
+            &setup.bundle_dir,
+            &setup.shared_dir,
+            setup.runtime_uid,
+            setup.runtime_gid,
+        )
+        .await?;
+        log::info!("Container {name} exited with status {status:?}");
+
+        let action = ExitAction::new(restart_policy, &status);
+        // The receiver only goes away when the orchestrator shuts down.
+        let _ = status_sender.send(ContainerStatus {
+            name: name.to_string(),
+            restart_count,
+            last_exit_code: status.code(),
+            restarting: action == ExitAction::Restart,
+        });
+        match action {
+            ExitAction::Terminate => return Ok(true),
+            ExitAction::Stop => return Ok(false),
+            ExitAction::Restart => {}
+        }
+
+        let delay = backoff.delay(start.elapsed());
+        log::info!("Restarting container {name} in {delay:?}");
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = cancellation_token.cancelled() => return Ok(false),
+        }
+        let unpack = setup.clone();
+        tokio::task::spawn_blocking(move || unpack.unpack())
+            .await
+            .context("container setup panicked")??;
+        restart_count = restart_count.saturating_add(1);
+    }
+}
+
+async fn run_container(
+    name: &str,
+    bundle_dir: &Path,
+    shared_dir: &Path,
+    runtime_uid: Uid,
+    runtime_gid: Gid,
+) -> Result<ExitStatus, anyhow::Error> {
     let mut start_trusted_app_cmd = {
         let mut cmd = tokio::process::Command::new("/bin/systemd-run");
-        let container_dir: &str =
-            container_dir.as_os_str().try_into().expect("invalid container path");
+        let bundle_dir: &str = bundle_dir.as_os_str().try_into().expect("invalid container path");
+        let shared_dir: &str = shared_dir.as_os_str().try_into().expect("invalid shared path");
         cmd.args([
-            "--property=RuntimeDirectory=oakc",
+            format!("--property=RuntimeDirectory=oakc/{}", name).as_str(),
             "--property=ProtectSystem=strict",
-            format!("--property=ReadWritePaths={}", container_dir).as_str(),
+            format!("--property=ReadWritePaths={} {}", bundle_dir, shared_dir).as_str(),
             format!("--uid={}", runtime_uid).as_str(),
             format!("--gid={}", runtime_gid).as_str(),
             "--pty",
@@ -128,18 +473,119 @@ pub async fn run<B: Buf>(
             "/bin/runc",
             "--root=${RUNTIME_DIRECTORY}/runc",
             "run",
-            format!("--bundle={}", container_dir).as_str(),
-            "oakc",
+            format!("--bundle={}", bundle_dir).as_str(),
+            name,
         ]);
         cmd
     };
 
-    let status = start_trusted_app_cmd
+    start_trusted_app_cmd
         .status()
         .await
-        .context(format!("failed to run trusted app, cmd: {start_trusted_app_cmd:?}"))?;
-    log::info!("Container exited with status {status:?}");
+        .context(format!("failed to run trusted app, cmd: {start_trusted_app_cmd:?}"))
+}
 
-    cancellation_token.cancel();
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use std::os::unix::process::ExitStatusExt;
+
+    use super::*;
+
+    fn manifest(names: &[&str]) -> ContainerBundleManifest {
+        ContainerBundleManifest {
+            containers: names
+                .iter()
+                .map(|name| ContainerBundle { name: name.to_string(), ..Default::default() })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn validate_manifest_accepts_unique_names() {
+        assert!(validate_manifest(&manifest(&["oakc"])).is_ok());
+        assert!(validate_manifest(&manifest(&["app", "sidecar-1", "log_exporter"])).is_ok());
+    }
+
+    #[test]
+    fn validate_manifest_rejects_invalid_manifests() {
+        assert!(validate_manifest(&manifest(&[])).is_err());
+        assert!(validate_manifest(&manifest(&[""])).is_err());
+        assert!(validate_manifest(&manifest(&["../app"])).is_err());
+        assert!(validate_manifest(&manifest(&["app", "app"])).is_err());
+    }
+
+    #[test]
+    fn exit_action_follows_restart_policy() {
+        let success = ExitStatus::from_raw(0);
+        let failure = ExitStatus::from_raw(1 << 8);
+
+        assert_eq!(ExitAction::new(RestartPolicy::Unspecified, &success), ExitAction::Terminate);
+        assert_eq!(ExitAction::new(RestartPolicy::Terminate, &failure), ExitAction::Terminate);
+        assert_eq!(ExitAction::new(RestartPolicy::Never, &failure), ExitAction::Stop);
+        assert_eq!(ExitAction::new(RestartPolicy::OnFailure, &success), ExitAction::Stop);
+        assert_eq!(ExitAction::new(RestartPolicy::OnFailure, &failure), ExitAction::Restart);
+        assert_eq!(ExitAction::new(RestartPolicy::Always, &success), ExitAction::Restart);
+    }
+
+    #[test]
+    fn restart_backoff_doubles_until_reset() {
+        let mut backoff = RestartBackoff::new();
+        let crash = Duration::from_secs(1);
+
+        let delays: Vec<Duration> = (0..8).map(|_| backoff.delay(crash)).collect();
+        assert_eq!(delays, [1, 2, 4, 8, 16, 32, 60, 60].map(Duration::from_secs).to_vec(),);
+        assert_eq!(backoff.delay(RESTART_DELAY_RESET_THRESHOLD), INITIAL_RESTART_DELAY);
+        assert_eq!(backoff.delay(crash), INITIAL_RESTART_DELAY * 2);
+    }
+
+    #[test]
+    fn apply_resource_limits_overrides_set_limits() {
+        let mut resources = LinuxResources::default();
+        apply_resource_limits(
+            &mut resources,
+            &ResourceLimits { memory_bytes: 1 << 30, cpu_millis: 1500, pids: 0 },
+        )
+        .unwrap();
+
+        let resources = serde_json::to_value(&resources).unwrap();
+        assert_eq!(resources["memory"]["limit"], 1 << 30);
+        assert_eq!(resources["cpu"]["quota"], 150_000);
+        assert_eq!(resources["cpu"]["period"], CPU_PERIOD_MICROS);
+        assert!(resources.get("pids").is_none());
+    }
+
+    #[test]
+    fn unpack_replaces_previous_bundle() {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, contents) in [
+            ("config.json", serde_json::to_vec(&Spec::default()).unwrap()),
+            ("rootfs/file", b"original".to_vec()),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_mode(0o644);
+            header.set_size(contents.len() as u64);
+            builder.append_data(&mut header, path, contents.as_slice()).unwrap();
+        }
+        let container_dir = tempfile::tempdir().unwrap();
+        let setup = ContainerSetup {
+            name: "app".to_string(),
+            bundle: builder.into_inner().unwrap().into(),
+            format: ContainerBundleFormat::OciRuntimeBundle,
+            resource_limits: ResourceLimits::default(),
+            bundle_dir: container_dir.path().join("bundles/app"),
+            shared_dir: container_dir.path().join("shared"),
+            runtime_uid: Uid::current(),
+            runtime_gid: Gid::current(),
+            ipc_socket_path: None,
+        };
+        let rootfs = setup.bundle_dir.join("rootfs");
+
+        setup.unpack().unwrap();
+        std::fs::write(rootfs.join("file"), b"modified").unwrap();
+        std::fs::write(rootfs.join("added"), b"added").unwrap();
+        setup.unpack().unwrap();
+
+        assert_eq!(std::fs::read(rootfs.join("file")).unwrap(), b"original");
+        assert!(!rootfs.join("added").exists());
+    }
 }
@@ -0,0 +1,140 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reports the status of the containers to the launcher and as metrics.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use oak_containers_agent::metrics::OakObserver;
+use oak_proto_rust::oak::containers::ContainerStatus;
+use opentelemetry::{metrics::MetricsError, KeyValue};
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::launcher_client::LauncherClient;
+
+/// Latest status of every container that exited at least once, by name.
+#[derive(Clone, Default)]
+pub struct ContainerStatuses(Arc<Mutex<BTreeMap<String, ContainerStatus>>>);
+
+impl ContainerStatuses {
+    fn update(&self, status: ContainerStatus) {
+        self.0.lock().unwrap().insert(status.name.clone(), status);
+    }
+
+    /// Returns the latest statuses, ordered by container name.
+    pub fn get(&self) -> Vec<ContainerStatus> {
+        self.0.lock().unwrap().values().cloned().collect()
+    }
+}
+
+pub struct ContainerStatusReporter {
+    statuses: ContainerStatuses,
+}
+
+impl ContainerStatusReporter {
+    /// Creates a reporter and registers the container metrics with the
+    /// observer.
+    pub fn new(observer: &mut OakObserver) -> Self {
+        let statuses = ContainerStatuses::default();
+        if let Err(err) = register_metrics(observer, &statuses) {
+            log::warn!("couldn't register container metrics: {err:?}");
+        }
+        Self { statuses }
+    }
+
+    /// Returns the statuses tracked by this reporter, for serving them to the
+    /// application through the orchestrator IPC socket.
+    pub fn statuses(&self) -> ContainerStatuses {
+        self.statuses.clone()
+    }
+
+    /// Forwards the statuses sent by the container runtime to the launcher
+    /// until the runtime stops.
+    pub async fn run(
+        self,
+        mut receiver: UnboundedReceiver<ContainerStatus>,
+        launcher_client: Arc<LauncherClient>,
+    ) -> anyhow::Result<()> {
+        while let Some(status) = receiver.recv().await {
+            self.statuses.update(status.clone());
+            if let Err(err) = launcher_client.notify_container_status(status).await {
+                log::warn!("couldn't notify launcher of container status: {err:?}");
+            }
+        }
+        Ok(())
+    }
+}
+
+fn register_metrics(
+    observer: &mut OakObserver,
+    statuses: &ContainerStatuses,
+) -> Result<(), MetricsError> {
+    let restart_statuses = statuses.clone();
+    observer.register_metric(
+        observer
+            .meter
+            .u64_observable_counter("container_restarts")
+            .with_description("Number of times a container has been restarted")
+            .with_callback(move |counter| {
+                for status in restart_statuses.get() {
+                    counter.observe(
+                        status.restart_count.into(),
+                        &[KeyValue::new("container", status.name)],
+                    );
+                }
+            })
+            .try_init()?,
+    );
+
+    let exit_code_statuses = statuses.clone();
+    observer.register_metric(
+        observer
+            .meter
+            .i64_observable_gauge("container_last_exit_code")
+            .with_description("Exit code of the last run of a container")
+            .with_callback(move |gauge| {
+                for status in exit_code_statuses.get() {
+                    if let Some(exit_code) = status.last_exit_code {
+                        gauge.observe(exit_code.into(), &[KeyValue::new("container", status.name)]);
+                    }
+                }
+            })
+            .try_init()?,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(name: &str, restart_count: u32) -> ContainerStatus {
+        ContainerStatus { name: name.to_string(), restart_count, ..Default::default() }
+    }
+
+    #[test]
+    fn statuses_keep_latest_status_per_container() {
+        let statuses = ContainerStatuses::default();
+
+        statuses.update(status("sidecar", 0));
+        statuses.update(status("app", 0));
+        statuses.update(status("sidecar", 1));
+
+        assert_eq!(statuses.get(), vec![status("app", 0), status("sidecar", 1)]);
+    }
+}
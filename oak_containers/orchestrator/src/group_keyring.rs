@@ -0,0 +1,422 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Versioned group keys distributed with Key Provisioning.
+//!
+//! The leader enclave starts with the group keys of epoch 0 and generates a
+//! new epoch on every rotation. Keys of the previous epoch stay usable for
+//! decryption until their grace period elapses, while new data is encrypted to
+//! the current epoch. Followers mirror the keyring of the leader by
+//! periodically fetching it with `GetGroupKeys`, and drop keys of previous
+//! epochs once their remaining grace period elapses.
+//!
+//! The leader only attests the group keys of epoch 0, which it generates on
+//! startup. A restarted leader starts over at epoch 0 with new keys and a new
+//! keyring identifier, which followers adopt in place of their keyring.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use oak_containers_attestation::{GroupKeys, InstanceKeys};
+use oak_crypto::encryption_key::generate_encryption_key_pair;
+use oak_proto_rust::oak::key_provisioning::v1::{GroupKeyEpoch, GroupKeys as GroupKeysProto};
+use rand_core::{OsRng, RngCore};
+
+/// Epoch of the group keys whose public key is part of the attestation
+/// evidence of the leader enclave.
+pub const ATTESTED_EPOCH: u32 = 0;
+
+/// Size of the random keyring identifier in bytes.
+const KEYRING_ID_SIZE: usize = 16;
+
+pub struct GroupKeyring {
+    is_leader: bool,
+    state: RwLock<KeyringState>,
+}
+
+struct KeyringState {
+    keyring_id: Vec<u8>,
+    current_epoch: u32,
+    epochs: BTreeMap<u32, EpochKeys>,
+}
+
+struct EpochKeys {
+    group_keys: Arc<GroupKeys>,
+    // Point in time after which the keys can't be used anymore. Unset for the
+    // current epoch.
+    expires_at: Option<Instant>,
+}
+
+impl EpochKeys {
+    fn is_valid(&self, now: Instant) -> bool {
+        self.expires_at.is_none_or(|expires_at| now < expires_at)
+    }
+}
+
+impl GroupKeyring {
+    /// Creates the keyring of the leader enclave, with `group_keys` as epoch 0.
+    pub fn new_leader(group_keys: GroupKeys) -> Self {
+        let mut keyring_id = vec![0; KEYRING_ID_SIZE];
+        OsRng.fill_bytes(&mut keyring_id);
+        let epochs = BTreeMap::from([(
+            ATTESTED_EPOCH,
+            EpochKeys { group_keys: Arc::new(group_keys), expires_at: None },
+        )]);
+        Self {
+            is_leader: true,
+            state: RwLock::new(KeyringState { keyring_id, current_epoch: ATTESTED_EPOCH, epochs }),
+        }
+    }
+
+    /// Creates the keyring of a follower enclave from the group keys provided
+    /// by the leader enclave.
+    pub fn new_follower(
+        instance_keys: &InstanceKeys,
+        group_keys: GroupKeysProto,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            is_leader: false,
+            state: RwLock::new(decrypt_keyring(instance_keys, group_keys, Instant::now())?),
+        })
+    }
+
+    pub fn current_epoch(&self) -> u32 {
+        self.state.read().unwrap().current_epoch
+    }
+
+    /// Returns the group keys of the given epoch, or of the current epoch if
+    /// none is provided, together with the epoch of the keys.
+    pub fn group_keys(&self, epoch: Option<u32>) -> anyhow::Result<(u32, Arc<GroupKeys>)> {
+        self.group_keys_at(epoch, Instant::now())
+    }
+
+    fn group_keys_at(
+        &self,
+        epoch: Option<u32>,
+        now: Instant,
+    ) -> anyhow::Result<(u32, Arc<GroupKeys>)> {
+        let state = self.state.read().unwrap();
+        let epoch = epoch.unwrap_or(state.current_epoch);
+        state
+            .epochs
+            .get(&epoch)
+            .filter(|keys| keys.is_valid(now))
+            .map(|keys| (epoch, keys.group_keys.clone()))
+            .with_context(|| format!("no valid group keys for epoch {epoch}"))
+    }
+
+    /// Generates the group keys of a new epoch, which becomes the current one.
+    /// Keys of the previous epoch remain usable for `grace_period`. Returns
+    /// the new epoch and the corresponding group encryption public key.
+    pub fn rotate(&self, grace_period: Duration) -> anyhow::Result<(u32, Vec<u8>)> {
+        self.rotate_at(grace_period, Instant::now())
+    }
+
+    fn rotate_at(&self, grace_period: Duration, now: Instant) -> anyhow::Result<(u32, Vec<u8>)> {
+        anyhow::ensure!(self.is_leader, "only the leader enclave can rotate group keys");
+        let mut state = self.state.write().unwrap();
+        let previous_epoch = state.current_epoch;
+        let epoch = previous_epoch.checked_add(1).context("group key epoch overflow")?;
+        state.epochs.retain(|_, keys| keys.is_valid(now));
+        if let Some(previous_keys) = state.epochs.get_mut(&previous_epoch) {
+            previous_keys.expires_at = Some(now + grace_period);
+        }
+
+        let (encryption_key, encryption_public_key) = generate_encryption_key_pair();
+        state.epochs.insert(
+            epoch,
+            EpochKeys { group_keys: Arc::new(GroupKeys { encryption_key }), expires_at: None },
+        );
+        state.current_epoch = epoch;
+        Ok((epoch, encryption_public_key))
+    }
+
+    /// Returns all valid group keys, encrypted with the encryption public key
+    /// of a follower enclave.
+    pub fn encrypted_group_keys(
+        &self,
+        peer_encryption_public_key: &[u8],
+    ) -> anyhow::Result<GroupKeysProto> {
+        self.encrypted_group_keys_at(peer_encryption_public_key, Instant::now())
+    }
+
+    fn encrypted_group_keys_at(
+        &self,
+        peer_encryption_public_key: &[u8],
+        now: Instant,
+    ) -> anyhow::Result<GroupKeysProto> {
+        let state = self.state.read().unwrap();
+        let mut group_keys = GroupKeysProto {
+            epoch: state.current_epoch,
+            keyring_id: state.keyring_id.clone(),
+            ..Default::default()
+        };
+        for (&epoch, keys) in state.epochs.iter().filter(|(_, keys)| keys.is_valid(now)) {
+            let encrypted_encryption_private_key = keys
+                .group_keys
+                .encrypted_group_encryption_key(peer_encryption_public_key)
+                .with_context(|| format!("couldn't encrypt group keys of epoch {epoch}"))?;
+            if epoch == state.current_epoch {
+                group_keys.encrypted_encryption_private_key =
+                    Some(encrypted_encryption_private_key);
+            } else {
+                let remaining_grace_period = keys
+                    .expires_at
+                    .map(|expires_at| {
+                        prost_types::Duration::try_from(expires_at.saturating_duration_since(now))
+                            .context("invalid grace period")
+                    })
+                    .transpose()?;
+                group_keys.previous_epochs.push(GroupKeyEpoch {
+                    epoch,
+                    encrypted_encryption_private_key: Some(encrypted_encryption_private_key),
+                    remaining_grace_period,
+                });
+            }
+        }
+        Ok(group_keys)
+    }
+
+    /// Replaces the keys of a follower enclave with the group keys provided by
+    /// the leader enclave.
+    ///
+    /// Within the same keyring the epoch must not go back. A keyring with a
+    /// new identifier comes from a restarted leader enclave and replaces the
+    /// keys regardless of its epoch.
+    pub fn update(
+        &self,
+        instance_keys: &InstanceKeys,
+        group_keys: GroupKeysProto,
+    ) -> anyhow::Result<()> {
+        self.update_at(instance_keys, group_keys, Instant::now())
+    }
+
+    fn update_at(
+        &self,
+        instance_keys: &InstanceKeys,
+        group_keys: GroupKeysProto,
+        now: Instant,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(!self.is_leader, "the leader enclave doesn't receive group keys");
+        let keyring = decrypt_keyring(instance_keys, group_keys, now)?;
+        let mut state = self.state.write().unwrap();
+        if keyring.keyring_id == state.keyring_id {
+            anyhow::ensure!(
+                keyring.current_epoch >= state.current_epoch,
+                "group key epoch went back from {} to {}",
+                state.current_epoch,
+                keyring.current_epoch
+            );
+        } else {
+            log::warn!(
+                "the leader enclave restarted, replacing group keys of epoch {} with epoch {}",
+                state.current_epoch,
+                keyring.current_epoch
+            );
+        }
+        *state = keyring;
+        Ok(())
+    }
+}
+
+fn decrypt_keyring(
+    instance_keys: &InstanceKeys,
+    group_keys: GroupKeysProto,
+    now: Instant,
+) -> anyhow::Result<KeyringState> {
+    let current = GroupKeyEpoch {
+        epoch: group_keys.epoch,
+        encrypted_encryption_private_key: group_keys.encrypted_encryption_private_key,
+        remaining_grace_period: None,
+    };
+    let mut epochs = BTreeMap::new();
+    for GroupKeyEpoch { epoch, encrypted_encryption_private_key, remaining_grace_period } in
+        core::iter::once(current).chain(group_keys.previous_epochs)
+    {
+        let encrypted_encryption_private_key = encrypted_encryption_private_key
+            .with_context(|| format!("no encrypted group keys for epoch {epoch}"))?;
+        let keys = instance_keys
+            .decrypt_group_keys(&encrypted_encryption_private_key)
+            .with_context(|| format!("couldn't decrypt group keys of epoch {epoch}"))?;
+        let expires_at = remaining_grace_period
+            .map(|remaining_grace_period| {
+                Duration::try_from(remaining_grace_period)
+                    .with_context(|| format!("invalid grace period for epoch {epoch}"))
+                    .map(|remaining_grace_period| now + remaining_grace_period)
+            })
+            .transpose()?;
+        epochs.insert(epoch, EpochKeys { group_keys: Arc::new(keys), expires_at });
+    }
+    Ok(KeyringState { keyring_id: group_keys.keyring_id, current_epoch: group_keys.epoch, epochs })
+}
+
+#[cfg(test)]
+mod tests {
+    use oak_containers_attestation::generate_instance_keys;
+
+    use super::*;
+
+    const GRACE_PERIOD: Duration = Duration::from_secs(60);
+
+    fn create_leader() -> GroupKeyring {
+        let (instance_keys, _) = generate_instance_keys();
+        GroupKeyring::new_leader(instance_keys.generate_group_keys().0)
+    }
+
+    fn public_key(keyring: &GroupKeyring, epoch: Option<u32>) -> Vec<u8> {
+        keyring.group_keys(epoch).unwrap().1.encryption_key.public_key()
+    }
+
+    #[test]
+    fn rotate_advances_epoch() {
+        let leader = create_leader();
+        assert_eq!(leader.current_epoch(), 0);
+        let initial_public_key = public_key(&leader, None);
+
+        let (epoch, public_key_1) = leader.rotate(GRACE_PERIOD).unwrap();
+
+        assert_eq!(epoch, 1);
+        assert_eq!(leader.current_epoch(), 1);
+        assert_eq!(public_key(&leader, None), public_key_1);
+        assert_eq!(public_key(&leader, Some(0)), initial_public_key);
+        assert_ne!(public_key_1, initial_public_key);
+    }
+
+    #[test]
+    fn previous_epoch_expires_after_grace_period() {
+        let leader = create_leader();
+        let now = Instant::now();
+        leader.rotate_at(GRACE_PERIOD, now).unwrap();
+
+        assert!(leader.group_keys_at(Some(0), now + GRACE_PERIOD / 2).is_ok());
+        assert!(leader.group_keys_at(Some(0), now + GRACE_PERIOD).is_err());
+        assert!(leader.group_keys_at(Some(1), now + GRACE_PERIOD).is_ok());
+        assert!(leader.group_keys_at(Some(2), now).is_err());
+    }
+
+    #[test]
+    fn rotate_drops_expired_epochs() {
+        let leader = create_leader();
+        let now = Instant::now();
+        leader.rotate_at(GRACE_PERIOD, now).unwrap();
+        leader.rotate_at(GRACE_PERIOD, now + GRACE_PERIOD).unwrap();
+
+        let (_, peer_public_keys) = generate_instance_keys();
+        let group_keys = leader
+            .encrypted_group_keys_at(&peer_public_keys.encryption_public_key, now + GRACE_PERIOD)
+            .unwrap();
+        assert_eq!(group_keys.epoch, 2);
+        let previous_epochs: Vec<u32> =
+            group_keys.previous_epochs.iter().map(|previous| previous.epoch).collect();
+        assert_eq!(previous_epochs, vec![1]);
+    }
+
+    #[test]
+    fn follower_mirrors_leader() {
+        let leader = create_leader();
+        let (follower_keys, follower_public_keys) = generate_instance_keys();
+        let follower = GroupKeyring::new_follower(
+            &follower_keys,
+            leader.encrypted_group_keys(&follower_public_keys.encryption_public_key).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(follower.current_epoch(), 0);
+        assert_eq!(public_key(&follower, None), public_key(&leader, None));
+
+        leader.rotate(GRACE_PERIOD).unwrap();
+        follower
+            .update(
+                &follower_keys,
+                leader.encrypted_group_keys(&follower_public_keys.encryption_public_key).unwrap(),
+            )
+            .unwrap();
+
+        assert_eq!(follower.current_epoch(), 1);
+        assert_eq!(public_key(&follower, Some(0)), public_key(&leader, Some(0)));
+        assert_eq!(public_key(&follower, Some(1)), public_key(&leader, Some(1)));
+        assert!(follower.rotate(GRACE_PERIOD).is_err());
+    }
+
+    #[test]
+    fn follower_drops_previous_epoch_after_remaining_grace_period() {
+        let leader = create_leader();
+        let (follower_keys, follower_public_keys) = generate_instance_keys();
+        let now = Instant::now();
+        leader.rotate_at(GRACE_PERIOD, now).unwrap();
+
+        let group_keys = leader
+            .encrypted_group_keys_at(
+                &follower_public_keys.encryption_public_key,
+                now + GRACE_PERIOD / 2,
+            )
+            .unwrap();
+        let follower = GroupKeyring::new_follower(&follower_keys, group_keys.clone()).unwrap();
+        follower.update_at(&follower_keys, group_keys, now).unwrap();
+
+        // The follower only learned about the remaining half of the grace
+        // period, which it counts from its own update.
+        assert!(follower.group_keys_at(Some(0), now + GRACE_PERIOD / 4).is_ok());
+        assert!(follower.group_keys_at(Some(0), now + GRACE_PERIOD / 2).is_err());
+        assert!(follower.group_keys_at(Some(1), now + GRACE_PERIOD).is_ok());
+    }
+
+    #[test]
+    fn follower_adopts_keyring_of_restarted_leader() {
+        let leader = create_leader();
+        let (follower_keys, follower_public_keys) = generate_instance_keys();
+        leader.rotate(GRACE_PERIOD).unwrap();
+        let follower = GroupKeyring::new_follower(
+            &follower_keys,
+            leader.encrypted_group_keys(&follower_public_keys.encryption_public_key).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(follower.current_epoch(), 1);
+
+        let restarted_leader = create_leader();
+        follower
+            .update(
+                &follower_keys,
+                restarted_leader
+                    .encrypted_group_keys(&follower_public_keys.encryption_public_key)
+                    .unwrap(),
+            )
+            .unwrap();
+
+        assert_eq!(follower.current_epoch(), ATTESTED_EPOCH);
+        assert_eq!(public_key(&follower, None), public_key(&restarted_leader, None));
+        assert!(follower.group_keys(Some(1)).is_err());
+    }
+
+    #[test]
+    fn follower_rejects_older_epoch() {
+        let leader = create_leader();
+        let (follower_keys, follower_public_keys) = generate_instance_keys();
+        let stale_group_keys =
+            leader.encrypted_group_keys(&follower_public_keys.encryption_public_key).unwrap();
+        leader.rotate(GRACE_PERIOD).unwrap();
+        let follower = GroupKeyring::new_follower(
+            &follower_keys,
+            leader.encrypted_group_keys(&follower_public_keys.encryption_public_key).unwrap(),
+        )
+        .unwrap();
+
+        assert!(follower.update(&follower_keys, stale_group_keys).is_err());
+        assert_eq!(follower.current_epoch(), 1);
+    }
+}
@@ -16,8 +16,8 @@
 use std::{fs::Permissions, os::unix::prelude::PermissionsExt, sync::Arc};
 
 use anyhow::Context;
-use oak_containers_attestation::{GroupKeys, InstanceKeys};
-use oak_crypto::encryption_key::EncryptionKeyHandle;
+use oak_containers_attestation::InstanceKeys;
+use oak_crypto::encryption_key::{EncryptionKey, EncryptionKeyHandle};
 use oak_grpc::oak::containers::{
     orchestrator_server::{Orchestrator, OrchestratorServer},
     v1::orchestrator_crypto_server::{OrchestratorCrypto, OrchestratorCryptoServer},
@@ -27,28 +27,66 @@ use oak_proto_rust::oak::{
     containers::{
         v1::{
             BindSessionRequest, BindSessionResponse, DeriveSessionKeysRequest,
-            DeriveSessionKeysResponse, KeyOrigin, SignRequest, SignResponse,
+            DeriveSessionKeysResponse, KeyOrigin, RotateGroupKeysRequest, RotateGroupKeysResponse,
+            SealRequest, SealResponse, SignRequest, SignResponse, UnsealRequest, UnsealResponse,
         },
-        GetApplicationConfigResponse,
+        GetApplicationConfigResponse, GetContainerStatusesResponse,
     },
     crypto::v1::Signature,
     session::v1::EndorsedEvidence,
 };
+use oak_time_std::clock::SystemTimeClock;
 use tokio::{fs::set_permissions, net::UnixListener};
 use tokio_stream::wrappers::UnixListenerStream;
 use tokio_util::sync::CancellationToken;
 use tonic::{transport::Server, Request, Response};
 
-use crate::launcher_client::LauncherClient;
+use crate::{
+    container_status::ContainerStatuses,
+    group_keyring::{GroupKeyring, ATTESTED_EPOCH},
+    launcher_client::LauncherClient,
+    sealing::Sealer,
+};
 
 pub struct CryptoService {
-    instance_keys: InstanceKeys,
-    group_keys: Arc<GroupKeys>,
+    instance_keys: Arc<InstanceKeys>,
+    group_keyring: Arc<GroupKeyring>,
+    sealer: Sealer,
 }
 
 impl CryptoService {
-    pub fn new(instance_keys: InstanceKeys, group_keys: Arc<GroupKeys>) -> Self {
-        Self { instance_keys, group_keys }
+    pub fn new(
+        instance_keys: Arc<InstanceKeys>,
+        group_keyring: Arc<GroupKeyring>,
+        sealer: Sealer,
+    ) -> Self {
+        Self { instance_keys, group_keyring, sealer }
+    }
+
+    /// Returns the encryption key of the given origin. For group keys, the key
+    /// of `group_key_epoch` (or the current epoch if unset) is returned along
+    /// with its epoch, which is always 0 for instance keys.
+    #[allow(clippy::result_large_err)]
+    fn encryption_key(
+        &self,
+        key_origin: KeyOrigin,
+        group_key_epoch: Option<u32>,
+    ) -> Result<(u32, EncryptionKey), tonic::Status> {
+        match key_origin {
+            KeyOrigin::Unspecified => {
+                Err(tonic::Status::invalid_argument("unspecified key origin"))
+            }
+            KeyOrigin::Instance => Ok((0, self.instance_keys.encryption_key.clone())),
+            KeyOrigin::Group => {
+                let (epoch, group_keys) =
+                    self.group_keyring.group_keys(group_key_epoch).map_err(|err| {
+                        tonic::Status::failed_precondition(format!(
+                            "couldn't get group keys: {err:#}"
+                        ))
+                    })?;
+                Ok((epoch, group_keys.encryption_key.clone()))
+            }
+        }
     }
 
     #[allow(clippy::result_large_err)]
@@ -80,14 +118,10 @@ impl OrchestratorCrypto for CryptoService {
     ) -> Result<Response<DeriveSessionKeysResponse>, tonic::Status> {
         let request = request.into_inner();
 
-        let encryption_key = match request.key_origin() {
-            KeyOrigin::Unspecified => {
-                Err(tonic::Status::invalid_argument("unspecified key origin"))?
-            }
-            KeyOrigin::Instance => &self.instance_keys.encryption_key,
-            KeyOrigin::Group => &self.group_keys.encryption_key,
-        };
-
+        // Clients encrypt to the attested group key unless they ask otherwise.
+        let group_key_epoch = request.group_key_epoch.unwrap_or(ATTESTED_EPOCH);
+        let (_, encryption_key) =
+            self.encryption_key(request.key_origin(), Some(group_key_epoch))?;
         let session_keys = encryption_key
             .generate_recipient_context(&request.serialized_encapsulated_public_key)
             .map_err(|err| tonic::Status::internal(format!("couldn't derive session keys: {err}")))?
@@ -126,6 +160,58 @@ impl OrchestratorCrypto for CryptoService {
         };
         Ok(tonic::Response::new(BindSessionResponse { signature: Some(signature) }))
     }
+
+    async fn seal(
+        &self,
+        request: Request<SealRequest>,
+    ) -> Result<Response<SealResponse>, tonic::Status> {
+        let request = request.into_inner();
+        let key_origin = request.key_origin();
+        let (group_key_epoch, encryption_key) = self.encryption_key(key_origin, None)?;
+        let sealed_blob = self
+            .sealer
+            .seal(key_origin, group_key_epoch, &encryption_key, &request.plaintext, request.policy)
+            .map_err(|err| tonic::Status::failed_precondition(format!("couldn't seal: {err:#}")))?;
+        Ok(tonic::Response::new(SealResponse { sealed_blob: Some(sealed_blob) }))
+    }
+
+    async fn unseal(
+        &self,
+        request: Request<UnsealRequest>,
+    ) -> Result<Response<UnsealResponse>, tonic::Status> {
+        let sealed_blob = request
+            .into_inner()
+            .sealed_blob
+            .ok_or_else(|| tonic::Status::invalid_argument("no sealed blob provided"))?;
+        let header = sealed_blob
+            .header
+            .as_ref()
+            .ok_or_else(|| tonic::Status::invalid_argument("sealed blob has no header"))?;
+        let (_, encryption_key) =
+            self.encryption_key(header.key_origin(), Some(header.group_key_epoch))?;
+        let plaintext = self.sealer.unseal(&encryption_key, &sealed_blob).map_err(|err| {
+            tonic::Status::failed_precondition(format!("couldn't unseal: {err:#}"))
+        })?;
+        Ok(tonic::Response::new(UnsealResponse { plaintext }))
+    }
+
+    async fn rotate_group_keys(
+        &self,
+        request: Request<RotateGroupKeysRequest>,
+    ) -> Result<Response<RotateGroupKeysResponse>, tonic::Status> {
+        let grace_period = request
+            .into_inner()
+            .grace_period
+            .map(std::time::Duration::try_from)
+            .transpose()
+            .map_err(|err| tonic::Status::invalid_argument(format!("invalid grace period: {err}")))?
+            .unwrap_or_default();
+        let (epoch, group_encryption_public_key) =
+            self.group_keyring.rotate(grace_period).map_err(|err| {
+                tonic::Status::failed_precondition(format!("couldn't rotate group keys: {err:#}"))
+            })?;
+        Ok(tonic::Response::new(RotateGroupKeysResponse { epoch, group_encryption_public_key }))
+    }
 }
 
 pub struct ServiceImplementation {
@@ -133,6 +219,7 @@ pub struct ServiceImplementation {
     launcher_client: Arc<LauncherClient>,
     evidence: Evidence,
     endorsements: Endorsements,
+    container_statuses: ContainerStatuses,
 }
 
 #[tonic::async_trait]
@@ -163,6 +250,15 @@ impl Orchestrator for ServiceImplementation {
             endorsements: Some(self.endorsements.clone()),
         }))
     }
+
+    async fn get_container_statuses(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<GetContainerStatusesResponse>, tonic::Status> {
+        Ok(tonic::Response::new(GetContainerStatusesResponse {
+            statuses: self.container_statuses.get(),
+        }))
+    }
 }
 
 pub async fn server<P>(
@@ -194,21 +290,28 @@ where
 pub fn create_services(
     evidence: Evidence,
     endorsements: Endorsements,
-    instance_keys: InstanceKeys,
-    group_keys: Arc<GroupKeys>,
+    instance_keys: Arc<InstanceKeys>,
+    group_keyring: Arc<GroupKeyring>,
     application_config: Vec<u8>,
     launcher_client: Arc<LauncherClient>,
-) -> (OrchestratorServer<ServiceImplementation>, OrchestratorCryptoServer<CryptoService>) {
+    container_statuses: ContainerStatuses,
+) -> anyhow::Result<(
+    OrchestratorServer<ServiceImplementation>,
+    OrchestratorCryptoServer<CryptoService>,
+)> {
+    let sealer = Sealer::create(&evidence, &endorsements, Arc::new(SystemTimeClock))
+        .context("couldn't create sealer")?;
     let service_instance = ServiceImplementation {
         // TODO(#4442): Remove once apps use the new crypto service.
         application_config,
         launcher_client,
         evidence,
         endorsements,
+        container_statuses,
     };
-    let crypto_service_instance = CryptoService::new(instance_keys, group_keys);
-    (
+    let crypto_service_instance = CryptoService::new(instance_keys, group_keyring, sealer);
+    Ok((
         OrchestratorServer::new(service_instance),
         OrchestratorCryptoServer::new(crypto_service_instance),
-    )
+    ))
 }
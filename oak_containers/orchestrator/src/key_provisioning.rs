@@ -13,28 +13,33 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
+use anyhow::Context;
 use oak_attestation_verification::verifier::verify_dice_chain_and_extract_evidence;
-use oak_containers_attestation::GroupKeys;
+use oak_containers_attestation::InstanceKeys;
 use oak_grpc::oak::key_provisioning::v1::key_provisioning_server::{
     KeyProvisioning, KeyProvisioningServer,
 };
-use oak_proto_rust::oak::key_provisioning::v1::{
-    GetGroupKeysRequest, GetGroupKeysResponse, GroupKeys as GroupKeysProto,
-};
+use oak_proto_rust::oak::key_provisioning::v1::{GetGroupKeysRequest, GetGroupKeysResponse};
 use tokio::net::TcpListener;
 use tokio_stream::wrappers::TcpListenerStream;
 use tokio_util::sync::CancellationToken;
 use tonic::{transport::Server, Request, Response};
 
+use crate::{group_keyring::GroupKeyring, launcher_client::LauncherClient};
+
+/// How often follower enclaves fetch the group keys from the leader enclave to
+/// pick up rotations.
+const GROUP_KEYS_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
 struct KeyProvisioningService {
-    group_keys: Arc<GroupKeys>,
+    group_keyring: Arc<GroupKeyring>,
 }
 
 impl KeyProvisioningService {
-    pub fn new(group_keys: Arc<GroupKeys>) -> Self {
-        Self { group_keys }
+    pub fn new(group_keyring: Arc<GroupKeyring>) -> Self {
+        Self { group_keyring }
     }
 }
 
@@ -61,27 +66,23 @@ impl KeyProvisioning for KeyProvisioningService {
                 tonic::Status::invalid_argument(format!("couldn't verify endorsed evidence: {err}"))
             })?;
 
-        // Encrypt group keys.
-        let encrypted_encryption_private_key = self
-            .group_keys
-            .encrypted_group_encryption_key(&attestation_results.encryption_public_key)
+        // Encrypt group keys of all epochs that are still valid.
+        let group_keys = self
+            .group_keyring
+            .encrypted_group_keys(&attestation_results.encryption_public_key)
             .map_err(|err| {
                 tonic::Status::internal(format!("couldn't encrypt encryption private key: {err}"))
             })?;
-        Ok(tonic::Response::new(GetGroupKeysResponse {
-            group_keys: Some(GroupKeysProto {
-                encrypted_encryption_private_key: Some(encrypted_encryption_private_key),
-            }),
-        }))
+        Ok(tonic::Response::new(GetGroupKeysResponse { group_keys: Some(group_keys) }))
     }
 }
 
 pub async fn create(
     address: &str,
-    group_keys: Arc<GroupKeys>,
+    group_keyring: Arc<GroupKeyring>,
     cancellation_token: CancellationToken,
 ) -> Result<(), anyhow::Error> {
-    let key_provisioning_service_instance = KeyProvisioningService::new(group_keys);
+    let key_provisioning_service_instance = KeyProvisioningService::new(group_keyring);
 
     let listener = TcpListener::bind(address).await?;
 
@@ -95,3 +96,32 @@ pub async fn create(
 
     Ok(())
 }
+
+/// Periodically fetches the group keys from the leader enclave, so that a
+/// follower enclave picks up new epochs and drops expired ones.
+pub async fn refresh_group_keys(
+    launcher_client: Arc<LauncherClient>,
+    instance_keys: Arc<InstanceKeys>,
+    group_keyring: Arc<GroupKeyring>,
+    cancellation_token: CancellationToken,
+) -> Result<(), anyhow::Error> {
+    let mut interval = tokio::time::interval(GROUP_KEYS_REFRESH_INTERVAL);
+    // The keys were provided on startup, so the immediate first tick is skipped.
+    interval.tick().await;
+    loop {
+        tokio::select! {
+            _ = cancellation_token.cancelled() => return Ok(()),
+            _ = interval.tick() => {}
+        }
+        let result = launcher_client
+            .get_group_keys()
+            .await
+            .and_then(|group_keys| group_keyring.update(&instance_keys, group_keys))
+            .context("couldn't refresh group keys");
+        // Keys of the current epoch stay usable, so a failed refresh is retried
+        // on the next tick instead of stopping the enclave.
+        if let Err(err) = result {
+            log::warn!("{err:#}");
+        }
+    }
+}
@@ -14,6 +14,7 @@
 // limitations under the License.
 
 use anyhow::Context;
+use bytes::{Buf, Bytes};
 use oak_containers_channel::{buffer::Buffer, create_channel};
 use oak_grpc::oak::containers::{
     launcher_client::LauncherClient as GrpcLauncherClient,
@@ -21,11 +22,18 @@ use oak_grpc::oak::containers::{
 };
 use oak_proto_rust::oak::{
     attestation::v1::{Endorsements, Evidence},
-    containers::{v1::KeyProvisioningRole, SendAttestationEvidenceRequest},
+    containers::{
+        v1::KeyProvisioningRole, ContainerBundle, ContainerBundleManifest, ContainerStatus,
+        GetImageResponse, GetManifestContainerBundleRequest, SendAttestationEvidenceRequest,
+    },
     key_provisioning::v1::GroupKeys,
 };
 use opentelemetry_otlp::TonicExporterBuilder;
-use tonic::transport::Channel;
+use tonic::{transport::Channel, Streaming};
+
+/// Name of the single container of launchers that only serve a legacy
+/// container bundle.
+const LEGACY_CONTAINER_NAME: &str = "oakc";
 
 /// Utility struct used to interface with the launcher
 pub struct LauncherClient {
@@ -42,23 +50,58 @@ impl LauncherClient {
         Ok(Self { channel, inner, hostlib_key_provisioning_client })
     }
 
-    pub async fn get_container_bundle(&self) -> Result<Buffer, Box<dyn std::error::Error>> {
-        let mut stream = self
+    /// Fetches the container manifest and the bundles of all its containers.
+    /// Falls back to the legacy single bundle if the launcher doesn't serve
+    /// container manifests.
+    pub async fn get_container_bundle(
+        &self,
+    ) -> Result<ContainerBundleManifest, Box<dyn std::error::Error>> {
+        let mut manifest = match self.inner.clone().get_container_bundle_manifest(()).await {
+            Ok(response) => response.into_inner(),
+            Err(status) if status.code() == tonic::Code::Unimplemented => {
+                log::info!("launcher doesn't serve container manifests, using the legacy bundle");
+                return Ok(ContainerBundleManifest {
+                    containers: vec![ContainerBundle {
+                        name: LEGACY_CONTAINER_NAME.to_string(),
+                        bundle: self.get_legacy_container_bundle().await?,
+                        ..Default::default()
+                    }],
+                });
+            }
+            Err(status) => {
+                return Err(anyhow::Error::from(status)
+                    .context("couldn't get container bundle manifest")
+                    .into())
+            }
+        };
+
+        for container in manifest.containers.iter_mut() {
+            let stream = self
+                .inner
+                .clone()
+                .get_manifest_container_bundle(GetManifestContainerBundleRequest {
+                    name: container.name.clone(),
+                })
+                .await
+                .context("couldn't form streaming connection")?
+                .into_inner();
+            container.bundle = collect_chunks(stream)
+                .await
+                .with_context(|| format!("couldn't load bundle of container {}", container.name))?;
+        }
+
+        Ok(manifest)
+    }
+
+    async fn get_legacy_container_bundle(&self) -> anyhow::Result<Bytes> {
+        let stream = self
             .inner
             .clone()
             .get_container_bundle(())
             .await
             .context("couldn't form streaming connection")?
             .into_inner();
-
-        let mut container_buf = Buffer::new();
-        while let Some(load_response) =
-            stream.message().await.context("couldn't load message from stream")?
-        {
-            container_buf.push_back(load_response.image_chunk);
-        }
-
-        Ok(container_buf)
+        collect_chunks(stream).await
     }
 
     pub async fn get_application_config(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
@@ -105,6 +148,15 @@ impl LauncherClient {
         Ok(())
     }
 
+    pub async fn notify_container_status(&self, status: ContainerStatus) -> anyhow::Result<()> {
+        self.inner
+            .clone()
+            .notify_container_status(tonic::Request::new(status))
+            .await
+            .context("couldn't send container status")?;
+        Ok(())
+    }
+
     pub async fn get_key_provisioning_role(&self) -> anyhow::Result<KeyProvisioningRole> {
         let key_provisioning_role = self
             .hostlib_key_provisioning_client
@@ -133,3 +185,14 @@ impl LauncherClient {
         opentelemetry_otlp::new_exporter().tonic().with_channel(self.channel.clone())
     }
 }
+
+async fn collect_chunks(mut stream: Streaming<GetImageResponse>) -> anyhow::Result<Bytes> {
+    let mut buffer = Buffer::new();
+    while let Some(response) =
+        stream.message().await.context("couldn't load message from stream")?
+    {
+        buffer.push_back(response.image_chunk);
+    }
+    let len = buffer.remaining();
+    Ok(buffer.copy_to_bytes(len))
+}
@@ -27,13 +27,19 @@ use oak_proto_rust::oak::containers::v1::KeyProvisioningRole;
 use prost::Message;
 use tokio_util::sync::CancellationToken;
 
+use crate::group_keyring::GroupKeyring;
+
 mod cdi;
 pub mod container_runtime;
+pub mod container_status;
 pub mod dice;
+pub mod group_keyring;
 pub mod ipc_server;
 pub mod key_provisioning;
 pub mod launcher_client;
 pub mod logging;
+mod oci_image;
+pub mod sealing;
 
 #[derive(Parser, Debug)]
 struct Args {
@@ -74,7 +80,9 @@ pub async fn main<A: Attester + ApplicationKeysAttester + Serializable + 'static
         excluded_metrics: None,
     };
 
-    let _oak_observer = oak_containers_agent::metrics::init_metrics(metrics_config);
+    let mut oak_observer = oak_containers_agent::metrics::init_metrics(metrics_config);
+    let container_status_reporter =
+        crate::container_status::ContainerStatusReporter::new(&mut oak_observer);
 
     // Get key provisioning role.
     let key_provisioning_role = launcher_client
@@ -85,47 +93,58 @@ pub async fn main<A: Attester + ApplicationKeysAttester + Serializable + 'static
     // Generate application keys.
     let (instance_keys, instance_public_keys) = generate_instance_keys();
     #[cfg(feature = "application_keys")]
-    let (mut group_keys, group_public_keys) =
+    let (mut group_keyring, group_public_keys) =
         if key_provisioning_role == KeyProvisioningRole::Leader {
             let (group_keys, group_public_keys) = instance_keys.generate_group_keys();
-            (Some(Arc::new(group_keys)), Some(group_public_keys))
+            (Some(Arc::new(GroupKeyring::new_leader(group_keys))), Some(group_public_keys))
         } else {
             (None, None)
         };
     #[cfg(not(feature = "application_keys"))]
-    let (mut group_keys, _group_public_keys) =
+    let (mut group_keyring, _group_public_keys) =
         if key_provisioning_role == KeyProvisioningRole::Leader {
             let (group_keys, group_public_keys) = instance_keys.generate_group_keys();
-            (Some(Arc::new(group_keys)), Some(group_public_keys))
+            (Some(Arc::new(GroupKeyring::new_leader(group_keys))), Some(group_public_keys))
         } else {
             (None, None)
         };
 
     // Load application.
-    let container_bundle = launcher_client
+    let container_manifest = launcher_client
         .get_container_bundle()
         .await
         .map_err(|error| anyhow!("couldn't get container bundle: {:?}", error))?;
+    crate::container_runtime::validate_manifest(&container_manifest)?;
     let application_config = launcher_client
         .get_application_config()
         .await
         .map_err(|error| anyhow!("couldn't get application config: {:?}", error))?;
 
-    // Create a container event and add it to the event log.
+    // Create an event for every container and add them to the event log, in the
+    // order in which the containers are started.
     let mut attester: A = crate::dice::load_stage1_dice_data()?;
-    let container_event = oak_containers_attestation::create_container_event(
-        container_bundle.clone(),
-        &application_config[..],
-        &instance_public_keys,
-    );
-    let encoded_event = container_event.encode_to_vec();
+    let container_events = container_manifest
+        .containers
+        .iter()
+        .map(|container| {
+            Ok(oak_containers_attestation::create_container_event(
+                crate::container_runtime::measured_bundle(container)?,
+                &application_config[..],
+                &instance_public_keys,
+            ))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let encoded_events: Vec<_> =
+        container_events.iter().map(|event| event.encode_to_vec()).collect();
     // Spawn the `extend`` operation on a separate thread to support cases where we
     // have async attesters.
     let attester = tokio::runtime::Handle::current()
         .spawn_blocking(move || {
-            attester
-                .extend(&encoded_event)
-                .context("couldn't add container event to the evidence")?;
+            for encoded_event in encoded_events {
+                attester
+                    .extend(&encoded_event)
+                    .context("couldn't add container event to the evidence")?;
+            }
             Ok::<A, anyhow::Error>(attester)
         })
         .await??;
@@ -138,8 +157,11 @@ pub async fn main<A: Attester + ApplicationKeysAttester + Serializable + 'static
             // have async attesters.
             tokio::runtime::Handle::current()
                 .spawn_blocking(move || {
+                    // The application keys are bound to the last container. The
+                    // manifest is validated to contain at least one container.
+                    let container_event = container_events.last().expect("no container events");
                     let container_layer =
-                        oak_containers_attestation::create_container_dice_layer(&container_event);
+                        oak_containers_attestation::create_container_dice_layer(container_event);
                     attester.add_application_keys(
                         container_layer,
                         &instance_public_keys.encryption_public_key,
@@ -173,11 +195,13 @@ pub async fn main<A: Attester + ApplicationKeysAttester + Serializable + 'static
             .get_group_keys()
             .await
             .map_err(|error| anyhow!("couldn't get group keys: {:?}", error))?;
-        let provisioned_group_keys = instance_keys
-            .provide_group_keys(get_group_keys_response)
-            .context("couldn't provide group keys")?;
-        group_keys = Some(Arc::new(provisioned_group_keys));
+        let provisioned_group_keyring =
+            GroupKeyring::new_follower(&instance_keys, get_group_keys_response)
+                .context("couldn't provide group keys")?;
+        group_keyring = Some(Arc::new(provisioned_group_keyring));
     }
+    let group_keyring = group_keyring.context("group keys were not provisioned")?;
+    let instance_keys = Arc::new(instance_keys);
 
     if let Some(path) = args.ipc_socket_path.parent() {
         tokio::fs::create_dir_all(path).await?;
@@ -191,17 +215,40 @@ pub async fn main<A: Attester + ApplicationKeysAttester + Serializable + 'static
     let (orchestrator_server, crypto_server) = crate::ipc_server::create_services(
         evidence,
         endorsements,
-        instance_keys,
-        group_keys.clone().context("group keys were not provisioned")?,
+        instance_keys.clone(),
+        group_keyring.clone(),
         application_config,
-        launcher_client,
-    );
+        launcher_client.clone(),
+        container_status_reporter.statuses(),
+    )?;
 
     // Start application and gRPC servers.
     let user = nix::unistd::User::from_name(&args.runtime_user)
         .context(format!("error resolving user {}", args.runtime_user))?
         .context(format!("user `{}` not found", args.runtime_user))?;
     let cancellation_token = CancellationToken::new();
+    let (container_status_sender, container_status_receiver) =
+        tokio::sync::mpsc::unbounded_channel();
+    let report_container_status =
+        container_status_reporter.run(container_status_receiver, launcher_client.clone());
+    // Only followers receive group keys, the leader rotates them itself.
+    let refresh_group_keys = {
+        let group_keyring = group_keyring.clone();
+        let cancellation_token = cancellation_token.clone();
+        async move {
+            if key_provisioning_role == KeyProvisioningRole::Follower {
+                crate::key_provisioning::refresh_group_keys(
+                    launcher_client,
+                    instance_keys,
+                    group_keyring,
+                    cancellation_token,
+                )
+                .await
+            } else {
+                Ok(())
+            }
+        }
+    };
     tokio::try_join!(
         crate::ipc_server::server(
             &args.ipc_socket_path,
@@ -211,15 +258,18 @@ pub async fn main<A: Attester + ApplicationKeysAttester + Serializable + 'static
         ),
         crate::key_provisioning::create(
             &args.orchestrator_addr,
-            group_keys.context("group keys were not provisioned")?,
+            group_keyring,
             cancellation_token.clone(),
         ),
+        refresh_group_keys,
+        report_container_status,
         crate::container_runtime::run(
-            container_bundle,
+            container_manifest,
             &args.container_dir,
             user.uid,
             user.gid,
             &args.ipc_socket_path,
+            container_status_sender,
             cancellation_token,
         ),
     )?;
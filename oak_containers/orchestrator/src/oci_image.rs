@@ -0,0 +1,554 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Containers distributed as OCI image layouts.
+//!
+//! The layout is unpacked into an OCI runtime bundle by applying the layers of
+//! the image manifest to the root filesystem and generating the runtime spec
+//! from the image config. Every blob is checked against the digest it's
+//! referenced by, so the contents of the container are determined by the
+//! digest of the image manifest, which is the digest that gets measured.
+
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsStr,
+    fs,
+    io::Read,
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::Context;
+use bytes::{Buf, Bytes};
+use flate2::read::GzDecoder;
+use oci_spec::{
+    image::{Arch, Descriptor, ImageConfiguration, ImageIndex, ImageManifest, MediaType, Os},
+    runtime::{Root, Spec},
+};
+use sha2::{Digest, Sha256};
+
+/// Prefix of files that remove a file of a lower layer.
+const WHITEOUT_PREFIX: &str = ".wh.";
+
+/// File that removes all children of its directory from lower layers.
+const OPAQUE_WHITEOUT: &str = ".wh..wh..opq";
+
+/// Directory of the root filesystem within the runtime bundle.
+const ROOTFS: &str = "rootfs";
+
+pub struct OciImageLayout {
+    // Blobs by digest, e.g. `sha256:<hex>`, sliced from the layout tarball.
+    blobs: HashMap<String, Bytes>,
+    manifest_digest: String,
+}
+
+impl OciImageLayout {
+    /// Indexes the blobs of a tarball containing an OCI image layout and
+    /// selects its image manifest.
+    pub fn parse(layout: &Bytes) -> anyhow::Result<Self> {
+        let mut blobs = HashMap::new();
+        let mut index = None;
+        let mut archive = tar::Archive::new(layout.clone().reader());
+        for entry in archive.entries().context("couldn't read image layout")? {
+            let entry = entry.context("couldn't read image layout entry")?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let start = usize::try_from(entry.raw_file_position())?;
+            let end = start + usize::try_from(entry.size())?;
+            anyhow::ensure!(end <= layout.len(), "truncated image layout");
+            let contents = layout.slice(start..end);
+            let path = entry.path()?;
+            let components: Vec<&str> = path
+                .components()
+                .filter(|component| *component != Component::CurDir)
+                .map(|component| component.as_os_str().to_str().context("invalid path"))
+                .collect::<anyhow::Result<_>>()?;
+            match components[..] {
+                ["index.json"] => index = Some(contents),
+                ["blobs", algorithm, encoded] => {
+                    blobs.insert(format!("{algorithm}:{encoded}"), contents);
+                }
+                _ => {}
+            }
+        }
+
+        let index = ImageIndex::from_reader(index.context("image layout has no index")?.reader())
+            .context("couldn't parse image index")?;
+        let manifest_digest: &str = select_manifest(&index)?.digest().as_ref();
+        let layout = Self { blobs, manifest_digest: manifest_digest.to_string() };
+        layout.blob(&layout.manifest_digest)?;
+        Ok(layout)
+    }
+
+    /// Returns the serialized image manifest, the digest of which identifies
+    /// the image.
+    pub fn manifest(&self) -> Bytes {
+        self.blobs[&self.manifest_digest].clone()
+    }
+
+    /// Creates an OCI runtime bundle from the image in `bundle_dir`.
+    pub fn unpack(&self, bundle_dir: &Path) -> anyhow::Result<()> {
+        let manifest = ImageManifest::from_reader(self.blob(&self.manifest_digest)?.reader())
+            .context("couldn't parse image manifest")?;
+        let config = ImageConfiguration::from_reader(
+            self.blob(manifest.config().digest().as_ref())?.reader(),
+        )
+        .context("couldn't parse image config")?;
+
+        let rootfs = bundle_dir.join(ROOTFS);
+        fs::create_dir_all(&rootfs)?;
+        for layer in manifest.layers() {
+            let digest: &str = layer.digest().as_ref();
+            unpack_layer(layer, self.blob(digest)?, &rootfs)
+                .with_context(|| format!("couldn't unpack layer {digest}"))?;
+        }
+
+        runtime_spec(&config)?
+            .save(bundle_dir.join("config.json"))
+            .context("error writing OCI spec")?;
+        Ok(())
+    }
+
+    fn blob(&self, digest: &str) -> anyhow::Result<Bytes> {
+        let blob = self.blobs.get(digest).with_context(|| format!("missing blob {digest}"))?;
+        let expected = digest
+            .strip_prefix("sha256:")
+            .with_context(|| format!("unsupported digest algorithm of blob {digest}"))?;
+        anyhow::ensure!(
+            format!("{:x}", Sha256::digest(blob)) == expected,
+            "digest mismatch of blob {digest}"
+        );
+        Ok(blob.clone())
+    }
+}
+
+/// Returns the only image manifest of the index, or the one for linux/amd64 if
+/// the index lists manifests for multiple platforms.
+fn select_manifest(index: &ImageIndex) -> anyhow::Result<&Descriptor> {
+    let manifests: Vec<&Descriptor> = match index.manifests().as_slice() {
+        [manifest] => vec![manifest],
+        manifests => manifests
+            .iter()
+            .filter(|manifest| {
+                manifest.platform().as_ref().is_some_and(|platform| {
+                    *platform.os() == Os::Linux && *platform.architecture() == Arch::Amd64
+                })
+            })
+            .collect(),
+    };
+    let [manifest] = manifests[..] else {
+        anyhow::bail!("image index must contain a single image manifest for linux/amd64");
+    };
+    anyhow::ensure!(
+        *manifest.media_type() == MediaType::ImageManifest,
+        "unsupported image manifest media type {}",
+        manifest.media_type()
+    );
+    Ok(manifest)
+}
+
+fn unpack_layer(layer: &Descriptor, blob: Bytes, rootfs: &Path) -> anyhow::Result<()> {
+    let media_type = layer.media_type().to_string();
+    // Covers the OCI and Docker layer media types, e.g.
+    // `application/vnd.oci.image.layer.v1.tar+gzip` and
+    // `application/vnd.docker.image.rootfs.diff.tar.gzip`.
+    let reader: Box<dyn Read> =
+        if media_type.ends_with("tar+gzip") || media_type.ends_with("tar.gzip") {
+            Box::new(GzDecoder::new(blob.reader()))
+        } else if media_type.ends_with(".tar") {
+            Box::new(blob.reader())
+        } else {
+            anyhow::bail!("unsupported layer media type {media_type}");
+        };
+
+    let mut archive = tar::Archive::new(reader);
+    archive.set_preserve_permissions(true);
+    let rootfs_dir = rootfs.canonicalize()?;
+    // Entries of this layer and their parent directories. Whiteouts only hide
+    // the contents of lower layers, regardless of the order of the entries.
+    let mut unpacked = HashSet::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        let file_name = path.file_name().and_then(OsStr::to_str);
+
+        if file_name == Some(OPAQUE_WHITEOUT) {
+            if let Some(dir) = resolve(rootfs, path.parent().unwrap_or(Path::new("")))? {
+                remove_lower(&dir, &unpacked)?;
+            }
+            continue;
+        }
+        if let Some(whiteout) = file_name.and_then(|name| name.strip_prefix(WHITEOUT_PREFIX)) {
+            if let Some(target) = resolve(rootfs, &path.with_file_name(whiteout))? {
+                if unpacked.contains(&target) {
+                    remove_lower(&target, &unpacked)?;
+                } else {
+                    remove(&target)?;
+                }
+            }
+            continue;
+        }
+
+        // Entries of upper layers replace those of lower layers, except for
+        // directories, which are merged.
+        if let Some(target) = resolve(rootfs, &path)?.filter(|target| target != rootfs) {
+            let is_dir = entry.header().entry_type().is_dir();
+            if !is_dir || target.symlink_metadata().is_ok_and(|metadata| !metadata.is_dir()) {
+                remove(&target)?;
+            }
+        }
+        entry.unpack_in(rootfs).with_context(|| format!("couldn't unpack {path:?}"))?;
+        if let Some(target) = resolve(rootfs, &path)?.filter(|target| target != rootfs) {
+            unpacked.extend(
+                target
+                    .ancestors()
+                    .take_while(|ancestor| *ancestor != rootfs_dir)
+                    .map(Path::to_path_buf),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Removes the contents of `dir` that weren't unpacked from the current layer.
+fn remove_lower(dir: &Path, unpacked: &HashSet<PathBuf>) -> anyhow::Result<()> {
+    if !dir.symlink_metadata().is_ok_and(|metadata| metadata.is_dir()) {
+        return Ok(());
+    }
+    for child in fs::read_dir(dir)? {
+        let child = child?.path();
+        if unpacked.contains(&child) {
+            remove_lower(&child, unpacked)?;
+        } else {
+            remove(&child)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the location of `path` in the root filesystem, if its parent
+/// directory exists. Fails if `path` would escape the root filesystem, e.g.
+/// through a symbolic link of a lower layer.
+fn resolve(rootfs: &Path, path: &Path) -> anyhow::Result<Option<PathBuf>> {
+    let mut relative = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => relative.push(name),
+            Component::CurDir => {}
+            _ => anyhow::bail!("invalid path {path:?}"),
+        }
+    }
+    let Some(file_name) = relative.file_name().map(OsStr::to_owned) else {
+        return Ok(Some(rootfs.to_path_buf()));
+    };
+    let parent = rootfs.join(relative.parent().unwrap_or(Path::new("")));
+    let Ok(parent) = parent.canonicalize() else {
+        return Ok(None);
+    };
+    anyhow::ensure!(parent.starts_with(rootfs.canonicalize()?), "path {path:?} escapes rootfs");
+    Ok(Some(parent.join(file_name)))
+}
+
+fn remove(path: &Path) -> anyhow::Result<()> {
+    match path.symlink_metadata() {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(path)?,
+        Ok(_) => fs::remove_file(path)?,
+        Err(_) => {}
+    }
+    Ok(())
+}
+
+/// Generates a rootless runtime spec that runs the image's entrypoint.
+fn runtime_spec(config: &ImageConfiguration) -> anyhow::Result<Spec> {
+    let image_config = config.config().clone().unwrap_or_default();
+    let mut spec = Spec::rootless(0, 0);
+
+    let mut process = spec.process().clone().context("default spec has no process")?;
+    let args: Vec<String> = image_config
+        .entrypoint()
+        .iter()
+        .chain(image_config.cmd().iter())
+        .flatten()
+        .cloned()
+        .collect();
+    anyhow::ensure!(!args.is_empty(), "image config has neither entrypoint nor cmd");
+    process.set_args(Some(args));
+    let mut env = process.env().clone().unwrap_or_default();
+    for variable in image_config.env().iter().flatten() {
+        let name = variable.split('=').next().unwrap_or_default();
+        env.retain(|existing| existing.split('=').next() != Some(name));
+        env.push(variable.clone());
+    }
+    process.set_env(Some(env));
+    if let Some(working_dir) = image_config.working_dir().as_ref().filter(|dir| !dir.is_empty()) {
+        process.set_cwd(working_dir.into());
+    }
+    // Rootless containers can only map the runtime user to root in the
+    // container, so images that run as another user are rejected rather than
+    // silently run with different privileges.
+    if let Some(user) = image_config.user().as_ref() {
+        let (user_name, group) = user.split_once(':').unwrap_or((user, ""));
+        anyhow::ensure!(
+            matches!(user_name, "" | "0" | "root") && matches!(group, "" | "0" | "root"),
+            "unsupported user {user:?} in image config, containers run as root"
+        );
+    }
+    spec.set_process(Some(process));
+
+    let mut root = Root::default();
+    root.set_path(PathBuf::from(ROOTFS));
+    root.set_readonly(Some(false));
+    spec.set_root(Some(root));
+    Ok(spec)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    enum Entry<'a> {
+        Dir(&'a str),
+        File(&'a str, &'a [u8]),
+        Symlink(&'a str, &'a str),
+    }
+
+    fn tar(entries: &[Entry]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for entry in entries {
+            let mut header = tar::Header::new_gnu();
+            match entry {
+                Entry::Dir(path) => {
+                    header.set_entry_type(tar::EntryType::Directory);
+                    header.set_mode(0o755);
+                    header.set_size(0);
+                    builder.append_data(&mut header, path, std::io::empty()).unwrap();
+                }
+                Entry::File(path, contents) => {
+                    header.set_entry_type(tar::EntryType::Regular);
+                    header.set_mode(0o644);
+                    header.set_size(contents.len() as u64);
+                    builder.append_data(&mut header, path, *contents).unwrap();
+                }
+                Entry::Symlink(path, target) => {
+                    header.set_entry_type(tar::EntryType::Symlink);
+                    header.set_mode(0o777);
+                    header.set_size(0);
+                    builder.append_link(&mut header, path, target).unwrap();
+                }
+            }
+        }
+        builder.into_inner().unwrap()
+    }
+
+    fn digest(blob: &[u8]) -> String {
+        format!("sha256:{:x}", Sha256::digest(blob))
+    }
+
+    fn descriptor(media_type: &str, blob: &[u8]) -> Value {
+        json!({ "mediaType": media_type, "digest": digest(blob), "size": blob.len() })
+    }
+
+    struct TestImage {
+        layout: Bytes,
+        manifest_digest: String,
+    }
+
+    /// Creates the tarball of an image layout. Layers are stored with their own
+    /// contents, or with the given replacement contents.
+    fn image_layout(layers: &[(Vec<u8>, Option<Vec<u8>>)]) -> TestImage {
+        let config = serde_json::to_vec(&json!({
+            "architecture": "amd64",
+            "os": "linux",
+            "config": {
+                "Entrypoint": ["/bin/app"],
+                "Cmd": ["--flag"],
+                "Env": ["PATH=/bin", "FOO=bar"],
+                "WorkingDir": "/app",
+            },
+            "rootfs": { "type": "layers", "diff_ids": [] },
+        }))
+        .unwrap();
+        let manifest = serde_json::to_vec(&json!({
+            "schemaVersion": 2,
+            "mediaType": "application/vnd.oci.image.manifest.v1+json",
+            "config": descriptor("application/vnd.oci.image.config.v1+json", &config),
+            "layers": layers
+                .iter()
+                .map(|(layer, _)| descriptor("application/vnd.oci.image.layer.v1.tar", layer))
+                .collect::<Vec<_>>(),
+        }))
+        .unwrap();
+        let index = serde_json::to_vec(&json!({
+            "schemaVersion": 2,
+            "manifests": [descriptor("application/vnd.oci.image.manifest.v1+json", &manifest)],
+        }))
+        .unwrap();
+
+        let mut blobs = vec![(digest(&config), config), (digest(&manifest), manifest.clone())];
+        for (layer, replacement) in layers {
+            blobs.push((digest(layer), replacement.clone().unwrap_or_else(|| layer.clone())));
+        }
+        let blob_paths: Vec<String> = blobs
+            .iter()
+            .map(|(digest, _)| format!("blobs/sha256/{}", digest.strip_prefix("sha256:").unwrap()))
+            .collect();
+        let mut entries = vec![
+            Entry::File("oci-layout", br#"{"imageLayoutVersion":"1.0.0"}"#),
+            Entry::File("index.json", &index),
+            Entry::Dir("blobs"),
+            Entry::Dir("blobs/sha256"),
+        ];
+        for (path, (_, blob)) in blob_paths.iter().zip(&blobs) {
+            entries.push(Entry::File(path, blob));
+        }
+
+        TestImage { layout: tar(&entries).into(), manifest_digest: digest(&manifest) }
+    }
+
+    #[test]
+    fn manifest_is_measured() {
+        let image = image_layout(&[(tar(&[Entry::File("file", b"contents")]), None)]);
+
+        let layout = OciImageLayout::parse(&image.layout).unwrap();
+
+        assert_eq!(digest(&layout.manifest()), image.manifest_digest);
+    }
+
+    #[test]
+    fn unpack_applies_layers() {
+        let lower = tar(&[
+            Entry::Dir("etc"),
+            Entry::File("etc/removed", b"removed"),
+            Entry::File("etc/replaced", b"lower"),
+            Entry::Dir("opt"),
+            Entry::File("opt/hidden", b"hidden"),
+        ]);
+        let upper = tar(&[
+            Entry::File("etc/.wh.removed", b""),
+            Entry::File("etc/replaced", b"upper"),
+            Entry::File("opt/.wh..wh..opq", b""),
+            Entry::File("opt/added", b"added"),
+        ]);
+        let image = image_layout(&[(lower, None), (upper, None)]);
+        let bundle_dir = tempfile::tempdir().unwrap();
+
+        OciImageLayout::parse(&image.layout).unwrap().unpack(bundle_dir.path()).unwrap();
+
+        let rootfs = bundle_dir.path().join(ROOTFS);
+        assert!(!rootfs.join("etc/removed").exists());
+        assert_eq!(fs::read(rootfs.join("etc/replaced")).unwrap(), b"upper");
+        assert!(!rootfs.join("opt/hidden").exists());
+        assert_eq!(fs::read(rootfs.join("opt/added")).unwrap(), b"added");
+        assert!(!rootfs.join("opt/.wh..wh..opq").exists());
+
+        let spec: Value =
+            serde_json::from_slice(&fs::read(bundle_dir.path().join("config.json")).unwrap())
+                .unwrap();
+        assert_eq!(spec["root"]["path"], ROOTFS);
+        assert_eq!(spec["process"]["args"], json!(["/bin/app", "--flag"]));
+        assert_eq!(spec["process"]["cwd"], "/app");
+        let env = spec["process"]["env"].as_array().unwrap();
+        assert!(env.contains(&json!("PATH=/bin")));
+        assert!(env.contains(&json!("FOO=bar")));
+        assert_eq!(
+            env.iter().filter(|variable| variable.as_str().unwrap().starts_with("PATH=")).count(),
+            1
+        );
+    }
+
+    #[test]
+    fn whiteouts_only_hide_lower_layers() {
+        let lower = tar(&[
+            Entry::Dir("etc"),
+            Entry::File("etc/config", b"lower"),
+            Entry::Dir("opt"),
+            Entry::File("opt/hidden", b"hidden"),
+            Entry::Dir("opt/lib"),
+            Entry::File("opt/lib/hidden", b"hidden"),
+        ]);
+        let upper = tar(&[
+            Entry::File("etc/config", b"upper"),
+            Entry::File("etc/.wh.config", b""),
+            Entry::File("opt/added", b"added"),
+            Entry::File("opt/lib/added", b"added"),
+            Entry::File("opt/.wh..wh..opq", b""),
+        ]);
+        let image = image_layout(&[(lower, None), (upper, None)]);
+        let bundle_dir = tempfile::tempdir().unwrap();
+
+        OciImageLayout::parse(&image.layout).unwrap().unpack(bundle_dir.path()).unwrap();
+
+        let rootfs = bundle_dir.path().join(ROOTFS);
+        assert_eq!(fs::read(rootfs.join("etc/config")).unwrap(), b"upper");
+        assert_eq!(fs::read(rootfs.join("opt/added")).unwrap(), b"added");
+        assert_eq!(fs::read(rootfs.join("opt/lib/added")).unwrap(), b"added");
+        assert!(!rootfs.join("opt/hidden").exists());
+        assert!(!rootfs.join("opt/lib/hidden").exists());
+    }
+
+    #[test]
+    fn runtime_spec_rejects_non_root_user() {
+        let config = |user: &str| {
+            ImageConfiguration::from_reader(
+                serde_json::to_vec(&json!({
+                    "architecture": "amd64",
+                    "os": "linux",
+                    "config": { "Entrypoint": ["/bin/app"], "User": user },
+                    "rootfs": { "type": "layers", "diff_ids": [] },
+                }))
+                .unwrap()
+                .as_slice(),
+            )
+            .unwrap()
+        };
+
+        for user in ["", "0", "root", "0:0", "root:root"] {
+            assert!(runtime_spec(&config(user)).is_ok(), "user {user:?}");
+        }
+        for user in ["1000", "nobody", "0:1000", "app:app"] {
+            assert!(runtime_spec(&config(user)).is_err(), "user {user:?}");
+        }
+    }
+
+    #[test]
+    fn unpack_rejects_tampered_layer() {
+        let layer = tar(&[Entry::File("file", b"contents")]);
+        let tampered_layer = tar(&[Entry::File("file", b"tampered")]);
+        let image = image_layout(&[(layer, Some(tampered_layer))]);
+        let bundle_dir = tempfile::tempdir().unwrap();
+
+        let layout = OciImageLayout::parse(&image.layout).unwrap();
+
+        assert!(layout.unpack(bundle_dir.path()).is_err());
+    }
+
+    #[test]
+    fn unpack_rejects_whiteout_outside_rootfs() {
+        let outside_dir = tempfile::tempdir().unwrap();
+        let outside_file = outside_dir.path().join("file");
+        fs::write(&outside_file, b"contents").unwrap();
+        let lower = tar(&[Entry::Symlink("escape", outside_dir.path().to_str().unwrap())]);
+        let upper = tar(&[Entry::File("escape/.wh.file", b"")]);
+        let image = image_layout(&[(lower, None), (upper, None)]);
+        let bundle_dir = tempfile::tempdir().unwrap();
+
+        let result = OciImageLayout::parse(&image.layout).unwrap().unpack(bundle_dir.path());
+
+        assert!(result.is_err());
+        assert!(outside_file.exists());
+    }
+}
@@ -0,0 +1,345 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Sealing of application data, such that it can be stored outside of the
+//! enclave and only be unsealed by containers which satisfy a policy.
+//!
+//! Data is encrypted to the instance or group encryption key, with the header
+//! of the sealed blob as associated data. The header holds the policy, which
+//! is checked against the container layer of the evidence and its
+//! endorsement, both when sealing and when unsealing.
+
+use std::sync::Arc;
+
+use anyhow::Context;
+use oak_attestation_verification::ContainerPolicy;
+use oak_attestation_verification_types::policy::Policy;
+use oak_crypto::{
+    encryption_key::EncryptionKey,
+    encryptor::{ClientEncryptor, ServerEncryptor},
+};
+use oak_proto_rust::oak::{
+    attestation::v1::{
+        binary_reference_value, ContainerLayerReferenceValues, Endorsements, Evidence,
+    },
+    containers::v1::{KeyOrigin, SealedBlob, SealedBlobHeader},
+    Variant,
+};
+use oak_time::Clock;
+use prost::Message;
+
+pub struct Sealer {
+    // Encoded container event of the evidence.
+    container_event: Vec<u8>,
+    // Endorsement of the container event, empty if there is none.
+    container_endorsement: Variant,
+    clock: Arc<dyn Clock>,
+}
+
+impl Sealer {
+    /// Creates a sealer which checks policies against the last event of the
+    /// evidence, which is expected to be the container event.
+    pub fn create(
+        evidence: &Evidence,
+        endorsements: &Endorsements,
+        clock: Arc<dyn Clock>,
+    ) -> anyhow::Result<Self> {
+        let events =
+            &evidence.event_log.as_ref().context("no event log in evidence")?.encoded_events;
+        let container_event = events.last().context("no container event in evidence")?.clone();
+        let container_endorsement =
+            endorsements.events.get(events.len() - 1).cloned().unwrap_or_default();
+        Ok(Self { container_event, container_endorsement, clock })
+    }
+
+    /// Encrypts `plaintext` to `encryption_key`, which is the key of the given
+    /// origin and, for group keys, of `group_key_epoch`. If no policy is
+    /// provided, the data can only be unsealed by containers with the same
+    /// digests as the current one.
+    pub fn seal(
+        &self,
+        key_origin: KeyOrigin,
+        group_key_epoch: u32,
+        encryption_key: &EncryptionKey,
+        plaintext: &[u8],
+        policy: Option<ContainerLayerReferenceValues>,
+    ) -> anyhow::Result<SealedBlob> {
+        anyhow::ensure!(key_origin != KeyOrigin::Unspecified, "unspecified key origin");
+        let policy = match policy {
+            Some(policy) => policy,
+            None => ContainerPolicy::evidence_to_reference_values(&self.container_event)?,
+        };
+        anyhow::ensure!(
+            matches!(
+                policy.binary.as_ref().and_then(|binary| binary.r#type.as_ref()),
+                Some(
+                    binary_reference_value::Type::Digests(_)
+                        | binary_reference_value::Type::Endorsement(_)
+                )
+            ),
+            "sealing policy must check the container binary by digest or endorsement"
+        );
+        // Data which the sealing container can't unseal would be lost if the
+        // policy is wrong, so it is rejected right away.
+        self.check_policy(&policy)?;
+
+        let header = SealedBlobHeader {
+            key_origin: key_origin.into(),
+            policy: Some(policy),
+            group_key_epoch,
+        };
+        let encrypted_data = ClientEncryptor::create(&encryption_key.public_key())
+            .context("creating client encryptor")?
+            .encrypt(plaintext, &header.encode_to_vec())
+            .context("encrypting data")?;
+        Ok(SealedBlob { header: Some(header), encrypted_data: Some(encrypted_data) })
+    }
+
+    /// Decrypts a sealed blob with `encryption_key`, which is the key of the
+    /// origin and group key epoch in the header of the blob.
+    pub fn unseal(
+        &self,
+        encryption_key: &EncryptionKey,
+        sealed_blob: &SealedBlob,
+    ) -> anyhow::Result<Vec<u8>> {
+        let header = sealed_blob.header.as_ref().context("sealed blob has no header")?;
+        let policy = header.policy.as_ref().context("sealed blob has no policy")?;
+        self.check_policy(policy)?;
+
+        let encrypted_data =
+            sealed_blob.encrypted_data.as_ref().context("sealed blob has no encrypted data")?;
+        let (_, plaintext, associated_data) =
+            ServerEncryptor::decrypt(encrypted_data, encryption_key)
+                .context("decrypting sealed data")?;
+        // The header was only checked before, now it is also authenticated.
+        anyhow::ensure!(
+            associated_data == header.encode_to_vec(),
+            "sealed blob header doesn't match the sealed data"
+        );
+        Ok(plaintext)
+    }
+
+    fn check_policy(&self, policy: &ContainerLayerReferenceValues) -> anyhow::Result<()> {
+        ContainerPolicy::new(policy)
+            .verify(self.clock.get_time(), &self.container_event, &self.container_endorsement)
+            .context("container doesn't satisfy the sealing policy")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use digest_util::hex_digest_from_contents;
+    use intoto::statement::{make_statement, serialize_statement};
+    use oak_attestation::EventLogAttester;
+    use oak_attestation_types::attester::Attester;
+    use oak_containers_attestation::{create_container_event, generate_instance_keys};
+    use oak_crypto::encryption_key::generate_encryption_key_pair;
+    use oak_proto_rust::oak::attestation::v1::{
+        BinaryReferenceValue, ContainerEndorsement, SkipVerification,
+    };
+    use oak_time::{clock::FixedClock, Duration, Instant};
+    use p256::{
+        ecdsa::{signature::Signer, Signature},
+        pkcs8::EncodePublicKey,
+    };
+    use verify_endorsement::{
+        create_endorsement_reference_value, create_signed_endorsement,
+        create_verifying_key_from_raw,
+    };
+
+    use super::*;
+
+    const BUNDLE: &[u8] = b"container bundle";
+    const CONFIG: &[u8] = b"application config";
+    const PLAINTEXT: &[u8] = b"secret";
+
+    fn now() -> Instant {
+        Instant::from_unix_seconds(1_750_000_000)
+    }
+
+    // Builds the evidence of an enclave running the given container with the
+    // fake attester, which only records the event log.
+    fn create_evidence(bundle: &[u8], config: &[u8]) -> Evidence {
+        let (_, instance_public_keys) = generate_instance_keys();
+        let event = create_container_event(bundle, config, &instance_public_keys);
+        let mut attester = EventLogAttester::new();
+        attester.extend(&event.encode_to_vec()).expect("couldn't extend evidence");
+        attester.quote().expect("couldn't quote evidence")
+    }
+
+    fn create_sealer(bundle: &[u8], config: &[u8], endorsements: &Endorsements) -> Sealer {
+        Sealer::create(
+            &create_evidence(bundle, config),
+            endorsements,
+            Arc::new(FixedClock::at_instant(now())),
+        )
+        .expect("couldn't create sealer")
+    }
+
+    fn create_endorser() -> p256::ecdsa::SigningKey {
+        p256::ecdsa::SigningKey::random(&mut rand_core::OsRng)
+    }
+
+    // Returns endorsements which endorse the bundle with the given key.
+    fn endorse(endorser: &p256::ecdsa::SigningKey, bundle: &[u8]) -> Endorsements {
+        let statement = make_statement(
+            "container",
+            &hex_digest_from_contents(bundle),
+            now(),
+            now() - Duration::from_days(1),
+            now() + Duration::from_days(1),
+            vec![],
+        );
+        let serialized_statement = serialize_statement(&statement).unwrap();
+        let signature: Signature = endorser.sign(&serialized_statement);
+        let endorsement = ContainerEndorsement {
+            binary: Some(create_signed_endorsement(
+                &serialized_statement,
+                signature.to_der().as_bytes(),
+                1,
+                &[],
+                &[],
+            )),
+            configuration: None,
+        };
+        Endorsements { events: vec![endorsement.into()], ..Default::default() }
+    }
+
+    // Returns a policy which accepts any bundle endorsed with the given key.
+    fn endorsed_policy(endorser: &p256::ecdsa::SigningKey) -> ContainerLayerReferenceValues {
+        let public_key = endorser.verifying_key().to_public_key_der().unwrap().into_vec();
+        ContainerLayerReferenceValues {
+            binary: Some(BinaryReferenceValue {
+                r#type: Some(binary_reference_value::Type::Endorsement(
+                    create_endorsement_reference_value(
+                        create_verifying_key_from_raw(&public_key, 1),
+                        None,
+                    ),
+                )),
+            }),
+            configuration: Some(BinaryReferenceValue {
+                r#type: Some(binary_reference_value::Type::Skip(SkipVerification {})),
+            }),
+        }
+    }
+
+    #[test]
+    fn unseal_with_same_digests_succeeds() {
+        let (key, _) = generate_encryption_key_pair();
+        let sealer = create_sealer(BUNDLE, CONFIG, &Endorsements::default());
+
+        let sealed_blob = sealer.seal(KeyOrigin::Instance, 0, &key, PLAINTEXT, None).unwrap();
+        // A restarted container has the same digests.
+        let restarted = create_sealer(BUNDLE, CONFIG, &Endorsements::default());
+
+        assert_eq!(restarted.unseal(&key, &sealed_blob).unwrap(), PLAINTEXT);
+    }
+
+    #[test]
+    fn unseal_with_other_digests_fails() {
+        let (key, _) = generate_encryption_key_pair();
+        let sealer = create_sealer(BUNDLE, CONFIG, &Endorsements::default());
+        let sealed_blob = sealer.seal(KeyOrigin::Group, 0, &key, PLAINTEXT, None).unwrap();
+
+        let other_bundle = create_sealer(b"other bundle", CONFIG, &Endorsements::default());
+        let other_config = create_sealer(BUNDLE, b"other config", &Endorsements::default());
+
+        assert!(other_bundle.unseal(&key, &sealed_blob).is_err());
+        assert!(other_config.unseal(&key, &sealed_blob).is_err());
+    }
+
+    #[test]
+    fn unseal_with_other_key_fails() {
+        let (key, _) = generate_encryption_key_pair();
+        let (other_key, _) = generate_encryption_key_pair();
+        let sealer = create_sealer(BUNDLE, CONFIG, &Endorsements::default());
+
+        let sealed_blob = sealer.seal(KeyOrigin::Instance, 0, &key, PLAINTEXT, None).unwrap();
+
+        assert!(sealer.unseal(&other_key, &sealed_blob).is_err());
+    }
+
+    #[test]
+    fn unseal_with_endorsed_bundle_succeeds() {
+        let (key, _) = generate_encryption_key_pair();
+        let endorser = create_endorser();
+        let sealer = create_sealer(BUNDLE, CONFIG, &endorse(&endorser, BUNDLE));
+        let policy = endorsed_policy(&endorser);
+        let sealed_blob = sealer.seal(KeyOrigin::Group, 0, &key, PLAINTEXT, Some(policy)).unwrap();
+
+        // An update endorsed by the same key can unseal the data.
+        let update = create_sealer(
+            b"updated bundle",
+            b"updated config",
+            &endorse(&endorser, b"updated bundle"),
+        );
+
+        assert_eq!(update.unseal(&key, &sealed_blob).unwrap(), PLAINTEXT);
+    }
+
+    #[test]
+    fn unseal_with_bundle_endorsed_by_other_key_fails() {
+        let (key, _) = generate_encryption_key_pair();
+        let endorser = create_endorser();
+        let sealer = create_sealer(BUNDLE, CONFIG, &endorse(&endorser, BUNDLE));
+        let policy = endorsed_policy(&endorser);
+        let sealed_blob = sealer.seal(KeyOrigin::Group, 0, &key, PLAINTEXT, Some(policy)).unwrap();
+
+        let other = create_sealer(BUNDLE, CONFIG, &endorse(&create_endorser(), BUNDLE));
+        let unendorsed = create_sealer(BUNDLE, CONFIG, &Endorsements::default());
+
+        assert!(other.unseal(&key, &sealed_blob).is_err());
+        assert!(unendorsed.unseal(&key, &sealed_blob).is_err());
+    }
+
+    #[test]
+    fn seal_with_unsatisfied_policy_fails() {
+        let (key, _) = generate_encryption_key_pair();
+        let policy = endorsed_policy(&create_endorser());
+        let sealer = create_sealer(BUNDLE, CONFIG, &Endorsements::default());
+
+        assert!(sealer.seal(KeyOrigin::Instance, 0, &key, PLAINTEXT, Some(policy)).is_err());
+    }
+
+    #[test]
+    fn seal_without_binary_check_fails() {
+        let (key, _) = generate_encryption_key_pair();
+        let sealer = create_sealer(BUNDLE, CONFIG, &Endorsements::default());
+        let policy = ContainerLayerReferenceValues {
+            binary: Some(BinaryReferenceValue {
+                r#type: Some(binary_reference_value::Type::Skip(SkipVerification {})),
+            }),
+            configuration: Some(BinaryReferenceValue {
+                r#type: Some(binary_reference_value::Type::Skip(SkipVerification {})),
+            }),
+        };
+
+        assert!(sealer.seal(KeyOrigin::Instance, 0, &key, PLAINTEXT, Some(policy)).is_err());
+    }
+
+    #[test]
+    fn unseal_with_modified_header_fails() {
+        let (key, _) = generate_encryption_key_pair();
+        let endorser = create_endorser();
+        let sealer = create_sealer(BUNDLE, CONFIG, &endorse(&endorser, BUNDLE));
+        let mut sealed_blob = sealer.seal(KeyOrigin::Instance, 0, &key, PLAINTEXT, None).unwrap();
+
+        // Relaxing the policy would let other containers unseal the data.
+        sealed_blob.header.as_mut().unwrap().policy = Some(endorsed_policy(&endorser));
+
+        assert!(sealer.unseal(&key, &sealed_blob).is_err());
+    }
+}
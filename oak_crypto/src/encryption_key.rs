@@ -24,8 +24,8 @@ use zeroize::{Zeroize, Zeroizing};
 use crate::{
     encryptor::ClientEncryptor,
     hpke::{
-        generate_kem_key_pair, setup_base_recipient, Deserializable, PrivateKey, RecipientContext,
-        Serializable, OAK_HPKE_INFO,
+        derive_kem_public_key, generate_kem_key_pair, setup_base_recipient, Deserializable,
+        PrivateKey, RecipientContext, Serializable, OAK_HPKE_INFO,
     },
     EMPTY_ASSOCIATED_DATA,
 };
@@ -48,6 +48,12 @@ impl EncryptionKey {
         Self { private_key }
     }
 
+    /// Returns the NIST P-256 SEC1 encoded point public key corresponding to
+    /// the private key.
+    pub fn public_key(&self) -> Vec<u8> {
+        derive_kem_public_key(&self.private_key).to_bytes().to_vec()
+    }
+
     pub fn serialize(self) -> Vec<u8> {
         self.private_key.to_bytes().to_vec()
     }
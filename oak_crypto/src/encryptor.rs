@@ -84,6 +84,22 @@ impl From<Payload> for EncryptedMessage {
 pub trait Encryptor: Send {
     fn encrypt(&mut self, plaintext: Payload) -> anyhow::Result<Payload>;
     fn decrypt(&mut self, ciphertext: Payload) -> anyhow::Result<Payload>;
+
+    /// Switches the key used by `encrypt` to a new key derived from the
+    /// current one.
+    ///
+    /// Encryptors that don't support rekeying return an error.
+    fn rekey_encryption(&mut self) -> anyhow::Result<()> {
+        anyhow::bail!("rekeying is not supported by this encryptor")
+    }
+
+    /// Switches the key used by `decrypt` to a new key derived from the
+    /// current one, mirroring a `rekey_encryption` call made by the peer.
+    ///
+    /// Encryptors that don't support rekeying return an error.
+    fn rekey_decryption(&mut self) -> anyhow::Result<()> {
+        anyhow::bail!("rekeying is not supported by this encryptor")
+    }
 }
 
 /// Encryptor object for encrypting requests/decrypting responses.
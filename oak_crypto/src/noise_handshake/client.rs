@@ -12,12 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use alloc::{boxed::Box, vec::Vec};
+use alloc::{boxed::Box, vec, vec::Vec};
+
+use zeroize::Zeroizing;
 
 pub use crate::noise_handshake::crypto_wrapper::{
     aes_256_gcm_open_in_place, aes_256_gcm_seal_in_place, ecdsa_verify, hkdf_sha256,
-    p256_scalar_mult, rand_bytes, sha256, sha256_two_part, EcdsaKeyPair, P256Scalar, NONCE_LEN,
-    P256_SCALAR_LEN, P256_X962_LEN, SHA256_OUTPUT_LEN, SYMMETRIC_KEY_LEN,
+    p256_scalar_mult, rand_bytes, sha256, sha256_two_part, EcdsaKeyPair, MlKem768DecapsulationKey,
+    P256Scalar, NONCE_LEN, P256_SCALAR_LEN, P256_X962_LEN, SHA256_OUTPUT_LEN, SYMMETRIC_KEY_LEN,
 };
 use crate::noise_handshake::{
     error::Error,
@@ -27,42 +29,107 @@ use crate::noise_handshake::{
 
 pub struct HandshakeInitiator {
     noise: Noise,
+    handshake_type: HandshakeType,
     peer_identity_pub_key: Option<[u8; P256_X962_LEN]>,
     self_identity_priv_key: Option<Box<dyn IdentityKeyHandle>>,
     ephemeral_priv_key: P256Scalar,
+    // The ephemeral ML-KEM-768 key pair, for hybrid handshakes.
+    kem_key_pair: Option<(MlKem768DecapsulationKey, Vec<u8>)>,
+    psk: Option<Zeroizing<[u8; SYMMETRIC_KEY_LEN]>>,
+    final_message: Option<NoiseMessage>,
 }
 
 impl HandshakeInitiator {
-    pub fn new_nk(peer_public_key: &[u8; P256_X962_LEN]) -> Self {
+    fn new(
+        handshake_type: HandshakeType,
+        peer_identity_pub_key: Option<[u8; P256_X962_LEN]>,
+        self_identity_priv_key: Option<Box<dyn IdentityKeyHandle>>,
+        psk: Option<[u8; SYMMETRIC_KEY_LEN]>,
+    ) -> Self {
         Self {
-            noise: Noise::new(HandshakeType::Nk),
-            peer_identity_pub_key: Some(*peer_public_key),
-            self_identity_priv_key: None,
+            noise: Noise::new(handshake_type),
+            handshake_type,
+            peer_identity_pub_key,
+            self_identity_priv_key,
             ephemeral_priv_key: P256Scalar::generate(),
+            kem_key_pair: None,
+            psk: psk.map(Zeroizing::new),
+            final_message: None,
         }
     }
 
+    pub fn new_nk(peer_public_key: &[u8; P256_X962_LEN]) -> Self {
+        Self::new(HandshakeType::Nk, Some(*peer_public_key), None, None)
+    }
+
     pub fn new_nn() -> Self {
-        Self {
-            noise: Noise::new(HandshakeType::Nn),
-            peer_identity_pub_key: None,
-            self_identity_priv_key: None,
-            ephemeral_priv_key: P256Scalar::generate(),
-        }
+        Self::new(HandshakeType::Nn, None, None, None)
     }
     pub fn new_kk(
         peer_public_key: [u8; P256_X962_LEN],
         self_priv_key: Box<dyn IdentityKeyHandle>,
     ) -> Self {
-        Self {
-            noise: Noise::new(HandshakeType::Kk),
-            peer_identity_pub_key: Some(peer_public_key),
-            self_identity_priv_key: Some(self_priv_key),
-            ephemeral_priv_key: P256Scalar::generate(),
-        }
+        Self::new(HandshakeType::Kk, Some(peer_public_key), Some(self_priv_key), None)
+    }
+
+    /// Creates an initiator for the Noise IK pattern, or for IKpsk2 if `psk`
+    /// is provided. The initiator's static key is sent (encrypted) in the
+    /// initial message, so the responder doesn't need to know it beforehand.
+    pub fn new_ik(
+        peer_public_key: [u8; P256_X962_LEN],
+        self_priv_key: Box<dyn IdentityKeyHandle>,
+        psk: Option<[u8; SYMMETRIC_KEY_LEN]>,
+    ) -> Self {
+        let handshake_type = if psk.is_some() { HandshakeType::IkPsk2 } else { HandshakeType::Ik };
+        Self::new(handshake_type, Some(peer_public_key), Some(self_priv_key), psk)
+    }
+
+    /// Creates an initiator for the Noise XX pattern, or for XXpsk3 if `psk`
+    /// is provided. Both static keys are exchanged (encrypted) during the
+    /// handshake, which takes an additional message from the initiator: see
+    /// `take_final_message`.
+    pub fn new_xx(
+        self_priv_key: Box<dyn IdentityKeyHandle>,
+        psk: Option<[u8; SYMMETRIC_KEY_LEN]>,
+    ) -> Self {
+        let handshake_type = if psk.is_some() { HandshakeType::XxPsk3 } else { HandshakeType::Xx };
+        Self::new(handshake_type, None, Some(self_priv_key), psk)
+    }
+
+    /// Switches the initiator to the hybrid variant of its handshake pattern,
+    /// in which an ML-KEM-768 shared secret is mixed into the handshake along
+    /// with the P-256 ones. The responder must answer with an encapsulated
+    /// secret, otherwise the handshake fails.
+    pub fn with_hybrid_kem(mut self) -> Self {
+        self.noise = Noise::new_hybrid(self.handshake_type);
+        self.kem_key_pair = Some(MlKem768DecapsulationKey::generate());
+        self
+    }
+
+    /// Returns the responder's static public key, either the one provided when
+    /// creating the initiator, or the one received during the handshake.
+    pub fn peer_static_public_key(&self) -> Option<&[u8; P256_X962_LEN]> {
+        self.peer_identity_pub_key.as_ref()
+    }
+
+    /// Takes the message that completes the handshake from the initiator's
+    /// side, for patterns that require three messages (XX). It's available
+    /// after `process_response` succeeds.
+    pub fn take_final_message(&mut self) -> Option<NoiseMessage> {
+        self.final_message.take()
     }
 
     pub fn build_initial_message(&mut self) -> Result<NoiseMessage, Error> {
+        match self.handshake_type {
+            HandshakeType::Nk | HandshakeType::Nn | HandshakeType::Kk => {
+                self.build_initial_message_nn_nk_kk()
+            }
+            HandshakeType::Ik | HandshakeType::IkPsk2 => self.build_initial_message_ik(),
+            HandshakeType::Xx | HandshakeType::XxPsk3 => self.build_initial_message_xx(),
+        }
+    }
+
+    fn build_initial_message_nn_nk_kk(&mut self) -> Result<NoiseMessage, Error> {
         self.noise.mix_hash(&[0; 1]);
         if let Some(peer_identity_pub_key) = self.peer_identity_pub_key {
             self.noise.mix_hash_point(peer_identity_pub_key.as_slice());
@@ -72,6 +139,7 @@ impl HandshakeInitiator {
 
         self.noise.mix_hash(ephemeral_pub_key_bytes);
         self.noise.mix_key(ephemeral_pub_key_bytes);
+        let kem_public_key = self.mix_kem_public_key();
         if let Some(peer_identity_pub_key) = self.peer_identity_pub_key {
             let es_ecdh_bytes = p256_scalar_mult(&self.ephemeral_priv_key, &peer_identity_pub_key)
                 .map_err(|_| Error::InvalidHandshake)?;
@@ -98,31 +166,184 @@ impl HandshakeInitiator {
             }
         }
         let ciphertext = self.noise.encrypt_and_hash(&[]);
-        Ok(NoiseMessage { ciphertext, ephemeral_public_key: ephemeral_pub_key.to_vec() })
+        Ok(NoiseMessage {
+            ciphertext,
+            ephemeral_public_key: ephemeral_pub_key.to_vec(),
+            static_public_key: vec![],
+            kem_public_key,
+            kem_ciphertext: vec![],
+        })
     }
 
+    // -> e, es, s, ss
+    fn build_initial_message_ik(&mut self) -> Result<NoiseMessage, Error> {
+        let peer_identity_pub_key =
+            self.peer_identity_pub_key.ok_or(Error::MissingPeerPublicKey)?;
+        self.noise.mix_hash(&[0; 1]);
+        self.noise.mix_hash_point(peer_identity_pub_key.as_slice());
+
+        let ephemeral_pub_key = self.ephemeral_priv_key.compute_public_key();
+        self.noise.mix_hash(ephemeral_pub_key.as_ref());
+        self.noise.mix_key(ephemeral_pub_key.as_ref());
+        let kem_public_key = self.mix_kem_public_key();
+        let es_ecdh_bytes = p256_scalar_mult(&self.ephemeral_priv_key, &peer_identity_pub_key)
+            .map_err(|_| Error::InvalidHandshake)?;
+        self.noise.mix_key(&es_ecdh_bytes);
+
+        let self_priv_key = self.self_identity_priv_key.as_ref().ok_or(Error::InvalidPrivateKey)?;
+        let self_static_pub_key =
+            self_priv_key.get_public_key().map_err(|_| Error::InvalidPublicKey)?;
+        let static_public_key = self.noise.encrypt_and_hash(&self_static_pub_key);
+        let ss_ecdh_bytes = self_priv_key
+            .derive_dh_secret(peer_identity_pub_key.as_slice())
+            .map_err(|_| Error::InvalidHandshake)?;
+        self.noise.mix_key(&ss_ecdh_bytes);
+
+        let ciphertext = self.noise.encrypt_and_hash(&[]);
+        Ok(NoiseMessage {
+            ciphertext,
+            ephemeral_public_key: ephemeral_pub_key.to_vec(),
+            static_public_key,
+            kem_public_key,
+            kem_ciphertext: vec![],
+        })
+    }
+
+    // -> e
+    fn build_initial_message_xx(&mut self) -> Result<NoiseMessage, Error> {
+        self.noise.mix_hash(&[0; 1]);
+        let ephemeral_pub_key = self.ephemeral_priv_key.compute_public_key();
+        self.noise.mix_hash(ephemeral_pub_key.as_ref());
+        self.noise.mix_key(ephemeral_pub_key.as_ref());
+        let kem_public_key = self.mix_kem_public_key();
+        let ciphertext = self.noise.encrypt_and_hash(&[]);
+        Ok(NoiseMessage {
+            ciphertext,
+            ephemeral_public_key: ephemeral_pub_key.to_vec(),
+            static_public_key: vec![],
+            kem_public_key,
+            kem_ciphertext: vec![],
+        })
+    }
+
+    // e1: in hybrid handshakes, the ML-KEM encapsulation key is sent right
+    // after the ephemeral key.
+    fn mix_kem_public_key(&mut self) -> Vec<u8> {
+        match self.kem_key_pair.as_ref() {
+            Some((_, kem_public_key)) => {
+                self.noise.mix_hash(kem_public_key);
+                kem_public_key.clone()
+            }
+            None => vec![],
+        }
+    }
+
+    // -> s, se (psk)
+    fn build_final_message_xx(
+        &mut self,
+        peer_ephemeral_pub_key: &[u8; P256_X962_LEN],
+    ) -> Result<NoiseMessage, Error> {
+        let self_priv_key = self.self_identity_priv_key.as_ref().ok_or(Error::InvalidPrivateKey)?;
+        let self_static_pub_key =
+            self_priv_key.get_public_key().map_err(|_| Error::InvalidPublicKey)?;
+        let static_public_key = self.noise.encrypt_and_hash(&self_static_pub_key);
+        let se_ecdh_bytes = self_priv_key
+            .derive_dh_secret(peer_ephemeral_pub_key.as_slice())
+            .map_err(|_| Error::InvalidHandshake)?;
+        self.noise.mix_key(&se_ecdh_bytes);
+        if let Some(psk) = self.psk.as_ref() {
+            self.noise.mix_key_and_hash(psk.as_slice());
+        }
+        let ciphertext = self.noise.encrypt_and_hash(&[]);
+        Ok(NoiseMessage {
+            ciphertext,
+            ephemeral_public_key: vec![],
+            static_public_key,
+            kem_public_key: vec![],
+            kem_ciphertext: vec![],
+        })
+    }
+
+    /// Processes the responder's message and returns the handshake hash and
+    /// the crypter for the session.
+    ///
+    /// For XX, the returned hash covers the transcript up to the responder's
+    /// message, which is the last point both parties can bind to before the
+    /// session is used. The crypter is derived from the complete handshake,
+    /// including the final message that has to be sent to the responder.
     pub fn process_response(
         &mut self,
         handshake_response: &NoiseMessage,
     ) -> Result<([u8; SHA256_OUTPUT_LEN], OrderedCrypter), Error> {
-        let ee_ecdh_bytes = p256_scalar_mult(
-            &self.ephemeral_priv_key,
-            handshake_response
-                .ephemeral_public_key
-                .as_slice()
-                .try_into()
-                .map_err(|_| Error::DecryptFailed)?,
-        )
-        .map_err(|_| Error::InvalidHandshake)?;
+        let peer_ephemeral_pub_key: [u8; P256_X962_LEN] = handshake_response
+            .ephemeral_public_key
+            .as_slice()
+            .try_into()
+            .map_err(|_| Error::DecryptFailed)?;
+        let ee_ecdh_bytes = p256_scalar_mult(&self.ephemeral_priv_key, &peer_ephemeral_pub_key)
+            .map_err(|_| Error::InvalidHandshake)?;
         self.noise.mix_hash(&handshake_response.ephemeral_public_key);
         self.noise.mix_key(&handshake_response.ephemeral_public_key);
         self.noise.mix_key(&ee_ecdh_bytes);
+        // ekem1
+        match (self.kem_key_pair.as_ref(), handshake_response.kem_ciphertext.is_empty()) {
+            (Some((kem_decapsulation_key, _)), false) => {
+                let kem_shared_secret = Zeroizing::new(
+                    kem_decapsulation_key
+                        .decapsulate(&handshake_response.kem_ciphertext)
+                        .map_err(|_| Error::KemFailed)?,
+                );
+                self.noise.mix_hash(&handshake_response.kem_ciphertext);
+                self.noise.mix_key(kem_shared_secret.as_slice());
+            }
+            (None, true) => {}
+            // Either the responder doesn't support the hybrid handshake, or it
+            // answered a message that wasn't sent.
+            _ => return Err(Error::InvalidHandshake),
+        }
+        match self.handshake_type {
+            // <- e, ee, se (psk)
+            HandshakeType::Ik | HandshakeType::IkPsk2 => {
+                let se_ecdh_bytes = self
+                    .self_identity_priv_key
+                    .as_ref()
+                    .ok_or(Error::InvalidPrivateKey)?
+                    .derive_dh_secret(peer_ephemeral_pub_key.as_slice())
+                    .map_err(|_| Error::InvalidHandshake)?;
+                self.noise.mix_key(&se_ecdh_bytes);
+                if let Some(psk) = self.psk.as_ref() {
+                    self.noise.mix_key_and_hash(psk.as_slice());
+                }
+            }
+            // <- e, ee, s, es
+            HandshakeType::Xx | HandshakeType::XxPsk3 => {
+                let peer_static_pub_key: [u8; P256_X962_LEN] = self
+                    .noise
+                    .decrypt_and_hash(&handshake_response.static_public_key)
+                    .map_err(|_| Error::DecryptFailed)?
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| Error::InvalidPublicKey)?;
+                let es_ecdh_bytes =
+                    p256_scalar_mult(&self.ephemeral_priv_key, &peer_static_pub_key)
+                        .map_err(|_| Error::InvalidHandshake)?;
+                self.noise.mix_key(&es_ecdh_bytes);
+                self.peer_identity_pub_key = Some(peer_static_pub_key);
+            }
+            HandshakeType::Nk | HandshakeType::Nn | HandshakeType::Kk => {}
+        }
         let plaintext = self
             .noise
             .decrypt_and_hash(&handshake_response.ciphertext)
             .map_err(|_| Error::DecryptFailed)?;
-        assert_eq!(plaintext.len(), 0);
+        if !plaintext.is_empty() {
+            return Err(Error::InvalidHandshake);
+        }
+        let handshake_hash = self.noise.handshake_hash();
+        if matches!(self.handshake_type, HandshakeType::Xx | HandshakeType::XxPsk3) {
+            self.final_message = Some(self.build_final_message_xx(&peer_ephemeral_pub_key)?);
+        }
         let (write_key, read_key) = self.noise.traffic_keys();
-        Ok((self.noise.handshake_hash(), OrderedCrypter::new(&read_key, &write_key)))
+        Ok((handshake_hash, OrderedCrypter::new(&read_key, &write_key)))
     }
 }
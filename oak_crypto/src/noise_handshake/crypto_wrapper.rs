@@ -34,10 +34,20 @@ pub const P256_X962_LEN: usize = 65;
 /// The length of a P-256 scalar value.
 pub const P256_SCALAR_LEN: usize = 32;
 
+/// The length of an encoded ML-KEM-768 encapsulation key.
+pub const ML_KEM_768_ENCAPSULATION_KEY_LEN: usize = 1184;
+
+/// The length of an ML-KEM-768 ciphertext.
+pub const ML_KEM_768_CIPHERTEXT_LEN: usize = 1088;
+
 use alloc::vec::Vec;
 
 use aes_gcm::{AeadInPlace, KeyInit};
 use ecdsa;
+use ml_kem::{
+    kem::{Decapsulate, Encapsulate},
+    EncodedSizeUser, KemCore, MlKem768,
+};
 use p256::ecdsa::signature::Signer;
 use pkcs8::{DecodePrivateKey, EncodePrivateKey};
 use primeorder::{
@@ -194,6 +204,41 @@ pub fn p256_scalar_mult(
     Ok(x.as_slice().try_into().unwrap())
 }
 
+type MlKem768EncapsulationKey = <MlKem768 as KemCore>::EncapsulationKey;
+
+pub struct MlKem768DecapsulationKey {
+    key: <MlKem768 as KemCore>::DecapsulationKey,
+}
+
+impl MlKem768DecapsulationKey {
+    /// Generates a new key pair, and returns the decapsulation key along with
+    /// the encoded encapsulation key.
+    pub fn generate() -> (MlKem768DecapsulationKey, Vec<u8>) {
+        let mut rng = rand_chacha::ChaCha20Rng::from_entropy();
+        let (key, encapsulation_key) = MlKem768::generate(&mut rng);
+        (MlKem768DecapsulationKey { key }, encapsulation_key.as_bytes().to_vec())
+    }
+
+    pub fn decapsulate(&self, ciphertext: &[u8]) -> Result<[u8; SYMMETRIC_KEY_LEN], ()> {
+        let ciphertext = ml_kem::Ciphertext::<MlKem768>::try_from(ciphertext).map_err(|_| ())?;
+        let shared_secret = self.key.decapsulate(&ciphertext)?;
+        Ok(shared_secret.into())
+    }
+}
+
+/// Encapsulates a new shared secret to the encoded ML-KEM-768
+/// `encapsulation_key`, and returns the ciphertext along with the secret.
+pub fn ml_kem_768_encapsulate(
+    encapsulation_key: &[u8],
+) -> Result<(Vec<u8>, [u8; SYMMETRIC_KEY_LEN]), ()> {
+    let encapsulation_key =
+        ml_kem::Encoded::<MlKem768EncapsulationKey>::try_from(encapsulation_key).map_err(|_| ())?;
+    let encapsulation_key = MlKem768EncapsulationKey::from_bytes(&encapsulation_key);
+    let mut rng = rand_chacha::ChaCha20Rng::from_entropy();
+    let (ciphertext, shared_secret) = encapsulation_key.encapsulate(&mut rng)?;
+    Ok((ciphertext.to_vec(), shared_secret.into()))
+}
+
 pub struct EcdsaKeyPair {
     key_pair: p256::ecdsa::SigningKey,
 }
@@ -23,6 +23,7 @@ pub enum Error {
     InvalidNonce,
     InvalidPrivateKey,
     InvalidPublicKey,
+    KemFailed,
     MissingPeerPublicKey,
     ReplayedNonce,
     Unknown(&'static str),
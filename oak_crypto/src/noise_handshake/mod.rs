@@ -21,12 +21,12 @@ pub mod client;
 mod crypto_wrapper;
 mod error;
 mod noise;
+mod replay_window;
 #[cfg(test)]
 mod tests;
-use alloc::vec::Vec;
+use alloc::{vec, vec::Vec};
 
 use anyhow::anyhow;
-use hashbrown::HashSet;
 use oak_proto_rust::oak::session::v1::NoiseHandshakeMessage;
 use zeroize::Zeroizing;
 
@@ -36,15 +36,29 @@ use crate::noise_handshake::{
 };
 pub use crate::{
     identity_key::IdentityKeyHandle,
-    noise_handshake::crypto_wrapper::{
-        aes_256_gcm_open_in_place, aes_256_gcm_seal_in_place, ecdsa_verify, hkdf_sha256,
-        p256_scalar_mult, rand_bytes, session_binding_token_hash, sha256, sha256_two_part,
-        EcdsaKeyPair, P256Scalar, NONCE_LEN, P256_X962_LEN, SHA256_OUTPUT_LEN, SYMMETRIC_KEY_LEN,
+    noise_handshake::{
+        crypto_wrapper::{
+            aes_256_gcm_open_in_place, aes_256_gcm_seal_in_place, ecdsa_verify, hkdf_sha256,
+            ml_kem_768_encapsulate, p256_scalar_mult, rand_bytes, session_binding_token_hash,
+            sha256, sha256_two_part, EcdsaKeyPair, MlKem768DecapsulationKey, P256Scalar,
+            ML_KEM_768_CIPHERTEXT_LEN, ML_KEM_768_ENCAPSULATION_KEY_LEN, NONCE_LEN, P256_X962_LEN,
+            SHA256_OUTPUT_LEN, SYMMETRIC_KEY_LEN,
+        },
+        replay_window::{ReplayWindow, ReplayWindowStats},
     },
 };
 
-// This is assumed to be vastly larger than any connection will ever reach.
-const MAX_SEQUENCE: u32 = 1u32 << 24;
+// The number of messages that can be encrypted with a single traffic key.
+// Long-lived channels are expected to rekey (see `OrderedCrypter::rekey_write`)
+// before reaching this limit.
+pub const MAX_SEQUENCE: u32 = 1u32 << 24;
+
+// Nonce used to derive a new traffic key from the current one, as defined by
+// the default `REKEY` function of the Noise specification:
+// <https://noiseprotocol.org/noise.html#cipher-functions>
+// It can never collide with a message nonce, since those are bounded by
+// `MAX_SEQUENCE`.
+const REKEY_NONCE: [u8; NONCE_LEN] = [0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
 
 pub struct Nonce {
     pub nonce: u32,
@@ -61,6 +75,12 @@ impl Nonce {
         Ok(ret)
     }
 
+    /// Returns the number of nonces that can still be generated by
+    /// `next_nonce`.
+    pub fn remaining(&self) -> u32 {
+        (MAX_SEQUENCE + 1).saturating_sub(self.nonce)
+    }
+
     // Nonce must be `NONCE_LEN` bytes with the last 4 bytes holding the nonce value
     // and the rest padded with 0.
     pub fn get_nonce_value(nonce: &[u8; NONCE_LEN]) -> Result<u32, Error> {
@@ -75,14 +95,23 @@ impl Nonce {
 
 pub struct NoiseMessage {
     pub ephemeral_public_key: Vec<u8>,
+    // Encrypted static public key, for the patterns that transmit it (IK, XX).
+    pub static_public_key: Vec<u8>,
     pub ciphertext: Vec<u8>,
+    // The initiator's ML-KEM-768 encapsulation key, in hybrid handshakes.
+    pub kem_public_key: Vec<u8>,
+    // The ML-KEM-768 ciphertext sent by the responder, in hybrid handshakes.
+    pub kem_ciphertext: Vec<u8>,
 }
 
 impl From<&NoiseHandshakeMessage> for NoiseMessage {
     fn from(value: &NoiseHandshakeMessage) -> Self {
         NoiseMessage {
             ephemeral_public_key: value.ephemeral_public_key.clone(),
+            static_public_key: value.static_public_key.clone(),
             ciphertext: value.ciphertext.clone(),
+            kem_public_key: value.kem_public_key.clone(),
+            kem_ciphertext: value.kem_ciphertext.clone(),
         }
     }
 }
@@ -91,7 +120,10 @@ impl From<NoiseHandshakeMessage> for NoiseMessage {
     fn from(value: NoiseHandshakeMessage) -> Self {
         NoiseMessage {
             ephemeral_public_key: value.ephemeral_public_key,
+            static_public_key: value.static_public_key,
             ciphertext: value.ciphertext,
+            kem_public_key: value.kem_public_key,
+            kem_ciphertext: value.kem_ciphertext,
         }
     }
 }
@@ -100,6 +132,7 @@ fn aes_gcm_256_encrypt(
     key: &[u8; SYMMETRIC_KEY_LEN],
     nonce: &[u8; NONCE_LEN],
     plaintext: &[u8],
+    aad: &[u8],
 ) -> Result<Vec<u8>, Error> {
     // The crypto library will include a small tag at the end after encrypting.
     // We can avoid potential re-alloc in the crypto library by ensuring that
@@ -133,7 +166,7 @@ fn aes_gcm_256_encrypt(
     let num_zeros = padded_size - plaintext.len() - 1;
     padded_encrypt_data[padded_size - 1] = num_zeros as u8;
 
-    crypto_wrapper::aes_256_gcm_seal_in_place(key, nonce, &[], &mut padded_encrypt_data);
+    crypto_wrapper::aes_256_gcm_seal_in_place(key, nonce, aad, &mut padded_encrypt_data);
     Ok(padded_encrypt_data)
 }
 
@@ -141,11 +174,12 @@ fn aes_gcm_256_decrypt(
     key: &[u8; SYMMETRIC_KEY_LEN],
     nonce: &[u8; NONCE_LEN],
     ciphertext: &[u8],
+    aad: &[u8],
 ) -> Result<Vec<u8>, Error> {
     // Aes256Gcm implements Aead in terms of AeadInPlace, so even if you remove the
     // `Vec::from` here the underlying libraries will end up doing the copy anyway.
     let mut plaintext =
-        crypto_wrapper::aes_256_gcm_open_in_place(key, nonce, &[], Vec::from(ciphertext))
+        crypto_wrapper::aes_256_gcm_open_in_place(key, nonce, aad, Vec::from(ciphertext))
             .map_err(|_| Error::DecryptFailed)?;
 
     // Plaintext must have a padding byte, and the unpadded length must be
@@ -158,6 +192,17 @@ fn aes_gcm_256_decrypt(
     Ok(plaintext)
 }
 
+/// Derives a new traffic key from `key` using the Noise `REKEY` function.
+fn rekey(key: &[u8; SYMMETRIC_KEY_LEN]) -> Zeroizing<[u8; SYMMETRIC_KEY_LEN]> {
+    // Leave space for the tag so that sealing doesn't re-allocate the buffer.
+    let mut output = Zeroizing::new(Vec::with_capacity(SYMMETRIC_KEY_LEN * 2));
+    output.resize(SYMMETRIC_KEY_LEN, 0u8);
+    crypto_wrapper::aes_256_gcm_seal_in_place(key, &REKEY_NONCE, &[], &mut output);
+    let mut new_key = Zeroizing::new([0u8; SYMMETRIC_KEY_LEN]);
+    new_key.copy_from_slice(&output[..SYMMETRIC_KEY_LEN]);
+    new_key
+}
+
 pub struct OrderedCrypter {
     read_key: Zeroizing<[u8; SYMMETRIC_KEY_LEN]>,
     write_key: Zeroizing<[u8; SYMMETRIC_KEY_LEN]>,
@@ -181,11 +226,46 @@ impl OrderedCrypter {
     }
 
     pub fn encrypt(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, Error> {
-        aes_gcm_256_encrypt(&self.write_key, &self.write_nonce.next_nonce()?, plaintext)
+        self.encrypt_with_aad(plaintext, &[])
     }
 
     pub fn decrypt(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
-        aes_gcm_256_decrypt(&self.read_key, &self.read_nonce.next_nonce()?, ciphertext)
+        self.decrypt_with_aad(ciphertext, &[])
+    }
+
+    /// Encrypts `plaintext` and authenticates it together with `aad`, which is
+    /// not included in the ciphertext.
+    pub fn encrypt_with_aad(&mut self, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, Error> {
+        aes_gcm_256_encrypt(&self.write_key, &self.write_nonce.next_nonce()?, plaintext, aad)
+    }
+
+    /// Decrypts `ciphertext`, which must have been encrypted with the same
+    /// `aad`.
+    pub fn decrypt_with_aad(&mut self, ciphertext: &[u8], aad: &[u8]) -> Result<Vec<u8>, Error> {
+        aes_gcm_256_decrypt(&self.read_key, &self.read_nonce.next_nonce()?, ciphertext, aad)
+    }
+
+    /// Replaces the write key with a new key derived from it and resets the
+    /// write nonce.
+    ///
+    /// The peer must call `rekey_read` right after decrypting the last message
+    /// that was encrypted with the previous key.
+    pub fn rekey_write(&mut self) {
+        self.write_key = rekey(&self.write_key);
+        self.write_nonce = Nonce { nonce: 0 };
+    }
+
+    /// Replaces the read key with a new key derived from it and resets the
+    /// read nonce. This mirrors a `rekey_write` call made by the peer.
+    pub fn rekey_read(&mut self) {
+        self.read_key = rekey(&self.read_key);
+        self.read_nonce = Nonce { nonce: 0 };
+    }
+
+    /// Returns the number of messages that can still be encrypted before the
+    /// write key has to be replaced.
+    pub fn remaining_write_messages(&self) -> u32 {
+        self.write_nonce.remaining()
     }
 }
 
@@ -202,14 +282,9 @@ pub struct UnorderedCrypter {
     read_key: Zeroizing<[u8; SYMMETRIC_KEY_LEN]>,
     write_key: Zeroizing<[u8; SYMMETRIC_KEY_LEN]>,
     write_nonce: Nonce,
-    // The current furthest read nonce seen so far.
-    furthest_read_nonce: u32,
-    // Window size to ratchet receiving nonces in order to avoid receiving
-    // nonces way too far in the past.
-    window_size: u32,
-    // Buffered read nonces with max capacity equivalent to `window_size` i.e.
-    // nonces lower than (`furthest_read_nonce` - `window_size`) will not be decrypted.
-    buffered_read_nonces: HashSet<u32>,
+    // Window of the `window_size` most recent read nonces, used to reject
+    // replayed messages and messages way too far in the past.
+    replay_window: ReplayWindow,
 }
 
 impl UnorderedCrypter {
@@ -222,54 +297,53 @@ impl UnorderedCrypter {
             read_key: (*read_key).into(),
             write_key: (*write_key).into(),
             write_nonce: Nonce { nonce: 1 },
-            furthest_read_nonce: 0,
-            window_size,
-            buffered_read_nonces: HashSet::with_capacity(window_size.try_into().unwrap()),
+            replay_window: ReplayWindow::new(window_size),
         }
     }
 
-    fn get_lowest_acceptable_read_nonce(&self) -> u32 {
-        let mut lowest_acceptable_read_nonce = 1;
-        if self.furthest_read_nonce > self.window_size {
-            lowest_acceptable_read_nonce = self.furthest_read_nonce - self.window_size + 1;
-        }
-        lowest_acceptable_read_nonce
+    /// Returns the counters of the read nonces accepted and rejected so far.
+    pub fn replay_window_stats(&self) -> ReplayWindowStats {
+        self.replay_window.stats()
     }
 
     pub fn encrypt(&mut self, plaintext: &[u8]) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        self.encrypt_with_aad(plaintext, &[])
+    }
+
+    pub fn decrypt(
+        &mut self,
+        nonce: &[u8; NONCE_LEN],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        self.decrypt_with_aad(nonce, ciphertext, &[])
+    }
+
+    /// Encrypts `plaintext` and authenticates it together with `aad`, which is
+    /// not included in the ciphertext.
+    pub fn encrypt_with_aad(
+        &mut self,
+        plaintext: &[u8],
+        aad: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), Error> {
         let nonce = self.write_nonce.next_nonce()?;
-        let encrypted_message = aes_gcm_256_encrypt(&self.write_key, &nonce, plaintext)?;
+        let encrypted_message = aes_gcm_256_encrypt(&self.write_key, &nonce, plaintext, aad)?;
         Ok((encrypted_message, nonce.to_vec()))
     }
 
-    pub fn decrypt(
+    /// Decrypts `ciphertext`, which must have been encrypted with the same
+    /// `aad`.
+    pub fn decrypt_with_aad(
         &mut self,
         nonce: &[u8; NONCE_LEN],
         ciphertext: &[u8],
+        aad: &[u8],
     ) -> Result<Vec<u8>, Error> {
         let nonce_value = Nonce::get_nonce_value(nonce)?;
-        let lowest_acceptable_nonce = self.get_lowest_acceptable_read_nonce();
-        // Nonce is way too far in the past, reject it.
-        if nonce_value < lowest_acceptable_nonce {
-            return Err(Error::InvalidNonce);
-        }
-        // Nonce is within the window, check for replayed nonces.
-        else if nonce_value >= lowest_acceptable_nonce && nonce_value <= self.furthest_read_nonce
-        {
-            if self.buffered_read_nonces.contains(&nonce_value) {
-                return Err(Error::ReplayedNonce);
-            }
-            self.buffered_read_nonces.insert(nonce_value);
-        }
-        // Nonce is greater than the furthest seen so far.
-        else {
-            self.furthest_read_nonce = nonce_value;
-            // Retain only buffered nonces in the new window span.
-            let new_lowest_acceptable_nonce = self.get_lowest_acceptable_read_nonce();
-            self.buffered_read_nonces.retain(|&n| n >= new_lowest_acceptable_nonce);
-            self.buffered_read_nonces.insert(nonce_value);
-        }
-        aes_gcm_256_decrypt(&self.read_key, nonce, ciphertext)
+        self.replay_window.check(nonce_value)?;
+        let plaintext = aes_gcm_256_decrypt(&self.read_key, nonce, ciphertext, aad)?;
+        // Only authenticated messages move the window.
+        self.replay_window.accept(nonce_value);
+        Ok(plaintext)
     }
 }
 
@@ -299,36 +373,36 @@ pub struct Response {
     pub crypter: OrderedCrypter,
     pub handshake_hash: [u8; SHA256_OUTPUT_LEN],
     pub response: NoiseMessage,
+    // The initiator's static public key, if it was received during the
+    // handshake (IK).
+    pub peer_static_public_key: Option<[u8; P256_X962_LEN]>,
 }
 
 pub fn respond_nk(
     identity_key: &dyn IdentityKeyHandle,
     in_message: &NoiseMessage,
 ) -> Result<Response, Error> {
-    let mut noise = Noise::new(HandshakeType::Nk);
+    let mut noise = responder_noise(HandshakeType::Nk, in_message);
     noise.mix_hash(&[0; 1]); // Prologue
     noise.mix_hash_point(
         identity_key.get_public_key().map_err(|_| Error::InvalidPrivateKey)?.as_slice(),
     );
 
-    // unwrap: we know that `in_data` is `P256_X962_LENGTH` bytes long.
-    noise.mix_hash(in_message.ephemeral_public_key.as_slice());
-    noise.mix_key(in_message.ephemeral_public_key.as_slice());
+    mix_initiator_ephemeral_keys(&mut noise, in_message);
 
     let es_ecdh_bytes = identity_key
         .derive_dh_secret(in_message.ephemeral_public_key.as_slice())
         .map_err(|_| Error::InvalidHandshake)?;
     noise.mix_key(es_ecdh_bytes.as_slice());
-    finish_response(&mut noise, in_message)
+    finish_response(&mut noise, in_message, None, None)
 }
 
 pub fn respond_nn(in_message: &NoiseMessage) -> Result<Response, Error> {
-    let mut noise = Noise::new(HandshakeType::Nn);
+    let mut noise = responder_noise(HandshakeType::Nn, in_message);
     noise.mix_hash(&[0; 1]); // Prologue
 
-    noise.mix_hash(in_message.ephemeral_public_key.as_slice());
-    noise.mix_key(in_message.ephemeral_public_key.as_slice());
-    finish_response(&mut noise, in_message)
+    mix_initiator_ephemeral_keys(&mut noise, in_message);
+    finish_response(&mut noise, in_message, None, None)
 }
 
 pub fn respond_kk(
@@ -339,14 +413,13 @@ pub fn respond_kk(
     // e, es, (ss for Kk only)
     in_message: &NoiseMessage,
 ) -> Result<Response, Error> {
-    let mut noise = Noise::new(HandshakeType::Kk);
+    let mut noise = responder_noise(HandshakeType::Kk, in_message);
     noise.mix_hash(&[0; 1]); // Prologue
     noise.mix_hash_point(
         identity_priv.get_public_key().map_err(|_| Error::InvalidPrivateKey)?.as_slice(),
     );
 
-    noise.mix_hash(in_message.ephemeral_public_key.as_slice());
-    noise.mix_key(in_message.ephemeral_public_key.as_slice());
+    mix_initiator_ephemeral_keys(&mut noise, in_message);
 
     let es_ecdh_bytes = identity_priv
         .derive_dh_secret(in_message.ephemeral_public_key.as_slice())
@@ -364,10 +437,152 @@ pub fn respond_kk(
         .derive_dh_secret(initiator_static_pub_bytes.as_slice())
         .map_err(|_| Error::InvalidHandshake)?;
     noise.mix_key(&se_ecdh_bytes);
-    finish_response(&mut noise, in_message)
+    finish_response(&mut noise, in_message, None, None)
 }
 
-fn finish_response(noise: &mut Noise, in_message: &NoiseMessage) -> Result<Response, Error> {
+pub fn respond_ik(
+    identity_key: &dyn IdentityKeyHandle,
+    psk: Option<&[u8; SYMMETRIC_KEY_LEN]>,
+    in_message: &NoiseMessage,
+) -> Result<Response, Error> {
+    let mut noise = responder_noise(
+        if psk.is_some() { HandshakeType::IkPsk2 } else { HandshakeType::Ik },
+        in_message,
+    );
+    noise.mix_hash(&[0; 1]); // Prologue
+    noise.mix_hash_point(
+        identity_key.get_public_key().map_err(|_| Error::InvalidPrivateKey)?.as_slice(),
+    );
+
+    // -> e, es, s, ss
+    mix_initiator_ephemeral_keys(&mut noise, in_message);
+    let es_ecdh_bytes = identity_key
+        .derive_dh_secret(in_message.ephemeral_public_key.as_slice())
+        .map_err(|_| Error::InvalidHandshake)?;
+    noise.mix_key(es_ecdh_bytes.as_slice());
+
+    let initiator_static_pub: [u8; P256_X962_LEN] = noise
+        .decrypt_and_hash(&in_message.static_public_key)?
+        .as_slice()
+        .try_into()
+        .map_err(|_| Error::InvalidPublicKey)?;
+    let ss_ecdh_bytes = identity_key
+        .derive_dh_secret(initiator_static_pub.as_slice())
+        .map_err(|_| Error::InvalidHandshake)?;
+    noise.mix_key(ss_ecdh_bytes.as_slice());
+    finish_response(&mut noise, in_message, Some(initiator_static_pub), psk)
+}
+
+/// A responder that has answered the first message of a three-message
+/// handshake (XX), and waits for the final message from the initiator.
+pub struct PendingResponse {
+    noise: Noise,
+    ephemeral_priv_key: P256Scalar,
+    psk: Option<Zeroizing<[u8; SYMMETRIC_KEY_LEN]>>,
+    // The hash of the transcript up to the responder's message. See
+    // `HandshakeInitiator::process_response`.
+    pub handshake_hash: [u8; SHA256_OUTPUT_LEN],
+    pub response: NoiseMessage,
+}
+
+impl PendingResponse {
+    /// Processes the final message of the handshake, and returns the crypter
+    /// for the session along with the initiator's static public key.
+    pub fn finish(
+        mut self,
+        final_message: &NoiseMessage,
+    ) -> Result<(OrderedCrypter, [u8; P256_X962_LEN]), Error> {
+        // -> s, se (psk)
+        let initiator_static_pub: [u8; P256_X962_LEN] = self
+            .noise
+            .decrypt_and_hash(&final_message.static_public_key)?
+            .as_slice()
+            .try_into()
+            .map_err(|_| Error::InvalidPublicKey)?;
+        let se_ecdh_bytes =
+            crypto_wrapper::p256_scalar_mult(&self.ephemeral_priv_key, &initiator_static_pub)
+                .map_err(|_| Error::InvalidHandshake)?;
+        self.noise.mix_key(se_ecdh_bytes.as_slice());
+        if let Some(psk) = self.psk.as_ref() {
+            self.noise.mix_key_and_hash(psk.as_slice());
+        }
+        let plaintext = self.noise.decrypt_and_hash(&final_message.ciphertext)?;
+        if !plaintext.is_empty() {
+            return Err(Error::InvalidHandshake);
+        }
+
+        let keys = self.noise.traffic_keys();
+        Ok((OrderedCrypter::new(&keys.0, &keys.1), initiator_static_pub))
+    }
+}
+
+pub fn respond_xx(
+    identity_key: &dyn IdentityKeyHandle,
+    psk: Option<&[u8; SYMMETRIC_KEY_LEN]>,
+    in_message: &NoiseMessage,
+) -> Result<PendingResponse, Error> {
+    let mut noise = responder_noise(
+        if psk.is_some() { HandshakeType::XxPsk3 } else { HandshakeType::Xx },
+        in_message,
+    );
+    noise.mix_hash(&[0; 1]); // Prologue
+
+    // -> e
+    mix_initiator_ephemeral_keys(&mut noise, in_message);
+    let plaintext = noise.decrypt_and_hash(&in_message.ciphertext)?;
+    if !plaintext.is_empty() {
+        return Err(Error::InvalidHandshake);
+    }
+
+    // <- e, ee, s, es
+    let ephemeral_priv = P256Scalar::generate();
+    let ephemeral_pub_key_bytes = ephemeral_priv.compute_public_key();
+    noise.mix_hash(ephemeral_pub_key_bytes.as_slice());
+    noise.mix_key(ephemeral_pub_key_bytes.as_slice());
+    let ee_ecdh_bytes = crypto_wrapper::p256_scalar_mult(
+        &ephemeral_priv,
+        in_message
+            .ephemeral_public_key
+            .as_slice()
+            .try_into()
+            .map_err(|_| Error::InvalidPublicKey)?,
+    )
+    .map_err(|_| Error::InvalidHandshake)?;
+    noise.mix_key(ee_ecdh_bytes.as_slice());
+    let kem_ciphertext = encapsulate_to_initiator(&mut noise, in_message)?;
+
+    let static_public_key = noise.encrypt_and_hash(
+        identity_key.get_public_key().map_err(|_| Error::InvalidPrivateKey)?.as_slice(),
+    );
+    let es_ecdh_bytes = identity_key
+        .derive_dh_secret(in_message.ephemeral_public_key.as_slice())
+        .map_err(|_| Error::InvalidHandshake)?;
+    noise.mix_key(es_ecdh_bytes.as_slice());
+    let response_ciphertext = noise.encrypt_and_hash(&[]);
+
+    Ok(PendingResponse {
+        handshake_hash: noise.handshake_hash(),
+        noise,
+        ephemeral_priv_key: ephemeral_priv,
+        psk: psk.map(|psk| Zeroizing::new(*psk)),
+        response: NoiseMessage {
+            ciphertext: response_ciphertext,
+            ephemeral_public_key: ephemeral_pub_key_bytes.to_vec(),
+            static_public_key,
+            kem_public_key: vec![],
+            kem_ciphertext,
+        },
+    })
+}
+
+// Completes the responder's side of the handshake, with the `se` and `psk`
+// tokens for the patterns that have them in the responder's message (IK).
+fn finish_response(
+    noise: &mut Noise,
+    in_message: &NoiseMessage,
+    initiator_static_pub: Option<[u8; P256_X962_LEN]>,
+    psk: Option<&[u8; SYMMETRIC_KEY_LEN]>,
+) -> Result<Response, Error> {
     let plaintext = noise.decrypt_and_hash(&in_message.ciphertext)?;
     if !plaintext.is_empty() {
         return Err(Error::InvalidHandshake);
@@ -388,6 +603,16 @@ fn finish_response(noise: &mut Noise, in_message: &NoiseMessage) -> Result<Respo
     )
     .map_err(|_| Error::InvalidHandshake)?;
     noise.mix_key(ee_ecdh_bytes.as_slice());
+    let kem_ciphertext = encapsulate_to_initiator(noise, in_message)?;
+    if let Some(initiator_static_pub) = initiator_static_pub {
+        let se_ecdh_bytes =
+            crypto_wrapper::p256_scalar_mult(&ephemeral_priv, &initiator_static_pub)
+                .map_err(|_| Error::InvalidHandshake)?;
+        noise.mix_key(se_ecdh_bytes.as_slice());
+    }
+    if let Some(psk) = psk {
+        noise.mix_key_and_hash(psk);
+    }
 
     let response_ciphertext = noise.encrypt_and_hash(&[]);
 
@@ -398,6 +623,49 @@ fn finish_response(noise: &mut Noise, in_message: &NoiseMessage) -> Result<Respo
         response: NoiseMessage {
             ciphertext: response_ciphertext,
             ephemeral_public_key: ephemeral_pub_key_bytes.to_vec(),
+            static_public_key: vec![],
+            kem_public_key: vec![],
+            kem_ciphertext,
         },
+        peer_static_public_key: initiator_static_pub,
     })
 }
+
+// Creates the responder's state for `handshake_type`. The hybrid variant is
+// used if the initiator offered it by sending an ML-KEM encapsulation key.
+fn responder_noise(handshake_type: HandshakeType, in_message: &NoiseMessage) -> Noise {
+    if in_message.kem_public_key.is_empty() {
+        Noise::new(handshake_type)
+    } else {
+        Noise::new_hybrid(handshake_type)
+    }
+}
+
+// Mixes the initiator's ephemeral keys into the handshake: `e`, followed by
+// `e1` in hybrid handshakes.
+fn mix_initiator_ephemeral_keys(noise: &mut Noise, in_message: &NoiseMessage) {
+    noise.mix_hash(in_message.ephemeral_public_key.as_slice());
+    noise.mix_key(in_message.ephemeral_public_key.as_slice());
+    if !in_message.kem_public_key.is_empty() {
+        noise.mix_hash(in_message.kem_public_key.as_slice());
+    }
+}
+
+// Encapsulates a shared secret to the initiator's ML-KEM key and mixes it into
+// the handshake (`ekem1`), returning the ciphertext to send back. Returns an
+// empty ciphertext for classical handshakes.
+fn encapsulate_to_initiator(
+    noise: &mut Noise,
+    in_message: &NoiseMessage,
+) -> Result<Vec<u8>, Error> {
+    if in_message.kem_public_key.is_empty() {
+        return Ok(vec![]);
+    }
+    let (kem_ciphertext, kem_shared_secret) =
+        crypto_wrapper::ml_kem_768_encapsulate(&in_message.kem_public_key)
+            .map_err(|_| Error::KemFailed)?;
+    let kem_shared_secret = Zeroizing::new(kem_shared_secret);
+    noise.mix_hash(&kem_ciphertext);
+    noise.mix_key(kem_shared_secret.as_slice());
+    Ok(kem_ciphertext)
+}
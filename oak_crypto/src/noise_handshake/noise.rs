@@ -21,9 +21,27 @@ use crate::noise_handshake::{crypto_wrapper, error::Error};
 
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum HandshakeType {
-    Nk, // https://noiseexplorer.com/patterns/NK/
-    Nn, // https://noiseexplorer.com/patterns/NN/
-    Kk, // https://noiseexplorer.com/patterns/KK/
+    Nk,     // https://noiseexplorer.com/patterns/NK/
+    Nn,     // https://noiseexplorer.com/patterns/NN/
+    Kk,     // https://noiseexplorer.com/patterns/KK/
+    Ik,     // https://noiseexplorer.com/patterns/IK/
+    Xx,     // https://noiseexplorer.com/patterns/XX/
+    IkPsk2, // https://noiseexplorer.com/patterns/IKpsk2/
+    XxPsk3, // https://noiseexplorer.com/patterns/XXpsk3/
+}
+
+impl HandshakeType {
+    fn pattern_name(&self) -> &'static [u8] {
+        match self {
+            HandshakeType::Nk => b"NK",
+            HandshakeType::Nn => b"NN",
+            HandshakeType::Kk => b"KK",
+            HandshakeType::Ik => b"IK",
+            HandshakeType::Xx => b"XX",
+            HandshakeType::IkPsk2 => b"IKpsk2",
+            HandshakeType::XxPsk3 => b"XXpsk3",
+        }
+    }
 }
 
 // Helper to generate 2 keys.
@@ -49,13 +67,30 @@ pub struct Noise {
 
 impl Noise {
     pub fn new(handshake_type: HandshakeType) -> Self {
-        let mut chaining_key_in = [0; SYMMETRIC_KEY_LEN];
-        let protocol_name = match handshake_type {
-            HandshakeType::Nk => b"Noise_NK_P256_AESGCM_SHA256",
-            HandshakeType::Nn => b"Noise_NN_P256_AESGCM_SHA256",
-            HandshakeType::Kk => b"Noise_KK_P256_AESGCM_SHA256",
+        Self::with_protocol_name(
+            &[b"Noise_", handshake_type.pattern_name(), b"_P256_AESGCM_SHA256"].concat(),
+        )
+    }
+
+    /// Creates the state for the hybrid variant of `handshake_type`, which
+    /// combines the ephemeral P-256 key exchange with an ML-KEM-768 key
+    /// encapsulation, following the naming of the Noise HFS extension.
+    pub fn new_hybrid(handshake_type: HandshakeType) -> Self {
+        Self::with_protocol_name(
+            &[b"Noise_", handshake_type.pattern_name(), b"hfs_P256+MLKEM768_AESGCM_SHA256"]
+                .concat(),
+        )
+    }
+
+    fn with_protocol_name(protocol_name: &[u8]) -> Self {
+        // See https://www.noiseprotocol.org/noise.html#the-handshakestate-object
+        let chaining_key_in = if protocol_name.len() <= SYMMETRIC_KEY_LEN {
+            let mut chaining_key_in = [0; SYMMETRIC_KEY_LEN];
+            chaining_key_in[..protocol_name.len()].copy_from_slice(protocol_name);
+            chaining_key_in
+        } else {
+            crypto_wrapper::sha256(protocol_name)
         };
-        chaining_key_in[..protocol_name.len()].copy_from_slice(protocol_name);
         Noise {
             chaining_key: chaining_key_in.into(),
             h: chaining_key_in,
@@ -76,7 +111,6 @@ impl Noise {
         self.initialize_key(&derived_keys.1);
     }
 
-    #[cfg(test)]
     pub fn mix_key_and_hash(&mut self, ikm: &[u8]) {
         // See https://www.noiseprotocol.org/noise.html#the-symmetricstate-object
         let mut output = [0; SYMMETRIC_KEY_LEN * 3];
@@ -165,6 +199,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn hybrid_protocol_name_is_hashed() {
+        // The protocol name is longer than the hash output, so it's hashed
+        // rather than padded.
+        let noise = Noise::new_hybrid(HandshakeType::Nn);
+        assert_eq!(
+            noise.handshake_hash(),
+            crypto_wrapper::sha256(b"Noise_NNhfs_P256+MLKEM768_AESGCM_SHA256")
+        );
+    }
+
     #[test]
     fn mix_hash_point() {
         let x962_point: [u8; 65] = [
@@ -0,0 +1,143 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use alloc::{vec, vec::Vec};
+
+use crate::noise_handshake::error::Error;
+
+/// Counters of the nonces checked by a [`ReplayWindow`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReplayWindowStats {
+    /// Nonces of messages that were successfully decrypted.
+    pub accepted: u64,
+    /// Nonces rejected because a message with the same nonce had already been
+    /// decrypted.
+    pub duplicated: u64,
+    /// Nonces rejected because they were older than the window.
+    pub too_old: u64,
+    /// Nonces that left the window without a message being decrypted with
+    /// them, i.e. messages that were lost or arrived too late.
+    pub dropped: u64,
+}
+
+/// Sliding window of the nonces received by an
+/// [`UnorderedCrypter`](crate::noise_handshake::UnorderedCrypter), which
+/// rejects replayed messages while tolerating dropped and reordered ones.
+///
+/// As in DTLS (RFC 6347, section 4.1.2.6), the window spans the `size` nonces
+/// up to the highest nonce received so far, and a bitmap records which of them
+/// have been received. Nonces below the window are rejected, since it is no
+/// longer known whether they have been received.
+///
+/// A nonce is [`check`](Self::check)ed before the message is decrypted, but
+/// only [`accept`](Self::accept)ed once the message has been authenticated, so
+/// that forged messages can't move the window.
+pub struct ReplayWindow {
+    size: u32,
+    /// The highest nonce accepted so far, or 0 if none has been accepted.
+    highest: u32,
+    /// Bit `n % size` is set if nonce `n` within the window has been accepted.
+    bitmap: Vec<u64>,
+    stats: ReplayWindowStats,
+}
+
+impl ReplayWindow {
+    pub fn new(size: u32) -> Self {
+        Self {
+            size,
+            highest: 0,
+            bitmap: vec![0u64; size.div_ceil(u64::BITS) as usize],
+            stats: ReplayWindowStats::default(),
+        }
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub fn stats(&self) -> ReplayWindowStats {
+        self.stats
+    }
+
+    /// Returns the lowest nonce that is still within the window. Nonces start
+    /// at 1.
+    fn lowest(&self) -> u32 {
+        if self.highest > self.size {
+            self.highest - self.size + 1
+        } else {
+            1
+        }
+    }
+
+    fn slot(&self, nonce: u32) -> (usize, u64) {
+        let index = nonce % self.size;
+        ((index / u64::BITS) as usize, 1u64 << (index % u64::BITS))
+    }
+
+    fn is_received(&self, nonce: u32) -> bool {
+        let (word, bit) = self.slot(nonce);
+        self.bitmap[word] & bit != 0
+    }
+
+    fn set_received(&mut self, nonce: u32, received: bool) {
+        let (word, bit) = self.slot(nonce);
+        if received {
+            self.bitmap[word] |= bit;
+        } else {
+            self.bitmap[word] &= !bit;
+        }
+    }
+
+    /// Checks whether a message with `nonce` may be decrypted, without updating
+    /// the window.
+    pub fn check(&mut self, nonce: u32) -> Result<(), Error> {
+        if nonce < self.lowest() || (self.size == 0 && nonce <= self.highest) {
+            self.stats.too_old += 1;
+            return Err(Error::InvalidNonce);
+        }
+        if nonce <= self.highest && self.is_received(nonce) {
+            self.stats.duplicated += 1;
+            return Err(Error::ReplayedNonce);
+        }
+        Ok(())
+    }
+
+    /// Records that a message with `nonce` has been decrypted. The nonce must
+    /// have passed [`check`](Self::check).
+    pub fn accept(&mut self, nonce: u32) {
+        self.stats.accepted += 1;
+        if self.size == 0 {
+            self.stats.dropped += u64::from(nonce - self.highest - 1);
+            self.highest = nonce;
+            return;
+        }
+        if nonce > self.highest {
+            // Nonces up to `last_leaving` leave the window as it slides.
+            if let Some(last_leaving) = nonce.checked_sub(self.size) {
+                for leaving in self.lowest()..=last_leaving.min(self.highest) {
+                    if !self.is_received(leaving) {
+                        self.stats.dropped += 1;
+                    }
+                    self.set_received(leaving, false);
+                }
+                // Skipped nonces that don't even fit in the new window.
+                self.stats.dropped += u64::from(last_leaving.saturating_sub(self.highest));
+            }
+            self.highest = nonce;
+        }
+        self.set_received(nonce, true);
+    }
+}
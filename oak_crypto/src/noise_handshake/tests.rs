@@ -17,7 +17,12 @@ use alloc::{boxed::Box, vec};
 
 use crate::{
     identity_key::{IdentityKey, IdentityKeyHandle},
-    noise_handshake::{client::HandshakeInitiator, respond_kk, respond_nk, respond_nn},
+    noise_handshake::{
+        client::HandshakeInitiator, error::Error, respond_ik, respond_kk, respond_nk, respond_nn,
+        respond_xx, Nonce, OrderedCrypter, ReplayWindow, ReplayWindowStats, UnorderedCrypter,
+        MAX_SEQUENCE, ML_KEM_768_CIPHERTEXT_LEN, ML_KEM_768_ENCAPSULATION_KEY_LEN, P256_X962_LEN,
+        SYMMETRIC_KEY_LEN,
+    },
 };
 
 #[test]
@@ -113,3 +118,410 @@ fn process_nn_handshake() {
         assert_eq!(message, &plaintext);
     }
 }
+
+fn assert_crypters_match(
+    client_crypter: &mut OrderedCrypter,
+    enclave_crypter: &mut OrderedCrypter,
+) {
+    let test_messages = vec![vec![1u8, 2u8, 3u8, 4u8], vec![4u8, 3u8, 2u8, 1u8], vec![]];
+
+    // Client -> Enclave encrypt+decrypt
+    for message in &test_messages {
+        let ciphertext = client_crypter.encrypt(message).unwrap();
+        let plaintext = enclave_crypter.decrypt(&ciphertext).unwrap();
+        assert_eq!(message, &plaintext);
+    }
+
+    // Enclave -> Client encrypt+decrypt
+    for message in &test_messages {
+        let ciphertext = enclave_crypter.encrypt(message).unwrap();
+        let plaintext = client_crypter.decrypt(&ciphertext).unwrap();
+        assert_eq!(message, &plaintext);
+    }
+}
+
+fn process_ik_handshake_with_psk(psk: Option<[u8; 32]>) {
+    let identity_priv = IdentityKey::generate();
+    let identity_pub_bytes = identity_priv.get_public_key().unwrap();
+    let init_priv: Box<dyn IdentityKeyHandle> = Box::new(IdentityKey::generate());
+    let init_pub = init_priv.get_public_key().unwrap();
+    let mut initiator =
+        HandshakeInitiator::new_ik(identity_pub_bytes.try_into().unwrap(), init_priv, psk);
+    let message = initiator.build_initial_message().unwrap();
+    let handshake_response = respond_ik(&identity_priv, psk.as_ref(), &message).unwrap();
+    assert_eq!(handshake_response.peer_static_public_key.map(|key| key.to_vec()), Some(init_pub));
+    let mut enclave_crypter = handshake_response.crypter;
+
+    let (client_hash, mut client_crypter) =
+        initiator.process_response(&handshake_response.response).unwrap();
+    assert_eq!(&client_hash, &handshake_response.handshake_hash);
+    assert!(initiator.take_final_message().is_none());
+
+    assert_crypters_match(&mut client_crypter, &mut enclave_crypter);
+}
+
+#[test]
+fn process_ik_handshake() {
+    process_ik_handshake_with_psk(None);
+}
+
+#[test]
+fn process_ik_psk2_handshake() {
+    process_ik_handshake_with_psk(Some([7u8; 32]));
+}
+
+fn process_xx_handshake_with_psk(psk: Option<[u8; 32]>) {
+    let identity_priv = IdentityKey::generate();
+    let identity_pub_bytes = identity_priv.get_public_key().unwrap();
+    let init_priv: Box<dyn IdentityKeyHandle> = Box::new(IdentityKey::generate());
+    let init_pub = init_priv.get_public_key().unwrap();
+    let mut initiator = HandshakeInitiator::new_xx(init_priv, psk);
+    let message = initiator.build_initial_message().unwrap();
+    let pending_response = respond_xx(&identity_priv, psk.as_ref(), &message).unwrap();
+    let enclave_hash = pending_response.handshake_hash;
+
+    let (client_hash, mut client_crypter) =
+        initiator.process_response(&pending_response.response).unwrap();
+    assert_eq!(&client_hash, &enclave_hash);
+    assert_eq!(
+        initiator.peer_static_public_key().map(|key| key.to_vec()),
+        Some(identity_pub_bytes)
+    );
+
+    let final_message = initiator.take_final_message().expect("no final message");
+    let (mut enclave_crypter, peer_static_public_key) =
+        pending_response.finish(&final_message).unwrap();
+    assert_eq!(peer_static_public_key.to_vec(), init_pub);
+
+    assert_crypters_match(&mut client_crypter, &mut enclave_crypter);
+}
+
+#[test]
+fn process_xx_handshake() {
+    process_xx_handshake_with_psk(None);
+}
+
+#[test]
+fn process_xx_psk3_handshake() {
+    process_xx_handshake_with_psk(Some([7u8; 32]));
+}
+
+#[test]
+fn ik_handshake_fails_with_wrong_responder_key() {
+    let identity_priv = IdentityKey::generate();
+    let other_identity_pub_bytes = IdentityKey::generate().get_public_key().unwrap();
+    let mut initiator = HandshakeInitiator::new_ik(
+        other_identity_pub_bytes.try_into().unwrap(),
+        Box::new(IdentityKey::generate()),
+        None,
+    );
+    let message = initiator.build_initial_message().unwrap();
+    assert_eq!(respond_ik(&identity_priv, None, &message).err(), Some(Error::DecryptFailed));
+}
+
+#[test]
+fn ik_psk2_handshake_fails_with_mismatched_psk() {
+    let identity_priv = IdentityKey::generate();
+    let identity_pub_bytes = identity_priv.get_public_key().unwrap();
+    let mut initiator = HandshakeInitiator::new_ik(
+        identity_pub_bytes.try_into().unwrap(),
+        Box::new(IdentityKey::generate()),
+        Some([7u8; 32]),
+    );
+    let message = initiator.build_initial_message().unwrap();
+    let handshake_response = respond_ik(&identity_priv, Some(&[8u8; 32]), &message).unwrap();
+    assert_eq!(
+        initiator.process_response(&handshake_response.response).err(),
+        Some(Error::DecryptFailed)
+    );
+}
+
+#[test]
+fn xx_psk3_handshake_fails_with_mismatched_psk() {
+    let identity_priv = IdentityKey::generate();
+    let mut initiator =
+        HandshakeInitiator::new_xx(Box::new(IdentityKey::generate()), Some([7u8; 32]));
+    let message = initiator.build_initial_message().unwrap();
+    let pending_response = respond_xx(&identity_priv, Some(&[8u8; 32]), &message).unwrap();
+    assert!(initiator.process_response(&pending_response.response).is_ok());
+    let final_message = initiator.take_final_message().expect("no final message");
+    assert_eq!(pending_response.finish(&final_message).err(), Some(Error::DecryptFailed));
+}
+
+#[test]
+fn handshake_fails_with_mismatched_patterns() {
+    let identity_priv = IdentityKey::generate();
+    let identity_pub_bytes = identity_priv.get_public_key().unwrap();
+
+    // The pattern is part of the protocol name, so the responder can't decrypt
+    // the payload of a message created for a different pattern.
+    let mut initiator = HandshakeInitiator::new_xx(Box::new(IdentityKey::generate()), None);
+    let message = initiator.build_initial_message().unwrap();
+    assert_eq!(respond_nn(&message).err(), Some(Error::DecryptFailed));
+
+    let mut initiator = HandshakeInitiator::new_xx(Box::new(IdentityKey::generate()), None);
+    let message = initiator.build_initial_message().unwrap();
+    assert_eq!(
+        respond_xx(&identity_priv, Some(&[7u8; 32]), &message).err(),
+        Some(Error::DecryptFailed)
+    );
+
+    let mut initiator = HandshakeInitiator::new_ik(
+        identity_pub_bytes.as_slice().try_into().unwrap(),
+        Box::new(IdentityKey::generate()),
+        None,
+    );
+    let message = initiator.build_initial_message().unwrap();
+    assert_eq!(respond_nk(&identity_priv, &message).err(), Some(Error::DecryptFailed));
+}
+
+#[test]
+fn process_hybrid_handshakes() {
+    let identity_priv = IdentityKey::generate();
+    let identity_pub_bytes: [u8; P256_X962_LEN] =
+        identity_priv.get_public_key().unwrap().try_into().unwrap();
+
+    let mut initiator = HandshakeInitiator::new_nn().with_hybrid_kem();
+    let message = initiator.build_initial_message().unwrap();
+    assert_eq!(message.kem_public_key.len(), ML_KEM_768_ENCAPSULATION_KEY_LEN);
+    let handshake_response = respond_nn(&message).unwrap();
+    assert_eq!(handshake_response.response.kem_ciphertext.len(), ML_KEM_768_CIPHERTEXT_LEN);
+    let (client_hash, mut client_crypter) =
+        initiator.process_response(&handshake_response.response).unwrap();
+    assert_eq!(&client_hash, &handshake_response.handshake_hash);
+    assert_crypters_match(&mut client_crypter, &mut { handshake_response.crypter });
+
+    let mut initiator = HandshakeInitiator::new_nk(&identity_pub_bytes).with_hybrid_kem();
+    let message = initiator.build_initial_message().unwrap();
+    let handshake_response = respond_nk(&identity_priv, &message).unwrap();
+    let (client_hash, mut client_crypter) =
+        initiator.process_response(&handshake_response.response).unwrap();
+    assert_eq!(&client_hash, &handshake_response.handshake_hash);
+    assert_crypters_match(&mut client_crypter, &mut { handshake_response.crypter });
+
+    let init_priv: Box<dyn IdentityKeyHandle> = Box::new(IdentityKey::generate());
+    let init_pub = init_priv.get_public_key().unwrap();
+    let mut initiator = HandshakeInitiator::new_kk(identity_pub_bytes, init_priv).with_hybrid_kem();
+    let message = initiator.build_initial_message().unwrap();
+    let handshake_response = respond_kk(&identity_priv, &init_pub, &message).unwrap();
+    let (client_hash, mut client_crypter) =
+        initiator.process_response(&handshake_response.response).unwrap();
+    assert_eq!(&client_hash, &handshake_response.handshake_hash);
+    assert_crypters_match(&mut client_crypter, &mut { handshake_response.crypter });
+
+    let mut initiator = HandshakeInitiator::new_ik(
+        identity_pub_bytes,
+        Box::new(IdentityKey::generate()),
+        Some([7u8; 32]),
+    )
+    .with_hybrid_kem();
+    let message = initiator.build_initial_message().unwrap();
+    let handshake_response = respond_ik(&identity_priv, Some(&[7u8; 32]), &message).unwrap();
+    let (client_hash, mut client_crypter) =
+        initiator.process_response(&handshake_response.response).unwrap();
+    assert_eq!(&client_hash, &handshake_response.handshake_hash);
+    assert_crypters_match(&mut client_crypter, &mut { handshake_response.crypter });
+
+    let mut initiator =
+        HandshakeInitiator::new_xx(Box::new(IdentityKey::generate()), None).with_hybrid_kem();
+    let message = initiator.build_initial_message().unwrap();
+    let pending_response = respond_xx(&identity_priv, None, &message).unwrap();
+    let (client_hash, mut client_crypter) =
+        initiator.process_response(&pending_response.response).unwrap();
+    assert_eq!(&client_hash, &pending_response.handshake_hash);
+    let final_message = initiator.take_final_message().expect("no final message");
+    let (mut enclave_crypter, _) = pending_response.finish(&final_message).unwrap();
+    assert_crypters_match(&mut client_crypter, &mut enclave_crypter);
+}
+
+#[test]
+fn hybrid_handshake_fails_without_kem_ciphertext() {
+    let mut initiator = HandshakeInitiator::new_nn().with_hybrid_kem();
+    let message = initiator.build_initial_message().unwrap();
+    let mut handshake_response = respond_nn(&message).unwrap();
+    handshake_response.response.kem_ciphertext.clear();
+    assert_eq!(
+        initiator.process_response(&handshake_response.response).err(),
+        Some(Error::InvalidHandshake)
+    );
+}
+
+#[test]
+fn hybrid_handshake_fails_with_modified_kem_ciphertext() {
+    let mut initiator = HandshakeInitiator::new_nn().with_hybrid_kem();
+    let message = initiator.build_initial_message().unwrap();
+    let mut handshake_response = respond_nn(&message).unwrap();
+    handshake_response.response.kem_ciphertext[0] ^= 1;
+    // ML-KEM decapsulation implicitly rejects the ciphertext, so the initiator
+    // derives a different key and can't decrypt the payload.
+    assert_eq!(
+        initiator.process_response(&handshake_response.response).err(),
+        Some(Error::DecryptFailed)
+    );
+}
+
+#[test]
+fn classical_handshake_fails_with_unexpected_kem_ciphertext() {
+    let mut initiator = HandshakeInitiator::new_nn();
+    let message = initiator.build_initial_message().unwrap();
+    let mut handshake_response = respond_nn(&message).unwrap();
+    handshake_response.response.kem_ciphertext = vec![0; ML_KEM_768_CIPHERTEXT_LEN];
+    assert_eq!(
+        initiator.process_response(&handshake_response.response).err(),
+        Some(Error::InvalidHandshake)
+    );
+}
+
+#[test]
+fn hybrid_handshake_fails_if_kem_public_key_is_stripped() {
+    let mut initiator = HandshakeInitiator::new_nn().with_hybrid_kem();
+    let mut message = initiator.build_initial_message().unwrap();
+    // Without the encapsulation key, the responder falls back to the classical
+    // protocol name, which doesn't match the initiator's.
+    message.kem_public_key.clear();
+    assert_eq!(respond_nn(&message).err(), Some(Error::DecryptFailed));
+}
+
+fn create_crypter_pair() -> (OrderedCrypter, OrderedCrypter) {
+    let mut initiator = HandshakeInitiator::new_nn();
+    let message = initiator.build_initial_message().unwrap();
+    let handshake_response = respond_nn(&message).unwrap();
+    let (_, client_crypter) = initiator.process_response(&handshake_response.response).unwrap();
+    (client_crypter, handshake_response.crypter)
+}
+
+#[test]
+fn ordered_crypter_authenticates_associated_data() {
+    let (mut client_crypter, mut enclave_crypter) = create_crypter_pair();
+
+    let ciphertext = client_crypter.encrypt_with_aad(&[1u8, 2u8], b"first").unwrap();
+    assert_eq!(enclave_crypter.decrypt_with_aad(&ciphertext, b"first").unwrap(), vec![1u8, 2u8]);
+
+    let ciphertext = client_crypter.encrypt_with_aad(&[3u8, 4u8], b"second").unwrap();
+    assert_eq!(enclave_crypter.decrypt(&ciphertext), Err(Error::DecryptFailed));
+}
+
+#[test]
+fn ordered_crypter_fails_past_max_sequence() {
+    let (mut client_crypter, mut enclave_crypter) = create_crypter_pair();
+    client_crypter.write_nonce = Nonce { nonce: MAX_SEQUENCE };
+    enclave_crypter.read_nonce = Nonce { nonce: MAX_SEQUENCE };
+
+    let ciphertext = client_crypter.encrypt(&[1u8, 2u8]).unwrap();
+    assert_eq!(enclave_crypter.decrypt(&ciphertext).unwrap(), vec![1u8, 2u8]);
+    assert_eq!(client_crypter.remaining_write_messages(), 0);
+    assert_eq!(client_crypter.encrypt(&[3u8, 4u8]), Err(Error::DecryptFailed));
+}
+
+#[test]
+fn ordered_crypter_rekey_continues_past_max_sequence() {
+    let (mut client_crypter, mut enclave_crypter) = create_crypter_pair();
+    client_crypter.write_nonce = Nonce { nonce: MAX_SEQUENCE };
+    enclave_crypter.read_nonce = Nonce { nonce: MAX_SEQUENCE };
+
+    let ciphertext = client_crypter.encrypt(&[1u8, 2u8]).unwrap();
+    assert_eq!(enclave_crypter.decrypt(&ciphertext).unwrap(), vec![1u8, 2u8]);
+
+    client_crypter.rekey_write();
+    enclave_crypter.rekey_read();
+    assert_eq!(client_crypter.remaining_write_messages(), MAX_SEQUENCE + 1);
+
+    for message in [vec![3u8, 4u8], vec![5u8, 6u8], vec![]] {
+        let ciphertext = client_crypter.encrypt(&message).unwrap();
+        assert_eq!(enclave_crypter.decrypt(&ciphertext).unwrap(), message);
+    }
+}
+
+#[test]
+fn ordered_crypter_rekey_changes_keys() {
+    let (mut client_crypter, mut enclave_crypter) = create_crypter_pair();
+
+    client_crypter.rekey_write();
+    let ciphertext = client_crypter.encrypt(&[1u8, 2u8]).unwrap();
+    // The peer hasn't rekeyed yet, so it can't decrypt the message.
+    assert_eq!(enclave_crypter.decrypt(&ciphertext), Err(Error::DecryptFailed));
+
+    // Rekeying only affects a single direction.
+    let ciphertext = enclave_crypter.encrypt(&[3u8, 4u8]).unwrap();
+    assert_eq!(client_crypter.decrypt(&ciphertext).unwrap(), vec![3u8, 4u8]);
+}
+
+#[test]
+fn replay_window_accepts_reordered_nonces() {
+    let mut window = ReplayWindow::new(4);
+    for nonce in [2, 1, 5, 3] {
+        assert_eq!(window.check(nonce), Ok(()));
+        window.accept(nonce);
+    }
+    assert_eq!(window.check(2), Err(Error::ReplayedNonce));
+    // Nonce 1 is behind the window [2, 5].
+    assert_eq!(window.check(1), Err(Error::InvalidNonce));
+    assert_eq!(window.check(4), Ok(()));
+    assert_eq!(
+        window.stats(),
+        ReplayWindowStats { accepted: 4, duplicated: 1, too_old: 1, dropped: 0 }
+    );
+}
+
+#[test]
+fn replay_window_counts_nonces_that_left_the_window() {
+    let mut window = ReplayWindow::new(3);
+    window.accept(1);
+    window.accept(3);
+    // Nonces 2 and 4 leave the window [5, 7] without being received.
+    window.accept(7);
+    assert_eq!(window.stats().dropped, 2);
+    // Nonces 5 and 6 leave the window, 8 to 97 are skipped past it.
+    window.accept(100);
+    assert_eq!(window.stats().dropped, 2 + 2 + 90);
+    assert_eq!(window.check(98), Ok(()));
+    assert_eq!(window.check(97), Err(Error::InvalidNonce));
+}
+
+#[test]
+fn replay_window_of_size_0_only_accepts_increasing_nonces() {
+    let mut window = ReplayWindow::new(0);
+    window.accept(3);
+    assert_eq!(window.check(3), Err(Error::InvalidNonce));
+    assert_eq!(window.check(2), Err(Error::InvalidNonce));
+    assert_eq!(window.check(4), Ok(()));
+    assert_eq!(window.stats().dropped, 2);
+}
+
+#[test]
+fn replay_window_spans_multiple_bitmap_words() {
+    let mut window = ReplayWindow::new(200);
+    // Even nonces in the window [101, 300], highest first.
+    for nonce in (102..=300).rev().step_by(2) {
+        assert_eq!(window.check(nonce), Ok(()));
+        window.accept(nonce);
+    }
+    for nonce in (102..=300).step_by(2) {
+        assert_eq!(window.check(nonce), Err(Error::ReplayedNonce));
+    }
+    for nonce in (101..=299).step_by(2) {
+        assert_eq!(window.check(nonce), Ok(()));
+    }
+    assert_eq!(window.check(100), Err(Error::InvalidNonce));
+}
+
+#[test]
+fn unordered_crypter_ignores_forged_messages() {
+    let key_1 = &[42u8; SYMMETRIC_KEY_LEN];
+    let key_2 = &[52u8; SYMMETRIC_KEY_LEN];
+    let mut sender = UnorderedCrypter::new(key_1, key_2, 4);
+    let mut receiver = UnorderedCrypter::new(key_2, key_1, 4);
+
+    let (ciphertext, nonce) = sender.encrypt(&[1u8, 2u8]).unwrap();
+    let nonce: [u8; 12] = nonce.try_into().unwrap();
+    let mut forged_nonce = [0u8; 12];
+    forged_nonce[11] = 100;
+    assert_eq!(receiver.decrypt(&forged_nonce, &ciphertext), Err(Error::DecryptFailed));
+    // The forged message didn't move the window.
+    assert_eq!(receiver.decrypt(&nonce, &ciphertext), Ok(vec![1u8, 2u8]));
+    assert_eq!(receiver.decrypt(&nonce, &ciphertext), Err(Error::ReplayedNonce));
+    assert_eq!(
+        receiver.replay_window_stats(),
+        ReplayWindowStats { accepted: 1, duplicated: 1, too_old: 0, dropped: 0 }
+    );
+}
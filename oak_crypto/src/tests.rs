@@ -98,6 +98,13 @@ fn test_hpke() {
     assert_eq!(TEST_RESPONSE_MESSAGE, decrypted_response);
 }
 
+#[test]
+fn test_encryption_key_public_key() {
+    let (encryption_key, encryption_public_key) = generate_encryption_key_pair();
+
+    assert_eq!(encryption_key.public_key(), encryption_public_key);
+}
+
 #[test]
 fn test_encryptor() {
     let (encryption_key, encryption_public_key) = generate_encryption_key_pair();
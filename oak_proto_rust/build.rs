@@ -57,6 +57,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let mut needed_types = HashSet::new();
     for t in [
+        "oak.session.v1.AlertMessage",
         "oak.session.v1.AttestRequest",
         "oak.session.v1.AttestResponse",
         "oak.session.v1.NoiseHandshakeMessage",
@@ -65,6 +66,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         "oak.session.v1.HandshakeResponse",
         "oak.session.v1.EncryptedMessage",
         "oak.session.v1.PlaintextMessage",
+        "oak.session.v1.RekeyMessage",
+        "oak.session.v1.SessionAlert",
         "oak.session.v1.SessionRequest",
         "oak.session.v1.SessionRequestWithSessionId",
         "oak.session.v1.SessionResponse",
@@ -116,6 +119,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     config.btree_map(["."]);
 
     config.bytes(vec![
+        ".oak.containers.ContainerBundle".to_string(),
         ".oak.containers.GetImageResponse".to_string(),
         ".oak.functions.LookupDataEntry".to_string(),
         ".oak.functions.ExtendNextLookupDataRequest".to_string(),
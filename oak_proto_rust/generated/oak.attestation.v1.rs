@@ -94,6 +94,20 @@ pub struct SignedEndorsement {
     #[prost(bytes = "vec", tag = "3")]
     #[serde(with = "crate::base64data")]
     pub rekor_log_entry: ::prost::alloc::vec::Vec<u8>,
+    /// A Sigstore bundle (v0.1 to v0.3) in its JSON serialization, as produced by
+    /// current sigstore tooling. The bundle carries either a message signature
+    /// over `endorsement.serialized` or a DSSE envelope whose payload is
+    /// `endorsement.serialized`, along with the transparency log entry. When set,
+    /// `signature` and `rekor_log_entry` are ignored.
+    #[prost(bytes = "vec", tag = "4")]
+    #[serde(with = "crate::base64data")]
+    pub sigstore_bundle: ::prost::alloc::vec::Vec<u8>,
+    /// An RFC 3161 timestamp over `signature.raw`, as ASN.1 DER encoded
+    /// TimeStampToken or TimeStampResp. Empty if there is no timestamp. Bundles
+    /// carry their timestamps in the timestamp verification data instead.
+    #[prost(bytes = "vec", tag = "5")]
+    #[serde(with = "crate::base64data")]
+    pub rfc3161_timestamp: ::prost::alloc::vec::Vec<u8>,
 }
 /// Tink endorsement consists of a single Tink signature over the endorsed
 /// evidence.
@@ -160,6 +174,18 @@ pub struct RootLayerEndorsements {
     /// Endorsement of the Stage0 binary.
     #[prost(message, optional, tag = "2")]
     pub stage0: ::core::option::Option<TransparentReleaseEndorsement>,
+    /// DER-encoded certificate revocation lists (CRLs) for the AMD SEV-SNP
+    /// certificate chain of `tee_certificate`. See
+    /// `AmdSevSnpEndorsement.crls` for details.
+    #[prost(bytes = "vec", repeated, tag = "3")]
+    #[serde(with = "crate::base64data::repeated_bytes")]
+    pub crls: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// The DER-encoded AMD SEV VLEK signing key (ASVK) certificate. Only needed
+    /// when the attestation report is signed by a VLEK. See
+    /// `AmdSevSnpEndorsement.asvk_certificate` for details.
+    #[prost(bytes = "vec", tag = "4")]
+    #[serde(with = "crate::base64data")]
+    pub asvk_certificate: ::prost::alloc::vec::Vec<u8>,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -250,6 +276,22 @@ pub struct AmdSevSnpEndorsement {
     /// AMD SEV Key (ASK) are long-lived.
     #[prost(bytes = "vec", tag = "1")]
     pub tee_certificate: ::prost::alloc::vec::Vec<u8>,
+    /// DER-encoded certificate revocation lists (CRLs) for the AMD SEV-SNP
+    /// certificate chain of `tee_certificate`. AMD publishes a CRL for each
+    /// product at <https://kdsintf.amd.com/vcek/v1/${PRODUCT_NAME}/crl> that is
+    /// issued by the ARK and lists the revoked ASKs. CRLs issued by the ASK,
+    /// which list revoked VCEKs, are checked as well. CRLs issued by other
+    /// certificates are ignored, so the CRLs of all products can be supplied.
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub crls: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// The DER-encoded AMD SEV VLEK signing key (ASVK) certificate. Only needed
+    /// when the attestation report is signed by a Versioned Loaded Endorsement
+    /// Key (VLEK), in which case `tee_certificate` holds the VLEK certificate.
+    /// AMD publishes the ASVK for each product as part of
+    /// <https://kdsintf.amd.com/vlek/v1/${PRODUCT_NAME}/cert_chain> and the
+    /// certificate is verified against the bundled AMD Root Key (ARK).
+    #[prost(bytes = "vec", tag = "3")]
+    pub asvk_certificate: ::prost::alloc::vec::Vec<u8>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FirmwareEndorsement {
@@ -869,6 +911,38 @@ pub struct TdxTcbSvn {
     #[prost(uint32, tag = "16")]
     pub svn_15: u32,
 }
+/// The kind of key that signed an AMD SEV-SNP attestation report.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum AmdSigningKey {
+    Unspecified = 0,
+    /// Versioned Chip Endorsement Key, derived from chip-unique secrets.
+    Vcek = 1,
+    /// Versioned Loaded Endorsement Key, provisioned by AMD to a cloud provider.
+    Vlek = 2,
+}
+impl AmdSigningKey {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "AMD_SIGNING_KEY_UNSPECIFIED",
+            Self::Vcek => "AMD_SIGNING_KEY_VCEK",
+            Self::Vlek => "AMD_SIGNING_KEY_VLEK",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "AMD_SIGNING_KEY_UNSPECIFIED" => Some(Self::Unspecified),
+            "AMD_SIGNING_KEY_VCEK" => Some(Self::Vcek),
+            "AMD_SIGNING_KEY_VLEK" => Some(Self::Vlek),
+            _ => None,
+        }
+    }
+}
 #[derive(Clone, Copy, PartialEq, ::prost::Message)]
 pub struct SkipVerification {}
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -912,6 +986,12 @@ pub struct VerifyingKeySet {
     /// verification will be performed at all.
     #[prost(message, optional, tag = "2")]
     pub signed_timestamp: ::core::option::Option<TimestampReferenceValue>,
+    /// If set, the Rekor log entry must carry an inclusion proof. The proof is
+    /// verified against the root hash of the checkpoint (signed tree head) in the
+    /// log entry, and the checkpoint must be signed by one of the keys. Like
+    /// `signed_timestamp`, this only applies to Rekor log entries.
+    #[prost(bool, tag = "3")]
+    pub require_inclusion_proof: bool,
 }
 /// Reference values that control how the endorsement is verified.
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -978,6 +1058,49 @@ pub struct EndorsementReferenceValue {
     /// Verifies the Rekor log entry, if present and requested.
     #[prost(message, optional, tag = "5")]
     pub rekor: ::core::option::Option<VerifyingKeyReferenceValue>,
+    /// Verifies that the checkpoint in the Rekor log entry is consistent with a
+    /// checkpoint previously observed by the client. This implies verification
+    /// of the inclusion proof. Ignored if Rekor verification is skipped.
+    #[prost(message, optional, tag = "6")]
+    pub rekor_checkpoint_consistency: ::core::option::Option<
+        CheckpointConsistencyReferenceValue,
+    >,
+    /// Verifies the RFC 3161 timestamp over the endorsement signature. If set, a
+    /// timestamp issued by one of the trusted timestamp authorities is required,
+    /// which anchors the signing time without a transparency log. Can be used
+    /// alongside or instead of Rekor verification.
+    #[prost(message, optional, tag = "7")]
+    pub timestamp_authority: ::core::option::Option<TimestampAuthorityReferenceValue>,
+}
+/// A checkpoint (signed tree head) previously observed by the client, together
+/// with a proof that the log only appended entries between this checkpoint and
+/// the checkpoint in a Rekor log entry. This allows clients to detect a log
+/// which presents different views to different clients.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CheckpointConsistencyReferenceValue {
+    /// The checkpoint in signed note format, as returned by the log. It must be
+    /// signed by one of the Rekor keys.
+    #[prost(string, tag = "1")]
+    pub checkpoint: ::prost::alloc::string::String,
+    /// The consistency proof between the smaller and the larger of the two
+    /// checkpoints, as specified in RFC 9162. Empty if both have the same size.
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub proof_hashes: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+/// Trusted timestamp authorities (TSAs) for RFC 3161 timestamps. Only ECDSA
+/// P-256 keys with SHA2-256 signatures are supported throughout the chain.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TimestampAuthorityReferenceValue {
+    /// The ASN.1 DER encoded root certificates of the trusted TSAs.
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub root_certificates: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// ASN.1 DER encoded intermediate and signing certificates, for timestamps
+    /// which do not embed the certificates needed to build the chain.
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub intermediate_certificates: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// Verifies the time in the timestamp, if set.
+    #[prost(message, optional, tag = "3")]
+    pub signed_timestamp: ::core::option::Option<TimestampReferenceValue>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct BinaryReferenceValue {
@@ -1172,7 +1295,7 @@ pub mod tdx_tcb_svn_reference_value {
     }
 }
 /// Collection of reference values for an AMD SEV-SNP hardware root.
-/// NEXT_ID: 10
+/// NEXT_ID: 13
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AmdSevReferenceValues {
     /// Deprecated: Minimum accepted versions of all TCB components.
@@ -1209,6 +1332,19 @@ pub struct AmdSevReferenceValues {
     /// Versioned Chip Endorsement Key (VCEK) certificate.
     #[prost(bool, tag = "9")]
     pub check_vcek_cert_expiry: bool,
+    /// Whether verification fails if no CRL issued by the ARK is supplied with the
+    /// endorsements. CRLs that are supplied are always checked, and verification
+    /// fails if they revoke the ASK or the VCEK.
+    #[prost(bool, tag = "10")]
+    pub require_crls: bool,
+    /// Whether verification fails if a supplied CRL is past its next update time.
+    /// Otherwise, stale CRLs are still used to check for revoked certificates.
+    #[prost(bool, tag = "11")]
+    pub reject_stale_crls: bool,
+    /// The kinds of keys that are allowed to sign the attestation report. If
+    /// empty, only reports signed by a VCEK are accepted.
+    #[prost(enumeration = "AmdSigningKey", repeated, tag = "12")]
+    pub allowed_signing_keys: ::prost::alloc::vec::Vec<i32>,
 }
 /// Collection of reference values for an Intel TDX Attestation Quote.
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -1318,8 +1454,17 @@ pub struct OakContainersReferenceValues {
     pub kernel_layer: ::core::option::Option<KernelLayerReferenceValues>,
     #[prost(message, optional, tag = "3")]
     pub system_layer: ::core::option::Option<SystemLayerReferenceValues>,
+    /// Verifies the last container of the event log, which holds the instance
+    /// keys.
     #[prost(message, optional, tag = "4")]
     pub container_layer: ::core::option::Option<ContainerLayerReferenceValues>,
+    /// Verifies the containers that are measured before the last one when the
+    /// enclave runs multiple containers, in the order of the event log. Only
+    /// supported by the policy-based verifiers.
+    #[prost(message, repeated, tag = "5")]
+    pub additional_container_layers: ::prost::alloc::vec::Vec<
+        ContainerLayerReferenceValues,
+    >,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CbReferenceValues {
@@ -1506,7 +1651,7 @@ pub struct KernelExpectedValues {
     #[prost(message, optional, tag = "2")]
     pub setup_data: ::core::option::Option<ExpectedDigests>,
 }
-/// NEXT_ID: 8
+/// NEXT_ID: 11
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AmdSevExpectedValues {
     #[prost(message, optional, tag = "1")]
@@ -1532,8 +1677,19 @@ pub struct AmdSevExpectedValues {
     /// Versioned Chip Endorsement Key (VCEK) certificate.
     #[prost(bool, tag = "7")]
     pub check_vcek_cert_expiry: bool,
+    /// Whether verification fails if no CRL issued by the ARK is supplied with the
+    /// endorsements.
+    #[prost(bool, tag = "8")]
+    pub require_crls: bool,
+    /// Whether verification fails if a supplied CRL is past its next update time.
+    #[prost(bool, tag = "9")]
+    pub reject_stale_crls: bool,
+    /// The kinds of keys that are allowed to sign the attestation report. If
+    /// empty, only reports signed by a VCEK are accepted.
+    #[prost(enumeration = "AmdSigningKey", repeated, tag = "10")]
+    pub allowed_signing_keys: ::prost::alloc::vec::Vec<i32>,
 }
-#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct IntelTdxExpectedValues {
     /// Minimum acceptable SVNs for the TEE TCB.
     #[prost(message, optional, tag = "1")]
@@ -1541,6 +1697,10 @@ pub struct IntelTdxExpectedValues {
     /// If true, will skip the check that the TEE is not in debug mode.
     #[prost(bool, tag = "2")]
     pub allow_debug: bool,
+    /// Expected values for the stage0 firmware, which is measured into MRTD. If
+    /// unset, MRTD is not checked.
+    #[prost(message, optional, tag = "3")]
+    pub stage0_expected: ::core::option::Option<ExpectedDigests>,
 }
 #[derive(Clone, Copy, PartialEq, ::prost::Message)]
 pub struct InsecureExpectedValues {}
@@ -1840,7 +2000,7 @@ pub mod root_layer_data {
     }
 }
 /// Values extracted from an AMD SEV-SNP attestation report.
-/// NEXT_ID: 9
+/// NEXT_ID: 10
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AmdAttestationReport {
     /// The custom bytes that were passed to the report when it was requested.
@@ -1879,6 +2039,9 @@ pub struct AmdAttestationReport {
     /// was generated.
     #[prost(uint32, tag = "6")]
     pub vmpl: u32,
+    /// The kind of key that signed the attestation report.
+    #[prost(enumeration = "AmdSigningKey", tag = "9")]
+    pub signing_key: i32,
 }
 /// Values extracted from an Intel TDX attestation report.
 /// NEXT_ID: 5
@@ -1955,7 +2118,9 @@ pub struct SystemLayerData {
 /// Bundle used in Oak Containers.
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ContainerLayerData {
-    /// Measurement RawDigest of the container bundle.
+    /// Measurement RawDigest of the container bundle. For containers started
+    /// from an OCI image layout this is the digest of the image manifest, i.e.
+    /// the image digest.
     #[prost(message, optional, tag = "1")]
     pub bundle: ::core::option::Option<super::super::RawDigest>,
     /// Measurement RawDigest of the configuration used by the container.
@@ -7,6 +7,88 @@ pub struct GetImageResponse {
     #[prost(bytes = "bytes", tag = "1")]
     pub image_chunk: ::prost::bytes::Bytes,
 }
+/// Containers that the orchestrator runs in the enclave. The launcher serves
+/// the manifest through `GetContainerBundleManifest` without the bundles, which
+/// the orchestrator fetches one at a time with `GetManifestContainerBundle`.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ContainerBundleManifest {
+    /// Containers are measured into the event log in the given order, one event
+    /// per container, and started in the same order. The instance keys are bound
+    /// to the event of the last container, so only the last container can access
+    /// the orchestrator IPC socket. Other containers communicate with it through
+    /// the shared directory.
+    #[prost(message, repeated, tag = "1")]
+    pub containers: ::prost::alloc::vec::Vec<ContainerBundle>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ContainerBundle {
+    /// Name of the container, which must be unique within the manifest. It is
+    /// used as the container ID and may only contain ASCII alphanumeric
+    /// characters, `-` and `_`.
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    /// Tarball of the container in the given format. For OCI runtime bundles the
+    /// digest of the tarball is measured into the event of the container, for OCI
+    /// image layouts the digest of the image manifest. Left empty by
+    /// `GetContainerBundleManifest`.
+    #[prost(bytes = "bytes", tag = "2")]
+    pub bundle: ::prost::bytes::Bytes,
+    /// The restart policy and resource limits only affect the availability of the
+    /// enclave and are not measured.
+    #[prost(enumeration = "RestartPolicy", tag = "3")]
+    pub restart_policy: i32,
+    #[prost(message, optional, tag = "4")]
+    pub resource_limits: ::core::option::Option<ResourceLimits>,
+    #[prost(enumeration = "ContainerBundleFormat", tag = "5")]
+    pub format: i32,
+}
+/// Limits applied to the resources of the OCI runtime spec of a container. Zero
+/// values leave the corresponding limit of the bundle unchanged.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ResourceLimits {
+    /// Memory limit in bytes.
+    #[prost(uint64, tag = "1")]
+    pub memory_bytes: u64,
+    /// CPU limit in thousandths of a CPU.
+    #[prost(uint64, tag = "2")]
+    pub cpu_millis: u64,
+    /// Maximum number of processes.
+    #[prost(int64, tag = "3")]
+    pub pids: i64,
+}
+/// Status of a container, reported by the orchestrator whenever the container
+/// exits. The launcher receives it over the untrusted host channel, so it is not
+/// attested there; the application can get the status from the orchestrator
+/// with `Orchestrator.GetContainerStatuses` instead.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ContainerStatus {
+    /// Name of the container in the `ContainerBundleManifest`.
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    /// Number of times the container has been restarted so far.
+    #[prost(uint32, tag = "2")]
+    pub restart_count: u32,
+    /// Exit code of the last run of the container. Unset if the container was
+    /// terminated by a signal.
+    #[prost(int32, optional, tag = "3")]
+    pub last_exit_code: ::core::option::Option<i32>,
+    /// Whether the container is restarted according to its restart policy, as
+    /// opposed to staying stopped.
+    #[prost(bool, tag = "4")]
+    pub restarting: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetManifestContainerBundleRequest {
+    /// Name of the container in the `ContainerBundleManifest`.
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetContainerStatusesResponse {
+    /// Status of every container that exited at least once, ordered by name.
+    #[prost(message, repeated, tag = "1")]
+    pub statuses: ::prost::alloc::vec::Vec<ContainerStatus>,
+}
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetApplicationConfigResponse {
     /// Arbitrary config that the container can retrieve from the orchestrator.
@@ -19,3 +101,78 @@ pub struct SendAttestationEvidenceRequest {
     #[prost(message, optional, tag = "2")]
     pub dice_evidence: ::core::option::Option<super::attestation::v1::Evidence>,
 }
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ContainerBundleFormat {
+    /// Same as `OCI_RUNTIME_BUNDLE`.
+    Unspecified = 0,
+    /// Root filesystem and `config.json` of an OCI runtime bundle.
+    OciRuntimeBundle = 1,
+    /// OCI image layout with a single image manifest, or a single manifest for
+    /// the linux/amd64 platform. The runtime bundle is created from the image
+    /// layers and config when the container is started.
+    OciImageLayout = 2,
+}
+impl ContainerBundleFormat {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "CONTAINER_BUNDLE_FORMAT_UNSPECIFIED",
+            Self::OciRuntimeBundle => "OCI_RUNTIME_BUNDLE",
+            Self::OciImageLayout => "OCI_IMAGE_LAYOUT",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "CONTAINER_BUNDLE_FORMAT_UNSPECIFIED" => Some(Self::Unspecified),
+            "OCI_RUNTIME_BUNDLE" => Some(Self::OciRuntimeBundle),
+            "OCI_IMAGE_LAYOUT" => Some(Self::OciImageLayout),
+            _ => None,
+        }
+    }
+}
+/// Defines what happens when a container exits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum RestartPolicy {
+    /// Same as `TERMINATE`.
+    Unspecified = 0,
+    /// The enclave shuts down when the container exits.
+    Terminate = 1,
+    /// The container is not restarted, other containers keep running.
+    Never = 2,
+    /// The container is restarted if it exits with an error.
+    OnFailure = 3,
+    /// The container is restarted whenever it exits.
+    Always = 4,
+}
+impl RestartPolicy {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "RESTART_POLICY_UNSPECIFIED",
+            Self::Terminate => "TERMINATE",
+            Self::Never => "NEVER",
+            Self::OnFailure => "ON_FAILURE",
+            Self::Always => "ALWAYS",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "RESTART_POLICY_UNSPECIFIED" => Some(Self::Unspecified),
+            "TERMINATE" => Some(Self::Terminate),
+            "NEVER" => Some(Self::Never),
+            "ON_FAILURE" => Some(Self::OnFailure),
+            "ALWAYS" => Some(Self::Always),
+            _ => None,
+        }
+    }
+}
@@ -48,6 +48,13 @@ pub struct DeriveSessionKeysRequest {
     /// keys.
     #[prost(bytes = "vec", tag = "2")]
     pub serialized_encapsulated_public_key: ::prost::alloc::vec::Vec<u8>,
+    /// Epoch of the group key that the client encrypted to. If unset, epoch 0 is
+    /// used, whose public key is the one in the attestation evidence of the leader
+    /// enclave. Keys of later epochs are not attested, so clients that encrypt to
+    /// them must learn them otherwise and set the epoch. Ignored for instance
+    /// keys.
+    #[prost(uint32, optional, tag = "3")]
+    pub group_key_epoch: ::core::option::Option<u32>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DeriveSessionKeysResponse {
@@ -83,6 +90,86 @@ pub struct BindSessionResponse {
     #[prost(message, optional, tag = "1")]
     pub signature: ::core::option::Option<super::super::crypto::v1::Signature>,
 }
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SealRequest {
+    #[prost(enumeration = "KeyOrigin", tag = "1")]
+    pub key_origin: i32,
+    #[prost(bytes = "vec", tag = "2")]
+    pub plaintext: ::prost::alloc::vec::Vec<u8>,
+    /// Policy that a container must satisfy to unseal the data. The binary and
+    /// the configuration are either matched against exact digests or checked via
+    /// endorsements signed by a given key. If unset, the data can only be
+    /// unsealed by a container with the same digests as the sealing one.
+    #[prost(message, optional, tag = "3")]
+    pub policy: ::core::option::Option<
+        super::super::attestation::v1::ContainerLayerReferenceValues,
+    >,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SealResponse {
+    #[prost(message, optional, tag = "1")]
+    pub sealed_blob: ::core::option::Option<SealedBlob>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnsealRequest {
+    #[prost(message, optional, tag = "1")]
+    pub sealed_blob: ::core::option::Option<SealedBlob>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnsealResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub plaintext: ::prost::alloc::vec::Vec<u8>,
+}
+/// Data encrypted by the Orchestrator, which can be stored outside of the
+/// enclave.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SealedBlob {
+    #[prost(message, optional, tag = "1")]
+    pub header: ::core::option::Option<SealedBlobHeader>,
+    /// Data encrypted to the key selected by the header. The serialized header
+    /// is used as associated data, so it can't be changed without making the
+    /// decryption fail.
+    #[prost(message, optional, tag = "2")]
+    pub encrypted_data: ::core::option::Option<
+        super::super::crypto::v1::EncryptedRequest,
+    >,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SealedBlobHeader {
+    /// Key that the data is encrypted to. Instance keys are generated on every
+    /// start of the enclave, so data sealed with them only outlives restarts of
+    /// the container. Data sealed with the group key can be unsealed by every
+    /// enclave of the group, including ones started later.
+    #[prost(enumeration = "KeyOrigin", tag = "1")]
+    pub key_origin: i32,
+    /// Policy that a container must satisfy to unseal the data.
+    #[prost(message, optional, tag = "2")]
+    pub policy: ::core::option::Option<
+        super::super::attestation::v1::ContainerLayerReferenceValues,
+    >,
+    /// Epoch of the group key that the data is encrypted to. Data sealed with the
+    /// group key is always encrypted to the current epoch, and can be unsealed
+    /// until the epoch falls out of its grace window after a rotation. Unused for
+    /// instance keys.
+    #[prost(uint32, tag = "3")]
+    pub group_key_epoch: u32,
+}
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct RotateGroupKeysRequest {
+    /// How long the group key of the current epoch remains usable for decryption
+    /// after the rotation.
+    #[prost(message, optional, tag = "1")]
+    pub grace_period: ::core::option::Option<::prost_types::Duration>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RotateGroupKeysResponse {
+    /// Epoch of the newly generated group key.
+    #[prost(uint32, tag = "1")]
+    pub epoch: u32,
+    /// Serialized group encryption public key of the new epoch.
+    #[prost(bytes = "vec", tag = "2")]
+    pub group_encryption_public_key: ::prost::alloc::vec::Vec<u8>,
+}
 /// Choice between a key generated by the enclave instance and the key
 /// distributed to the enclave group with Key Provisioning.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
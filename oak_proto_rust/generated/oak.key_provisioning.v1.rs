@@ -7,6 +7,39 @@ pub struct GroupKeys {
     pub encrypted_encryption_private_key: ::core::option::Option<
         super::super::crypto::v1::EncryptedRequest,
     >,
+    /// Epoch of the current group encryption key in
+    /// `encrypted_encryption_private_key`. Epochs start at 0 when the leader
+    /// enclave generates the group keys and are incremented on every rotation.
+    #[prost(uint32, tag = "2")]
+    pub epoch: u32,
+    /// Group encryption keys from previous epochs that are still within their
+    /// grace window, so that data encrypted under them remains decryptable.
+    #[prost(message, repeated, tag = "3")]
+    pub previous_epochs: ::prost::alloc::vec::Vec<GroupKeyEpoch>,
+    /// Random identifier that the leader enclave generates on startup. A
+    /// restarted leader enclave generates new group keys starting at epoch 0, so
+    /// followers that see a new identifier replace all of their group keys
+    /// instead of rejecting the lower epoch. Keys of the previous leader enclave
+    /// can't be rotated anymore and are dropped.
+    #[prost(bytes = "vec", tag = "4")]
+    pub keyring_id: ::prost::alloc::vec::Vec<u8>,
+}
+/// Group encryption key from a specific epoch.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GroupKeyEpoch {
+    #[prost(uint32, tag = "1")]
+    pub epoch: u32,
+    /// Encryption private key that was encrypted with HPKE using the encryption
+    /// public key provided in the endorsed evidence.
+    #[prost(message, optional, tag = "2")]
+    pub encrypted_encryption_private_key: ::core::option::Option<
+        super::super::crypto::v1::EncryptedRequest,
+    >,
+    /// Remaining time, as of the response, for which the key of a previous epoch
+    /// can be used for decryption. Followers drop the key once it has elapsed,
+    /// even if they can't reach the leader enclave anymore.
+    #[prost(message, optional, tag = "3")]
+    pub remaining_grace_period: ::core::option::Option<::prost_types::Duration>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetGroupKeysRequest {
@@ -74,6 +74,12 @@ pub struct AttestRequest {
         ::prost::alloc::string::String,
         super::super::attestation::v1::Assertion,
     >,
+    /// Opaque resumption ticket issued by the server in a previous session. If
+    /// set, the client asks the server to skip the attestation and to restore the
+    /// attestation results of that session instead.
+    #[prost(bytes = "vec", tag = "3")]
+    #[serde(with = "crate::base64data")]
+    pub resumption_ticket: ::prost::alloc::vec::Vec<u8>,
 }
 /// Response message for the remote attestation.
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -97,6 +103,11 @@ pub struct AttestResponse {
         ::prost::alloc::string::String,
         super::super::attestation::v1::Assertion,
     >,
+    /// Whether the server accepted the resumption ticket provided in the
+    /// `AttestRequest`. If the ticket was rejected, the server performs a regular
+    /// attestation instead.
+    #[prost(bool, tag = "3")]
+    pub resumption_accepted: bool,
 }
 /// Noise handshake message containing fields for all handshake patterns.
 /// <<http://www.noiseprotocol.org/noise.html#handshake-patterns>>
@@ -123,6 +134,16 @@ pub struct NoiseHandshakeMessage {
     #[prost(bytes = "vec", tag = "3")]
     #[serde(with = "crate::base64data")]
     pub ciphertext: ::prost::alloc::vec::Vec<u8>,
+    /// ML-KEM-768 encapsulation key, sent by the initiator to offer a hybrid
+    /// post-quantum handshake.
+    #[prost(bytes = "vec", tag = "4")]
+    #[serde(with = "crate::base64data")]
+    pub kem_public_key: ::prost::alloc::vec::Vec<u8>,
+    /// ML-KEM-768 ciphertext encapsulating a shared secret to `kem_public_key`,
+    /// sent by the responder if it accepts the hybrid handshake.
+    #[prost(bytes = "vec", tag = "5")]
+    #[serde(with = "crate::base64data")]
+    pub kem_ciphertext: ::prost::alloc::vec::Vec<u8>,
 }
 /// Message that binds the Noise session (and optionally other data) to the
 /// Attestation Evidence.
@@ -219,6 +240,177 @@ pub struct EncryptedMessage {
     #[serde(with = "crate::base64data::option_bytes")]
     pub nonce: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
 }
+/// Message sent after a secure session is established to signal that the sender
+/// switches to a new traffic key for all the messages it sends afterwards.
+///
+/// The new key is derived from the previous one, so rekeying doesn't require any
+/// additional key exchange.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RekeyMessage {
+    /// A single byte encrypted with the previous traffic key of the sender. It
+    /// authenticates the rekey message and fixes its position in the sequence of
+    /// encrypted messages.
+    ///
+    /// The byte is 1 if the peer is asked to also switch its own traffic key, and
+    /// 0 otherwise. The peer responds to a request with a `RekeyMessage` of its
+    /// own, which doesn't request a rekey.
+    #[prost(message, optional, tag = "1")]
+    pub confirmation: ::core::option::Option<EncryptedMessage>,
+}
+/// Resumption ticket issued by the server over an open session. It is only
+/// ever transmitted encrypted with the session keys.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NewResumptionTicket {
+    /// Opaque ticket to be presented to the server in the `AttestRequest` of a
+    /// new session.
+    #[prost(bytes = "vec", tag = "1")]
+    pub ticket: ::prost::alloc::vec::Vec<u8>,
+    /// Secret shared with the server, used to bind the new session to the one the
+    /// ticket was issued in.
+    #[prost(bytes = "vec", tag = "2")]
+    pub resumption_secret: ::prost::alloc::vec::Vec<u8>,
+}
+/// State of a session sealed into a resumption ticket by the server. It is only
+/// ever transmitted encrypted with the ticket key of the server.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ResumptionTicketContents {
+    /// Secret shared with the client, see `NewResumptionTicket`.
+    #[prost(bytes = "vec", tag = "1")]
+    pub resumption_secret: ::prost::alloc::vec::Vec<u8>,
+    /// Expiration time of the ticket, in milliseconds since the Unix epoch.
+    #[prost(int64, tag = "2")]
+    pub expires_at_unix_millis: i64,
+    /// The attestation evidence supplied by the client in the session the ticket
+    /// was issued in.
+    #[prost(btree_map = "string, message", tag = "3")]
+    pub evidence: ::prost::alloc::collections::BTreeMap<
+        ::prost::alloc::string::String,
+        EndorsedEvidence,
+    >,
+    #[prost(btree_map = "string, message", tag = "4")]
+    pub evidence_bindings: ::prost::alloc::collections::BTreeMap<
+        ::prost::alloc::string::String,
+        SessionBinding,
+    >,
+    #[prost(btree_map = "string, message", tag = "5")]
+    pub assertions: ::prost::alloc::collections::BTreeMap<
+        ::prost::alloc::string::String,
+        super::super::attestation::v1::Assertion,
+    >,
+    #[prost(btree_map = "string, message", tag = "6")]
+    pub assertion_bindings: ::prost::alloc::collections::BTreeMap<
+        ::prost::alloc::string::String,
+        SessionBinding,
+    >,
+    /// The handshake hash of the session the ticket was issued in.
+    #[prost(bytes = "vec", tag = "7")]
+    pub handshake_hash: ::prost::alloc::vec::Vec<u8>,
+}
+/// Reason for ending a session, sent to the peer in an `AlertMessage`.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SessionAlert {
+    #[prost(enumeration = "session_alert::Reason", tag = "1")]
+    pub reason: i32,
+    /// IDs of the verifiers that rejected the evidence of the peer, set for
+    /// `ATTESTATION_VERIFICATION_FAILED`.
+    #[prost(string, repeated, tag = "2")]
+    pub failed_verifier_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Human-readable description of the failure, for debugging purposes.
+    #[prost(string, tag = "3")]
+    pub description: ::prost::alloc::string::String,
+}
+/// Nested message and enum types in `SessionAlert`.
+pub mod session_alert {
+    #[derive(
+        Clone,
+        Copy,
+        Debug,
+        PartialEq,
+        Eq,
+        Hash,
+        PartialOrd,
+        Ord,
+        ::prost::Enumeration
+    )]
+    #[repr(i32)]
+    pub enum Reason {
+        Unspecified = 0,
+        /// Orderly shutdown: the sender won't send any further messages.
+        CloseNotify = 1,
+        /// The sender couldn't verify the attestation evidence of the peer.
+        AttestationVerificationFailed = 2,
+        /// The handshake, or the verification of the session bindings, failed.
+        HandshakeFailed = 3,
+        /// The sender won't accept further messages encrypted with the current
+        /// traffic key of the peer.
+        RekeyRequired = 4,
+        /// The sender received a message that it didn't expect or couldn't process.
+        ProtocolError = 5,
+    }
+    impl Reason {
+        /// String value of the enum field names used in the ProtoBuf definition.
+        ///
+        /// The values are not transformed in any way and thus are considered stable
+        /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+        pub fn as_str_name(&self) -> &'static str {
+            match self {
+                Self::Unspecified => "REASON_UNSPECIFIED",
+                Self::CloseNotify => "CLOSE_NOTIFY",
+                Self::AttestationVerificationFailed => "ATTESTATION_VERIFICATION_FAILED",
+                Self::HandshakeFailed => "HANDSHAKE_FAILED",
+                Self::RekeyRequired => "REKEY_REQUIRED",
+                Self::ProtocolError => "PROTOCOL_ERROR",
+            }
+        }
+        /// Creates an enum from field names used in the ProtoBuf definition.
+        pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+            match value {
+                "REASON_UNSPECIFIED" => Some(Self::Unspecified),
+                "CLOSE_NOTIFY" => Some(Self::CloseNotify),
+                "ATTESTATION_VERIFICATION_FAILED" => {
+                    Some(Self::AttestationVerificationFailed)
+                }
+                "HANDSHAKE_FAILED" => Some(Self::HandshakeFailed),
+                "REKEY_REQUIRED" => Some(Self::RekeyRequired),
+                "PROTOCOL_ERROR" => Some(Self::ProtocolError),
+                _ => None,
+            }
+        }
+    }
+}
+/// Message that ends the session. Every alert other than `CLOSE_NOTIFY` is
+/// fatal, and the sender discards the session after sending it.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AlertMessage {
+    #[prost(oneof = "alert_message::Alert", tags = "1, 2")]
+    #[serde(flatten)]
+    pub alert: ::core::option::Option<alert_message::Alert>,
+}
+/// Nested message and enum types in `AlertMessage`.
+pub mod alert_message {
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Alert {
+        /// Alert sent before the session is open, when there are no session keys
+        /// to protect it yet.
+        #[prost(message, tag = "1")]
+        PlaintextAlert(super::SessionAlert),
+        /// A `SessionAlert` encrypted with the session keys, used once the session
+        /// is open. This prevents the network from forging a close of the session.
+        #[prost(message, tag = "2")]
+        EncryptedAlert(super::EncryptedMessage),
+    }
+}
 /// Message with decrypted content (not to be transmitted over the wire).
 #[derive(serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -237,7 +429,7 @@ pub struct PlaintextMessage {
 #[serde(default)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SessionRequest {
-    #[prost(oneof = "session_request::Request", tags = "1, 2, 3")]
+    #[prost(oneof = "session_request::Request", tags = "1, 2, 3, 4, 5")]
     #[serde(flatten)]
     pub request: ::core::option::Option<session_request::Request>,
 }
@@ -253,6 +445,10 @@ pub mod session_request {
         HandshakeRequest(super::HandshakeRequest),
         #[prost(message, tag = "3")]
         EncryptedMessage(super::EncryptedMessage),
+        #[prost(message, tag = "4")]
+        RekeyMessage(super::RekeyMessage),
+        #[prost(message, tag = "5")]
+        AlertMessage(super::AlertMessage),
     }
 }
 /// Wrapper around SessionRequest that is used in cases where it is necessary to
@@ -287,7 +483,7 @@ pub struct SessionRequestWithSessionId {
 #[serde(default)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SessionResponse {
-    #[prost(oneof = "session_response::Response", tags = "1, 2, 3")]
+    #[prost(oneof = "session_response::Response", tags = "1, 2, 3, 4, 5, 6")]
     #[serde(flatten)]
     pub response: ::core::option::Option<session_response::Response>,
 }
@@ -303,6 +499,13 @@ pub mod session_response {
         HandshakeResponse(super::HandshakeResponse),
         #[prost(message, tag = "3")]
         EncryptedMessage(super::EncryptedMessage),
+        #[prost(message, tag = "4")]
+        RekeyMessage(super::RekeyMessage),
+        /// A `NewResumptionTicket` encrypted with the session keys.
+        #[prost(message, tag = "5")]
+        ResumptionTicket(super::EncryptedMessage),
+        #[prost(message, tag = "6")]
+        AlertMessage(super::AlertMessage),
     }
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -8,9 +8,43 @@ pub struct ExecuteRequest {
     /// Wasm module bytecode to execute
     #[prost(bytes = "vec", tag = "2")]
     pub wasm_module: ::prost::alloc::vec::Vec<u8>,
+    /// Digest of a Wasm module previously sent to the service, used instead of
+    /// `wasm_module` when the latter is empty. If both are set, the digest must
+    /// match `wasm_module`. Only SHA2-256 is supported.
+    #[prost(message, optional, tag = "3")]
+    pub wasm_module_digest: ::core::option::Option<super::RawDigest>,
+}
+/// Message of a streamed execution request.
+///
+/// The Wasm module must be sent first, either as one or more `wasm_module_chunk`
+/// messages or as a single `wasm_module_digest` message, followed by any number
+/// of `input_data_chunk` messages. The total sizes of the Wasm module and of the
+/// input data are limited by the service.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExecuteStreamRequest {
+    #[prost(oneof = "execute_stream_request::Request", tags = "1, 2, 3")]
+    pub request: ::core::option::Option<execute_stream_request::Request>,
+}
+/// Nested message and enum types in `ExecuteStreamRequest`.
+pub mod execute_stream_request {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Request {
+        /// Chunk of the Wasm module bytecode to execute
+        #[prost(bytes, tag = "1")]
+        WasmModuleChunk(::prost::alloc::vec::Vec<u8>),
+        /// Digest of a Wasm module previously sent to the service. Only SHA2-256 is
+        /// supported.
+        #[prost(message, tag = "2")]
+        WasmModuleDigest(super::super::RawDigest),
+        /// Chunk of the binary data to operate on
+        #[prost(bytes, tag = "3")]
+        InputDataChunk(::prost::alloc::vec::Vec<u8>),
+    }
 }
 /// Manifest containing digests of all inputs and outputs for verification
-/// TODO: b/448322106 - Consider using in-toto or SLSA instead of this protobuf.
+///
+/// See `ManifestFormat` for an in-toto alternative, currently only available for
+/// execution pipelines.
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ExecutionManifest {
     /// Digest of the input data
@@ -40,3 +74,152 @@ pub struct ExecuteResponse {
     #[prost(bytes = "vec", tag = "3")]
     pub output_data: ::prost::alloc::vec::Vec<u8>,
 }
+/// Source of (part of) the input of a pipeline step.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct StepInput {
+    #[prost(oneof = "step_input::Source", tags = "1, 2")]
+    pub source: ::core::option::Option<step_input::Source>,
+}
+/// Nested message and enum types in `StepInput`.
+pub mod step_input {
+    #[derive(Clone, Copy, PartialEq, ::prost::Oneof)]
+    pub enum Source {
+        /// The input data of the pipeline. Must be true if set.
+        #[prost(bool, tag = "1")]
+        PipelineInput(bool),
+        /// The output of an earlier step, given by its index in the pipeline.
+        #[prost(uint32, tag = "2")]
+        StepOutput(u32),
+    }
+}
+/// Input passed to a pipeline step that has more than one input. The entries are
+/// in the order of the inputs of the step.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StepInputData {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub inputs: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+/// A single step of an execution pipeline.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PipelineStep {
+    /// Wasm module bytecode to execute
+    #[prost(bytes = "vec", tag = "1")]
+    pub wasm_module: ::prost::alloc::vec::Vec<u8>,
+    /// Digest of a Wasm module previously sent to the service, used instead of
+    /// `wasm_module` when the latter is empty. If both are set, the digest must
+    /// match `wasm_module`. Only SHA2-256 is supported.
+    #[prost(message, optional, tag = "2")]
+    pub wasm_module_digest: ::core::option::Option<super::RawDigest>,
+    /// Sources of the input of the step. If empty, the input is the output of the
+    /// previous step, or the pipeline input for the first step. A single input is
+    /// passed to the Wasm module unchanged, while several inputs are passed as a
+    /// serialized `StepInputData`.
+    #[prost(message, repeated, tag = "3")]
+    pub inputs: ::prost::alloc::vec::Vec<StepInput>,
+}
+/// Request message containing the input data and the Wasm modules to execute.
+///
+/// The steps form a directed acyclic graph: each step consumes the pipeline
+/// input and/or the outputs of earlier steps, as given by its `inputs`. The
+/// output of the pipeline is the output of the last step.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExecutePipelineRequest {
+    /// Binary data passed to the steps that take the pipeline input
+    #[prost(bytes = "vec", tag = "1")]
+    pub input_data: ::prost::alloc::vec::Vec<u8>,
+    /// Steps to execute, in order. Must not be empty.
+    #[prost(message, repeated, tag = "2")]
+    pub steps: ::prost::alloc::vec::Vec<PipelineStep>,
+    /// Format in which the manifest is serialized in the response.
+    #[prost(enumeration = "ManifestFormat", tag = "3")]
+    pub manifest_format: i32,
+}
+/// Manifest of a single step of a pipeline.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PipelineStepManifest {
+    /// Digests of the data passed to the Wasm module, of the Wasm module and of
+    /// its output.
+    #[prost(message, optional, tag = "1")]
+    pub execution: ::core::option::Option<ExecutionManifest>,
+    /// Sources of the input of the step, never empty. The digest of each source is
+    /// the `input_data_digest` of the pipeline or the `output_data_digest` of the
+    /// referenced step.
+    #[prost(message, repeated, tag = "2")]
+    pub inputs: ::prost::alloc::vec::Vec<StepInput>,
+}
+/// Manifest linking the executions of all the steps of a pipeline.
+///
+/// The `inputs` of the steps are the edges of the graph of the pipeline, which
+/// allows a verifier to follow the data from the pipeline input to its final
+/// output through every step.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PipelineManifest {
+    /// Digest of the input data of the pipeline
+    #[prost(message, optional, tag = "1")]
+    pub input_data_digest: ::core::option::Option<super::RawDigest>,
+    /// Manifests of the individual steps, in execution order.
+    #[prost(message, repeated, tag = "2")]
+    pub steps: ::prost::alloc::vec::Vec<PipelineStepManifest>,
+    /// Digest of the output data of the last step
+    #[prost(message, optional, tag = "3")]
+    pub output_data_digest: ::core::option::Option<super::RawDigest>,
+}
+/// Response message containing the pipeline manifest and the raw output of the
+/// last step.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExecutePipelineResponse {
+    /// Serialized manifest, in the format given by `manifest_format`.
+    #[prost(bytes = "vec", tag = "1")]
+    pub serialized_manifest: ::prost::alloc::vec::Vec<u8>,
+    /// Assertions about the serialized manifest, generated by different assertion
+    /// generators. The key is the ID of the assertion generator.
+    #[prost(btree_map = "string, message", tag = "2")]
+    pub assertions: ::prost::alloc::collections::BTreeMap<
+        ::prost::alloc::string::String,
+        super::attestation::v1::Assertion,
+    >,
+    /// Raw output bytes from the last step
+    #[prost(bytes = "vec", tag = "3")]
+    pub output_data: ::prost::alloc::vec::Vec<u8>,
+    /// Format of `serialized_manifest`. Never `MANIFEST_FORMAT_UNSPECIFIED`.
+    #[prost(enumeration = "ManifestFormat", tag = "4")]
+    pub manifest_format: i32,
+}
+/// Serialization format of a pipeline manifest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ManifestFormat {
+    /// Treated as `MANIFEST_FORMAT_PROTO`.
+    Unspecified = 0,
+    /// Binary serialized `google.protobuf.Any` containing the `PipelineManifest`,
+    /// with the type URL `type.googleapis.com/oak.verity.PipelineManifest`. The
+    /// type URL prevents the signed bytes from being mistaken for an
+    /// `ExecutionManifest`.
+    Proto = 1,
+    /// JSON in-toto statement with a SLSA provenance v1 predicate. The subject is
+    /// the pipeline output, and the predicate records the digests of the input,
+    /// output and Wasm module, as well as the input sources, of every step.
+    InToto = 2,
+}
+impl ManifestFormat {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "MANIFEST_FORMAT_UNSPECIFIED",
+            Self::Proto => "MANIFEST_FORMAT_PROTO",
+            Self::InToto => "MANIFEST_FORMAT_IN_TOTO",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "MANIFEST_FORMAT_UNSPECIFIED" => Some(Self::Unspecified),
+            "MANIFEST_FORMAT_PROTO" => Some(Self::Proto),
+            "MANIFEST_FORMAT_IN_TOTO" => Some(Self::InToto),
+            _ => None,
+        }
+    }
+}
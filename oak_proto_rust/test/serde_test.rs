@@ -28,8 +28,7 @@ fn proto_to_json_succeeds() {
     assert!(json_str.is_ok());
     let json_str = json_str.unwrap();
 
-    let expected_str =
-        "{\"ephemeralPublicKey\":\"YWFiYg==\",\"staticPublicKey\":\"\",\"ciphertext\":\"\"}";
+    let expected_str = "{\"ephemeralPublicKey\":\"YWFiYg==\",\"staticPublicKey\":\"\",\"ciphertext\":\"\",\"kemPublicKey\":\"\",\"kemCiphertext\":\"\"}";
     assert_eq!(json_str, expected_str);
 }
 
@@ -20,5 +20,6 @@ pub use oak_containers_agent::metrics::{
 pub use orchestrator_channel::default_orchestrator_channel;
 pub use orchestrator_client::OrchestratorClient;
 pub use orchestrator_crypto_client::{
-    InstanceEncryptionKeyHandle, InstanceSessionBinder, InstanceSigner, Signer,
+    endorsed_container_policy, InstanceEncryptionKeyHandle, InstanceSessionBinder, InstanceSigner,
+    Sealer, Signer,
 };
@@ -15,7 +15,7 @@
 
 use anyhow::Result;
 use oak_grpc::oak::containers::orchestrator_client::OrchestratorClient as GrpcOrchestratorClient;
-use oak_proto_rust::oak::session::v1::EndorsedEvidence;
+use oak_proto_rust::oak::{containers::ContainerStatus, session::v1::EndorsedEvidence};
 
 /// Utility struct used to interface with the Orchestrator.
 #[derive(Clone)]
@@ -51,4 +51,10 @@ impl OrchestratorClient {
     pub async fn get_endorsed_evidence(&mut self) -> Result<EndorsedEvidence> {
         Ok(self.inner.get_endorsed_evidence(()).await?.into_inner())
     }
+
+    /// Retrieves the restart counts and exit codes of the containers that
+    /// exited at least once, as tracked by the Orchestrator.
+    pub async fn get_container_statuses(&mut self) -> Result<Vec<ContainerStatus>> {
+        Ok(self.inner.get_container_statuses(()).await?.into_inner().statuses)
+    }
 }
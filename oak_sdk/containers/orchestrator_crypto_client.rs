@@ -20,7 +20,15 @@ use async_trait::async_trait;
 use oak_crypto::{encryption_key::AsyncEncryptionKeyHandle, hpke::RecipientContext};
 use oak_grpc::oak::containers::v1::orchestrator_crypto_client::OrchestratorCryptoClient as GrpcOrchestratorCryptoClient;
 use oak_proto_rust::oak::{
-    containers::v1::{BindSessionRequest, DeriveSessionKeysRequest, KeyOrigin, SignRequest},
+    attestation::v1::{
+        binary_reference_value, verifying_key_reference_value, BinaryReferenceValue,
+        ClaimReferenceValue, ContainerLayerReferenceValues, EndorsementReferenceValue,
+        SkipVerification, VerifyingKey, VerifyingKeyReferenceValue, VerifyingKeySet,
+    },
+    containers::v1::{
+        BindSessionRequest, DeriveSessionKeysRequest, KeyOrigin, SealRequest, SealedBlob,
+        SignRequest, UnsealRequest,
+    },
     crypto::v1::{SessionKeys, Signature},
 };
 use oak_session::session_binding::SessionBinder;
@@ -48,6 +56,7 @@ impl OrchestratorCryptoClient {
             .derive_session_keys(DeriveSessionKeysRequest {
                 key_origin: key_origin.into(),
                 serialized_encapsulated_public_key: serialized_encapsulated_public_key.to_vec(),
+                group_key_epoch: None,
             })
             .await?
             .into_inner()
@@ -84,6 +93,33 @@ impl OrchestratorCryptoClient {
             .signature
             .context("session binding was not provided by the Orchestrator")
     }
+
+    async fn seal(
+        &self,
+        key_origin: KeyOrigin,
+        plaintext: Vec<u8>,
+        policy: Option<ContainerLayerReferenceValues>,
+    ) -> anyhow::Result<SealedBlob> {
+        self.inner
+            // TODO(#4477): Remove unnecessary copies of the Orchestrator client.
+            .clone()
+            .seal(SealRequest { key_origin: key_origin.into(), plaintext, policy })
+            .await?
+            .into_inner()
+            .sealed_blob
+            .context("sealed blob was not provided by the Orchestrator")
+    }
+
+    async fn unseal(&self, sealed_blob: SealedBlob) -> anyhow::Result<Vec<u8>> {
+        Ok(self
+            .inner
+            // TODO(#4477): Remove unnecessary copies of the Orchestrator client.
+            .clone()
+            .unseal(UnsealRequest { sealed_blob: Some(sealed_blob) })
+            .await?
+            .into_inner()
+            .plaintext)
+    }
 }
 
 pub struct InstanceEncryptionKeyHandle {
@@ -190,3 +226,69 @@ impl oak_crypto::signer::Signer for InstanceSigner {
             .signature
     }
 }
+
+/// Seals data with the Orchestrator, such that it can be stored outside of the
+/// enclave and only be unsealed by containers which satisfy a policy.
+///
+/// Data sealed with [`KeyOrigin::Instance`] can only be unsealed until the
+/// enclave restarts. Data sealed with [`KeyOrigin::Group`] can be unsealed by
+/// every enclave of the group.
+#[derive(Clone)]
+pub struct Sealer {
+    orchestrator_crypto_client: Arc<OrchestratorCryptoClient>,
+    key_origin: KeyOrigin,
+}
+
+impl Sealer {
+    pub fn create(channel: &tonic::transport::channel::Channel, key_origin: KeyOrigin) -> Self {
+        Self {
+            orchestrator_crypto_client: Arc::new(OrchestratorCryptoClient::create(channel)),
+            key_origin,
+        }
+    }
+
+    /// Seals `plaintext` such that it can only be unsealed by containers with
+    /// the same binary and configuration digests as the current one.
+    pub async fn seal(&self, plaintext: &[u8]) -> anyhow::Result<SealedBlob> {
+        self.orchestrator_crypto_client.seal(self.key_origin, plaintext.to_vec(), None).await
+    }
+
+    /// Seals `plaintext` such that it can only be unsealed by containers which
+    /// satisfy `policy`, e.g. one created with [`endorsed_container_policy`].
+    pub async fn seal_with_policy(
+        &self,
+        plaintext: &[u8],
+        policy: ContainerLayerReferenceValues,
+    ) -> anyhow::Result<SealedBlob> {
+        self.orchestrator_crypto_client
+            .seal(self.key_origin, plaintext.to_vec(), Some(policy))
+            .await
+    }
+
+    /// Unseals data sealed with either key origin.
+    pub async fn unseal(&self, sealed_blob: SealedBlob) -> anyhow::Result<Vec<u8>> {
+        self.orchestrator_crypto_client.unseal(sealed_blob).await
+    }
+}
+
+/// Returns a sealing policy which accepts every container binary endorsed by
+/// `endorser_key`, regardless of its configuration. Transparency log entries of
+/// the endorsements are not checked.
+pub fn endorsed_container_policy(endorser_key: VerifyingKey) -> ContainerLayerReferenceValues {
+    let endorsement = EndorsementReferenceValue {
+        endorser: Some(VerifyingKeySet { keys: vec![endorser_key], ..Default::default() }),
+        required_claims: Some(ClaimReferenceValue { claim_types: vec![] }),
+        rekor: Some(VerifyingKeyReferenceValue {
+            r#type: Some(verifying_key_reference_value::Type::Skip(SkipVerification {})),
+        }),
+        ..Default::default()
+    };
+    ContainerLayerReferenceValues {
+        binary: Some(BinaryReferenceValue {
+            r#type: Some(binary_reference_value::Type::Endorsement(endorsement)),
+        }),
+        configuration: Some(BinaryReferenceValue {
+            r#type: Some(binary_reference_value::Type::Skip(SkipVerification {})),
+        }),
+    }
+}
@@ -23,7 +23,7 @@ use oak_crypto::identity_key::{IdentityKey, IdentityKeyHandle};
 use oak_session::{
     attestation::{AttestationState, PeerAttestationVerdict},
     config::HandshakeHandlerConfig,
-    handshake::{ClientHandshakeHandler, HandshakeType, ServerHandshakeHandler},
+    handshake::{ClientHandshakeHandler, HandshakeType, KeyExchange, ServerHandshakeHandler},
     ProtocolEngine,
 };
 fn process_kk_handshake() {
@@ -36,6 +36,8 @@ fn process_kk_handshake() {
             self_static_private_key: Some(responder_identity_key),
             peer_static_public_key: Some(initiator_identity_key.get_public_key().unwrap()),
             session_binders: BTreeMap::new(),
+            pre_shared_key: None,
+            key_exchange: KeyExchange::Classical,
         },
         AttestationState {
             peer_attestation_verdict: PeerAttestationVerdict::AttestationPassed {
@@ -45,6 +47,7 @@ fn process_kk_handshake() {
             self_assertions: BTreeMap::new(),
             peer_session_binding_verifiers: BTreeMap::new(),
             attestation_binding_token: Vec::new(),
+            resumed_session: None,
         },
     )
     .unwrap();
@@ -54,6 +57,8 @@ fn process_kk_handshake() {
             self_static_private_key: Some(initiator_identity_key),
             peer_static_public_key: Some(responder_public_key),
             session_binders: BTreeMap::new(),
+            pre_shared_key: None,
+            key_exchange: KeyExchange::Classical,
         },
         false,
         AttestationState {
@@ -64,6 +69,7 @@ fn process_kk_handshake() {
             self_assertions: BTreeMap::new(),
             peer_session_binding_verifiers: BTreeMap::new(),
             attestation_binding_token: Vec::new(),
+            resumed_session: None,
         },
     );
     do_handshake(client_handshaker, server_handshaker);
@@ -77,6 +83,8 @@ fn process_nk_handshake() {
             self_static_private_key: None,
             peer_static_public_key: Some(identity_key.get_public_key().unwrap()),
             session_binders: BTreeMap::new(),
+            pre_shared_key: None,
+            key_exchange: KeyExchange::Classical,
         },
         AttestationState {
             peer_attestation_verdict: PeerAttestationVerdict::AttestationPassed {
@@ -86,6 +94,7 @@ fn process_nk_handshake() {
             self_assertions: BTreeMap::new(),
             peer_session_binding_verifiers: BTreeMap::new(),
             attestation_binding_token: Vec::new(),
+            resumed_session: None,
         },
     )
     .unwrap();
@@ -95,6 +104,8 @@ fn process_nk_handshake() {
             self_static_private_key: Some(identity_key),
             peer_static_public_key: None,
             session_binders: BTreeMap::new(),
+            pre_shared_key: None,
+            key_exchange: KeyExchange::Classical,
         },
         false,
         AttestationState {
@@ -105,6 +116,7 @@ fn process_nk_handshake() {
             self_assertions: BTreeMap::new(),
             peer_session_binding_verifiers: BTreeMap::new(),
             attestation_binding_token: Vec::new(),
+            resumed_session: None,
         },
     );
     do_handshake(client_handshaker, server_handshaker);
@@ -117,6 +129,8 @@ fn process_nn_handshake() {
             self_static_private_key: None,
             peer_static_public_key: None,
             session_binders: BTreeMap::new(),
+            pre_shared_key: None,
+            key_exchange: KeyExchange::Classical,
         },
         AttestationState {
             peer_attestation_verdict: PeerAttestationVerdict::AttestationPassed {
@@ -126,6 +140,7 @@ fn process_nn_handshake() {
             self_assertions: BTreeMap::new(),
             peer_session_binding_verifiers: BTreeMap::new(),
             attestation_binding_token: Vec::new(),
+            resumed_session: None,
         },
     )
     .unwrap();
@@ -135,6 +150,8 @@ fn process_nn_handshake() {
             self_static_private_key: None,
             peer_static_public_key: None,
             session_binders: BTreeMap::new(),
+            pre_shared_key: None,
+            key_exchange: KeyExchange::Classical,
         },
         false,
         AttestationState {
@@ -145,6 +162,7 @@ fn process_nn_handshake() {
             self_assertions: BTreeMap::new(),
             peer_session_binding_verifiers: BTreeMap::new(),
             attestation_binding_token: Vec::new(),
+            resumed_session: None,
         },
     );
     do_handshake(client_handshaker, server_handshaker);
@@ -0,0 +1,137 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! This module implements the alerts that end a session.
+//!
+//! A party ends a session by sending an [`AlertMessage`] to its peer, carrying
+//! a [`SessionAlert`] with the reason:
+//!
+//! - [`AlertReason::CloseNotify`] is sent by [`Session::close`] for an orderly
+//!   shutdown. It tells the peer that no further messages will follow, so the
+//!   peer can tell a complete stream of messages from one that was cut short by
+//!   the network (see [`Session::is_peer_closed`]).
+//! - Any other reason is fatal. When a session fails locally (e.g. the
+//!   attestation evidence of the peer is rejected, or the handshake fails), the
+//!   method that failed returns an error, and an alert describing the failure
+//!   is queued for the peer. It can be retrieved with `get_outgoing_message` as
+//!   the last message of the session.
+//!
+//! Once the session is open, alerts are encrypted with the session keys and
+//! processed in order with the other encrypted messages, so the network can
+//! neither forge nor reorder them. Before that, there are no keys to protect
+//! them, and alerts are sent in plaintext.
+//!
+//! [`Session::close`]: crate::Session::close
+//! [`Session::is_peer_closed`]: crate::Session::is_peer_closed
+
+use alloc::{string::String, vec::Vec};
+
+use anyhow::{anyhow, Context, Error};
+use oak_crypto::encryptor::Encryptor;
+use oak_proto_rust::oak::session::v1::{alert_message::Alert, AlertMessage};
+pub use oak_proto_rust::oak::session::v1::{session_alert::Reason as AlertReason, SessionAlert};
+use prost::Message;
+
+use crate::{
+    attestation::{PeerAttestationVerdict, VerifierResult},
+    encryptors::{decrypt_message, encrypt_message, MessageKind},
+    verifier::BoundAssertionVerifierResult,
+};
+
+/// Wraps an alert sent before the session is open.
+pub(crate) fn plaintext_alert(alert: SessionAlert) -> AlertMessage {
+    AlertMessage { alert: Some(Alert::PlaintextAlert(alert)) }
+}
+
+/// Encrypts an alert sent once the session is open.
+pub(crate) fn encrypt_alert(
+    encryptor: &mut dyn Encryptor,
+    alert: &SessionAlert,
+) -> Result<AlertMessage, Error> {
+    let encrypted_alert = encrypt_message(encryptor, MessageKind::Alert, alert.encode_to_vec())
+        .context("encrypting alert")?;
+    Ok(AlertMessage { alert: Some(Alert::EncryptedAlert(encrypted_alert)) })
+}
+
+/// Decrypts an alert received once the session is open.
+pub(crate) fn decrypt_alert(
+    encryptor: &mut dyn Encryptor,
+    alert_message: AlertMessage,
+) -> Result<SessionAlert, Error> {
+    match alert_message.alert {
+        Some(Alert::EncryptedAlert(encrypted_alert)) => {
+            let plaintext = decrypt_message(encryptor, MessageKind::Alert, encrypted_alert)
+                .context("decrypting alert")?;
+            SessionAlert::decode(plaintext.message.as_slice())
+                .map_err(|err| anyhow!("decoding alert: {err:?}"))
+        }
+        _ => Err(anyhow!("unencrypted alert received in an open session")),
+    }
+}
+
+/// Builds the alert reporting a local failure to the peer.
+///
+/// If the failure was caused by the rejection of the peer's attestation
+/// evidence, the alert lists the IDs of the verifiers that rejected it.
+pub(crate) fn failure_alert(
+    reason: AlertReason,
+    description: String,
+    peer_attestation_verdict: Option<&PeerAttestationVerdict>,
+) -> SessionAlert {
+    let mut alert =
+        SessionAlert { reason: reason.into(), failed_verifier_ids: Vec::new(), description };
+    if let Some(PeerAttestationVerdict::AttestationFailed {
+        legacy_verification_results,
+        assertion_verification_results,
+        ..
+    }) = peer_attestation_verdict
+    {
+        alert.set_reason(AlertReason::AttestationVerificationFailed);
+        alert.failed_verifier_ids = legacy_verification_results
+            .iter()
+            .filter(|(_, result)| matches!(result, VerifierResult::Failure { .. }))
+            .map(|(id, _)| id.clone())
+            .chain(
+                assertion_verification_results
+                    .iter()
+                    .filter(|(_, result)| {
+                        matches!(result, BoundAssertionVerifierResult::Failure { .. })
+                    })
+                    .map(|(id, _)| id.clone()),
+            )
+            .collect();
+    }
+    alert
+}
+
+/// Returns the error reported locally when the peer ends the session with a
+/// fatal `alert`.
+pub(crate) fn peer_alert_error(alert: &SessionAlert) -> Error {
+    if alert.failed_verifier_ids.is_empty() {
+        anyhow!(
+            "the peer ended the session with {}: {}",
+            alert.reason().as_str_name(),
+            alert.description
+        )
+    } else {
+        anyhow!(
+            "the peer ended the session with {} (failed verifiers: {:?}): {}",
+            alert.reason().as_str_name(),
+            alert.failed_verifier_ids,
+            alert.description
+        )
+    }
+}
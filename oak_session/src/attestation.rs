@@ -105,6 +105,7 @@ use crate::{
     aggregators::AggregatedVerificationError,
     config::{AttestationHandlerConfig, PeerAttestationVerifier},
     generator::BindableAssertion,
+    resumption::{ResumedSession, ResumptionRole, RESUMPTION_BINDING_ID},
     session_binding::SessionBindingVerifier,
     verifier::{BoundAssertionVerifier, BoundAssertionVerifierResult},
     ProtocolEngine,
@@ -212,6 +213,9 @@ pub struct AttestationState {
     /// A token derived from the attestation exchange, intended to be used to
     /// cryptographically bind the session keys to the attestation results.
     pub attestation_binding_token: Vec<u8>,
+    /// The state restored from a resumption ticket, if the attestation was
+    /// skipped because the session is resumed.
+    pub resumed_session: Option<ResumedSession>,
 }
 
 /// Defines the configuration for the attestation flow between two parties.
@@ -277,6 +281,7 @@ pub struct ClientAttestationHandler {
     attestation_result: Option<PeerAttestationVerdict>,
     bindable_assertions: BTreeMap<String, Box<dyn BindableAssertion>>,
     attestation_binding_token: Vec<u8>,
+    resumed_session: Option<ResumedSession>,
 }
 
 impl ClientAttestationHandler {
@@ -290,7 +295,23 @@ impl ClientAttestationHandler {
     ///
     /// The lifetime of the attesters and endorsers in `config` must be managed
     /// by the caller; they are typically `Arc`ed to allow sharing.
+    ///
+    /// If `config` contains a resumption ticket, the request carries the
+    /// ticket instead of the client's evidence.
     pub fn create(config: AttestationHandlerConfig) -> Result<Self, Error> {
+        if let Some(resumption_ticket) = &config.resumption_ticket {
+            return Ok(Self {
+                attest_request: Some(AttestRequest {
+                    resumption_ticket: resumption_ticket.ticket().to_vec(),
+                    ..Default::default()
+                }),
+                bindable_assertions: BTreeMap::new(),
+                config,
+                attestation_result: None,
+                attestation_binding_token: Vec::new(),
+                resumed_session: None,
+            });
+        }
         let bindable_assertions = config
             .self_assertion_generators
             .iter()
@@ -322,11 +343,13 @@ impl ClientAttestationHandler {
                         (id.clone(), bindable_assertion.assertion().clone())
                     })
                     .collect(),
+                ..Default::default()
             }),
             bindable_assertions,
             config,
             attestation_result: None,
             attestation_binding_token: Vec::new(),
+            resumed_session: None,
         })
     }
 }
@@ -352,11 +375,21 @@ impl AttestationHandler for ClientAttestationHandler {
                     _ => None,
                 }
             }).collect::<Result<BTreeMap<String, Box<dyn SessionBindingVerifier>>, Error>>()?;
+        let peer_session_binding_verifiers = match &self.resumed_session {
+            // A resumed session is bound to the original one with the resumption secret
+            // instead of the keys from the peer's evidence.
+            Some(resumed_session) => BTreeMap::from([(
+                RESUMPTION_BINDING_ID.into(),
+                resumed_session.session_binding_verifier(ResumptionRole::Client),
+            )]),
+            None => peer_session_binding_verifiers,
+        };
         Ok(AttestationState {
             peer_session_binding_verifiers,
             peer_attestation_verdict: verdict,
             self_assertions: self.bindable_assertions,
             attestation_binding_token: self.attestation_binding_token,
+            resumed_session: self.resumed_session,
         })
     }
 }
@@ -403,24 +436,27 @@ impl ProtocolEngine<AttestResponse, AttestRequest> for ClientAttestationHandler
             // Attestation result is already obtained - no new messages expected.
             return Ok(None);
         }
-        let legacy_results = combine_attestation_results(
-            &self.config.peer_verifiers,
+        if incoming_message.resumption_accepted {
+            let resumption_ticket = self.config.resumption_ticket.as_ref().ok_or(anyhow!(
+                "the server accepted a resumption ticket that hasn't been presented"
+            ))?;
+            let resumed_session = resumption_ticket.resume();
+            // The evidence of the original session is checked again, so that a ticket
+            // doesn't outlive changes to the verifiers or expiring endorsements.
+            let peer_attestation_evidence = resumed_session.peer_attestation_evidence();
+            self.attestation_result = Some(verify_peer_attestation(
+                &self.config,
+                peer_attestation_evidence.evidence.clone(),
+                peer_attestation_evidence.assertions.clone(),
+            )?);
+            self.resumed_session = Some(resumed_session);
+            return Ok(Some(()));
+        }
+        self.attestation_result = Some(verify_peer_attestation(
+            &self.config,
             incoming_message.endorsed_evidence,
-        )?;
-        let assertion_results = combine_assertion_results(
-            &self.config.peer_assertion_verifiers,
             incoming_message.assertions,
-        );
-        self.attestation_result = Some(combine_legacy_and_assertion_aggregated_verification(
-            self.config
-                .legacy_attestation_results_aggregator
-                .process_assertion_results(&legacy_results),
-            self.config
-                .assertion_attestation_aggregator
-                .process_assertion_results(&assertion_results),
-            legacy_results,
-            assertion_results,
-        ));
+        )?);
         Ok(Some(()))
     }
 }
@@ -441,6 +477,7 @@ pub struct ServerAttestationHandler {
     attestation_result: Option<PeerAttestationVerdict>,
     bindable_assertions: BTreeMap<String, Box<dyn BindableAssertion>>,
     attestation_binding_token: Vec<u8>,
+    resumed_session: Option<ResumedSession>,
 }
 
 impl ServerAttestationHandler {
@@ -486,11 +523,13 @@ impl ServerAttestationHandler {
                         (id.clone(), bindable_assertion.assertion().clone())
                     })
                     .collect(),
+                ..Default::default()
             }),
             bindable_assertions,
             config,
             attestation_result: None,
             attestation_binding_token: Vec::new(),
+            resumed_session: None,
         })
     }
 }
@@ -516,11 +555,21 @@ impl AttestationHandler for ServerAttestationHandler {
                     _ => None,
                 }
             }).collect::<Result<BTreeMap<String, Box<dyn SessionBindingVerifier>>, Error>>()?;
+        let peer_session_binding_verifiers = match &self.resumed_session {
+            // A resumed session is bound to the original one with the resumption secret
+            // instead of the keys from the peer's evidence.
+            Some(resumed_session) => BTreeMap::from([(
+                RESUMPTION_BINDING_ID.into(),
+                resumed_session.session_binding_verifier(ResumptionRole::Server),
+            )]),
+            None => peer_session_binding_verifiers,
+        };
         Ok(AttestationState {
             peer_session_binding_verifiers,
             peer_attestation_verdict: verdict,
             self_assertions: self.bindable_assertions,
             attestation_binding_token: self.attestation_binding_token,
+            resumed_session: self.resumed_session,
         })
     }
 }
@@ -566,28 +615,61 @@ impl ProtocolEngine<AttestRequest, AttestResponse> for ServerAttestationHandler
             // Attestation result is already obtained - no new messages expected.
             return Ok(None);
         }
-        let legacy_results = combine_attestation_results(
-            &self.config.peer_verifiers,
+        if !incoming_message.resumption_ticket.is_empty() {
+            // A ticket that can't be redeemed, or whose evidence isn't accepted by the
+            // current verifiers anymore, is ignored, and the attestation falls back to the
+            // regular flow.
+            if let Some((resumed_session, verdict)) = self
+                .config
+                .resumption_ticket_issuer
+                .as_ref()
+                .and_then(|issuer| issuer.redeem(&incoming_message.resumption_ticket).ok())
+                .and_then(|resumed_session| {
+                    let peer_attestation_evidence = resumed_session.peer_attestation_evidence();
+                    let verdict = verify_peer_attestation(
+                        &self.config,
+                        peer_attestation_evidence.evidence.clone(),
+                        peer_attestation_evidence.assertions.clone(),
+                    )
+                    .ok()?;
+                    matches!(verdict, PeerAttestationVerdict::AttestationPassed { .. })
+                        .then_some((resumed_session, verdict))
+                })
+            {
+                self.resumed_session = Some(resumed_session);
+                self.bindable_assertions = BTreeMap::new();
+                self.attest_response =
+                    Some(AttestResponse { resumption_accepted: true, ..Default::default() });
+                self.attestation_result = Some(verdict);
+                return Ok(Some(()));
+            }
+        }
+        self.attestation_result = Some(verify_peer_attestation(
+            &self.config,
             incoming_message.endorsed_evidence,
-        )?;
-        let assertion_results = combine_assertion_results(
-            &self.config.peer_assertion_verifiers,
             incoming_message.assertions,
-        );
-        self.attestation_result = Some(combine_legacy_and_assertion_aggregated_verification(
-            self.config
-                .legacy_attestation_results_aggregator
-                .process_assertion_results(&legacy_results),
-            self.config
-                .assertion_attestation_aggregator
-                .process_assertion_results(&assertion_results),
-            legacy_results,
-            assertion_results,
-        ));
+        )?);
         Ok(Some(()))
     }
 }
 
+/// Verifies the evidence and assertions supplied by the peer with the verifiers
+/// and aggregators of `config`.
+fn verify_peer_attestation(
+    config: &AttestationHandlerConfig,
+    endorsed_evidence: BTreeMap<String, EndorsedEvidence>,
+    assertions: BTreeMap<String, Assertion>,
+) -> Result<PeerAttestationVerdict, Error> {
+    let legacy_results = combine_attestation_results(&config.peer_verifiers, endorsed_evidence)?;
+    let assertion_results = combine_assertion_results(&config.peer_assertion_verifiers, assertions);
+    Ok(combine_legacy_and_assertion_aggregated_verification(
+        config.legacy_attestation_results_aggregator.process_assertion_results(&legacy_results),
+        config.assertion_attestation_aggregator.process_assertion_results(&assertion_results),
+        legacy_results,
+        assertion_results,
+    ))
+}
+
 /// Combines received `attested_evidence` with configured `verifiers`.
 ///
 /// This function performs a merge-join between the set of verifiers (keyed by
@@ -24,10 +24,17 @@
 //!   self-attestation, and which verifiers to use for peer attestation.
 //!   Configuration is held in [`AttestationHandlerConfig`].
 //! - **Handshake**: Defines the cryptographic handshake protocol (e.g., Noise
-//!   patterns like KK, NK, NN), and any pre-shared static keys required by the
-//!   chosen protocol. Configuration is held in [`HandshakeHandlerConfig`].
+//!   patterns like KK, NK, NN, XX), whether it's combined with a post-quantum
+//!   key encapsulation, and any pre-shared static keys or symmetric keys
+//!   required by the chosen protocol. Configuration is held in
+//!   [`HandshakeHandlerConfig`].
 //! - **Encryption**: Determines how session encryptors are provided after a
-//!   successful handshake. Configuration is held in [`EncryptorConfig`].
+//!   successful handshake, when traffic keys are replaced, and how messages are
+//!   padded and compressed before they are encrypted. Configuration is held in
+//!   [`EncryptorConfig`].
+//! - **Resumption**: Allows clients to reconnect with a resumption ticket
+//!   issued by the server, instead of repeating the attestation. Configuration
+//!   is held in [`AttestationHandlerConfig`].
 //! - **Session Binding**: Manages how attestation results are cryptographically
 //!   bound to the session. This includes providers for verifying session
 //!   bindings received from the peer, and binders for this party to create its
@@ -40,11 +47,13 @@
 
 use alloc::{boxed::Box, collections::BTreeMap, string::String, sync::Arc, vec::Vec};
 
-use anyhow::Error;
+use anyhow::{Context, Error};
 use oak_attestation_types::{attester::Attester, endorser::Endorser};
 use oak_attestation_verification_types::verifier::AttestationVerifier;
 use oak_crypto::{
-    encryptor::Encryptor, identity_key::IdentityKeyHandle, noise_handshake::OrderedCrypter,
+    encryptor::Encryptor,
+    identity_key::IdentityKeyHandle,
+    noise_handshake::{OrderedCrypter, SYMMETRIC_KEY_LEN},
 };
 
 use crate::{
@@ -53,10 +62,15 @@ use crate::{
         LegacyVerifierResultsAggregator,
     },
     attestation::AttestationType,
-    encryptors::OrderedChannelEncryptor,
+    encoding::{CompressionPolicy, PaddingPolicy, PlaintextEncoder, DEFAULT_MAX_MESSAGE_LEN},
+    encryptors::{
+        EncodingEncryptor, OrderedChannelEncryptor, ReplayMetrics, UnorderedChannelEncryptor,
+    },
     generator::BindableAssertionGenerator,
-    handshake::HandshakeType,
+    handshake::{HandshakeType, KeyExchange},
     key_extractor::{DefaultSigningKeyExtractor, KeyExtractor},
+    rekey::RekeyPolicy,
+    resumption::{ResumptionTicket, ResumptionTicketIssuer},
     session::AttestationPublisher,
     session_binding::{
         SessionBinder, SessionBindingVerifierProvider, SignatureBindingVerifierProvider,
@@ -115,6 +129,12 @@ pub struct SessionConfigBuilder {
 /// available after a successful handshake.
 pub trait EncryptorProvider: Send {
     fn provide_encryptor(&self, crypter: OrderedCrypter) -> Result<Box<dyn Encryptor>, Error>;
+
+    /// Returns whether the provided encryptors support rekeying, which is
+    /// required by a [`RekeyPolicy`].
+    fn supports_rekey(&self) -> bool {
+        true
+    }
 }
 
 /// An [`EncryptorProvider`] that creates [`OrderedChannelEncryptor`] instances.
@@ -127,6 +147,64 @@ impl EncryptorProvider for OrderedChannelEncryptorProvider {
     }
 }
 
+/// An [`EncryptorProvider`] that creates [`UnorderedChannelEncryptor`]
+/// instances, for transports that can drop or reorder messages.
+pub struct UnorderedChannelEncryptorProvider {
+    /// The number of most recent messages within which messages can arrive in
+    /// any order.
+    pub window_size: u32,
+    /// Receives the counters of accepted and rejected messages, if set.
+    pub replay_metrics: Option<Arc<ReplayMetrics>>,
+}
+
+impl EncryptorProvider for UnorderedChannelEncryptorProvider {
+    fn provide_encryptor(&self, crypter: OrderedCrypter) -> Result<Box<dyn Encryptor>, Error> {
+        let encryptor =
+            TryInto::<UnorderedChannelEncryptor>::try_into((crypter, self.window_size))?;
+        match &self.replay_metrics {
+            Some(replay_metrics) => {
+                Ok(Box::new(encryptor.with_replay_metrics(replay_metrics.clone())))
+            }
+            None => Ok(Box::new(encryptor)),
+        }
+    }
+
+    fn supports_rekey(&self) -> bool {
+        false
+    }
+}
+
+/// An [`EncryptorProvider`] that wraps the encryptors of the configured
+/// provider in an [`EncodingEncryptor`] applying the padding and compression
+/// policies of the session.
+pub(crate) struct EncodingEncryptorProvider {
+    encryptor_provider: Box<dyn EncryptorProvider>,
+    encoder: PlaintextEncoder,
+}
+
+impl EncodingEncryptorProvider {
+    pub(crate) fn create(
+        encryptor_provider: Box<dyn EncryptorProvider>,
+        encoder: PlaintextEncoder,
+    ) -> Box<dyn EncryptorProvider> {
+        Box::new(Self { encryptor_provider, encoder })
+    }
+}
+
+impl EncryptorProvider for EncodingEncryptorProvider {
+    fn provide_encryptor(&self, crypter: OrderedCrypter) -> Result<Box<dyn Encryptor>, Error> {
+        let encryptor = self.encryptor_provider.provide_encryptor(crypter)?;
+        if self.encoder.is_passthrough() {
+            return Ok(encryptor);
+        }
+        Ok(Box::new(EncodingEncryptor::new(encryptor, self.encoder.clone())))
+    }
+
+    fn supports_rekey(&self) -> bool {
+        self.encryptor_provider.supports_rekey()
+    }
+}
+
 impl SessionConfigBuilder {
     /// Creates a new `SessionConfigBuilder` with essential attestation and
     /// handshake types.
@@ -142,10 +220,17 @@ impl SessionConfigBuilder {
             self_static_private_key: None,
             peer_static_public_key: None,
             session_binders: BTreeMap::new(),
+            pre_shared_key: None,
+            key_exchange: KeyExchange::default(),
         };
 
-        let encryptor_config =
-            EncryptorConfig { encryptor_provider: Box::new(OrderedChannelEncryptorProvider) };
+        let encryptor_config = EncryptorConfig {
+            encryptor_provider: Box::new(OrderedChannelEncryptorProvider),
+            rekey_policy: RekeyPolicy::default(),
+            padding_policy: PaddingPolicy::default(),
+            compression_policy: CompressionPolicy::default(),
+            max_message_len: DEFAULT_MAX_MESSAGE_LEN,
+        };
 
         let config = SessionConfig {
             attestation_type,
@@ -520,7 +605,10 @@ impl SessionConfigBuilder {
     /// Sets the peer's static public key for the handshake.
     ///
     /// This key is used in handshake patterns where the peer's static identity
-    /// is known beforehand (e.g., Noise patterns like IK, KK).
+    /// is known beforehand (e.g., Noise patterns like IK, KK). For patterns in
+    /// which the peer sends its static public key during the handshake (e.g.,
+    /// the server side of IK, or XX), the key is optional: if it's set, the
+    /// handshake fails unless the peer presents that key.
     /// Panics if the key has already been set.
     pub fn set_peer_static_public_key(mut self, public_key: &[u8]) -> Self {
         if self.config.handshake_handler_config.peer_static_public_key.is_none() {
@@ -531,6 +619,35 @@ impl SessionConfigBuilder {
         self
     }
 
+    /// Sets the key shared with the peer beforehand, which is mixed into the
+    /// handshake by the PSK variants of the handshake patterns.
+    /// Panics if the handshake type doesn't use a pre-shared key, or if the
+    /// key has already been set.
+    pub fn set_pre_shared_key(mut self, pre_shared_key: [u8; SYMMETRIC_KEY_LEN]) -> Self {
+        assert!(
+            self.config.handshake_handler_config.handshake_type.uses_pre_shared_key(),
+            "Pre-shared keys are not supported for handshake type {:?}",
+            self.config.handshake_handler_config.handshake_type
+        );
+        if self.config.handshake_handler_config.pre_shared_key.is_none() {
+            self.config.handshake_handler_config.pre_shared_key = Some(pre_shared_key);
+        } else {
+            panic!("pre-shared key has already been set");
+        }
+        self
+    }
+
+    /// Sets the [`KeyExchange`] used by the handshake.
+    ///
+    /// With [`KeyExchange::HybridMlKem768`], a client only completes hybrid
+    /// handshakes, and a server rejects clients that don't offer one. Servers
+    /// using the default [`KeyExchange::Classical`] still accept hybrid
+    /// handshakes offered by clients.
+    pub fn set_key_exchange(mut self, key_exchange: KeyExchange) -> Self {
+        self.config.handshake_handler_config.key_exchange = key_exchange;
+        self
+    }
+
     /// Sets a custom [`EncryptorProvider`] for creating the session encryptor.
     ///
     /// This allows overriding the default [`OrderedChannelEncryptorProvider`].
@@ -542,6 +659,66 @@ impl SessionConfigBuilder {
         self
     }
 
+    /// Uses an [`UnorderedChannelEncryptor`] for transports that can drop,
+    /// reorder or duplicate messages, such as datagram transports.
+    ///
+    /// Messages are accepted in any order as long as they are within the
+    /// `window_size` most recent messages, and each of them is accepted only
+    /// once. Replayed messages and messages older than the window are
+    /// discarded without ending the session, and counted in `replay_metrics`.
+    /// The peer has to use an unordered encryptor as well.
+    pub fn set_replay_window(self, window_size: u32, replay_metrics: Arc<ReplayMetrics>) -> Self {
+        self.set_encryption_provider(Box::new(UnorderedChannelEncryptorProvider {
+            window_size,
+            replay_metrics: Some(replay_metrics),
+        }))
+    }
+
+    /// Sets the [`RekeyPolicy`] that determines when the session switches to a
+    /// new traffic key for the messages it sends.
+    ///
+    /// Rekeying requires an [`Encryptor`] that supports it, such as the default
+    /// [`OrderedChannelEncryptor`]. Combining a rekey policy with
+    /// [`Self::set_replay_window`] makes the creation of the session fail.
+    pub fn set_rekey_policy(mut self, rekey_policy: RekeyPolicy) -> Self {
+        self.config.encryptor_config.rekey_policy = rekey_policy;
+        self
+    }
+
+    /// Sets the [`PaddingPolicy`] that determines the length to which
+    /// messages are padded before they are encrypted, to hide their actual
+    /// length.
+    ///
+    /// The peer has to use the same padding and compression policies. An
+    /// invalid policy, e.g. one without buckets, makes the creation of the
+    /// session fail.
+    pub fn set_padding_policy(mut self, padding_policy: PaddingPolicy) -> Self {
+        self.config.encryptor_config.padding_policy = padding_policy;
+        self
+    }
+
+    /// Sets the [`CompressionPolicy`] that determines whether messages are
+    /// compressed before they are encrypted.
+    ///
+    /// Compression has to be combined with [`PaddingPolicy::PadToMax`] unless
+    /// the policy explicitly allows the length of messages to leak
+    /// information about their contents, otherwise the creation of the session
+    /// fails. The peer has to use the same padding and compression policies.
+    pub fn set_compression_policy(mut self, compression_policy: CompressionPolicy) -> Self {
+        self.config.encryptor_config.compression_policy = compression_policy;
+        self
+    }
+
+    /// Sets the maximum length of a compressed message once decompressed.
+    /// Longer messages are sent uncompressed.
+    ///
+    /// Defaults to [`DEFAULT_MAX_MESSAGE_LEN`]. The peer has to use the same
+    /// limit.
+    pub fn set_max_message_len(mut self, max_message_len: usize) -> Self {
+        self.config.encryptor_config.max_message_len = max_message_len;
+        self
+    }
+
     /// Adds a [`SessionBinder`] used by this party to bind its attestation to
     /// the current session's handshake. The `attestation_id` associates this
     /// binder with a specific attestation flow/result, ensuring that the
@@ -613,6 +790,28 @@ impl SessionConfigBuilder {
         self
     }
 
+    /// Sets a [`ResumptionTicket`] received in a previous session with the same
+    /// server. Only used by clients.
+    ///
+    /// The client presents the ticket instead of its own evidence. If the
+    /// server accepts it, the attestation is skipped and the session restores
+    /// the attestation evidence of the session the ticket was issued in.
+    pub fn set_resumption_ticket(mut self, resumption_ticket: ResumptionTicket) -> Self {
+        self.config.attestation_handler_config.resumption_ticket = Some(resumption_ticket);
+        self
+    }
+
+    /// Sets the [`ResumptionTicketIssuer`] used to issue resumption tickets to
+    /// clients, and to accept the tickets they present. Only used by servers.
+    pub fn set_resumption_ticket_issuer(
+        mut self,
+        resumption_ticket_issuer: ResumptionTicketIssuer,
+    ) -> Self {
+        self.config.attestation_handler_config.resumption_ticket_issuer =
+            Some(Arc::new(resumption_ticket_issuer));
+        self
+    }
+
     /// Consumes the builder and returns the configured [`SessionConfig`].
     pub fn build(self) -> SessionConfig {
         assert!(
@@ -678,6 +877,12 @@ pub struct AttestationHandlerConfig {
     /// and `assertion_attestation_aggregator` must succeed for the
     /// attestation to succeed.
     pub assertion_attestation_aggregator: Box<dyn AssertionResultsAggregator>,
+    /// A [`ResumptionTicket`] presented by a client instead of its evidence, to
+    /// skip the attestation.
+    pub resumption_ticket: Option<ResumptionTicket>,
+    /// The [`ResumptionTicketIssuer`] used by a server to issue and accept
+    /// resumption tickets.
+    pub resumption_ticket_issuer: Option<Arc<ResumptionTicketIssuer>>,
 }
 
 impl Default for alloc::boxed::Box<dyn LegacyVerifierResultsAggregator> {
@@ -712,6 +917,12 @@ pub struct HandshakeHandlerConfig {
     /// party's attestation (identified by `attestation_id`) to the current
     /// session's handshake hash.
     pub session_binders: BTreeMap<String, Arc<dyn SessionBinder>>,
+    /// The symmetric key shared by both parties beforehand. Required for the
+    /// PSK variants of the handshake patterns (e.g., Noise IKpsk2, XXpsk3).
+    pub pre_shared_key: Option<[u8; SYMMETRIC_KEY_LEN]>,
+    /// The key exchange used by the handshake, classical or hybrid
+    /// post-quantum.
+    pub key_exchange: KeyExchange,
 }
 
 /// Configuration for creating the session encryptor.
@@ -722,4 +933,35 @@ pub struct EncryptorConfig {
     /// A provider that creates an [`Encryptor`] instance once session keys are
     /// established from the handshake.
     pub encryptor_provider: Box<dyn EncryptorProvider>,
+    /// Conditions under which the session switches to a new traffic key for the
+    /// messages it sends.
+    pub rekey_policy: RekeyPolicy,
+    /// The length to which messages are padded before they are encrypted.
+    pub padding_policy: PaddingPolicy,
+    /// Whether messages are compressed before they are encrypted.
+    pub compression_policy: CompressionPolicy,
+    /// The maximum length of a compressed message once decompressed.
+    pub max_message_len: usize,
+}
+
+impl EncryptorConfig {
+    /// Returns an error if the rekey, padding and compression policies can't
+    /// be applied.
+    pub fn validate(&self) -> Result<(), Error> {
+        anyhow::ensure!(
+            self.rekey_policy.is_empty() || self.encryptor_provider.supports_rekey(),
+            "the rekey policy requires an encryptor that supports rekeying"
+        );
+        self.padding_policy.validate().context("invalid padding policy")?;
+        self.compression_policy
+            .validate(&self.padding_policy)
+            .context("invalid compression policy")?;
+        Ok(())
+    }
+
+    /// Returns the encoder applying the padding and compression policies.
+    pub(crate) fn encoder(&self) -> PlaintextEncoder {
+        PlaintextEncoder::new(self.padding_policy.clone(), self.compression_policy.clone())
+            .with_max_message_len(self.max_message_len)
+    }
 }
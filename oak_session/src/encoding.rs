@@ -0,0 +1,252 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! This module implements the padding and compression applied to the plaintext
+//! of session messages before they are encrypted.
+//!
+//! The Noise crypter only pads plaintexts to a multiple of 32 bytes, so the
+//! length of each ciphertext reveals the length of the message it carries up to
+//! 32 bytes. A [`PaddingPolicy`] hides lengths further, at the cost of larger
+//! ciphertexts. A [`CompressionPolicy`] reduces the size of compressible
+//! messages, but makes the length of the ciphertext depend on the contents of
+//! the message, which can leak secrets mixed with attacker-controlled data
+//! (see the CRIME and BREACH attacks). It therefore has to be combined with
+//! [`PaddingPolicy::PadToMax`], or explicitly allowed to leak lengths.
+//!
+//! Unless one of the policies is set, plaintexts are passed to the crypter
+//! unchanged. Otherwise each plaintext is encoded as:
+//!
+//! - 1 byte of flags, indicating whether the body is compressed;
+//! - the length of the body, as a big-endian 32-bit integer;
+//! - the body;
+//! - zero bytes up to the length chosen by the padding policy.
+//!
+//! Both parties of a session have to use the same policies, as a party without
+//! any policy doesn't expect the encoding. They also have to agree on the
+//! maximum length of messages, which bounds the memory used to decompress them.
+
+use alloc::vec::Vec;
+
+use anyhow::{anyhow, Context, Error};
+
+/// Length of the header that precedes the body of an encoded plaintext.
+const HEADER_LEN: usize = 5;
+
+/// Flag set in the header if the body is compressed.
+const COMPRESSED_FLAG: u8 = 1;
+
+/// Default maximum length of a compressed message once decompressed.
+pub const DEFAULT_MAX_MESSAGE_LEN: usize = 16 * 1024 * 1024;
+
+/// Determines the length to which the plaintext of each message is padded
+/// before it is encrypted.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum PaddingPolicy {
+    /// Plaintexts are only padded to the 32-byte granularity of the Noise
+    /// crypter.
+    #[default]
+    None,
+    /// Plaintexts are padded to the smallest of the given bucket sizes that
+    /// fits them. Plaintexts larger than all buckets are padded to a multiple
+    /// of the largest bucket.
+    Buckets(Vec<usize>),
+    /// Plaintexts are padded to the next power of two, so that the length of
+    /// the ciphertext only reveals the order of magnitude of the message.
+    PowerOfTwo,
+    /// All plaintexts are padded to the given length, so that all ciphertexts
+    /// have the same length. Larger messages can't be sent.
+    PadToMax(usize),
+}
+
+impl PaddingPolicy {
+    /// Returns an error if the policy can't be applied, e.g. because it has no
+    /// buckets.
+    pub fn validate(&self) -> Result<(), Error> {
+        match self {
+            PaddingPolicy::None | PaddingPolicy::PowerOfTwo => Ok(()),
+            PaddingPolicy::Buckets(buckets) => {
+                anyhow::ensure!(!buckets.is_empty(), "no padding buckets were provided");
+                anyhow::ensure!(
+                    buckets.iter().all(|&bucket| bucket >= HEADER_LEN),
+                    "padding buckets must be at least {HEADER_LEN} bytes long"
+                );
+                Ok(())
+            }
+            PaddingPolicy::PadToMax(max_len) => {
+                anyhow::ensure!(
+                    *max_len >= HEADER_LEN,
+                    "the padded length must be at least {HEADER_LEN} bytes long"
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the length to which a plaintext of `len` bytes is padded.
+    fn padded_len(&self, len: usize) -> Result<usize, Error> {
+        match self {
+            PaddingPolicy::None => Ok(len),
+            PaddingPolicy::Buckets(buckets) => {
+                if let Some(bucket) = buckets.iter().filter(|&&bucket| bucket >= len).min() {
+                    return Ok(*bucket);
+                }
+                let largest_bucket = buckets
+                    .iter()
+                    .max()
+                    .filter(|&&bucket| bucket > 0)
+                    .context("no non-empty padding buckets")?;
+                len.div_ceil(*largest_bucket)
+                    .checked_mul(*largest_bucket)
+                    .context("padded length overflow")
+            }
+            PaddingPolicy::PowerOfTwo => {
+                len.checked_next_power_of_two().context("padded length overflow")
+            }
+            PaddingPolicy::PadToMax(max_len) => {
+                anyhow::ensure!(
+                    len <= *max_len,
+                    "encoded message of {len} bytes exceeds the padded length of {max_len} bytes"
+                );
+                Ok(*max_len)
+            }
+        }
+    }
+}
+
+/// Determines whether the plaintext of each message is compressed before it is
+/// encrypted.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum CompressionPolicy {
+    /// Plaintexts are not compressed.
+    #[default]
+    Disabled,
+    /// Plaintexts are compressed with DEFLATE at the given level (0-10), unless
+    /// compression doesn't make them smaller.
+    ///
+    /// Since the length of a compressed message depends on its contents,
+    /// compression can only be combined with [`PaddingPolicy::PadToMax`],
+    /// unless `allow_length_leaks` is set to acknowledge that the length of
+    /// the ciphertexts leaks information about the messages.
+    Deflate { level: u8, allow_length_leaks: bool },
+}
+
+impl CompressionPolicy {
+    /// Returns an error if the policy can't be combined with `padding_policy`
+    /// without leaking information through the length of the ciphertexts,
+    /// and the policy doesn't allow such leaks.
+    pub fn validate(&self, padding_policy: &PaddingPolicy) -> Result<(), Error> {
+        match self {
+            CompressionPolicy::Disabled => Ok(()),
+            CompressionPolicy::Deflate { level, allow_length_leaks } => {
+                anyhow::ensure!(*level <= 10, "invalid compression level {level}");
+                anyhow::ensure!(
+                    *allow_length_leaks || matches!(padding_policy, PaddingPolicy::PadToMax(_)),
+                    "compression leaks the contents of messages through their length, unless it \
+                     is combined with PaddingPolicy::PadToMax or length leaks are allowed"
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Applies a [`PaddingPolicy`] and a [`CompressionPolicy`] to the plaintext
+/// of messages before they are encrypted, and removes them after decryption.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlaintextEncoder {
+    padding_policy: PaddingPolicy,
+    compression_policy: CompressionPolicy,
+    /// Messages longer than this are never compressed, and compressed messages
+    /// that decompress to more than this are rejected.
+    max_message_len: usize,
+}
+
+impl Default for PlaintextEncoder {
+    fn default() -> Self {
+        Self::new(PaddingPolicy::default(), CompressionPolicy::default())
+    }
+}
+
+impl PlaintextEncoder {
+    pub fn new(padding_policy: PaddingPolicy, compression_policy: CompressionPolicy) -> Self {
+        Self { padding_policy, compression_policy, max_message_len: DEFAULT_MAX_MESSAGE_LEN }
+    }
+
+    /// Bounds the length of compressed messages once decompressed, so that a
+    /// small message can't decompress to an arbitrarily large one. Both
+    /// parties of a session have to use the same limit.
+    pub fn with_max_message_len(mut self, max_message_len: usize) -> Self {
+        self.max_message_len = max_message_len;
+        self
+    }
+
+    /// Returns whether plaintexts are passed to the crypter unchanged.
+    pub fn is_passthrough(&self) -> bool {
+        self.padding_policy == PaddingPolicy::None
+            && self.compression_policy == CompressionPolicy::Disabled
+    }
+
+    /// Compresses and pads `plaintext` according to the policies.
+    pub fn encode(&self, plaintext: Vec<u8>) -> Result<Vec<u8>, Error> {
+        if self.is_passthrough() {
+            return Ok(plaintext);
+        }
+        let (flags, body) = match self.compression_policy {
+            CompressionPolicy::Deflate { level, .. } if plaintext.len() <= self.max_message_len => {
+                let compressed = miniz_oxide::deflate::compress_to_vec(&plaintext, level);
+                if compressed.len() < plaintext.len() {
+                    (COMPRESSED_FLAG, compressed)
+                } else {
+                    (0, plaintext)
+                }
+            }
+            _ => (0, plaintext),
+        };
+        let body_len = u32::try_from(body.len()).context("message too large")?;
+        let padded_len = self.padding_policy.padded_len(HEADER_LEN + body.len())?;
+
+        let mut encoded = Vec::with_capacity(padded_len);
+        encoded.push(flags);
+        encoded.extend_from_slice(&body_len.to_be_bytes());
+        encoded.extend_from_slice(&body);
+        encoded.resize(padded_len, 0u8);
+        Ok(encoded)
+    }
+
+    /// Removes the padding from `encoded` and decompresses it if needed.
+    pub fn decode(&self, mut encoded: Vec<u8>) -> Result<Vec<u8>, Error> {
+        if self.is_passthrough() {
+            return Ok(encoded);
+        }
+        anyhow::ensure!(encoded.len() >= HEADER_LEN, "encoded message is too short");
+        let flags = encoded[0];
+        anyhow::ensure!(flags & !COMPRESSED_FLAG == 0, "unknown encoding flags {flags:#x}");
+        let body_len = u32::from_be_bytes([encoded[1], encoded[2], encoded[3], encoded[4]]);
+        let body_end = usize::try_from(body_len)
+            .ok()
+            .and_then(|body_len| body_len.checked_add(HEADER_LEN))
+            .filter(|&body_end| body_end <= encoded.len())
+            .context("encoded message is shorter than its body")?;
+        encoded.truncate(body_end);
+        let body = encoded.split_off(HEADER_LEN);
+        if flags & COMPRESSED_FLAG != 0 {
+            miniz_oxide::inflate::decompress_to_vec_with_limit(&body, self.max_message_len)
+                .map_err(|err| anyhow!("decompressing message: {err:?}"))
+        } else {
+            Ok(body)
+        }
+    }
+}
@@ -17,11 +17,17 @@
 //! This module provides an interface and an implementation of the Encryptor,
 //! which handles encrypted communication over a channel.
 
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::sync::atomic::{AtomicU64, Ordering};
+
 use anyhow::{anyhow, Context, Error};
 use oak_crypto::{
     encryptor::{Encryptor, Payload},
-    noise_handshake::{OrderedCrypter, UnorderedCrypter, NONCE_LEN},
+    noise_handshake::{OrderedCrypter, ReplayWindowStats, UnorderedCrypter, NONCE_LEN},
 };
+use oak_proto_rust::oak::session::v1::EncryptedMessage;
+
+use crate::encoding::PlaintextEncoder;
 
 // This is the default implementation of the encryptor to use for the Noise
 // protocol (consecutive nonces, no packet drop or reordering allowed)
@@ -32,17 +38,33 @@ pub struct OrderedChannelEncryptor {
 impl Encryptor for OrderedChannelEncryptor {
     fn encrypt(&mut self, plaintext: Payload) -> anyhow::Result<Payload> {
         self.crypter
-            .encrypt(plaintext.message.as_slice())
+            .encrypt_with_aad(
+                plaintext.message.as_slice(),
+                plaintext.aad.as_deref().unwrap_or_default(),
+            )
             .map(From::from)
             .map_err(|e| anyhow!("Encryption error: {e:#?}"))
     }
 
     fn decrypt(&mut self, ciphertext: Payload) -> anyhow::Result<Payload> {
         self.crypter
-            .decrypt(ciphertext.message.as_slice())
+            .decrypt_with_aad(
+                ciphertext.message.as_slice(),
+                ciphertext.aad.as_deref().unwrap_or_default(),
+            )
             .map(From::from)
             .map_err(|e| anyhow!("Encryption error: {e:#?}"))
     }
+
+    fn rekey_encryption(&mut self) -> anyhow::Result<()> {
+        self.crypter.rekey_write();
+        Ok(())
+    }
+
+    fn rekey_decryption(&mut self) -> anyhow::Result<()> {
+        self.crypter.rekey_read();
+        Ok(())
+    }
 }
 
 impl TryFrom<OrderedCrypter> for OrderedChannelEncryptor {
@@ -53,21 +75,83 @@ impl TryFrom<OrderedCrypter> for OrderedChannelEncryptor {
     }
 }
 
+/// Error returned when decrypting a message that has already been received,
+/// or that is older than the replay window.
+///
+/// Such messages are expected on transports that can duplicate or delay
+/// messages, so the session discards them instead of failing.
+#[derive(Debug, thiserror::Error)]
+#[error("message was replayed or is older than the replay window")]
+pub struct DiscardedMessageError;
+
+/// Counters of the messages received by [`MeteredUnorderedChannelEncryptor`]s,
+/// shared with the application.
+///
+/// The counters are cumulative, so a single instance can aggregate the
+/// messages of several sessions.
+#[derive(Debug, Default)]
+pub struct ReplayMetrics {
+    accepted: AtomicU64,
+    duplicated: AtomicU64,
+    too_old: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl ReplayMetrics {
+    /// Returns the current value of the counters. See [`ReplayWindowStats`]
+    /// for their meaning.
+    pub fn snapshot(&self) -> ReplayWindowStats {
+        ReplayWindowStats {
+            accepted: self.accepted.load(Ordering::Relaxed),
+            duplicated: self.duplicated.load(Ordering::Relaxed),
+            too_old: self.too_old.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Adds the difference between two snapshots of the counters of a
+    /// crypter.
+    fn record(&self, before: ReplayWindowStats, after: ReplayWindowStats) {
+        self.accepted.fetch_add(after.accepted - before.accepted, Ordering::Relaxed);
+        self.duplicated.fetch_add(after.duplicated - before.duplicated, Ordering::Relaxed);
+        self.too_old.fetch_add(after.too_old - before.too_old, Ordering::Relaxed);
+        self.dropped.fetch_add(after.dropped - before.dropped, Ordering::Relaxed);
+    }
+}
+
 /// `OrderedChannelEncryptor` that explicitly ignores message ordering.
 ///
 /// It ignores message ordering but protects against replayed messages. This
 /// implementation ratchets messages upto a given `window_size` i.e. very old
 /// messages outside the given window will fail decryption. Messages within the
-/// allowed window will be decrypted in any order. Applications using this
-/// implementation must ensure they can handle re-ordered and dropped messages.
+/// allowed window will be decrypted in any order, and each of them only once.
+/// Applications using this implementation must ensure they can handle
+/// re-ordered and dropped messages.
+///
+/// Rekeying isn't supported, since the peer couldn't tell which key a
+/// reordered message was encrypted with.
 pub struct UnorderedChannelEncryptor {
     pub crypter: UnorderedCrypter,
 }
 
+impl UnorderedChannelEncryptor {
+    /// Returns an encryptor that also adds the counters of the messages it
+    /// accepts and rejects to `replay_metrics`.
+    pub fn with_replay_metrics(
+        self,
+        replay_metrics: Arc<ReplayMetrics>,
+    ) -> MeteredUnorderedChannelEncryptor {
+        MeteredUnorderedChannelEncryptor { encryptor: self, replay_metrics }
+    }
+}
+
 impl Encryptor for UnorderedChannelEncryptor {
     fn encrypt(&mut self, plaintext: Payload) -> anyhow::Result<Payload> {
         self.crypter
-            .encrypt(plaintext.message.as_slice())
+            .encrypt_with_aad(
+                plaintext.message.as_slice(),
+                plaintext.aad.as_deref().unwrap_or_default(),
+            )
             .map(From::from)
             .map_err(|e| anyhow!("Encryption error: {e:#?}"))
     }
@@ -75,15 +159,23 @@ impl Encryptor for UnorderedChannelEncryptor {
     fn decrypt(&mut self, ciphertext: Payload) -> anyhow::Result<Payload> {
         let nonce: [u8; NONCE_LEN] = ciphertext
             .nonce
-            .as_ref()
-            .unwrap()
             .clone()
+            .context("ciphertext without a nonce")?
             .try_into()
             .map_err(|e| anyhow!("Failed to extract nonce error: {e:#?}"))?;
-        self.crypter
-            .decrypt(&nonce, ciphertext.message.as_slice())
-            .map(From::from)
-            .map_err(|e| anyhow!("Encryption error: {e:#?}"))
+        let stats_before = self.crypter.replay_window_stats();
+        let result = self.crypter.decrypt_with_aad(
+            &nonce,
+            ciphertext.message.as_slice(),
+            ciphertext.aad.as_deref().unwrap_or_default(),
+        );
+        let stats_after = self.crypter.replay_window_stats();
+        if stats_after.duplicated > stats_before.duplicated
+            || stats_after.too_old > stats_before.too_old
+        {
+            return Err(DiscardedMessageError.into());
+        }
+        result.map(From::from).map_err(|e| anyhow!("Encryption error: {e:#?}"))
     }
 }
 
@@ -98,3 +190,118 @@ impl TryFrom<(OrderedCrypter, u32)> for UnorderedChannelEncryptor {
         })
     }
 }
+
+/// [`UnorderedChannelEncryptor`] that records the messages it receives in
+/// [`ReplayMetrics`].
+pub struct MeteredUnorderedChannelEncryptor {
+    encryptor: UnorderedChannelEncryptor,
+    replay_metrics: Arc<ReplayMetrics>,
+}
+
+impl Encryptor for MeteredUnorderedChannelEncryptor {
+    fn encrypt(&mut self, plaintext: Payload) -> anyhow::Result<Payload> {
+        self.encryptor.encrypt(plaintext)
+    }
+
+    fn decrypt(&mut self, ciphertext: Payload) -> anyhow::Result<Payload> {
+        let stats_before = self.encryptor.crypter.replay_window_stats();
+        let result = self.encryptor.decrypt(ciphertext);
+        self.replay_metrics.record(stats_before, self.encryptor.crypter.replay_window_stats());
+        result
+    }
+}
+
+/// An [`Encryptor`] that pads and compresses plaintexts with a
+/// [`PlaintextEncoder`] before they are encrypted by another encryptor, and
+/// reverts the encoding after they are decrypted.
+pub struct EncodingEncryptor {
+    encryptor: Box<dyn Encryptor>,
+    encoder: PlaintextEncoder,
+}
+
+impl EncodingEncryptor {
+    pub fn new(encryptor: Box<dyn Encryptor>, encoder: PlaintextEncoder) -> Self {
+        Self { encryptor, encoder }
+    }
+}
+
+impl Encryptor for EncodingEncryptor {
+    fn encrypt(&mut self, plaintext: Payload) -> anyhow::Result<Payload> {
+        let message = self.encoder.encode(plaintext.message).context("encoding plaintext")?;
+        self.encryptor.encrypt(Payload { message, ..plaintext })
+    }
+
+    fn decrypt(&mut self, ciphertext: Payload) -> anyhow::Result<Payload> {
+        let plaintext = self.encryptor.decrypt(ciphertext)?;
+        let message = self.encoder.decode(plaintext.message).context("decoding plaintext")?;
+        Ok(Payload { message, ..plaintext })
+    }
+
+    fn rekey_encryption(&mut self) -> anyhow::Result<()> {
+        self.encryptor.rekey_encryption()
+    }
+
+    fn rekey_decryption(&mut self) -> anyhow::Result<()> {
+        self.encryptor.rekey_decryption()
+    }
+}
+
+/// Kind of the encrypted messages exchanged in an open session.
+///
+/// All kinds are encrypted with the same traffic keys and share the same
+/// sequence of nonces, so the kind is bound to each message as associated
+/// data. Otherwise the network could relabel a message of one kind as another,
+/// e.g. turn an application message into an alert that ends the session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum MessageKind {
+    ApplicationData,
+    Rekey,
+    ResumptionTicket,
+    Alert,
+}
+
+impl MessageKind {
+    /// Associated data bound to messages of this kind. Application messages
+    /// have none, so that they stay compatible with peers that don't use any
+    /// other kind of message.
+    fn associated_data(self) -> Option<Vec<u8>> {
+        match self {
+            MessageKind::ApplicationData => None,
+            MessageKind::Rekey => Some(b"oak_session rekey".to_vec()),
+            MessageKind::ResumptionTicket => Some(b"oak_session resumption ticket".to_vec()),
+            MessageKind::Alert => Some(b"oak_session alert".to_vec()),
+        }
+    }
+}
+
+/// Encrypts `plaintext` as a message of the given `kind`.
+///
+/// The associated data isn't sent to the peer, which knows the kind of the
+/// message from the field it is received in.
+pub(crate) fn encrypt_message(
+    encryptor: &mut dyn Encryptor,
+    kind: MessageKind,
+    plaintext: Vec<u8>,
+) -> anyhow::Result<EncryptedMessage> {
+    let payload = Payload { message: plaintext, aad: kind.associated_data(), nonce: None };
+    let ciphertext = encryptor.encrypt(payload)?;
+    Ok(EncryptedMessage {
+        ciphertext: ciphertext.message,
+        associated_data: None,
+        nonce: ciphertext.nonce,
+    })
+}
+
+/// Decrypts a message received as the given `kind`. Any associated data sent by
+/// the peer is ignored.
+pub(crate) fn decrypt_message(
+    encryptor: &mut dyn Encryptor,
+    kind: MessageKind,
+    encrypted_message: EncryptedMessage,
+) -> anyhow::Result<Payload> {
+    encryptor.decrypt(Payload {
+        message: encrypted_message.ciphertext,
+        aad: kind.associated_data(),
+        nonce: encrypted_message.nonce,
+    })
+}
@@ -33,15 +33,21 @@
 //!   the attestation results to this specific communication instance.
 //! - **Authentication (Implicit/Explicit)**: Depending on the chosen
 //!   `HandshakeType` (e.g., Noise KK, NK, NN patterns), parties may
-//!   authenticate each other using pre-shared static public keys or rely on
-//!   ephemeral key exchanges.
+//!   authenticate each other using pre-shared static public keys, exchange
+//!   their static public keys during the handshake (IK, XX patterns), or rely
+//!   on ephemeral key exchanges. The PSK variants additionally require both
+//!   parties to hold the same pre-shared symmetric key.
 //!
 //! ## Key Abstractions
 //!
 //! - **`HandshakeType`**: An enum specifying the desired Noise Protocol
-//!   Framework pattern (e.g., `NoiseKK`, `NoiseNK`, `NoiseNN`). The choice of
-//!   pattern determines how parties are authenticated and how keys are
-//!   exchanged.
+//!   Framework pattern (e.g., `NoiseKK`, `NoiseNK`, `NoiseNN`, `NoiseXX`). The
+//!   choice of pattern determines how parties are authenticated and how keys
+//!   are exchanged.
+//! - **`KeyExchange`**: Whether the Diffie-Hellman exchanges of the pattern are
+//!   combined with an ML-KEM-768 key encapsulation (hybrid post-quantum mode).
+//!   The encapsulated secret is mixed into the same Noise state, so the
+//!   `HandshakeResult` is used the same way in both modes.
 //! - **`HandshakeResult`**: A structure containing the outcomes of a successful
 //!   handshake:
 //!     - `session_keys`: The derived symmetric keys for encrypting session
@@ -92,7 +98,8 @@ use anyhow::{anyhow, Context, Error};
 use oak_crypto::{
     identity_key::IdentityKeyHandle,
     noise_handshake::{
-        client::HandshakeInitiator, respond_kk, respond_nk, respond_nn, OrderedCrypter, Response,
+        client::HandshakeInitiator, respond_ik, respond_kk, respond_nk, respond_nn, respond_xx,
+        NoiseMessage, OrderedCrypter, PendingResponse, Response, SYMMETRIC_KEY_LEN,
     },
 };
 use oak_proto_rust::oak::session::v1::{
@@ -103,6 +110,7 @@ use oak_proto_rust::oak::session::v1::{
 use crate::{
     attestation::AttestationState,
     config::HandshakeHandlerConfig,
+    resumption::{ResumptionRole, RESUMPTION_BINDING_ID},
     session_binding::{create_session_binding_token, SessionBinder},
     ProtocolEngine,
 };
@@ -117,6 +125,44 @@ pub enum HandshakeType {
     NoiseKN,
     NoiseNK,
     NoiseNN,
+    /// The client sends its static public key during the handshake, and must
+    /// know the server's static public key beforehand.
+    NoiseIK,
+    /// Same as `NoiseIK`, with a pre-shared key mixed into the handshake.
+    NoiseIKPsk2,
+    /// Both parties send their static public keys during the handshake. This
+    /// takes an additional message from the client.
+    NoiseXX,
+    /// Same as `NoiseXX`, with a pre-shared key mixed into the handshake.
+    NoiseXXPsk3,
+}
+
+impl HandshakeType {
+    /// Returns true if the handshake needs a final message from the client
+    /// after the server's response, regardless of session bindings.
+    fn has_final_client_message(&self) -> bool {
+        matches!(self, HandshakeType::NoiseXX | HandshakeType::NoiseXXPsk3)
+    }
+
+    /// Returns true if the handshake pattern mixes in a pre-shared key.
+    pub(crate) fn uses_pre_shared_key(&self) -> bool {
+        matches!(self, HandshakeType::NoiseIKPsk2 | HandshakeType::NoiseXXPsk3)
+    }
+}
+
+/// Specifies the key exchange used by the Noise handshake.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum KeyExchange {
+    /// Only the P-256 Diffie-Hellman exchanges of the handshake pattern. A
+    /// server configured this way still accepts hybrid handshakes offered by
+    /// the client.
+    #[default]
+    Classical,
+    /// The P-256 exchanges combined with an ML-KEM-768 key encapsulation, so
+    /// that recorded traffic stays confidential even if P-256 is later broken
+    /// (e.g., by a quantum computer). The client offers it in its initial
+    /// message, and both parties require it.
+    HybridMlKem768,
 }
 
 /// Holds the results of a successfully completed Oak Session handshake.
@@ -150,6 +196,10 @@ pub struct HandshakeState {
     pub peer_session_bindings: BTreeMap<String, SessionBinding>,
     /// Bindings for assertions received from the peer.
     pub peer_assertion_bindings: BTreeMap<String, SessionBinding>,
+    /// The static public key of the peer, if the handshake pattern uses one:
+    /// either the key configured beforehand, or the key received during the
+    /// handshake (IK, XX).
+    pub peer_static_public_key: Option<Vec<u8>>,
 }
 
 /// A trait for building a `HandshakeHandler` instance.
@@ -262,6 +312,9 @@ pub struct ClientHandshakeHandler {
     initial_message: Option<HandshakeRequest>,
     /// The follow-up handshake message sent to the server.
     followup_message: Option<HandshakeRequest>,
+    /// The static public key the server is expected to present, for handshake
+    /// patterns in which it's received during the handshake (XX).
+    expected_peer_static_public_key: Option<Vec<u8>>,
     /// The result of the completed handshake, containing the session keys and
     /// handshake hash.
     handshake_result: Option<HandshakeResult>,
@@ -284,6 +337,8 @@ impl ClientHandshakeHandler {
     ) -> anyhow::Result<Self> {
         let handshake_type = handshake_handler_config.handshake_type;
         let peer_static_public_key = handshake_handler_config.peer_static_public_key.clone();
+        let pre_shared_key =
+            pre_shared_key_for(handshake_type, handshake_handler_config.pre_shared_key)?;
         let mut handshake_initiator = match handshake_type {
             HandshakeType::NoiseKN => core::unimplemented!(),
             HandshakeType::NoiseKK => HandshakeInitiator::new_kk(
@@ -304,32 +359,53 @@ impl ClientHandshakeHandler {
                     .map_err(|error| anyhow!("invalid peer public key: {:?}", error))?,
             ),
             HandshakeType::NoiseNN => HandshakeInitiator::new_nn(),
+            HandshakeType::NoiseIK | HandshakeType::NoiseIKPsk2 => HandshakeInitiator::new_ik(
+                peer_static_public_key
+                    .context("no peer_static_public_key in handshake_handler_config")?
+                    .as_slice()
+                    .try_into()
+                    .map_err(|error| anyhow!("invalid peer public key: {:?}", error))?,
+                handshake_handler_config
+                    .self_static_private_key
+                    .context("no self_static_private_key in handshake_handler_config")?,
+                pre_shared_key,
+            ),
+            HandshakeType::NoiseXX | HandshakeType::NoiseXXPsk3 => HandshakeInitiator::new_xx(
+                handshake_handler_config
+                    .self_static_private_key
+                    .context("no self_static_private_key in handshake_handler_config")?,
+                pre_shared_key,
+            ),
         };
+        if handshake_handler_config.key_exchange == KeyExchange::HybridMlKem768 {
+            handshake_initiator = handshake_initiator.with_hybrid_kem();
+        }
         let initial_noise_message = handshake_initiator
             .build_initial_message()
             .map_err(|e| anyhow!("Error building initial message: {e:?}"))?;
         let initial_message = HandshakeRequest {
             r#handshake_type: Some(handshake_request::HandshakeType::NoiseHandshakeMessage(
-                NoiseHandshakeMessage {
-                    ephemeral_public_key: initial_noise_message.ephemeral_public_key,
-                    static_public_key: match handshake_type {
-                        HandshakeType::NoiseKK
-                        | HandshakeType::NoiseKN
-                        | HandshakeType::NoiseNK
-                        | HandshakeType::NoiseNN => vec![],
-                    },
-                    ciphertext: initial_noise_message.ciphertext,
-                },
+                noise_message_to_proto(initial_noise_message),
             )),
             attestation_bindings: BTreeMap::new(),
             ..Default::default()
         };
+        let session_binders = session_binders_for(
+            handshake_handler_config.session_binders,
+            &attestation_state,
+            ResumptionRole::Client,
+        );
         Ok(Self {
             handshake_initiator,
-            session_binders: handshake_handler_config.session_binders,
+            session_binders,
             attestation_state,
             initial_message: Some(initial_message),
             followup_message: None,
+            expected_peer_static_public_key: if handshake_type.has_final_client_message() {
+                handshake_handler_config.peer_static_public_key
+            } else {
+                None
+            },
             handshake_result: None,
         })
     }
@@ -413,9 +489,19 @@ impl ProtocolEngine<HandshakeResponse, HandshakeRequest> for ClientHandshakeHand
                             handshake_binding_token: handshake_hash.to_vec(),
                             peer_session_bindings: incoming_message.attestation_bindings,
                             peer_assertion_bindings: incoming_message.assertion_bindings,
+                            peer_static_public_key: self
+                                .handshake_initiator
+                                .peer_static_public_key()
+                                .map(|key| key.to_vec()),
                         },
                     })?;
-                if !self.session_binders.is_empty()
+                check_peer_static_public_key(
+                    self.expected_peer_static_public_key.as_deref(),
+                    handshake_result.handshake_state.peer_static_public_key.as_deref(),
+                )?;
+                let final_noise_message = self.handshake_initiator.take_final_message();
+                if final_noise_message.is_some()
+                    || !self.session_binders.is_empty()
                     || !self.attestation_state.self_assertions.is_empty()
                 {
                     let assertion_bound_data = create_session_binding_token(
@@ -423,7 +509,11 @@ impl ProtocolEngine<HandshakeResponse, HandshakeRequest> for ClientHandshakeHand
                         handshake_result.handshake_state.handshake_binding_token.as_slice(),
                     );
                     self.followup_message = Some(HandshakeRequest {
-                        r#handshake_type: None,
+                        r#handshake_type: final_noise_message.map(|noise_message| {
+                            handshake_request::HandshakeType::NoiseHandshakeMessage(
+                                noise_message_to_proto(noise_message),
+                            )
+                        }),
                         attestation_bindings: self
                             .session_binders
                             .iter()
@@ -481,6 +571,10 @@ pub struct ServerHandshakeHandler {
     /// Whether to expect a follow-up message with the client's session
     /// bindings.
     client_binding_expected: bool,
+    /// The pre-shared key, for the handshake patterns that use one.
+    pre_shared_key: Option<[u8; SYMMETRIC_KEY_LEN]>,
+    /// The key exchange required from the client.
+    key_exchange: KeyExchange,
     /// The state from the preceding attestation phase, which is carried through
     /// the handshake and used for binding attestation information to the
     /// session.
@@ -488,6 +582,9 @@ pub struct ServerHandshakeHandler {
     /// The Noise protocol response, kept temporarily if a follow-up message is
     /// expected from the client.
     noise_response: Option<Response>,
+    /// The Noise protocol state, kept until the final handshake message is
+    /// received from the client (XX).
+    pending_response: Option<PendingResponse>,
     /// The generated handshake response to be sent to the client.
     handshake_response: Option<HandshakeResponse>,
     /// The result of a successfully completed handshake.
@@ -509,18 +606,73 @@ impl ServerHandshakeHandler {
         client_binding_expected: bool,
         attestation_state: AttestationState,
     ) -> Self {
+        let session_binders = session_binders_for(
+            handshake_handler_config.session_binders,
+            &attestation_state,
+            ResumptionRole::Server,
+        );
         Self {
             handshake_type: handshake_handler_config.handshake_type,
             self_identity_key: handshake_handler_config.self_static_private_key,
             peer_public_key: handshake_handler_config.peer_static_public_key,
-            session_binders: handshake_handler_config.session_binders,
+            session_binders,
             client_binding_expected,
+            pre_shared_key: handshake_handler_config.pre_shared_key,
+            key_exchange: handshake_handler_config.key_exchange,
             attestation_state,
             noise_response: None,
+            pending_response: None,
             handshake_response: None,
             handshake_result: None,
         }
     }
+
+    /// Returns the client's static public key for a completed Noise response.
+    fn peer_static_public_key(&self, noise_response: &Response) -> Option<Vec<u8>> {
+        match self.handshake_type {
+            HandshakeType::NoiseKK => self.peer_public_key.clone(),
+            _ => noise_response.peer_static_public_key.map(|key| key.to_vec()),
+        }
+    }
+
+    /// Creates the response to the client's initial handshake message,
+    /// including the server's session bindings.
+    fn create_handshake_response(
+        &self,
+        noise_message: &NoiseMessage,
+        handshake_hash: &[u8],
+    ) -> Result<HandshakeResponse, Error> {
+        let assertion_bound_data = create_session_binding_token(
+            self.attestation_state.attestation_binding_token.as_slice(),
+            handshake_hash,
+        );
+        Ok(HandshakeResponse {
+            r#handshake_type: Some(handshake_response::HandshakeType::NoiseHandshakeMessage(
+                NoiseHandshakeMessage {
+                    ephemeral_public_key: noise_message.ephemeral_public_key.clone(),
+                    static_public_key: noise_message.static_public_key.clone(),
+                    ciphertext: noise_message.ciphertext.clone(),
+                    kem_public_key: noise_message.kem_public_key.clone(),
+                    kem_ciphertext: noise_message.kem_ciphertext.clone(),
+                },
+            )),
+            attestation_bindings: self
+                .session_binders
+                .iter()
+                .map(|(id, binder)| {
+                    (id.clone(), SessionBinding { binding: binder.bind(handshake_hash) })
+                })
+                .collect(),
+            assertion_bindings: self
+                .attestation_state
+                .self_assertions
+                .iter()
+                .map(|(id, assertion)| {
+                    Ok((id.clone(), assertion.bind(assertion_bound_data.as_slice())?))
+                })
+                .collect::<Result<BTreeMap<String, SessionBinding>, Error>>()?,
+        })
+    }
 }
 
 impl HandshakeHandler for ServerHandshakeHandler {
@@ -573,94 +725,192 @@ impl ProtocolEngine<HandshakeRequest, HandshakeResponse> for ServerHandshakeHand
         }
         if let Some(noise_response) = self.noise_response.take() {
             self.handshake_result = Some(HandshakeResult {
-                crypter: noise_response.crypter,
                 handshake_state: HandshakeState {
                     handshake_binding_token: noise_response.handshake_hash.to_vec(),
                     peer_session_bindings: incoming_message.attestation_bindings,
                     peer_assertion_bindings: incoming_message.assertion_bindings,
+                    peer_static_public_key: self.peer_static_public_key(&noise_response),
+                },
+                crypter: noise_response.crypter,
+            });
+        } else if let Some(pending_response) = self.pending_response.take() {
+            let final_noise_message = match incoming_message.r#handshake_type {
+                Some(handshake_request::HandshakeType::NoiseHandshakeMessage(noise_message)) => {
+                    noise_message
+                }
+                None => return Err(anyhow!("Missing final handshake message")),
+            };
+            let handshake_hash = pending_response.handshake_hash;
+            let (crypter, peer_static_public_key) = pending_response
+                .finish(&final_noise_message.into())
+                .map_err(|e| anyhow!("handshake finalization failed: {e:?}"))?;
+            check_peer_static_public_key(
+                self.peer_public_key.as_deref(),
+                Some(peer_static_public_key.as_slice()),
+            )?;
+            let (peer_session_bindings, peer_assertion_bindings) = if self.client_binding_expected {
+                (incoming_message.attestation_bindings, incoming_message.assertion_bindings)
+            } else {
+                (BTreeMap::new(), BTreeMap::new())
+            };
+            self.handshake_result = Some(HandshakeResult {
+                crypter,
+                handshake_state: HandshakeState {
+                    handshake_binding_token: handshake_hash.to_vec(),
+                    peer_session_bindings,
+                    peer_assertion_bindings,
+                    peer_static_public_key: Some(peer_static_public_key.to_vec()),
                 },
             });
         } else {
-            let noise_response = match incoming_message.r#handshake_type {
+            let noise_message: NoiseMessage = match incoming_message.r#handshake_type {
                 Some(handshake_request::HandshakeType::NoiseHandshakeMessage(noise_message)) => {
-                    match self.handshake_type {
-                        HandshakeType::NoiseKN => core::unimplemented!(),
-                        HandshakeType::NoiseKK => respond_kk(
-                            self.self_identity_key
-                                .as_ref()
-                                .context("no self_identity_key in handshake_handler_config")?
-                                .as_ref(),
-                            self.peer_public_key
-                                .as_ref()
-                                .ok_or(anyhow!("Must provide public key for Kk"))?,
-                            &noise_message.into(),
-                        )
-                        .map_err(|e| anyhow!("handshake response failed: {e:?}"))?,
-                        HandshakeType::NoiseNK => respond_nk(
-                            self.self_identity_key
-                                .as_ref()
-                                .context("no self_identity_key in handshake_handler_config")?
-                                .as_ref(),
-                            &noise_message.into(),
-                        )
-                        .map_err(|e| anyhow!("handshake response failed: {e:?}"))?,
-                        HandshakeType::NoiseNN => respond_nn(&noise_message.into())
-                            .map_err(|e| anyhow!("handshake response failed: {e:?}"))?,
-                    }
+                    noise_message.into()
                 }
                 None => return Err(anyhow!("Missing handshake_type")),
             };
-            let assertion_bound_data = create_session_binding_token(
-                self.attestation_state.attestation_binding_token.as_slice(),
-                noise_response.handshake_hash.as_slice(),
-            );
-            self.handshake_response = Some(HandshakeResponse {
-                r#handshake_type: Some(handshake_response::HandshakeType::NoiseHandshakeMessage(
-                    NoiseHandshakeMessage {
-                        ephemeral_public_key: noise_response.response.ephemeral_public_key.clone(),
-                        static_public_key: match self.handshake_type {
-                            HandshakeType::NoiseKK
-                            | HandshakeType::NoiseKN
-                            | HandshakeType::NoiseNK
-                            | HandshakeType::NoiseNN => vec![],
-                        },
-                        ciphertext: noise_response.response.ciphertext.clone(),
-                    },
-                )),
-                attestation_bindings: self
-                    .session_binders
-                    .iter()
-                    .map(|(id, binder)| {
-                        (
-                            id.clone(),
-                            SessionBinding {
-                                binding: binder.bind(noise_response.handshake_hash.as_slice()),
-                            },
-                        )
-                    })
-                    .collect(),
-                assertion_bindings: self
-                    .attestation_state
-                    .self_assertions
-                    .iter()
-                    .map(|(id, assertion)| {
-                        Ok((id.clone(), assertion.bind(assertion_bound_data.as_slice())?))
-                    })
-                    .collect::<Result<BTreeMap<String, SessionBinding>, Error>>()?,
-            });
+            if self.key_exchange == KeyExchange::HybridMlKem768
+                && noise_message.kem_public_key.is_empty()
+            {
+                return Err(anyhow!("the client didn't offer a hybrid key exchange"));
+            }
+            let pre_shared_key = pre_shared_key_for(self.handshake_type, self.pre_shared_key)?;
+            if self.handshake_type.has_final_client_message() {
+                let pending_response = respond_xx(
+                    self.self_identity_key
+                        .as_ref()
+                        .context("no self_identity_key in handshake_handler_config")?
+                        .as_ref(),
+                    pre_shared_key.as_ref(),
+                    &noise_message,
+                )
+                .map_err(|e| anyhow!("handshake response failed: {e:?}"))?;
+                self.handshake_response = Some(self.create_handshake_response(
+                    &pending_response.response,
+                    &pending_response.handshake_hash,
+                )?);
+                self.pending_response = Some(pending_response);
+                return Ok(Some(()));
+            }
+            let noise_response = match self.handshake_type {
+                HandshakeType::NoiseKN => core::unimplemented!(),
+                HandshakeType::NoiseKK => respond_kk(
+                    self.self_identity_key
+                        .as_ref()
+                        .context("no self_identity_key in handshake_handler_config")?
+                        .as_ref(),
+                    self.peer_public_key
+                        .as_ref()
+                        .ok_or(anyhow!("Must provide public key for Kk"))?,
+                    &noise_message,
+                )
+                .map_err(|e| anyhow!("handshake response failed: {e:?}"))?,
+                HandshakeType::NoiseNK => respond_nk(
+                    self.self_identity_key
+                        .as_ref()
+                        .context("no self_identity_key in handshake_handler_config")?
+                        .as_ref(),
+                    &noise_message,
+                )
+                .map_err(|e| anyhow!("handshake response failed: {e:?}"))?,
+                HandshakeType::NoiseNN => respond_nn(&noise_message)
+                    .map_err(|e| anyhow!("handshake response failed: {e:?}"))?,
+                HandshakeType::NoiseIK | HandshakeType::NoiseIKPsk2 => {
+                    let noise_response = respond_ik(
+                        self.self_identity_key
+                            .as_ref()
+                            .context("no self_identity_key in handshake_handler_config")?
+                            .as_ref(),
+                        pre_shared_key.as_ref(),
+                        &noise_message,
+                    )
+                    .map_err(|e| anyhow!("handshake response failed: {e:?}"))?;
+                    check_peer_static_public_key(
+                        self.peer_public_key.as_deref(),
+                        noise_response.peer_static_public_key.as_ref().map(|key| key.as_slice()),
+                    )?;
+                    noise_response
+                }
+                HandshakeType::NoiseXX | HandshakeType::NoiseXXPsk3 => {
+                    return Err(anyhow!("unexpected handshake type"))
+                }
+            };
+            self.handshake_response = Some(self.create_handshake_response(
+                &noise_response.response,
+                &noise_response.handshake_hash,
+            )?);
             if self.client_binding_expected {
                 self.noise_response = Some(noise_response);
             } else {
                 self.handshake_result = Some(HandshakeResult {
-                    crypter: noise_response.crypter,
                     handshake_state: HandshakeState {
                         handshake_binding_token: noise_response.handshake_hash.to_vec(),
                         peer_session_bindings: BTreeMap::new(),
                         peer_assertion_bindings: BTreeMap::new(),
+                        peer_static_public_key: self.peer_static_public_key(&noise_response),
                     },
+                    crypter: noise_response.crypter,
                 })
             }
         }
         Ok(Some(()))
     }
 }
+
+/// Returns the binders used to bind this party to the handshake.
+///
+/// In a resumed session, no evidence has been exchanged, so the configured
+/// `session_binders` are replaced by a single binder keyed by the resumption
+/// secret.
+fn session_binders_for(
+    session_binders: BTreeMap<String, Arc<dyn SessionBinder>>,
+    attestation_state: &AttestationState,
+    role: ResumptionRole,
+) -> BTreeMap<String, Arc<dyn SessionBinder>> {
+    match &attestation_state.resumed_session {
+        Some(resumed_session) => {
+            BTreeMap::from([(RESUMPTION_BINDING_ID.into(), resumed_session.session_binder(role))])
+        }
+        None => session_binders,
+    }
+}
+
+/// Returns the pre-shared key to use for `handshake_type`, failing if the
+/// pattern requires one and none is configured.
+fn pre_shared_key_for(
+    handshake_type: HandshakeType,
+    pre_shared_key: Option<[u8; SYMMETRIC_KEY_LEN]>,
+) -> Result<Option<[u8; SYMMETRIC_KEY_LEN]>, Error> {
+    if handshake_type.uses_pre_shared_key() {
+        pre_shared_key
+            .map(Some)
+            .ok_or(anyhow!("no pre_shared_key in handshake_handler_config for {handshake_type:?}"))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Checks that the static public key received from the peer matches the one
+/// configured for it, if any.
+fn check_peer_static_public_key(
+    expected_key: Option<&[u8]>,
+    received_key: Option<&[u8]>,
+) -> Result<(), Error> {
+    match (expected_key, received_key) {
+        (Some(expected_key), Some(received_key)) if expected_key != received_key => {
+            Err(anyhow!("the peer static public key doesn't match the configured one"))
+        }
+        (Some(_), None) => Err(anyhow!("the peer didn't provide a static public key")),
+        _ => Ok(()),
+    }
+}
+
+fn noise_message_to_proto(noise_message: NoiseMessage) -> NoiseHandshakeMessage {
+    NoiseHandshakeMessage {
+        ephemeral_public_key: noise_message.ephemeral_public_key,
+        static_public_key: noise_message.static_public_key,
+        ciphertext: noise_message.ciphertext,
+        kem_public_key: noise_message.kem_public_key,
+        kem_ciphertext: noise_message.kem_ciphertext,
+    }
+}
@@ -23,13 +23,17 @@ extern crate alloc;
 extern crate std;
 
 pub mod aggregators;
+pub mod alert;
 pub mod attestation;
 pub mod channel;
 pub mod config;
+pub mod encoding;
 pub mod encryptors;
 pub mod generator;
 pub mod handshake;
 pub mod key_extractor;
+pub mod rekey;
+pub mod resumption;
 pub mod session;
 pub mod session_binding;
 pub mod verifier;
@@ -0,0 +1,182 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! This module implements in-session rekeying.
+//!
+//! A Noise traffic key can only be used to encrypt a limited number of messages
+//! ([`MAX_SEQUENCE`]). Instead of tearing down a long-lived session and running
+//! the attestation and the handshake again, each party can switch the key for
+//! the messages it sends to a new key derived from the current one. The switch
+//! is signalled to the peer with a [`RekeyMessage`], which is authenticated
+//! with the previous key and processed by the peer in order with the encrypted
+//! messages, so that both parties switch keys at the same point of the message
+//! sequence.
+//!
+//! Rekeying is triggered either explicitly with [`Session::request_rekey`], or
+//! automatically according to the [`RekeyPolicy`] provided in the
+//! [`SessionConfig`]. Regardless of the policy, the session always rekeys
+//! before the current traffic key is exhausted.
+//!
+//! [`Session::request_rekey`]: crate::Session::request_rekey
+//! [`SessionConfig`]: crate::config::SessionConfig
+
+use alloc::{sync::Arc, vec};
+
+use anyhow::{anyhow, Context, Error};
+use oak_crypto::{encryptor::Encryptor, noise_handshake::MAX_SEQUENCE};
+use oak_proto_rust::oak::session::v1::RekeyMessage;
+use oak_time::{Clock, Duration, Instant};
+
+use crate::encryptors::{decrypt_message, encrypt_message, MessageKind};
+
+/// The maximum number of application messages encrypted with a single traffic
+/// key. One nonce is kept in reserve for the confirmation of the
+/// [`RekeyMessage`].
+const MAX_MESSAGES_PER_KEY: u64 = MAX_SEQUENCE as u64;
+
+/// Conditions under which a session automatically switches to a new traffic
+/// key for the messages it sends.
+///
+/// Each condition is evaluated before a message is encrypted, and the session
+/// rekeys as soon as any of them is met. By default no condition is set, and
+/// the session only rekeys before the traffic key is exhausted.
+#[derive(Clone, Default)]
+pub struct RekeyPolicy {
+    /// Rekey once this many messages have been encrypted with the current key.
+    max_messages: Option<u64>,
+    /// Rekey once at least this many plaintext bytes have been encrypted with
+    /// the current key.
+    max_bytes: Option<u64>,
+    /// Rekey once the current key is older than the given duration, as
+    /// measured by the provided clock. The age of a key is counted from the
+    /// first message encrypted with it.
+    max_key_age: Option<(Duration, Arc<dyn Clock>)>,
+}
+
+impl RekeyPolicy {
+    /// Rekeys after `max_messages` messages have been encrypted with the same
+    /// key.
+    pub fn with_max_messages(mut self, max_messages: u64) -> Self {
+        self.max_messages = Some(max_messages);
+        self
+    }
+
+    /// Rekeys after at least `max_bytes` plaintext bytes have been encrypted
+    /// with the same key.
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Rekeys once the same key has been used for longer than `max_key_age`,
+    /// according to `clock`.
+    pub fn with_max_key_age(mut self, max_key_age: Duration, clock: Arc<dyn Clock>) -> Self {
+        self.max_key_age = Some((max_key_age, clock));
+        self
+    }
+
+    /// Returns whether no condition is set.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.max_messages.is_none() && self.max_bytes.is_none() && self.max_key_age.is_none()
+    }
+}
+
+/// Tracks the usage of the current sending key and creates the
+/// [`RekeyMessage`]s sent to the peer.
+pub(crate) struct Rekeyer {
+    policy: RekeyPolicy,
+    /// Number of messages encrypted with the current key.
+    messages: u64,
+    /// Number of plaintext bytes encrypted with the current key.
+    bytes: u64,
+    /// Time at which the current key was first used. Only set if the policy
+    /// limits the age of the key.
+    key_first_used_at: Option<Instant>,
+}
+
+impl Rekeyer {
+    pub(crate) fn new(policy: RekeyPolicy) -> Self {
+        Self { policy, messages: 0, bytes: 0, key_first_used_at: None }
+    }
+
+    /// Returns whether the sending key has to be switched before encrypting
+    /// the next message.
+    pub(crate) fn needs_rekey(&mut self) -> bool {
+        let max_messages =
+            self.policy.max_messages.map_or(MAX_MESSAGES_PER_KEY, |m| m.min(MAX_MESSAGES_PER_KEY));
+        if self.messages >= max_messages {
+            return true;
+        }
+        if self.policy.max_bytes.is_some_and(|max_bytes| self.bytes >= max_bytes) {
+            return true;
+        }
+        if let Some((max_key_age, clock)) = &self.policy.max_key_age {
+            let now = clock.get_time();
+            let key_first_used_at = *self.key_first_used_at.get_or_insert(now);
+            return now - key_first_used_at >= *max_key_age;
+        }
+        false
+    }
+
+    /// Records that a plaintext message of `len` bytes has been encrypted with
+    /// the current key.
+    pub(crate) fn record_message(&mut self, len: usize) {
+        self.messages += 1;
+        self.bytes = self.bytes.saturating_add(len as u64);
+    }
+
+    /// Switches the sending key of `encryptor` and returns the
+    /// [`RekeyMessage`] that informs the peer about the switch.
+    ///
+    /// The message must be sent to the peer after all the messages encrypted
+    /// with the previous key, and before any message encrypted with the new
+    /// one.
+    pub(crate) fn rekey(
+        &mut self,
+        encryptor: &mut dyn Encryptor,
+        request_peer_rekey: bool,
+    ) -> Result<RekeyMessage, Error> {
+        let confirmation =
+            encrypt_message(encryptor, MessageKind::Rekey, vec![request_peer_rekey.into()])
+                .context("encrypting rekey confirmation")?;
+        encryptor.rekey_encryption().context("switching the sending key")?;
+        self.messages = 0;
+        self.bytes = 0;
+        self.key_first_used_at = None;
+        Ok(RekeyMessage { confirmation: Some(confirmation) })
+    }
+}
+
+/// Verifies a [`RekeyMessage`] received from the peer and switches the
+/// receiving key of `encryptor` accordingly.
+///
+/// Returns whether the peer asked for the sending key to be switched as well.
+pub(crate) fn process_rekey_message(
+    encryptor: &mut dyn Encryptor,
+    rekey_message: RekeyMessage,
+) -> Result<bool, Error> {
+    let confirmation =
+        rekey_message.confirmation.ok_or(anyhow!("rekey message has no confirmation"))?;
+    let plaintext = decrypt_message(encryptor, MessageKind::Rekey, confirmation)
+        .context("decrypting rekey confirmation")?;
+    let request_peer_rekey = match plaintext.message.as_slice() {
+        [0] => false,
+        [1] => true,
+        _ => return Err(anyhow!("unexpected rekey confirmation content")),
+    };
+    encryptor.rekey_decryption().context("switching the receiving key")?;
+    Ok(request_peer_rekey)
+}
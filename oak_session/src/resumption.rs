@@ -0,0 +1,352 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! This module implements session resumption, which allows a client to
+//! reconnect to a server without repeating the attestation.
+//!
+//! ## Overview
+//!
+//! Once a session is open, a server configured with a
+//! [`ResumptionTicketIssuer`] can send the client a resumption ticket over the
+//! encrypted channel (see [`ServerSession::issue_resumption_ticket`]). The
+//! ticket consists of:
+//! - an opaque part, which seals the attestation evidence the client supplied
+//!   in the current session, the current handshake hash, and an expiration time
+//!   with a key only known to the server;
+//! - a random resumption secret, which the server also seals into the opaque
+//!   part.
+//!
+//! The client keeps the ticket as a [`ResumptionTicket`] (see
+//! [`ClientSession::take_resumption_ticket`]) together with the attestation
+//! evidence the server supplied in the current session. When the client
+//! reconnects with the ticket (see
+//! [`SessionConfigBuilder::set_resumption_ticket`]), it sends the opaque part
+//! instead of its evidence. If the server accepts the ticket, neither party
+//! sends any evidence, and both restore the attestation evidence of the
+//! original session and verify it again with their current verifiers. The new
+//! Noise handshake is then bound to the original session by session bindings
+//! computed with the resumption secret, which proves that both parties took
+//! part in it.
+//!
+//! If the server can't accept the ticket (for example because it has expired,
+//! or because the evidence it contains isn't accepted by the current verifiers
+//! anymore), it performs a regular attestation. A client that has to be
+//! attested by the server needs to start a new session without a ticket in that
+//! case.
+//!
+//! [`ServerSession::issue_resumption_ticket`]: crate::ServerSession::issue_resumption_ticket
+//! [`ClientSession::take_resumption_ticket`]: crate::ClientSession::take_resumption_ticket
+//! [`SessionConfigBuilder::set_resumption_ticket`]: crate::config::SessionConfigBuilder::set_resumption_ticket
+
+use alloc::{boxed::Box, string::String, sync::Arc, vec, vec::Vec};
+use core::fmt::{self, Debug};
+
+use anyhow::{anyhow, Context, Error};
+use oak_crypto::noise_handshake::{
+    aes_256_gcm_open_in_place, aes_256_gcm_seal_in_place, hkdf_sha256, rand_bytes, NONCE_LEN,
+    SHA256_OUTPUT_LEN, SYMMETRIC_KEY_LEN,
+};
+use oak_proto_rust::oak::session::v1::{NewResumptionTicket, ResumptionTicketContents};
+use oak_time::{Clock, Duration, Instant};
+use prost::Message;
+use zeroize::Zeroizing;
+
+use crate::{
+    session::AttestationEvidence,
+    session_binding::{SessionBinder, SessionBindingVerifier},
+};
+
+/// The ID under which the resumption session bindings are exchanged during the
+/// handshake of a resumed session.
+pub const RESUMPTION_BINDING_ID: &str = "oak.session.resumption";
+
+/// The length of the resumption secret shared by the client and the server.
+const RESUMPTION_SECRET_LEN: usize = 32;
+
+/// Associated data used when sealing the contents of a ticket, so that the
+/// ticket key can't be confused with keys used for other purposes.
+const TICKET_ASSOCIATED_DATA: &[u8] = b"oak.session.v1.ResumptionTicketContents";
+
+/// Labels that separate the session bindings created by the client from the
+/// ones created by the server, so that a binding can't be reflected back to
+/// the party that created it.
+const CLIENT_BINDING_LABEL: &[u8] = b"oak.session.resumption.client";
+const SERVER_BINDING_LABEL: &[u8] = b"oak.session.resumption.server";
+
+/// A resumption ticket received by a client, which can be used to open a new
+/// session with the same server without repeating the attestation.
+#[derive(Clone)]
+pub struct ResumptionTicket {
+    /// The opaque ticket to be presented to the server.
+    ticket: Vec<u8>,
+    /// The secret shared with the server.
+    resumption_secret: Zeroizing<Vec<u8>>,
+    /// The attestation evidence supplied by the server in the session the
+    /// ticket was issued in.
+    peer_attestation_evidence: AttestationEvidence,
+}
+
+impl ResumptionTicket {
+    pub(crate) fn new(
+        new_resumption_ticket: NewResumptionTicket,
+        peer_attestation_evidence: AttestationEvidence,
+    ) -> Result<Self, Error> {
+        anyhow::ensure!(!new_resumption_ticket.ticket.is_empty(), "empty resumption ticket");
+        anyhow::ensure!(
+            new_resumption_ticket.resumption_secret.len() == RESUMPTION_SECRET_LEN,
+            "invalid resumption secret length: {}",
+            new_resumption_ticket.resumption_secret.len()
+        );
+        Ok(Self {
+            ticket: new_resumption_ticket.ticket,
+            resumption_secret: Zeroizing::new(new_resumption_ticket.resumption_secret),
+            peer_attestation_evidence,
+        })
+    }
+
+    /// Returns the attestation evidence supplied by the server in the session
+    /// the ticket was issued in.
+    pub fn peer_attestation_evidence(&self) -> &AttestationEvidence {
+        &self.peer_attestation_evidence
+    }
+
+    /// Returns the opaque ticket to be sent to the server.
+    pub(crate) fn ticket(&self) -> &[u8] {
+        self.ticket.as_slice()
+    }
+
+    /// Returns the state to restore once the server has accepted the ticket.
+    pub(crate) fn resume(&self) -> ResumedSession {
+        ResumedSession {
+            resumption_secret: self.resumption_secret.clone(),
+            peer_attestation_evidence: self.peer_attestation_evidence.clone(),
+        }
+    }
+}
+
+impl Debug for ResumptionTicket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResumptionTicket")
+            .field("peer_attestation_evidence", &self.peer_attestation_evidence)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Issues and redeems resumption tickets on the server side.
+///
+/// Tickets are sealed with a symmetric ticket key. A fleet of servers that
+/// share the same ticket key accept each other's tickets.
+pub struct ResumptionTicketIssuer {
+    ticket_key: Zeroizing<[u8; SYMMETRIC_KEY_LEN]>,
+    lifetime: Duration,
+    clock: Arc<dyn Clock>,
+}
+
+impl ResumptionTicketIssuer {
+    /// Creates a new issuer that seals tickets with `ticket_key`. Issued
+    /// tickets are accepted for `lifetime`, as measured by `clock`.
+    pub fn new(
+        ticket_key: [u8; SYMMETRIC_KEY_LEN],
+        lifetime: Duration,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        Self { ticket_key: Zeroizing::new(ticket_key), lifetime, clock }
+    }
+
+    /// Creates a new ticket for a session in which the client supplied
+    /// `peer_attestation_evidence`.
+    pub(crate) fn issue(
+        &self,
+        peer_attestation_evidence: &AttestationEvidence,
+    ) -> Result<NewResumptionTicket, Error> {
+        let mut resumption_secret = vec![0u8; RESUMPTION_SECRET_LEN];
+        rand_bytes(&mut resumption_secret);
+        let expires_at = self.clock.get_time() + self.lifetime;
+        let contents = ResumptionTicketContents {
+            resumption_secret: resumption_secret.clone(),
+            expires_at_unix_millis: expires_at.into_unix_millis(),
+            evidence: peer_attestation_evidence.evidence.clone(),
+            evidence_bindings: peer_attestation_evidence.evidence_bindings.clone(),
+            assertions: peer_attestation_evidence.assertions.clone(),
+            assertion_bindings: peer_attestation_evidence.assertion_bindings.clone(),
+            handshake_hash: peer_attestation_evidence.handshake_hash.clone(),
+        };
+
+        let mut nonce = [0u8; NONCE_LEN];
+        rand_bytes(&mut nonce);
+        let mut sealed_contents = contents.encode_to_vec();
+        aes_256_gcm_seal_in_place(
+            &self.ticket_key,
+            &nonce,
+            TICKET_ASSOCIATED_DATA,
+            &mut sealed_contents,
+        );
+        Ok(NewResumptionTicket {
+            ticket: [nonce.as_slice(), sealed_contents.as_slice()].concat(),
+            resumption_secret,
+        })
+    }
+
+    /// Opens a ticket presented by a client, and returns the state of the
+    /// session it was issued in.
+    ///
+    /// Fails if the ticket wasn't issued with the same ticket key, or if it
+    /// has expired.
+    pub(crate) fn redeem(&self, ticket: &[u8]) -> Result<ResumedSession, Error> {
+        anyhow::ensure!(ticket.len() > NONCE_LEN, "resumption ticket is too short");
+        let (nonce, sealed_contents) = ticket.split_at(NONCE_LEN);
+        let contents = Zeroizing::new(
+            aes_256_gcm_open_in_place(
+                &self.ticket_key,
+                nonce.try_into().context("invalid resumption ticket nonce")?,
+                TICKET_ASSOCIATED_DATA,
+                sealed_contents.to_vec(),
+            )
+            .map_err(|_| anyhow!("couldn't open resumption ticket"))?,
+        );
+        let contents = ResumptionTicketContents::decode(contents.as_slice())
+            .map_err(|err| anyhow!("decoding resumption ticket contents: {err:?}"))?;
+
+        let expires_at = Instant::from_unix_millis(contents.expires_at_unix_millis);
+        anyhow::ensure!(self.clock.get_time() < expires_at, "resumption ticket has expired");
+        anyhow::ensure!(
+            contents.resumption_secret.len() == RESUMPTION_SECRET_LEN,
+            "invalid resumption secret length: {}",
+            contents.resumption_secret.len()
+        );
+        Ok(ResumedSession {
+            resumption_secret: Zeroizing::new(contents.resumption_secret),
+            peer_attestation_evidence: AttestationEvidence {
+                evidence: contents.evidence,
+                evidence_bindings: contents.evidence_bindings,
+                assertions: contents.assertions,
+                assertion_bindings: contents.assertion_bindings,
+                handshake_hash: contents.handshake_hash,
+            },
+        })
+    }
+}
+
+/// The role of a party in a resumed session, used to tell apart the session
+/// bindings created by each of them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ResumptionRole {
+    Client,
+    Server,
+}
+
+impl ResumptionRole {
+    fn binding_label(self) -> &'static [u8] {
+        match self {
+            ResumptionRole::Client => CLIENT_BINDING_LABEL,
+            ResumptionRole::Server => SERVER_BINDING_LABEL,
+        }
+    }
+
+    fn peer(self) -> Self {
+        match self {
+            ResumptionRole::Client => ResumptionRole::Server,
+            ResumptionRole::Server => ResumptionRole::Client,
+        }
+    }
+}
+
+/// The state of a session restored from a resumption ticket.
+pub struct ResumedSession {
+    resumption_secret: Zeroizing<Vec<u8>>,
+    peer_attestation_evidence: AttestationEvidence,
+}
+
+impl ResumedSession {
+    /// Returns the attestation evidence supplied by the peer in the session
+    /// the ticket was issued in.
+    pub fn peer_attestation_evidence(&self) -> &AttestationEvidence {
+        &self.peer_attestation_evidence
+    }
+
+    /// Creates the binder used by a party with the given `role` to bind the
+    /// new handshake to the resumed session.
+    pub fn session_binder(&self, role: ResumptionRole) -> Arc<dyn SessionBinder> {
+        Arc::new(ResumptionBinder {
+            resumption_secret: self.resumption_secret.clone(),
+            label: role.binding_label(),
+        })
+    }
+
+    /// Creates the verifier used by a party with the given `role` to check the
+    /// bindings created by its peer.
+    pub fn session_binding_verifier(
+        &self,
+        role: ResumptionRole,
+    ) -> Box<dyn SessionBindingVerifier> {
+        Box::new(ResumptionBinder {
+            resumption_secret: self.resumption_secret.clone(),
+            label: role.peer().binding_label(),
+        })
+    }
+}
+
+impl Debug for ResumedSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResumedSession")
+            .field("peer_attestation_evidence", &self.peer_attestation_evidence)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Binds the handshake of a resumed session with a MAC keyed by the resumption
+/// secret.
+struct ResumptionBinder {
+    resumption_secret: Zeroizing<Vec<u8>>,
+    label: &'static [u8],
+}
+
+impl ResumptionBinder {
+    fn mac(&self, bound_data: &[u8]) -> [u8; SHA256_OUTPUT_LEN] {
+        let mut mac = [0u8; SHA256_OUTPUT_LEN];
+        hkdf_sha256(&self.resumption_secret, bound_data, self.label, &mut mac)
+            .expect("the MAC length is always valid for HKDF-SHA256");
+        mac
+    }
+}
+
+impl SessionBinder for ResumptionBinder {
+    fn bind(&self, bound_data: &[u8]) -> Vec<u8> {
+        self.mac(bound_data).to_vec()
+    }
+}
+
+impl SessionBindingVerifier for ResumptionBinder {
+    fn verify_binding(&self, bound_data: &[u8], binding: &[u8]) -> Result<(), Error> {
+        let expected = self.mac(bound_data);
+        // Compare in constant time, so that the comparison doesn't reveal how
+        // much of the binding is correct.
+        let difference = expected
+            .iter()
+            .zip(binding.iter())
+            .fold((expected.len() != binding.len()) as u8, |acc, (a, b)| acc | (a ^ b));
+        anyhow::ensure!(difference == 0, "invalid resumption session binding");
+        Ok(())
+    }
+}
+
+impl Debug for ResumptionBinder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResumptionBinder")
+            .field("label", &String::from_utf8_lossy(self.label))
+            .finish_non_exhaustive()
+    }
+}
@@ -70,23 +70,31 @@ use oak_crypto::{encryptor::Encryptor, noise_handshake::session_binding_token_ha
 use oak_proto_rust::oak::{
     attestation::v1::Assertion,
     session::v1::{
-        session_request::Request, session_response::Response, EncryptedMessage, EndorsedEvidence,
-        PlaintextMessage, SessionBinding, SessionRequest, SessionResponse,
+        alert_message::Alert, session_request::Request, session_response::Response, AlertMessage,
+        EndorsedEvidence, NewResumptionTicket, PlaintextMessage, SessionBinding, SessionRequest,
+        SessionResponse,
     },
 };
 use prost::Message;
 
 use crate::{
+    alert::{
+        decrypt_alert, encrypt_alert, failure_alert, peer_alert_error, plaintext_alert,
+        AlertReason, SessionAlert,
+    },
     attestation::{
         AttestationHandler, AttestationState, ClientAttestationHandler, PeerAttestationVerdict,
         ServerAttestationHandler, VerifierResult,
     },
-    config::{EncryptorProvider, SessionConfig},
+    config::{EncodingEncryptorProvider, EncryptorProvider, SessionConfig},
+    encryptors::{decrypt_message, encrypt_message, DiscardedMessageError, MessageKind},
     handshake::{
         ClientHandshakeHandler, ClientHandshakeHandlerBuilder, HandshakeHandler,
         HandshakeHandlerBuilder, HandshakeState, ServerHandshakeHandler,
         ServerHandshakeHandlerBuilder,
     },
+    rekey::{process_rekey_message, Rekeyer},
+    resumption::{ResumptionTicket, ResumptionTicketIssuer},
     session_binding::{create_session_binding_token, SessionBindingVerifier},
     verifier::BoundAssertionVerifierResult,
     ProtocolEngine,
@@ -135,7 +143,7 @@ impl SessionBindingToken {
 /// Represents the evidence supplied by the peer during the attestation phase.
 ///
 /// This evidence is used to verify the peer's identity and trustworthiness.
-#[derive(Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct AttestationEvidence {
     /// The evidence supplied by the peer (in the legacy format).
     ///
@@ -173,7 +181,8 @@ pub trait AttestationPublisher: Send + Sync {
 ///
 /// If any method returns an `Error`, the session is considered compromised or
 /// in an error and a new session needs to be started (because the state-machine
-/// is in an incorrect state).
+/// is in an incorrect state). Failures caused by the messages of the peer are
+/// reported to it with an alert, see [`crate::alert`].
 pub trait Session: Send {
     /// Checks if the session is open and ready for secure communication.
     ///
@@ -220,8 +229,65 @@ pub trait Session: Send {
     /// Returns the attestation evidence for this session, as supplied by the
     /// peer.
     ///
+    /// For a resumed session, this is the evidence supplied in the session the
+    /// resumption ticket was issued in, including its handshake hash.
+    ///
     /// This method can only be called successfully when `is_open()` is true.
     fn get_peer_attestation_evidence(&self) -> Result<AttestationEvidence, Error>;
+
+    /// Returns the static public key of the peer, for the handshake patterns
+    /// that use one: either the key configured beforehand (e.g., Noise KK), or
+    /// the key the peer presented during the handshake (e.g., Noise IK, XX).
+    ///
+    /// This method can only be called successfully when `is_open()` is true.
+    fn get_peer_static_public_key(&self) -> Result<Option<Vec<u8>>, Error>;
+
+    /// Switches both directions of the session to new traffic keys derived from
+    /// the current ones.
+    ///
+    /// The sending key is switched immediately, and a rekey message asking the
+    /// peer to switch its own sending key is queued. Messages written after
+    /// this call are encrypted with the new key. The peer's new key is used as
+    /// soon as its rekey message is processed by `read()`.
+    ///
+    /// Sessions also rekey automatically according to the configured
+    /// [`RekeyPolicy`](crate::rekey::RekeyPolicy).
+    ///
+    /// This method can only be called successfully when `is_open()` is true.
+    fn request_rekey(&mut self) -> Result<(), Error>;
+
+    /// Closes the session in an orderly way.
+    ///
+    /// Queues a close notification for the peer, after which no further
+    /// messages can be written. Messages sent by the peer can still be read
+    /// until it closes the session as well.
+    ///
+    /// This method can only be called successfully when `is_open()` is true.
+    fn close(&mut self) -> Result<(), Error>;
+
+    /// Ends the session with a fatal alert that tells the peer why the session
+    /// is abandoned, e.g. [`AlertReason::RekeyRequired`] if the peer doesn't
+    /// rekey as often as the application expects.
+    ///
+    /// The alert is queued after the messages that have already been written,
+    /// and becomes the last outgoing message. No further messages can be
+    /// written or read. Use `close()` for an orderly shutdown instead.
+    fn abort(&mut self, reason: AlertReason, description: &str) -> Result<(), Error>;
+
+    /// Returns the alert with which the peer ended the session, if any.
+    ///
+    /// Alerts are processed in order with the other messages of the peer: by
+    /// `put_incoming_message()` before the session is open, and by `read()`
+    /// afterwards.
+    fn get_peer_alert(&self) -> Option<&SessionAlert>;
+
+    /// Checks if the peer has closed the session in an orderly way.
+    ///
+    /// If the transport carrying the session ends while this returns false,
+    /// the last messages of the peer may have been cut off.
+    fn is_peer_closed(&self) -> bool {
+        self.get_peer_alert().is_some_and(|alert| alert.reason() == AlertReason::CloseNotify)
+    }
 }
 
 /// Represents the internal state machine and data for a session's progression.
@@ -339,8 +405,10 @@ impl<AP: AttestationHandler, H: HandshakeHandler> Step<AP, H> {
                 // be bound to the session. Even if the evidence hasn't been verified the peer
                 // is expected (and required) to send the bindings for the evidence that it
                 // supplies.
+                // A resumed session is always bound with the resumption secret.
                 let expect_peer_bindings =
-                    attestation_state.peer_attestation_verdict.needs_session_bindings();
+                    attestation_state.peer_attestation_verdict.needs_session_bindings()
+                        || attestation_state.resumed_session.is_some();
                 let handshaker = match handshake_handler_provider
                     .build(expect_peer_bindings, attestation_state)
                 {
@@ -433,6 +501,19 @@ impl<AP: AttestationHandler, H: HandshakeHandler> Step<AP, H> {
         }
     }
 
+    /// Returns the static public key of the peer, as established by the
+    /// handshake.
+    ///
+    /// This method can only be called successfully when `is_open()` is true.
+    fn get_peer_static_public_key(&self) -> Result<Option<Vec<u8>>, Error> {
+        match &self {
+            Step::Open { handshake_state, .. } => {
+                Ok(handshake_state.peer_static_public_key.clone())
+            }
+            _ => Err(anyhow!("the session is not open")),
+        }
+    }
+
     /// Returns the attestation results for this session.
     ///
     /// This method can only be called successfully when `is_open()` is true.
@@ -452,6 +533,47 @@ impl<AP: AttestationHandler, H: HandshakeHandler> Step<AP, H> {
             _ => Err(anyhow!("the session is not open")),
         }
     }
+
+    /// Moves the session to the `Error` state after a failure.
+    ///
+    /// If a `reason` is provided, returns an alert that reports the failure to
+    /// the peer, encrypted with the session keys if the session was open. The
+    /// attestation evidence of the peer remains available.
+    fn fail(&mut self, reason: Option<AlertReason>, description: String) -> Option<AlertMessage> {
+        let (attestation_state, handshake_state, encryptor) =
+            match mem::replace(self, Step::Invalid) {
+                Step::Open { encryptor, attestation_state, handshake_state } => {
+                    (Some(attestation_state), Some(handshake_state), Some(encryptor))
+                }
+                Step::Error { attestation_state, handshake_state } => {
+                    (attestation_state, handshake_state, None)
+                }
+                _ => (None, None, None),
+            };
+        // The peer attestation verdict is only final before the handshake starts.
+        let peer_attestation_verdict = attestation_state
+            .as_ref()
+            .filter(|_| handshake_state.is_none())
+            .map(|attestation_state| &attestation_state.peer_attestation_verdict);
+        let alert =
+            reason.map(|reason| failure_alert(reason, description, peer_attestation_verdict));
+        *self = Step::Error { attestation_state, handshake_state };
+        match (alert, encryptor) {
+            (Some(alert), Some(mut encryptor)) => encrypt_alert(encryptor.as_mut(), &alert).ok(),
+            (Some(alert), None) => Some(plaintext_alert(alert)),
+            (None, _) => None,
+        }
+    }
+
+    /// Returns the reason reported to the peer if the session fails in the
+    /// current step, or `None` if it has already failed.
+    fn failure_reason(&self) -> Option<AlertReason> {
+        match self {
+            Step::Handshake { .. } => Some(AlertReason::HandshakeFailed),
+            Step::Attestation { .. } | Step::Open { .. } => Some(AlertReason::ProtocolError),
+            Step::Error { .. } | Step::Invalid => None,
+        }
+    }
 }
 
 /// Client-side implementation of an end-to-end secure attested session.
@@ -470,6 +592,15 @@ pub struct ClientSession {
     /// up to the session layer but not yet decrypted and read by the
     /// application.
     incoming_responses: VecDeque<SessionResponse>,
+    /// Keeps track of when the sending key needs to be replaced.
+    rekeyer: Rekeyer,
+    /// The most recent resumption ticket received from the server.
+    resumption_ticket: Option<ResumptionTicket>,
+    /// Whether the session has been closed, after which no further messages
+    /// are sent.
+    closed: bool,
+    /// The alert with which the server ended the session, if any.
+    peer_alert: Option<SessionAlert>,
 }
 
 impl ClientSession {
@@ -481,6 +612,8 @@ impl ClientSession {
     /// state. The lifetimes of objects within `config` (e.g., keys in
     /// `HandshakeHandlerConfig`) are now managed by the `ClientSession`.
     pub fn create(config: SessionConfig) -> Result<Self, Error> {
+        config.encryptor_config.validate()?;
+        let encoder = config.encryptor_config.encoder();
         Ok(Self {
             step: Step::Attestation {
                 attester: ClientAttestationHandler::create(config.attestation_handler_config)?,
@@ -488,93 +621,125 @@ impl ClientSession {
                 handshake_handler_provider: Box::new(ClientHandshakeHandlerBuilder {
                     config: config.handshake_handler_config,
                 }),
-                encryptor_provider: config.encryptor_config.encryptor_provider,
+                encryptor_provider: EncodingEncryptorProvider::create(
+                    config.encryptor_config.encryptor_provider,
+                    encoder,
+                ),
             },
             outgoing_requests: VecDeque::new(),
             incoming_responses: VecDeque::new(),
+            rekeyer: Rekeyer::new(config.encryptor_config.rekey_policy),
+            resumption_ticket: None,
+            closed: false,
+            peer_alert: None,
         })
     }
-}
 
-impl Session for ClientSession {
-    /// Checks if the client session is open. See `Session::is_open`.
-    fn is_open(&self) -> bool {
-        matches!(self.step, Step::Open { .. })
+    /// Takes the most recent resumption ticket received from the server, if
+    /// any.
+    ///
+    /// Tickets are processed by `read()`, in order with the other messages
+    /// from the server. The ticket can be provided to
+    /// [`SessionConfigBuilder::set_resumption_ticket`] to open a new session
+    /// with the server without repeating the attestation.
+    ///
+    /// [`SessionConfigBuilder::set_resumption_ticket`]: crate::config::SessionConfigBuilder::set_resumption_ticket
+    pub fn take_resumption_ticket(&mut self) -> Option<ResumptionTicket> {
+        self.resumption_ticket.take()
     }
 
-    /// Encrypts and queues a message for the server. See `Session::write`.
-    fn write(&mut self, plaintext: PlaintextMessage) -> Result<(), Error> {
-        match &mut self.step {
-            Step::Open { encryptor, .. } => {
-                anyhow::ensure!(
-                    self.outgoing_requests.len() < DEFAULT_MAX_MESSAGE_QUEUE_LEN,
-                    "Message queue limit exceeded"
-                );
-                let encrypted_message: EncryptedMessage = encryptor
-                    .encrypt(plaintext.into())
-                    .map(From::from)
-                    .context("encrypting plaintext")?;
-                self.outgoing_requests.push_back(SessionRequest {
-                    request: Some(Request::EncryptedMessage(encrypted_message)),
-                });
-                Ok(())
-            }
-            _ => Err(anyhow!("the session is not open")),
+    /// Moves the session to the `Error` state after `err`, replacing the
+    /// queued messages with an alert that reports the failure to the server.
+    /// Failures caused by an alert from the server aren't reported back.
+    fn fail(&mut self, reason: Option<AlertReason>, err: &Error) {
+        let reason = reason.filter(|_| self.peer_alert.is_none());
+        // Messages encrypted before the failure must still be delivered, as the
+        // peer can't decrypt the alert that follows them otherwise.
+        if !matches!(self.step, Step::Open { .. }) {
+            self.outgoing_requests.clear();
+        }
+        if let Some(alert_message) = self.step.fail(reason, format!("{err}")) {
+            self.outgoing_requests
+                .push_back(SessionRequest { request: Some(Request::AlertMessage(alert_message)) });
         }
     }
 
-    /// Reads and decrypts a message from the server. See `Session::read`.
-    fn read(&mut self) -> Result<Option<PlaintextMessage>, Error> {
+    /// Decrypts the next message from the server. See `Session::read`.
+    fn read_next_message(&mut self) -> Result<Option<PlaintextMessage>, Error> {
         match &mut self.step {
-            Step::Open { encryptor, .. } => match self.incoming_responses.pop_front() {
-                Some(response) => {
-                    let encrypted_message = match response.response {
-                        Some(Response::EncryptedMessage(encrypted_message)) => encrypted_message,
+            Step::Open { encryptor, attestation_state, handshake_state } => {
+                while let Some(response) = self.incoming_responses.pop_front() {
+                    anyhow::ensure!(
+                        self.peer_alert.is_none(),
+                        "message received after the server closed the session"
+                    );
+                    match response.response {
+                        Some(Response::EncryptedMessage(encrypted_message)) => {
+                            let result = decrypt_message(
+                                encryptor.as_mut(),
+                                MessageKind::ApplicationData,
+                                encrypted_message,
+                            );
+                            // Replayed messages are dropped without ending the session.
+                            if result.as_ref().is_err_and(|err| err.is::<DiscardedMessageError>()) {
+                                continue;
+                            }
+                            return Ok(Some(
+                                result.map(From::from).context("decrypting plaintext")?,
+                            ));
+                        }
+                        Some(Response::RekeyMessage(rekey_message)) => {
+                            if process_rekey_message(encryptor.as_mut(), rekey_message)?
+                                && !self.closed
+                            {
+                                let rekey_message =
+                                    self.rekeyer.rekey(encryptor.as_mut(), false)?;
+                                self.outgoing_requests.push_back(SessionRequest {
+                                    request: Some(Request::RekeyMessage(rekey_message)),
+                                });
+                            }
+                        }
+                        Some(Response::ResumptionTicket(encrypted_ticket)) => {
+                            let plaintext = decrypt_message(
+                                encryptor.as_mut(),
+                                MessageKind::ResumptionTicket,
+                                encrypted_ticket,
+                            )
+                            .context("decrypting resumption ticket")?;
+                            let new_resumption_ticket = NewResumptionTicket::decode(
+                                plaintext.message.as_slice(),
+                            )
+                            .map_err(|err| anyhow!("decoding resumption ticket: {err:?}"))?;
+                            self.resumption_ticket = Some(ResumptionTicket::new(
+                                new_resumption_ticket,
+                                AttestationEvidence::new_from_state(
+                                    attestation_state,
+                                    handshake_state,
+                                ),
+                            )?);
+                        }
+                        Some(Response::AlertMessage(alert_message)) => {
+                            let alert = decrypt_alert(encryptor.as_mut(), alert_message)?;
+                            let peer_alert = self.peer_alert.insert(alert);
+                            if peer_alert.reason() != AlertReason::CloseNotify {
+                                return Err(peer_alert_error(peer_alert));
+                            }
+                        }
                         _ => {
                             return Err(anyhow!(
                                 "unexpected content of SessionResponse: no encrypted message set"
                             ));
                         }
-                    };
-                    Ok(Some(
-                        encryptor
-                            .decrypt(encrypted_message.into())
-                            .map(From::from)
-                            .context("decrypting plaintext")?,
-                    ))
+                    }
                 }
-                None => Ok(None),
-            },
+                Ok(None)
+            }
             _ => Err(anyhow!("the session is not open")),
         }
     }
 
-    /// Gets a session binding token. See `Session::get_session_binding_token`.
-    fn get_session_binding_token(&self, info_string: &[u8]) -> Result<SessionBindingToken, Error> {
-        self.step.get_session_binding_token(info_string)
-    }
-
-    /// Gets the peer attestation evidence. See
-    /// `Session::get_peer_attestation_evidence`.
-    fn get_peer_attestation_evidence(&self) -> Result<AttestationEvidence, Error> {
-        self.step.get_peer_attestation_evidence()
-    }
-}
-
-impl ProtocolEngine<SessionResponse, SessionRequest> for ClientSession {
-    /// Gets the next outgoing `SessionRequest` to be sent to the server.
-    ///
-    /// Depending on the current `step`:
-    /// - `Attestation`: Returns an `AttestRequest` from the
-    ///   `ClientAttestationHandler`.
-    /// - `Handshake`: Returns a `HandshakeRequest` from the
-    ///   `ClientHandshakeHandler`. If the handshake completes as a result,
-    ///   transitions to `Open`.
-    /// - `Open`: Returns an `EncryptedMessage` (application data) from
-    ///   `outgoing_requests`.
-    ///
-    /// If no message is ready, returns `Ok(None)`.
-    fn get_outgoing_message(&mut self) -> Result<Option<SessionRequest>, Error> {
+    // Implements `ProtocolEngine::get_outgoing_message`.
+    fn next_outgoing_message(&mut self) -> Result<Option<SessionRequest>, Error> {
         match &mut self.step {
             Step::Attestation { attester, .. } => {
                 if let Some(attest_message) = attester.get_outgoing_message()? {
@@ -599,30 +764,20 @@ impl ProtocolEngine<SessionResponse, SessionRequest> for ClientSession {
             }
             Step::Open { .. } => {}
             Step::Invalid | Step::Error { .. } => {
-                return Err(anyhow!("session is in an invalid state"))
+                // The alert reporting the failure of the session may still be pending.
+                return self
+                    .outgoing_requests
+                    .pop_front()
+                    .map(Some)
+                    .ok_or(anyhow!("session is in an invalid state"));
             }
         }
 
         Ok(self.outgoing_requests.pop_front())
     }
 
-    /// Processes an incoming `SessionResponse` from the server.
-    ///
-    /// Depending on the current `step` and message type:
-    /// - `Attestation` + `AttestResponse`: Passes to
-    ///   `ClientAttestationHandler`. If attestation completes, transitions to
-    ///   `Handshake`.
-    /// - `Handshake` + `HandshakeResponse`: Passes to `ClientHandshakeHandler`.
-    ///   Verifies server's session bindings using the
-    ///   `SessionBindingVerifierProvider` from the configured
-    ///   `PeerAttestationVerifier` and `attestation_results`. If handshake
-    ///   completes, transitions to `Open`.
-    /// - `Open` + `EncryptedMessage`: Queues in `incoming_responses` for
-    ///   `read()`.
-    ///
-    /// Returns `Ok(Some(()))` if processed, `Err` on mismatch or protocol
-    /// error.
-    fn put_incoming_message(
+    // Implements `ProtocolEngine::put_incoming_message`.
+    fn process_incoming_message(
         &mut self,
         incoming_message: SessionResponse,
     ) -> Result<Option<()>, Error> {
@@ -657,9 +812,21 @@ impl ProtocolEngine<SessionResponse, SessionRequest> for ClientSession {
                 Ok(Some(()))
             }
             (
-                im @ SessionResponse { response: Some(Response::EncryptedMessage(_)) },
+                im @ SessionResponse {
+                    response:
+                        Some(
+                            Response::EncryptedMessage(_)
+                            | Response::RekeyMessage(_)
+                            | Response::ResumptionTicket(_)
+                            | Response::AlertMessage(_),
+                        ),
+                },
                 Step::Open { .. },
             ) => {
+                anyhow::ensure!(
+                    self.peer_alert.is_none(),
+                    "message received after the server closed the session"
+                );
                 anyhow::ensure!(
                     self.incoming_responses.len() < DEFAULT_MAX_MESSAGE_QUEUE_LEN,
                     "Message queue limit exceeded"
@@ -667,11 +834,206 @@ impl ProtocolEngine<SessionResponse, SessionRequest> for ClientSession {
                 self.incoming_responses.push_back(im);
                 Ok(Some(()))
             }
+            (
+                SessionResponse {
+                    response:
+                        Some(Response::AlertMessage(AlertMessage {
+                            alert: Some(Alert::PlaintextAlert(alert)),
+                        })),
+                },
+                Step::Attestation { .. } | Step::Handshake { .. },
+            ) => Err(peer_alert_error(self.peer_alert.insert(alert))),
             (_, _) => Err(anyhow!("unexpected content of session response")),
         }
     }
 }
 
+impl Session for ClientSession {
+    /// Checks if the client session is open. See `Session::is_open`.
+    fn is_open(&self) -> bool {
+        matches!(self.step, Step::Open { .. })
+    }
+
+    /// Encrypts and queues a message for the server. See `Session::write`.
+    fn write(&mut self, plaintext: PlaintextMessage) -> Result<(), Error> {
+        match &mut self.step {
+            Step::Open { encryptor, .. } => {
+                anyhow::ensure!(!self.closed, "the session has been closed");
+                anyhow::ensure!(
+                    self.outgoing_requests.len() < DEFAULT_MAX_MESSAGE_QUEUE_LEN,
+                    "Message queue limit exceeded"
+                );
+                if self.rekeyer.needs_rekey() {
+                    let rekey_message = self.rekeyer.rekey(encryptor.as_mut(), false)?;
+                    self.outgoing_requests.push_back(SessionRequest {
+                        request: Some(Request::RekeyMessage(rekey_message)),
+                    });
+                }
+                self.rekeyer.record_message(plaintext.plaintext.len());
+                let encrypted_message = encrypt_message(
+                    encryptor.as_mut(),
+                    MessageKind::ApplicationData,
+                    plaintext.plaintext,
+                )
+                .context("encrypting plaintext")?;
+                self.outgoing_requests.push_back(SessionRequest {
+                    request: Some(Request::EncryptedMessage(encrypted_message)),
+                });
+                Ok(())
+            }
+            _ => Err(anyhow!("the session is not open")),
+        }
+    }
+
+    /// Reads and decrypts a message from the server. See `Session::read`.
+    ///
+    /// Rekey messages, resumption tickets and alerts sent by the server are
+    /// processed on the way, and don't produce any plaintext.
+    fn read(&mut self) -> Result<Option<PlaintextMessage>, Error> {
+        let was_open = self.is_open();
+        let result = self.read_next_message();
+        if let Err(err) = &result {
+            if was_open {
+                self.fail(Some(AlertReason::ProtocolError), err);
+            }
+        }
+        result
+    }
+
+    /// Gets a session binding token. See `Session::get_session_binding_token`.
+    fn get_session_binding_token(&self, info_string: &[u8]) -> Result<SessionBindingToken, Error> {
+        self.step.get_session_binding_token(info_string)
+    }
+
+    /// Gets the peer attestation evidence. See
+    /// `Session::get_peer_attestation_evidence`.
+    fn get_peer_attestation_evidence(&self) -> Result<AttestationEvidence, Error> {
+        self.step.get_peer_attestation_evidence()
+    }
+
+    /// Gets the peer static public key. See
+    /// `Session::get_peer_static_public_key`.
+    fn get_peer_static_public_key(&self) -> Result<Option<Vec<u8>>, Error> {
+        self.step.get_peer_static_public_key()
+    }
+
+    /// Switches to new traffic keys. See `Session::request_rekey`.
+    fn request_rekey(&mut self) -> Result<(), Error> {
+        match &mut self.step {
+            Step::Open { encryptor, .. } => {
+                anyhow::ensure!(!self.closed, "the session has been closed");
+                let rekey_message = self.rekeyer.rekey(encryptor.as_mut(), true)?;
+                self.outgoing_requests.push_back(SessionRequest {
+                    request: Some(Request::RekeyMessage(rekey_message)),
+                });
+                Ok(())
+            }
+            _ => Err(anyhow!("the session is not open")),
+        }
+    }
+
+    /// Queues a close notification for the server. See `Session::close`.
+    fn close(&mut self) -> Result<(), Error> {
+        match &mut self.step {
+            Step::Open { encryptor, .. } => {
+                anyhow::ensure!(!self.closed, "the session has already been closed");
+                if self.rekeyer.needs_rekey() {
+                    let rekey_message = self.rekeyer.rekey(encryptor.as_mut(), false)?;
+                    self.outgoing_requests.push_back(SessionRequest {
+                        request: Some(Request::RekeyMessage(rekey_message)),
+                    });
+                }
+                self.rekeyer.record_message(0);
+                let alert =
+                    SessionAlert { reason: AlertReason::CloseNotify.into(), ..Default::default() };
+                let alert_message = encrypt_alert(encryptor.as_mut(), &alert)?;
+                self.outgoing_requests.push_back(SessionRequest {
+                    request: Some(Request::AlertMessage(alert_message)),
+                });
+                self.closed = true;
+                Ok(())
+            }
+            _ => Err(anyhow!("the session is not open")),
+        }
+    }
+
+    /// Ends the session with a fatal alert. See `Session::abort`.
+    fn abort(&mut self, reason: AlertReason, description: &str) -> Result<(), Error> {
+        anyhow::ensure!(
+            reason != AlertReason::CloseNotify,
+            "an orderly shutdown requires closing the session"
+        );
+        anyhow::ensure!(self.step.failure_reason().is_some(), "session is in an invalid state");
+        self.fail(Some(reason), &anyhow!("{description}"));
+        Ok(())
+    }
+
+    /// Gets the alert received from the server. See `Session::get_peer_alert`.
+    fn get_peer_alert(&self) -> Option<&SessionAlert> {
+        self.peer_alert.as_ref()
+    }
+}
+
+impl ProtocolEngine<SessionResponse, SessionRequest> for ClientSession {
+    /// Gets the next outgoing `SessionRequest` to be sent to the server.
+    ///
+    /// Depending on the current `step`:
+    /// - `Attestation`: Returns an `AttestRequest` from the
+    ///   `ClientAttestationHandler`.
+    /// - `Handshake`: Returns a `HandshakeRequest` from the
+    ///   `ClientHandshakeHandler`. If the handshake completes as a result,
+    ///   transitions to `Open`.
+    /// - `Open`: Returns an `EncryptedMessage` (application data) or a
+    ///   `RekeyMessage` from `outgoing_requests`.
+    ///
+    /// If no message is ready, returns `Ok(None)`.
+    fn get_outgoing_message(&mut self) -> Result<Option<SessionRequest>, Error> {
+        let failure_reason = self.step.failure_reason();
+        let result = self.next_outgoing_message();
+        if let Err(err) = &result {
+            // Only failed step transitions end the session, not calls made while
+            // there is nothing to send.
+            if matches!(self.step, Step::Error { .. } | Step::Invalid) {
+                self.fail(failure_reason, err);
+            }
+        }
+        result
+    }
+
+    /// Processes an incoming `SessionResponse` from the server.
+    ///
+    /// Depending on the current `step` and message type:
+    /// - `Attestation` + `AttestResponse`: Passes to
+    ///   `ClientAttestationHandler`. If attestation completes, transitions to
+    ///   `Handshake`.
+    /// - `Handshake` + `HandshakeResponse`: Passes to `ClientHandshakeHandler`.
+    ///   Verifies server's session bindings using the
+    ///   `SessionBindingVerifierProvider` from the configured
+    ///   `PeerAttestationVerifier` and `attestation_results`. If handshake
+    ///   completes, transitions to `Open`.
+    /// - `Open` + `EncryptedMessage`, `RekeyMessage`, `ResumptionTicket` or
+    ///   `AlertMessage`: Queues in `incoming_responses` for `read()`.
+    /// - `Attestation` or `Handshake` + `AlertMessage`: Records the alert from
+    ///   the server, and returns an error describing it.
+    ///
+    /// If processing the message fails, an alert reporting the failure is
+    /// queued for the server.
+    ///
+    /// Returns `Ok(Some(()))` if processed, `Err` on mismatch or protocol
+    /// error.
+    fn put_incoming_message(
+        &mut self,
+        incoming_message: SessionResponse,
+    ) -> Result<Option<()>, Error> {
+        let failure_reason = self.step.failure_reason();
+        let result = self.process_incoming_message(incoming_message);
+        if let Err(err) = &result {
+            self.fail(failure_reason, err);
+        }
+        result
+    }
+}
+
 /// Server-side implementation of an end-to-end secure attested session.
 ///
 /// Orchestrates the `ServerAttestationHandler` and `ServerHandshakeHandler`
@@ -688,6 +1050,15 @@ pub struct ServerSession {
     /// to the session layer but not yet decrypted and read by the
     /// application.
     incoming_requests: VecDeque<SessionRequest>,
+    /// Keeps track of when the sending key needs to be replaced.
+    rekeyer: Rekeyer,
+    /// Issues resumption tickets to the client, if configured.
+    resumption_ticket_issuer: Option<Arc<ResumptionTicketIssuer>>,
+    /// Whether the session has been closed, after which no further messages
+    /// are sent.
+    closed: bool,
+    /// The alert with which the client ended the session, if any.
+    peer_alert: Option<SessionAlert>,
 }
 
 impl ServerSession {
@@ -698,6 +1069,10 @@ impl ServerSession {
     /// based on `config.attestation_handler_config.attestation_type` for
     /// the `ServerHandshakeHandler`. The configuration is consumed.
     pub fn create(config: SessionConfig) -> Result<Self, Error> {
+        config.encryptor_config.validate()?;
+        let encoder = config.encryptor_config.encoder();
+        let resumption_ticket_issuer =
+            config.attestation_handler_config.resumption_ticket_issuer.clone();
         Ok(Self {
             step: Step::Attestation {
                 attester: ServerAttestationHandler::create(config.attestation_handler_config)?,
@@ -705,34 +1080,56 @@ impl ServerSession {
                 handshake_handler_provider: Box::new(ServerHandshakeHandlerBuilder {
                     config: config.handshake_handler_config,
                 }),
-                encryptor_provider: config.encryptor_config.encryptor_provider,
+                encryptor_provider: EncodingEncryptorProvider::create(
+                    config.encryptor_config.encryptor_provider,
+                    encoder,
+                ),
             },
             outgoing_responses: VecDeque::new(),
             incoming_requests: VecDeque::new(),
+            rekeyer: Rekeyer::new(config.encryptor_config.rekey_policy),
+            resumption_ticket_issuer,
+            closed: false,
+            peer_alert: None,
         })
     }
-}
 
-impl Session for ServerSession {
-    /// Checks if the server session is open. See `Session::is_open`.
-    fn is_open(&self) -> bool {
-        matches!(self.step, Step::Open { .. })
-    }
-
-    /// Encrypts and queues a message for the client. See `Session::write`.
-    fn write(&mut self, plaintext: PlaintextMessage) -> Result<(), Error> {
+    /// Issues a resumption ticket and queues it for the client.
+    ///
+    /// The ticket allows the client to open a new session without repeating
+    /// the attestation, for as long as the ticket is valid. It is bound to the
+    /// attestation evidence supplied by the client in this session.
+    ///
+    /// Requires a [`ResumptionTicketIssuer`] to be configured, and can only be
+    /// called successfully when `is_open()` is true.
+    pub fn issue_resumption_ticket(&mut self) -> Result<(), Error> {
+        let resumption_ticket_issuer = self
+            .resumption_ticket_issuer
+            .as_ref()
+            .ok_or(anyhow!("no resumption ticket issuer configured"))?;
         match &mut self.step {
-            Step::Open { encryptor, .. } => {
+            Step::Open { encryptor, attestation_state, handshake_state } => {
+                anyhow::ensure!(!self.closed, "the session has been closed");
                 anyhow::ensure!(
                     self.outgoing_responses.len() < DEFAULT_MAX_MESSAGE_QUEUE_LEN,
                     "Message queue limit exceeded"
                 );
-                let encrypted_message: EncryptedMessage = encryptor
-                    .encrypt(plaintext.into())
-                    .map(From::from)
-                    .context("encrypting plaintext")?;
+                let new_resumption_ticket = resumption_ticket_issuer.issue(
+                    &AttestationEvidence::new_from_state(attestation_state, handshake_state),
+                )?;
+                if self.rekeyer.needs_rekey() {
+                    let rekey_message = self.rekeyer.rekey(encryptor.as_mut(), false)?;
+                    self.outgoing_responses.push_back(SessionResponse {
+                        response: Some(Response::RekeyMessage(rekey_message)),
+                    });
+                }
+                let plaintext = new_resumption_ticket.encode_to_vec();
+                self.rekeyer.record_message(plaintext.len());
+                let encrypted_ticket =
+                    encrypt_message(encryptor.as_mut(), MessageKind::ResumptionTicket, plaintext)
+                        .context("encrypting resumption ticket")?;
                 self.outgoing_responses.push_back(SessionResponse {
-                    response: Some(Response::EncryptedMessage(encrypted_message)),
+                    response: Some(Response::ResumptionTicket(encrypted_ticket)),
                 });
                 Ok(())
             }
@@ -740,59 +1137,80 @@ impl Session for ServerSession {
         }
     }
 
-    /// Reads and decrypts a message from the client. See `Session::read`.
-    fn read(&mut self) -> Result<Option<PlaintextMessage>, Error> {
+    /// Moves the session to the `Error` state after `err`, replacing the
+    /// queued messages with an alert that reports the failure to the client.
+    /// Failures caused by an alert from the client aren't reported back.
+    fn fail(&mut self, reason: Option<AlertReason>, err: &Error) {
+        let reason = reason.filter(|_| self.peer_alert.is_none());
+        // Messages encrypted before the failure must still be delivered, as the
+        // peer can't decrypt the alert that follows them otherwise.
+        if !matches!(self.step, Step::Open { .. }) {
+            self.outgoing_responses.clear();
+        }
+        if let Some(alert_message) = self.step.fail(reason, format!("{err}")) {
+            self.outgoing_responses.push_back(SessionResponse {
+                response: Some(Response::AlertMessage(alert_message)),
+            });
+        }
+    }
+
+    /// Decrypts the next message from the client. See `Session::read`.
+    fn read_next_message(&mut self) -> Result<Option<PlaintextMessage>, Error> {
         match &mut self.step {
-            Step::Open { encryptor, .. } => match self.incoming_requests.pop_front() {
-                Some(request) => {
-                    let encrypted_message = match request.request {
-                        Some(Request::EncryptedMessage(encrypted_message)) => encrypted_message,
+            Step::Open { encryptor, .. } => {
+                while let Some(request) = self.incoming_requests.pop_front() {
+                    anyhow::ensure!(
+                        self.peer_alert.is_none(),
+                        "message received after the client closed the session"
+                    );
+                    match request.request {
+                        Some(Request::EncryptedMessage(encrypted_message)) => {
+                            let result = decrypt_message(
+                                encryptor.as_mut(),
+                                MessageKind::ApplicationData,
+                                encrypted_message,
+                            );
+                            // Replayed messages are dropped without ending the session.
+                            if result.as_ref().is_err_and(|err| err.is::<DiscardedMessageError>()) {
+                                continue;
+                            }
+                            return Ok(Some(
+                                result.map(From::from).context("decrypting plaintext")?,
+                            ));
+                        }
+                        Some(Request::RekeyMessage(rekey_message)) => {
+                            if process_rekey_message(encryptor.as_mut(), rekey_message)?
+                                && !self.closed
+                            {
+                                let rekey_message =
+                                    self.rekeyer.rekey(encryptor.as_mut(), false)?;
+                                self.outgoing_responses.push_back(SessionResponse {
+                                    response: Some(Response::RekeyMessage(rekey_message)),
+                                });
+                            }
+                        }
+                        Some(Request::AlertMessage(alert_message)) => {
+                            let alert = decrypt_alert(encryptor.as_mut(), alert_message)?;
+                            let peer_alert = self.peer_alert.insert(alert);
+                            if peer_alert.reason() != AlertReason::CloseNotify {
+                                return Err(peer_alert_error(peer_alert));
+                            }
+                        }
                         _ => {
                             return Err(anyhow!(
                                 "unexpected content of SessionRequest: no encrypted message set"
                             ));
                         }
-                    };
-                    Ok(Some(
-                        encryptor
-                            .decrypt(encrypted_message.into())
-                            .map(From::from)
-                            .context("decrypting plaintext")?,
-                    ))
+                    }
                 }
-                None => Ok(None),
-            },
+                Ok(None)
+            }
             _ => Err(anyhow!("the session is not open")),
         }
     }
 
-    /// Gets a session binding token. See `Session::get_session_binding_token`.
-    fn get_session_binding_token(&self, info_string: &[u8]) -> Result<SessionBindingToken, Error> {
-        self.step.get_session_binding_token(info_string)
-    }
-
-    /// Gets the peer attestation evidence. See
-    /// `Session::get_peer_attestation_evidence`.
-    fn get_peer_attestation_evidence(&self) -> Result<AttestationEvidence, Error> {
-        self.step.get_peer_attestation_evidence()
-    }
-}
-
-impl ProtocolEngine<SessionRequest, SessionResponse> for ServerSession {
-    /// Gets the next outgoing `SessionResponse` to be sent to the client.
-    ///
-    /// Depending on the current `step`:
-    /// - `Attestation`: Returns an `AttestResponse` from
-    ///   `ServerAttestationHandler` (after processing client's request).
-    ///   Transitions to `Handshake`.
-    /// - `Handshake`: Returns a `HandshakeResponse` from
-    ///   `ServerHandshakeHandler`. If handshake completes, transitions to
-    ///   `Open`.
-    /// - `Open`: Returns an `EncryptedMessage` (application data) from
-    ///   `outgoing_responses`.
-    ///
-    /// If no message is ready, returns `Ok(None)`.
-    fn get_outgoing_message(&mut self) -> Result<Option<SessionResponse>, Error> {
+    // Implements `ProtocolEngine::get_outgoing_message`.
+    fn next_outgoing_message(&mut self) -> Result<Option<SessionResponse>, Error> {
         match &mut self.step {
             Step::Attestation { attester, .. } => {
                 if let Some(attest_message) = attester.get_outgoing_message()? {
@@ -818,25 +1236,17 @@ impl ProtocolEngine<SessionRequest, SessionResponse> for ServerSession {
                 }
             }
             Step::Open { .. } => Ok(self.outgoing_responses.pop_front()),
-            Step::Invalid | Step::Error { .. } => Err(anyhow!("session is in an invalid state")),
+            // The alert reporting the failure of the session may still be pending.
+            Step::Invalid | Step::Error { .. } => self
+                .outgoing_responses
+                .pop_front()
+                .map(Some)
+                .ok_or(anyhow!("session is in an invalid state")),
         }
     }
 
-    /// Processes an incoming `SessionRequest` from the client.
-    ///
-    /// Depending on the current `step` and message type:
-    /// - `Attestation` + `AttestRequest`: Passes to `ServerAttestationHandler`.
-    /// - `Handshake` + `HandshakeRequest`: Passes to `ServerHandshakeHandler`.
-    ///   If this is the client's binding follow-up, verifies it using the
-    ///   `SessionBindingVerifierProvider` from the configured
-    ///   `PeerAttestationVerifier` and `attestation_results`. If handshake
-    ///   completes, transitions to `Open`.
-    /// - `Open` + `EncryptedMessage`: Queues in `incoming_requests` for
-    ///   `read()`.
-    ///
-    /// Returns `Ok(Some(()))` if processed, `Err` on mismatch or protocol
-    /// error.
-    fn put_incoming_message(
+    // Implements `ProtocolEngine::put_incoming_message`.
+    fn process_incoming_message(
         &mut self,
         incoming_message: SessionRequest,
     ) -> Result<Option<()>, Error> {
@@ -866,9 +1276,20 @@ impl ProtocolEngine<SessionRequest, SessionResponse> for ServerSession {
                 Ok(Some(()))
             }
             (
-                im @ SessionRequest { request: Some(Request::EncryptedMessage(_)) },
+                im @ SessionRequest {
+                    request:
+                        Some(
+                            Request::EncryptedMessage(_)
+                            | Request::RekeyMessage(_)
+                            | Request::AlertMessage(_),
+                        ),
+                },
                 Step::Open { .. },
             ) => {
+                anyhow::ensure!(
+                    self.peer_alert.is_none(),
+                    "message received after the client closed the session"
+                );
                 anyhow::ensure!(
                     self.incoming_requests.len() < DEFAULT_MAX_MESSAGE_QUEUE_LEN,
                     "Message queue limit exceeded"
@@ -876,11 +1297,205 @@ impl ProtocolEngine<SessionRequest, SessionResponse> for ServerSession {
                 self.incoming_requests.push_back(im);
                 Ok(Some(()))
             }
+            (
+                SessionRequest {
+                    request:
+                        Some(Request::AlertMessage(AlertMessage {
+                            alert: Some(Alert::PlaintextAlert(alert)),
+                        })),
+                },
+                Step::Attestation { .. } | Step::Handshake { .. },
+            ) => Err(peer_alert_error(self.peer_alert.insert(alert))),
             (_, _) => Err(anyhow!("unexpected content of session request")),
         }
     }
 }
 
+impl Session for ServerSession {
+    /// Checks if the server session is open. See `Session::is_open`.
+    fn is_open(&self) -> bool {
+        matches!(self.step, Step::Open { .. })
+    }
+
+    /// Encrypts and queues a message for the client. See `Session::write`.
+    fn write(&mut self, plaintext: PlaintextMessage) -> Result<(), Error> {
+        match &mut self.step {
+            Step::Open { encryptor, .. } => {
+                anyhow::ensure!(!self.closed, "the session has been closed");
+                anyhow::ensure!(
+                    self.outgoing_responses.len() < DEFAULT_MAX_MESSAGE_QUEUE_LEN,
+                    "Message queue limit exceeded"
+                );
+                if self.rekeyer.needs_rekey() {
+                    let rekey_message = self.rekeyer.rekey(encryptor.as_mut(), false)?;
+                    self.outgoing_responses.push_back(SessionResponse {
+                        response: Some(Response::RekeyMessage(rekey_message)),
+                    });
+                }
+                self.rekeyer.record_message(plaintext.plaintext.len());
+                let encrypted_message = encrypt_message(
+                    encryptor.as_mut(),
+                    MessageKind::ApplicationData,
+                    plaintext.plaintext,
+                )
+                .context("encrypting plaintext")?;
+                self.outgoing_responses.push_back(SessionResponse {
+                    response: Some(Response::EncryptedMessage(encrypted_message)),
+                });
+                Ok(())
+            }
+            _ => Err(anyhow!("the session is not open")),
+        }
+    }
+
+    /// Reads and decrypts a message from the client. See `Session::read`.
+    ///
+    /// Rekey messages and alerts sent by the client are processed on the way,
+    /// and don't produce any plaintext.
+    fn read(&mut self) -> Result<Option<PlaintextMessage>, Error> {
+        let was_open = self.is_open();
+        let result = self.read_next_message();
+        if let Err(err) = &result {
+            if was_open {
+                self.fail(Some(AlertReason::ProtocolError), err);
+            }
+        }
+        result
+    }
+
+    /// Gets a session binding token. See `Session::get_session_binding_token`.
+    fn get_session_binding_token(&self, info_string: &[u8]) -> Result<SessionBindingToken, Error> {
+        self.step.get_session_binding_token(info_string)
+    }
+
+    /// Gets the peer attestation evidence. See
+    /// `Session::get_peer_attestation_evidence`.
+    fn get_peer_attestation_evidence(&self) -> Result<AttestationEvidence, Error> {
+        self.step.get_peer_attestation_evidence()
+    }
+
+    /// Gets the peer static public key. See
+    /// `Session::get_peer_static_public_key`.
+    fn get_peer_static_public_key(&self) -> Result<Option<Vec<u8>>, Error> {
+        self.step.get_peer_static_public_key()
+    }
+
+    /// Switches to new traffic keys. See `Session::request_rekey`.
+    fn request_rekey(&mut self) -> Result<(), Error> {
+        match &mut self.step {
+            Step::Open { encryptor, .. } => {
+                anyhow::ensure!(!self.closed, "the session has been closed");
+                let rekey_message = self.rekeyer.rekey(encryptor.as_mut(), true)?;
+                self.outgoing_responses.push_back(SessionResponse {
+                    response: Some(Response::RekeyMessage(rekey_message)),
+                });
+                Ok(())
+            }
+            _ => Err(anyhow!("the session is not open")),
+        }
+    }
+
+    /// Queues a close notification for the client. See `Session::close`.
+    fn close(&mut self) -> Result<(), Error> {
+        match &mut self.step {
+            Step::Open { encryptor, .. } => {
+                anyhow::ensure!(!self.closed, "the session has already been closed");
+                if self.rekeyer.needs_rekey() {
+                    let rekey_message = self.rekeyer.rekey(encryptor.as_mut(), false)?;
+                    self.outgoing_responses.push_back(SessionResponse {
+                        response: Some(Response::RekeyMessage(rekey_message)),
+                    });
+                }
+                self.rekeyer.record_message(0);
+                let alert =
+                    SessionAlert { reason: AlertReason::CloseNotify.into(), ..Default::default() };
+                let alert_message = encrypt_alert(encryptor.as_mut(), &alert)?;
+                self.outgoing_responses.push_back(SessionResponse {
+                    response: Some(Response::AlertMessage(alert_message)),
+                });
+                self.closed = true;
+                Ok(())
+            }
+            _ => Err(anyhow!("the session is not open")),
+        }
+    }
+
+    /// Ends the session with a fatal alert. See `Session::abort`.
+    fn abort(&mut self, reason: AlertReason, description: &str) -> Result<(), Error> {
+        anyhow::ensure!(
+            reason != AlertReason::CloseNotify,
+            "an orderly shutdown requires closing the session"
+        );
+        anyhow::ensure!(self.step.failure_reason().is_some(), "session is in an invalid state");
+        self.fail(Some(reason), &anyhow!("{description}"));
+        Ok(())
+    }
+
+    /// Gets the alert received from the client. See `Session::get_peer_alert`.
+    fn get_peer_alert(&self) -> Option<&SessionAlert> {
+        self.peer_alert.as_ref()
+    }
+}
+
+impl ProtocolEngine<SessionRequest, SessionResponse> for ServerSession {
+    /// Gets the next outgoing `SessionResponse` to be sent to the client.
+    ///
+    /// Depending on the current `step`:
+    /// - `Attestation`: Returns an `AttestResponse` from
+    ///   `ServerAttestationHandler` (after processing client's request).
+    ///   Transitions to `Handshake`.
+    /// - `Handshake`: Returns a `HandshakeResponse` from
+    ///   `ServerHandshakeHandler`. If handshake completes, transitions to
+    ///   `Open`.
+    /// - `Open`: Returns an `EncryptedMessage` (application data) or a
+    ///   `RekeyMessage` from `outgoing_responses`.
+    ///
+    /// If no message is ready, returns `Ok(None)`.
+    fn get_outgoing_message(&mut self) -> Result<Option<SessionResponse>, Error> {
+        let failure_reason = self.step.failure_reason();
+        let result = self.next_outgoing_message();
+        if let Err(err) = &result {
+            // Only failed step transitions end the session, not calls made while
+            // there is nothing to send.
+            if matches!(self.step, Step::Error { .. } | Step::Invalid) {
+                self.fail(failure_reason, err);
+            }
+        }
+        result
+    }
+
+    /// Processes an incoming `SessionRequest` from the client.
+    ///
+    /// Depending on the current `step` and message type:
+    /// - `Attestation` + `AttestRequest`: Passes to `ServerAttestationHandler`.
+    /// - `Handshake` + `HandshakeRequest`: Passes to `ServerHandshakeHandler`.
+    ///   If this is the client's binding follow-up, verifies it using the
+    ///   `SessionBindingVerifierProvider` from the configured
+    ///   `PeerAttestationVerifier` and `attestation_results`. If handshake
+    ///   completes, transitions to `Open`.
+    /// - `Open` + `EncryptedMessage`, `RekeyMessage` or `AlertMessage`: Queues
+    ///   in `incoming_requests` for `read()`.
+    /// - `Attestation` or `Handshake` + `AlertMessage`: Records the alert from
+    ///   the client, and returns an error describing it.
+    ///
+    /// If processing the message fails, an alert reporting the failure is
+    /// queued for the client.
+    ///
+    /// Returns `Ok(Some(()))` if processed, `Err` on mismatch or protocol
+    /// error.
+    fn put_incoming_message(
+        &mut self,
+        incoming_message: SessionRequest,
+    ) -> Result<Option<()>, Error> {
+        let failure_reason = self.step.failure_reason();
+        let result = self.process_incoming_message(incoming_message);
+        if let Err(err) = &result {
+            self.fail(failure_reason, err);
+        }
+        result
+    }
+}
+
 /// Verifies the received session `bindings` against the provided
 /// `attestation_results`.
 ///
@@ -958,6 +1573,9 @@ impl AttestationEvidence {
         attestation_state: &AttestationState,
         handshake_state: &HandshakeState,
     ) -> Self {
+        if let Some(resumed_session) = &attestation_state.resumed_session {
+            return resumed_session.peer_attestation_evidence().clone();
+        }
         Self {
             evidence: attestation_state
                 .peer_attestation_verdict
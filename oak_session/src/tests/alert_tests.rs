@@ -0,0 +1,344 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use googletest::prelude::*;
+use oak_proto_rust::oak::session::v1::{
+    alert_message::Alert, session_request::Request, session_response::Response, AlertMessage,
+    PlaintextMessage, SessionRequest, SessionResponse,
+};
+use oak_session::{
+    alert::{AlertReason, SessionAlert},
+    attestation::AttestationType,
+    config::SessionConfig,
+    handshake::{HandshakeType, KeyExchange},
+    ClientSession, ProtocolEngine, ServerSession, Session,
+};
+use oak_session_testing::{
+    create_failing_mock_verifier, create_mock_attester, create_mock_binder, create_mock_endorser,
+    create_mock_key_extractor, do_attest, do_handshake, HandshakeFollowup,
+};
+use prost::Message;
+
+const MATCHED_ATTESTER_ID1: &str = "MATCHED_ATTESTER_ID1";
+
+fn create_open_sessions() -> anyhow::Result<(ClientSession, ServerSession)> {
+    let mut client_session = ClientSession::create(
+        SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseNN).build(),
+    )?;
+    let mut server_session = ServerSession::create(
+        SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseNN).build(),
+    )?;
+    do_attest(&mut client_session, &mut server_session)?;
+    do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::NotExpected)?;
+    Ok((client_session, server_session))
+}
+
+fn flush_client_to_server(
+    client_session: &mut ClientSession,
+    server_session: &mut ServerSession,
+) -> anyhow::Result<()> {
+    while let Some(request) = client_session.get_outgoing_message()? {
+        server_session.put_incoming_message(request)?;
+    }
+    Ok(())
+}
+
+fn flush_server_to_client(
+    server_session: &mut ServerSession,
+    client_session: &mut ClientSession,
+) -> anyhow::Result<()> {
+    while let Some(response) = server_session.get_outgoing_message()? {
+        client_session.put_incoming_message(response)?;
+    }
+    Ok(())
+}
+
+#[googletest::test]
+fn close_is_delivered_after_pending_messages() -> anyhow::Result<()> {
+    let (mut client_session, mut server_session) = create_open_sessions()?;
+
+    client_session.write(PlaintextMessage { plaintext: b"last request".to_vec() })?;
+    client_session.close()?;
+    assert_that!(client_session.write(PlaintextMessage { plaintext: vec![] }), err(anything()));
+    assert_that!(client_session.close(), err(anything()));
+
+    flush_client_to_server(&mut client_session, &mut server_session)?;
+    assert_that!(server_session.is_peer_closed(), eq(false));
+    assert_that!(
+        server_session.read()?,
+        some(eq(&PlaintextMessage { plaintext: b"last request".to_vec() }))
+    );
+    assert_that!(server_session.read()?, none());
+    assert_that!(server_session.is_peer_closed(), eq(true));
+    assert_that!(
+        server_session.get_peer_alert(),
+        some(matches_pattern!(SessionAlert { reason: eq(&(AlertReason::CloseNotify as i32)), .. }))
+    );
+
+    // The other direction stays open until the server closes it as well.
+    server_session.write(PlaintextMessage { plaintext: b"last response".to_vec() })?;
+    server_session.close()?;
+    flush_server_to_client(&mut server_session, &mut client_session)?;
+    assert_that!(
+        client_session.read()?,
+        some(eq(&PlaintextMessage { plaintext: b"last response".to_vec() }))
+    );
+    assert_that!(client_session.read()?, none());
+    assert_that!(client_session.is_peer_closed(), eq(true));
+
+    Ok(())
+}
+
+#[googletest::test]
+fn close_is_encrypted() -> anyhow::Result<()> {
+    let (mut client_session, _) = create_open_sessions()?;
+
+    client_session.close()?;
+    let close_request = client_session.get_outgoing_message()?.expect("no request was produced");
+    assert_that!(
+        close_request,
+        matches_pattern!(SessionRequest {
+            request: some(matches_pattern!(Request::AlertMessage(matches_pattern!(
+                AlertMessage { alert: some(matches_pattern!(Alert::EncryptedAlert(anything()))) }
+            ))))
+        })
+    );
+
+    // A plaintext close can't be injected into an open session.
+    let forged_close = SessionResponse {
+        response: Some(Response::AlertMessage(AlertMessage {
+            alert: Some(Alert::PlaintextAlert(SessionAlert {
+                reason: AlertReason::CloseNotify.into(),
+                ..Default::default()
+            })),
+        })),
+    };
+    client_session.put_incoming_message(forged_close)?;
+    assert_that!(client_session.read(), err(anything()));
+    assert_that!(client_session.is_peer_closed(), eq(false));
+
+    Ok(())
+}
+
+#[googletest::test]
+fn messages_after_close_are_rejected() -> anyhow::Result<()> {
+    let (mut client_session, mut server_session) = create_open_sessions()?;
+
+    client_session.close()?;
+    flush_client_to_server(&mut client_session, &mut server_session)?;
+    assert_that!(server_session.read()?, none());
+
+    let mut other_client_session = create_open_sessions()?.0;
+    other_client_session.write(PlaintextMessage { plaintext: vec![] })?;
+    let request = other_client_session.get_outgoing_message()?.expect("no request was produced");
+    assert_that!(server_session.put_incoming_message(request), err(anything()));
+
+    Ok(())
+}
+
+#[googletest::test]
+fn client_attestation_failure_is_reported_to_server() -> anyhow::Result<()> {
+    let client_config =
+        SessionConfig::builder(AttestationType::PeerUnidirectional, HandshakeType::NoiseNN)
+            .add_peer_verifier_with_key_extractor(
+                MATCHED_ATTESTER_ID1.to_string(),
+                create_failing_mock_verifier(),
+                create_mock_key_extractor(),
+            )
+            .build();
+    let server_config =
+        SessionConfig::builder(AttestationType::SelfUnidirectional, HandshakeType::NoiseNN)
+            .add_self_attester(MATCHED_ATTESTER_ID1.to_string(), create_mock_attester())
+            .add_self_endorser(MATCHED_ATTESTER_ID1.to_string(), create_mock_endorser())
+            .add_session_binder(MATCHED_ATTESTER_ID1.to_string(), create_mock_binder())
+            .build();
+    let mut client_session = ClientSession::create(client_config)?;
+    let mut server_session = ServerSession::create(server_config)?;
+
+    assert_that!(do_attest(&mut client_session, &mut server_session), err(anything()));
+
+    let alert_request = client_session.get_outgoing_message()?.expect("no alert was produced");
+    assert_that!(
+        alert_request,
+        matches_pattern!(SessionRequest {
+            request: some(matches_pattern!(Request::AlertMessage(matches_pattern!(
+                AlertMessage { alert: some(matches_pattern!(Alert::PlaintextAlert(anything()))) }
+            ))))
+        })
+    );
+    assert_that!(client_session.get_outgoing_message(), err(anything()));
+
+    let result = server_session.put_incoming_message(alert_request);
+    assert_that!(
+        result.map_err(|err| err.to_string()),
+        err(contains_substring("ATTESTATION_VERIFICATION_FAILED"))
+    );
+    assert_that!(
+        server_session.get_peer_alert(),
+        some(matches_pattern!(SessionAlert {
+            reason: eq(&(AlertReason::AttestationVerificationFailed as i32)),
+            failed_verifier_ids: elements_are![eq(MATCHED_ATTESTER_ID1)],
+            ..
+        }))
+    );
+    // Alerts aren't answered with alerts.
+    assert_that!(server_session.get_outgoing_message(), err(anything()));
+
+    Ok(())
+}
+
+#[googletest::test]
+fn server_attestation_failure_is_reported_to_client() -> anyhow::Result<()> {
+    let client_config =
+        SessionConfig::builder(AttestationType::SelfUnidirectional, HandshakeType::NoiseNN)
+            .add_self_attester(MATCHED_ATTESTER_ID1.to_string(), create_mock_attester())
+            .add_self_endorser(MATCHED_ATTESTER_ID1.to_string(), create_mock_endorser())
+            .add_session_binder(MATCHED_ATTESTER_ID1.to_string(), create_mock_binder())
+            .build();
+    let server_config =
+        SessionConfig::builder(AttestationType::PeerUnidirectional, HandshakeType::NoiseNN)
+            .add_peer_verifier_with_key_extractor(
+                MATCHED_ATTESTER_ID1.to_string(),
+                create_failing_mock_verifier(),
+                create_mock_key_extractor(),
+            )
+            .build();
+    let mut client_session = ClientSession::create(client_config)?;
+    let mut server_session = ServerSession::create(server_config)?;
+
+    let attest_request = client_session.get_outgoing_message()?.expect("no request was produced");
+    server_session.put_incoming_message(attest_request)?;
+    assert_that!(server_session.get_outgoing_message(), err(anything()));
+
+    let alert_response = server_session.get_outgoing_message()?.expect("no alert was produced");
+    assert_that!(client_session.put_incoming_message(alert_response), err(anything()));
+    assert_that!(
+        client_session.get_peer_alert(),
+        some(matches_pattern!(SessionAlert {
+            reason: eq(&(AlertReason::AttestationVerificationFailed as i32)),
+            failed_verifier_ids: elements_are![eq(MATCHED_ATTESTER_ID1)],
+            ..
+        }))
+    );
+    assert_that!(client_session.is_peer_closed(), eq(false));
+
+    Ok(())
+}
+
+#[googletest::test]
+fn handshake_failure_is_reported_to_client() -> anyhow::Result<()> {
+    let mut client_session = ClientSession::create(
+        SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseNN).build(),
+    )?;
+    let mut server_session = ServerSession::create(
+        SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseNN)
+            .set_key_exchange(KeyExchange::HybridMlKem768)
+            .build(),
+    )?;
+    do_attest(&mut client_session, &mut server_session)?;
+
+    let handshake_request =
+        client_session.get_outgoing_message()?.expect("no request was produced");
+    assert_that!(server_session.put_incoming_message(handshake_request), err(anything()));
+
+    let alert_response = server_session.get_outgoing_message()?.expect("no alert was produced");
+    assert_that!(client_session.put_incoming_message(alert_response), err(anything()));
+    assert_that!(
+        client_session.get_peer_alert(),
+        some(matches_pattern!(SessionAlert {
+            reason: eq(&(AlertReason::HandshakeFailed as i32)),
+            failed_verifier_ids: is_empty(),
+            ..
+        }))
+    );
+
+    Ok(())
+}
+
+#[googletest::test]
+fn abort_ends_the_session_after_pending_messages() -> anyhow::Result<()> {
+    let (mut client_session, mut server_session) = create_open_sessions()?;
+
+    server_session.write(PlaintextMessage { plaintext: b"last response".to_vec() })?;
+    server_session.abort(AlertReason::RekeyRequired, "rekey more often")?;
+    assert_that!(server_session.is_open(), eq(false));
+    assert_that!(server_session.write(PlaintextMessage { plaintext: vec![] }), err(anything()));
+
+    // The alert is the last message of the session.
+    let last_response = server_session.get_outgoing_message()?.expect("no response was produced");
+    let alert_response = server_session.get_outgoing_message()?.expect("no alert was produced");
+    assert_that!(server_session.get_outgoing_message(), err(anything()));
+
+    client_session.put_incoming_message(last_response)?;
+    client_session.put_incoming_message(alert_response)?;
+    assert_that!(
+        client_session.read()?,
+        some(eq(&PlaintextMessage { plaintext: b"last response".to_vec() }))
+    );
+    let result = client_session.read();
+    assert_that!(
+        result.map_err(|err| err.to_string()),
+        err(contains_substring("rekey more often"))
+    );
+    assert_that!(
+        client_session.get_peer_alert(),
+        some(matches_pattern!(SessionAlert {
+            reason: eq(&(AlertReason::RekeyRequired as i32)),
+            ..
+        }))
+    );
+    assert_that!(client_session.is_open(), eq(false));
+    assert_that!(client_session.get_outgoing_message(), err(anything()));
+
+    Ok(())
+}
+
+#[googletest::test]
+fn abort_requires_a_fatal_reason() -> anyhow::Result<()> {
+    let (mut client_session, _) = create_open_sessions()?;
+
+    assert_that!(client_session.abort(AlertReason::CloseNotify, ""), err(anything()));
+    assert_that!(client_session.is_open(), eq(true));
+
+    Ok(())
+}
+
+#[googletest::test]
+fn application_message_relabelled_as_alert_is_rejected() -> anyhow::Result<()> {
+    let (mut client_session, mut server_session) = create_open_sessions()?;
+
+    // An application message that happens to decode as a close notification.
+    let plaintext = SessionAlert { reason: AlertReason::CloseNotify.into(), ..Default::default() }
+        .encode_to_vec();
+    client_session.write(PlaintextMessage { plaintext })?;
+    let Some(SessionRequest { request: Some(Request::EncryptedMessage(encrypted_message)) }) =
+        client_session.get_outgoing_message()?
+    else {
+        panic!("no encrypted message was produced");
+    };
+
+    let relabelled_request = SessionRequest {
+        request: Some(Request::AlertMessage(AlertMessage {
+            alert: Some(Alert::EncryptedAlert(encrypted_message)),
+        })),
+    };
+    assert_that!(server_session.put_incoming_message(relabelled_request), ok(some(())));
+    assert_that!(server_session.read(), err(anything()));
+    assert_that!(server_session.get_peer_alert(), none());
+    assert_that!(server_session.is_peer_closed(), eq(false));
+
+    Ok(())
+}
@@ -94,6 +94,7 @@ fn unattested_client_attestation_provides_empty_request() -> anyhow::Result<()>
         ok(some(matches_pattern!(AttestRequest {
             endorsed_evidence: is_empty(),
             assertions: is_empty(),
+            ..
         })))
     );
 
@@ -140,6 +141,7 @@ fn unattested_server_attestation_provides_response() -> anyhow::Result<()> {
         ok(some(matches_pattern!(AttestResponse {
             endorsed_evidence: is_empty(),
             assertions: is_empty(),
+            ..
         })))
     );
 
@@ -311,6 +313,7 @@ fn peer_attested_client_provides_request_accepts_response() -> anyhow::Result<()
             },
         )]),
         assertions: BTreeMap::from([(MATCHED_ATTESTER_ID1.to_string(), assertion)]),
+        ..Default::default()
     };
     assert_that!(client_attestation_provider.put_incoming_message(attest_response), ok(some(())));
     assert_that!(
@@ -351,6 +354,7 @@ fn peer_attested_server_accepts_request_provides_response() -> anyhow::Result<()
             },
         )]),
         assertions: BTreeMap::from([(MATCHED_ATTESTER_ID1.to_string(), assertion)]),
+        ..Default::default()
     };
     assert_that!(server_attestation_provider.put_incoming_message(attest_request), ok(some(())));
 
@@ -419,6 +423,7 @@ fn bidirectional_client_provides_request_accepts_response() -> anyhow::Result<()
             },
         )]),
         assertions: BTreeMap::from([(MATCHED_ATTESTER_ID1.to_string(), assertion)]),
+        ..Default::default()
     };
     assert_that!(client_attestation_provider.put_incoming_message(attest_response), ok(some(())));
 
@@ -469,6 +474,7 @@ fn bidirectional_server_accepts_request_provides_response() -> anyhow::Result<()
             },
         )]),
         assertions: BTreeMap::from([(MATCHED_ATTESTER_ID1.to_string(), assertion)]),
+        ..Default::default()
     };
     assert_that!(server_attestation_provider.put_incoming_message(attest_request), ok(some(())));
 
@@ -563,6 +569,7 @@ fn client_failed_evidence_verification_fails() -> anyhow::Result<()> {
             },
         )]),
         assertions: BTreeMap::from([(MATCHED_ATTESTER_ID1.to_string(), assertion)]),
+        ..Default::default()
     };
     assert_that!(client_attestation_provider.put_incoming_message(attest_response), ok(some(())));
     assert_that!(
@@ -617,6 +624,7 @@ fn client_failed_assertion_verification_fails() -> anyhow::Result<()> {
             },
         )]),
         assertions: BTreeMap::from([(MATCHED_ATTESTER_ID1.to_string(), assertion)]),
+        ..Default::default()
     };
     assert_that!(client_attestation_provider.put_incoming_message(attest_response), ok(some(())));
     assert_that!(
@@ -671,6 +679,7 @@ fn server_failed_evidence_verification_fails() -> anyhow::Result<()> {
             },
         )]),
         assertions: BTreeMap::from([(MATCHED_ATTESTER_ID1.to_string(), assertion)]),
+        ..Default::default()
     };
     assert_that!(server_attestation_provider.put_incoming_message(attest_request), ok(some(())));
     assert_that!(
@@ -725,6 +734,7 @@ fn server_failed_assertion_verification_fails() -> anyhow::Result<()> {
             },
         )]),
         assertions: BTreeMap::from([(MATCHED_ATTESTER_ID1.to_string(), assertion)]),
+        ..Default::default()
     };
     assert_that!(server_attestation_provider.put_incoming_message(attest_request), ok(some(())));
     assert_that!(
@@ -816,6 +826,7 @@ fn client_aggregated_attestation_succeeds() -> anyhow::Result<()> {
             (MATCHED_ATTESTER_ID1.to_string(), assertion1),
             (MATCHED_ATTESTER_ID2.to_string(), assertion2),
         ]),
+        ..Default::default()
     };
     assert_that!(client_attestation_provider.put_incoming_message(attest_response), ok(some(())));
     assert_that!(
@@ -925,6 +936,7 @@ fn server_aggregated_attestation_succeeds() -> anyhow::Result<()> {
             (MATCHED_ATTESTER_ID1.to_string(), assertion1),
             (MATCHED_ATTESTER_ID2.to_string(), assertion2),
         ]),
+        ..Default::default()
     };
     assert_that!(server_attestation_provider.put_incoming_message(attest_request), ok(some(())));
     assert_that!(
@@ -1043,6 +1055,7 @@ fn client_one_failed_evidence_verifier_aggregated_attestation_fails() -> anyhow:
             (MATCHED_ATTESTER_ID1.to_string(), assertion1),
             (MATCHED_ATTESTER_ID2.to_string(), assertion2),
         ]),
+        ..Default::default()
     };
     assert_that!(client_attestation_provider.put_incoming_message(attest_response), ok(some(())));
     assert_that!(
@@ -1149,6 +1162,7 @@ fn client_one_failed_assertion_verifier_aggregated_attestation_fails() -> anyhow
             (MATCHED_ATTESTER_ID1.to_string(), assertion1),
             (MATCHED_ATTESTER_ID2.to_string(), assertion2),
         ]),
+        ..Default::default()
     };
     assert_that!(client_attestation_provider.put_incoming_message(attest_response), ok(some(())));
     assert_that!(
@@ -1258,6 +1272,7 @@ fn server_one_failed_evidence_verifier_aggregated_attestation_fails() -> anyhow:
             (MATCHED_ATTESTER_ID1.to_string(), assertion1),
             (MATCHED_ATTESTER_ID2.to_string(), assertion2),
         ]),
+        ..Default::default()
     };
     assert_that!(server_attestation_provider.put_incoming_message(attest_request), ok(some(())));
     assert_that!(
@@ -1364,6 +1379,7 @@ fn server_one_failed_assertion_verifier_aggregated_attestation_fails() -> anyhow
             (MATCHED_ATTESTER_ID1.to_string(), assertion1),
             (MATCHED_ATTESTER_ID2.to_string(), assertion2),
         ]),
+        ..Default::default()
     };
     assert_that!(server_attestation_provider.put_incoming_message(attest_request), ok(some(())));
     assert_that!(
@@ -1555,6 +1571,7 @@ fn client_additional_attestation_passes() -> anyhow::Result<()> {
             (MATCHED_ATTESTER_ID1.to_string(), assertion),
             (UNMATCHED_ATTESTER_ID.to_string(), Assertion::default()),
         ]),
+        ..Default::default()
     };
     assert_that!(client_attestation_provider.put_incoming_message(attest_response), ok(some(())));
     assert_that!(
@@ -1607,6 +1624,7 @@ fn server_additional_attestation_passes() -> anyhow::Result<()> {
             (MATCHED_ATTESTER_ID1.to_string(), assertion),
             (UNMATCHED_ATTESTER_ID.to_string(), Assertion::default()),
         ]),
+        ..Default::default()
     };
     assert_that!(server_attestation_provider.put_incoming_message(attest_request), ok(some(())));
     assert_that!(
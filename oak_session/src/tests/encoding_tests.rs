@@ -0,0 +1,270 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use googletest::prelude::*;
+use oak_crypto::{
+    encryptor::{Encryptor, Payload},
+    noise_handshake::{UnorderedCrypter, SYMMETRIC_KEY_LEN},
+};
+use oak_proto_rust::oak::session::v1::{session_request::Request, PlaintextMessage};
+use oak_session::{
+    attestation::AttestationType,
+    config::SessionConfig,
+    encoding::{CompressionPolicy, PaddingPolicy, PlaintextEncoder},
+    encryptors::{EncodingEncryptor, UnorderedChannelEncryptor},
+    handshake::HandshakeType,
+    ClientSession, ProtocolEngine, ServerSession, Session,
+};
+use oak_session_testing::{do_attest, do_handshake, HandshakeFollowup};
+
+const DEFLATE_WITH_PADDING: CompressionPolicy =
+    CompressionPolicy::Deflate { level: 6, allow_length_leaks: false };
+
+fn encoded_len(encoder: &PlaintextEncoder, plaintext_len: usize) -> usize {
+    encoder.encode(vec![7u8; plaintext_len]).expect("couldn't encode plaintext").len()
+}
+
+fn create_open_sessions(
+    padding_policy: PaddingPolicy,
+    compression_policy: CompressionPolicy,
+) -> anyhow::Result<(ClientSession, ServerSession)> {
+    let config = || {
+        SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseNN)
+            .set_padding_policy(padding_policy.clone())
+            .set_compression_policy(compression_policy.clone())
+            .build()
+    };
+    let mut client_session = ClientSession::create(config())?;
+    let mut server_session = ServerSession::create(config())?;
+    do_attest(&mut client_session, &mut server_session)?;
+    do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::NotExpected)?;
+    Ok((client_session, server_session))
+}
+
+/// Sends `plaintext` from the client to the server, and returns the length of
+/// the ciphertext that carried it.
+fn send_to_server(
+    client_session: &mut ClientSession,
+    server_session: &mut ServerSession,
+    plaintext: Vec<u8>,
+) -> anyhow::Result<usize> {
+    client_session.write(PlaintextMessage { plaintext: plaintext.clone() })?;
+    let request = client_session.get_outgoing_message()?.expect("no request was produced");
+    let ciphertext_len = match &request.request {
+        Some(Request::EncryptedMessage(encrypted_message)) => encrypted_message.ciphertext.len(),
+        _ => panic!("unexpected request"),
+    };
+    server_session.put_incoming_message(request)?;
+    assert_that!(server_session.read()?, some(eq(&PlaintextMessage { plaintext })));
+    Ok(ciphertext_len)
+}
+
+#[googletest::test]
+fn passthrough_encoder_leaves_plaintext_unchanged() {
+    let encoder = PlaintextEncoder::default();
+    assert_that!(encoder.is_passthrough(), eq(true));
+    assert_that!(encoder.encode(b"message".to_vec()), ok(eq(b"message")));
+    assert_that!(encoder.decode(b"message".to_vec()), ok(eq(b"message")));
+}
+
+#[googletest::test]
+fn buckets_pad_to_smallest_fitting_bucket() {
+    let encoder = PlaintextEncoder::new(
+        PaddingPolicy::Buckets(vec![256, 64, 1024]),
+        CompressionPolicy::Disabled,
+    );
+    assert_that!(encoded_len(&encoder, 0), eq(64));
+    assert_that!(encoded_len(&encoder, 59), eq(64));
+    assert_that!(encoded_len(&encoder, 60), eq(256));
+    assert_that!(encoded_len(&encoder, 1000), eq(1024));
+    // Larger messages are padded to a multiple of the largest bucket.
+    assert_that!(encoded_len(&encoder, 1500), eq(2048));
+}
+
+#[googletest::test]
+fn power_of_two_pads_to_next_power_of_two() {
+    let encoder = PlaintextEncoder::new(PaddingPolicy::PowerOfTwo, CompressionPolicy::Disabled);
+    assert_that!(encoded_len(&encoder, 3), eq(8));
+    assert_that!(encoded_len(&encoder, 100), eq(128));
+    assert_that!(encoded_len(&encoder, 123), eq(128));
+    assert_that!(encoded_len(&encoder, 124), eq(256));
+}
+
+#[googletest::test]
+fn pad_to_max_rejects_larger_messages() {
+    let encoder = PlaintextEncoder::new(PaddingPolicy::PadToMax(128), CompressionPolicy::Disabled);
+    assert_that!(encoded_len(&encoder, 0), eq(128));
+    assert_that!(encoded_len(&encoder, 123), eq(128));
+    assert_that!(encoder.encode(vec![0u8; 124]), err(anything()));
+}
+
+#[googletest::test]
+fn encoded_plaintext_round_trips() {
+    let encoders = [
+        PlaintextEncoder::new(PaddingPolicy::Buckets(vec![64, 512]), CompressionPolicy::Disabled),
+        PlaintextEncoder::new(PaddingPolicy::PowerOfTwo, CompressionPolicy::Disabled),
+        PlaintextEncoder::new(PaddingPolicy::PadToMax(4096), DEFLATE_WITH_PADDING),
+        PlaintextEncoder::new(
+            PaddingPolicy::None,
+            CompressionPolicy::Deflate { level: 1, allow_length_leaks: true },
+        ),
+    ];
+    let plaintexts = [vec![], b"short".to_vec(), vec![42u8; 3000], (0..=255).collect()];
+    for encoder in &encoders {
+        for plaintext in &plaintexts {
+            let encoded = encoder.encode(plaintext.clone()).expect("couldn't encode plaintext");
+            expect_that!(encoder.decode(encoded), ok(eq(plaintext)));
+        }
+    }
+}
+
+#[googletest::test]
+fn compression_shrinks_compressible_messages() {
+    let encoder = PlaintextEncoder::new(
+        PaddingPolicy::None,
+        CompressionPolicy::Deflate { level: 6, allow_length_leaks: true },
+    );
+    assert_that!(encoded_len(&encoder, 4096), lt(100));
+}
+
+#[googletest::test]
+fn corrupted_encoding_is_rejected() {
+    let encoder = PlaintextEncoder::new(PaddingPolicy::PowerOfTwo, CompressionPolicy::Disabled);
+    let mut encoded = encoder.encode(b"message".to_vec()).expect("couldn't encode plaintext");
+    encoded[4] = 0xff;
+    assert_that!(encoder.decode(encoded), err(anything()));
+    assert_that!(encoder.decode(vec![0u8; 3]), err(anything()));
+    assert_that!(encoder.decode(vec![0x80, 0, 0, 0, 0]), err(anything()));
+}
+
+#[googletest::test]
+fn compression_requires_padding_or_explicit_length_leaks() {
+    let deflate = CompressionPolicy::Deflate { level: 6, allow_length_leaks: false };
+    assert_that!(deflate.validate(&PaddingPolicy::PowerOfTwo), err(anything()));
+    assert_that!(deflate.validate(&PaddingPolicy::PadToMax(1024)), ok(anything()));
+    let leaky_deflate = CompressionPolicy::Deflate { level: 6, allow_length_leaks: true };
+    assert_that!(leaky_deflate.validate(&PaddingPolicy::None), ok(anything()));
+}
+
+#[googletest::test]
+fn session_creation_rejects_leaky_compression() {
+    let config = SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseNN)
+        .set_compression_policy(CompressionPolicy::Deflate { level: 6, allow_length_leaks: false })
+        .build();
+    assert_that!(
+        ClientSession::create(config).map(|_| ()).map_err(|err| format!("{err:#}")),
+        err(contains_substring("invalid compression policy"))
+    );
+}
+
+#[googletest::test]
+fn session_creation_rejects_invalid_buckets() {
+    for buckets in [vec![], vec![0]] {
+        let config = SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseNN)
+            .set_padding_policy(PaddingPolicy::Buckets(buckets))
+            .build();
+        assert_that!(
+            ServerSession::create(config).map(|_| ()).map_err(|err| format!("{err:#}")),
+            err(contains_substring("invalid padding policy"))
+        );
+    }
+}
+
+#[googletest::test]
+fn unvalidated_empty_bucket_is_rejected() {
+    let encoder =
+        PlaintextEncoder::new(PaddingPolicy::Buckets(vec![0]), CompressionPolicy::Disabled);
+    assert_that!(encoder.encode(b"message".to_vec()), err(anything()));
+}
+
+#[googletest::test]
+fn decompression_is_bounded_by_max_message_len() {
+    let compression = CompressionPolicy::Deflate { level: 6, allow_length_leaks: true };
+    let encoder = PlaintextEncoder::new(PaddingPolicy::None, compression.clone());
+    let small_encoder =
+        PlaintextEncoder::new(PaddingPolicy::None, compression).with_max_message_len(1024);
+
+    let encoded = encoder.encode(vec![0u8; 4096]).expect("couldn't encode plaintext");
+    assert_that!(encoded.len(), lt(100));
+    assert_that!(small_encoder.decode(encoded), err(anything()));
+
+    // Messages longer than the limit are sent uncompressed.
+    let encoded = small_encoder.encode(vec![0u8; 4096]).expect("couldn't encode plaintext");
+    assert_that!(encoded.len(), gt(4096));
+    assert_that!(encoder.decode(encoded), ok(eq(&vec![0u8; 4096])));
+}
+
+#[googletest::test]
+fn padded_sessions_hide_message_lengths() -> anyhow::Result<()> {
+    let (mut client_session, mut server_session) =
+        create_open_sessions(PaddingPolicy::PadToMax(1024), CompressionPolicy::Disabled)?;
+
+    let short_len = send_to_server(&mut client_session, &mut server_session, vec![])?;
+    let long_len = send_to_server(&mut client_session, &mut server_session, vec![1u8; 1000])?;
+    assert_that!(short_len, eq(long_len));
+    assert_that!(
+        client_session.write(PlaintextMessage { plaintext: vec![1u8; 1024] }),
+        err(anything())
+    );
+
+    Ok(())
+}
+
+#[googletest::test]
+fn compressed_sessions_exchange_messages() -> anyhow::Result<()> {
+    let (mut client_session, mut server_session) =
+        create_open_sessions(PaddingPolicy::PadToMax(1024), DEFLATE_WITH_PADDING)?;
+
+    // The message only fits in the padded length once compressed.
+    let compressible_len =
+        send_to_server(&mut client_session, &mut server_session, vec![b'a'; 8192])?;
+    let short_len = send_to_server(&mut client_session, &mut server_session, b"short".to_vec())?;
+    assert_that!(compressible_len, eq(short_len));
+
+    // The policies also apply to the messages sent by the server.
+    server_session.write(PlaintextMessage { plaintext: vec![b'b'; 8192] })?;
+    let response = server_session.get_outgoing_message()?.expect("no response was produced");
+    client_session.put_incoming_message(response)?;
+    assert_that!(
+        client_session.read()?,
+        some(eq(&PlaintextMessage { plaintext: vec![b'b'; 8192] }))
+    );
+
+    Ok(())
+}
+
+#[googletest::test]
+fn unordered_encryptor_applies_encoder() -> anyhow::Result<()> {
+    let key_1 = &[42u8; SYMMETRIC_KEY_LEN];
+    let key_2 = &[52u8; SYMMETRIC_KEY_LEN];
+    let encoder = PlaintextEncoder::new(PaddingPolicy::PadToMax(512), CompressionPolicy::Disabled);
+    let mut replica_1 = EncodingEncryptor::new(
+        Box::new(UnorderedChannelEncryptor { crypter: UnorderedCrypter::new(key_1, key_2, 0) }),
+        encoder.clone(),
+    );
+    let mut replica_2 = EncodingEncryptor::new(
+        Box::new(UnorderedChannelEncryptor { crypter: UnorderedCrypter::new(key_2, key_1, 0) }),
+        encoder,
+    );
+
+    let short_ciphertext = replica_1.encrypt(Payload::from(b"short".to_vec()))?;
+    let long_ciphertext = replica_1.encrypt(Payload::from(vec![3u8; 500]))?;
+    assert_that!(short_ciphertext.message.len(), eq(long_ciphertext.message.len()));
+    assert_that!(replica_2.decrypt(short_ciphertext)?.message, eq(b"short"));
+    assert_that!(replica_2.decrypt(long_ciphertext)?.message, eq(&vec![3u8; 500]));
+
+    Ok(())
+}
@@ -33,7 +33,10 @@ use oak_session::{
     config::HandshakeHandlerConfig,
     encryptors::OrderedChannelEncryptor,
     generator::{BindableAssertion, BindableAssertionGeneratorError},
-    handshake::{ClientHandshakeHandler, HandshakeHandler, HandshakeType, ServerHandshakeHandler},
+    handshake::{
+        ClientHandshakeHandler, HandshakeHandler, HandshakeType, KeyExchange,
+        ServerHandshakeHandler,
+    },
     ProtocolEngine,
 };
 
@@ -48,6 +51,8 @@ fn process_kk_handshake() {
             self_static_private_key: Some(responder_identity_key),
             peer_static_public_key: Some(initiator_identity_key.get_public_key().unwrap()),
             session_binders: BTreeMap::new(),
+            pre_shared_key: None,
+            key_exchange: KeyExchange::Classical,
         },
         AttestationState {
             peer_attestation_verdict: PeerAttestationVerdict::AttestationPassed {
@@ -57,6 +62,7 @@ fn process_kk_handshake() {
             self_assertions: BTreeMap::new(),
             peer_session_binding_verifiers: BTreeMap::new(),
             attestation_binding_token: Vec::new(),
+            resumed_session: None,
         },
     )
     .unwrap();
@@ -66,6 +72,8 @@ fn process_kk_handshake() {
             self_static_private_key: Some(initiator_identity_key),
             peer_static_public_key: Some(responder_public_key),
             session_binders: BTreeMap::new(),
+            pre_shared_key: None,
+            key_exchange: KeyExchange::Classical,
         },
         false,
         AttestationState {
@@ -76,6 +84,7 @@ fn process_kk_handshake() {
             self_assertions: BTreeMap::new(),
             peer_session_binding_verifiers: BTreeMap::new(),
             attestation_binding_token: Vec::new(),
+            resumed_session: None,
         },
     );
     do_handshake(client_handshaker, server_handshaker);
@@ -90,6 +99,8 @@ fn process_nk_handshake() {
             self_static_private_key: None,
             peer_static_public_key: Some(identity_key.get_public_key().unwrap()),
             session_binders: BTreeMap::new(),
+            pre_shared_key: None,
+            key_exchange: KeyExchange::Classical,
         },
         AttestationState {
             peer_attestation_verdict: PeerAttestationVerdict::AttestationPassed {
@@ -99,6 +110,7 @@ fn process_nk_handshake() {
             self_assertions: BTreeMap::new(),
             peer_session_binding_verifiers: BTreeMap::new(),
             attestation_binding_token: Vec::new(),
+            resumed_session: None,
         },
     )
     .unwrap();
@@ -108,6 +120,8 @@ fn process_nk_handshake() {
             self_static_private_key: Some(identity_key),
             peer_static_public_key: None,
             session_binders: BTreeMap::new(),
+            pre_shared_key: None,
+            key_exchange: KeyExchange::Classical,
         },
         false,
         AttestationState {
@@ -118,6 +132,7 @@ fn process_nk_handshake() {
             self_assertions: BTreeMap::new(),
             peer_session_binding_verifiers: BTreeMap::new(),
             attestation_binding_token: Vec::new(),
+            resumed_session: None,
         },
     );
     do_handshake(client_handshaker, server_handshaker);
@@ -131,6 +146,8 @@ fn process_nn_handshake() {
             self_static_private_key: None,
             peer_static_public_key: None,
             session_binders: BTreeMap::new(),
+            pre_shared_key: None,
+            key_exchange: KeyExchange::Classical,
         },
         AttestationState {
             peer_attestation_verdict: PeerAttestationVerdict::AttestationPassed {
@@ -140,6 +157,7 @@ fn process_nn_handshake() {
             self_assertions: BTreeMap::new(),
             peer_session_binding_verifiers: BTreeMap::new(),
             attestation_binding_token: Vec::new(),
+            resumed_session: None,
         },
     )
     .unwrap();
@@ -149,6 +167,8 @@ fn process_nn_handshake() {
             self_static_private_key: None,
             peer_static_public_key: None,
             session_binders: BTreeMap::new(),
+            pre_shared_key: None,
+            key_exchange: KeyExchange::Classical,
         },
         false,
         AttestationState {
@@ -159,6 +179,7 @@ fn process_nn_handshake() {
             self_assertions: BTreeMap::new(),
             peer_session_binding_verifiers: BTreeMap::new(),
             attestation_binding_token: Vec::new(),
+            resumed_session: None,
         },
     );
     do_handshake(client_handshaker, server_handshaker);
@@ -245,6 +266,8 @@ fn client_sends_assertion_bindings() {
             self_static_private_key: None,
             peer_static_public_key: None,
             session_binders: BTreeMap::new(),
+            pre_shared_key: None,
+            key_exchange: KeyExchange::Classical,
         },
         AttestationState {
             peer_attestation_verdict: PeerAttestationVerdict::AttestationPassed {
@@ -254,6 +277,7 @@ fn client_sends_assertion_bindings() {
             self_assertions,
             peer_session_binding_verifiers: BTreeMap::new(),
             attestation_binding_token: b"attestation_binding_token".to_vec(),
+            resumed_session: None,
         },
     )
     .unwrap();
@@ -264,6 +288,8 @@ fn client_sends_assertion_bindings() {
             self_static_private_key: None,
             peer_static_public_key: None,
             session_binders: BTreeMap::new(),
+            pre_shared_key: None,
+            key_exchange: KeyExchange::Classical,
         },
         true, // Expect client binding
         AttestationState {
@@ -274,6 +300,7 @@ fn client_sends_assertion_bindings() {
             self_assertions: BTreeMap::new(),
             peer_session_binding_verifiers: BTreeMap::new(),
             attestation_binding_token: b"attestation_binding_token".to_vec(),
+            resumed_session: None,
         },
     );
 
@@ -303,6 +330,8 @@ fn server_sends_assertion_bindings() {
             self_static_private_key: None,
             peer_static_public_key: None,
             session_binders: BTreeMap::new(),
+            pre_shared_key: None,
+            key_exchange: KeyExchange::Classical,
         },
         AttestationState {
             peer_attestation_verdict: PeerAttestationVerdict::AttestationPassed {
@@ -312,6 +341,7 @@ fn server_sends_assertion_bindings() {
             self_assertions: BTreeMap::new(),
             peer_session_binding_verifiers: BTreeMap::new(),
             attestation_binding_token: Vec::new(),
+            resumed_session: None,
         },
     )
     .unwrap();
@@ -322,6 +352,8 @@ fn server_sends_assertion_bindings() {
             self_static_private_key: None,
             peer_static_public_key: None,
             session_binders: BTreeMap::new(),
+            pre_shared_key: None,
+            key_exchange: KeyExchange::Classical,
         },
         false,
         AttestationState {
@@ -332,6 +364,7 @@ fn server_sends_assertion_bindings() {
             self_assertions,
             peer_session_binding_verifiers: BTreeMap::new(),
             attestation_binding_token: Vec::new(),
+            resumed_session: None,
         },
     );
 
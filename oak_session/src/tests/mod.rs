@@ -14,9 +14,13 @@
 
 extern crate std;
 mod aggregators_tests;
+mod alert_tests;
 mod attestation_tests;
+mod encoding_tests;
 mod encryptor_tests;
 mod handshake_tests;
 mod proptests;
+mod rekey_tests;
+mod resumption_tests;
 mod session_binding_tests;
 mod session_tests;
@@ -12,8 +12,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::vec::Vec;
+use std::{collections::BTreeSet, sync::Arc, vec::Vec};
 
+use oak_crypto::{
+    encryptor::{Encryptor, Payload},
+    noise_handshake::{ReplayWindowStats, UnorderedCrypter, SYMMETRIC_KEY_LEN},
+};
+use oak_proto_rust::oak::session::v1::PlaintextMessage;
+use oak_session::{
+    attestation::AttestationType,
+    config::SessionConfig,
+    encryptors::{
+        DiscardedMessageError, MeteredUnorderedChannelEncryptor, ReplayMetrics,
+        UnorderedChannelEncryptor,
+    },
+    handshake::HandshakeType,
+    ClientSession, ProtocolEngine, ServerSession, Session,
+};
+use oak_session_testing::{do_attest, do_handshake, HandshakeFollowup};
 use proptest::prelude::*;
 
 proptest! {
@@ -22,3 +38,194 @@ proptest! {
         oak_session_testing::test_unattested_nn_encryption_and_decryption_inner(message).expect("Testing test_unattested_nn_encryption_and_decryption_inner")
     }
 }
+
+/// Reference model of the replay window of an unordered encryptor.
+struct ReplayModel {
+    window_size: u32,
+    highest: u32,
+    accepted: BTreeSet<u32>,
+    stats: ReplayWindowStats,
+}
+
+impl ReplayModel {
+    fn new(window_size: u32) -> Self {
+        Self {
+            window_size,
+            highest: 0,
+            accepted: BTreeSet::new(),
+            stats: ReplayWindowStats::default(),
+        }
+    }
+
+    /// Returns whether a message with `nonce` is accepted.
+    fn receive(&mut self, nonce: u32) -> bool {
+        let lowest =
+            if self.highest > self.window_size { self.highest - self.window_size + 1 } else { 1 };
+        if nonce < lowest || (self.window_size == 0 && nonce <= self.highest) {
+            self.stats.too_old += 1;
+            return false;
+        }
+        if !self.accepted.insert(nonce) {
+            self.stats.duplicated += 1;
+            return false;
+        }
+        self.stats.accepted += 1;
+        self.highest = self.highest.max(nonce);
+        true
+    }
+}
+
+fn unordered_encryptor_pair(
+    window_size: u32,
+    replay_metrics: Arc<ReplayMetrics>,
+) -> (UnorderedChannelEncryptor, MeteredUnorderedChannelEncryptor) {
+    let key_1 = &[42u8; SYMMETRIC_KEY_LEN];
+    let key_2 = &[52u8; SYMMETRIC_KEY_LEN];
+    let sender =
+        UnorderedChannelEncryptor { crypter: UnorderedCrypter::new(key_1, key_2, window_size) };
+    let receiver =
+        UnorderedChannelEncryptor { crypter: UnorderedCrypter::new(key_2, key_1, window_size) }
+            .with_replay_metrics(replay_metrics);
+    (sender, receiver)
+}
+
+proptest! {
+    // Delivers the messages in an arbitrary order, with duplicates and losses,
+    // and checks that each of them is decrypted at most once, exactly when the
+    // model of the replay window accepts it.
+    #[test]
+    fn test_unordered_encryptor_never_accepts_replays(
+        window_size in 0u32..40,
+        message_count in 1usize..100,
+        deliveries in prop::collection::vec(any::<prop::sample::Index>(), 0..200),
+    ) {
+        let replay_metrics = Arc::new(ReplayMetrics::default());
+        let (mut sender, mut receiver) =
+            unordered_encryptor_pair(window_size, replay_metrics.clone());
+        let ciphertexts: Vec<Payload> = (0..message_count)
+            .map(|i| {
+                sender
+                    .encrypt(Payload::from(i.to_be_bytes().to_vec()))
+                    .expect("couldn't encrypt message")
+            })
+            .collect();
+
+        let mut model = ReplayModel::new(window_size);
+        for delivery in &deliveries {
+            let i = delivery.index(message_count);
+            let ciphertext = &ciphertexts[i];
+            let result = receiver.decrypt(Payload {
+                message: ciphertext.message.clone(),
+                nonce: ciphertext.nonce.clone(),
+                aad: ciphertext.aad.clone(),
+            });
+            if model.receive(i as u32 + 1) {
+                prop_assert_eq!(
+                    result.expect("message wasn't accepted").message,
+                    i.to_be_bytes().to_vec()
+                );
+            } else {
+                prop_assert!(result.is_err_and(|err| err.is::<DiscardedMessageError>()));
+            }
+        }
+        let stats = replay_metrics.snapshot();
+        prop_assert_eq!(stats.accepted, model.stats.accepted);
+        prop_assert_eq!(stats.duplicated, model.stats.duplicated);
+        prop_assert_eq!(stats.too_old, model.stats.too_old);
+        prop_assert_eq!(
+            stats.accepted + stats.duplicated + stats.too_old,
+            deliveries.len() as u64
+        );
+    }
+
+    // Messages delivered in order, with some of them lost, are all accepted,
+    // and the lost ones are eventually counted as dropped.
+    #[test]
+    fn test_unordered_encryptor_counts_lost_messages(
+        window_size in 0u32..40,
+        delivered in prop::collection::vec(any::<bool>(), 1..200),
+    ) {
+        let replay_metrics = Arc::new(ReplayMetrics::default());
+        let (mut sender, mut receiver) =
+            unordered_encryptor_pair(window_size, replay_metrics.clone());
+        for &is_delivered in &delivered {
+            let ciphertext =
+                sender.encrypt(Payload::from(vec![1u8])).expect("couldn't encrypt message");
+            if is_delivered {
+                prop_assert!(receiver.decrypt(ciphertext).is_ok());
+            }
+        }
+        // A final message after `window_size` skipped ones moves all the earlier
+        // messages out of the window, as well as the first skipped one.
+        let mut last = None;
+        for _ in 0..=window_size {
+            last = Some(
+                sender.encrypt(Payload::from(vec![2u8])).expect("couldn't encrypt message"),
+            );
+        }
+        prop_assert!(receiver.decrypt(last.expect("no message was encrypted")).is_ok());
+
+        let lost = delivered.iter().filter(|&&is_delivered| !is_delivered).count() as u64;
+        let stats = replay_metrics.snapshot();
+        prop_assert_eq!(stats.dropped, lost + u64::from(window_size > 0));
+        prop_assert_eq!(stats.duplicated + stats.too_old, 0);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    // Replayed and reordered messages don't end a session using a replay
+    // window, and each message is read at most once.
+    #[test]
+    fn test_session_with_replay_window_discards_replays(
+        message_count in 1usize..20,
+        deliveries in prop::collection::vec(any::<prop::sample::Index>(), 1..60),
+    ) {
+        let replay_metrics = Arc::new(ReplayMetrics::default());
+        let config = |replay_metrics: Arc<ReplayMetrics>| {
+            SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseNN)
+                .set_replay_window(64, replay_metrics)
+                .build()
+        };
+        let mut client_session = ClientSession::create(config(Arc::default()))
+            .expect("couldn't create client session");
+        let mut server_session = ServerSession::create(config(replay_metrics.clone()))
+            .expect("couldn't create server session");
+        do_attest(&mut client_session, &mut server_session).expect("couldn't attest");
+        do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::NotExpected)
+            .expect("couldn't handshake");
+
+        let mut requests = Vec::new();
+        for i in 0..message_count {
+            client_session
+                .write(PlaintextMessage { plaintext: i.to_be_bytes().to_vec() })
+                .expect("couldn't write message");
+            requests.push(
+                client_session
+                    .get_outgoing_message()
+                    .expect("couldn't get request")
+                    .expect("no request was produced"),
+            );
+        }
+
+        let mut read_messages = BTreeSet::new();
+        for delivery in &deliveries {
+            let request = requests[delivery.index(message_count)].clone();
+            server_session.put_incoming_message(request).expect("couldn't put request");
+            while let Some(message) = server_session.read().expect("couldn't read message") {
+                prop_assert!(read_messages.insert(message.plaintext), "message was read twice");
+            }
+        }
+        prop_assert!(server_session.is_open());
+        let distinct_deliveries = deliveries
+            .iter()
+            .map(|delivery| delivery.index(message_count))
+            .collect::<BTreeSet<_>>();
+        prop_assert_eq!(read_messages.len(), distinct_deliveries.len());
+        prop_assert_eq!(
+            replay_metrics.snapshot().duplicated,
+            (deliveries.len() - distinct_deliveries.len()) as u64
+        );
+    }
+}
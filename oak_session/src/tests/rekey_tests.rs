@@ -0,0 +1,335 @@
+// Copyright 2025 Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::{Arc, Mutex};
+
+use googletest::prelude::*;
+use oak_proto_rust::oak::session::v1::{
+    session_request::Request, session_response::Response, PlaintextMessage, RekeyMessage,
+    SessionRequest, SessionResponse,
+};
+use oak_session::{
+    attestation::AttestationType, config::SessionConfig, handshake::HandshakeType,
+    rekey::RekeyPolicy, ClientSession, ProtocolEngine, ServerSession, Session,
+};
+use oak_session_testing::{do_attest, do_handshake, invoke_hello_world, HandshakeFollowup};
+use oak_time::{Clock, Duration, Instant};
+
+/// A clock that only moves forward when told to.
+struct ManualClock {
+    time: Mutex<Instant>,
+}
+
+impl ManualClock {
+    fn advance(&self, duration: Duration) {
+        *self.time.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for ManualClock {
+    fn get_time(&self) -> Instant {
+        *self.time.lock().unwrap()
+    }
+}
+
+fn create_open_sessions(
+    client_rekey_policy: RekeyPolicy,
+    server_rekey_policy: RekeyPolicy,
+) -> anyhow::Result<(ClientSession, ServerSession)> {
+    let client_config = SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseNN)
+        .set_rekey_policy(client_rekey_policy)
+        .build();
+    let server_config = SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseNN)
+        .set_rekey_policy(server_rekey_policy)
+        .build();
+
+    let mut client_session = ClientSession::create(client_config)?;
+    let mut server_session = ServerSession::create(server_config)?;
+
+    do_attest(&mut client_session, &mut server_session)?;
+    do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::NotExpected)?;
+
+    Ok((client_session, server_session))
+}
+
+/// Moves all pending messages from the client to the server, and returns how
+/// many of them were rekey messages.
+fn flush_client_to_server(
+    client_session: &mut ClientSession,
+    server_session: &mut ServerSession,
+) -> usize {
+    let mut rekey_messages = 0;
+    while let Some(request) =
+        client_session.get_outgoing_message().expect("failed to get the client outgoing message")
+    {
+        if matches!(request.request, Some(Request::RekeyMessage(_))) {
+            rekey_messages += 1;
+        }
+        assert_that!(server_session.put_incoming_message(request), ok(some(())));
+    }
+    rekey_messages
+}
+
+/// Moves all pending messages from the server to the client, and returns how
+/// many of them were rekey messages.
+fn flush_server_to_client(
+    server_session: &mut ServerSession,
+    client_session: &mut ClientSession,
+) -> usize {
+    let mut rekey_messages = 0;
+    while let Some(response) =
+        server_session.get_outgoing_message().expect("failed to get the server outgoing message")
+    {
+        if matches!(response.response, Some(Response::RekeyMessage(_))) {
+            rekey_messages += 1;
+        }
+        assert_that!(client_session.put_incoming_message(response), ok(some(())));
+    }
+    rekey_messages
+}
+
+fn send_client_to_server(
+    client_session: &mut ClientSession,
+    server_session: &mut ServerSession,
+    message: &[u8],
+) -> usize {
+    assert_that!(client_session.write(PlaintextMessage { plaintext: message.to_vec() }), ok(()));
+    let rekey_messages = flush_client_to_server(client_session, server_session);
+    assert_that!(
+        server_session.read(),
+        ok(some(matches_pattern!(PlaintextMessage { plaintext: eq(message) })))
+    );
+    rekey_messages
+}
+
+#[googletest::test]
+fn client_requested_rekey_switches_both_directions() -> anyhow::Result<()> {
+    let (mut client_session, mut server_session) =
+        create_open_sessions(RekeyPolicy::default(), RekeyPolicy::default())?;
+
+    assert_that!(client_session.request_rekey(), ok(()));
+    expect_that!(flush_client_to_server(&mut client_session, &mut server_session), eq(1));
+    // Processing the rekey message doesn't produce any plaintext.
+    assert_that!(server_session.read(), ok(none()));
+    expect_that!(flush_server_to_client(&mut server_session, &mut client_session), eq(1));
+    assert_that!(client_session.read(), ok(none()));
+
+    invoke_hello_world(&mut client_session, &mut server_session);
+
+    Ok(())
+}
+
+#[googletest::test]
+fn server_requested_rekey_switches_both_directions() -> anyhow::Result<()> {
+    let (mut client_session, mut server_session) =
+        create_open_sessions(RekeyPolicy::default(), RekeyPolicy::default())?;
+
+    assert_that!(server_session.request_rekey(), ok(()));
+    expect_that!(flush_server_to_client(&mut server_session, &mut client_session), eq(1));
+    assert_that!(client_session.read(), ok(none()));
+    expect_that!(flush_client_to_server(&mut client_session, &mut server_session), eq(1));
+    assert_that!(server_session.read(), ok(none()));
+
+    invoke_hello_world(&mut client_session, &mut server_session);
+
+    Ok(())
+}
+
+#[googletest::test]
+fn rekey_is_processed_in_order_with_pending_messages() -> anyhow::Result<()> {
+    let (mut client_session, mut server_session) =
+        create_open_sessions(RekeyPolicy::default(), RekeyPolicy::default())?;
+
+    // Messages encrypted before and after the switch are queued together.
+    assert_that!(client_session.write(PlaintextMessage { plaintext: "before".into() }), ok(()));
+    assert_that!(client_session.request_rekey(), ok(()));
+    assert_that!(client_session.write(PlaintextMessage { plaintext: "after".into() }), ok(()));
+    expect_that!(flush_client_to_server(&mut client_session, &mut server_session), eq(1));
+
+    assert_that!(
+        server_session.read(),
+        ok(some(matches_pattern!(PlaintextMessage { plaintext: eq("before".as_bytes()) })))
+    );
+    assert_that!(
+        server_session.read(),
+        ok(some(matches_pattern!(PlaintextMessage { plaintext: eq("after".as_bytes()) })))
+    );
+    assert_that!(server_session.read(), ok(none()));
+
+    Ok(())
+}
+
+#[googletest::test]
+fn rekeys_after_max_messages() -> anyhow::Result<()> {
+    let (mut client_session, mut server_session) = create_open_sessions(
+        RekeyPolicy::default().with_max_messages(4),
+        RekeyPolicy::default().with_max_messages(4),
+    )?;
+
+    let mut rekey_messages = 0;
+    for _ in 0..19 {
+        rekey_messages += send_client_to_server(&mut client_session, &mut server_session, b"Hello");
+    }
+    expect_that!(rekey_messages, eq(4));
+    // Automatic rekeys don't ask the peer to rekey as well.
+    expect_that!(flush_server_to_client(&mut server_session, &mut client_session), eq(0));
+
+    invoke_hello_world(&mut client_session, &mut server_session);
+
+    Ok(())
+}
+
+#[googletest::test]
+fn rekeys_after_max_bytes() -> anyhow::Result<()> {
+    let (mut client_session, mut server_session) =
+        create_open_sessions(RekeyPolicy::default().with_max_bytes(10), RekeyPolicy::default())?;
+
+    expect_that!(send_client_to_server(&mut client_session, &mut server_session, b"Hello"), eq(0));
+    expect_that!(send_client_to_server(&mut client_session, &mut server_session, b"World"), eq(0));
+    expect_that!(send_client_to_server(&mut client_session, &mut server_session, b"Again"), eq(1));
+
+    invoke_hello_world(&mut client_session, &mut server_session);
+
+    Ok(())
+}
+
+#[googletest::test]
+fn rekeys_after_max_key_age() -> anyhow::Result<()> {
+    let clock = Arc::new(ManualClock { time: Mutex::new(Instant::from_unix_seconds(0)) });
+    let (mut client_session, mut server_session) = create_open_sessions(
+        RekeyPolicy::default().with_max_key_age(Duration::from_minutes(10), clock.clone()),
+        RekeyPolicy::default(),
+    )?;
+
+    expect_that!(send_client_to_server(&mut client_session, &mut server_session, b"Hello"), eq(0));
+    clock.advance(Duration::from_minutes(5));
+    expect_that!(send_client_to_server(&mut client_session, &mut server_session, b"Hello"), eq(0));
+    clock.advance(Duration::from_minutes(5));
+    expect_that!(send_client_to_server(&mut client_session, &mut server_session, b"Hello"), eq(1));
+    clock.advance(Duration::from_minutes(5));
+    expect_that!(send_client_to_server(&mut client_session, &mut server_session, b"Hello"), eq(0));
+
+    invoke_hello_world(&mut client_session, &mut server_session);
+
+    Ok(())
+}
+
+#[googletest::test]
+fn request_rekey_fails_when_session_is_not_open() -> anyhow::Result<()> {
+    let client_config =
+        SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseNN).build();
+    let server_config =
+        SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseNN).build();
+
+    let mut client_session = ClientSession::create(client_config)?;
+    let mut server_session = ServerSession::create(server_config)?;
+
+    assert_that!(client_session.request_rekey(), err(anything()));
+    assert_that!(server_session.request_rekey(), err(anything()));
+
+    Ok(())
+}
+
+#[googletest::test]
+fn rekey_policy_with_replay_window_is_rejected() {
+    let config = SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseNN)
+        .set_replay_window(64, Arc::default())
+        .set_rekey_policy(RekeyPolicy::default().with_max_messages(10))
+        .build();
+
+    assert_that!(
+        ClientSession::create(config).map(|_| ()).map_err(|err| format!("{err:#}")),
+        err(contains_substring("requires an encryptor that supports rekeying"))
+    );
+}
+
+#[googletest::test]
+fn rekey_message_without_confirmation_fails() -> anyhow::Result<()> {
+    let (_client_session, mut server_session) =
+        create_open_sessions(RekeyPolicy::default(), RekeyPolicy::default())?;
+
+    let rekey_message = SessionRequest {
+        request: Some(Request::RekeyMessage(RekeyMessage { confirmation: None })),
+    };
+    assert_that!(server_session.put_incoming_message(rekey_message), ok(some(())));
+    assert_that!(server_session.read(), err(anything()));
+
+    Ok(())
+}
+
+#[googletest::test]
+fn replayed_rekey_message_fails() -> anyhow::Result<()> {
+    let (mut client_session, mut server_session) =
+        create_open_sessions(RekeyPolicy::default(), RekeyPolicy::default())?;
+
+    assert_that!(server_session.request_rekey(), ok(()));
+    let rekey_message: SessionResponse =
+        server_session.get_outgoing_message()?.expect("no rekey message was produced");
+    assert_that!(client_session.put_incoming_message(rekey_message.clone()), ok(some(())));
+    assert_that!(client_session.read(), ok(none()));
+
+    // The confirmation was encrypted with the previous key, which the client
+    // doesn't accept anymore.
+    assert_that!(client_session.put_incoming_message(rekey_message), ok(some(())));
+    assert_that!(client_session.read(), err(anything()));
+
+    Ok(())
+}
+
+#[googletest::test]
+fn application_message_relabelled_as_rekey_message_fails() -> anyhow::Result<()> {
+    let (mut client_session, mut server_session) =
+        create_open_sessions(RekeyPolicy::default(), RekeyPolicy::default())?;
+
+    // The plaintext of a rekey confirmation is a single byte.
+    assert_that!(client_session.write(PlaintextMessage { plaintext: vec![1] }), ok(()));
+    let Some(SessionRequest { request: Some(Request::EncryptedMessage(encrypted_message)) }) =
+        client_session.get_outgoing_message()?
+    else {
+        panic!("no encrypted message was produced");
+    };
+
+    let relabelled_request = SessionRequest {
+        request: Some(Request::RekeyMessage(RekeyMessage {
+            confirmation: Some(encrypted_message),
+        })),
+    };
+    assert_that!(server_session.put_incoming_message(relabelled_request), ok(some(())));
+    assert_that!(server_session.read(), err(anything()));
+
+    Ok(())
+}
+
+#[googletest::test]
+fn rekey_message_relabelled_as_application_message_fails() -> anyhow::Result<()> {
+    let (mut client_session, mut server_session) =
+        create_open_sessions(RekeyPolicy::default(), RekeyPolicy::default())?;
+
+    assert_that!(client_session.request_rekey(), ok(()));
+    let Some(SessionRequest { request: Some(Request::RekeyMessage(rekey_message)) }) =
+        client_session.get_outgoing_message()?
+    else {
+        panic!("no rekey message was produced");
+    };
+
+    let relabelled_request = SessionRequest {
+        request: Some(Request::EncryptedMessage(
+            rekey_message.confirmation.expect("rekey message without confirmation"),
+        )),
+    };
+    assert_that!(server_session.put_incoming_message(relabelled_request), ok(some(())));
+    assert_that!(server_session.read(), err(anything()));
+
+    Ok(())
+}
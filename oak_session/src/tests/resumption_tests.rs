@@ -0,0 +1,310 @@
+// Copyright 2025 Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    string::ToString,
+    sync::{Arc, Mutex},
+};
+
+use googletest::prelude::*;
+use oak_proto_rust::oak::{
+    attestation::v1::{Endorsements, Evidence},
+    session::v1::{
+        session_request::Request, session_response::Response, AttestRequest, AttestResponse,
+        EndorsedEvidence, SessionRequest, SessionResponse,
+    },
+};
+use oak_session::{
+    attestation::AttestationType,
+    config::SessionConfig,
+    handshake::HandshakeType,
+    resumption::{ResumptionTicket, ResumptionTicketIssuer},
+    session::AttestationEvidence,
+    ClientSession, ProtocolEngine, ServerSession, Session,
+};
+use oak_session_testing::{
+    create_failing_mock_verifier, create_mock_attester, create_mock_binder, create_mock_endorser,
+    create_mock_key_extractor, create_passing_mock_verifier, do_attest, do_handshake,
+    invoke_hello_world, HandshakeFollowup,
+};
+use oak_time::{Clock, Duration, Instant};
+
+const MATCHED_ATTESTER_ID1: &str = "MATCHED_ATTESTER_ID1";
+const TICKET_KEY: [u8; 32] = [42; 32];
+
+/// A clock that only moves forward when told to.
+struct ManualClock {
+    time: Mutex<Instant>,
+}
+
+impl ManualClock {
+    fn advance(&self, duration: Duration) {
+        *self.time.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for ManualClock {
+    fn get_time(&self) -> Instant {
+        *self.time.lock().unwrap()
+    }
+}
+
+fn create_clock() -> Arc<ManualClock> {
+    Arc::new(ManualClock { time: Mutex::new(Instant::from_unix_seconds(0)) })
+}
+
+fn create_client_session(
+    resumption_ticket: Option<ResumptionTicket>,
+) -> anyhow::Result<ClientSession> {
+    let mut builder =
+        SessionConfig::builder(AttestationType::PeerUnidirectional, HandshakeType::NoiseNN)
+            .add_peer_verifier_with_key_extractor(
+                MATCHED_ATTESTER_ID1.to_string(),
+                create_passing_mock_verifier(),
+                create_mock_key_extractor(),
+            );
+    if let Some(resumption_ticket) = resumption_ticket {
+        builder = builder.set_resumption_ticket(resumption_ticket);
+    }
+    ClientSession::create(builder.build())
+}
+
+fn create_server_session(
+    ticket_key: [u8; 32],
+    clock: Arc<ManualClock>,
+) -> anyhow::Result<ServerSession> {
+    let server_config =
+        SessionConfig::builder(AttestationType::SelfUnidirectional, HandshakeType::NoiseNN)
+            .add_self_attester(MATCHED_ATTESTER_ID1.to_string(), create_mock_attester())
+            .add_self_endorser(MATCHED_ATTESTER_ID1.to_string(), create_mock_endorser())
+            .add_session_binder(MATCHED_ATTESTER_ID1.to_string(), create_mock_binder())
+            .set_resumption_ticket_issuer(ResumptionTicketIssuer::new(
+                ticket_key,
+                Duration::from_minutes(10),
+                clock,
+            ))
+            .build();
+    ServerSession::create(server_config)
+}
+
+/// Opens an attested session, and returns the resumption ticket issued in it.
+fn obtain_resumption_ticket(clock: Arc<ManualClock>) -> anyhow::Result<ResumptionTicket> {
+    let mut client_session = create_client_session(None)?;
+    let mut server_session = create_server_session(TICKET_KEY, clock)?;
+
+    do_attest(&mut client_session, &mut server_session)?;
+    do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::NotExpected)?;
+
+    assert_that!(server_session.issue_resumption_ticket(), ok(()));
+    let ticket_message = server_session.get_outgoing_message()?.expect("no ticket was produced");
+    assert_that!(
+        ticket_message,
+        matches_pattern!(SessionResponse {
+            response: some(matches_pattern!(Response::ResumptionTicket(anything())))
+        })
+    );
+    assert_that!(client_session.put_incoming_message(ticket_message), ok(some(())));
+    assert_that!(client_session.read(), ok(none()));
+
+    Ok(client_session.take_resumption_ticket().expect("the ticket wasn't processed"))
+}
+
+/// Runs the attestation exchange and returns whether the server accepted the
+/// resumption ticket.
+fn do_resumed_attest(
+    client_session: &mut ClientSession,
+    server_session: &mut ServerSession,
+) -> anyhow::Result<bool> {
+    let attest_request = client_session.get_outgoing_message()?.expect("no request was produced");
+    server_session.put_incoming_message(attest_request)?;
+    let attest_response = server_session.get_outgoing_message()?.expect("no response was produced");
+    let resumption_accepted = matches!(
+        &attest_response.response,
+        Some(Response::AttestResponse(AttestResponse { resumption_accepted: true, .. }))
+    );
+    client_session.put_incoming_message(attest_response)?;
+    Ok(resumption_accepted)
+}
+
+#[googletest::test]
+fn resumption_ticket_contains_peer_evidence() -> anyhow::Result<()> {
+    let resumption_ticket = obtain_resumption_ticket(create_clock())?;
+
+    assert_that!(
+        resumption_ticket.peer_attestation_evidence(),
+        matches_pattern!(AttestationEvidence {
+            evidence: elements_are![(
+                &MATCHED_ATTESTER_ID1.to_string(),
+                &EndorsedEvidence {
+                    evidence: Some(Evidence { ..Default::default() }),
+                    endorsements: Some(Endorsements { ..Default::default() })
+                }
+            )],
+            ..
+        })
+    );
+
+    Ok(())
+}
+
+#[googletest::test]
+fn resumed_session_skips_attestation() -> anyhow::Result<()> {
+    let clock = create_clock();
+    let resumption_ticket = obtain_resumption_ticket(clock.clone())?;
+
+    let mut client_session = create_client_session(Some(resumption_ticket))?;
+    let mut server_session = create_server_session(TICKET_KEY, clock)?;
+
+    let attest_request: SessionRequest =
+        client_session.get_outgoing_message()?.expect("no request was produced");
+    assert_that!(
+        attest_request,
+        matches_pattern!(SessionRequest {
+            request: some(matches_pattern!(Request::AttestRequest(matches_pattern!(
+                AttestRequest {
+                    endorsed_evidence: is_empty(),
+                    assertions: is_empty(),
+                    resumption_ticket: not(is_empty()),
+                }
+            ))))
+        })
+    );
+    assert_that!(server_session.put_incoming_message(attest_request), ok(some(())));
+    let attest_response: SessionResponse =
+        server_session.get_outgoing_message()?.expect("no response was produced");
+    assert_that!(
+        attest_response,
+        matches_pattern!(SessionResponse {
+            response: some(matches_pattern!(Response::AttestResponse(matches_pattern!(
+                AttestResponse {
+                    endorsed_evidence: is_empty(),
+                    assertions: is_empty(),
+                    resumption_accepted: eq(&true),
+                }
+            ))))
+        })
+    );
+    assert_that!(client_session.put_incoming_message(attest_response), ok(some(())));
+
+    // Both sides bind the handshake to the resumption secret.
+    do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::Expected)?;
+    invoke_hello_world(&mut client_session, &mut server_session);
+
+    assert_that!(
+        client_session.get_peer_attestation_evidence(),
+        ok(matches_pattern!(AttestationEvidence {
+            evidence: elements_are![(eq(&MATCHED_ATTESTER_ID1.to_string()), anything())],
+            ..
+        }))
+    );
+
+    Ok(())
+}
+
+#[googletest::test]
+fn resumed_session_can_issue_new_ticket() -> anyhow::Result<()> {
+    let clock = create_clock();
+    let resumption_ticket = obtain_resumption_ticket(clock.clone())?;
+
+    let mut client_session = create_client_session(Some(resumption_ticket))?;
+    let mut server_session = create_server_session(TICKET_KEY, clock)?;
+    assert_that!(do_resumed_attest(&mut client_session, &mut server_session), ok(eq(&true)));
+    do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::Expected)?;
+
+    assert_that!(server_session.issue_resumption_ticket(), ok(()));
+    let ticket_message = server_session.get_outgoing_message()?.expect("no ticket was produced");
+    assert_that!(client_session.put_incoming_message(ticket_message), ok(some(())));
+    assert_that!(client_session.read(), ok(none()));
+    assert_that!(client_session.take_resumption_ticket(), some(anything()));
+
+    Ok(())
+}
+
+#[googletest::test]
+fn expired_ticket_falls_back_to_attestation() -> anyhow::Result<()> {
+    let clock = create_clock();
+    let resumption_ticket = obtain_resumption_ticket(clock.clone())?;
+    clock.advance(Duration::from_minutes(11));
+
+    let mut client_session = create_client_session(Some(resumption_ticket))?;
+    let mut server_session = create_server_session(TICKET_KEY, clock)?;
+    assert_that!(do_resumed_attest(&mut client_session, &mut server_session), ok(eq(&false)));
+    do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::NotExpected)?;
+    invoke_hello_world(&mut client_session, &mut server_session);
+
+    Ok(())
+}
+
+#[googletest::test]
+fn ticket_from_other_issuer_falls_back_to_attestation() -> anyhow::Result<()> {
+    let clock = create_clock();
+    let resumption_ticket = obtain_resumption_ticket(clock.clone())?;
+
+    let mut client_session = create_client_session(Some(resumption_ticket))?;
+    let mut server_session = create_server_session([7; 32], clock)?;
+    assert_that!(do_resumed_attest(&mut client_session, &mut server_session), ok(eq(&false)));
+    do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::NotExpected)?;
+    invoke_hello_world(&mut client_session, &mut server_session);
+
+    Ok(())
+}
+
+#[googletest::test]
+fn resumed_session_fails_when_peer_evidence_is_rejected() -> anyhow::Result<()> {
+    let clock = create_clock();
+    let resumption_ticket = obtain_resumption_ticket(clock.clone())?;
+
+    // The evidence in the ticket is verified again with the current verifiers.
+    let mut client_session = ClientSession::create(
+        SessionConfig::builder(AttestationType::PeerUnidirectional, HandshakeType::NoiseNN)
+            .add_peer_verifier_with_key_extractor(
+                MATCHED_ATTESTER_ID1.to_string(),
+                create_failing_mock_verifier(),
+                create_mock_key_extractor(),
+            )
+            .set_resumption_ticket(resumption_ticket)
+            .build(),
+    )?;
+    let mut server_session = create_server_session(TICKET_KEY, clock)?;
+    assert_that!(do_resumed_attest(&mut client_session, &mut server_session), err(anything()));
+    assert_that!(client_session.is_open(), eq(false));
+
+    Ok(())
+}
+
+#[googletest::test]
+fn issue_resumption_ticket_fails_without_issuer() -> anyhow::Result<()> {
+    let client_config =
+        SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseNN).build();
+    let server_config =
+        SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseNN).build();
+    let mut client_session = ClientSession::create(client_config)?;
+    let mut server_session = ServerSession::create(server_config)?;
+
+    do_attest(&mut client_session, &mut server_session)?;
+    do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::NotExpected)?;
+
+    assert_that!(server_session.issue_resumption_ticket(), err(anything()));
+
+    Ok(())
+}
+
+#[googletest::test]
+fn issue_resumption_ticket_fails_when_session_is_not_open() -> anyhow::Result<()> {
+    let mut server_session = create_server_session(TICKET_KEY, create_clock())?;
+
+    assert_that!(server_session.issue_resumption_ticket(), err(anything()));
+
+    Ok(())
+}
@@ -16,16 +16,22 @@ use std::{
     boxed::Box,
     string::{String, ToString},
     sync::Arc,
+    vec::Vec,
 };
 
 use anyhow::Context;
 use googletest::prelude::*;
-use oak_crypto::identity_key::{IdentityKey, IdentityKeyHandle};
+use oak_crypto::{
+    identity_key::{IdentityKey, IdentityKeyHandle},
+    noise_handshake::MAX_SEQUENCE,
+};
 use oak_proto_rust::oak::{
     attestation::v1::{Assertion, Endorsements, Evidence},
     session::v1::{
-        session_request::Request, session_response::Response, AttestRequest, AttestResponse,
-        EndorsedEvidence, PlaintextMessage, SessionBinding, SessionRequest, SessionResponse,
+        handshake_request, handshake_response, session_request::Request,
+        session_response::Response, AttestRequest, AttestResponse, EndorsedEvidence,
+        HandshakeRequest, HandshakeResponse, NoiseHandshakeMessage, PlaintextMessage,
+        SessionBinding, SessionRequest, SessionResponse,
     },
 };
 use oak_session::{
@@ -33,7 +39,7 @@ use oak_session::{
     attestation::AttestationType,
     channel::{SessionChannel, SessionInitializer},
     config::SessionConfig,
-    handshake::HandshakeType,
+    handshake::{HandshakeType, KeyExchange},
     session::{
         AttestationEvidence, AttestationPublisher, DEFAULT_MAX_ATTESTATION_SIZE,
         DEFAULT_MAX_MESSAGE_QUEUE_LEN,
@@ -116,6 +122,416 @@ fn pairwise_nk_unattested_mismatched_keys_fails() -> anyhow::Result<()> {
     Ok(())
 }
 
+const PRE_SHARED_KEY: [u8; 32] = [7; 32];
+
+fn create_ik_session_pair(
+    client_handshake_type: HandshakeType,
+    server_handshake_type: HandshakeType,
+    client_pre_shared_key: Option<[u8; 32]>,
+    server_pre_shared_key: Option<[u8; 32]>,
+) -> anyhow::Result<(ClientSession, ServerSession, Vec<u8>, Vec<u8>)> {
+    let client_identity_key = Box::new(IdentityKey::generate());
+    let server_identity_key = Box::new(IdentityKey::generate());
+    let client_public_key = client_identity_key.get_public_key()?;
+    let server_public_key = server_identity_key.get_public_key()?;
+
+    let mut client_config_builder =
+        SessionConfig::builder(AttestationType::Unattested, client_handshake_type)
+            .set_self_static_private_key(client_identity_key)
+            .set_peer_static_public_key(server_public_key.as_slice());
+    if let Some(pre_shared_key) = client_pre_shared_key {
+        client_config_builder = client_config_builder.set_pre_shared_key(pre_shared_key);
+    }
+    let mut server_config_builder =
+        SessionConfig::builder(AttestationType::Unattested, server_handshake_type)
+            .set_self_static_private_key(server_identity_key);
+    if let Some(pre_shared_key) = server_pre_shared_key {
+        server_config_builder = server_config_builder.set_pre_shared_key(pre_shared_key);
+    }
+
+    Ok((
+        ClientSession::create(client_config_builder.build())?,
+        ServerSession::create(server_config_builder.build())?,
+        client_public_key,
+        server_public_key,
+    ))
+}
+
+fn create_xx_session_pair(
+    handshake_type: HandshakeType,
+    client_pre_shared_key: Option<[u8; 32]>,
+    server_pre_shared_key: Option<[u8; 32]>,
+) -> anyhow::Result<(ClientSession, ServerSession, Vec<u8>, Vec<u8>)> {
+    let client_identity_key = Box::new(IdentityKey::generate());
+    let server_identity_key = Box::new(IdentityKey::generate());
+    let client_public_key = client_identity_key.get_public_key()?;
+    let server_public_key = server_identity_key.get_public_key()?;
+
+    let mut client_config_builder =
+        SessionConfig::builder(AttestationType::Unattested, handshake_type)
+            .set_self_static_private_key(client_identity_key);
+    if let Some(pre_shared_key) = client_pre_shared_key {
+        client_config_builder = client_config_builder.set_pre_shared_key(pre_shared_key);
+    }
+    let mut server_config_builder =
+        SessionConfig::builder(AttestationType::Unattested, handshake_type)
+            .set_self_static_private_key(server_identity_key);
+    if let Some(pre_shared_key) = server_pre_shared_key {
+        server_config_builder = server_config_builder.set_pre_shared_key(pre_shared_key);
+    }
+
+    Ok((
+        ClientSession::create(client_config_builder.build())?,
+        ServerSession::create(server_config_builder.build())?,
+        client_public_key,
+        server_public_key,
+    ))
+}
+
+#[googletest::test]
+fn pairwise_ik_unattested_succeeds() -> anyhow::Result<()> {
+    let (mut client_session, mut server_session, client_public_key, server_public_key) =
+        create_ik_session_pair(HandshakeType::NoiseIK, HandshakeType::NoiseIK, None, None)?;
+
+    do_attest(&mut client_session, &mut server_session)?;
+    do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::NotExpected)?;
+    invoke_hello_world(&mut client_session, &mut server_session);
+
+    assert_that!(server_session.get_peer_static_public_key(), ok(some(eq(&client_public_key))));
+    assert_that!(client_session.get_peer_static_public_key(), ok(some(eq(&server_public_key))));
+
+    Ok(())
+}
+
+#[googletest::test]
+fn pairwise_ik_psk2_unattested_succeeds() -> anyhow::Result<()> {
+    let (mut client_session, mut server_session, client_public_key, _) = create_ik_session_pair(
+        HandshakeType::NoiseIKPsk2,
+        HandshakeType::NoiseIKPsk2,
+        Some(PRE_SHARED_KEY),
+        Some(PRE_SHARED_KEY),
+    )?;
+
+    do_attest(&mut client_session, &mut server_session)?;
+    do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::NotExpected)?;
+    invoke_hello_world(&mut client_session, &mut server_session);
+
+    assert_that!(server_session.get_peer_static_public_key(), ok(some(eq(&client_public_key))));
+
+    Ok(())
+}
+
+#[googletest::test]
+fn pairwise_ik_server_rejects_unexpected_client_key() -> anyhow::Result<()> {
+    let server_identity_key = Box::new(IdentityKey::generate());
+    let client_config = SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseIK)
+        .set_self_static_private_key(Box::new(IdentityKey::generate()))
+        .set_peer_static_public_key(server_identity_key.get_public_key()?.as_slice())
+        .build();
+    let server_config = SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseIK)
+        .set_self_static_private_key(server_identity_key)
+        .set_peer_static_public_key(IdentityKey::generate().get_public_key()?.as_slice())
+        .build();
+
+    let mut client_session = ClientSession::create(client_config)?;
+    let mut server_session = ServerSession::create(server_config)?;
+
+    do_attest(&mut client_session, &mut server_session)?;
+    assert_that!(
+        do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::NotExpected),
+        err(anything())
+    );
+
+    Ok(())
+}
+
+#[googletest::test]
+fn pairwise_xx_unattested_succeeds() -> anyhow::Result<()> {
+    let (mut client_session, mut server_session, client_public_key, server_public_key) =
+        create_xx_session_pair(HandshakeType::NoiseXX, None, None)?;
+
+    do_attest(&mut client_session, &mut server_session)?;
+    // The client sends the final message of the handshake.
+    do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::Expected)?;
+    invoke_hello_world(&mut client_session, &mut server_session);
+
+    assert_that!(server_session.get_peer_static_public_key(), ok(some(eq(&client_public_key))));
+    assert_that!(client_session.get_peer_static_public_key(), ok(some(eq(&server_public_key))));
+
+    Ok(())
+}
+
+#[googletest::test]
+fn pairwise_xx_psk3_unattested_succeeds() -> anyhow::Result<()> {
+    let (mut client_session, mut server_session, client_public_key, _) = create_xx_session_pair(
+        HandshakeType::NoiseXXPsk3,
+        Some(PRE_SHARED_KEY),
+        Some(PRE_SHARED_KEY),
+    )?;
+
+    do_attest(&mut client_session, &mut server_session)?;
+    do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::Expected)?;
+    invoke_hello_world(&mut client_session, &mut server_session);
+
+    assert_that!(server_session.get_peer_static_public_key(), ok(some(eq(&client_public_key))));
+
+    Ok(())
+}
+
+#[googletest::test]
+fn pairwise_xx_bidirectional_succeeds() -> anyhow::Result<()> {
+    let client_config =
+        SessionConfig::builder(AttestationType::Bidirectional, HandshakeType::NoiseXX)
+            .set_self_static_private_key(Box::new(IdentityKey::generate()))
+            .add_self_attester(MATCHED_ATTESTER_ID2.to_string(), create_mock_attester())
+            .add_self_endorser(MATCHED_ATTESTER_ID2.to_string(), create_mock_endorser())
+            .add_session_binder(MATCHED_ATTESTER_ID2.to_string(), create_mock_binder())
+            .add_peer_verifier_with_key_extractor(
+                MATCHED_ATTESTER_ID1.to_string(),
+                create_passing_mock_verifier(),
+                create_mock_key_extractor(),
+            )
+            .build();
+    let server_config =
+        SessionConfig::builder(AttestationType::Bidirectional, HandshakeType::NoiseXX)
+            .set_self_static_private_key(Box::new(IdentityKey::generate()))
+            .add_self_attester(MATCHED_ATTESTER_ID1.to_string(), create_mock_attester())
+            .add_self_endorser(MATCHED_ATTESTER_ID1.to_string(), create_mock_endorser())
+            .add_session_binder(MATCHED_ATTESTER_ID1.to_string(), create_mock_binder())
+            .add_peer_verifier_with_key_extractor(
+                MATCHED_ATTESTER_ID2.to_string(),
+                create_passing_mock_verifier(),
+                create_mock_key_extractor(),
+            )
+            .build();
+
+    let mut client_session = ClientSession::create(client_config)?;
+    let mut server_session = ServerSession::create(server_config)?;
+
+    do_attest(&mut client_session, &mut server_session)?;
+    do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::Expected)?;
+    invoke_hello_world(&mut client_session, &mut server_session);
+
+    Ok(())
+}
+
+#[googletest::test]
+fn pairwise_xx_client_rejects_unexpected_server_key() -> anyhow::Result<()> {
+    let client_config = SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseXX)
+        .set_self_static_private_key(Box::new(IdentityKey::generate()))
+        .set_peer_static_public_key(IdentityKey::generate().get_public_key()?.as_slice())
+        .build();
+    let server_config = SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseXX)
+        .set_self_static_private_key(Box::new(IdentityKey::generate()))
+        .build();
+
+    let mut client_session = ClientSession::create(client_config)?;
+    let mut server_session = ServerSession::create(server_config)?;
+
+    do_attest(&mut client_session, &mut server_session)?;
+    assert_that!(
+        do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::Expected),
+        err(anything())
+    );
+
+    Ok(())
+}
+
+#[googletest::test]
+fn pairwise_psk_mismatched_keys_fails() -> anyhow::Result<()> {
+    let (mut client_session, mut server_session, _, _) = create_ik_session_pair(
+        HandshakeType::NoiseIKPsk2,
+        HandshakeType::NoiseIKPsk2,
+        Some(PRE_SHARED_KEY),
+        Some([8; 32]),
+    )?;
+    do_attest(&mut client_session, &mut server_session)?;
+    assert_that!(
+        do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::NotExpected),
+        err(anything())
+    );
+
+    let (mut client_session, mut server_session, _, _) =
+        create_xx_session_pair(HandshakeType::NoiseXXPsk3, Some(PRE_SHARED_KEY), Some([8; 32]))?;
+    do_attest(&mut client_session, &mut server_session)?;
+    assert_that!(
+        do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::Expected),
+        err(anything())
+    );
+
+    Ok(())
+}
+
+#[googletest::test]
+fn pairwise_psk_missing_key_fails() -> anyhow::Result<()> {
+    let (mut client_session, mut server_session, _, _) = create_ik_session_pair(
+        HandshakeType::NoiseIKPsk2,
+        HandshakeType::NoiseIKPsk2,
+        None,
+        Some(PRE_SHARED_KEY),
+    )?;
+    // The client can't start the handshake once attestation has finished.
+    assert_that!(do_attest(&mut client_session, &mut server_session), err(anything()));
+
+    let (mut client_session, mut server_session, _, _) =
+        create_xx_session_pair(HandshakeType::NoiseXXPsk3, Some(PRE_SHARED_KEY), None)?;
+    do_attest(&mut client_session, &mut server_session)?;
+    assert_that!(
+        do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::Expected),
+        err(anything())
+    );
+
+    Ok(())
+}
+
+#[googletest::test]
+fn pairwise_mismatched_handshake_types_fail() -> anyhow::Result<()> {
+    for (client_handshake_type, server_handshake_type) in [
+        (HandshakeType::NoiseIK, HandshakeType::NoiseNK),
+        (HandshakeType::NoiseIK, HandshakeType::NoiseXX),
+        (HandshakeType::NoiseIK, HandshakeType::NoiseKK),
+        (HandshakeType::NoiseKK, HandshakeType::NoiseIK),
+        (HandshakeType::NoiseXX, HandshakeType::NoiseIK),
+        (HandshakeType::NoiseXX, HandshakeType::NoiseNN),
+    ] {
+        let client_identity_key = Box::new(IdentityKey::generate());
+        let server_identity_key = Box::new(IdentityKey::generate());
+        let client_public_key = client_identity_key.get_public_key()?;
+        let server_public_key = server_identity_key.get_public_key()?;
+        let client_config =
+            SessionConfig::builder(AttestationType::Unattested, client_handshake_type)
+                .set_peer_static_public_key(server_public_key.as_slice())
+                .set_self_static_private_key(client_identity_key)
+                .build();
+        let server_config =
+            SessionConfig::builder(AttestationType::Unattested, server_handshake_type)
+                .set_peer_static_public_key(client_public_key.as_slice())
+                .set_self_static_private_key(server_identity_key)
+                .build();
+
+        let mut client_session = ClientSession::create(client_config)?;
+        let mut server_session = ServerSession::create(server_config)?;
+
+        do_attest(&mut client_session, &mut server_session)?;
+        expect_that!(
+            do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::Expected),
+            err(anything()),
+            "client {client_handshake_type:?} with server {server_handshake_type:?}"
+        );
+    }
+
+    Ok(())
+}
+
+fn create_nn_session_pair(
+    client_key_exchange: KeyExchange,
+    server_key_exchange: KeyExchange,
+) -> anyhow::Result<(ClientSession, ServerSession)> {
+    let client_config = SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseNN)
+        .set_key_exchange(client_key_exchange)
+        .build();
+    let server_config = SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseNN)
+        .set_key_exchange(server_key_exchange)
+        .build();
+    Ok((ClientSession::create(client_config)?, ServerSession::create(server_config)?))
+}
+
+#[googletest::test]
+fn pairwise_nn_hybrid_succeeds() -> anyhow::Result<()> {
+    let (mut client_session, mut server_session) =
+        create_nn_session_pair(KeyExchange::HybridMlKem768, KeyExchange::HybridMlKem768)?;
+    do_attest(&mut client_session, &mut server_session)?;
+
+    let handshake_request: SessionRequest =
+        client_session.get_outgoing_message()?.expect("no request was produced");
+    assert_that!(
+        handshake_request,
+        matches_pattern!(SessionRequest {
+            request: some(matches_pattern!(Request::HandshakeRequest(matches_pattern!(
+                HandshakeRequest {
+                    handshake_type: some(matches_pattern!(
+                        handshake_request::HandshakeType::NoiseHandshakeMessage(matches_pattern!(
+                            NoiseHandshakeMessage { kem_public_key: not(is_empty()), .. }
+                        ))
+                    )),
+                    ..
+                }
+            ))))
+        })
+    );
+    assert_that!(server_session.put_incoming_message(handshake_request), ok(some(())));
+    let handshake_response: SessionResponse =
+        server_session.get_outgoing_message()?.expect("no response was produced");
+    assert_that!(
+        handshake_response,
+        matches_pattern!(SessionResponse {
+            response: some(matches_pattern!(Response::HandshakeResponse(matches_pattern!(
+                HandshakeResponse {
+                    handshake_type: some(matches_pattern!(
+                        handshake_response::HandshakeType::NoiseHandshakeMessage(matches_pattern!(
+                            NoiseHandshakeMessage { kem_ciphertext: not(is_empty()), .. }
+                        ))
+                    )),
+                    ..
+                }
+            ))))
+        })
+    );
+    assert_that!(client_session.put_incoming_message(handshake_response), ok(some(())));
+
+    invoke_hello_world(&mut client_session, &mut server_session);
+
+    Ok(())
+}
+
+#[googletest::test]
+fn pairwise_xx_hybrid_succeeds() -> anyhow::Result<()> {
+    let client_config = SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseXX)
+        .set_self_static_private_key(Box::new(IdentityKey::generate()))
+        .set_key_exchange(KeyExchange::HybridMlKem768)
+        .build();
+    let server_config = SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseXX)
+        .set_self_static_private_key(Box::new(IdentityKey::generate()))
+        .set_key_exchange(KeyExchange::HybridMlKem768)
+        .build();
+
+    let mut client_session = ClientSession::create(client_config)?;
+    let mut server_session = ServerSession::create(server_config)?;
+
+    do_attest(&mut client_session, &mut server_session)?;
+    do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::Expected)?;
+    invoke_hello_world(&mut client_session, &mut server_session);
+
+    Ok(())
+}
+
+#[googletest::test]
+fn pairwise_hybrid_client_classical_server_succeeds() -> anyhow::Result<()> {
+    // The server accepts the hybrid key exchange offered by the client.
+    let (mut client_session, mut server_session) =
+        create_nn_session_pair(KeyExchange::HybridMlKem768, KeyExchange::Classical)?;
+
+    do_attest(&mut client_session, &mut server_session)?;
+    do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::NotExpected)?;
+    invoke_hello_world(&mut client_session, &mut server_session);
+
+    Ok(())
+}
+
+#[googletest::test]
+fn pairwise_classical_client_hybrid_server_fails() -> anyhow::Result<()> {
+    let (mut client_session, mut server_session) =
+        create_nn_session_pair(KeyExchange::Classical, KeyExchange::HybridMlKem768)?;
+
+    do_attest(&mut client_session, &mut server_session)?;
+    assert_that!(
+        do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::NotExpected),
+        err(anything())
+    );
+
+    Ok(())
+}
+
 #[googletest::test]
 fn pairwise_nn_unattested_self_succeeds() -> anyhow::Result<()> {
     let client_attestation_publisher = Arc::new(TestAttestationPublisher::new());
@@ -891,6 +1307,45 @@ fn test_outgoing_message_queue_fails_when_exceeded() -> anyhow::Result<()> {
     Ok(())
 }
 
+// Sends more messages than a single traffic key can encrypt, which requires the
+// session to rekey transparently. Run by the
+// `oak_session_nonce_exhaustion_test` target, as it takes minutes in
+// unoptimized builds.
+#[googletest::test]
+#[ignore = "sends 2^24 messages"]
+fn session_rekeys_before_exhausting_the_nonces() -> anyhow::Result<()> {
+    let client_config =
+        SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseNN).build();
+    let server_config =
+        SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseNN).build();
+
+    let mut client_session = ClientSession::create(client_config)?;
+    let mut server_session = ServerSession::create(server_config)?;
+
+    do_attest(&mut client_session, &mut server_session)?;
+
+    do_handshake(&mut client_session, &mut server_session, HandshakeFollowup::NotExpected)?;
+
+    let mut rekey_messages = 0;
+    for i in 0..=u64::from(MAX_SEQUENCE) + 1 {
+        let plaintext = i.to_le_bytes().to_vec();
+        client_session.write(PlaintextMessage { plaintext: plaintext.clone() })?;
+        while let Some(request) = client_session.get_outgoing_message()? {
+            if matches!(request.request, Some(Request::RekeyMessage(_))) {
+                rekey_messages += 1;
+            }
+            server_session.put_incoming_message(request)?;
+        }
+        let received = server_session.read()?.context("no message received")?;
+        anyhow::ensure!(received.plaintext == plaintext, "unexpected message {i}");
+    }
+    assert_that!(rekey_messages, eq(1));
+
+    invoke_hello_world(&mut client_session, &mut server_session);
+
+    Ok(())
+}
+
 #[googletest::test]
 fn test_client_incoming_message_queue_fails_when_exceeded() -> anyhow::Result<()> {
     let client_config =
@@ -0,0 +1,86 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Length-delimited framing of protobuf messages over a byte stream.
+
+use std::{io, marker::PhantomData};
+
+use bytes::{Bytes, BytesMut};
+use prost::Message;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_util::codec::{Decoder, Encoder, Framed, LengthDelimitedCodec};
+
+/// Codec that decodes incoming frames as `I` messages and encodes outgoing `O`
+/// messages, each frame prefixed with its big-endian 32-bit length.
+pub struct ProtoCodec<I, O> {
+    inner: LengthDelimitedCodec,
+    _phantom: PhantomData<fn(O) -> I>,
+}
+
+impl<I, O> ProtoCodec<I, O> {
+    pub fn new() -> Self {
+        Self { inner: LengthDelimitedCodec::new(), _phantom: PhantomData }
+    }
+
+    /// Sets the maximum size of a single frame. Frames exceeding it are
+    /// rejected with an [`io::ErrorKind::InvalidData`] error.
+    pub fn with_max_frame_length(mut self, max_frame_length: usize) -> Self {
+        self.inner.set_max_frame_length(max_frame_length);
+        self
+    }
+}
+
+impl<I, O> Default for ProtoCodec<I, O> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I: Message + Default, O> Decoder for ProtoCodec<I, O> {
+    type Item = I;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<I>, io::Error> {
+        match self.inner.decode(src)? {
+            Some(frame) => I::decode(frame).map(Some).map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("couldn't decode message: {err}"),
+                )
+            }),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<I, O: Message> Encoder<O> for ProtoCodec<I, O> {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: O, dst: &mut BytesMut) -> Result<(), io::Error> {
+        self.inner.encode(Bytes::from(item.encode_to_vec()), dst)
+    }
+}
+
+/// Wraps a byte stream into a transport of length-delimited `I` and `O`
+/// protobuf messages.
+pub fn framed<T, I, O>(io: T) -> Framed<T, ProtoCodec<I, O>>
+where
+    T: AsyncRead + AsyncWrite,
+    I: Message + Default,
+    O: Message,
+{
+    Framed::new(io, ProtoCodec::new())
+}
@@ -0,0 +1,49 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Async adapter that runs an Oak Session over a tokio transport.
+//!
+//! [`SessionStream`] wraps an [`oak_session::ClientSession`] or
+//! [`oak_session::ServerSession`] together with a transport that carries
+//! session protocol messages, and exposes the decrypted application data as a
+//! plain [`tokio::io::AsyncRead`] + [`tokio::io::AsyncWrite`] byte stream.
+//!
+//! The transport can either be:
+//! - Any byte stream implementing `AsyncRead + AsyncWrite` (e.g. a TCP
+//!   connection). Session messages are then framed with a length prefix, see
+//!   [`connect`] and [`accept`].
+//! - Any message transport implementing both `Stream` and `Sink` of session
+//!   messages (e.g. a gRPC stream), see [`SessionStream::connect`] and
+//!   [`SessionStream::accept`]. Separate stream and sink halves can be joined
+//!   with [`MessageChannel`].
+//!
+//! Both functions perform the session initialization (attestation and
+//! handshake) before returning, so the resulting stream is ready to carry
+//! application data.
+//!
+//! Shutting down the write half of a [`SessionStream`] closes the session and
+//! then the underlying transport, which the peer observes as the end of its
+//! read stream. If the transport ends before the peer has closed the session,
+//! reads fail with [`std::io::ErrorKind::UnexpectedEof`] instead, so truncated
+//! data can't be mistaken for complete data.
+
+mod framing;
+mod stream;
+#[cfg(test)]
+mod tests;
+
+pub use framing::{framed, ProtoCodec};
+pub use stream::{accept, connect, MessageChannel, SessionEndpoint, SessionStream};
@@ -0,0 +1,355 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::{
+    collections::VecDeque,
+    io,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use anyhow::Context as _;
+use bytes::BytesMut;
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use oak_proto_rust::oak::session::v1::{PlaintextMessage, SessionRequest, SessionResponse};
+use oak_session::{
+    channel::SessionInitializer, config::SessionConfig, ClientSession, ServerSession,
+};
+use prost::Message;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_util::codec::Framed;
+
+use crate::framing::{framed, ProtoCodec};
+
+/// Maximum amount of plaintext that is encrypted into a single session
+/// message. Larger writes are split across several messages.
+const MAX_PLAINTEXT_CHUNK_LEN: usize = 64 * 1024;
+
+/// An Oak Session endpoint that can be driven by a [`SessionStream`].
+pub trait SessionEndpoint: SessionInitializer<Self::Incoming, Self::Outgoing> + Unpin {
+    /// Messages received from the peer.
+    type Incoming: Message + Default + Unpin;
+    /// Messages sent to the peer.
+    type Outgoing: Message + Unpin;
+    /// Whether this endpoint sends the first session initialization message.
+    const INITIATOR: bool;
+}
+
+impl SessionEndpoint for ClientSession {
+    type Incoming = SessionResponse;
+    type Outgoing = SessionRequest;
+    const INITIATOR: bool = true;
+}
+
+impl SessionEndpoint for ServerSession {
+    type Incoming = SessionRequest;
+    type Outgoing = SessionResponse;
+    const INITIATOR: bool = false;
+}
+
+/// A plaintext byte stream carried over an open Oak Session.
+///
+/// Data written to the stream is encrypted by the session and sent over the
+/// transport `T`; messages received over the transport are decrypted and made
+/// available for reading.
+///
+/// Writes only make progress once all previously produced session messages
+/// have been accepted by the transport, so a slow peer applies backpressure to
+/// the writer. As with other buffered writers, written data is only
+/// guaranteed to reach the peer after the stream has been flushed.
+pub struct SessionStream<S: SessionEndpoint, T> {
+    session: S,
+    transport: T,
+    // Decrypted data that has not been read yet.
+    read_buffer: BytesMut,
+    // Session messages that have not been accepted by the transport yet.
+    pending_messages: VecDeque<S::Outgoing>,
+    // Whether the peer has closed the session or the transport has reached the
+    // end of its incoming messages.
+    read_closed: bool,
+    // Whether the session has been closed by a call to `poll_shutdown`.
+    write_closed: bool,
+}
+
+impl<S, T> SessionStream<S, T>
+where
+    S: SessionEndpoint,
+    T: Stream<Item = io::Result<S::Incoming>> + Sink<S::Outgoing, Error = io::Error> + Unpin,
+{
+    /// Initializes `session` over `transport` and returns a stream for the
+    /// open session.
+    ///
+    /// If the initialization fails locally, the alert produced by the session
+    /// is sent to the peer before the error is returned.
+    pub async fn initialize(mut session: S, mut transport: T) -> anyhow::Result<Self> {
+        if let Err(err) = Self::run_initialization(&mut session, &mut transport).await {
+            if let Ok(Some(alert)) = session.get_outgoing_message() {
+                // The peer is told why the session failed on a best-effort basis.
+                let _ = transport.send(alert).await;
+            }
+            return Err(err);
+        }
+        Ok(Self {
+            session,
+            transport,
+            read_buffer: BytesMut::new(),
+            pending_messages: VecDeque::new(),
+            read_closed: false,
+            write_closed: false,
+        })
+    }
+
+    async fn run_initialization(session: &mut S, transport: &mut T) -> anyhow::Result<()> {
+        let mut send_next = S::INITIATOR;
+        while !session.is_open() {
+            if send_next {
+                let message = session.next_init_message()?;
+                transport.send(message).await.context("sending session init message")?;
+            } else {
+                let message = transport
+                    .next()
+                    .await
+                    .context("transport closed during session initialization")?
+                    .context("receiving session init message")?;
+                session.handle_init_message(message)?;
+            }
+            send_next = !send_next;
+        }
+        Ok(())
+    }
+
+    /// Returns the underlying open session, e.g. to obtain a session binding
+    /// token.
+    pub fn session(&self) -> &S {
+        &self.session
+    }
+
+    // Moves all the messages produced by the session into the pending queue.
+    fn collect_outgoing_messages(&mut self) -> io::Result<()> {
+        while let Some(message) = self.session.get_outgoing_message().map_err(to_io_error)? {
+            self.pending_messages.push_back(message);
+        }
+        Ok(())
+    }
+
+    // Hands pending messages to the transport until the queue is empty.
+    fn poll_send_pending(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while !self.pending_messages.is_empty() {
+            ready!(self.transport.poll_ready_unpin(cx))?;
+            if let Some(message) = self.pending_messages.pop_front() {
+                self.transport.start_send_unpin(message)?;
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T> SessionStream<ClientSession, T>
+where
+    T: Stream<Item = io::Result<SessionResponse>> + Sink<SessionRequest, Error = io::Error> + Unpin,
+{
+    /// Creates a client session from `config` and initializes it over a
+    /// message transport.
+    pub async fn connect(transport: T, config: SessionConfig) -> anyhow::Result<Self> {
+        Self::initialize(ClientSession::create(config)?, transport).await
+    }
+}
+
+impl<T> SessionStream<ServerSession, T>
+where
+    T: Stream<Item = io::Result<SessionRequest>> + Sink<SessionResponse, Error = io::Error> + Unpin,
+{
+    /// Creates a server session from `config` and initializes it over a
+    /// message transport.
+    pub async fn accept(transport: T, config: SessionConfig) -> anyhow::Result<Self> {
+        Self::initialize(ServerSession::create(config)?, transport).await
+    }
+}
+
+/// Creates a client session from `config` and initializes it over a byte
+/// stream, framing session messages with a length prefix.
+pub async fn connect<T>(
+    io: T,
+    config: SessionConfig,
+) -> anyhow::Result<
+    SessionStream<ClientSession, Framed<T, ProtoCodec<SessionResponse, SessionRequest>>>,
+>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    SessionStream::connect(framed(io), config).await
+}
+
+/// Creates a server session from `config` and initializes it over a byte
+/// stream, framing session messages with a length prefix.
+pub async fn accept<T>(
+    io: T,
+    config: SessionConfig,
+) -> anyhow::Result<
+    SessionStream<ServerSession, Framed<T, ProtoCodec<SessionRequest, SessionResponse>>>,
+>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    SessionStream::accept(framed(io), config).await
+}
+
+impl<S, T> AsyncRead for SessionStream<S, T>
+where
+    S: SessionEndpoint,
+    T: Stream<Item = io::Result<S::Incoming>> + Sink<S::Outgoing, Error = io::Error> + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if !this.read_buffer.is_empty() {
+                let len = this.read_buffer.len().min(buf.remaining());
+                buf.put_slice(&this.read_buffer.split_to(len));
+                return Poll::Ready(Ok(()));
+            }
+            if this.read_closed {
+                return Poll::Ready(Ok(()));
+            }
+
+            // Messages produced while processing incoming ones (e.g. rekey
+            // replies) are sent without waiting for the next write.
+            if let Poll::Ready(result) = this.poll_send_pending(cx) {
+                result?;
+            }
+
+            match ready!(this.transport.poll_next_unpin(cx)) {
+                Some(message) => {
+                    this.session.put_incoming_message(message?).map_err(to_io_error)?;
+                    while let Some(PlaintextMessage { plaintext }) =
+                        this.session.read().map_err(to_io_error)?
+                    {
+                        this.read_buffer.extend_from_slice(&plaintext);
+                    }
+                    this.collect_outgoing_messages()?;
+                    this.read_closed = this.session.is_peer_closed();
+                }
+                // The end of the data is only trusted if the peer has closed
+                // the session, otherwise the data may have been truncated.
+                None => {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "transport closed before the peer closed the session",
+                    )))
+                }
+            }
+        }
+    }
+}
+
+impl<S, T> AsyncWrite for SessionStream<S, T>
+where
+    S: SessionEndpoint,
+    T: Stream<Item = io::Result<S::Incoming>> + Sink<S::Outgoing, Error = io::Error> + Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(this.poll_send_pending(cx))?;
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        let len = buf.len().min(MAX_PLAINTEXT_CHUNK_LEN);
+        this.session
+            .write(PlaintextMessage { plaintext: buf[..len].to_vec() })
+            .map_err(to_io_error)?;
+        this.collect_outgoing_messages()?;
+        // The data has been accepted by the session, so the write succeeds even
+        // if the transport is not ready yet; the next call waits for it.
+        if let Poll::Ready(result) = this.poll_send_pending(cx) {
+            result?;
+        }
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_send_pending(cx))?;
+        this.transport.poll_flush_unpin(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if !this.write_closed {
+            this.session.close().map_err(to_io_error)?;
+            this.collect_outgoing_messages()?;
+            this.write_closed = true;
+        }
+        ready!(this.poll_send_pending(cx))?;
+        this.transport.poll_close_unpin(cx)
+    }
+}
+
+/// Joins a separate stream of incoming messages and sink of outgoing messages
+/// into a single transport for [`SessionStream`].
+pub struct MessageChannel<St, Si> {
+    stream: St,
+    sink: Si,
+}
+
+impl<St, Si> MessageChannel<St, Si> {
+    pub fn new(stream: St, sink: Si) -> Self {
+        Self { stream, sink }
+    }
+
+    pub fn into_inner(self) -> (St, Si) {
+        (self.stream, self.sink)
+    }
+}
+
+impl<St: Stream + Unpin, Si: Unpin> Stream for MessageChannel<St, Si> {
+    type Item = St::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<St::Item>> {
+        self.get_mut().stream.poll_next_unpin(cx)
+    }
+}
+
+impl<M, St: Unpin, Si: Sink<M> + Unpin> Sink<M> for MessageChannel<St, Si> {
+    type Error = Si::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Si::Error>> {
+        self.get_mut().sink.poll_ready_unpin(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: M) -> Result<(), Si::Error> {
+        self.get_mut().sink.start_send_unpin(item)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Si::Error>> {
+        self.get_mut().sink.poll_flush_unpin(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Si::Error>> {
+        self.get_mut().sink.poll_close_unpin(cx)
+    }
+}
+
+fn to_io_error(err: anyhow::Error) -> io::Error {
+    io::Error::other(format!("{err:#}"))
+}
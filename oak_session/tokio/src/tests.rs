@@ -0,0 +1,191 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::io;
+
+use futures::{channel::mpsc, SinkExt, StreamExt};
+use googletest::prelude::*;
+use oak_session::{
+    attestation::AttestationType,
+    config::SessionConfig,
+    handshake::{HandshakeType, KeyExchange},
+    Session,
+};
+use tokio::io::{duplex, AsyncReadExt, AsyncWriteExt};
+
+use crate::{accept, connect, MessageChannel, SessionStream};
+
+fn unattested_nn_config() -> SessionConfig {
+    SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseNN).build()
+}
+
+#[googletest::gtest]
+#[tokio::test]
+async fn byte_stream_round_trip_succeeds() -> anyhow::Result<()> {
+    let (client_io, server_io) = duplex(64 * 1024);
+    let (client, server) = tokio::join!(
+        connect(client_io, unattested_nn_config()),
+        accept(server_io, unattested_nn_config())
+    );
+    let (mut client, mut server) = (client?, server?);
+    assert_that!(client.session().is_open(), eq(true));
+    assert_that!(server.session().is_open(), eq(true));
+
+    client.write_all(b"Hello server").await?;
+    client.flush().await?;
+    let mut request = [0u8; 12];
+    server.read_exact(&mut request).await?;
+    assert_that!(&request, eq(b"Hello server"));
+
+    server.write_all(b"Hello client").await?;
+    server.flush().await?;
+    let mut response = [0u8; 12];
+    client.read_exact(&mut response).await?;
+    assert_that!(&response, eq(b"Hello client"));
+
+    Ok(())
+}
+
+#[googletest::gtest]
+#[tokio::test]
+async fn shutdown_is_observed_as_end_of_stream() -> anyhow::Result<()> {
+    let (client_io, server_io) = duplex(64 * 1024);
+    let (client, server) = tokio::join!(
+        connect(client_io, unattested_nn_config()),
+        accept(server_io, unattested_nn_config())
+    );
+    let (mut client, mut server) = (client?, server?);
+
+    client.write_all(b"last words").await?;
+    client.shutdown().await?;
+
+    let mut received = Vec::new();
+    server.read_to_end(&mut received).await?;
+    assert_that!(received, eq(b"last words"));
+
+    Ok(())
+}
+
+#[googletest::gtest]
+#[tokio::test]
+async fn truncation_is_reported_as_unexpected_eof() -> anyhow::Result<()> {
+    let (client_io, server_io) = duplex(64 * 1024);
+    let (client, server) = tokio::join!(
+        connect(client_io, unattested_nn_config()),
+        accept(server_io, unattested_nn_config())
+    );
+    let (mut client, mut server) = (client?, server?);
+
+    client.write_all(b"cut short").await?;
+    client.flush().await?;
+    // Dropping the stream closes the transport without closing the session.
+    drop(client);
+
+    let mut received = Vec::new();
+    let result = server.read_to_end(&mut received).await;
+    assert_that!(result.map_err(|err| err.kind()), err(eq(io::ErrorKind::UnexpectedEof)));
+    assert_that!(received, eq(b"cut short"));
+
+    Ok(())
+}
+
+#[googletest::gtest]
+#[tokio::test]
+async fn large_writes_are_split_and_apply_backpressure() -> anyhow::Result<()> {
+    // A small transport buffer means the writer can only make progress while
+    // the reader is consuming data.
+    let (client_io, server_io) = duplex(1024);
+    let (client, server) = tokio::join!(
+        connect(client_io, unattested_nn_config()),
+        accept(server_io, unattested_nn_config())
+    );
+    let (mut client, mut server) = (client?, server?);
+
+    let data: Vec<u8> = (0..1024 * 1024).map(|i| (i % 251) as u8).collect();
+    let write = async {
+        client.write_all(&data).await?;
+        client.shutdown().await
+    };
+    let mut received = Vec::new();
+    let (write_result, read_result) = tokio::join!(write, server.read_to_end(&mut received));
+    write_result?;
+    read_result?;
+    assert_that!(received.len(), eq(data.len()));
+    assert_that!(received == data, eq(true));
+
+    Ok(())
+}
+
+#[googletest::gtest]
+#[tokio::test]
+async fn message_transport_round_trip_succeeds() -> anyhow::Result<()> {
+    let (request_sender, request_receiver) = mpsc::channel(1);
+    let (response_sender, response_receiver) = mpsc::channel(1);
+    let client_transport = MessageChannel::new(
+        response_receiver.map(Ok),
+        request_sender.sink_map_err(io::Error::other),
+    );
+    let server_transport = MessageChannel::new(
+        request_receiver.map(Ok),
+        response_sender.sink_map_err(io::Error::other),
+    );
+
+    let (client, server) = tokio::join!(
+        SessionStream::connect(client_transport, unattested_nn_config()),
+        SessionStream::accept(server_transport, unattested_nn_config())
+    );
+    let (mut client, mut server) = (client?, server?);
+
+    client.write_all(b"ping").await?;
+    client.flush().await?;
+    let mut request = [0u8; 4];
+    server.read_exact(&mut request).await?;
+    assert_that!(&request, eq(b"ping"));
+
+    server.write_all(b"pong").await?;
+    server.shutdown().await?;
+    let mut response = Vec::new();
+    client.read_to_end(&mut response).await?;
+    assert_that!(response, eq(b"pong"));
+
+    Ok(())
+}
+
+#[googletest::gtest]
+#[tokio::test]
+async fn mismatched_configs_fail_on_both_sides() -> anyhow::Result<()> {
+    let (client_io, server_io) = duplex(64 * 1024);
+    let server_config = SessionConfig::builder(AttestationType::Unattested, HandshakeType::NoiseNN)
+        .set_key_exchange(KeyExchange::HybridMlKem768)
+        .build();
+    let (client, server) =
+        tokio::join!(connect(client_io, unattested_nn_config()), accept(server_io, server_config));
+    assert_that!(server.err(), some(anything()));
+    assert_that!(client.err(), some(anything()));
+
+    Ok(())
+}
+
+#[googletest::gtest]
+#[tokio::test]
+async fn connect_fails_if_transport_is_closed() -> anyhow::Result<()> {
+    let (client_io, server_io) = duplex(64 * 1024);
+    drop(server_io);
+    let result = connect(client_io, unattested_nn_config()).await;
+    assert_that!(result.err(), some(anything()));
+
+    Ok(())
+}
@@ -351,7 +351,7 @@ pub enum SigningAlgorithm {
 /// Key used to sign the attestation report.
 ///
 /// See Table 23 of the specification.
-#[derive(Debug, FromRepr, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, FromRepr, PartialEq)]
 #[repr(u32)]
 pub enum SigningKey {
     VCEK = 0,
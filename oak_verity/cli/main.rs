@@ -25,7 +25,9 @@ use std::{
 
 use anyhow::{Context, Result};
 use clap::Parser;
-use oak_proto_rust::oak::verity::ExecuteRequest;
+use oak_proto_rust::oak::verity::{
+    ExecutePipelineRequest, ExecuteRequest, ManifestFormat, PipelineStep,
+};
 use oak_verity::OakVerity;
 use prost::Message;
 
@@ -39,16 +41,23 @@ struct Flags {
     #[arg(long, value_parser = path_parser, value_name = "FILE")]
     input_data: PathBuf,
 
-    /// Path to the WebAssembly module file.
-    #[arg(long, value_parser = path_parser, value_name = "FILE")]
-    wasm_module: PathBuf,
+    /// Path to the WebAssembly module file. May be repeated to execute a
+    /// pipeline, where the output of each module is the input of the next one.
+    #[arg(long, value_parser = path_parser, value_name = "FILE", required = true)]
+    wasm_module: Vec<PathBuf>,
+
+    /// Produce the manifest as an in-toto statement with a SLSA provenance
+    /// predicate. Always executes the modules as a pipeline.
+    #[arg(long)]
+    in_toto: bool,
 
     /// Optional: Path where the raw output data will be written.
     #[arg(long, value_parser = path_parser, value_name = "FILE")]
     output_data: Option<PathBuf>,
 
     /// Optional: Path where the full ExecuteResponse protobuf will be written
-    /// (contains both output data and manifest).
+    /// (contains both output data and manifest). For pipelines, this is an
+    /// ExecutePipelineResponse instead.
     #[arg(long, value_parser = path_parser, value_name = "FILE")]
     output_response: Option<PathBuf>,
 }
@@ -71,29 +80,57 @@ fn main() -> Result<()> {
     let input_data = fs::read(&input_path)
         .with_context(|| format!("Failed to read input data from {:?}", input_path))?;
 
-    // Read the compiled Wasm module (must be Oak Functions compatible).
-    let wasm_path = base_path.join(&flags.wasm_module);
-    let wasm_module = fs::read(&wasm_path)
-        .with_context(|| format!("Failed to read Wasm module from {:?}", wasm_path))?;
+    // Read the compiled Wasm modules (must be Oak Functions compatible).
+    let mut wasm_modules = flags
+        .wasm_module
+        .iter()
+        .map(|wasm_module| {
+            let wasm_path = base_path.join(wasm_module);
+            fs::read(&wasm_path)
+                .with_context(|| format!("Failed to read Wasm module from {:?}", wasm_path))
+        })
+        .collect::<Result<Vec<_>>>()?;
 
     // Create Oak Verity instance which handles Wasm execution and manifest
     // generation.
     let oak_verity = OakVerity::new().context("Failed to create Oak Verity instance")?;
 
-    // Create the execution request with input data and Wasm module.
-    let request = ExecuteRequest { input_data, wasm_module };
-
-    // Execute the Wasm module with Oak Verity.
-    // This runs the Wasm module and generates a manifest with SHA-256 digests.
-    let response =
-        oak_verity.execute(request).context("Failed to execute Wasm module with Oak Verity")?;
+    // Execute the Wasm modules with Oak Verity.
+    // This runs the Wasm modules and generates a manifest with SHA-256 digests.
+    let (output_data, response_bytes) = if wasm_modules.len() == 1 && !flags.in_toto {
+        let request = ExecuteRequest {
+            input_data,
+            wasm_module: wasm_modules.remove(0),
+            ..Default::default()
+        };
+        let response =
+            oak_verity.execute(request).context("Failed to execute Wasm module with Oak Verity")?;
+        let response_bytes = response.encode_to_vec();
+        (response.output_data, response_bytes)
+    } else {
+        let manifest_format =
+            if flags.in_toto { ManifestFormat::InToto } else { ManifestFormat::Proto };
+        let request = ExecutePipelineRequest {
+            input_data,
+            steps: wasm_modules
+                .into_iter()
+                .map(|wasm_module| PipelineStep { wasm_module, ..Default::default() })
+                .collect(),
+            manifest_format: manifest_format.into(),
+        };
+        let response = oak_verity
+            .execute_pipeline(request)
+            .context("Failed to execute Wasm pipeline with Oak Verity")?;
+        let response_bytes = response.encode_to_vec();
+        (response.output_data, response_bytes)
+    };
 
     println!("✅ Execution successful!");
 
     // Optionally write the raw output data to a file.
     if let Some(output_data_path) = flags.output_data {
         let output_data_path = base_path.join(output_data_path);
-        fs::write(&output_data_path, &response.output_data)
+        fs::write(&output_data_path, &output_data)
             .with_context(|| format!("Failed to write output data to {:?}", output_data_path))?;
         println!("   Raw output data written to: {:?}", output_data_path);
     }
@@ -101,8 +138,6 @@ fn main() -> Result<()> {
     // Optionally write the full ExecuteResponse protobuf (contains both output_data
     // and manifest).
     if let Some(output_response_path) = flags.output_response {
-        let response_bytes = response.encode_to_vec();
-
         let output_response_path = base_path.join(output_response_path);
         fs::write(&output_response_path, &response_bytes).with_context(|| {
             format!("Failed to write ExecuteResponse to {:?}", output_response_path)
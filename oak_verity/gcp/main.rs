@@ -27,7 +27,8 @@ use anyhow::Result;
 use maplit::btreemap;
 use oak_attestation_gcp::assertions::GcpAssertionGenerator;
 use oak_attestation_types::assertion_generator::AssertionGenerator;
-use oak_verity_grpc::OakVerityService;
+use oak_verity::StreamLimits;
+use oak_verity_grpc::{OakVerityService, DEFAULT_MAX_CONCURRENT_STREAMS};
 use tonic::transport::Server;
 
 const OAK_VERITY_GCP_CONFIDENTIAL_SPACE_TOKEN_AUDIENCE: &str = "z00063450530956731445";
@@ -41,7 +42,11 @@ async fn main() -> Result<()> {
         ) as Box<dyn AssertionGenerator>,
     };
 
-    let service = OakVerityService::new(assertion_generators);
+    let service = OakVerityService::new(
+        assertion_generators,
+        StreamLimits::default(),
+        DEFAULT_MAX_CONCURRENT_STREAMS,
+    );
     let addr = "[::]:8080".parse::<std::net::SocketAddr>()?;
     eprintln!("listening on address {addr}");
     Server::builder()
@@ -78,7 +78,7 @@ async fn main() -> Result<()> {
         .with_context(|| format!("Failed to read Wasm module from {:?}", wasm_path))?;
 
     // Create the execution request with input data and Wasm module.
-    let request = ExecuteRequest { input_data, wasm_module };
+    let request = ExecuteRequest { input_data, wasm_module, ..Default::default() };
 
     let mut client = OakVerityServiceClient::connect(flags.server_address).await?;
     let response = client.execute(request).await?.into_inner();
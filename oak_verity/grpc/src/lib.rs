@@ -14,25 +14,43 @@
 // limitations under the License.
 //
 
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, sync::Arc};
 
 use anyhow::Result;
 use oak_attestation_types::assertion_generator::AssertionGenerator;
 use oak_grpc::oak::verity::oak_verity_service_server::OakVerityService as OakVerityServiceTrait;
-use oak_proto_rust::oak::verity::{ExecuteRequest, ExecuteResponse};
-use oak_verity::OakVerity;
-use tonic::{Request, Response, Status};
+use oak_proto_rust::oak::verity::{
+    ExecutePipelineRequest, ExecutePipelineResponse, ExecuteRequest, ExecuteResponse,
+    ExecuteStreamRequest,
+};
+use oak_verity::{module_cache::ModuleNotFound, ModuleCache, OakVerity, StreamLimits};
+use tokio::sync::Semaphore;
+use tonic::{Code, Request, Response, Status, Streaming};
+
+/// Default maximum number of streamed executions that are served at the same
+/// time. Each of them buffers up to the [`StreamLimits`] in memory.
+pub const DEFAULT_MAX_CONCURRENT_STREAMS: usize = 4;
 
 /// Wrapper around the core [`OakVerity`] library to expose it as a gRPC
 /// service.
 pub struct OakVerityService {
     oak_verity: OakVerity,
+    stream_permits: Arc<Semaphore>,
 }
 
 impl OakVerityService {
-    pub fn new(assertion_generators: BTreeMap<String, Box<dyn AssertionGenerator>>) -> Self {
-        let oak_verity = OakVerity { assertion_generators };
-        Self { oak_verity }
+    /// Creates a service whose streamed executions are limited to
+    /// `stream_limits`, with at most `max_concurrent_streams` of them served
+    /// at the same time. Further streams are rejected with
+    /// `RESOURCE_EXHAUSTED`.
+    pub fn new(
+        assertion_generators: BTreeMap<String, Box<dyn AssertionGenerator>>,
+        stream_limits: StreamLimits,
+        max_concurrent_streams: usize,
+    ) -> Self {
+        let oak_verity =
+            OakVerity { assertion_generators, module_cache: ModuleCache::default(), stream_limits };
+        Self { oak_verity, stream_permits: Arc::new(Semaphore::new(max_concurrent_streams)) }
     }
 }
 
@@ -47,7 +65,49 @@ impl OakVerityServiceTrait for OakVerityService {
         let response = self
             .oak_verity
             .execute(request)
-            .map_err(|err| Status::internal(format!("failed to execute Wasm module: {}", err)))?;
+            .map_err(|err| to_status(Code::Internal, "failed to execute Wasm module", err))?;
+        Ok(Response::new(response))
+    }
+
+    async fn execute_stream(
+        &self,
+        request: Request<Streaming<ExecuteStreamRequest>>,
+    ) -> Result<Response<ExecuteResponse>, Status> {
+        // Held until the execution finishes, since the stream buffers the Wasm
+        // module and the input data until then.
+        let _permit = self
+            .stream_permits
+            .try_acquire()
+            .map_err(|_| Status::resource_exhausted("too many concurrent streamed executions"))?;
+        let mut requests = request.into_inner();
+        let mut stream = self.oak_verity.execute_stream();
+        while let Some(request) = requests.message().await? {
+            stream
+                .push(request)
+                .map_err(|err| to_status(Code::InvalidArgument, "invalid stream request", err))?;
+        }
+        let response = stream
+            .finish()
+            .map_err(|err| to_status(Code::Internal, "failed to execute Wasm module", err))?;
         Ok(Response::new(response))
     }
+
+    async fn execute_pipeline(
+        &self,
+        request: Request<ExecutePipelineRequest>,
+    ) -> Result<Response<ExecutePipelineResponse>, Status> {
+        let request = request.into_inner();
+        let response = self
+            .oak_verity
+            .execute_pipeline(request)
+            .map_err(|err| to_status(Code::Internal, "failed to execute pipeline", err))?;
+        Ok(Response::new(response))
+    }
+}
+
+/// Converts an Oak Verity error to a gRPC status, so that callers can tell when
+/// a Wasm module referred to by digest needs to be sent again.
+fn to_status(code: Code, message: &str, err: anyhow::Error) -> Status {
+    let code = if err.downcast_ref::<ModuleNotFound>().is_some() { Code::NotFound } else { code };
+    Status::new(code, format!("{message}: {err:#}"))
 }
@@ -19,12 +19,17 @@
 
 use std::collections::BTreeMap;
 
-use oak_verity_grpc::OakVerityService;
+use oak_verity::StreamLimits;
+use oak_verity_grpc::{OakVerityService, DEFAULT_MAX_CONCURRENT_STREAMS};
 use tonic::transport::Server;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let service = OakVerityService::new(BTreeMap::new());
+    let service = OakVerityService::new(
+        BTreeMap::new(),
+        StreamLimits::default(),
+        DEFAULT_MAX_CONCURRENT_STREAMS,
+    );
     let addr = "[::]:8080".parse::<std::net::SocketAddr>()?;
     Server::builder()
         .add_service(oak_grpc::oak::verity::oak_verity_service_server::OakVerityServiceServer::new(
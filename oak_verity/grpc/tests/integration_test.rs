@@ -19,13 +19,26 @@
 use std::{collections::BTreeMap, time::Duration};
 
 use anyhow::Result;
+use futures::StreamExt;
 use oak_grpc::oak::verity::{
     oak_verity_service_client::OakVerityServiceClient,
     oak_verity_service_server::OakVerityServiceServer,
 };
-use oak_proto_rust::oak::verity::{ExecuteRequest, ExecutionManifest};
+use oak_proto_rust::oak::{
+    verity::{
+        execute_stream_request::Request, ExecutePipelineRequest, ExecuteRequest,
+        ExecuteStreamRequest, ExecutionManifest, ManifestFormat, PipelineManifest, PipelineStep,
+    },
+    RawDigest,
+};
+use oak_verity::{StreamLimits, PIPELINE_MANIFEST_TYPE_URL};
+use oak_verity_grpc::DEFAULT_MAX_CONCURRENT_STREAMS;
 use prost::Message;
-use tonic::transport::{Channel, Endpoint, Server};
+use sha2::{Digest, Sha256};
+use tonic::{
+    transport::{Channel, Endpoint, Server},
+    Code,
+};
 
 const ECHO_WASM_PATH: &str = "oak_functions/examples/echo/echo.wasm";
 
@@ -38,8 +51,12 @@ async fn create_client(port: u16) -> Result<OakVerityServiceClient<Channel>> {
     Ok(OakVerityServiceClient::new(channel))
 }
 
-async fn run_server(port: u16) -> anyhow::Result<()> {
-    let service = oak_verity_grpc::OakVerityService::new(BTreeMap::new());
+async fn run_server(port: u16, max_concurrent_streams: usize) -> anyhow::Result<()> {
+    let service = oak_verity_grpc::OakVerityService::new(
+        BTreeMap::new(),
+        StreamLimits::default(),
+        max_concurrent_streams,
+    );
     let addr = format!("[::]:{}", port).parse::<std::net::SocketAddr>()?;
     Server::builder().add_service(OakVerityServiceServer::new(service)).serve(addr).await?;
     Ok(())
@@ -49,7 +66,7 @@ async fn run_server(port: u16) -> anyhow::Result<()> {
 async fn test_execute_with_echo_wasm() -> Result<()> {
     println!("Current dir: {:?}", std::env::current_dir());
     let port = portpicker::pick_unused_port().expect("failed to pick a port");
-    let server_handle = tokio::spawn(run_server(port));
+    let server_handle = tokio::spawn(run_server(port, DEFAULT_MAX_CONCURRENT_STREAMS));
 
     // The server is automatically started as part of the test setup.
     let mut client = create_client(port).await?;
@@ -59,7 +76,8 @@ async fn test_execute_with_echo_wasm() -> Result<()> {
         .unwrap_or_else(|_| panic!("Failed to read echo.wasm from {}", wasm_path.display()));
     let test_input = b"Hello, from a test!".to_vec();
 
-    let request = ExecuteRequest { wasm_module, input_data: test_input.clone() };
+    let request =
+        ExecuteRequest { wasm_module, input_data: test_input.clone(), ..Default::default() };
 
     let response = client.execute(request).await?.into_inner();
 
@@ -84,3 +102,142 @@ async fn test_execute_with_echo_wasm() -> Result<()> {
     server_handle.abort();
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_execute_pipeline_with_echo_wasm() -> Result<()> {
+    let port = portpicker::pick_unused_port().expect("failed to pick a port");
+    let server_handle = tokio::spawn(run_server(port, DEFAULT_MAX_CONCURRENT_STREAMS));
+
+    let mut client = create_client(port).await?;
+
+    let wasm_path = oak_file_utils::data_path(ECHO_WASM_PATH);
+    let wasm_module = std::fs::read(&wasm_path)
+        .unwrap_or_else(|_| panic!("Failed to read echo.wasm from {}", wasm_path.display()));
+    let test_input = b"Hello, from a pipeline test!".to_vec();
+
+    let request = ExecutePipelineRequest {
+        input_data: test_input.clone(),
+        steps: vec![PipelineStep { wasm_module, ..Default::default() }; 2],
+        manifest_format: ManifestFormat::Proto.into(),
+    };
+
+    let response = client.execute_pipeline(request).await?.into_inner();
+
+    assert_eq!(response.output_data, test_input, "Output must match input for echo pipeline");
+    assert_eq!(response.manifest_format(), ManifestFormat::Proto);
+
+    // Verify that the steps of the manifest are linked.
+    let manifest = prost_types::Any::decode(response.serialized_manifest.as_slice())?;
+    assert_eq!(manifest.type_url, PIPELINE_MANIFEST_TYPE_URL);
+    let manifest = PipelineManifest::decode(manifest.value.as_slice())?;
+    assert_eq!(manifest.steps.len(), 2);
+    let first = manifest.steps[0].execution.as_ref().unwrap();
+    let second = manifest.steps[1].execution.as_ref().unwrap();
+    assert_eq!(first.input_data_digest, manifest.input_data_digest);
+    assert_eq!(second.input_data_digest, first.output_data_digest);
+    assert_eq!(manifest.output_data_digest, second.output_data_digest);
+
+    assert!(response.assertions.is_empty());
+
+    server_handle.abort();
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_execute_stream_with_echo_wasm() -> Result<()> {
+    let port = portpicker::pick_unused_port().expect("failed to pick a port");
+    let server_handle = tokio::spawn(run_server(port, DEFAULT_MAX_CONCURRENT_STREAMS));
+
+    let mut client = create_client(port).await?;
+
+    let wasm_path = oak_file_utils::data_path(ECHO_WASM_PATH);
+    let wasm_module = std::fs::read(&wasm_path)
+        .unwrap_or_else(|_| panic!("Failed to read echo.wasm from {}", wasm_path.display()));
+    let wasm_module_digest =
+        RawDigest { sha2_256: Sha256::digest(&wasm_module).to_vec(), ..Default::default() };
+    let test_input: Vec<u8> = (0..100_000).map(|i| (i % 256) as u8).collect();
+
+    let input_data_chunks = || {
+        test_input
+            .chunks(4096)
+            .map(|chunk| ExecuteStreamRequest {
+                request: Some(Request::InputDataChunk(chunk.to_vec())),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // The module has not been sent yet, so it cannot be referred to by digest.
+    let digest_request =
+        ExecuteStreamRequest { request: Some(Request::WasmModuleDigest(wasm_module_digest)) };
+    let status = client
+        .execute_stream(futures::stream::iter(
+            std::iter::once(digest_request.clone()).chain(input_data_chunks()),
+        ))
+        .await
+        .expect_err("Unknown Wasm module digest must be rejected");
+    assert_eq!(status.code(), Code::NotFound);
+
+    let requests = wasm_module
+        .chunks(4096)
+        .map(|chunk| ExecuteStreamRequest {
+            request: Some(Request::WasmModuleChunk(chunk.to_vec())),
+        })
+        .chain(input_data_chunks())
+        .collect::<Vec<_>>();
+    let response = client.execute_stream(futures::stream::iter(requests)).await?.into_inner();
+
+    assert_eq!(response.output_data, test_input, "Output must match input for echo Wasm");
+    let manifest = ExecutionManifest::decode(response.serialized_manifest.as_slice())?;
+    assert_eq!(manifest.input_data_digest, manifest.output_data_digest);
+
+    // The module is now cached, and the manifest must be the same.
+    let cached_response = client
+        .execute_stream(futures::stream::iter(
+            std::iter::once(digest_request).chain(input_data_chunks()),
+        ))
+        .await?
+        .into_inner();
+    assert_eq!(cached_response.serialized_manifest, response.serialized_manifest);
+
+    server_handle.abort();
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_execute_stream_rejects_too_many_concurrent_streams() -> Result<()> {
+    let port = portpicker::pick_unused_port().expect("failed to pick a port");
+    let server_handle = tokio::spawn(run_server(port, 1));
+
+    let client = create_client(port).await?;
+
+    // Keep a stream open, which holds the only stream permit.
+    let first_chunk =
+        ExecuteStreamRequest { request: Some(Request::WasmModuleChunk(b"\0asm".to_vec())) };
+    let mut open_client = client.clone();
+    let open_stream = tokio::spawn(async move {
+        open_client
+            .execute_stream(futures::stream::iter([first_chunk]).chain(futures::stream::pending()))
+            .await
+    });
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    let status = client
+        .clone()
+        .execute_stream(futures::stream::iter(Vec::<ExecuteStreamRequest>::new()))
+        .await
+        .expect_err("A second concurrent stream must be rejected");
+    assert_eq!(status.code(), Code::ResourceExhausted);
+
+    // Once the open stream is closed, its permit is released.
+    open_stream.abort();
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    let status = client
+        .clone()
+        .execute_stream(futures::stream::iter(Vec::<ExecuteStreamRequest>::new()))
+        .await
+        .expect_err("A stream without a Wasm module must be rejected");
+    assert_ne!(status.code(), Code::ResourceExhausted);
+
+    server_handle.abort();
+    Ok(())
+}
@@ -21,60 +21,267 @@
 //! WebAssembly modules using the same ABI as Oak Functions and produces
 //! manifests containing digests of inputs and outputs for verification
 //! purposes.
+//!
+//! Modules can also be combined into pipelines, whose steps form a directed
+//! acyclic graph, in which case the manifest links the digests of all the
+//! steps, optionally as an in-toto provenance statement (see [`provenance`]).
+//!
+//! Wasm modules are kept in a [`ModuleCache`], so that subsequent requests can
+//! refer to them by digest, and large inputs can be streamed in chunks with
+//! [`OakVerity::execute_stream`].
 
-use std::collections::BTreeMap;
+pub mod module_cache;
+pub mod provenance;
+mod stream;
 
-use anyhow::{Context, Result};
+use std::{borrow::Cow, collections::BTreeMap, sync::Arc};
+
+use anyhow::{ensure, Context, Result};
 use oak_attestation_types::assertion_generator::AssertionGenerator;
 use oak_functions_service::{
     instance::OakFunctionsInstance,
     wasm::{WasmConfig, WasmHandler},
 };
 use oak_proto_rust::oak::{
+    attestation::v1::Assertion,
     functions::InitializeRequest,
-    verity::{ExecuteRequest, ExecuteResponse, ExecutionManifest},
+    verity::{
+        step_input::Source, ExecutePipelineRequest, ExecutePipelineResponse, ExecuteRequest,
+        ExecuteResponse, ExecutionManifest, ManifestFormat, PipelineManifest, PipelineStepManifest,
+        StepInput, StepInputData,
+    },
     RawDigest,
 };
 use prost::Message;
 use sha2::{Digest, Sha256};
 
+pub use crate::{
+    module_cache::ModuleCache,
+    stream::{ExecuteStream, StreamLimits},
+};
+
+/// Type URL of the `google.protobuf.Any` wrapping a pipeline manifest in the
+/// proto format, which distinguishes it from a serialized
+/// [`ExecutionManifest`] covered by the same assertions.
+pub const PIPELINE_MANIFEST_TYPE_URL: &str = "type.googleapis.com/oak.verity.PipelineManifest";
+
 /// Main entry point for Oak Verity execution.
 pub struct OakVerity {
     pub assertion_generators: BTreeMap<String, Box<dyn AssertionGenerator>>,
+    pub module_cache: ModuleCache,
+    pub stream_limits: StreamLimits,
+}
+
+/// A Wasm module together with its digest.
+struct WasmModule {
+    bytecode: Arc<[u8]>,
+    digest: RawDigest,
 }
 
 impl OakVerity {
     /// Create a new Oak Verity instance.
     pub fn new() -> Result<Self> {
-        Ok(Self { assertion_generators: BTreeMap::new() })
+        Ok(Self {
+            assertion_generators: BTreeMap::new(),
+            module_cache: ModuleCache::default(),
+            stream_limits: StreamLimits::default(),
+        })
     }
 
     /// Execute a Wasm module with the given request and return the response
     /// with manifest.
     pub fn execute(&self, request: ExecuteRequest) -> Result<ExecuteResponse> {
-        // Execute the Wasm module using Oak Functions infrastructure.
-        let output_data = self.execute_wasm(&request.wasm_module, &request.input_data)?;
+        let wasm_module =
+            self.resolve_wasm_module(request.wasm_module, request.wasm_module_digest.as_ref())?;
+        let input_data_digest = Self::create_raw_digest(&request.input_data);
+        let (output_data, manifest) =
+            self.execute_step(&wasm_module, &request.input_data, input_data_digest)?;
+
+        let serialized_manifest = manifest.encode_to_vec();
+        let assertions = self.generate_assertions(&serialized_manifest)?;
+
+        Ok(ExecuteResponse { serialized_manifest, assertions, output_data })
+    }
+
+    /// Start a streamed execution, to which the Wasm module and the input data
+    /// are passed in chunks.
+    pub fn execute_stream(&self) -> ExecuteStream<'_> {
+        ExecuteStream::new(self)
+    }
+
+    /// Execute a pipeline of Wasm modules, passing the pipeline input and the
+    /// outputs of earlier steps to later ones as given by the step inputs, and
+    /// return the response with a manifest linking all the steps.
+    pub fn execute_pipeline(
+        &self,
+        request: ExecutePipelineRequest,
+    ) -> Result<ExecutePipelineResponse> {
+        ensure!(!request.steps.is_empty(), "pipeline must contain at least one step");
+
+        let manifest_format = match request.manifest_format() {
+            ManifestFormat::Unspecified | ManifestFormat::Proto => ManifestFormat::Proto,
+            ManifestFormat::InToto => ManifestFormat::InToto,
+        };
+
+        let input_data_digest = Self::create_raw_digest(&request.input_data);
+        let mut outputs: Vec<Vec<u8>> = Vec::with_capacity(request.steps.len());
+        let mut steps = Vec::with_capacity(request.steps.len());
+        for (i, step) in request.steps.into_iter().enumerate() {
+            let (output_data, manifest) = Self::resolve_step_inputs(i, step.inputs)
+                .and_then(|inputs| {
+                    let wasm_module = self
+                        .resolve_wasm_module(step.wasm_module, step.wasm_module_digest.as_ref())?;
+                    let data = Self::step_input_data(&request.input_data, &outputs, &inputs);
+                    let (output_data, execution) =
+                        self.execute_step(&wasm_module, &data, Self::create_raw_digest(&data))?;
+                    Ok((output_data, PipelineStepManifest { execution: Some(execution), inputs }))
+                })
+                .with_context(|| format!("Failed to execute pipeline step {i}"))?;
+            steps.push(manifest);
+            outputs.push(output_data);
+        }
+
+        let manifest = PipelineManifest {
+            input_data_digest: Some(input_data_digest),
+            output_data_digest: steps
+                .last()
+                .and_then(|step| step.execution.as_ref())
+                .and_then(|execution| execution.output_data_digest.clone()),
+            steps,
+        };
+
+        let serialized_manifest = match manifest_format {
+            ManifestFormat::InToto => provenance::serialize_statement(
+                &provenance::make_statement(&manifest)
+                    .context("Failed to create provenance statement")?,
+            )?,
+            _ => prost_types::Any {
+                type_url: PIPELINE_MANIFEST_TYPE_URL.to_string(),
+                value: manifest.encode_to_vec(),
+            }
+            .encode_to_vec(),
+        };
+        let assertions = self.generate_assertions(&serialized_manifest)?;
+
+        Ok(ExecutePipelineResponse {
+            serialized_manifest,
+            assertions,
+            output_data: outputs.pop().unwrap_or_default(),
+            manifest_format: manifest_format.into(),
+        })
+    }
+
+    /// Return the sources of the input of the step with the given index, which
+    /// default to the output of the previous step, or the pipeline input for
+    /// the first step. Steps may only consume the outputs of earlier steps, so
+    /// that the pipeline is acyclic.
+    fn resolve_step_inputs(index: usize, inputs: Vec<StepInput>) -> Result<Vec<StepInput>> {
+        if inputs.is_empty() {
+            let source = match index.checked_sub(1) {
+                Some(previous) => Source::StepOutput(previous.try_into()?),
+                None => Source::PipelineInput(true),
+            };
+            return Ok(vec![StepInput { source: Some(source) }]);
+        }
 
-        // Generate the manifest with digests of input, Wasm module, and output.
-        let input_digest = Self::create_raw_digest(&request.input_data);
-        let wasm_digest = Self::create_raw_digest(&request.wasm_module);
-        let output_digest = Self::create_raw_digest(&output_data);
+        for input in &inputs {
+            match input.source {
+                Some(Source::PipelineInput(true)) => {}
+                Some(Source::StepOutput(step)) => ensure!(
+                    (step as usize) < index,
+                    "Input refers to the output of step {step}, which is not an earlier step"
+                ),
+                _ => anyhow::bail!("Input has no source"),
+            }
+        }
+        Ok(inputs)
+    }
+
+    /// Return the data passed to a step with the given (resolved) input
+    /// sources: a single input unchanged, or several inputs as a serialized
+    /// [`StepInputData`].
+    fn step_input_data<'a>(
+        pipeline_input: &'a [u8],
+        outputs: &'a [Vec<u8>],
+        inputs: &[StepInput],
+    ) -> Cow<'a, [u8]> {
+        let data = |input: &StepInput| match input.source {
+            Some(Source::StepOutput(step)) => outputs[step as usize].as_slice(),
+            _ => pipeline_input,
+        };
+        match inputs {
+            [input] => Cow::Borrowed(data(input)),
+            _ => Cow::Owned(
+                StepInputData { inputs: inputs.iter().map(|input| data(input).to_vec()).collect() }
+                    .encode_to_vec(),
+            ),
+        }
+    }
+
+    /// Execute a single Wasm module and return its output together with the
+    /// manifest with digests of input, Wasm module, and output.
+    fn execute_step(
+        &self,
+        wasm_module: &WasmModule,
+        input_data: &[u8],
+        input_data_digest: RawDigest,
+    ) -> Result<(Vec<u8>, ExecutionManifest)> {
+        // Execute the Wasm module using Oak Functions infrastructure.
+        let output_data = self.execute_wasm(&wasm_module.bytecode, input_data)?;
 
         let manifest = ExecutionManifest {
-            input_data_digest: Some(input_digest),
-            wasm_module_digest: Some(wasm_digest),
-            output_data_digest: Some(output_digest),
+            input_data_digest: Some(input_data_digest),
+            wasm_module_digest: Some(wasm_module.digest.clone()),
+            output_data_digest: Some(Self::create_raw_digest(&output_data)),
         };
 
-        let serialized_manifest = manifest.encode_to_vec();
+        Ok((output_data, manifest))
+    }
+
+    /// Return the Wasm module with the given bytecode, adding it to the module
+    /// cache, or look it up in the module cache by digest if the bytecode is
+    /// empty. If both are given, the digest must match the bytecode.
+    fn resolve_wasm_module(
+        &self,
+        bytecode: Vec<u8>,
+        digest: Option<&RawDigest>,
+    ) -> Result<WasmModule> {
+        if bytecode.is_empty() {
+            let digest = digest.context("Either a Wasm module or its digest must be provided")?;
+            let bytecode = self.module_cache.get(digest)?;
+            let digest = RawDigest { sha2_256: digest.sha2_256.clone(), ..Default::default() };
+            return Ok(WasmModule { bytecode, digest });
+        }
+
+        let bytecode_digest = Self::create_raw_digest(&bytecode);
+        if let Some(digest) = digest {
+            ensure!(
+                digest.sha2_256 == bytecode_digest.sha2_256,
+                "Wasm module does not match the given digest"
+            );
+        }
+        Ok(self.cache_wasm_module(bytecode.into(), bytecode_digest))
+    }
+
+    /// Add a Wasm module with a digest computed by the caller to the module
+    /// cache.
+    fn cache_wasm_module(&self, bytecode: Arc<[u8]>, digest: RawDigest) -> WasmModule {
+        self.module_cache.insert(digest.sha2_256.clone(), bytecode.clone());
+        WasmModule { bytecode, digest }
+    }
 
+    /// Generate assertions about the serialized manifest with all the
+    /// configured assertion generators.
+    fn generate_assertions(
+        &self,
+        serialized_manifest: &[u8],
+    ) -> Result<BTreeMap<String, Assertion>> {
         let mut assertions = BTreeMap::new();
         for (id, generator) in &self.assertion_generators {
-            let assertion = generator.generate(&serialized_manifest)?;
+            let assertion = generator.generate(serialized_manifest)?;
             assertions.insert(id.clone(), assertion);
         }
-
-        Ok(ExecuteResponse { serialized_manifest, assertions, output_data })
+        Ok(assertions)
     }
 
     /// Execute a Wasm module with the given input data.
@@ -119,8 +326,6 @@ pub fn compute_sha256_digest(data: &[u8]) -> Vec<u8> {
 
 #[cfg(test)]
 mod tests {
-    use oak_proto_rust::oak::attestation::v1::Assertion;
-
     use super::*;
 
     #[test]
@@ -169,7 +374,11 @@ mod tests {
 
         let wasm_module = std::fs::read("oak_functions/examples/echo/echo.wasm").unwrap();
 
-        let request = ExecuteRequest { input_data: b"test input".to_vec(), wasm_module };
+        let request = ExecuteRequest {
+            input_data: b"test input".to_vec(),
+            wasm_module,
+            ..Default::default()
+        };
 
         let response = oak_verity.execute(request).unwrap();
 
@@ -179,6 +388,16 @@ mod tests {
         assert_eq!(assertion.content, "ok".to_string().into_bytes());
     }
 
+    #[test]
+    fn test_execute_pipeline_without_steps_fails() {
+        let oak_verity = OakVerity::new().unwrap();
+
+        let request =
+            ExecutePipelineRequest { input_data: b"test input".to_vec(), ..Default::default() };
+
+        assert!(oak_verity.execute_pipeline(request).is_err());
+    }
+
     // Note: Full integration tests with actual Wasm modules would require
     // compiled Oak Functions-compatible Wasm modules and are better suited
     // for integration test files, contained in the tests folder.
@@ -0,0 +1,201 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Cache of Wasm modules keyed by their digest, so that callers can refer to a
+//! module that was already sent to Oak Verity instead of sending it again.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use oak_proto_rust::oak::RawDigest;
+
+/// Default maximum total size of the modules kept in the cache.
+pub const DEFAULT_CAPACITY_BYTES: usize = 256 * 1024 * 1024;
+
+/// Length of a SHA2-256 digest, the only algorithm used as cache key.
+const SHA2_256_LENGTH: usize = 32;
+
+/// Error returned when a module is requested by a digest that is not in the
+/// cache, either because it was never sent or because it has been evicted.
+///
+/// Callers should send the module bytes again.
+#[derive(Debug)]
+pub struct ModuleNotFound {
+    pub sha2_256: Vec<u8>,
+}
+
+impl fmt::Display for ModuleNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Wasm module with SHA2-256 digest ")?;
+        for byte in &self.sha2_256 {
+            write!(f, "{byte:02x}")?;
+        }
+        write!(f, " not found")
+    }
+}
+
+impl std::error::Error for ModuleNotFound {}
+
+/// Least recently used cache of Wasm modules, bounded by their total size.
+pub struct ModuleCache {
+    capacity_bytes: usize,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    modules: HashMap<Vec<u8>, Arc<[u8]>>,
+    /// SHA2-256 digests of the cached modules, from least to most recently
+    /// used.
+    order: VecDeque<Vec<u8>>,
+    size_bytes: usize,
+}
+
+impl ModuleCache {
+    /// Create an empty cache that keeps at most `capacity_bytes` of modules.
+    pub fn new(capacity_bytes: usize) -> Self {
+        Self { capacity_bytes, inner: Mutex::new(Inner::default()) }
+    }
+
+    /// Return the module with the given digest, marking it as recently used.
+    pub fn get(&self, digest: &RawDigest) -> anyhow::Result<Arc<[u8]>> {
+        let key = Self::key(digest)?;
+        let mut inner = self.inner.lock().expect("module cache lock poisoned");
+        let wasm_module = inner
+            .modules
+            .get(key)
+            .cloned()
+            .ok_or_else(|| ModuleNotFound { sha2_256: key.to_vec() })?;
+        inner.touch(key);
+        Ok(wasm_module)
+    }
+
+    /// Insert a module with the given SHA2-256 digest, evicting the least
+    /// recently used modules if the cache is full. Modules larger than the
+    /// capacity of the cache are not kept.
+    ///
+    /// The digest must have been computed over `wasm_module` by the caller.
+    pub(crate) fn insert(&self, sha2_256: Vec<u8>, wasm_module: Arc<[u8]>) {
+        if wasm_module.len() > self.capacity_bytes {
+            return;
+        }
+        let mut inner = self.inner.lock().expect("module cache lock poisoned");
+        if inner.modules.contains_key(&sha2_256) {
+            inner.touch(&sha2_256);
+            return;
+        }
+        while inner.size_bytes + wasm_module.len() > self.capacity_bytes {
+            let Some(evicted) = inner.order.pop_front() else {
+                break;
+            };
+            if let Some(evicted_module) = inner.modules.remove(&evicted) {
+                inner.size_bytes -= evicted_module.len();
+            }
+        }
+        inner.size_bytes += wasm_module.len();
+        inner.order.push_back(sha2_256.clone());
+        inner.modules.insert(sha2_256, wasm_module);
+    }
+
+    fn key(digest: &RawDigest) -> anyhow::Result<&[u8]> {
+        anyhow::ensure!(
+            digest.sha2_256.len() == SHA2_256_LENGTH,
+            "Wasm module digests must contain a SHA2-256 digest"
+        );
+        Ok(&digest.sha2_256)
+    }
+}
+
+impl Default for ModuleCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY_BYTES)
+    }
+}
+
+impl Inner {
+    fn touch(&mut self, key: &[u8]) {
+        if let Some(position) = self.order.iter().position(|cached| cached == key) {
+            let key = self.order.remove(position).expect("position is within bounds");
+            self.order.push_back(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute_sha256_digest;
+
+    fn insert(cache: &ModuleCache, wasm_module: &[u8]) -> RawDigest {
+        let sha2_256 = compute_sha256_digest(wasm_module);
+        cache.insert(sha2_256.clone(), wasm_module.into());
+        RawDigest { sha2_256, ..Default::default() }
+    }
+
+    #[test]
+    fn test_get_inserted_module() {
+        let cache = ModuleCache::default();
+        let digest = insert(&cache, b"module");
+
+        assert_eq!(&*cache.get(&digest).unwrap(), b"module");
+    }
+
+    #[test]
+    fn test_get_unknown_module_fails() {
+        let cache = ModuleCache::default();
+        let digest = RawDigest { sha2_256: compute_sha256_digest(b"module"), ..Default::default() };
+
+        let err = cache.get(&digest).unwrap_err();
+        assert!(err.downcast_ref::<ModuleNotFound>().is_some());
+    }
+
+    #[test]
+    fn test_get_without_sha2_256_fails() {
+        let cache = ModuleCache::default();
+        let digest = RawDigest { sha2_512: vec![0; 64], ..Default::default() };
+
+        let err = cache.get(&digest).unwrap_err();
+        assert!(err.downcast_ref::<ModuleNotFound>().is_none());
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used_module() {
+        let cache = ModuleCache::new(8);
+        let first = insert(&cache, b"aaaa");
+        let second = insert(&cache, b"bbbb");
+
+        // Using the first module makes the second one the least recently used.
+        cache.get(&first).unwrap();
+        let third = insert(&cache, b"cccc");
+
+        assert!(cache.get(&first).is_ok());
+        assert!(cache.get(&second).is_err());
+        assert!(cache.get(&third).is_ok());
+    }
+
+    #[test]
+    fn test_does_not_keep_modules_larger_than_capacity() {
+        let cache = ModuleCache::new(4);
+        let small = insert(&cache, b"aaaa");
+        let large = insert(&cache, b"bbbbbbbb");
+
+        assert!(cache.get(&small).is_ok());
+        assert!(cache.get(&large).is_err());
+    }
+}
@@ -0,0 +1,185 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Conversion of pipeline manifests to in-toto statements with a SLSA
+//! provenance v1 predicate.
+//!
+//! See <https://slsa.dev/spec/v1.0/provenance>.
+
+use anyhow::{Context, Result};
+use digest_util::{hex_to_set_digest, raw_to_hex_digest, DigestSet};
+use intoto::statement::{Statement, Subject};
+use oak_proto_rust::oak::{
+    verity::{step_input::Source, PipelineManifest, PipelineStepManifest, StepInput},
+    RawDigest,
+};
+use serde::{Deserialize, Serialize};
+
+/// URI representing in-toto statements.
+pub const STATEMENT_TYPE: &str = "https://in-toto.io/Statement/v1";
+
+/// Predicate type of SLSA provenance v1.
+pub const PREDICATE_TYPE: &str = "https://slsa.dev/provenance/v1";
+
+/// Build type of Oak Verity pipeline executions.
+pub const BUILD_TYPE: &str = "https://project-oak.github.io/oak/oak_verity/pipeline/v1";
+
+/// ID of the Oak Verity builder.
+pub const BUILDER_ID: &str = "https://project-oak.github.io/oak/oak_verity";
+
+/// Name of the subject, which is the output of the last pipeline step.
+pub const OUTPUT_SUBJECT_NAME: &str = "output_data";
+
+/// An in-toto statement describing a pipeline execution.
+pub type ProvenanceStatement = Statement<ProvenancePredicate>;
+
+/// The SLSA provenance v1 predicate.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct ProvenancePredicate {
+    #[serde(rename = "buildDefinition")]
+    pub build_definition: BuildDefinition,
+
+    #[serde(rename = "runDetails")]
+    pub run_details: RunDetails,
+}
+
+/// Describes the inputs of the pipeline execution.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct BuildDefinition {
+    #[serde(rename = "buildType")]
+    pub build_type: String,
+
+    #[serde(rename = "externalParameters")]
+    pub external_parameters: ExternalParameters,
+
+    /// The pipeline input and the Wasm modules of all the steps.
+    #[serde(rename = "resolvedDependencies")]
+    pub resolved_dependencies: Vec<ResourceDescriptor>,
+}
+
+/// The full graph of steps of the pipeline.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct ExternalParameters {
+    pub steps: Vec<StepParameters>,
+}
+
+/// Digests of the input, Wasm module and output of a single step, and the
+/// sources of its input.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct StepParameters {
+    /// Names of the sources of the step input: `input_data` for the pipeline
+    /// input, or `steps/<i>/output_data` for the output of step `i`.
+    pub inputs: Vec<String>,
+
+    #[serde(rename = "inputData")]
+    pub input_data: DigestSet,
+
+    #[serde(rename = "wasmModule")]
+    pub wasm_module: DigestSet,
+
+    #[serde(rename = "outputData")]
+    pub output_data: DigestSet,
+}
+
+/// An artifact consumed by the pipeline execution.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct ResourceDescriptor {
+    pub name: String,
+    pub digest: DigestSet,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct RunDetails {
+    pub builder: Builder,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct Builder {
+    pub id: String,
+}
+
+/// Creates a provenance statement equivalent to the given pipeline manifest.
+pub fn make_statement(manifest: &PipelineManifest) -> Result<ProvenanceStatement> {
+    let steps = manifest
+        .steps
+        .iter()
+        .enumerate()
+        .map(|(i, step)| step_parameters(step).with_context(|| format!("invalid step {i}")))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut resolved_dependencies = vec![ResourceDescriptor {
+        name: "input_data".to_string(),
+        digest: digest_set(manifest.input_data_digest.as_ref())
+            .context("missing pipeline input digest")?,
+    }];
+    resolved_dependencies.extend(steps.iter().enumerate().map(|(i, step)| ResourceDescriptor {
+        name: format!("steps/{i}/wasm_module"),
+        digest: step.wasm_module.clone(),
+    }));
+
+    Ok(ProvenanceStatement {
+        _type: STATEMENT_TYPE.to_string(),
+        predicate_type: PREDICATE_TYPE.to_string(),
+        subject: vec![Subject {
+            name: OUTPUT_SUBJECT_NAME.to_string(),
+            digest: digest_set(manifest.output_data_digest.as_ref())
+                .context("missing pipeline output digest")?,
+        }],
+        predicate: ProvenancePredicate {
+            build_definition: BuildDefinition {
+                build_type: BUILD_TYPE.to_string(),
+                external_parameters: ExternalParameters { steps },
+                resolved_dependencies,
+            },
+            run_details: RunDetails { builder: Builder { id: BUILDER_ID.to_string() } },
+        },
+    })
+}
+
+/// Serializes a provenance statement as JSON.
+pub fn serialize_statement(statement: &ProvenanceStatement) -> Result<Vec<u8>> {
+    Ok(serde_json::to_vec(statement)?)
+}
+
+/// Parses the given serialized JSON into a provenance statement.
+pub fn parse_statement(bytes: &[u8]) -> Result<ProvenanceStatement> {
+    Ok(serde_json::from_slice(bytes)?)
+}
+
+fn step_parameters(step: &PipelineStepManifest) -> Result<StepParameters> {
+    let inputs = step.inputs.iter().map(input_name).collect::<Result<Vec<_>>>()?;
+    let step = step.execution.as_ref().context("missing step execution")?;
+    Ok(StepParameters {
+        inputs,
+        input_data: digest_set(step.input_data_digest.as_ref()).context("missing input digest")?,
+        wasm_module: digest_set(step.wasm_module_digest.as_ref())
+            .context("missing Wasm module digest")?,
+        output_data: digest_set(step.output_data_digest.as_ref())
+            .context("missing output digest")?,
+    })
+}
+
+fn input_name(input: &StepInput) -> Result<String> {
+    match input.source {
+        Some(Source::PipelineInput(true)) => Ok("input_data".to_string()),
+        Some(Source::StepOutput(step)) => Ok(format!("steps/{step}/output_data")),
+        _ => anyhow::bail!("missing input source"),
+    }
+}
+
+fn digest_set(digest: Option<&RawDigest>) -> Option<DigestSet> {
+    digest.map(|digest| hex_to_set_digest(&raw_to_hex_digest(digest)))
+}
@@ -0,0 +1,175 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Execution of Wasm modules with the module and the input data received in
+//! chunks.
+
+use anyhow::{bail, ensure, Context, Result};
+use oak_proto_rust::oak::{
+    verity::{execute_stream_request::Request, ExecuteResponse, ExecuteStreamRequest},
+    RawDigest,
+};
+use prost::Message;
+use sha2::{Digest, Sha256};
+
+use crate::{OakVerity, WasmModule};
+
+/// Default maximum size of a Wasm module sent in chunks.
+pub const DEFAULT_MAX_WASM_MODULE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Default maximum size of the input data sent in chunks.
+pub const DEFAULT_MAX_INPUT_DATA_BYTES: usize = 64 * 1024 * 1024;
+
+/// Maximum sizes of the Wasm module and the input data of a streamed execution,
+/// both of which are buffered in memory until the execution finishes.
+#[derive(Clone, Copy, Debug)]
+pub struct StreamLimits {
+    pub max_wasm_module_bytes: usize,
+    pub max_input_data_bytes: usize,
+}
+
+impl Default for StreamLimits {
+    fn default() -> Self {
+        Self {
+            max_wasm_module_bytes: DEFAULT_MAX_WASM_MODULE_BYTES,
+            max_input_data_bytes: DEFAULT_MAX_INPUT_DATA_BYTES,
+        }
+    }
+}
+
+/// A streamed execution, created with [`OakVerity::execute_stream`].
+///
+/// The Wasm module and the input data are hashed incrementally as chunks are
+/// pushed, so that the resulting manifest is identical to the one produced by
+/// [`OakVerity::execute`] for the same module and input data.
+pub struct ExecuteStream<'a> {
+    oak_verity: &'a OakVerity,
+    wasm_module: WasmModuleState,
+    input_data: Vec<u8>,
+    input_data_hasher: Sha256,
+}
+
+enum WasmModuleState {
+    /// No part of the Wasm module has been received yet.
+    Missing,
+    /// Chunks of the Wasm module are being received.
+    Receiving { bytecode: Vec<u8>, hasher: Sha256 },
+    /// The Wasm module is complete.
+    Ready(WasmModule),
+}
+
+impl<'a> ExecuteStream<'a> {
+    pub(crate) fn new(oak_verity: &'a OakVerity) -> Self {
+        Self {
+            oak_verity,
+            wasm_module: WasmModuleState::Missing,
+            input_data: Vec::new(),
+            input_data_hasher: Sha256::new(),
+        }
+    }
+
+    /// Process the next message of the stream.
+    pub fn push(&mut self, request: ExecuteStreamRequest) -> Result<()> {
+        let limits = self.oak_verity.stream_limits;
+        match request.request.context("Empty stream request")? {
+            Request::WasmModuleChunk(chunk) => match &mut self.wasm_module {
+                WasmModuleState::Missing => {
+                    check_size("Wasm module", 0, &chunk, limits.max_wasm_module_bytes)?;
+                    let mut hasher = Sha256::new();
+                    hasher.update(&chunk);
+                    self.wasm_module = WasmModuleState::Receiving { bytecode: chunk, hasher };
+                }
+                WasmModuleState::Receiving { bytecode, hasher } => {
+                    check_size(
+                        "Wasm module",
+                        bytecode.len(),
+                        &chunk,
+                        limits.max_wasm_module_bytes,
+                    )?;
+                    hasher.update(&chunk);
+                    bytecode.extend_from_slice(&chunk);
+                }
+                WasmModuleState::Ready(_) => {
+                    bail!("Wasm module chunks must be sent before the input data")
+                }
+            },
+            Request::WasmModuleDigest(digest) => {
+                let WasmModuleState::Missing = self.wasm_module else {
+                    bail!("Wasm module digest must be the first message of the stream");
+                };
+                self.wasm_module = WasmModuleState::Ready(
+                    self.oak_verity.resolve_wasm_module(Vec::new(), Some(&digest))?,
+                );
+            }
+            Request::InputDataChunk(chunk) => {
+                self.finish_wasm_module()?;
+                check_size(
+                    "Input data",
+                    self.input_data.len(),
+                    &chunk,
+                    limits.max_input_data_bytes,
+                )?;
+                self.input_data_hasher.update(&chunk);
+                self.input_data.extend_from_slice(&chunk);
+            }
+        }
+        Ok(())
+    }
+
+    /// Execute the Wasm module with all the input data received so far and
+    /// return the response with manifest.
+    pub fn finish(mut self) -> Result<ExecuteResponse> {
+        self.finish_wasm_module()?;
+        let WasmModuleState::Ready(wasm_module) = self.wasm_module else {
+            unreachable!("Wasm module must be ready");
+        };
+        let input_data_digest = finalize_digest(self.input_data_hasher);
+        let (output_data, manifest) =
+            self.oak_verity.execute_step(&wasm_module, &self.input_data, input_data_digest)?;
+
+        let serialized_manifest = manifest.encode_to_vec();
+        let assertions = self.oak_verity.generate_assertions(&serialized_manifest)?;
+
+        Ok(ExecuteResponse { serialized_manifest, assertions, output_data })
+    }
+
+    /// Complete the reception of the Wasm module, adding it to the module cache
+    /// if it was sent in chunks.
+    fn finish_wasm_module(&mut self) -> Result<()> {
+        self.wasm_module = match std::mem::replace(&mut self.wasm_module, WasmModuleState::Missing)
+        {
+            WasmModuleState::Missing => bail!("Wasm module must be sent before the input data"),
+            WasmModuleState::Receiving { bytecode, hasher } => WasmModuleState::Ready(
+                self.oak_verity.cache_wasm_module(bytecode.into(), finalize_digest(hasher)),
+            ),
+            ready @ WasmModuleState::Ready(_) => ready,
+        };
+        Ok(())
+    }
+}
+
+/// Check that appending `chunk` to `received_bytes` doesn't exceed `max_bytes`.
+fn check_size(name: &str, received_bytes: usize, chunk: &[u8], max_bytes: usize) -> Result<()> {
+    ensure!(
+        received_bytes.saturating_add(chunk.len()) <= max_bytes,
+        "{name} exceeds the maximum size of {max_bytes} bytes"
+    );
+    Ok(())
+}
+
+fn finalize_digest(hasher: Sha256) -> RawDigest {
+    RawDigest { sha2_256: hasher.finalize().to_vec(), ..Default::default() }
+}
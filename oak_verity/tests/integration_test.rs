@@ -17,8 +17,17 @@
 //! Integration tests for Oak Verity using real Wasm modules.
 
 use anyhow::Result;
-use oak_proto_rust::oak::verity::{ExecuteRequest, ExecutionManifest};
-use oak_verity::OakVerity;
+use oak_proto_rust::oak::{
+    verity::{
+        execute_stream_request::Request, step_input::Source, ExecutePipelineRequest,
+        ExecuteRequest, ExecuteStreamRequest, ExecutionManifest, ManifestFormat, PipelineManifest,
+        PipelineStep, StepInput, StepInputData,
+    },
+    RawDigest,
+};
+use oak_verity::{
+    compute_sha256_digest, provenance, OakVerity, StreamLimits, PIPELINE_MANIFEST_TYPE_URL,
+};
 use prost::Message;
 
 /// Test Oak Verity with the echo Wasm module.
@@ -36,7 +45,8 @@ fn test_oak_verity_with_echo_wasm() -> Result<()> {
     let test_input = b"Hello, Oak Verity!";
 
     // Create execute request.
-    let request = ExecuteRequest { input_data: test_input.to_vec(), wasm_module };
+    let request =
+        ExecuteRequest { input_data: test_input.to_vec(), wasm_module, ..Default::default() };
 
     // Execute the Wasm module.
     let response = oak_verity.execute(request)?;
@@ -100,8 +110,11 @@ fn test_oak_verity_echo_different_inputs() -> Result<()> {
     ];
 
     for (i, test_input) in test_cases.iter().enumerate() {
-        let request =
-            ExecuteRequest { input_data: test_input.clone(), wasm_module: wasm_module.clone() };
+        let request = ExecuteRequest {
+            input_data: test_input.clone(),
+            wasm_module: wasm_module.clone(),
+            ..Default::default()
+        };
 
         let response =
             oak_verity.execute(request).unwrap_or_else(|_| panic!("Test case {i} must succeed"));
@@ -135,3 +148,347 @@ fn test_oak_verity_echo_different_inputs() -> Result<()> {
 
     Ok(())
 }
+
+/// Test an Oak Verity pipeline chaining the echo Wasm module multiple times.
+#[test]
+fn test_oak_verity_echo_pipeline() -> Result<()> {
+    let wasm_module_path = "oak_functions/examples/echo/echo.wasm";
+    let wasm_module =
+        std::fs::read(wasm_module_path).expect("Failed to read echo.wasm - make sure it's built");
+
+    let oak_verity = OakVerity::new()?;
+
+    let test_input = b"Hello, Oak Verity pipeline!";
+    let request = ExecutePipelineRequest {
+        input_data: test_input.to_vec(),
+        steps: vec![PipelineStep { wasm_module: wasm_module.clone(), ..Default::default() }; 3],
+        manifest_format: ManifestFormat::Unspecified.into(),
+    };
+
+    let response = oak_verity.execute_pipeline(request)?;
+
+    assert_eq!(response.output_data, test_input, "Echo pipeline must return the input data");
+    assert_eq!(response.manifest_format(), ManifestFormat::Proto);
+
+    let manifest = prost_types::Any::decode(response.serialized_manifest.as_slice())?;
+    assert_eq!(manifest.type_url, PIPELINE_MANIFEST_TYPE_URL);
+    let manifest = PipelineManifest::decode(manifest.value.as_slice())?;
+    assert_eq!(manifest.steps.len(), 3);
+
+    // Each step must consume the output of the previous one.
+    let mut previous_output_digest = manifest.input_data_digest.clone();
+    for (i, step) in manifest.steps.iter().enumerate() {
+        let expected_input = match i {
+            0 => Source::PipelineInput(true),
+            _ => Source::StepOutput(i as u32 - 1),
+        };
+        assert_eq!(step.inputs, vec![StepInput { source: Some(expected_input) }]);
+        let execution = step.execution.as_ref().unwrap();
+        assert_eq!(
+            execution.input_data_digest, previous_output_digest,
+            "Step {i} input must be the previous output"
+        );
+        assert_eq!(
+            execution.wasm_module_digest.as_ref().unwrap().sha2_256,
+            compute_sha256_digest(&wasm_module),
+            "Step {i} Wasm module digest must match"
+        );
+        previous_output_digest = execution.output_data_digest.clone();
+    }
+    assert_eq!(manifest.output_data_digest, previous_output_digest);
+    assert_eq!(
+        manifest.output_data_digest.unwrap().sha2_256,
+        compute_sha256_digest(&response.output_data)
+    );
+
+    assert!(response.assertions.is_empty());
+
+    Ok(())
+}
+
+/// Test an Oak Verity pipeline whose steps fan out from the pipeline input and
+/// fan in to the last step.
+#[test]
+fn test_oak_verity_echo_pipeline_dag() -> Result<()> {
+    let wasm_module_path = "oak_functions/examples/echo/echo.wasm";
+    let wasm_module =
+        std::fs::read(wasm_module_path).expect("Failed to read echo.wasm - make sure it's built");
+
+    let oak_verity = OakVerity::new()?;
+
+    let step = |inputs: Vec<Source>| PipelineStep {
+        wasm_module: wasm_module.clone(),
+        inputs: inputs.into_iter().map(|source| StepInput { source: Some(source) }).collect(),
+        ..Default::default()
+    };
+    let test_input = b"Hello, Oak Verity DAG!";
+    let request = ExecutePipelineRequest {
+        input_data: test_input.to_vec(),
+        steps: vec![
+            step(vec![]),
+            step(vec![Source::PipelineInput(true)]),
+            step(vec![Source::StepOutput(0), Source::StepOutput(1), Source::PipelineInput(true)]),
+        ],
+        manifest_format: ManifestFormat::Proto.into(),
+    };
+
+    let response = oak_verity.execute_pipeline(request)?;
+
+    // The last step receives all of its inputs, in order.
+    let expected_output = StepInputData { inputs: vec![test_input.to_vec(); 3] }.encode_to_vec();
+    assert_eq!(response.output_data, expected_output);
+
+    let manifest = prost_types::Any::decode(response.serialized_manifest.as_slice())?;
+    let manifest = PipelineManifest::decode(manifest.value.as_slice())?;
+    assert_eq!(manifest.steps.len(), 3);
+    let execution = |i: usize| manifest.steps[i].execution.clone().unwrap();
+
+    // Both the first and the second step consume the pipeline input.
+    for i in [0, 1] {
+        assert_eq!(
+            manifest.steps[i].inputs,
+            vec![StepInput { source: Some(Source::PipelineInput(true)) }]
+        );
+        assert_eq!(execution(i).input_data_digest, manifest.input_data_digest);
+    }
+
+    // The last step consumes the outputs of both and the pipeline input.
+    assert_eq!(
+        manifest.steps[2].inputs,
+        vec![
+            StepInput { source: Some(Source::StepOutput(0)) },
+            StepInput { source: Some(Source::StepOutput(1)) },
+            StepInput { source: Some(Source::PipelineInput(true)) },
+        ]
+    );
+    assert_eq!(
+        execution(2).input_data_digest.unwrap().sha2_256,
+        compute_sha256_digest(&expected_output)
+    );
+    assert_eq!(manifest.output_data_digest, execution(2).output_data_digest);
+
+    Ok(())
+}
+
+/// Test that pipeline steps can only consume the outputs of earlier steps.
+#[test]
+fn test_oak_verity_pipeline_with_later_step_input_fails() -> Result<()> {
+    let wasm_module_path = "oak_functions/examples/echo/echo.wasm";
+    let wasm_module =
+        std::fs::read(wasm_module_path).expect("Failed to read echo.wasm - make sure it's built");
+
+    let oak_verity = OakVerity::new()?;
+
+    let result = oak_verity.execute_pipeline(ExecutePipelineRequest {
+        input_data: b"test input".to_vec(),
+        steps: vec![PipelineStep {
+            wasm_module,
+            inputs: vec![StepInput { source: Some(Source::StepOutput(0)) }],
+            ..Default::default()
+        }],
+        ..Default::default()
+    });
+    assert!(result.is_err(), "A step must not consume its own output");
+
+    Ok(())
+}
+
+/// Test an Oak Verity pipeline returning an in-toto provenance statement.
+#[test]
+fn test_oak_verity_echo_pipeline_in_toto() -> Result<()> {
+    let wasm_module_path = "oak_functions/examples/echo/echo.wasm";
+    let wasm_module =
+        std::fs::read(wasm_module_path).expect("Failed to read echo.wasm - make sure it's built");
+
+    let oak_verity = OakVerity::new()?;
+
+    let test_input = b"Hello, in-toto!";
+    let request = ExecutePipelineRequest {
+        input_data: test_input.to_vec(),
+        steps: vec![PipelineStep { wasm_module, ..Default::default() }; 2],
+        manifest_format: ManifestFormat::InToto.into(),
+    };
+
+    let response = oak_verity.execute_pipeline(request)?;
+    assert_eq!(response.manifest_format(), ManifestFormat::InToto);
+
+    let statement = provenance::parse_statement(&response.serialized_manifest)?;
+    assert_eq!(statement._type, provenance::STATEMENT_TYPE);
+    assert_eq!(statement.predicate_type, provenance::PREDICATE_TYPE);
+
+    let output_digest = hex::encode(compute_sha256_digest(&response.output_data));
+    assert_eq!(statement.subject.len(), 1);
+    assert_eq!(statement.subject[0].digest.get("sha256"), Some(&output_digest));
+
+    let build_definition = &statement.predicate.build_definition;
+    assert_eq!(build_definition.external_parameters.steps.len(), 2);
+    assert_eq!(build_definition.external_parameters.steps[0].inputs, vec!["input_data"]);
+    assert_eq!(build_definition.external_parameters.steps[1].inputs, vec!["steps/0/output_data"]);
+    // Pipeline input and one Wasm module per step.
+    assert_eq!(build_definition.resolved_dependencies.len(), 3);
+
+    Ok(())
+}
+
+/// Test that streaming the echo Wasm module and the input data in chunks
+/// produces the same manifest as a unary execution.
+#[test]
+fn test_oak_verity_echo_stream() -> Result<()> {
+    let wasm_module_path = "oak_functions/examples/echo/echo.wasm";
+    let wasm_module =
+        std::fs::read(wasm_module_path).expect("Failed to read echo.wasm - make sure it's built");
+
+    let oak_verity = OakVerity::new()?;
+
+    let test_input: Vec<u8> = (0..10_000).map(|i| (i % 256) as u8).collect();
+
+    let mut stream = oak_verity.execute_stream();
+    for chunk in wasm_module.chunks(1024) {
+        stream.push(ExecuteStreamRequest {
+            request: Some(Request::WasmModuleChunk(chunk.to_vec())),
+        })?;
+    }
+    for chunk in test_input.chunks(1000) {
+        stream.push(ExecuteStreamRequest {
+            request: Some(Request::InputDataChunk(chunk.to_vec())),
+        })?;
+    }
+    let streamed_response = stream.finish()?;
+
+    assert_eq!(streamed_response.output_data, test_input);
+
+    let request = ExecuteRequest { input_data: test_input, wasm_module, ..Default::default() };
+    let response = oak_verity.execute(request)?;
+
+    assert_eq!(streamed_response.serialized_manifest, response.serialized_manifest);
+
+    Ok(())
+}
+
+/// Test referring to a previously sent Wasm module by its digest.
+#[test]
+fn test_oak_verity_echo_cached_module() -> Result<()> {
+    let wasm_module_path = "oak_functions/examples/echo/echo.wasm";
+    let wasm_module =
+        std::fs::read(wasm_module_path).expect("Failed to read echo.wasm - make sure it's built");
+    let wasm_module_digest =
+        RawDigest { sha2_256: compute_sha256_digest(&wasm_module), ..Default::default() };
+
+    let oak_verity = OakVerity::new()?;
+
+    let test_input = b"Hello, cached module!";
+
+    // The module has not been sent yet.
+    let request = ExecuteRequest {
+        input_data: test_input.to_vec(),
+        wasm_module_digest: Some(wasm_module_digest.clone()),
+        ..Default::default()
+    };
+    assert!(oak_verity.execute(request.clone()).is_err());
+
+    let first_response = oak_verity.execute(ExecuteRequest {
+        input_data: test_input.to_vec(),
+        wasm_module,
+        ..Default::default()
+    })?;
+
+    // The module is now cached.
+    let response = oak_verity.execute(request)?;
+    assert_eq!(response.output_data, test_input);
+    assert_eq!(response.serialized_manifest, first_response.serialized_manifest);
+
+    // The cached module can also be used in pipelines and streams.
+    let response = oak_verity.execute_pipeline(ExecutePipelineRequest {
+        input_data: test_input.to_vec(),
+        steps: vec![
+            PipelineStep {
+                wasm_module_digest: Some(wasm_module_digest.clone()),
+                ..Default::default()
+            };
+            2
+        ],
+        ..Default::default()
+    })?;
+    assert_eq!(response.output_data, test_input);
+
+    let mut stream = oak_verity.execute_stream();
+    stream.push(ExecuteStreamRequest {
+        request: Some(Request::WasmModuleDigest(wasm_module_digest)),
+    })?;
+    stream.push(ExecuteStreamRequest {
+        request: Some(Request::InputDataChunk(test_input.to_vec())),
+    })?;
+    let response = stream.finish()?;
+    assert_eq!(response.serialized_manifest, first_response.serialized_manifest);
+
+    Ok(())
+}
+
+/// Test that the input data cannot be streamed before the Wasm module.
+#[test]
+fn test_oak_verity_stream_requires_module_first() -> Result<()> {
+    let oak_verity = OakVerity::new()?;
+
+    let mut stream = oak_verity.execute_stream();
+    let result = stream
+        .push(ExecuteStreamRequest { request: Some(Request::InputDataChunk(b"input".to_vec())) });
+
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+/// Test that a Wasm module which doesn't match the given digest is rejected.
+#[test]
+fn test_oak_verity_rejects_mismatched_module_digest() -> Result<()> {
+    let wasm_module_path = "oak_functions/examples/echo/echo.wasm";
+    let wasm_module =
+        std::fs::read(wasm_module_path).expect("Failed to read echo.wasm - make sure it's built");
+    let other_digest =
+        RawDigest { sha2_256: compute_sha256_digest(b"other module"), ..Default::default() };
+
+    let oak_verity = OakVerity::new()?;
+
+    let result = oak_verity.execute(ExecuteRequest {
+        input_data: b"input".to_vec(),
+        wasm_module: wasm_module.clone(),
+        wasm_module_digest: Some(other_digest.clone()),
+    });
+    assert!(result.is_err());
+
+    let result = oak_verity.execute_pipeline(ExecutePipelineRequest {
+        input_data: b"input".to_vec(),
+        steps: vec![PipelineStep {
+            wasm_module,
+            wasm_module_digest: Some(other_digest),
+            ..Default::default()
+        }],
+        ..Default::default()
+    });
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+/// Test that streamed Wasm modules and input data larger than the configured
+/// limits are rejected.
+#[test]
+fn test_oak_verity_stream_size_limits() -> Result<()> {
+    let mut oak_verity = OakVerity::new()?;
+    oak_verity.stream_limits = StreamLimits { max_wasm_module_bytes: 8, max_input_data_bytes: 8 };
+
+    let mut stream = oak_verity.execute_stream();
+    stream.push(ExecuteStreamRequest { request: Some(Request::WasmModuleChunk(vec![0; 5])) })?;
+    let result =
+        stream.push(ExecuteStreamRequest { request: Some(Request::WasmModuleChunk(vec![0; 5])) });
+    assert!(result.is_err());
+
+    let mut stream = oak_verity.execute_stream();
+    stream.push(ExecuteStreamRequest { request: Some(Request::WasmModuleChunk(vec![0; 8])) })?;
+    stream.push(ExecuteStreamRequest { request: Some(Request::InputDataChunk(vec![0; 5])) })?;
+    let result =
+        stream.push(ExecuteStreamRequest { request: Some(Request::InputDataChunk(vec![0; 5])) });
+    assert!(result.is_err());
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Provides a content-addressable storage layer on a local directory tree.
+//!
+//! The layout is the same as on the HTTP-based storage, relative to a root
+//! directory instead of a URL prefix:
+//!
+//! - `<root>/<fbucket>/<content_hash>` holds the file with the given hash.
+//! - `<root>/<ibucket>/<link_type>/<content_hash>` holds the link of the given
+//!   type from the file with the given hash.
+//!
+//! Hence a copy of the buckets, e.g. obtained via `gcloud storage cp`, can be
+//! used directly, and [`FsStorage`] can be the destination of a mirror.
+
+extern crate std;
+
+use alloc::{format, string::String, vec::Vec};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+
+use crate::storage::{ContentAddressable, ContentAddressableWriter, NotFound};
+
+/// Implements the [`ContentAddressable`] trait on a local directory tree.
+pub struct FsStorage {
+    /// The root directory which contains the buckets.
+    pub root: PathBuf,
+
+    /// The name of the file bucket.
+    pub fbucket: String,
+
+    /// The name of the index bucket.
+    pub ibucket: String,
+}
+
+impl FsStorage {
+    fn file_path(&self, file_hash: &str) -> Result<PathBuf> {
+        check_path_component(file_hash)?;
+        Ok(self.root.join(&self.fbucket).join(file_hash))
+    }
+
+    fn link_path(&self, file_hash: &str, link_type: &str) -> Result<PathBuf> {
+        check_path_component(file_hash)?;
+        check_path_component(link_type)?;
+        Ok(self.root.join(&self.ibucket).join(link_type).join(file_hash))
+    }
+}
+
+impl ContentAddressable for FsStorage {
+    fn get_file(&self, file_hash: &str) -> Result<Vec<u8>> {
+        read(&self.file_path(file_hash)?)
+    }
+
+    fn get_linked_file(&self, file_hash: &str, link_type: &str) -> Result<Vec<u8>> {
+        let link_file_hash = self.get_link(file_hash, link_type)?;
+        self.get_file(&link_file_hash).with_context(|| {
+            format!("reading link file {link_type} ({link_file_hash}) for file {file_hash}")
+        })
+    }
+
+    fn get_link(&self, file_hash: &str, link_type: &str) -> Result<String> {
+        let contents = read(&self.link_path(file_hash, link_type)?)?;
+        String::from_utf8(contents)
+            .with_context(|| format!("parsing link {link_type} for file {file_hash}"))
+    }
+}
+
+impl ContentAddressableWriter for FsStorage {
+    fn put_file(&self, file_hash: &str, contents: &[u8]) -> Result<()> {
+        write(&self.file_path(file_hash)?, contents)
+    }
+
+    fn put_link(&self, file_hash: &str, link_type: &str, link: &str) -> Result<()> {
+        write(&self.link_path(file_hash, link_type)?, link.as_bytes())
+    }
+}
+
+// Hashes and link types end up in file paths, so they must not be able to
+// escape the buckets.
+fn check_path_component(component: &str) -> Result<()> {
+    if component.is_empty()
+        || !component.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == ':')
+    {
+        anyhow::bail!("invalid hash or link type: {component:?}");
+    }
+    Ok(())
+}
+
+// Reads the given file, and maps a missing file to `NotFound`.
+fn read(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path).map_err(|err| match err.kind() {
+        io::ErrorKind::NotFound => anyhow::Error::new(NotFound(format!("{}", path.display()))),
+        _ => anyhow::Error::new(err).context(format!("reading {}", path.display())),
+    })
+}
+
+fn write(path: &Path, contents: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("creating directory {}", parent.display()))?;
+    }
+    fs::write(path, contents).with_context(|| format!("writing {}", path.display()))
+}
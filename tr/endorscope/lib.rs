@@ -18,5 +18,8 @@
 
 extern crate alloc;
 
+pub mod fs_storage;
+pub mod mirror;
+pub mod oci_storage;
 pub mod package;
 pub mod storage;
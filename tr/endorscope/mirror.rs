@@ -0,0 +1,218 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Copies endorsements from one content-addressable storage layer to another,
+//! together with everything needed to load and verify them: signature, log
+//! entry, endorser key, subject if needed, and the links between these. The
+//! destination is usually a local directory, for offline use:
+//!
+//! let mirror = EndorsementMirror::new(Box::new(source), Box::new(fs_storage));
+//! let hashes = mirror.mirror_endorsements_by_key(key_hash)?;
+
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use anyhow::{Context, Result};
+use digest_util::hex_digest_from_contents;
+use intoto::statement::{get_hex_digest_from_statement, parse_statement};
+
+use crate::storage::{
+    is_not_found, ContentAddressable, ContentAddressableWriter, ENDORSEMENTS_FOR_KEY_LINK,
+    ENDORSEMENT_FOR_SUBJECT_LINK, INLINE_CLAIM_TYPE, KEYS_FOR_KEYSET_LINK,
+    LOG_ENTRY_FOR_ENDORSEMENT_LINK, MPM_CLAIM_TYPE, PUBLIC_KEY_FOR_SIGNATURE_LINK,
+    SIGNATURE_FOR_ENDORSEMENT_LINK,
+};
+
+/// Mirrors endorsements from a source to a destination storage layer.
+pub struct EndorsementMirror {
+    source: Box<dyn ContentAddressable>,
+    destination: Box<dyn ContentAddressableWriter>,
+}
+
+impl EndorsementMirror {
+    /// Creates a new endorsement mirror.
+    ///
+    /// Params:
+    /// - source: the content-addressable storage layer to copy from.
+    /// - destination: the content-addressable storage layer to copy to.
+    pub fn new(
+        source: Box<dyn ContentAddressable>,
+        destination: Box<dyn ContentAddressableWriter>,
+    ) -> Self {
+        EndorsementMirror { source, destination }
+    }
+
+    /// Copies a single endorsement along with its signature, log entry if
+    /// present, endorser key and subject if needed for the verification.
+    /// The endorsement is appended to the lists of endorsements by subject and
+    /// by endorser key in the destination.
+    ///
+    /// Params:
+    /// - endorsement_hash: The hash of the endorsement.
+    pub fn mirror_endorsement(&self, endorsement_hash: &str) -> Result<()> {
+        let endorsement = self
+            .copy_file(endorsement_hash)
+            .with_context(|| format!("copying endorsement {endorsement_hash}"))?;
+        let signature_hash = self
+            .copy_linked_file(endorsement_hash, SIGNATURE_FOR_ENDORSEMENT_LINK)
+            .with_context(|| format!("copying signature for endorsement {endorsement_hash}"))?;
+        self.copy_linked_file(endorsement_hash, LOG_ENTRY_FOR_ENDORSEMENT_LINK)
+            .map(|_| ())
+            .or_else(|err| {
+                // Endorsements need not be committed to a transparency log.
+                if is_not_found(&err) {
+                    Ok(())
+                } else {
+                    Err(err)
+                }
+            })
+            .with_context(|| format!("copying log entry for endorsement {endorsement_hash}"))?;
+        let endorser_key_hash =
+            self.copy_linked_file(&signature_hash, PUBLIC_KEY_FOR_SIGNATURE_LINK).with_context(
+                || format!("copying endorser public key for endorsement {endorsement_hash}"),
+            )?;
+
+        let statement = parse_statement(&endorsement).context("parsing endorsement statement")?;
+        let subject_hash =
+            format!("sha2-256:{}", get_hex_digest_from_statement(&statement)?.sha2_256);
+        if statement
+            .predicate
+            .claims
+            .iter()
+            .any(|c| c.r#type == INLINE_CLAIM_TYPE || c.r#type == MPM_CLAIM_TYPE)
+        {
+            self.copy_file(&subject_hash)
+                .with_context(|| format!("copying subject for endorsement {endorsement_hash}"))?;
+        }
+
+        self.append_link(&subject_hash, ENDORSEMENT_FOR_SUBJECT_LINK, &[endorsement_hash])?;
+        self.append_link(&endorser_key_hash, ENDORSEMENTS_FOR_KEY_LINK, &[endorsement_hash])
+    }
+
+    /// Copies all endorsements for the given subject hash.
+    ///
+    /// Returns:
+    /// - The list of copied endorsement hashes.
+    pub fn mirror_endorsements_by_subject(&self, subject_hash: &str) -> Result<Vec<String>> {
+        let endorsement_hashes = self
+            .read_link_list(subject_hash, ENDORSEMENT_FOR_SUBJECT_LINK)
+            .with_context(|| format!("reading endorsements by subject {subject_hash}"))?;
+        for endorsement_hash in &endorsement_hashes {
+            self.mirror_endorsement(endorsement_hash)?;
+        }
+        Ok(endorsement_hashes)
+    }
+
+    /// Copies all endorsements signed by the given endorser key hash.
+    ///
+    /// Returns:
+    /// - The list of copied endorsement hashes.
+    pub fn mirror_endorsements_by_key(&self, endorser_key_hash: &str) -> Result<Vec<String>> {
+        let endorsement_hashes = self
+            .read_link_list(endorser_key_hash, ENDORSEMENTS_FOR_KEY_LINK)
+            .with_context(|| format!("reading endorsements by key {endorser_key_hash}"))?;
+        for endorsement_hash in &endorsement_hashes {
+            self.mirror_endorsement(endorsement_hash)?;
+        }
+        Ok(endorsement_hashes)
+    }
+
+    /// Copies the list of keys in the given endorser keyset, as well as all
+    /// endorsements signed by any of these keys.
+    ///
+    /// Returns:
+    /// - The list of copied endorsement hashes.
+    pub fn mirror_endorsements_by_keyset(&self, endorser_keyset_hash: &str) -> Result<Vec<String>> {
+        let endorser_key_hashes =
+            self.read_link_list(endorser_keyset_hash, KEYS_FOR_KEYSET_LINK).with_context(|| {
+                format!("reading endorser keys for keyset {endorser_keyset_hash}")
+            })?;
+        let mut endorsement_hashes = Vec::new();
+        for endorser_key_hash in &endorser_key_hashes {
+            endorsement_hashes.extend(self.mirror_endorsements_by_key(endorser_key_hash)?);
+        }
+        // Keys without endorsements are still part of the keyset.
+        let endorser_key_hashes: Vec<&str> =
+            endorser_key_hashes.iter().map(|s| s.as_str()).collect();
+        self.append_link(endorser_keyset_hash, KEYS_FOR_KEYSET_LINK, &endorser_key_hashes)?;
+        Ok(endorsement_hashes)
+    }
+
+    // Copies a file after checking that it matches its hash.
+    fn copy_file(&self, file_hash: &str) -> Result<Vec<u8>> {
+        let contents = self.source.get_file(file_hash)?;
+        let digest = hex_digest_from_contents(&contents);
+        if format!("sha2-256:{}", digest.sha2_256) != file_hash {
+            anyhow::bail!("contents of file {file_hash} do not match its hash");
+        }
+        self.destination.put_file(file_hash, &contents)?;
+        Ok(contents)
+    }
+
+    // Copies a link which points to a single file, and the file itself.
+    // Returns the hash of the linked file.
+    fn copy_linked_file(&self, file_hash: &str, link_type: &str) -> Result<String> {
+        let linked_hash = self.source.get_link(file_hash, link_type)?;
+        self.copy_file(&linked_hash)?;
+        self.destination.put_link(file_hash, link_type, &linked_hash)?;
+        Ok(linked_hash)
+    }
+
+    // Reads a link which points to a list of files, treating a missing link as
+    // an empty list.
+    fn read_link_list(&self, file_hash: &str, link_type: &str) -> Result<Vec<String>> {
+        read_link_list(self.source.as_ref(), file_hash, link_type)
+    }
+
+    // Adds hashes to a link which points to a list of files in the
+    // destination, keeping existing entries and their order.
+    fn append_link(&self, file_hash: &str, link_type: &str, hashes: &[&str]) -> Result<()> {
+        let mut list = read_link_list(self.destination.as_ref(), file_hash, link_type)?;
+        let length = list.len();
+        for hash in hashes {
+            if !list.iter().any(|h| h == hash) {
+                list.push(hash.to_string());
+            }
+        }
+        if list.len() == length && length > 0 {
+            return Ok(());
+        }
+        let link: String = list.iter().map(|h| format!("{h}\n")).collect();
+        self.destination.put_link(file_hash, link_type, &link)
+    }
+}
+
+fn read_link_list<S: ContentAddressable + ?Sized>(
+    storage: &S,
+    file_hash: &str,
+    link_type: &str,
+) -> Result<Vec<String>> {
+    let link = storage.get_link(file_hash, link_type).or_else(|err| {
+        if is_not_found(&err) {
+            Ok(String::new())
+        } else {
+            Err(err)
+        }
+    })?;
+    Ok(link.split_terminator("\n").map(|s| s.to_string()).collect())
+}
+
+#[cfg(test)]
+mod tests;
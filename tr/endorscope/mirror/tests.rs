@@ -0,0 +1,179 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+extern crate std;
+
+use alloc::{boxed::Box, format, string::String, vec};
+use std::{fs, path::Path};
+
+use digest_util::hex_digest_from_contents;
+use oak_file_utils::data_path;
+use oak_time::{make_instant, Instant};
+use test_util::EndorsementData;
+
+use crate::{
+    fs_storage::FsStorage,
+    mirror::EndorsementMirror,
+    storage::{
+        ContentAddressable, ContentAddressableWriter, EndorsementLoader, ENDORSEMENTS_FOR_KEY_LINK,
+        LOG_ENTRY_FOR_ENDORSEMENT_LINK, PUBLIC_KEY_FOR_SIGNATURE_LINK,
+        SIGNATURE_FOR_ENDORSEMENT_LINK,
+    },
+};
+
+const ENDORSER_PUBLIC_KEY_PATH: &str =
+    "oak_attestation_verification/testdata/endorser_public_key.pem";
+
+/// The typed hashes of the files of a stored endorsement.
+struct Hashes {
+    endorsement: String,
+    signature: String,
+    log_entry: String,
+    endorser_key: String,
+}
+
+fn typed_hash(contents: &[u8]) -> String {
+    format!("sha2-256:{}", hex_digest_from_contents(contents).sha2_256)
+}
+
+fn create_storage(root: &Path) -> FsStorage {
+    FsStorage { root: root.to_path_buf(), fbucket: "files".into(), ibucket: "index".into() }
+}
+
+// Populates the storage with the endorsement from the test data.
+fn populate(storage: &FsStorage) -> Hashes {
+    let d = EndorsementData::load();
+    let endorser_key =
+        fs::read(data_path(ENDORSER_PUBLIC_KEY_PATH)).expect("couldn't read endorser key");
+    let hashes = Hashes {
+        endorsement: typed_hash(&d.endorsement),
+        signature: typed_hash(&d.signature),
+        log_entry: typed_hash(&d.log_entry),
+        endorser_key: typed_hash(&endorser_key),
+    };
+    for (hash, contents) in [
+        (&hashes.endorsement, &d.endorsement),
+        (&hashes.signature, &d.signature),
+        (&hashes.log_entry, &d.log_entry),
+        (&hashes.endorser_key, &endorser_key),
+    ] {
+        storage.put_file(hash, contents).expect("couldn't write file");
+    }
+    for (from, link_type, to) in [
+        (&hashes.endorsement, SIGNATURE_FOR_ENDORSEMENT_LINK, &hashes.signature),
+        (&hashes.endorsement, LOG_ENTRY_FOR_ENDORSEMENT_LINK, &hashes.log_entry),
+        (&hashes.signature, PUBLIC_KEY_FOR_SIGNATURE_LINK, &hashes.endorser_key),
+        (&hashes.endorser_key, ENDORSEMENTS_FOR_KEY_LINK, &format!("{}\n", hashes.endorsement)),
+    ] {
+        storage.put_link(from, link_type, to).expect("couldn't write link");
+    }
+    hashes
+}
+
+#[test]
+fn test_mirror_endorsements_by_key() {
+    let source_dir = tempfile::tempdir().expect("couldn't create directory");
+    let destination_dir = tempfile::tempdir().expect("couldn't create directory");
+    let hashes = populate(&create_storage(source_dir.path()));
+    let mirror = EndorsementMirror::new(
+        Box::new(create_storage(source_dir.path())),
+        Box::new(create_storage(destination_dir.path())),
+    );
+
+    let result = mirror.mirror_endorsements_by_key(&hashes.endorser_key);
+
+    assert_eq!(result.expect("mirroring failed"), vec![hashes.endorsement.clone()]);
+    let loader = EndorsementLoader::new(Box::new(create_storage(destination_dir.path())));
+    assert_eq!(
+        loader.list_endorsements_by_key(&hashes.endorser_key).expect("listing failed"),
+        vec![hashes.endorsement.clone()]
+    );
+    let package = loader
+        .load(&hashes.endorsement, Some(EndorsementData::load().rekor_public_key_pem))
+        .expect("loading failed");
+    let now = make_instant!("2024-03-01T01:00:00Z");
+    assert!(package.verify(now.into_unix_millis()).is_ok());
+}
+
+#[test]
+fn test_mirror_endorsement_twice_keeps_links() {
+    let source_dir = tempfile::tempdir().expect("couldn't create directory");
+    let destination_dir = tempfile::tempdir().expect("couldn't create directory");
+    let hashes = populate(&create_storage(source_dir.path()));
+    let mirror = EndorsementMirror::new(
+        Box::new(create_storage(source_dir.path())),
+        Box::new(create_storage(destination_dir.path())),
+    );
+
+    mirror.mirror_endorsement(&hashes.endorsement).expect("mirroring failed");
+    mirror.mirror_endorsement(&hashes.endorsement).expect("mirroring failed");
+
+    let destination = create_storage(destination_dir.path());
+    assert_eq!(
+        destination.get_link(&hashes.endorser_key, ENDORSEMENTS_FOR_KEY_LINK).unwrap(),
+        format!("{}\n", hashes.endorsement)
+    );
+}
+
+#[test]
+fn test_mirror_endorsement_without_log_entry() {
+    let source_dir = tempfile::tempdir().expect("couldn't create directory");
+    let destination_dir = tempfile::tempdir().expect("couldn't create directory");
+    let hashes = populate(&create_storage(source_dir.path()));
+    fs::remove_file(
+        source_dir
+            .path()
+            .join("index")
+            .join(LOG_ENTRY_FOR_ENDORSEMENT_LINK)
+            .join(&hashes.endorsement),
+    )
+    .expect("couldn't remove link");
+    let mirror = EndorsementMirror::new(
+        Box::new(create_storage(source_dir.path())),
+        Box::new(create_storage(destination_dir.path())),
+    );
+
+    let result = mirror.mirror_endorsement(&hashes.endorsement);
+
+    assert!(result.is_ok(), "{:?}", result);
+    let loader = EndorsementLoader::new(Box::new(create_storage(destination_dir.path())));
+    let package = loader.load(&hashes.endorsement, None).expect("loading failed");
+    assert!(package.log_entry.is_none());
+}
+
+#[test]
+fn test_mirror_tampered_file_fails() {
+    let source_dir = tempfile::tempdir().expect("couldn't create directory");
+    let destination_dir = tempfile::tempdir().expect("couldn't create directory");
+    let source = create_storage(source_dir.path());
+    let hashes = populate(&source);
+    source.put_file(&hashes.signature, b"tampered").expect("couldn't write file");
+    let mirror =
+        EndorsementMirror::new(Box::new(source), Box::new(create_storage(destination_dir.path())));
+
+    let result = mirror.mirror_endorsement(&hashes.endorsement);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_fs_storage_rejects_path_traversal() {
+    let dir = tempfile::tempdir().expect("couldn't create directory");
+    let storage = create_storage(dir.path());
+
+    assert!(storage.get_file("../index").is_err());
+    assert!(storage.put_link("sha2-256:00", "../13", "sha2-256:11").is_err());
+}
@@ -0,0 +1,385 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Provides a read-only content-addressable storage layer on an OCI registry,
+//! where endorsements of an image are stored as referrers of the image
+//! digest. See
+//! <https://github.com/opencontainers/distribution-spec/blob/main/spec.md#listing-referrers>
+//!
+//! Every referrer with artifact type [`ENDORSEMENT_ARTIFACT_TYPE`] is a
+//! manifest which describes a single endorsement. Its layers hold the
+//! endorsement, the signature, the endorser's public key and optionally the
+//! Rekor log entry, distinguished by their media types. Files are served from
+//! the blobs of the repository, while links are derived from the manifests,
+//! ordered by their creation annotation. Linking keys to key sets is not
+//! supported.
+
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::cell::OnceCell;
+
+use anyhow::{Context, Result};
+use digest_util::hex_digest_from_contents;
+use serde::Deserialize;
+use url::Url;
+
+use crate::storage::{
+    ContentAddressable, NotFound, ENDORSEMENTS_FOR_KEY_LINK, ENDORSEMENT_FOR_SUBJECT_LINK,
+    LOG_ENTRY_FOR_ENDORSEMENT_LINK, PUBLIC_KEY_FOR_SIGNATURE_LINK, SIGNATURE_FOR_ENDORSEMENT_LINK,
+};
+
+/// The artifact type of referrers which describe endorsements.
+pub const ENDORSEMENT_ARTIFACT_TYPE: &str = "application/vnd.oak.tr.endorsement.v1";
+
+/// The media type of the layer holding the endorsement statement.
+pub const ENDORSEMENT_MEDIA_TYPE: &str = "application/vnd.in-toto+json";
+
+/// The media type of the layer holding the raw signature over the endorsement.
+pub const SIGNATURE_MEDIA_TYPE: &str = "application/vnd.oak.tr.signature.v1";
+
+/// The media type of the layer holding the Rekor log entry.
+pub const LOG_ENTRY_MEDIA_TYPE: &str = "application/vnd.oak.tr.rekor-log-entry.v1+json";
+
+/// The media type of the layer holding the endorser's public key as PEM.
+pub const PUBLIC_KEY_MEDIA_TYPE: &str = "application/vnd.oak.tr.public-key.v1+pem";
+
+const INDEX_MEDIA_TYPE: &str = "application/vnd.oci.image.index.v1+json";
+const MANIFEST_MEDIA_TYPE: &str = "application/vnd.oci.image.manifest.v1+json";
+const CREATED_ANNOTATION: &str = "org.opencontainers.image.created";
+
+#[derive(Deserialize)]
+struct ImageIndex {
+    #[serde(default)]
+    manifests: Vec<Descriptor>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ImageManifest {
+    #[serde(default)]
+    artifact_type: Option<String>,
+    #[serde(default)]
+    layers: Vec<Descriptor>,
+    #[serde(default)]
+    annotations: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Descriptor {
+    #[serde(default)]
+    media_type: String,
+    digest: String,
+    #[serde(default)]
+    artifact_type: Option<String>,
+    #[serde(default)]
+    annotations: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    #[serde(default)]
+    token: Option<String>,
+    #[serde(default)]
+    access_token: Option<String>,
+}
+
+/// The typed hashes of the files belonging to a single endorsement.
+struct Referrer {
+    created: String,
+    endorsement: String,
+    signature: Option<String>,
+    log_entry: Option<String>,
+    public_key: Option<String>,
+}
+
+/// Implements the [`ContentAddressable`] trait on the referrers of an image in
+/// an OCI registry.
+pub struct OciStorage {
+    registry: Url,
+    repository: String,
+    subject_hash: String,
+    access_token: Option<String>,
+    anonymous_token: OnceCell<String>,
+    referrers: OnceCell<Vec<Referrer>>,
+}
+
+impl OciStorage {
+    /// Creates a storage layer for the endorsements of an image.
+    ///
+    /// Params:
+    /// - registry: the URL of the registry, e.g. `https://ghcr.io`.
+    /// - repository: the repository within the registry, e.g.
+    ///   `project-oak/oak`.
+    /// - subject_hash: the typed hash of the image manifest.
+    /// - access_token: a bearer token for the registry. If `None`, an anonymous
+    ///   token is requested when the registry asks for one.
+    pub fn new(
+        registry: Url,
+        repository: String,
+        subject_hash: String,
+        access_token: Option<String>,
+    ) -> Self {
+        OciStorage {
+            registry,
+            repository,
+            subject_hash,
+            access_token,
+            anonymous_token: OnceCell::new(),
+            referrers: OnceCell::new(),
+        }
+    }
+
+    fn url(&self, path: &str) -> Result<Url> {
+        Ok(self.registry.join(&format!("v2/{}/{}", self.repository, path))?)
+    }
+
+    // Fetches the content of the given URL, authenticating anonymously if the
+    // registry requires it and no access token was given.
+    #[allow(clippy::result_large_err)]
+    fn fetch(&self, url: &Url, accept: &str) -> Result<Vec<u8>> {
+        let call = |token: Option<&String>| {
+            let request = ureq::get(url.as_str()).set("Accept", accept);
+            match token {
+                Some(token) => request.set("Authorization", &format!("Bearer {token}")).call(),
+                None => request.call(),
+            }
+        };
+        let mut result = call(self.access_token.as_ref().or(self.anonymous_token.get()));
+        if let Err(ureq::Error::Status(401, response)) = &result {
+            if self.access_token.is_none() && self.anonymous_token.get().is_none() {
+                let challenge = response
+                    .header("WWW-Authenticate")
+                    .with_context(|| format!("registry requires authentication for URL {url}"))?;
+                let token = fetch_anonymous_token(challenge)?;
+                result = call(Some(self.anonymous_token.get_or_init(|| token)));
+            }
+        }
+        match result {
+            Ok(response) => {
+                let mut buffer = Vec::new();
+                response
+                    .into_reader()
+                    .read_to_end(&mut buffer)
+                    .with_context(|| format!("reading response bytes from URL {url}"))?;
+                Ok(buffer)
+            }
+            Err(ureq::Error::Status(404, _)) => Err(NotFound(url.to_string()).into()),
+            Err(err) => Err(anyhow::Error::new(err).context(format!("fetching URL {url}"))),
+        }
+    }
+
+    // Returns the endorsements which refer to the image, fetching them on
+    // first use.
+    fn referrers(&self) -> Result<&[Referrer]> {
+        if let Some(referrers) = self.referrers.get() {
+            return Ok(referrers);
+        }
+        let referrers = self.fetch_referrers().context("fetching referrers")?;
+        Ok(self.referrers.get_or_init(|| referrers))
+    }
+
+    fn fetch_referrers(&self) -> Result<Vec<Referrer>> {
+        let digest = to_oci_digest(&self.subject_hash)?;
+        let mut url = self.url(&format!("referrers/{digest}"))?;
+        url.query_pairs_mut().append_pair("artifactType", ENDORSEMENT_ARTIFACT_TYPE);
+        let index = self.fetch(&url, INDEX_MEDIA_TYPE).or_else(|err| {
+            // Registries without the referrers API maintain the referrers in
+            // an index which is tagged by the image digest instead.
+            if err.is::<NotFound>() {
+                let tag = digest.replace(':', "-");
+                self.fetch(&self.url(&format!("manifests/{tag}"))?, INDEX_MEDIA_TYPE)
+            } else {
+                Err(err)
+            }
+        });
+        let index: ImageIndex = match index {
+            Ok(index) => serde_json::from_slice(&index).context("parsing referrers index")?,
+            Err(err) if err.is::<NotFound>() => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+
+        let mut referrers = Vec::new();
+        for descriptor in index.manifests {
+            // Not all registries filter by artifact type.
+            if descriptor.artifact_type.as_deref().is_some_and(|t| t != ENDORSEMENT_ARTIFACT_TYPE) {
+                continue;
+            }
+            let url = self.url(&format!("manifests/{}", descriptor.digest))?;
+            let manifest: ImageManifest =
+                serde_json::from_slice(&self.fetch(&url, MANIFEST_MEDIA_TYPE)?)
+                    .with_context(|| format!("parsing manifest {}", descriptor.digest))?;
+            if manifest.artifact_type.as_deref() != Some(ENDORSEMENT_ARTIFACT_TYPE) {
+                continue;
+            }
+            let created = descriptor
+                .annotations
+                .get(CREATED_ANNOTATION)
+                .or(manifest.annotations.get(CREATED_ANNOTATION))
+                .cloned()
+                .unwrap_or_default();
+            referrers.push(
+                parse_referrer(created, &manifest).with_context(|| {
+                    format!("parsing endorsement manifest {}", descriptor.digest)
+                })?,
+            );
+        }
+
+        // Creation timestamps are RFC 3339, so they can be compared as strings.
+        referrers.sort_by(|a, b| a.created.cmp(&b.created));
+        Ok(referrers)
+    }
+}
+
+impl ContentAddressable for OciStorage {
+    fn get_file(&self, file_hash: &str) -> Result<Vec<u8>> {
+        let url = self.url(&format!("blobs/{}", to_oci_digest(file_hash)?))?;
+        let contents = self.fetch(&url, "*/*")?;
+        let digest = hex_digest_from_contents(&contents);
+        if from_oci_digest(&format!("sha256:{}", digest.sha2_256))? != file_hash {
+            anyhow::bail!("contents of blob {url} do not match its digest");
+        }
+        Ok(contents)
+    }
+
+    fn get_linked_file(&self, file_hash: &str, link_type: &str) -> Result<Vec<u8>> {
+        let link_file_hash = self.get_link(file_hash, link_type)?;
+        self.get_file(&link_file_hash).with_context(|| {
+            format!("reading link file {link_type} ({link_file_hash}) for file {file_hash}")
+        })
+    }
+
+    fn get_link(&self, file_hash: &str, link_type: &str) -> Result<String> {
+        let referrers = self.referrers()?;
+        let linked: Vec<&str> = match link_type {
+            ENDORSEMENT_FOR_SUBJECT_LINK if file_hash == self.subject_hash => {
+                referrers.iter().map(|r| r.endorsement.as_str()).collect()
+            }
+            ENDORSEMENTS_FOR_KEY_LINK => referrers
+                .iter()
+                .filter(|r| r.public_key.as_deref() == Some(file_hash))
+                .map(|r| r.endorsement.as_str())
+                .collect(),
+            SIGNATURE_FOR_ENDORSEMENT_LINK => referrers
+                .iter()
+                .filter(|r| r.endorsement == file_hash)
+                .filter_map(|r| r.signature.as_deref())
+                .take(1)
+                .collect(),
+            LOG_ENTRY_FOR_ENDORSEMENT_LINK => referrers
+                .iter()
+                .filter(|r| r.endorsement == file_hash)
+                .filter_map(|r| r.log_entry.as_deref())
+                .take(1)
+                .collect(),
+            PUBLIC_KEY_FOR_SIGNATURE_LINK => referrers
+                .iter()
+                .filter(|r| r.signature.as_deref() == Some(file_hash))
+                .filter_map(|r| r.public_key.as_deref())
+                .take(1)
+                .collect(),
+            _ => Vec::new(),
+        };
+        if linked.is_empty() {
+            return Err(NotFound(format!("link {link_type} for file {file_hash}")).into());
+        }
+        Ok(linked.join("\n"))
+    }
+}
+
+fn parse_referrer(created: String, manifest: &ImageManifest) -> Result<Referrer> {
+    let layer = |media_type: &str| {
+        manifest
+            .layers
+            .iter()
+            .find(|l| l.media_type == media_type)
+            .map(|l| from_oci_digest(&l.digest))
+            .transpose()
+    };
+    Ok(Referrer {
+        created,
+        endorsement: layer(ENDORSEMENT_MEDIA_TYPE)?.context("no endorsement layer")?,
+        signature: layer(SIGNATURE_MEDIA_TYPE)?,
+        log_entry: layer(LOG_ENTRY_MEDIA_TYPE)?,
+        public_key: layer(PUBLIC_KEY_MEDIA_TYPE)?,
+    })
+}
+
+// Converts a typed hash like `sha2-256:abc` to an OCI digest like `sha256:abc`.
+fn to_oci_digest(typed_hash: &str) -> Result<String> {
+    match typed_hash.split_once(':') {
+        Some(("sha2-256", hash)) => Ok(format!("sha256:{hash}")),
+        _ => anyhow::bail!("unsupported typed hash for OCI registries: {typed_hash}"),
+    }
+}
+
+// Converts an OCI digest like `sha256:abc` to a typed hash like `sha2-256:abc`.
+fn from_oci_digest(digest: &str) -> Result<String> {
+    match digest.split_once(':') {
+        Some(("sha256", hash)) => Ok(format!("sha2-256:{hash}")),
+        _ => anyhow::bail!("unsupported OCI digest: {digest}"),
+    }
+}
+
+// Requests an anonymous pull token as described by the bearer challenge of the
+// registry, e.g.
+// `Bearer realm="https://ghcr.io/token",service="ghcr.io",scope="repository:x:pull"`.
+// See <https://distribution.github.io/distribution/spec/auth/token/>
+fn fetch_anonymous_token(challenge: &str) -> Result<String> {
+    let params = challenge
+        .strip_prefix("Bearer ")
+        .with_context(|| format!("unsupported authentication challenge: {challenge}"))?;
+    let params = parse_challenge_params(params);
+    let realm = params.get("realm").context("no realm in authentication challenge")?;
+    let mut url = Url::parse(realm).context("parsing authentication realm")?;
+    for key in ["service", "scope"] {
+        if let Some(value) = params.get(key) {
+            url.query_pairs_mut().append_pair(key, value);
+        }
+    }
+
+    let response = ureq::get(url.as_str())
+        .call()
+        .with_context(|| format!("fetching anonymous token from {url}"))?
+        .into_string()
+        .context("reading anonymous token")?;
+    let response: TokenResponse =
+        serde_json::from_str(&response).context("parsing anonymous token")?;
+    response.token.or(response.access_token).context("no token in token response")
+}
+
+// Parses comma-separated `key="value"` pairs, where quoted values may contain
+// commas.
+fn parse_challenge_params(params: &str) -> BTreeMap<String, String> {
+    let mut result = BTreeMap::new();
+    let mut rest = params.trim();
+    while let Some((key, value)) = rest.split_once('=') {
+        let (value, remainder) = match value.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+            None => value.split_once(',').unwrap_or((value, "")),
+        };
+        result.insert(key.trim().to_string(), value.to_string());
+        rest = remainder.trim_start_matches(',').trim();
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,250 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+extern crate std;
+
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec,
+    vec::Vec,
+};
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    sync::Mutex,
+    thread,
+};
+
+use digest_util::hex_digest_from_contents;
+use test_util::EndorsementData;
+use url::Url;
+
+use super::{
+    from_oci_digest, parse_challenge_params, to_oci_digest, OciStorage, ENDORSEMENT_ARTIFACT_TYPE,
+    ENDORSEMENT_MEDIA_TYPE, LOG_ENTRY_MEDIA_TYPE, SIGNATURE_MEDIA_TYPE,
+};
+use crate::storage::{
+    ContentAddressable, NotFound, ENDORSEMENT_FOR_SUBJECT_LINK, LOG_ENTRY_FOR_ENDORSEMENT_LINK,
+    SIGNATURE_FOR_ENDORSEMENT_LINK,
+};
+
+const REPOSITORY: &str = "project-oak/oak";
+const SUBJECT_DIGEST: &str =
+    "sha256:0000000000000000000000000000000000000000000000000000000000000001";
+
+fn oci_digest(contents: &[u8]) -> String {
+    format!("sha256:{}", hex_digest_from_contents(contents).sha2_256)
+}
+
+/// A registry which serves fixed contents by path, and records the paths of
+/// the requests it receives.
+struct FakeRegistry {
+    url: Url,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl FakeRegistry {
+    fn start(contents: BTreeMap<String, Vec<u8>>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("couldn't bind listener");
+        let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.expect("couldn't accept connection");
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                // Skip the headers.
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                let target = request_line.split(' ').nth(1).unwrap_or_default().to_string();
+                let path = target.split('?').next().unwrap_or_default().to_string();
+                recorded.lock().unwrap().push(target);
+                let (status, body) = match contents.get(&path) {
+                    Some(body) => ("200 OK", body.as_slice()),
+                    None => ("404 Not Found", &[][..]),
+                };
+                let header = format!(
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                stream.write_all(header.as_bytes()).unwrap();
+                stream.write_all(body).unwrap();
+            }
+        });
+        FakeRegistry { url, requests }
+    }
+
+    fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+// Returns the registry contents for the endorsement from the test data,
+// with the referrers index served at `index_path`.
+fn registry_contents(d: &EndorsementData, index_path: &str) -> BTreeMap<String, Vec<u8>> {
+    let manifest = serde_json::to_vec(&serde_json::json!({
+        "schemaVersion": 2,
+        "artifactType": ENDORSEMENT_ARTIFACT_TYPE,
+        "layers": [
+            { "mediaType": ENDORSEMENT_MEDIA_TYPE, "digest": oci_digest(&d.endorsement) },
+            { "mediaType": SIGNATURE_MEDIA_TYPE, "digest": oci_digest(&d.signature) },
+            { "mediaType": LOG_ENTRY_MEDIA_TYPE, "digest": oci_digest(&d.log_entry) },
+        ],
+    }))
+    .unwrap();
+    let index = serde_json::to_vec(&serde_json::json!({
+        "schemaVersion": 2,
+        "manifests": [
+            { "digest": oci_digest(&manifest), "artifactType": ENDORSEMENT_ARTIFACT_TYPE },
+        ],
+    }))
+    .unwrap();
+
+    let path = |suffix: String| format!("/v2/{REPOSITORY}/{suffix}");
+    let mut contents = BTreeMap::new();
+    contents.insert(path(format!("manifests/{}", oci_digest(&manifest))), manifest);
+    contents.insert(path(index_path.to_string()), index);
+    for blob in [&d.endorsement, &d.signature, &d.log_entry] {
+        contents.insert(path(format!("blobs/{}", oci_digest(blob))), blob.clone());
+    }
+    contents
+}
+
+fn create_storage(registry: &FakeRegistry) -> OciStorage {
+    OciStorage::new(
+        registry.url.clone(),
+        REPOSITORY.to_string(),
+        from_oci_digest(SUBJECT_DIGEST).unwrap(),
+        Some("token".to_string()),
+    )
+}
+
+#[test]
+fn test_oci_digest_round_trip() {
+    let typed_hash = from_oci_digest(SUBJECT_DIGEST).expect("couldn't convert digest");
+    assert_eq!(
+        typed_hash,
+        "sha2-256:0000000000000000000000000000000000000000000000000000000000000001"
+    );
+    assert_eq!(to_oci_digest(&typed_hash).expect("couldn't convert typed hash"), SUBJECT_DIGEST);
+}
+
+#[test]
+fn test_oci_digest_rejects_unsupported_algorithms() {
+    assert!(to_oci_digest("sha2-512:abc").is_err());
+    assert!(to_oci_digest("abc").is_err());
+    assert!(from_oci_digest("sha512:abc").is_err());
+    assert!(from_oci_digest("abc").is_err());
+}
+
+#[test]
+fn test_parse_challenge_params() {
+    let params = parse_challenge_params(
+        r#"realm="https://ghcr.io/token",service="ghcr.io",scope="repository:a/b:pull,push""#,
+    );
+    assert_eq!(
+        params,
+        BTreeMap::from([
+            ("realm".to_string(), "https://ghcr.io/token".to_string()),
+            ("service".to_string(), "ghcr.io".to_string()),
+            ("scope".to_string(), "repository:a/b:pull,push".to_string()),
+        ])
+    );
+}
+
+#[test]
+fn test_parse_challenge_params_with_unquoted_values_and_spaces() {
+    let params = parse_challenge_params(r#" realm="https://r.example/token", service=r.example "#);
+    assert_eq!(
+        params,
+        BTreeMap::from([
+            ("realm".to_string(), "https://r.example/token".to_string()),
+            ("service".to_string(), "r.example".to_string()),
+        ])
+    );
+    assert!(parse_challenge_params("").is_empty());
+}
+
+#[test]
+fn test_get_endorsement_via_referrers_api() {
+    let d = EndorsementData::load();
+    let registry =
+        FakeRegistry::start(registry_contents(&d, &format!("referrers/{SUBJECT_DIGEST}")));
+    let storage = create_storage(&registry);
+
+    let endorsement_hash = storage
+        .get_link(&from_oci_digest(SUBJECT_DIGEST).unwrap(), ENDORSEMENT_FOR_SUBJECT_LINK)
+        .expect("couldn't get endorsement link");
+    assert_eq!(endorsement_hash, from_oci_digest(&oci_digest(&d.endorsement)).unwrap());
+    assert_eq!(storage.get_file(&endorsement_hash).unwrap(), d.endorsement);
+    assert_eq!(
+        storage.get_linked_file(&endorsement_hash, SIGNATURE_FOR_ENDORSEMENT_LINK).unwrap(),
+        d.signature
+    );
+    assert_eq!(
+        storage.get_linked_file(&endorsement_hash, LOG_ENTRY_FOR_ENDORSEMENT_LINK).unwrap(),
+        d.log_entry
+    );
+    assert!(!registry.requests().iter().any(|r| r.contains("manifests/sha256-")));
+}
+
+#[test]
+fn test_get_endorsement_falls_back_to_referrers_tag() {
+    let d = EndorsementData::load();
+    let tag = SUBJECT_DIGEST.replace(':', "-");
+    let registry = FakeRegistry::start(registry_contents(&d, &format!("manifests/{tag}")));
+    let storage = create_storage(&registry);
+
+    let endorsement_hash = storage
+        .get_link(&from_oci_digest(SUBJECT_DIGEST).unwrap(), ENDORSEMENT_FOR_SUBJECT_LINK)
+        .expect("couldn't get endorsement link");
+    assert_eq!(storage.get_file(&endorsement_hash).unwrap(), d.endorsement);
+
+    let requests = registry.requests();
+    assert!(requests[0].starts_with(&format!("/v2/{REPOSITORY}/referrers/{SUBJECT_DIGEST}?")));
+    assert_eq!(requests[1], format!("/v2/{REPOSITORY}/manifests/{tag}"));
+}
+
+#[test]
+fn test_get_link_without_referrers_is_not_found() {
+    let registry = FakeRegistry::start(BTreeMap::new());
+    let storage = create_storage(&registry);
+
+    let result =
+        storage.get_link(&from_oci_digest(SUBJECT_DIGEST).unwrap(), ENDORSEMENT_FOR_SUBJECT_LINK);
+    assert!(result.is_err_and(|err| err.is::<NotFound>()));
+    assert_eq!(registry.requests().len(), 2);
+}
+
+#[test]
+fn test_get_file_rejects_blob_with_other_digest() {
+    let d = EndorsementData::load();
+    let mut contents = registry_contents(&d, &format!("referrers/{SUBJECT_DIGEST}"));
+    let blob_path = format!("/v2/{REPOSITORY}/blobs/{}", oci_digest(&d.endorsement));
+    contents.insert(blob_path, vec![1, 2, 3]);
+    let registry = FakeRegistry::start(contents);
+    let storage = create_storage(&registry);
+
+    let result = storage.get_file(&from_oci_digest(&oci_digest(&d.endorsement)).unwrap());
+    assert!(result.is_err_and(|err| err.to_string().contains("do not match its digest")));
+}
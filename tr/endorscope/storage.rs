@@ -22,6 +22,10 @@
 //!   [`ContentAddressable`].
 //! - [`CaStorage`]: A concrete implementation of [`ContentAddressable`] which
 //!   is based on the HTTP protocol.
+//! - [`FsStorage`](crate::fs_storage::FsStorage): Same as [`CaStorage`], but on
+//!   a local directory tree, e.g. for air-gapped verification.
+//! - [`OciStorage`](crate::oci_storage::OciStorage): Reads endorsements which
+//!   are stored as referrers of an image in an OCI registry.
 //!
 //! First create a loader:
 //!
@@ -41,6 +45,7 @@ use alloc::{
     string::{String, ToString},
     vec::Vec,
 };
+use core::fmt;
 
 use anyhow::{Context, Result};
 use intoto::statement::{get_hex_digest_from_statement, parse_statement};
@@ -48,19 +53,40 @@ use url::Url;
 
 use crate::package::Package;
 
-const INLINE_CLAIM_TYPE: &str =
+pub(crate) const INLINE_CLAIM_TYPE: &str =
     "https://github.com/project-oak/oak/blob/main/docs/tr/claim/77149.md";
-const MPM_CLAIM_TYPE: &str = "https://github.com/project-oak/oak/blob/main/docs/tr/claim/31543.md";
+pub(crate) const MPM_CLAIM_TYPE: &str =
+    "https://github.com/project-oak/oak/blob/main/docs/tr/claim/31543.md";
 
 // Link types establish certain relationships between items in the
 // content-addressable storage, similar to database indices. Details can be
 // found at go/oak-search-index-structure.
-const ENDORSEMENT_FOR_SUBJECT_LINK: &str = "13";
-const SIGNATURE_FOR_ENDORSEMENT_LINK: &str = "14";
-const LOG_ENTRY_FOR_ENDORSEMENT_LINK: &str = "15";
-const PUBLIC_KEY_FOR_SIGNATURE_LINK: &str = "16";
-const ENDORSEMENTS_FOR_KEY_LINK: &str = "21";
-const KEYS_FOR_KEYSET_LINK: &str = "22";
+pub(crate) const ENDORSEMENT_FOR_SUBJECT_LINK: &str = "13";
+pub(crate) const SIGNATURE_FOR_ENDORSEMENT_LINK: &str = "14";
+pub(crate) const LOG_ENTRY_FOR_ENDORSEMENT_LINK: &str = "15";
+pub(crate) const PUBLIC_KEY_FOR_SIGNATURE_LINK: &str = "16";
+pub(crate) const ENDORSEMENTS_FOR_KEY_LINK: &str = "21";
+pub(crate) const KEYS_FOR_KEYSET_LINK: &str = "22";
+
+/// Error returned by implementations of [`ContentAddressable`] when the
+/// requested file or link does not exist.
+#[derive(Debug)]
+pub struct NotFound(pub String);
+
+impl fmt::Display for NotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "not found: {}", self.0)
+    }
+}
+
+impl core::error::Error for NotFound {}
+
+/// Returns whether the error originates from a missing file or link, in any
+/// of the storage layers.
+pub fn is_not_found(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<NotFound>().is_some()
+        || matches!(err.downcast_ref::<ureq::Error>(), Some(ureq::Error::Status(404, _)))
+}
 
 /// ContentAddressable is a trait that defines the interface for a
 /// content-addressable storage layer.
@@ -107,6 +133,26 @@ pub trait ContentAddressable {
     fn get_linked_file(&self, content_hash: &str, link_type: &str) -> Result<Vec<u8>>;
 }
 
+/// A content-addressable storage layer which can also be written to, e.g. as
+/// the destination of a mirror.
+pub trait ContentAddressableWriter: ContentAddressable {
+    /// Stores a file in `fbucket` under the given hash.
+    ///
+    /// Params:
+    /// - content_hash: the hash of the file contents.
+    /// - contents: the contents of the file.
+    fn put_file(&self, content_hash: &str, contents: &[u8]) -> Result<()>;
+
+    /// Stores a link of the given link type, replacing an existing one.
+    ///
+    /// Params:
+    /// - content_hash: the hash of the file to link from.
+    /// - link_type: the link type.
+    /// - link: the contents of the link, i.e. one or more hashes separated by
+    ///   newlines.
+    fn put_link(&self, content_hash: &str, link_type: &str, link: &str) -> Result<()>;
+}
+
 /// Loads endorsement package from a content-addressable storage layer.
 ///
 /// The details of the storage layer are defined by the trait
@@ -133,12 +179,15 @@ impl EndorsementLoader {
             .storage
             .get_link(endorser_keyset_hash, KEYS_FOR_KEYSET_LINK)
             .with_context(|| format!("reading endorser keys for keyset {}", endorser_keyset_hash))
-            .or_else(|err| match err.downcast_ref::<ureq::Error>() {
+            .or_else(|err| {
                 // If the link file for the endorser keyset is not found in
                 // the index, we treat it as if the endorser keyset is empty.
                 // So we return an empty list.
-                Some(ureq::Error::Status(404, _)) => Ok(String::new()),
-                _ => Err(err),
+                if is_not_found(&err) {
+                    Ok(String::new())
+                } else {
+                    Err(err)
+                }
             })?;
         Ok(endorser_keys.split_terminator("\n").map(|s| s.to_string()).collect())
     }
@@ -155,12 +204,15 @@ impl EndorsementLoader {
             .storage
             .get_link(endorser_key_hash, ENDORSEMENTS_FOR_KEY_LINK)
             .with_context(|| format!("reading endorsements by key {}", endorser_key_hash))
-            .or_else(|err| match err.downcast_ref::<ureq::Error>() {
+            .or_else(|err| {
                 // If the link file for the endorsement list is not found
                 // in the index, we assume the endorser has not signed any
                 // endorsements. So we return an empty list.
-                Some(ureq::Error::Status(404, _)) => Ok(String::new()),
-                _ => Err(err),
+                if is_not_found(&err) {
+                    Ok(String::new())
+                } else {
+                    Err(err)
+                }
             })?;
 
         Ok(endorsements.split_terminator("\n").map(|s| s.to_string()).collect())
@@ -178,12 +230,15 @@ impl EndorsementLoader {
             .storage
             .get_link(subject_hash, ENDORSEMENT_FOR_SUBJECT_LINK)
             .with_context(|| format!("reading endorsements by subject {}", subject_hash))
-            .or_else(|err| match err.downcast_ref::<ureq::Error>() {
+            .or_else(|err| {
                 // If the link file for the endorsement list is not found in
                 // the index, we assume the subject has never been endorsed.
                 // So we return an empty list.
-                Some(ureq::Error::Status(404, _)) => Ok(String::new()),
-                _ => Err(err),
+                if is_not_found(&err) {
+                    Ok(String::new())
+                } else {
+                    Err(err)
+                }
             })?;
 
         Ok(endorsements.split_terminator("\n").map(|s| s.to_string()).collect())
@@ -221,12 +276,15 @@ impl EndorsementLoader {
                 format!("reading rekor log entry for endorsement {}", endorsement_hash)
             })
             .map(Some)
-            .or_else(|err| match err.downcast_ref::<ureq::Error>() {
+            .or_else(|err| {
                 // If the link file for the rekor log entry is not found in the
                 // index, we assume the endorsement is not committed to a
                 // transparency log.
-                Some(ureq::Error::Status(404, _)) => Ok(None),
-                _ => Err(err),
+                if is_not_found(&err) {
+                    Ok(None)
+                } else {
+                    Err(err)
+                }
             })?;
 
         let statement = parse_statement(&endorsement).context("parsing endorsement statement")?;
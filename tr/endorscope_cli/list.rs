@@ -19,19 +19,18 @@
 
 use anyhow::Context;
 use clap::Args;
-use endorscope::{
-    package::Package,
-    storage::{CaStorage, EndorsementLoader},
-};
+use endorscope::{package::Package, storage::EndorsementLoader};
 use intoto::statement::Validity;
 use oak_proto_rust::oak::attestation::v1::MpmAttachment;
 use oak_time::Instant;
 use prost::Message;
 use rekor::get_rekor_v1_public_key_pem;
-use url::Url;
 use verify_endorsement::verify_endorsement;
 
-use crate::verify::{parse_bucket_name, parse_typed_hash, parse_url, string_to_option_string};
+use crate::{
+    storage::StorageArgs,
+    verify::{parse_typed_hash, string_to_option_string},
+};
 
 const MPM_CLAIM_TYPE: &str = "https://github.com/project-oak/oak/blob/main/docs/tr/claim/31543.md";
 const PUBLISHED_CLAIM_TYPE: &str =
@@ -60,7 +59,8 @@ fn prettify_claim(claim: &str) -> String {
 //
 // The fbucket_name and ibucket_name are used to determine the storage location
 // of the content addressable files and the link index file. The url_prefix can
-// be used to override the default storage location (Google Cloud Storage).
+// be used to override the default storage location (Google Cloud Storage). See
+// StorageArgs for the other storage backends.
 //
 // Example:
 //   list --endorser-key-hash=sha2-256:12345 --fbucket=12345 --ibucket=67890
@@ -94,27 +94,8 @@ pub(crate) struct ListArgs {
     )]
     rekor_public_key: String,
 
-    #[arg(
-        long,
-        help = "URL prefix of the content addressable storage.",
-        default_value = "https://storage.googleapis.com",
-        value_parser = parse_url,
-    )]
-    url_prefix: Url,
-
-    #[arg(
-        long,
-        help = "Name of the file bucket associated with the index bucket.",
-        value_parser = parse_bucket_name,
-    )]
-    fbucket: String,
-
-    #[arg(
-        long,
-        help = "Name of the index GCS bucket.",
-        value_parser = parse_bucket_name,
-    )]
-    ibucket: String,
+    #[command(flatten)]
+    storage: StorageArgs,
 
     #[arg(long, help = "List at most that many items. No limit if zero.", default_value = "0")]
     limit: usize,
@@ -122,8 +103,7 @@ pub(crate) struct ListArgs {
 
 /// Lists endorsements depending on arguments.
 pub(crate) fn list(current_time: Instant, p: ListArgs) {
-    let storage = CaStorage { url_prefix: p.url_prefix, fbucket: p.fbucket, ibucket: p.ibucket };
-    let loader = EndorsementLoader::new(Box::new(storage));
+    let loader = EndorsementLoader::new(p.storage.create());
 
     if p.subject_hash.is_some() {
         list_endorsements_by_subject(
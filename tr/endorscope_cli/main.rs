@@ -14,13 +14,15 @@
 // limitations under the License.
 //
 
-//! Endorscope tool: lists and verifies endorsements on static storage.
+//! Endorscope tool: lists, verifies and mirrors endorsements on static storage.
 
 use clap::{Parser, Subcommand};
 use oak_time::Instant;
 use oak_time_std::instant::now;
 
 mod list;
+mod mirror;
+mod storage;
 mod verify;
 
 #[derive(Parser)]
@@ -42,6 +44,9 @@ enum Commands {
 
     #[command(subcommand = "list", about = "List endorsements.")]
     List(list::ListArgs),
+
+    #[command(subcommand = "mirror", about = "Copy endorsements to a local directory.")]
+    Mirror(mirror::MirrorArgs),
 }
 
 fn main() {
@@ -54,5 +59,6 @@ fn main() {
             verify::VerifyCommands::Remote(args) => verify::verify_remote(current_time, args),
         },
         Commands::List(args) => list::list(current_time, args),
+        Commands::Mirror(args) => mirror::mirror(args),
     }
 }
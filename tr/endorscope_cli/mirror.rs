@@ -0,0 +1,136 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! This module implements the `mirror` command. It copies endorsements with
+//! everything needed to verify them into a local directory, which can then be
+//! used offline via `--storage-dir`.
+
+use std::path::PathBuf;
+
+use clap::Args;
+use endorscope::{fs_storage::FsStorage, mirror::EndorsementMirror};
+
+use crate::{
+    storage::StorageArgs,
+    verify::{parse_bucket_name, parse_typed_hash},
+};
+
+const DEFAULT_FBUCKET: &str = "files";
+const DEFAULT_IBUCKET: &str = "index";
+
+// Subcommand for copying endorsements to a local directory.
+//
+// Endorsements are selected in the same way as for listing. The destination
+// uses the bucket names of the source unless overridden, so that the same
+// arguments plus --storage-dir work on the copy.
+//
+// Example:
+//   mirror --endorser-key-hash=sha2-256:12345 --fbucket=12345 --ibucket=67890
+//          --destination-dir=/tmp/endorsements
+#[derive(Args)]
+pub(crate) struct MirrorArgs {
+    #[arg(
+        long,
+        help = "Typed hash of a single endorsement to copy.",
+        value_parser = parse_typed_hash,
+    )]
+    endorsement_hash: Option<String>,
+
+    #[arg(
+        long,
+        help = "Typed hash of the endorsed subject.",
+        value_parser = parse_typed_hash,
+    )]
+    subject_hash: Option<String>,
+
+    #[arg(
+        long,
+        help = "Typed hash of the verifying key used to sign endorsements.",
+        value_parser = parse_typed_hash,
+    )]
+    endorser_key_hash: Option<String>,
+
+    #[arg(
+        long,
+        help = "Typed hash of the endorser keyset used to sign endorsements.",
+        value_parser = parse_typed_hash,
+    )]
+    endorser_keyset_hash: Option<String>,
+
+    #[command(flatten)]
+    storage: StorageArgs,
+
+    #[arg(long, help = "Local directory to copy the endorsements to.")]
+    destination_dir: PathBuf,
+
+    #[arg(
+        long,
+        help = "Name of the file bucket in the destination. Defaults to --fbucket.",
+        value_parser = parse_bucket_name,
+    )]
+    destination_fbucket: Option<String>,
+
+    #[arg(
+        long,
+        help = "Name of the index bucket in the destination. Defaults to --ibucket.",
+        value_parser = parse_bucket_name,
+    )]
+    destination_ibucket: Option<String>,
+}
+
+/// Copies endorsements depending on arguments.
+pub(crate) fn mirror(p: MirrorArgs) {
+    let destination = FsStorage {
+        root: p.destination_dir,
+        fbucket: p
+            .destination_fbucket
+            .or(p.storage.fbucket.clone())
+            .unwrap_or(DEFAULT_FBUCKET.to_string()),
+        ibucket: p
+            .destination_ibucket
+            .or(p.storage.ibucket.clone())
+            .unwrap_or(DEFAULT_IBUCKET.to_string()),
+    };
+    println!(
+        "📂  Copying to {} (--fbucket={} --ibucket={})",
+        destination.root.display(),
+        destination.fbucket,
+        destination.ibucket
+    );
+    let mirror = EndorsementMirror::new(p.storage.create(), Box::new(destination));
+
+    let result = if let Some(endorsement_hash) = p.endorsement_hash {
+        mirror.mirror_endorsement(&endorsement_hash).map(|()| vec![endorsement_hash])
+    } else if let Some(subject_hash) = p.subject_hash {
+        mirror.mirror_endorsements_by_subject(&subject_hash)
+    } else if let Some(endorser_key_hash) = p.endorser_key_hash {
+        mirror.mirror_endorsements_by_key(&endorser_key_hash)
+    } else if let Some(endorser_keyset_hash) = p.endorser_keyset_hash {
+        mirror.mirror_endorsements_by_keyset(&endorser_keyset_hash)
+    } else {
+        panic!("One of --endorsement-hash, --subject-hash, --endorser-key-hash, or --endorser-keyset-hash must be specified.");
+    };
+
+    match result {
+        Ok(endorsement_hashes) => {
+            for endorsement_hash in &endorsement_hashes {
+                println!("✅  Copied endorsement {endorsement_hash}");
+            }
+            println!("🧲  Copied {} endorsements", endorsement_hashes.len());
+        }
+        Err(err) => panic!("❌  Mirroring failed: {:?}", err),
+    }
+}
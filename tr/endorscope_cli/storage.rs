@@ -0,0 +1,116 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! This module implements the arguments shared by all commands which read
+//! from a content addressable storage. The supported backends are:
+//! - remote: static storage behind a URL prefix, e.g. GCS (default)
+//! - directory: a local copy of the buckets, e.g. created by `mirror`
+//! - OCI: endorsements stored as referrers of an image in an OCI registry
+
+use std::path::PathBuf;
+
+use clap::Args;
+use endorscope::{
+    fs_storage::FsStorage,
+    oci_storage::OciStorage,
+    storage::{CaStorage, ContentAddressable},
+};
+use url::Url;
+
+use crate::verify::{parse_bucket_name, parse_typed_hash, parse_url};
+
+/// Selects the content addressable storage to read from.
+///
+/// The `fbucket` and `ibucket` names are used to determine the storage location
+/// of the content addressable files and the link index file, either below
+/// `url_prefix` or below `storage_dir`. They are not needed for OCI registries.
+#[derive(Args)]
+pub(crate) struct StorageArgs {
+    #[arg(
+        long,
+        help = "URL prefix of the content addressable storage.",
+        default_value = "https://storage.googleapis.com",
+        value_parser = parse_url,
+    )]
+    url_prefix: Url,
+
+    #[arg(
+        long,
+        help = "Local directory which holds the buckets. Takes precedence over --url-prefix.",
+        conflicts_with = "oci_registry"
+    )]
+    storage_dir: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Name of the file bucket associated with the index bucket.",
+        value_parser = parse_bucket_name,
+        required_unless_present = "oci_registry",
+    )]
+    pub(crate) fbucket: Option<String>,
+
+    #[arg(
+        long,
+        help = "Name of the index GCS bucket.",
+        value_parser = parse_bucket_name,
+        required_unless_present = "oci_registry",
+    )]
+    pub(crate) ibucket: Option<String>,
+
+    #[arg(
+        long,
+        help = "URL of an OCI registry which holds endorsements as referrers of an image, e.g. https://ghcr.io.",
+        value_parser = parse_url,
+        requires_all = ["oci_repository", "oci_subject_hash"],
+    )]
+    oci_registry: Option<Url>,
+
+    #[arg(long, help = "Repository within the OCI registry, e.g. project-oak/oak.")]
+    oci_repository: Option<String>,
+
+    #[arg(
+        long,
+        help = "Typed hash of the image manifest whose referrers are the endorsements.",
+        value_parser = parse_typed_hash,
+    )]
+    oci_subject_hash: Option<String>,
+
+    #[arg(
+        long,
+        help = "Bearer token for the OCI registry. An anonymous token is requested if unset."
+    )]
+    oci_access_token: Option<String>,
+}
+
+impl StorageArgs {
+    /// Creates the storage layer selected by the arguments.
+    pub(crate) fn create(self) -> Box<dyn ContentAddressable> {
+        if let Some(registry) = self.oci_registry {
+            return Box::new(OciStorage::new(
+                registry,
+                self.oci_repository.expect("--oci-repository must be specified"),
+                self.oci_subject_hash.expect("--oci-subject-hash must be specified"),
+                self.oci_access_token,
+            ));
+        }
+        let fbucket = self.fbucket.expect("--fbucket must be specified");
+        let ibucket = self.ibucket.expect("--ibucket must be specified");
+        match self.storage_dir {
+            Some(root) => Box::new(FsStorage { root, fbucket, ibucket }),
+            None => Box::new(CaStorage { url_prefix: self.url_prefix, fbucket, ibucket }),
+        }
+    }
+}
@@ -71,6 +71,21 @@ ${CLI} --now-utc-millis=1759860000000 verify remote \
 
 echo "===== 👍👍👍 verifying remotely (without log entry) works ==========="
 
+readonly MIRROR_DIR="$(mktemp -d)"
+trap 'rm -rf "${MIRROR_DIR}"' EXIT
+
+${CLI} mirror \
+  --fbucket=oak-files --ibucket=oak-index \
+  --endorsement-hash=sha2-256:eb8150f44a8e583a7f9e829d8855644b440c41d8ccc71df7a66002f6c0ca3a93 \
+  "--destination-dir=${MIRROR_DIR}"
+
+# Verification time: 7 October 2025, 18:00 UTC.
+${CLI} --now-utc-millis=1759860000000 verify remote \
+  --fbucket=oak-files --ibucket=oak-index "--storage-dir=${MIRROR_DIR}" \
+  --endorsement-hash=sha2-256:eb8150f44a8e583a7f9e829d8855644b440c41d8ccc71df7a66002f6c0ca3a93
+
+echo "===== 👍👍👍 verifying a mirrored endorsement works ================="
+
 # Verification time: 1 March 2024, 1:00 UTC.
 ${CLI} --now-utc-millis=1709254800000 verify file \
   "--endorsement=${TESTDATA_DIR}/endorsement.json" \
@@ -25,15 +25,14 @@ use std::{fs, path::PathBuf};
 
 use anyhow::{Context, Result};
 use clap::{Args, Subcommand};
-use endorscope::{
-    package::Package,
-    storage::{CaStorage, EndorsementLoader},
-};
+use endorscope::{package::Package, storage::EndorsementLoader};
 use intoto::statement::DefaultStatement;
 use oak_time::Instant;
 use rekor::get_rekor_v1_public_key_pem;
 use url::Url;
 
+use crate::storage::StorageArgs;
+
 /// Subcommands for the verify command.
 #[derive(Subcommand)]
 pub(crate) enum VerifyCommands {
@@ -80,7 +79,7 @@ pub(crate) struct VerifyFileArgs {
 /// The `fbucket` and `ibucket` names are used to determine the storage location
 /// of the content addressable files and the link index file. The `url_prefix`
 /// can be used to override the default storage location, wich is Google Cloud
-/// Storage (GCS).
+/// Storage (GCS). See [`StorageArgs`] for the other storage backends.
 ///
 /// Example:
 ///   verify remote --endorsement-hash=${hash} --fbucket=12345 --ibucket=67890
@@ -100,27 +99,8 @@ pub(crate) struct VerifyRemoteArgs {
     )]
     rekor_public_key: String,
 
-    #[arg(
-        long,
-        help = "URL prefix of the content addressable storage.",
-        default_value = "https://storage.googleapis.com",
-        value_parser = parse_url,
-    )]
-    url_prefix: Url,
-
-    #[arg(
-        long,
-        help = "Name of the file bucket associated with the index bucket.",
-        value_parser = parse_bucket_name,
-    )]
-    fbucket: String,
-
-    #[arg(
-        long,
-        help = "Name of the index GCS bucket.",
-        value_parser = parse_bucket_name,
-    )]
-    ibucket: String,
+    #[command(flatten)]
+    storage: StorageArgs,
 }
 
 // Verifies only the most basic things from
@@ -213,8 +193,7 @@ pub(crate) fn verify_file(current_time: Instant, p: VerifyFileArgs) {
 
 /// Verifies an endorsement package from a remote content addressable storage.
 pub(crate) fn verify_remote(current_time: Instant, p: VerifyRemoteArgs) {
-    let storage = CaStorage { url_prefix: p.url_prefix, fbucket: p.fbucket, ibucket: p.ibucket };
-    let loader = EndorsementLoader::new(Box::new(storage));
+    let loader = EndorsementLoader::new(p.storage.create());
     let package = loader
         .load(p.endorsement_hash.as_str(), string_to_option_string(p.rekor_public_key))
         .expect("Failed to load endorsement");
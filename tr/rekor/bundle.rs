@@ -0,0 +1,656 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Provides parsing and verification of Sigstore bundles (v0.1 to v0.3) in
+//! their JSON serialization. A bundle carries either a message signature over
+//! the artifact or a DSSE envelope wrapping it, along with the transparency
+//! log entry. Log entries of kind `hashedrekord` and `dsse` are supported both
+//! from Rekor v1 (version 0.0.1) and Rekor v2 (version 0.0.2). See
+//! <https://github.com/sigstore/protobuf-specs/blob/main/protos/sigstore_bundle.proto>
+
+use alloc::{format, string::String, vec, vec::Vec};
+
+use anyhow::{ensure, Context};
+use base64::{prelude::BASE64_STANDARD, Engine as _};
+use digest_util::hash_sha2_256;
+use key_util::{convert_pem_to_raw, equal_keys, verify_signature_ecdsa};
+use oak_proto_rust::oak::attestation::v1::{KeyType, VerifyingKeySet};
+use serde::{de::Error as _, Deserialize, Deserializer};
+use serde_json::Value;
+
+use crate::log_entry::{
+    self, verify_rekor_body, verify_rekor_inclusion_proof, verify_rekor_integration, LogEntry,
+    Verification,
+};
+
+/// The media types of the supported bundle versions.
+const MEDIA_TYPES: [&str; 4] = [
+    "application/vnd.dev.sigstore.bundle+json;version=0.1",
+    "application/vnd.dev.sigstore.bundle+json;version=0.2",
+    "application/vnd.dev.sigstore.bundle+json;version=0.3",
+    "application/vnd.dev.sigstore.bundle.v0.3+json",
+];
+
+/// The payload type of DSSE envelopes wrapping in-toto statements.
+pub const IN_TOTO_PAYLOAD_TYPE: &str = "application/vnd.in-toto+json";
+
+/// The log entry version written by Rekor v1.
+const REKOR_V1_ENTRY_VERSION: &str = "0.0.1";
+
+/// The log entry version written by Rekor v2.
+const REKOR_V2_ENTRY_VERSION: &str = "0.0.2";
+
+const HASHEDREKORD_KIND: &str = "hashedrekord";
+const DSSE_KIND: &str = "dsse";
+
+/// Represents a Sigstore bundle. Fields which are not needed for verification,
+/// such as the verification material identifying the signer, are dropped.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Bundle {
+    /// Identifies the version of the bundle format.
+    pub media_type: String,
+
+    pub verification_material: VerificationMaterial,
+
+    /// A signature directly over the artifact. Exactly one of
+    /// `message_signature` and `dsse_envelope` is set.
+    #[serde(default)]
+    pub message_signature: Option<MessageSignature>,
+
+    /// A DSSE envelope whose payload is the artifact.
+    #[serde(default)]
+    pub dsse_envelope: Option<DsseEnvelope>,
+}
+
+/// Represents the material needed to verify a bundle.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationMaterial {
+    #[serde(default)]
+    pub tlog_entries: Vec<TransparencyLogEntry>,
+
+    #[serde(default)]
+    pub timestamp_verification_data: Option<TimestampVerificationData>,
+}
+
+/// Represents a transparency log entry in a bundle.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransparencyLogEntry {
+    /// The index of the entry in the log.
+    #[serde(deserialize_with = "deserialize_int64")]
+    pub log_index: u64,
+
+    pub log_id: LogId,
+
+    pub kind_version: KindVersion,
+
+    /// The time the entry was integrated in the log, in seconds since the
+    /// Unix Epoch. Not provided by Rekor v2.
+    #[serde(default, deserialize_with = "deserialize_int64")]
+    pub integrated_time: u64,
+
+    /// Rekor's signature over the entry (the signed entry timestamp). Not
+    /// provided by Rekor v2.
+    #[serde(default)]
+    pub inclusion_promise: Option<InclusionPromise>,
+
+    #[serde(default)]
+    pub inclusion_proof: Option<InclusionProof>,
+
+    /// The base64-encoded body of the log entry.
+    pub canonicalized_body: String,
+}
+
+/// Identifies the log.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LogId {
+    /// Base64-encoded SHA2-256 digest of the log's public key.
+    pub key_id: String,
+}
+
+/// Identifies the type of a log entry body.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct KindVersion {
+    /// Example: "hashedrekord".
+    pub kind: String,
+
+    /// Example: "0.0.1".
+    pub version: String,
+}
+
+/// Represents the signed entry timestamp of a log entry.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InclusionPromise {
+    /// Base64-encoded signature over body, integratedTime, logID, logIndex.
+    pub signed_entry_timestamp: String,
+}
+
+/// Represents the inclusion proof of a log entry.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InclusionProof {
+    /// The index of the entry in the tree.
+    #[serde(deserialize_with = "deserialize_int64")]
+    pub log_index: u64,
+
+    /// The base64-encoded root hash of the tree.
+    pub root_hash: String,
+
+    #[serde(deserialize_with = "deserialize_int64")]
+    pub tree_size: u64,
+
+    /// Base64-encoded hashes of the proof, ordered from the leaf to the root.
+    pub hashes: Vec<String>,
+
+    pub checkpoint: Checkpoint,
+}
+
+/// Represents a checkpoint in a bundle.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Checkpoint {
+    /// The checkpoint in signed note format.
+    pub envelope: String,
+}
+
+/// Represents timestamps from timestamp authorities.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimestampVerificationData {
+    #[serde(default)]
+    pub rfc3161_timestamps: Vec<Rfc3161SignedTimestamp>,
+}
+
+/// Represents an RFC 3161 timestamp.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Rfc3161SignedTimestamp {
+    /// The base64-encoded DER timestamp response.
+    pub signed_timestamp: String,
+}
+
+/// Represents a signature directly over the artifact.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageSignature {
+    /// The digest of the artifact, if provided.
+    #[serde(default)]
+    pub message_digest: Option<HashOutput>,
+
+    /// The base64-encoded signature over the artifact.
+    pub signature: String,
+}
+
+/// Represents a hash obtained via a certain algorithm.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct HashOutput {
+    /// The algorithm used for this hash. Example: "SHA2_256".
+    pub algorithm: String,
+
+    /// The base64-encoded value of the hash.
+    pub digest: String,
+}
+
+/// Represents a DSSE envelope. See
+/// <https://github.com/secure-systems-lab/dsse/blob/master/envelope.md>
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DsseEnvelope {
+    /// The base64-encoded payload.
+    pub payload: String,
+
+    /// Example: "application/vnd.in-toto+json".
+    pub payload_type: String,
+
+    pub signatures: Vec<DsseSignature>,
+}
+
+/// Represents a signature in a DSSE envelope.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct DsseSignature {
+    /// The base64-encoded signature over the pre-authentication encoding.
+    pub sig: String,
+
+    #[serde(default)]
+    pub keyid: String,
+}
+
+/// Represents the body of a log entry of any kind.
+#[derive(Debug, Deserialize)]
+struct EntryBody<S> {
+    #[serde(rename = "apiVersion")]
+    api_version: String,
+    kind: String,
+    spec: S,
+}
+
+/// Represents the `spec` of a `dsse` log entry body from Rekor v1.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DsseSpecV001 {
+    payload_hash: log_entry::Hash,
+    signatures: Vec<DsseSignatureV001>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DsseSignatureV001 {
+    /// Base64-encoded signature.
+    signature: String,
+    /// Base64-encoded PEM public key.
+    verifier: String,
+}
+
+/// Represents the `spec` of a `hashedrekord` log entry body from Rekor v2.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HashedRekordSpecV002 {
+    hashed_rekord_v002: HashedRekordV002,
+}
+
+#[derive(Debug, Deserialize)]
+struct HashedRekordV002 {
+    data: HashOutput,
+    signature: SignatureV002,
+}
+
+/// Represents the `spec` of a `dsse` log entry body from Rekor v2.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DsseSpecV002 {
+    dsse_v002: DsseV002,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DsseV002 {
+    payload_hash: HashOutput,
+    signatures: Vec<SignatureV002>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SignatureV002 {
+    /// Base64-encoded signature.
+    content: String,
+    verifier: VerifierV002,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VerifierV002 {
+    /// Not set when the signature is verified by a certificate.
+    #[serde(default)]
+    public_key: Option<PublicKeyV002>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PublicKeyV002 {
+    /// Base64-encoded ASN.1 DER public key.
+    raw_bytes: String,
+}
+
+/// Parses a Sigstore bundle and checks that it is of a supported version and
+/// contains exactly one of message signature and DSSE envelope.
+pub fn parse_sigstore_bundle(serialized_bundle: &[u8]) -> anyhow::Result<Bundle> {
+    let bundle: Bundle = serde_json::from_slice(serialized_bundle)
+        .map_err(|error| anyhow::anyhow!("couldn't parse Sigstore bundle: {error}"))?;
+    ensure!(
+        MEDIA_TYPES.contains(&bundle.media_type.as_str()),
+        "unsupported Sigstore bundle media type: {}",
+        bundle.media_type
+    );
+    ensure!(
+        bundle.message_signature.is_some() != bundle.dsse_envelope.is_some(),
+        "bundle must contain exactly one of message signature and DSSE envelope"
+    );
+    Ok(bundle)
+}
+
+/// A bundle signature which was verified by [`verify_bundle_signature`].
+pub struct VerifiedSignature {
+    /// The decoded signature.
+    pub signature: Vec<u8>,
+    /// The raw public key which verified the signature.
+    pub public_key: Vec<u8>,
+}
+
+/// Verifies the signature in the bundle over the artifact with one of the keys
+/// in the set.
+///
+/// For a DSSE envelope, the payload must equal `artifact_bytes` and be an
+/// in-toto statement.
+///
+/// # Returns
+///
+/// The signature which was verified, and the raw public key which verified it.
+pub fn verify_bundle_signature(
+    bundle: &Bundle,
+    artifact_bytes: &[u8],
+    key_set: &VerifyingKeySet,
+) -> anyhow::Result<VerifiedSignature> {
+    if let Some(message_signature) = &bundle.message_signature {
+        if let Some(message_digest) = &message_signature.message_digest {
+            check_hash_output(message_digest, artifact_bytes).context("checking message digest")?;
+        }
+        let signature = decode_base64(&message_signature.signature, "message signature")?;
+        let public_key = find_verifying_key(key_set, &signature, artifact_bytes)?;
+        return Ok(VerifiedSignature { signature, public_key });
+    }
+
+    let envelope = bundle.dsse_envelope.as_ref().context("no signature in bundle")?;
+    ensure!(
+        envelope.payload_type == IN_TOTO_PAYLOAD_TYPE,
+        "unsupported DSSE payload type: {}",
+        envelope.payload_type
+    );
+    let payload = decode_base64(&envelope.payload, "DSSE payload")?;
+    ensure!(payload == artifact_bytes, "DSSE payload does not match the artifact");
+    let pae = pre_authentication_encoding(&envelope.payload_type, &payload);
+    for signature in &envelope.signatures {
+        let signature = decode_base64(&signature.sig, "DSSE signature")?;
+        if let Ok(public_key) = find_verifying_key(key_set, &signature, &pae) {
+            return Ok(VerifiedSignature { signature, public_key });
+        }
+    }
+    anyhow::bail!("could not verify DSSE envelope signature")
+}
+
+/// Returns the decoded signatures in the bundle, which are the data covered
+/// by its RFC 3161 timestamps.
+pub fn get_bundle_signatures(bundle: &Bundle) -> anyhow::Result<Vec<Vec<u8>>> {
+    if let Some(message_signature) = &bundle.message_signature {
+        return Ok(vec![decode_base64(&message_signature.signature, "message signature")?]);
+    }
+    let envelope = bundle.dsse_envelope.as_ref().context("no signature in bundle")?;
+    envelope.signatures.iter().map(|s| decode_base64(&s.sig, "DSSE signature")).collect()
+}
+
+/// Returns the decoded RFC 3161 timestamps in the bundle, as ASN.1 DER encoded
+/// timestamp responses.
+pub fn get_bundle_rfc3161_timestamps(bundle: &Bundle) -> anyhow::Result<Vec<Vec<u8>>> {
+    bundle
+        .verification_material
+        .timestamp_verification_data
+        .iter()
+        .flat_map(|data| &data.rfc3161_timestamps)
+        .map(|t| decode_base64(&t.signed_timestamp, "RFC 3161 timestamp"))
+        .collect()
+}
+
+/// Verifies the transparency log entry in the bundle.
+///
+/// Steps include:
+///
+/// 1. that the log entry body records the bundle signature, the artifact and
+///    `endorser_public_key`,
+/// 2. for Rekor v1 entries, the signed entry timestamp, and the time stamp and
+///    inclusion proof if requested by the key set,
+/// 3. for Rekor v2 entries, the inclusion proof. Since these entries carry no
+///    integration time, time stamp verification is not supported. An RFC 3161
+///    timestamp can be used instead.
+///
+/// # Arguments
+///
+/// * `bundle`: The parsed bundle.
+/// * `key_set`: Rekor's verifying keys, optionally with a time stamp reference
+///   value.
+/// * `artifact_bytes`: The artifact signed by the bundle.
+/// * `endorser_public_key`: The raw key which verified the bundle signature.
+/// * `now_utc_millis`: The current time in milliseconds since Unix Epoch.
+///
+/// # Returns
+///
+/// The log entry in the format of Rekor v1 API responses, for further checks
+/// such as checkpoint consistency.
+pub fn verify_bundle_log_entry(
+    bundle: &Bundle,
+    key_set: &VerifyingKeySet,
+    artifact_bytes: &[u8],
+    endorser_public_key: &[u8],
+    now_utc_millis: i64,
+) -> anyhow::Result<LogEntry> {
+    let entry = match bundle.verification_material.tlog_entries.as_slice() {
+        [entry] => entry,
+        [] => anyhow::bail!("no transparency log entry in bundle"),
+        _ => anyhow::bail!("multiple transparency log entries are not supported"),
+    };
+
+    let log_entry = convert_log_entry(entry)?;
+    verify_entry_body(bundle, entry, &log_entry, artifact_bytes, endorser_public_key)
+        .context("verifying log entry body")?;
+
+    match entry.kind_version.version.as_str() {
+        REKOR_V1_ENTRY_VERSION => {
+            ensure!(
+                entry.inclusion_promise.is_some(),
+                "Rekor v1 log entry has no inclusion promise"
+            );
+            verify_rekor_integration(&log_entry, key_set, now_utc_millis)?;
+        }
+        REKOR_V2_ENTRY_VERSION => {
+            ensure!(
+                key_set.signed_timestamp.is_none(),
+                "time stamp verification is not supported for Rekor v2 log entries, use a \
+                 timestamp authority instead"
+            );
+            verify_rekor_inclusion_proof(&log_entry, key_set)
+                .context("verifying Rekor inclusion proof")?;
+        }
+        version => anyhow::bail!("unsupported log entry version: {version}"),
+    }
+    Ok(log_entry)
+}
+
+/// Converts a log entry from a bundle into the format of Rekor v1 API
+/// responses.
+fn convert_log_entry(entry: &TransparencyLogEntry) -> anyhow::Result<LogEntry> {
+    let inclusion_proof = match &entry.inclusion_proof {
+        Some(proof) => Some(log_entry::InclusionProof {
+            checkpoint: proof.checkpoint.envelope.clone(),
+            hashes: proof
+                .hashes
+                .iter()
+                .map(|h| decode_base64(h, "proof hash").map(hex::encode))
+                .collect::<anyhow::Result<Vec<String>>>()?,
+            log_index: proof.log_index,
+            root_hash: hex::encode(decode_base64(&proof.root_hash, "root hash")?),
+            tree_size: proof.tree_size,
+        }),
+        None => None,
+    };
+    Ok(LogEntry {
+        body: entry.canonicalized_body.clone(),
+        integrated_time: entry.integrated_time.try_into()?,
+        log_id: hex::encode(decode_base64(&entry.log_id.key_id, "log ID")?),
+        log_index: entry.log_index,
+        verification: Some(Verification {
+            inclusion_proof,
+            signed_entry_timestamp: entry
+                .inclusion_promise
+                .as_ref()
+                .map(|p| p.signed_entry_timestamp.clone())
+                .unwrap_or_default(),
+        }),
+    })
+}
+
+/// Verifies that the log entry body matches the bundle.
+fn verify_entry_body(
+    bundle: &Bundle,
+    entry: &TransparencyLogEntry,
+    log_entry: &LogEntry,
+    artifact_bytes: &[u8],
+    endorser_public_key: &[u8],
+) -> anyhow::Result<()> {
+    let kind = entry.kind_version.kind.as_str();
+    let version = entry.kind_version.version.as_str();
+    let body = decode_base64(&log_entry.body, "log entry body")?;
+    let header: EntryBody<Value> = parse_body(&body)?;
+    ensure!(
+        header.kind == kind && header.api_version == version,
+        "log entry body does not match kind {kind} and version {version}"
+    );
+
+    if let Some(message_signature) = &bundle.message_signature {
+        ensure!(kind == HASHEDREKORD_KIND, "unexpected log entry kind for a signature: {kind}");
+        let signature = decode_base64(&message_signature.signature, "message signature")?;
+        let recorded = match version {
+            REKOR_V1_ENTRY_VERSION => {
+                let body = log_entry.body()?;
+                verify_rekor_body(&body, artifact_bytes)?;
+                let recorded = decode_base64(&body.spec.signature.content, "signature")?;
+                [(recorded, log_entry.get_public_key()?)].to_vec()
+            }
+            REKOR_V2_ENTRY_VERSION => {
+                let body: EntryBody<HashedRekordSpecV002> = parse_body(&body)?;
+                let spec = body.spec.hashed_rekord_v002;
+                check_hash_output(&spec.data, artifact_bytes).context("checking artifact hash")?;
+                [decode_signature_v002(&spec.signature)?].to_vec()
+            }
+            _ => anyhow::bail!("unsupported log entry version: {version}"),
+        };
+        return check_recorded_signatures(&recorded, &[signature], endorser_public_key);
+    }
+
+    let envelope = bundle.dsse_envelope.as_ref().context("no signature in bundle")?;
+    ensure!(kind == DSSE_KIND, "unexpected log entry kind for a DSSE envelope: {kind}");
+    let payload = decode_base64(&envelope.payload, "DSSE payload")?;
+    let recorded = match version {
+        REKOR_V1_ENTRY_VERSION => {
+            let body: EntryBody<DsseSpecV001> = parse_body(&body)?;
+            let payload_hash = &body.spec.payload_hash;
+            ensure!(
+                payload_hash.algorithm == "sha256",
+                "unsupported hashing algorithm: {}",
+                payload_hash.algorithm
+            );
+            ensure!(
+                payload_hash.value == hex::encode(hash_sha2_256(&payload)),
+                "hash of DSSE payload does not match hash in log entry"
+            );
+            body.spec
+                .signatures
+                .iter()
+                .map(|s| {
+                    let signature = decode_base64(&s.signature, "signature")?;
+                    let pem = decode_base64(&s.verifier, "verifier")?;
+                    let pem = core::str::from_utf8(&pem)
+                        .map_err(|_| anyhow::anyhow!("failed to convert verifier to string"))?;
+                    Ok((signature, convert_pem_to_raw(pem)?))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?
+        }
+        REKOR_V2_ENTRY_VERSION => {
+            let body: EntryBody<DsseSpecV002> = parse_body(&body)?;
+            let spec = body.spec.dsse_v002;
+            check_hash_output(&spec.payload_hash, &payload).context("checking payload hash")?;
+            spec.signatures.iter().map(decode_signature_v002).collect::<anyhow::Result<Vec<_>>>()?
+        }
+        _ => anyhow::bail!("unsupported log entry version: {version}"),
+    };
+    let signatures = envelope
+        .signatures
+        .iter()
+        .map(|s| decode_base64(&s.sig, "DSSE signature"))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    check_recorded_signatures(&recorded, &signatures, endorser_public_key)
+}
+
+/// Checks that the log entry records one of the bundle signatures together
+/// with the endorser key.
+fn check_recorded_signatures(
+    recorded: &[(Vec<u8>, Vec<u8>)],
+    signatures: &[Vec<u8>],
+    endorser_public_key: &[u8],
+) -> anyhow::Result<()> {
+    let found = recorded.iter().any(|(signature, public_key)| {
+        signatures.contains(signature)
+            && equal_keys(public_key, endorser_public_key).unwrap_or(false)
+    });
+    ensure!(found, "log entry does not record the bundle signature by the endorser key");
+    Ok(())
+}
+
+/// Returns the signature and the raw public key of a Rekor v2 signature.
+fn decode_signature_v002(signature: &SignatureV002) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+    let public_key = signature
+        .verifier
+        .public_key
+        .as_ref()
+        .context("certificate verifiers are not supported")?;
+    Ok((
+        decode_base64(&signature.content, "signature")?,
+        decode_base64(&public_key.raw_bytes, "public key")?,
+    ))
+}
+
+/// Checks that the hash output is the SHA2-256 digest of `data`.
+fn check_hash_output(hash: &HashOutput, data: &[u8]) -> anyhow::Result<()> {
+    ensure!(hash.algorithm == "SHA2_256", "unsupported hashing algorithm: {}", hash.algorithm);
+    let digest = decode_base64(&hash.digest, "digest")?;
+    ensure!(digest == hash_sha2_256(data), "digest does not match");
+    Ok(())
+}
+
+/// Returns the raw key of the first ECDSA key in the set which verifies the
+/// signature.
+fn find_verifying_key(
+    key_set: &VerifyingKeySet,
+    signature: &[u8],
+    contents: &[u8],
+) -> anyhow::Result<Vec<u8>> {
+    key_set
+        .keys
+        .iter()
+        .filter(|k| k.r#type() == KeyType::EcdsaP256Sha256)
+        .find(|k| verify_signature_ecdsa(signature, contents, &k.raw).is_ok())
+        .map(|k| k.raw.clone())
+        .context("could not verify signature with any of the keys")
+}
+
+/// Computes the DSSE pre-authentication encoding which is covered by the
+/// envelope signatures.
+fn pre_authentication_encoding(payload_type: &str, payload: &[u8]) -> Vec<u8> {
+    let mut pae =
+        format!("DSSEv1 {} {} {} ", payload_type.len(), payload_type, payload.len()).into_bytes();
+    pae.extend_from_slice(payload);
+    pae
+}
+
+fn parse_body<'a, T: Deserialize<'a>>(body: &'a [u8]) -> anyhow::Result<T> {
+    serde_json::from_slice(body)
+        .map_err(|error| anyhow::anyhow!("couldn't parse log entry body: {error}"))
+}
+
+fn decode_base64(encoded: &str, what: &str) -> anyhow::Result<Vec<u8>> {
+    BASE64_STANDARD.decode(encoded).map_err(|_| anyhow::anyhow!("failed to base64-decode {what}"))
+}
+
+/// Deserializes a 64-bit integer, which the JSON mapping of protobuf encodes
+/// as a string.
+fn deserialize_int64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::String(s) => s.parse().map_err(D::Error::custom),
+        Value::Number(n) => n.as_u64().ok_or_else(|| D::Error::custom("invalid integer")),
+        _ => Err(D::Error::custom("expected an integer")),
+    }
+}
+
+#[cfg(test)]
+mod tests;
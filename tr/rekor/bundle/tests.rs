@@ -0,0 +1,215 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use oak_proto_rust::oak::attestation::v1::{TimestampReferenceValue, VerifyingKeySet};
+use serde_json::{json, Value};
+use test_util::{
+    sigstore_bundle_data::{BundleContent, RekorVersion},
+    SigstoreBundleData,
+};
+
+use crate::{
+    bundle::{parse_sigstore_bundle, verify_bundle_log_entry, verify_bundle_signature},
+    log_entry::LogEntry,
+};
+
+const ALL_BUNDLES: [(BundleContent, RekorVersion); 4] = [
+    (BundleContent::MessageSignature, RekorVersion::V1),
+    (BundleContent::MessageSignature, RekorVersion::V2),
+    (BundleContent::DsseEnvelope, RekorVersion::V1),
+    (BundleContent::DsseEnvelope, RekorVersion::V2),
+];
+
+fn endorser_key_set(d: &SigstoreBundleData) -> VerifyingKeySet {
+    d.ref_value().endorser.expect("no endorser key set")
+}
+
+fn verify_with(
+    d: &SigstoreBundleData,
+    bundle: &Value,
+    rekor_key_set: &VerifyingKeySet,
+) -> anyhow::Result<LogEntry> {
+    let serialized = serde_json::to_vec(bundle)?;
+    let bundle = parse_sigstore_bundle(&serialized)?;
+    let verified_signature =
+        verify_bundle_signature(&bundle, &d.endorsement, &endorser_key_set(d))?;
+    verify_bundle_log_entry(
+        &bundle,
+        rekor_key_set,
+        &d.endorsement,
+        &verified_signature.public_key,
+        d.integrated_time.into_unix_millis(),
+    )
+}
+
+fn verify(d: &SigstoreBundleData, bundle: &Value) -> anyhow::Result<LogEntry> {
+    verify_with(d, bundle, &d.rekor_key_set())
+}
+
+fn tlog_entry(bundle: &mut Value) -> &mut Value {
+    &mut bundle["verificationMaterial"]["tlogEntries"][0]
+}
+
+#[test]
+fn test_verify_bundle_success() {
+    let d = SigstoreBundleData::load();
+
+    for (content, version) in ALL_BUNDLES {
+        let result = verify(&d, &d.create_bundle_json(content, version));
+        assert!(result.is_ok(), "{content:?} {version:?}: {:?}", result);
+    }
+}
+
+#[test]
+fn test_parse_bundle_unsupported_media_type_fails() {
+    let d = SigstoreBundleData::load();
+    let mut bundle = d.create_bundle_json(BundleContent::MessageSignature, RekorVersion::V1);
+    bundle["mediaType"] = json!("application/vnd.dev.sigstore.bundle.v0.4+json");
+
+    let result = parse_sigstore_bundle(&serde_json::to_vec(&bundle).unwrap());
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_parse_bundle_without_signature_fails() {
+    let d = SigstoreBundleData::load();
+    let mut bundle = d.create_bundle_json(BundleContent::MessageSignature, RekorVersion::V1);
+    bundle.as_object_mut().unwrap().remove("messageSignature");
+
+    let result = parse_sigstore_bundle(&serde_json::to_vec(&bundle).unwrap());
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_parse_bundle_accepts_numeric_integers() {
+    let d = SigstoreBundleData::load();
+    let mut bundle = d.create_bundle_json(BundleContent::MessageSignature, RekorVersion::V1);
+    tlog_entry(&mut bundle)["logIndex"] = json!(1);
+
+    let result = parse_sigstore_bundle(&serde_json::to_vec(&bundle).unwrap());
+
+    assert_eq!(
+        result.expect("could not parse bundle").verification_material.tlog_entries[0].log_index,
+        1
+    );
+}
+
+#[test]
+fn test_verify_bundle_signature_wrong_key_fails() {
+    let d = SigstoreBundleData::load();
+
+    for (content, version) in ALL_BUNDLES {
+        let serialized = d.create_bundle(content, version);
+        let bundle = parse_sigstore_bundle(&serialized).expect("could not parse bundle");
+        let result = verify_bundle_signature(&bundle, &d.endorsement, &d.rekor_key_set());
+        assert!(result.is_err(), "{content:?} {version:?}");
+    }
+}
+
+#[test]
+fn test_verify_bundle_signature_other_artifact_fails() {
+    let d = SigstoreBundleData::load();
+    let other: Vec<u8> = b"{}".to_vec();
+
+    for (content, version) in ALL_BUNDLES {
+        let serialized = d.create_bundle(content, version);
+        let bundle = parse_sigstore_bundle(&serialized).expect("could not parse bundle");
+        let result = verify_bundle_signature(&bundle, &other, &endorser_key_set(&d));
+        assert!(result.is_err(), "{content:?} {version:?}");
+    }
+}
+
+#[test]
+fn test_verify_bundle_dsse_other_payload_type_fails() {
+    let d = SigstoreBundleData::load();
+    let mut bundle = d.create_bundle_json(BundleContent::DsseEnvelope, RekorVersion::V2);
+    bundle["dsseEnvelope"]["payloadType"] = json!("application/json");
+
+    assert!(verify(&d, &bundle).is_err());
+}
+
+#[test]
+fn test_verify_bundle_mismatched_body_fails() {
+    let d = SigstoreBundleData::load();
+    let mut bundle = d.create_bundle_json(BundleContent::MessageSignature, RekorVersion::V2);
+    let mut dsse = d.create_bundle_json(BundleContent::DsseEnvelope, RekorVersion::V2);
+    *tlog_entry(&mut bundle) = tlog_entry(&mut dsse).clone();
+
+    assert!(verify(&d, &bundle).is_err());
+}
+
+#[test]
+fn test_verify_bundle_v1_without_inclusion_promise_fails() {
+    let d = SigstoreBundleData::load();
+    let mut bundle = d.create_bundle_json(BundleContent::MessageSignature, RekorVersion::V1);
+    tlog_entry(&mut bundle).as_object_mut().unwrap().remove("inclusionPromise");
+
+    assert!(verify(&d, &bundle).is_err());
+}
+
+#[test]
+fn test_verify_bundle_v1_tampered_integrated_time_fails() {
+    let d = SigstoreBundleData::load();
+    let mut bundle = d.create_bundle_json(BundleContent::DsseEnvelope, RekorVersion::V1);
+    tlog_entry(&mut bundle)["integratedTime"] = json!("1");
+
+    assert!(verify(&d, &bundle).is_err());
+}
+
+#[test]
+fn test_verify_bundle_v2_tampered_inclusion_proof_fails() {
+    let d = SigstoreBundleData::load();
+    let mut bundle = d.create_bundle_json(BundleContent::DsseEnvelope, RekorVersion::V2);
+    tlog_entry(&mut bundle)["inclusionProof"]["logIndex"] = json!("0");
+
+    assert!(verify(&d, &bundle).is_err());
+}
+
+#[test]
+fn test_verify_bundle_v2_without_inclusion_proof_fails() {
+    let d = SigstoreBundleData::load();
+    let mut bundle = d.create_bundle_json(BundleContent::MessageSignature, RekorVersion::V2);
+    tlog_entry(&mut bundle).as_object_mut().unwrap().remove("inclusionProof");
+
+    assert!(verify(&d, &bundle).is_err());
+}
+
+#[test]
+fn test_verify_bundle_v2_with_timestamp_reference_value_fails() {
+    let d = SigstoreBundleData::load();
+    let bundle = d.create_bundle_json(BundleContent::MessageSignature, RekorVersion::V2);
+    let mut key_set = d.rekor_key_set();
+    key_set.signed_timestamp = Some(TimestampReferenceValue::default());
+
+    assert!(verify_with(&d, &bundle, &key_set).is_err());
+}
+
+#[test]
+fn test_verify_bundle_wrong_rekor_key_fails() {
+    let d = SigstoreBundleData::load();
+
+    for (content, version) in ALL_BUNDLES {
+        let result =
+            verify_with(&d, &d.create_bundle_json(content, version), &endorser_key_set(&d));
+        assert!(result.is_err(), "{content:?} {version:?}");
+    }
+}
@@ -0,0 +1,107 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Provides parsing and verification of transparency log checkpoints (signed
+//! tree heads) in signed note format. See
+//! <https://github.com/transparency-dev/formats/blob/main/log/README.md> and
+//! <https://pkg.go.dev/golang.org/x/mod/sumdb/note>
+
+use alloc::{string::String, vec::Vec};
+
+use anyhow::{ensure, Context};
+use base64::{prelude::BASE64_STANDARD, Engine as _};
+use key_util::verify_signature_ecdsa;
+use oak_proto_rust::oak::attestation::v1::{KeyType, VerifyingKeySet};
+
+/// Every signature line of a signed note starts with this prefix.
+const SIGNATURE_LINE_PREFIX: &str = "\u{2014} ";
+
+/// The length of the key hint which precedes the signature.
+const KEY_HINT_SIZE: usize = 4;
+
+/// Represents the body of a checkpoint.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Checkpoint {
+    /// Uniquely identifies the log and, for sharded logs, the shard.
+    pub origin: String,
+
+    /// The number of entries in the log.
+    pub tree_size: u64,
+
+    /// The root hash of the Merkle tree of size `tree_size`.
+    pub root_hash: Vec<u8>,
+}
+
+/// Parses the body of a checkpoint without verifying its signatures.
+pub fn parse_checkpoint(note: &str) -> anyhow::Result<Checkpoint> {
+    let (text, _) = split_note(note)?;
+    let mut lines = text.lines();
+    let origin = lines.next().context("checkpoint has no origin")?;
+    ensure!(!origin.is_empty(), "checkpoint has an empty origin");
+    let tree_size = lines
+        .next()
+        .context("checkpoint has no tree size")?
+        .parse::<u64>()
+        .map_err(|error| anyhow::anyhow!("invalid checkpoint tree size: {error}"))?;
+    let root_hash = BASE64_STANDARD
+        .decode(lines.next().context("checkpoint has no root hash")?)
+        .map_err(|_| anyhow::anyhow!("failed to base64-decode checkpoint root hash"))?;
+    Ok(Checkpoint { origin: origin.into(), tree_size, root_hash })
+}
+
+/// Verifies that the checkpoint is signed by one of the keys in the set and
+/// returns its parsed body.
+///
+/// The key hint preceding each signature is not used to select keys, since
+/// logs derive it differently (Rekor v1 uses the first 4 bytes of the SHA2-256
+/// digest of the ASN.1 DER public key, Rekor v2 a digest over the key name).
+pub fn verify_checkpoint(note: &str, key_set: &VerifyingKeySet) -> anyhow::Result<Checkpoint> {
+    let checkpoint = parse_checkpoint(note)?;
+    let (text, signatures) = split_note(note)?;
+
+    for line in signatures.lines() {
+        let (_name, signature) = line
+            .strip_prefix(SIGNATURE_LINE_PREFIX)
+            .and_then(|rest| rest.split_once(' '))
+            .context("malformed checkpoint signature line")?;
+        let signature = BASE64_STANDARD
+            .decode(signature)
+            .map_err(|_| anyhow::anyhow!("failed to base64-decode checkpoint signature"))?;
+        ensure!(signature.len() > KEY_HINT_SIZE, "checkpoint signature is too short");
+        let (_key_hint, signature) = signature.split_at(KEY_HINT_SIZE);
+
+        let verified = key_set
+            .keys
+            .iter()
+            .filter(|k| k.r#type() == KeyType::EcdsaP256Sha256)
+            .any(|k| verify_signature_ecdsa(signature, text.as_bytes(), &k.raw).is_ok());
+        if verified {
+            return Ok(checkpoint);
+        }
+    }
+
+    anyhow::bail!("could not verify checkpoint signature")
+}
+
+/// Splits a signed note into its text, including the final newline, and its
+/// signature lines.
+fn split_note(note: &str) -> anyhow::Result<(&str, &str)> {
+    let index = note.find("\n\n").context("checkpoint has no signatures")?;
+    Ok((&note[..index + 1], &note[index + 2..]))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,109 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+extern crate alloc;
+
+use alloc::string::String;
+
+use oak_proto_rust::oak::attestation::v1::{KeyType, VerifyingKey, VerifyingKeySet};
+use test_util::EndorsementData;
+
+use crate::{
+    checkpoint::{parse_checkpoint, verify_checkpoint},
+    log_entry::parse_rekor_log_entry,
+};
+
+fn create_verifying_key_set(public_key: &[u8]) -> VerifyingKeySet {
+    let key = VerifyingKey {
+        r#type: KeyType::EcdsaP256Sha256.into(),
+        key_id: 1,
+        raw: public_key.to_vec(),
+    };
+    VerifyingKeySet { keys: [key].to_vec(), ..Default::default() }
+}
+
+fn load_checkpoint(d: &EndorsementData) -> String {
+    let log_entry = parse_rekor_log_entry(&d.log_entry).expect("could not parse log entry");
+    log_entry
+        .verification
+        .expect("no verification")
+        .inclusion_proof
+        .expect("no inclusion proof")
+        .checkpoint
+}
+
+#[test]
+fn test_parse_checkpoint_success() {
+    let d = EndorsementData::load();
+
+    let checkpoint = parse_checkpoint(&load_checkpoint(&d)).expect("could not parse checkpoint");
+
+    assert_eq!(checkpoint.origin, "rekor.sigstore.dev - 1193050959916656506");
+    assert_eq!(checkpoint.tree_size, 10289604);
+    assert_eq!(
+        hex::encode(&checkpoint.root_hash),
+        "3e1f23846699717d400a4647d707bd2cc4a8d576092ed05113a003fe599ee7fb"
+    );
+}
+
+#[test]
+fn test_verify_checkpoint_success() {
+    let d = EndorsementData::load();
+    let key_set = create_verifying_key_set(&d.rekor_public_key);
+
+    let result = verify_checkpoint(&load_checkpoint(&d), &key_set);
+
+    assert!(result.is_ok(), "{:?}", result);
+}
+
+#[test]
+fn test_verify_checkpoint_wrong_key_fails() {
+    let d = EndorsementData::load();
+    let key_set = create_verifying_key_set(&d.endorser_public_key);
+
+    let result = verify_checkpoint(&load_checkpoint(&d), &key_set);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_verify_checkpoint_tampered_tree_size_fails() {
+    let d = EndorsementData::load();
+    let key_set = create_verifying_key_set(&d.rekor_public_key);
+    let checkpoint = load_checkpoint(&d).replacen("\n10289604\n", "\n10289605\n", 1);
+
+    let result = verify_checkpoint(&checkpoint, &key_set);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_verify_checkpoint_without_signature_fails() {
+    let d = EndorsementData::load();
+    let key_set = create_verifying_key_set(&d.rekor_public_key);
+    let checkpoint = load_checkpoint(&d);
+    let (text, _) = checkpoint.split_once("\n\n").expect("no signatures");
+
+    assert!(verify_checkpoint(text, &key_set).is_err());
+    assert!(verify_checkpoint(&alloc::format!("{text}\n\n"), &key_set).is_err());
+}
+
+#[test]
+fn test_parse_checkpoint_malformed_fails() {
+    assert!(parse_checkpoint("origin\nnot a number\nAAAA\n\n").is_err());
+    assert!(parse_checkpoint("origin\n1\nnot base64!\n\n").is_err());
+    assert!(parse_checkpoint("\n\n").is_err());
+}
@@ -18,7 +18,10 @@
 
 extern crate alloc;
 
+pub mod bundle;
+pub mod checkpoint;
 pub mod log_entry;
+pub mod merkle;
 mod util;
 
-pub use util::{get_rekor_v1_public_key_pem, get_rekor_v1_public_key_raw};
+pub use util::{get_rekor_v1_public_key_pem, get_rekor_v1_public_key_raw, verify_timestamp};
@@ -26,13 +26,15 @@ use anyhow::{ensure, Context};
 use base64::{prelude::BASE64_STANDARD, Engine as _};
 use digest_util::hash_sha2_256;
 use key_util::{convert_pem_to_raw, equal_keys, verify_signature_ecdsa};
-use oak_proto_rust::oak::attestation::v1::VerifyingKeySet;
+use oak_proto_rust::oak::attestation::v1::{CheckpointConsistencyReferenceValue, VerifyingKeySet};
 use oak_time::Instant;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::{
     alloc::{collections::BTreeMap, format, string::String, vec::Vec},
+    checkpoint::{verify_checkpoint, Checkpoint},
+    merkle::{hash_leaf, verify_consistency_proof, verify_inclusion_proof},
     util::verify_timestamp,
 };
 
@@ -78,7 +80,8 @@ impl LogEntry {
             bundle.get(SIGNED_ENTRY_TIMESTAMP_KEY).ok_or(anyhow::anyhow!("malformed bundle"))?;
 
         let signed: String = String::deserialize(signed)?;
-        let verification = Verification { signed_entry_timestamp: signed.to_owned() };
+        let verification =
+            Verification { inclusion_proof: None, signed_entry_timestamp: signed.to_owned() };
 
         // As per the spec above, the signature of the payload is done over
         // the canonicalized representation of its fields, which means:
@@ -186,16 +189,43 @@ pub struct PublicKey {
     pub content: String,
 }
 
-/// Represents a verification object in a Rekor log entry. Since we currently
-/// don't verify the inclusion proof, the associated fields are not mentioned
-/// or deserialized.
+/// Represents a verification object in a Rekor log entry.
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Verification {
+    /// Proof that the entry is included in the log, if provided by the log.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "inclusionProof")]
+    pub inclusion_proof: Option<InclusionProof>,
+
     // Base64-encoded signature over body, integratedTime, logID, logIndex.
     #[serde(rename = "signedEntryTimestamp")]
     pub signed_entry_timestamp: String,
 }
 
+/// Represents the inclusion proof of a Rekor log entry. See
+/// <https://github.com/sigstore/rekor/blob/d920fad17c98aff21d98036db6a4820542f7d18d/openapi.yaml#L609-L652>
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct InclusionProof {
+    /// The checkpoint (signed tree head) that the proof is based on.
+    pub checkpoint: String,
+
+    /// Hex-encoded hashes of the proof, ordered from the leaf to the root.
+    pub hashes: Vec<String>,
+
+    /// The index of the entry in the tree. Differs from the log index of the
+    /// entry when the log is sharded.
+    #[serde(rename = "logIndex")]
+    pub log_index: u64,
+
+    /// The hex-encoded root hash of the tree.
+    #[serde(rename = "rootHash")]
+    pub root_hash: String,
+
+    /// The size of the tree.
+    #[serde(rename = "treeSize")]
+    pub tree_size: u64,
+}
+
 /// Verifies a Rekor log entry by key set and current time.
 ///
 /// # Arguments
@@ -219,8 +249,22 @@ pub fn verify_rekor_log_entry(
 ) -> anyhow::Result<LogEntry> {
     let log_entry =
         parse_rekor_log_entry(serialized_log_entry).context("parsing Rekor log entry")?;
+    verify_rekor_integration(&log_entry, key_set, now_utc_millis)?;
 
-    if !key_set.keys.iter().any(|k| verify_rekor_signature(&log_entry, &k.raw).is_ok()) {
+    let body = log_entry.body()?;
+    verify_rekor_body(&body, artifact_bytes).context("verifying Rekor body")?;
+    Ok(log_entry)
+}
+
+/// Verifies that Rekor integrated the log entry, without looking at its body:
+/// the signature in `signedEntryTimestamp`, the integration time if requested
+/// by the key set, and the inclusion proof if required by the key set.
+pub(crate) fn verify_rekor_integration(
+    log_entry: &LogEntry,
+    key_set: &VerifyingKeySet,
+    now_utc_millis: i64,
+) -> anyhow::Result<()> {
+    if !key_set.keys.iter().any(|k| verify_rekor_signature(log_entry, &k.raw).is_ok()) {
         anyhow::bail!("could not verify rekor signature");
     }
 
@@ -233,9 +277,88 @@ pub fn verify_rekor_log_entry(
             .context("verifying rekor integrate timestamp")?;
     }
 
-    let body = log_entry.body()?;
-    verify_rekor_body(&body, artifact_bytes).context("verifying Rekor body")?;
-    Ok(log_entry)
+    if key_set.require_inclusion_proof {
+        verify_rekor_inclusion_proof(log_entry, key_set)
+            .context("verifying Rekor inclusion proof")?;
+    }
+    Ok(())
+}
+
+/// Verifies the inclusion proof of a Rekor log entry.
+///
+/// Steps include:
+///
+/// 1. the signature of the checkpoint using one of Rekor's public keys,
+/// 2. that the root hash and tree size of the proof match the checkpoint,
+/// 3. that the proof includes the log entry body in the tree.
+///
+/// # Returns
+///
+/// The verified checkpoint.
+pub fn verify_rekor_inclusion_proof(
+    log_entry: &LogEntry,
+    key_set: &VerifyingKeySet,
+) -> anyhow::Result<Checkpoint> {
+    let proof = log_entry
+        .verification
+        .as_ref()
+        .and_then(|v| v.inclusion_proof.as_ref())
+        .context("no inclusion proof in the log entry")?;
+    let checkpoint =
+        verify_checkpoint(&proof.checkpoint, key_set).context("verifying checkpoint")?;
+
+    let root_hash = hex::decode(&proof.root_hash)
+        .map_err(|_| anyhow::anyhow!("failed to hex-decode root hash"))?;
+    ensure!(checkpoint.tree_size == proof.tree_size, "tree size does not match checkpoint");
+    ensure!(checkpoint.root_hash == root_hash, "root hash does not match checkpoint");
+
+    let hashes = proof
+        .hashes
+        .iter()
+        .map(|h| hex::decode(h).map_err(|_| anyhow::anyhow!("failed to hex-decode proof hash")))
+        .collect::<anyhow::Result<Vec<Vec<u8>>>>()?;
+    let body: Vec<u8> = BASE64_STANDARD
+        .decode(&log_entry.body)
+        .map_err(|_| anyhow::anyhow!("failed to decode log entry body"))?;
+    verify_inclusion_proof(
+        proof.log_index,
+        proof.tree_size,
+        &hash_leaf(&body),
+        &hashes,
+        &checkpoint.root_hash,
+    )?;
+    Ok(checkpoint)
+}
+
+/// Verifies that the checkpoint of a Rekor log entry is consistent with a
+/// checkpoint previously observed by the client.
+///
+/// The inclusion proof of the log entry is verified first, so that the
+/// checkpoint of the log entry can be trusted. Both checkpoints need to be
+/// signed by one of Rekor's public keys.
+pub fn verify_rekor_checkpoint_consistency(
+    log_entry: &LogEntry,
+    key_set: &VerifyingKeySet,
+    reference_value: &CheckpointConsistencyReferenceValue,
+) -> anyhow::Result<()> {
+    let checkpoint = verify_rekor_inclusion_proof(log_entry, key_set)
+        .context("verifying Rekor inclusion proof")?;
+    let trusted = verify_checkpoint(&reference_value.checkpoint, key_set)
+        .context("verifying trusted checkpoint")?;
+    ensure!(checkpoint.origin == trusted.origin, "checkpoints are from different logs");
+
+    let (first, second) = if trusted.tree_size <= checkpoint.tree_size {
+        (&trusted, &checkpoint)
+    } else {
+        (&checkpoint, &trusted)
+    };
+    verify_consistency_proof(
+        first.tree_size,
+        second.tree_size,
+        &reference_value.proof_hashes,
+        &first.root_hash,
+        &second.root_hash,
+    )
 }
 
 /// Verifies a Rekor log entry.
@@ -278,7 +401,7 @@ pub fn verify_rekor_log_entry_ecdsa(
 ///
 /// * `body`: The log entry body.
 /// * `artifact_bytes` The underlying artifact or endorsement.
-fn verify_rekor_body(body: &Body, artifact_bytes: &[u8]) -> anyhow::Result<()> {
+pub(crate) fn verify_rekor_body(body: &Body, artifact_bytes: &[u8]) -> anyhow::Result<()> {
     // TODO: b/445876203 - Reject type "rekord" at some point in the future.
     ensure!(
         body.kind == "rekord" || body.kind == "hashedrekord",
@@ -16,14 +16,19 @@
 
 extern crate alloc;
 
-use oak_proto_rust::oak::attestation::v1::{KeyType, VerifyingKey, VerifyingKeySet};
+use alloc::vec;
+
+use oak_proto_rust::oak::attestation::v1::{
+    CheckpointConsistencyReferenceValue, KeyType, VerifyingKey, VerifyingKeySet,
+};
 use oak_time::make_instant;
 use serde_json::json;
 use test_util::EndorsementData;
 
 use super::*;
 use crate::log_entry::{
-    parse_rekor_log_entry, verify_rekor_log_entry_ecdsa, verify_rekor_signature, LogEntry,
+    parse_rekor_log_entry, verify_rekor_checkpoint_consistency, verify_rekor_inclusion_proof,
+    verify_rekor_log_entry_ecdsa, verify_rekor_signature, LogEntry,
 };
 
 /// Shorthand to create a reference value proto from ingredients.
@@ -105,6 +110,146 @@ fn test_from_cosign_bundle_invalid_signature_base64() {
     assert!(result.is_err());
 }
 
+fn get_inclusion_proof(log_entry: &mut LogEntry) -> &mut InclusionProof {
+    log_entry
+        .verification
+        .as_mut()
+        .expect("no verification")
+        .inclusion_proof
+        .as_mut()
+        .expect("no inclusion proof")
+}
+
+#[test]
+fn test_verify_rekor_inclusion_proof_success() {
+    let d = EndorsementData::load();
+    let key_set = create_verifying_key_set(&d.rekor_public_key);
+    let log_entry = parse_rekor_log_entry(&d.log_entry).expect("could not parse log entry");
+
+    let result = verify_rekor_inclusion_proof(&log_entry, &key_set);
+
+    assert!(result.is_ok(), "{:?}", result);
+    assert_eq!(result.unwrap().tree_size, 10289604);
+}
+
+#[test]
+fn test_verify_rekor_inclusion_proof_wrong_key_fails() {
+    let d = EndorsementData::load();
+    let key_set = create_verifying_key_set(&d.endorser_public_key);
+    let log_entry = parse_rekor_log_entry(&d.log_entry).expect("could not parse log entry");
+
+    let result = verify_rekor_inclusion_proof(&log_entry, &key_set);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_verify_rekor_inclusion_proof_tampered_hash_fails() {
+    let d = EndorsementData::load();
+    let key_set = create_verifying_key_set(&d.rekor_public_key);
+    let mut log_entry = parse_rekor_log_entry(&d.log_entry).expect("could not parse log entry");
+    get_inclusion_proof(&mut log_entry).hashes[0] = "00".repeat(32);
+
+    let result = verify_rekor_inclusion_proof(&log_entry, &key_set);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_verify_rekor_inclusion_proof_root_hash_mismatch_fails() {
+    let d = EndorsementData::load();
+    let key_set = create_verifying_key_set(&d.rekor_public_key);
+    let mut log_entry = parse_rekor_log_entry(&d.log_entry).expect("could not parse log entry");
+    get_inclusion_proof(&mut log_entry).root_hash = "00".repeat(32);
+
+    let result = verify_rekor_inclusion_proof(&log_entry, &key_set);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_verify_rekor_inclusion_proof_other_body_fails() {
+    let d = EndorsementData::load();
+    let key_set = create_verifying_key_set(&d.rekor_public_key);
+    let mut log_entry = parse_rekor_log_entry(&d.log_entry).expect("could not parse log entry");
+    let other = LogEntry::from_cosign_bundle(REKOR_BUNDLE).expect("failed to parse bundle");
+    log_entry.body = other.body;
+
+    let result = verify_rekor_inclusion_proof(&log_entry, &key_set);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_verify_rekor_log_entry_requiring_inclusion_proof_success() {
+    let d = EndorsementData::load();
+    let mut key_set = create_verifying_key_set(&d.rekor_public_key);
+    key_set.require_inclusion_proof = true;
+
+    let result = verify_rekor_log_entry(&d.log_entry, &key_set, &d.endorsement, 0);
+
+    assert!(result.is_ok(), "{:?}", result);
+}
+
+#[test]
+fn test_verify_rekor_log_entry_requiring_missing_inclusion_proof_fails() {
+    let d = EndorsementData::load();
+    let mut key_set = create_verifying_key_set(&d.rekor_public_key);
+    key_set.require_inclusion_proof = true;
+    let mut log_entry = parse_rekor_log_entry(&d.log_entry).expect("could not parse log entry");
+    log_entry.verification.as_mut().expect("no verification").inclusion_proof = None;
+    let serialized = serialize_rekor_log_entry(&log_entry).expect("failed to serialize");
+
+    let result = verify_rekor_log_entry(&serialized, &key_set, &d.endorsement, 0);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_verify_rekor_checkpoint_consistency_same_checkpoint_success() {
+    let d = EndorsementData::load();
+    let key_set = create_verifying_key_set(&d.rekor_public_key);
+    let mut log_entry = parse_rekor_log_entry(&d.log_entry).expect("could not parse log entry");
+    let reference_value = CheckpointConsistencyReferenceValue {
+        checkpoint: get_inclusion_proof(&mut log_entry).checkpoint.clone(),
+        proof_hashes: vec![],
+    };
+
+    let result = verify_rekor_checkpoint_consistency(&log_entry, &key_set, &reference_value);
+
+    assert!(result.is_ok(), "{:?}", result);
+}
+
+#[test]
+fn test_verify_rekor_checkpoint_consistency_invalid_proof_fails() {
+    let d = EndorsementData::load();
+    let key_set = create_verifying_key_set(&d.rekor_public_key);
+    let mut log_entry = parse_rekor_log_entry(&d.log_entry).expect("could not parse log entry");
+    let reference_value = CheckpointConsistencyReferenceValue {
+        checkpoint: get_inclusion_proof(&mut log_entry).checkpoint.clone(),
+        proof_hashes: vec![vec![0; 32]],
+    };
+
+    let result = verify_rekor_checkpoint_consistency(&log_entry, &key_set, &reference_value);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_verify_rekor_checkpoint_consistency_unsigned_checkpoint_fails() {
+    let d = EndorsementData::load();
+    let key_set = create_verifying_key_set(&d.rekor_public_key);
+    let log_entry = parse_rekor_log_entry(&d.log_entry).expect("could not parse log entry");
+    let reference_value = CheckpointConsistencyReferenceValue {
+        checkpoint: "rekor.sigstore.dev - 1193050959916656506\n1\nAAAA\n\n".into(),
+        proof_hashes: vec![],
+    };
+
+    let result = verify_rekor_checkpoint_consistency(&log_entry, &key_set, &reference_value);
+
+    assert!(result.is_err());
+}
+
 // An actual Rekor bundle returned from cosign::pull_payload(), along with all
 // additional pieces: statement, signature over it, and developer key.
 const STATEMENT: &str = r###"{
@@ -0,0 +1,171 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Provides verification of Merkle tree inclusion and consistency proofs as
+//! used by transparency logs. See
+//! <https://www.rfc-editor.org/rfc/rfc9162#section-2.1>
+
+use alloc::vec::Vec;
+
+use anyhow::ensure;
+use digest_util::hash_sha2_256;
+
+/// The length in bytes of a node hash in the tree.
+pub const HASH_SIZE: usize = 32;
+
+const LEAF_HASH_PREFIX: u8 = 0;
+const NODE_HASH_PREFIX: u8 = 1;
+
+/// Computes the hash of a leaf from its contents.
+pub fn hash_leaf(leaf: &[u8]) -> [u8; HASH_SIZE] {
+    let mut data = Vec::with_capacity(leaf.len() + 1);
+    data.push(LEAF_HASH_PREFIX);
+    data.extend_from_slice(leaf);
+    hash_sha2_256(&data)
+}
+
+/// Computes the hash of an interior node from the hashes of its children.
+pub fn hash_children(left: &[u8], right: &[u8]) -> [u8; HASH_SIZE] {
+    let mut data = Vec::with_capacity(left.len() + right.len() + 1);
+    data.push(NODE_HASH_PREFIX);
+    data.extend_from_slice(left);
+    data.extend_from_slice(right);
+    hash_sha2_256(&data)
+}
+
+/// Verifies that the leaf with the given hash is at `leaf_index` in the tree
+/// of size `tree_size` with root `root_hash`.
+///
+/// Implements the algorithm of RFC 9162, section 2.1.3.2.
+pub fn verify_inclusion_proof(
+    leaf_index: u64,
+    tree_size: u64,
+    leaf_hash: &[u8],
+    proof: &[Vec<u8>],
+    root_hash: &[u8],
+) -> anyhow::Result<()> {
+    ensure!(
+        leaf_index < tree_size,
+        "leaf index {} is out of range for tree size {}",
+        leaf_index,
+        tree_size
+    );
+    check_hash_sizes(proof)?;
+
+    let mut f_n = leaf_index;
+    let mut s_n = tree_size - 1;
+    let mut r = to_hash(leaf_hash)?;
+    for p in proof {
+        ensure!(s_n != 0, "inclusion proof is too long");
+        if f_n & 1 == 1 || f_n == s_n {
+            r = hash_children(p, &r);
+            while f_n & 1 == 0 && f_n != 0 {
+                f_n >>= 1;
+                s_n >>= 1;
+            }
+        } else {
+            r = hash_children(&r, p);
+        }
+        f_n >>= 1;
+        s_n >>= 1;
+    }
+
+    ensure!(s_n == 0, "inclusion proof is too short");
+    ensure!(r[..] == *root_hash, "inclusion proof does not match root hash");
+    Ok(())
+}
+
+/// Verifies that the tree of size `second_size` with root `second_root` is an
+/// append-only extension of the tree of size `first_size` with root
+/// `first_root`.
+///
+/// Implements the algorithm of RFC 9162, section 2.1.4.2.
+pub fn verify_consistency_proof(
+    first_size: u64,
+    second_size: u64,
+    proof: &[Vec<u8>],
+    first_root: &[u8],
+    second_root: &[u8],
+) -> anyhow::Result<()> {
+    ensure!(
+        first_size <= second_size,
+        "first tree size {} exceeds second tree size {}",
+        first_size,
+        second_size
+    );
+    check_hash_sizes(proof)?;
+
+    if first_size == second_size {
+        ensure!(proof.is_empty(), "consistency proof for trees of equal size must be empty");
+        ensure!(first_root == second_root, "roots of trees of equal size differ");
+        return Ok(());
+    }
+    if first_size == 0 {
+        // Every tree is consistent with the empty tree.
+        ensure!(proof.is_empty(), "consistency proof from the empty tree must be empty");
+        return Ok(());
+    }
+    ensure!(!proof.is_empty(), "consistency proof is empty");
+
+    // If the first tree is complete, its root is part of the proof.
+    let mut proof = proof.iter().map(|p| p.as_slice());
+    let mut f_n = first_size - 1;
+    let mut s_n = second_size - 1;
+    let start = if first_size.is_power_of_two() {
+        to_hash(first_root)?
+    } else {
+        to_hash(proof.next().expect("proof is not empty"))?
+    };
+    while f_n & 1 == 1 {
+        f_n >>= 1;
+        s_n >>= 1;
+    }
+
+    let mut f_r = start;
+    let mut s_r = start;
+    for c in proof {
+        ensure!(s_n != 0, "consistency proof is too long");
+        if f_n & 1 == 1 || f_n == s_n {
+            f_r = hash_children(c, &f_r);
+            s_r = hash_children(c, &s_r);
+            while f_n & 1 == 0 && f_n != 0 {
+                f_n >>= 1;
+                s_n >>= 1;
+            }
+        } else {
+            s_r = hash_children(&s_r, c);
+        }
+        f_n >>= 1;
+        s_n >>= 1;
+    }
+
+    ensure!(s_n == 0, "consistency proof is too short");
+    ensure!(f_r[..] == *first_root, "consistency proof does not match first root");
+    ensure!(s_r[..] == *second_root, "consistency proof does not match second root");
+    Ok(())
+}
+
+fn check_hash_sizes(proof: &[Vec<u8>]) -> anyhow::Result<()> {
+    ensure!(proof.iter().all(|p| p.len() == HASH_SIZE), "proof contains a hash of invalid size");
+    Ok(())
+}
+
+fn to_hash(bytes: &[u8]) -> anyhow::Result<[u8; HASH_SIZE]> {
+    bytes.try_into().map_err(|_| anyhow::anyhow!("invalid hash size: {}", bytes.len()))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,234 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+extern crate alloc;
+
+use alloc::{vec, vec::Vec};
+
+use crate::merkle::{
+    hash_children, hash_leaf, verify_consistency_proof, verify_inclusion_proof, HASH_SIZE,
+};
+
+const MAX_TREE_SIZE: usize = 17;
+
+// Reference implementation of the Merkle tree hash, audit paths and
+// consistency proofs following the recursive definitions of RFC 6962,
+// section 2.1.
+
+fn leaves(n: usize) -> Vec<Vec<u8>> {
+    (0..n).map(|i| vec![i as u8; i % 3 + 1]).collect()
+}
+
+// Largest power of two strictly smaller than n.
+fn split(n: usize) -> usize {
+    let mut k = 1;
+    while k << 1 < n {
+        k <<= 1;
+    }
+    k
+}
+
+fn tree_hash(leaves: &[Vec<u8>]) -> [u8; HASH_SIZE] {
+    match leaves.len() {
+        0 => digest_util::hash_sha2_256(&[]),
+        1 => hash_leaf(&leaves[0]),
+        n => {
+            let k = split(n);
+            hash_children(&tree_hash(&leaves[..k]), &tree_hash(&leaves[k..]))
+        }
+    }
+}
+
+fn audit_path(m: usize, leaves: &[Vec<u8>]) -> Vec<Vec<u8>> {
+    let n = leaves.len();
+    if n <= 1 {
+        return vec![];
+    }
+    let k = split(n);
+    if m < k {
+        let mut path = audit_path(m, &leaves[..k]);
+        path.push(tree_hash(&leaves[k..]).to_vec());
+        path
+    } else {
+        let mut path = audit_path(m - k, &leaves[k..]);
+        path.push(tree_hash(&leaves[..k]).to_vec());
+        path
+    }
+}
+
+fn subproof(m: usize, leaves: &[Vec<u8>], complete: bool) -> Vec<Vec<u8>> {
+    let n = leaves.len();
+    if m == n {
+        return if complete { vec![] } else { vec![tree_hash(leaves).to_vec()] };
+    }
+    let k = split(n);
+    if m <= k {
+        let mut proof = subproof(m, &leaves[..k], complete);
+        proof.push(tree_hash(&leaves[k..]).to_vec());
+        proof
+    } else {
+        let mut proof = subproof(m - k, &leaves[k..], false);
+        proof.push(tree_hash(&leaves[..k]).to_vec());
+        proof
+    }
+}
+
+fn consistency_proof(m: usize, leaves: &[Vec<u8>]) -> Vec<Vec<u8>> {
+    subproof(m, leaves, true)
+}
+
+#[test]
+fn test_verify_inclusion_proof_success() {
+    for n in 1..=MAX_TREE_SIZE {
+        let leaves = leaves(n);
+        let root = tree_hash(&leaves);
+        for m in 0..n {
+            let proof = audit_path(m, &leaves);
+            let result =
+                verify_inclusion_proof(m as u64, n as u64, &hash_leaf(&leaves[m]), &proof, &root);
+            assert!(result.is_ok(), "n={n} m={m}: {:?}", result);
+        }
+    }
+}
+
+#[test]
+fn test_verify_inclusion_proof_wrong_leaf_fails() {
+    let leaves = leaves(7);
+    let root = tree_hash(&leaves);
+    let proof = audit_path(3, &leaves);
+
+    let result = verify_inclusion_proof(3, 7, &hash_leaf(&leaves[4]), &proof, &root);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_verify_inclusion_proof_wrong_index_fails() {
+    let leaves = leaves(7);
+    let root = tree_hash(&leaves);
+    let proof = audit_path(3, &leaves);
+
+    let result = verify_inclusion_proof(2, 7, &hash_leaf(&leaves[3]), &proof, &root);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_verify_inclusion_proof_tampered_hash_fails() {
+    let leaves = leaves(7);
+    let root = tree_hash(&leaves);
+    let mut proof = audit_path(3, &leaves);
+    proof[1][0] ^= 1;
+
+    let result = verify_inclusion_proof(3, 7, &hash_leaf(&leaves[3]), &proof, &root);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_verify_inclusion_proof_wrong_length_fails() {
+    let leaves = leaves(7);
+    let root = tree_hash(&leaves);
+    let proof = audit_path(3, &leaves);
+    let leaf_hash = hash_leaf(&leaves[3]);
+
+    let mut longer = proof.clone();
+    longer.push(vec![0; HASH_SIZE]);
+    assert!(verify_inclusion_proof(3, 7, &leaf_hash, &longer, &root).is_err());
+    assert!(verify_inclusion_proof(3, 7, &leaf_hash, &proof[..proof.len() - 1], &root).is_err());
+}
+
+#[test]
+fn test_verify_inclusion_proof_index_out_of_range_fails() {
+    let leaves = leaves(4);
+    let root = tree_hash(&leaves);
+
+    let result = verify_inclusion_proof(4, 4, &hash_leaf(&leaves[3]), &[], &root);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_verify_consistency_proof_success() {
+    for n in 1..=MAX_TREE_SIZE {
+        let leaves = leaves(n);
+        let second_root = tree_hash(&leaves);
+        for m in 1..=n {
+            let first_root = tree_hash(&leaves[..m]);
+            let proof = consistency_proof(m, &leaves);
+            let result =
+                verify_consistency_proof(m as u64, n as u64, &proof, &first_root, &second_root);
+            assert!(result.is_ok(), "n={n} m={m}: {:?}", result);
+        }
+    }
+}
+
+#[test]
+fn test_verify_consistency_proof_wrong_first_root_fails() {
+    let leaves = leaves(11);
+    let proof = consistency_proof(6, &leaves);
+    let first_root = tree_hash(&leaves[..5]);
+
+    let result = verify_consistency_proof(6, 11, &proof, &first_root, &tree_hash(&leaves));
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_verify_consistency_proof_forked_tree_fails() {
+    let leaves = leaves(11);
+    let first_root = tree_hash(&leaves[..6]);
+    // A tree which rewrites an entry of the first tree.
+    let mut forked = leaves.clone();
+    forked[2] = vec![42];
+    let proof = consistency_proof(6, &forked);
+
+    let result = verify_consistency_proof(6, 11, &proof, &first_root, &tree_hash(&forked));
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_verify_consistency_proof_tampered_hash_fails() {
+    let leaves = leaves(11);
+    let mut proof = consistency_proof(6, &leaves);
+    proof[0][0] ^= 1;
+
+    let result =
+        verify_consistency_proof(6, 11, &proof, &tree_hash(&leaves[..6]), &tree_hash(&leaves));
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_verify_consistency_proof_equal_sizes() {
+    let leaves = leaves(5);
+    let root = tree_hash(&leaves);
+    let other_root = tree_hash(&leaves[..4]);
+
+    assert!(verify_consistency_proof(5, 5, &[], &root, &root).is_ok());
+    assert!(verify_consistency_proof(5, 5, &[], &root, &other_root).is_err());
+    assert!(verify_consistency_proof(5, 5, &[root.to_vec()], &root, &root).is_err());
+}
+
+#[test]
+fn test_verify_consistency_proof_decreasing_size_fails() {
+    let leaves = leaves(5);
+
+    let result = verify_consistency_proof(5, 4, &[], &tree_hash(&leaves), &tree_hash(&leaves[..4]));
+
+    assert!(result.is_err());
+}
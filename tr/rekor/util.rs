@@ -42,7 +42,7 @@ pub fn get_rekor_v1_public_key_raw() -> Vec<u8> {
 ///   current_time: The current time as instant.
 ///   timestamp: The timestamp of the signature, e.g. signedLogEntryTimestamp
 ///   reference_value: The reference value from the client.
-pub(crate) fn verify_timestamp(
+pub fn verify_timestamp(
     current_time: Instant,
     timestamp: Instant,
     reference_value: &TimestampReferenceValue,
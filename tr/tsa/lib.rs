@@ -0,0 +1,450 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Provides verification of RFC 3161 timestamps issued by a timestamp
+//! authority (TSA). Only the subset of CMS used by timestamp tokens is parsed,
+//! and only ECDSA P-256 keys with SHA2-256 signatures are supported. See
+//! <https://www.rfc-editor.org/rfc/rfc3161> and
+//! <https://www.rfc-editor.org/rfc/rfc5652>
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::{format, vec::Vec};
+
+use anyhow::{ensure, Context};
+use digest_util::hash_sha2_256;
+use key_util::verify_signature_ecdsa;
+use oak_proto_rust::oak::attestation::v1::TimestampAuthorityReferenceValue;
+use oak_time::Instant;
+use x509_cert::{
+    der::{
+        asn1::{ObjectIdentifier, OctetStringRef},
+        oid::AssociatedOid,
+        AnyRef, DateTime, Decode, Encode, Reader, SliceReader, Tag, TagNumber, Tagged,
+    },
+    ext::pkix::{BasicConstraints, ExtendedKeyUsage},
+    Certificate,
+};
+
+const ID_SIGNED_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.2");
+const ID_CT_TST_INFO: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.16.1.4");
+const ID_CONTENT_TYPE: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.3");
+const ID_MESSAGE_DIGEST: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.4");
+const ID_SHA256: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.1");
+const ID_ECDSA_WITH_SHA256: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.4.3.2");
+const ID_KP_TIME_STAMPING: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.5.5.7.3.8");
+
+/// The statuses of a timestamp response which carry a timestamp: `granted`
+/// and `grantedWithMods`.
+const GRANTED_STATUSES: [u8; 2] = [0, 1];
+
+/// The maximum number of certificates from the signing certificate up to, but
+/// excluding, the root.
+const MAX_CHAIN_LENGTH: usize = 5;
+
+/// Verifies an RFC 3161 timestamp over `signed_data` and returns the time
+/// certified by the TSA.
+///
+/// Steps include:
+///
+/// 1. that the message imprint is the SHA2-256 digest of `signed_data`,
+/// 2. the signature of the TSA over the timestamp,
+/// 3. that the signing certificate is dedicated to timestamping and chains up
+///    to one of the trusted roots, with all certificates valid at the certified
+///    time.
+///
+/// Policies on the certified time in `reference_value.signed_timestamp` are
+/// not applied here.
+///
+/// # Arguments
+///
+/// * `serialized_timestamp`: The ASN.1 DER encoded TimeStampToken or
+///   TimeStampResp.
+/// * `signed_data`: The data covered by the timestamp, usually a signature.
+/// * `reference_value`: The trusted TSA certificates.
+pub fn verify_rfc3161_timestamp(
+    serialized_timestamp: &[u8],
+    signed_data: &[u8],
+    reference_value: &TimestampAuthorityReferenceValue,
+) -> anyhow::Result<Instant> {
+    let token = parse_timestamp_token(serialized_timestamp).context("parsing timestamp")?;
+    let tst_info = parse_tst_info(token.tst_info).context("parsing timestamp info")?;
+    ensure!(
+        tst_info.hashed_message == hash_sha2_256(signed_data),
+        "timestamp does not cover the signed data"
+    );
+
+    let mut certificates = token.certificates;
+    for certificate in &reference_value.intermediate_certificates {
+        certificates.push(Certificate::from_der(certificate).map_err(der_error)?);
+    }
+    let roots = reference_value
+        .root_certificates
+        .iter()
+        .map(|c| Certificate::from_der(c).map_err(der_error))
+        .collect::<anyhow::Result<Vec<Certificate>>>()?;
+    ensure!(!roots.is_empty(), "no trusted timestamp authority roots");
+
+    let signer = certificates
+        .iter()
+        .find(|c| {
+            c.tbs_certificate.issuer.to_der().ok() == Some(token.signer.issuer.clone())
+                && c.tbs_certificate.serial_number.as_bytes() == token.signer.serial_number
+        })
+        .context("could not find the signing certificate of the timestamp")?;
+    verify_signer_info(&token.signer, token.tst_info, signer)
+        .context("verifying timestamp signature")?;
+    check_time_stamping_usage(signer)?;
+    verify_certificate_chain(signer, &certificates, &roots, tst_info.gen_time)
+        .context("verifying timestamp authority certificate chain")?;
+
+    Ok(tst_info.gen_time)
+}
+
+/// The parts of a TimeStampToken needed for verification.
+struct TimestampToken<'a> {
+    /// The ASN.1 DER encoded TSTInfo.
+    tst_info: &'a [u8],
+    certificates: Vec<Certificate>,
+    signer: SignerInfo<'a>,
+}
+
+/// The parts of a CMS SignerInfo needed for verification.
+struct SignerInfo<'a> {
+    /// The ASN.1 DER encoded issuer name of the signing certificate.
+    issuer: Vec<u8>,
+    /// The encoded serial number of the signing certificate.
+    serial_number: &'a [u8],
+    /// The content of the signed attributes, without the tag.
+    signed_attributes: &'a [u8],
+    signature: &'a [u8],
+}
+
+/// The parts of a TSTInfo needed for verification.
+struct TstInfo<'a> {
+    hashed_message: &'a [u8],
+    gen_time: Instant,
+}
+
+/// Parses a TimeStampToken, which may be wrapped in a TimeStampResp.
+fn parse_timestamp_token(serialized: &[u8]) -> anyhow::Result<TimestampToken<'_>> {
+    let outer = AnyRef::from_der(serialized).map_err(der_error)?;
+    let fields = elements(outer, Tag::Sequence)?;
+
+    // A TimeStampResp starts with the PKIStatusInfo sequence, while the
+    // ContentInfo of a TimeStampToken starts with the content type.
+    let content_info = match fields.first() {
+        Some(status_info) if status_info.tag() == Tag::Sequence => {
+            let status = elements(*status_info, Tag::Sequence)?
+                .first()
+                .context("no status in timestamp response")?
+                .decode_as::<u8>()
+                .map_err(der_error)?;
+            ensure!(GRANTED_STATUSES.contains(&status), "timestamp was not granted: {status}");
+            let token = *fields.get(1).context("no timestamp token in timestamp response")?;
+            elements(token, Tag::Sequence)?
+        }
+        _ => fields,
+    };
+
+    let [content_type, content] = content_info.as_slice() else {
+        anyhow::bail!("malformed content info");
+    };
+    ensure!(oid(content_type)? == ID_SIGNED_DATA, "timestamp token is not signed data");
+    let signed_data = single(elements(*content, context_specific(TagNumber::N0))?)?;
+    let signed_data = elements(signed_data, Tag::Sequence)?;
+    ensure!(signed_data.len() >= 4, "malformed signed data");
+
+    let encapsulated = elements(signed_data[2], Tag::Sequence)?;
+    let [content_type, content] = encapsulated.as_slice() else {
+        anyhow::bail!("timestamp token has no content");
+    };
+    ensure!(oid(content_type)? == ID_CT_TST_INFO, "timestamp token does not contain TSTInfo");
+    let tst_info = single(elements(*content, context_specific(TagNumber::N0))?)?
+        .decode_as::<OctetStringRef>()
+        .map_err(der_error)?
+        .as_bytes();
+
+    let mut certificates = Vec::new();
+    for field in &signed_data[3..signed_data.len() - 1] {
+        if field.tag() == context_specific(TagNumber::N0) {
+            for certificate in elements(*field, field.tag())? {
+                certificates.push(certificate.decode_as::<Certificate>().map_err(der_error)?);
+            }
+        }
+    }
+
+    let signer_infos = elements(signed_data[signed_data.len() - 1], Tag::Set)?;
+    let signer = parse_signer_info(single(signer_infos).context("expected a single signer")?)?;
+    Ok(TimestampToken { tst_info, certificates, signer })
+}
+
+fn parse_signer_info(signer_info: AnyRef<'_>) -> anyhow::Result<SignerInfo<'_>> {
+    let fields = elements(signer_info, Tag::Sequence)?;
+    let [_version, sid, digest_algorithm, signed_attributes, signature_algorithm, signature, ..] =
+        fields.as_slice()
+    else {
+        anyhow::bail!("malformed signer info or no signed attributes");
+    };
+
+    let sid =
+        elements(*sid, Tag::Sequence).context("only issuer and serial number are supported")?;
+    let [issuer, serial_number] = sid.as_slice() else {
+        anyhow::bail!("malformed signer identifier");
+    };
+    ensure!(serial_number.tag() == Tag::Integer, "malformed signer serial number");
+    ensure!(
+        oid(elements(*digest_algorithm, Tag::Sequence)?
+            .first()
+            .context("malformed digest algorithm")?)?
+            == ID_SHA256,
+        "unsupported digest algorithm"
+    );
+    ensure!(
+        signed_attributes.tag() == context_specific(TagNumber::N0),
+        "timestamp has no signed attributes"
+    );
+    ensure!(
+        oid(elements(*signature_algorithm, Tag::Sequence)?
+            .first()
+            .context("malformed signature algorithm")?)?
+            == ID_ECDSA_WITH_SHA256,
+        "unsupported signature algorithm"
+    );
+
+    Ok(SignerInfo {
+        issuer: issuer.to_der().map_err(der_error)?,
+        serial_number: serial_number.value(),
+        signed_attributes: signed_attributes.value(),
+        signature: signature.decode_as::<OctetStringRef>().map_err(der_error)?.as_bytes(),
+    })
+}
+
+fn parse_tst_info(tst_info: &[u8]) -> anyhow::Result<TstInfo<'_>> {
+    let fields = elements(AnyRef::from_der(tst_info).map_err(der_error)?, Tag::Sequence)?;
+    let [_version, _policy, message_imprint, _serial_number, gen_time, ..] = fields.as_slice()
+    else {
+        anyhow::bail!("malformed TSTInfo");
+    };
+
+    let message_imprint = elements(*message_imprint, Tag::Sequence)?;
+    let [hash_algorithm, hashed_message] = message_imprint.as_slice() else {
+        anyhow::bail!("malformed message imprint");
+    };
+    let hash_algorithm = elements(*hash_algorithm, Tag::Sequence)?;
+    ensure!(
+        oid(hash_algorithm.first().context("malformed hash algorithm")?)? == ID_SHA256,
+        "unsupported message imprint hash algorithm"
+    );
+    ensure!(gen_time.tag() == Tag::GeneralizedTime, "malformed timestamp time");
+
+    Ok(TstInfo {
+        hashed_message: hashed_message.decode_as::<OctetStringRef>().map_err(der_error)?.as_bytes(),
+        gen_time: parse_generalized_time(gen_time.value())?,
+    })
+}
+
+/// Parses a GeneralizedTime of the form YYYYMMDDhhmmss[.s...]Z. The fraction
+/// of a second, which RFC 3161 allows, is truncated to milliseconds.
+fn parse_generalized_time(value: &[u8]) -> anyhow::Result<Instant> {
+    let value = core::str::from_utf8(value).context("invalid timestamp time")?;
+    let value = value.strip_suffix('Z').context("timestamp time is not in UTC")?;
+    let (seconds, fraction) = value.split_once('.').unwrap_or((value, ""));
+    ensure!(
+        seconds.len() == 14 && seconds.bytes().all(|b| b.is_ascii_digit()),
+        "malformed timestamp time"
+    );
+    ensure!(fraction.bytes().all(|b| b.is_ascii_digit()), "malformed timestamp time");
+    let field = |range: core::ops::Range<usize>| seconds[range].parse::<u8>();
+    let date_time = DateTime::new(
+        seconds[0..4].parse()?,
+        field(4..6)?,
+        field(6..8)?,
+        field(8..10)?,
+        field(10..12)?,
+        field(12..14)?,
+    )
+    .map_err(der_error)?;
+    let millis = format!("{fraction:0<3}")[..3].parse::<i64>()?;
+    let seconds = i64::try_from(date_time.unix_duration().as_secs())?;
+    Ok(Instant::from_unix_millis(seconds * 1000 + millis))
+}
+
+/// Verifies the signature of the signer over the signed attributes, and that
+/// the signed attributes cover the TSTInfo.
+fn verify_signer_info(
+    signer: &SignerInfo<'_>,
+    tst_info: &[u8],
+    certificate: &Certificate,
+) -> anyhow::Result<()> {
+    let mut content_type = None;
+    let mut message_digest = None;
+    let attributes = AnyRef::new(Tag::Set, signer.signed_attributes).map_err(der_error)?;
+    for attribute in elements(attributes, Tag::Set)? {
+        let attribute = elements(attribute, Tag::Sequence)?;
+        let [attribute_type, values] = attribute.as_slice() else {
+            anyhow::bail!("malformed signed attribute");
+        };
+        let value = single(elements(*values, Tag::Set)?)?;
+        match oid(attribute_type)? {
+            ID_CONTENT_TYPE => content_type = Some(oid(&value)?),
+            ID_MESSAGE_DIGEST => {
+                message_digest =
+                    Some(value.decode_as::<OctetStringRef>().map_err(der_error)?.as_bytes())
+            }
+            _ => {}
+        }
+    }
+    ensure!(content_type == Some(ID_CT_TST_INFO), "signed content type is not TSTInfo");
+    ensure!(
+        message_digest == Some(&hash_sha2_256(tst_info)[..]),
+        "signed message digest does not match TSTInfo"
+    );
+
+    // The signature covers the DER encoding of the attributes as SET OF,
+    // rather than with their implicit tag.
+    let signed = attributes.to_der().map_err(der_error)?;
+    let public_key =
+        certificate.tbs_certificate.subject_public_key_info.to_der().map_err(der_error)?;
+    verify_signature_ecdsa(signer.signature, &signed, &public_key)
+}
+
+/// Checks that the certificate may only be used for timestamping, as required
+/// by RFC 3161, section 2.3.
+fn check_time_stamping_usage(certificate: &Certificate) -> anyhow::Result<()> {
+    let extension = certificate
+        .tbs_certificate
+        .extensions
+        .iter()
+        .flatten()
+        .find(|e| e.extn_id == ExtendedKeyUsage::OID)
+        .context("timestamp signing certificate has no extended key usage")?;
+    ensure!(
+        extension.critical,
+        "extended key usage of timestamp signing certificate is not critical"
+    );
+    let usage = ExtendedKeyUsage::from_der(extension.extn_value.as_bytes()).map_err(der_error)?;
+    ensure!(
+        usage.0 == [ID_KP_TIME_STAMPING],
+        "timestamp signing certificate is not dedicated to timestamping"
+    );
+    Ok(())
+}
+
+/// Verifies that the certificate chains up to one of the roots via the
+/// intermediate certificates, and that all certificates are valid at `time`.
+fn verify_certificate_chain(
+    leaf: &Certificate,
+    intermediates: &[Certificate],
+    roots: &[Certificate],
+    time: Instant,
+) -> anyhow::Result<()> {
+    let mut certificate = leaf;
+    for _ in 0..=MAX_CHAIN_LENGTH {
+        check_validity(certificate, time)?;
+        if roots.contains(certificate) {
+            return Ok(());
+        }
+        if let Some(root) = roots.iter().find(|r| is_issuer(r, certificate)) {
+            return check_validity(root, time);
+        }
+        certificate = intermediates
+            .iter()
+            .find(|c| is_certificate_authority(c) && is_issuer(c, certificate))
+            .context("could not find a trusted issuer certificate")?;
+    }
+    anyhow::bail!("certificate chain is too long")
+}
+
+/// Returns whether `issuer` issued and signed `certificate`.
+fn is_issuer(issuer: &Certificate, certificate: &Certificate) -> bool {
+    issuer.tbs_certificate.subject == certificate.tbs_certificate.issuer
+        && verify_certificate_signature(issuer, certificate).is_ok()
+}
+
+fn verify_certificate_signature(
+    issuer: &Certificate,
+    certificate: &Certificate,
+) -> anyhow::Result<()> {
+    ensure!(
+        certificate.signature_algorithm.oid == ID_ECDSA_WITH_SHA256,
+        "unsupported certificate signature algorithm"
+    );
+    let signed = certificate.tbs_certificate.to_der().map_err(der_error)?;
+    let signature = certificate.signature.as_bytes().context("invalid certificate signature")?;
+    let public_key = issuer.tbs_certificate.subject_public_key_info.to_der().map_err(der_error)?;
+    verify_signature_ecdsa(signature, &signed, &public_key)
+}
+
+fn is_certificate_authority(certificate: &Certificate) -> bool {
+    certificate
+        .tbs_certificate
+        .extensions
+        .iter()
+        .flatten()
+        .filter(|e| e.extn_id == BasicConstraints::OID)
+        .any(|e| BasicConstraints::from_der(e.extn_value.as_bytes()).is_ok_and(|b| b.ca))
+}
+
+fn check_validity(certificate: &Certificate, time: Instant) -> anyhow::Result<()> {
+    let validity = &certificate.tbs_certificate.validity;
+    let not_before =
+        Instant::from_unix_seconds(validity.not_before.to_unix_duration().as_secs() as i64);
+    let not_after =
+        Instant::from_unix_seconds(validity.not_after.to_unix_duration().as_secs() as i64);
+    ensure!(
+        not_before <= time && time <= not_after,
+        "certificate is not valid at {time}: valid from {not_before} to {not_after}"
+    );
+    Ok(())
+}
+
+/// Returns the elements of a constructed value with the given tag.
+fn elements(value: AnyRef<'_>, tag: Tag) -> anyhow::Result<Vec<AnyRef<'_>>> {
+    ensure!(value.tag() == tag, "expected {tag}, found {}", value.tag());
+    let mut reader = SliceReader::new(value.value()).map_err(der_error)?;
+    let mut elements = Vec::new();
+    while !reader.is_finished() {
+        elements.push(reader.decode::<AnyRef>().map_err(der_error)?);
+    }
+    Ok(elements)
+}
+
+fn single<T>(elements: Vec<T>) -> anyhow::Result<T> {
+    let mut elements = elements.into_iter();
+    match (elements.next(), elements.next()) {
+        (Some(element), None) => Ok(element),
+        _ => anyhow::bail!("expected a single element"),
+    }
+}
+
+fn oid(value: &AnyRef<'_>) -> anyhow::Result<ObjectIdentifier> {
+    value.decode_as::<ObjectIdentifier>().map_err(der_error)
+}
+
+fn context_specific(number: TagNumber) -> Tag {
+    Tag::ContextSpecific { constructed: true, number }
+}
+
+// x509_cert::der::Error does not implement core::error::Error without std.
+fn der_error(error: x509_cert::der::Error) -> anyhow::Error {
+    anyhow::anyhow!("DER error: {error}")
+}
+
+#[cfg(test)]
+mod tests;
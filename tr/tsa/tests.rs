@@ -0,0 +1,144 @@
+//
+// Copyright 2025 The Project Oak Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use oak_time::{make_instant, Instant};
+use test_util::TimestampAuthorityData;
+
+use crate::{parse_generalized_time, verify_rfc3161_timestamp};
+
+const SIGNED_DATA: &[u8] = b"signature";
+
+fn timestamp_time() -> Instant {
+    make_instant!("2025-03-04T05:06:07.089Z")
+}
+
+#[test]
+fn test_verify_timestamp_token_success() {
+    let d = TimestampAuthorityData::load(0x33);
+    let token = d.create_timestamp_token(SIGNED_DATA, timestamp_time(), true);
+
+    let result = verify_rfc3161_timestamp(&token, SIGNED_DATA, &d.ref_value());
+
+    assert_eq!(result.expect("verification failed"), timestamp_time());
+}
+
+#[test]
+fn test_verify_timestamp_response_success() {
+    let d = TimestampAuthorityData::load(0x33);
+    let response = d.create_timestamp_response(SIGNED_DATA, timestamp_time(), 1);
+
+    let result = verify_rfc3161_timestamp(&response, SIGNED_DATA, &d.ref_value());
+
+    assert_eq!(result.expect("verification failed"), timestamp_time());
+}
+
+#[test]
+fn test_verify_rejected_timestamp_response_fails() {
+    let d = TimestampAuthorityData::load(0x33);
+    let response = d.create_timestamp_response(SIGNED_DATA, timestamp_time(), 2);
+
+    let result = verify_rfc3161_timestamp(&response, SIGNED_DATA, &d.ref_value());
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_verify_timestamp_with_supplied_certificate_success() {
+    let d = TimestampAuthorityData::load(0x33);
+    let token = d.create_timestamp_token(SIGNED_DATA, timestamp_time(), false);
+    let mut ref_value = d.ref_value();
+
+    assert!(verify_rfc3161_timestamp(&token, SIGNED_DATA, &ref_value).is_err());
+
+    ref_value.intermediate_certificates.push(d.signer_certificate.clone());
+    let result = verify_rfc3161_timestamp(&token, SIGNED_DATA, &ref_value);
+
+    assert_eq!(result.expect("verification failed"), timestamp_time());
+}
+
+#[test]
+fn test_verify_timestamp_other_data_fails() {
+    let d = TimestampAuthorityData::load(0x33);
+    let token = d.create_timestamp_token(SIGNED_DATA, timestamp_time(), true);
+
+    let result = verify_rfc3161_timestamp(&token, b"other", &d.ref_value());
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_verify_timestamp_untrusted_root_fails() {
+    let d = TimestampAuthorityData::load(0x33);
+    let other = TimestampAuthorityData::load(0x55);
+    let token = d.create_timestamp_token(SIGNED_DATA, timestamp_time(), true);
+
+    let result = verify_rfc3161_timestamp(&token, SIGNED_DATA, &other.ref_value());
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_verify_timestamp_without_roots_fails() {
+    let d = TimestampAuthorityData::load(0x33);
+    let token = d.create_timestamp_token(SIGNED_DATA, timestamp_time(), true);
+
+    let result = verify_rfc3161_timestamp(&token, SIGNED_DATA, &Default::default());
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_verify_timestamp_outside_certificate_validity_fails() {
+    let d = TimestampAuthorityData::load(0x33);
+    let time = d.valid_not_after + oak_time::Duration::from_seconds(1);
+    let token = d.create_timestamp_token(SIGNED_DATA, time, true);
+
+    let result = verify_rfc3161_timestamp(&token, SIGNED_DATA, &d.ref_value());
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_verify_tampered_timestamp_fails() {
+    let d = TimestampAuthorityData::load(0x33);
+    let token = d.create_timestamp_token(SIGNED_DATA, timestamp_time(), true);
+    let time = b"20250304050607.089Z";
+    let position = token.windows(time.len()).position(|w| w == time).expect("could not find time");
+    let mut tampered = token.clone();
+    tampered[position + 3] = b'6';
+
+    let result = verify_rfc3161_timestamp(&tampered, SIGNED_DATA, &d.ref_value());
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_parse_generalized_time() {
+    assert_eq!(
+        parse_generalized_time(b"20250304050607Z").expect("parsing failed"),
+        make_instant!("2025-03-04T05:06:07Z")
+    );
+    assert_eq!(
+        parse_generalized_time(b"20250304050607.5Z").expect("parsing failed"),
+        make_instant!("2025-03-04T05:06:07.500Z")
+    );
+    assert_eq!(
+        parse_generalized_time(b"20250304050607.123456Z").expect("parsing failed"),
+        make_instant!("2025-03-04T05:06:07.123Z")
+    );
+    assert!(parse_generalized_time(b"20250304050607").is_err());
+    assert!(parse_generalized_time(b"2025030405060Z").is_err());
+}
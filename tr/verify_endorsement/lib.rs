@@ -29,10 +29,18 @@ use key_util::{convert_pem_to_raw, verify_signature};
 use oak_proto_rust::oak::attestation::v1::{
     endorsement::Format, verifying_key_reference_value, ClaimReferenceValue, Endorsement,
     EndorsementReferenceValue, KeyType, Signature, SignedEndorsement, SkipVerification,
-    VerifyingKey, VerifyingKeyReferenceValue, VerifyingKeySet,
+    TimestampAuthorityReferenceValue, VerifyingKey, VerifyingKeyReferenceValue, VerifyingKeySet,
 };
 use oak_time::Instant;
-use rekor::log_entry::{verify_rekor_log_entry, LogEntry};
+use rekor::{
+    bundle::{
+        get_bundle_rfc3161_timestamps, parse_sigstore_bundle, verify_bundle_log_entry,
+        verify_bundle_signature,
+    },
+    log_entry::{verify_rekor_checkpoint_consistency, verify_rekor_log_entry, LogEntry},
+    verify_timestamp,
+};
+use tsa::verify_rfc3161_timestamp;
 
 /// No attempt will be made to decode the attachment of a firmware-type
 /// binary unless this claim is present in the endorsement.
@@ -70,6 +78,8 @@ pub fn create_signed_endorsement(
         endorsement: Some(endorsement),
         signature: Some(Signature { key_id, raw: signature.to_vec() }),
         rekor_log_entry: log_entry.to_vec(),
+        sigstore_bundle: Vec::new(),
+        rfc3161_timestamp: Vec::new(),
     }
 }
 
@@ -133,7 +143,7 @@ pub fn create_endorsement_reference_value_from_raw(
 ///
 /// `now_utc_millis`: The current time in milliseconds UTC since Unix Epoch.
 /// `signed_endorsement`: The endorsement along with signature and (optional)
-///     Rekor log entry.
+///     Rekor log entry and RFC 3161 timestamp, or along with a Sigstore bundle.
 /// `ref_value`: A reference value containing e.g. the public keys needed
 ///     for the verification. The deprecated fields `endorser_public_key` and
 ///     `rekor_public_key` will be ignored.
@@ -144,6 +154,15 @@ pub fn verify_endorsement(
 ) -> anyhow::Result<DefaultStatement> {
     let endorsement =
         signed_endorsement.endorsement.as_ref().context("no endorsement in signed endorsement")?;
+    if !signed_endorsement.sigstore_bundle.is_empty() {
+        return verify_sigstore_bundle_endorsement(
+            now_utc_millis,
+            endorsement,
+            &signed_endorsement.sigstore_bundle,
+            ref_value,
+        );
+    }
+
     let signature =
         signed_endorsement.signature.as_ref().context("no signature in signed endorsement")?;
     let endorser_key_set =
@@ -156,17 +175,17 @@ pub fn verify_endorsement(
     verify_signature(signature, &endorsement.serialized, endorser_key_set)
         .context("verifying signature")?;
 
-    let statement =
-        parse_statement(&endorsement.serialized).context("parsing endorsement statement")?;
-    let current_time = Instant::from_unix_millis(now_utc_millis);
-    let claims: Vec<&str> = required_claims.claim_types.iter().map(|x| &**x).collect();
-    statement.validate(None, current_time, &claims).context("validating endorsement statement")?;
+    let statement = parse_and_validate_statement(now_utc_millis, endorsement, required_claims)?;
 
-    let rekor_ref_value =
-        ref_value.rekor.as_ref().context("no rekor key set in signed endorsement")?;
-    match rekor_ref_value.r#type.as_ref() {
-        Some(verifying_key_reference_value::Type::Skip(_)) => Ok(statement),
-        Some(verifying_key_reference_value::Type::Verify(key_set)) => {
+    if let Some(tsa_ref_value) = ref_value.timestamp_authority.as_ref() {
+        let timestamp = &signed_endorsement.rfc3161_timestamp;
+        let timestamps = if timestamp.is_empty() { vec![] } else { vec![timestamp.clone()] };
+        verify_timestamp_authority(now_utc_millis, &timestamps, &signature.raw, tsa_ref_value)?;
+    }
+
+    match get_rekor_key_set(ref_value)? {
+        None => Ok(statement),
+        Some(key_set) => {
             let log_entry = &signed_endorsement.rekor_log_entry;
             if log_entry.is_empty() {
                 anyhow::bail!("log entry unavailable but verification was requested");
@@ -174,9 +193,118 @@ pub fn verify_endorsement(
             let log_entry =
                 verify_rekor_log_entry(log_entry, key_set, &endorsement.serialized, now_utc_millis)
                     .context("verifying Rekor log entry")?;
+            if let Some(consistency) = ref_value.rekor_checkpoint_consistency.as_ref() {
+                verify_rekor_checkpoint_consistency(&log_entry, key_set, consistency)
+                    .context("verifying Rekor checkpoint consistency")?;
+            }
             compare_endorser_public_key(&log_entry, signature.key_id, endorser_key_set)?;
             Ok(statement)
         }
+    }
+}
+
+/// Verifies an endorsement which is signed by a Sigstore bundle.
+///
+/// The bundle signature is checked against all keys in the endorser key set,
+/// and the log entry in the bundle needs to record the key which verified it.
+fn verify_sigstore_bundle_endorsement(
+    now_utc_millis: i64,
+    endorsement: &Endorsement,
+    serialized_bundle: &[u8],
+    ref_value: &EndorsementReferenceValue,
+) -> anyhow::Result<DefaultStatement> {
+    let endorser_key_set =
+        ref_value.endorser.as_ref().context("no endorser key set in signed endorsement")?;
+    let required_claims = ref_value.required_claims.as_ref().context("required claims missing")?;
+
+    let bundle = parse_sigstore_bundle(serialized_bundle).context("parsing Sigstore bundle")?;
+    let verified_signature =
+        verify_bundle_signature(&bundle, &endorsement.serialized, endorser_key_set)
+            .context("verifying Sigstore bundle signature")?;
+
+    let statement = parse_and_validate_statement(now_utc_millis, endorsement, required_claims)?;
+
+    if let Some(tsa_ref_value) = ref_value.timestamp_authority.as_ref() {
+        verify_timestamp_authority(
+            now_utc_millis,
+            &get_bundle_rfc3161_timestamps(&bundle)?,
+            &verified_signature.signature,
+            tsa_ref_value,
+        )?;
+    }
+
+    if let Some(key_set) = get_rekor_key_set(ref_value)? {
+        let log_entry = verify_bundle_log_entry(
+            &bundle,
+            key_set,
+            &endorsement.serialized,
+            &verified_signature.public_key,
+            now_utc_millis,
+        )
+        .context("verifying Sigstore bundle log entry")?;
+        if let Some(consistency) = ref_value.rekor_checkpoint_consistency.as_ref() {
+            verify_rekor_checkpoint_consistency(&log_entry, key_set, consistency)
+                .context("verifying Rekor checkpoint consistency")?;
+        }
+    }
+    Ok(statement)
+}
+
+/// Verifies that one of the RFC 3161 timestamps covers the verified
+/// `signature` and was issued by a trusted timestamp authority, then applies
+/// the time stamp policy of the reference value to the certified time.
+fn verify_timestamp_authority(
+    now_utc_millis: i64,
+    timestamps: &[Vec<u8>],
+    signature: &[u8],
+    ref_value: &TimestampAuthorityReferenceValue,
+) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        !timestamps.is_empty(),
+        "RFC 3161 timestamp unavailable but verification was requested"
+    );
+    let mut last_error = None;
+    for timestamp in timestamps {
+        match verify_rfc3161_timestamp(timestamp, signature, ref_value) {
+            Ok(time) => {
+                return match ref_value.signed_timestamp.as_ref() {
+                    Some(policy) => {
+                        verify_timestamp(Instant::from_unix_millis(now_utc_millis), time, policy)
+                            .context("verifying RFC 3161 timestamp time")
+                    }
+                    None => Ok(()),
+                };
+            }
+            Err(error) => last_error = Some(error),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| anyhow::anyhow!("no RFC 3161 timestamp to verify")))
+        .context("verifying RFC 3161 timestamp")
+}
+
+/// Parses the endorsement statement and validates it at the current time.
+fn parse_and_validate_statement(
+    now_utc_millis: i64,
+    endorsement: &Endorsement,
+    required_claims: &ClaimReferenceValue,
+) -> anyhow::Result<DefaultStatement> {
+    let statement =
+        parse_statement(&endorsement.serialized).context("parsing endorsement statement")?;
+    let current_time = Instant::from_unix_millis(now_utc_millis);
+    let claims: Vec<&str> = required_claims.claim_types.iter().map(|x| &**x).collect();
+    statement.validate(None, current_time, &claims).context("validating endorsement statement")?;
+    Ok(statement)
+}
+
+/// Returns Rekor's key set, or `None` if log entry verification is skipped.
+fn get_rekor_key_set(
+    ref_value: &EndorsementReferenceValue,
+) -> anyhow::Result<Option<&VerifyingKeySet>> {
+    let rekor_ref_value =
+        ref_value.rekor.as_ref().context("no rekor key set in signed endorsement")?;
+    match rekor_ref_value.r#type.as_ref() {
+        Some(verifying_key_reference_value::Type::Skip(_)) => Ok(None),
+        Some(verifying_key_reference_value::Type::Verify(key_set)) => Ok(Some(key_set)),
         None => Err(anyhow::anyhow!("empty Rekor verifying key set reference value")),
     }
 }